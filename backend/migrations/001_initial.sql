@@ -16,19 +16,68 @@ CREATE TABLE IF NOT EXISTS users (
     updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
 );
 
+-- Collections table for grouping NFTs
+CREATE TABLE IF NOT EXISTS collections (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(255) NOT NULL,
+    symbol VARCHAR(10),
+    description TEXT,
+    image_url TEXT,
+    banner_url TEXT,
+    creator_address VARCHAR(44) NOT NULL,
+    verified BOOLEAN DEFAULT FALSE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
 -- NFTs table for indexing all NFTs
 CREATE TABLE IF NOT EXISTS nfts (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    mint_address VARCHAR(44) UNIQUE NOT NULL,
+    collection_id UUID REFERENCES collections(id) ON DELETE SET NULL,
     name VARCHAR(255) NOT NULL,
     description TEXT,
     image_url TEXT,
+    animation_url TEXT,
+    external_url TEXT,
+    attributes JSONB,
     creator_address VARCHAR(44) NOT NULL,
     current_owner VARCHAR(44) NOT NULL,
     is_compressed BOOLEAN DEFAULT FALSE,
+    rarity_rank INTEGER,
+    rarity_score DECIMAL(10, 4),
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Listings table for marketplace listings
+CREATE TABLE IF NOT EXISTS listings (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    listing_address VARCHAR(44) UNIQUE,
+    nft_mint VARCHAR(44) NOT NULL REFERENCES nfts(mint_address),
+    seller_address VARCHAR(44) NOT NULL,
+    price BIGINT NOT NULL,
+    marketplace_address VARCHAR(44),
+    status VARCHAR(20) NOT NULL DEFAULT 'active',
+    transaction_signature VARCHAR(88),
+    block_time TIMESTAMP WITH TIME ZONE,
     created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
     updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
 );
 
+-- Sales table for completed purchases
+CREATE TABLE IF NOT EXISTS sales (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    nft_mint VARCHAR(44) NOT NULL REFERENCES nfts(mint_address),
+    seller_address VARCHAR(44) NOT NULL,
+    buyer_address VARCHAR(44) NOT NULL,
+    price BIGINT NOT NULL,
+    marketplace_fee BIGINT NOT NULL DEFAULT 0,
+    transaction_signature VARCHAR(88) UNIQUE NOT NULL,
+    block_time TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
 -- User favorites table
 CREATE TABLE IF NOT EXISTS user_favorites (
     id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
@@ -49,5 +98,12 @@ CREATE TABLE IF NOT EXISTS indexer_state (
 -- Create indexes for better query performance
 CREATE INDEX IF NOT EXISTS idx_nfts_current_owner ON nfts(current_owner);
 CREATE INDEX IF NOT EXISTS idx_nfts_creator_address ON nfts(creator_address);
+CREATE INDEX IF NOT EXISTS idx_nfts_collection_id ON nfts(collection_id);
+
+CREATE INDEX IF NOT EXISTS idx_listings_nft_mint ON listings(nft_mint);
+CREATE INDEX IF NOT EXISTS idx_listings_status ON listings(status);
+
+CREATE INDEX IF NOT EXISTS idx_sales_nft_mint ON sales(nft_mint);
+CREATE INDEX IF NOT EXISTS idx_sales_block_time ON sales(block_time);
 
 CREATE INDEX IF NOT EXISTS idx_users_wallet_address ON users(wallet_address);
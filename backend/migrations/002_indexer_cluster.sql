@@ -0,0 +1,3 @@
+-- Record which Solana cluster the indexer has been syncing so a database
+-- can't silently mix events from different clusters
+ALTER TABLE indexer_state ADD COLUMN IF NOT EXISTS cluster VARCHAR(20);
@@ -0,0 +1,11 @@
+-- Daily marketplace rollups, one row per UTC day
+CREATE TABLE IF NOT EXISTS daily_stats (
+    date DATE PRIMARY KEY,
+    total_volume BIGINT NOT NULL DEFAULT 0,
+    total_sales BIGINT NOT NULL DEFAULT 0,
+    unique_buyers BIGINT NOT NULL DEFAULT 0,
+    unique_sellers BIGINT NOT NULL DEFAULT 0,
+    average_price BIGINT NOT NULL DEFAULT 0,
+    new_listings BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
@@ -0,0 +1,9 @@
+-- Nightly snapshot of distinct holders per collection; can't be reconstructed later
+CREATE TABLE IF NOT EXISTS collection_holder_history (
+    collection_id UUID NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    snapshot_date DATE NOT NULL,
+    unique_holders BIGINT NOT NULL,
+    total_supply BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (collection_id, snapshot_date)
+);
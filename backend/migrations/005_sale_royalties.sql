@@ -0,0 +1,13 @@
+-- Creator royalties paid on each sale
+ALTER TABLE sales ADD COLUMN IF NOT EXISTS royalty_paid BIGINT NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS sale_royalties (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    sale_id UUID NOT NULL REFERENCES sales(id) ON DELETE CASCADE,
+    creator_address VARCHAR(44) NOT NULL,
+    amount BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE(sale_id, creator_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_sale_royalties_creator_address ON sale_royalties(creator_address);
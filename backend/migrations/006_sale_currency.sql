@@ -0,0 +1,13 @@
+-- Sales and listings may be priced in SPL tokens (e.g. USDC) as well as SOL.
+-- `price` stays in the currency's smallest unit; `base_price` is the value in
+-- lamports at sale time so volumes can be summed across currencies.
+ALTER TABLE sales ADD COLUMN IF NOT EXISTS currency_mint VARCHAR(44) NOT NULL
+    DEFAULT 'So11111111111111111111111111111111111111112';
+ALTER TABLE sales ADD COLUMN IF NOT EXISTS base_price BIGINT;
+UPDATE sales SET base_price = price WHERE base_price IS NULL;
+ALTER TABLE sales ALTER COLUMN base_price SET NOT NULL;
+
+ALTER TABLE listings ADD COLUMN IF NOT EXISTS currency_mint VARCHAR(44) NOT NULL
+    DEFAULT 'So11111111111111111111111111111111111111112';
+
+CREATE INDEX IF NOT EXISTS idx_sales_currency_mint ON sales(currency_mint);
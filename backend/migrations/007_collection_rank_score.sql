@@ -0,0 +1,8 @@
+-- Composite ranking score blending volume, floor change, sales velocity and holder growth
+ALTER TABLE collections ADD COLUMN IF NOT EXISTS rank_score DOUBLE PRECISION;
+ALTER TABLE collections ADD COLUMN IF NOT EXISTS rank_updated_at TIMESTAMP WITH TIME ZONE;
+
+-- Floor at snapshot time, so floor change can be computed from history
+ALTER TABLE collection_holder_history ADD COLUMN IF NOT EXISTS floor_price BIGINT;
+
+CREATE INDEX IF NOT EXISTS idx_collections_rank_score ON collections(rank_score DESC NULLS LAST);
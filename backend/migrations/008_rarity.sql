@@ -0,0 +1,13 @@
+-- Per-trait frequencies computed by the rarity engine
+CREATE TABLE IF NOT EXISTS collection_traits (
+    collection_id UUID NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    trait_type VARCHAR(255) NOT NULL,
+    value TEXT NOT NULL,
+    count BIGINT NOT NULL,
+    frequency DOUBLE PRECISION NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (collection_id, trait_type, value)
+);
+
+-- Set when NFTs are added to a collection; cleared once rarity is recomputed
+ALTER TABLE collections ADD COLUMN IF NOT EXISTS rarity_stale BOOLEAN NOT NULL DEFAULT TRUE;
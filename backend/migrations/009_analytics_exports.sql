@@ -0,0 +1,9 @@
+-- Daily Parquet partitions already written to object storage
+CREATE TABLE IF NOT EXISTS analytics_exports (
+    dataset VARCHAR(50) NOT NULL,
+    export_date DATE NOT NULL,
+    object_key TEXT NOT NULL,
+    row_count BIGINT NOT NULL,
+    exported_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (dataset, export_date)
+);
@@ -0,0 +1,88 @@
+-- Append-only history of listing lifecycle events. The listings table only
+-- holds current state; every status or price change is captured here by a
+-- trigger so churn can be analysed after the fact.
+CREATE TABLE IF NOT EXISTS listing_history (
+    id BIGSERIAL PRIMARY KEY,
+    listing_id UUID NOT NULL REFERENCES listings(id) ON DELETE CASCADE,
+    nft_mint VARCHAR(44) NOT NULL,
+    collection_id UUID REFERENCES collections(id) ON DELETE SET NULL,
+    seller_address VARCHAR(44) NOT NULL,
+    -- listed, price_change, delisted, sold
+    event_type VARCHAR(20) NOT NULL,
+    price BIGINT NOT NULL,
+    previous_price BIGINT,
+    currency_mint VARCHAR(44) NOT NULL,
+    -- Collection floor in the same currency at the time, excluding this listing
+    floor_price BIGINT,
+    occurred_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_listing_history_collection_occurred
+    ON listing_history(collection_id, occurred_at);
+CREATE INDEX IF NOT EXISTS idx_listing_history_listing_id ON listing_history(listing_id);
+CREATE INDEX IF NOT EXISTS idx_listing_history_nft_mint ON listing_history(nft_mint, occurred_at);
+
+CREATE OR REPLACE FUNCTION record_listing_history() RETURNS TRIGGER AS $$
+DECLARE
+    v_event VARCHAR(20);
+    v_previous_price BIGINT;
+    v_collection_id UUID;
+    v_floor BIGINT;
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        v_event := CASE NEW.status WHEN 'active' THEN 'listed' ELSE NULL END;
+    ELSIF NEW.status IS DISTINCT FROM OLD.status THEN
+        v_event := CASE NEW.status
+            WHEN 'active' THEN 'listed'
+            WHEN 'sold' THEN 'sold'
+            ELSE 'delisted'
+        END;
+    ELSIF NEW.price IS DISTINCT FROM OLD.price AND NEW.status = 'active' THEN
+        v_event := 'price_change';
+        v_previous_price := OLD.price;
+    END IF;
+
+    IF v_event IS NULL THEN
+        RETURN NEW;
+    END IF;
+
+    SELECT collection_id INTO v_collection_id FROM nfts WHERE mint_address = NEW.nft_mint;
+
+    IF v_collection_id IS NOT NULL THEN
+        SELECT MIN(l.price) INTO v_floor
+        FROM listings l
+        JOIN nfts n ON n.mint_address = l.nft_mint
+        WHERE n.collection_id = v_collection_id
+          AND l.status = 'active'
+          AND l.currency_mint = NEW.currency_mint
+          AND l.id <> NEW.id;
+    END IF;
+
+    INSERT INTO listing_history (
+        listing_id, nft_mint, collection_id, seller_address, event_type,
+        price, previous_price, currency_mint, floor_price, occurred_at
+    )
+    VALUES (
+        NEW.id, NEW.nft_mint, v_collection_id, NEW.seller_address, v_event,
+        NEW.price, v_previous_price, NEW.currency_mint, v_floor,
+        CASE WHEN TG_OP = 'INSERT' THEN COALESCE(NEW.block_time, NEW.created_at, NOW()) ELSE NOW() END
+    );
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS listings_record_history ON listings;
+CREATE TRIGGER listings_record_history
+    AFTER INSERT OR UPDATE OF status, price ON listings
+    FOR EACH ROW EXECUTE FUNCTION record_listing_history();
+
+-- Seed history with the listings that already exist
+INSERT INTO listing_history (
+    listing_id, nft_mint, collection_id, seller_address, event_type, price, currency_mint, occurred_at
+)
+SELECT l.id, l.nft_mint, n.collection_id, l.seller_address, 'listed', l.price, l.currency_mint,
+       COALESCE(l.block_time, l.created_at, NOW())
+FROM listings l
+LEFT JOIN nfts n ON n.mint_address = l.nft_mint
+WHERE NOT EXISTS (SELECT 1 FROM listing_history h WHERE h.listing_id = l.id);
@@ -0,0 +1,11 @@
+-- SOL/USD price history for valuing sales in fiat after the fact
+CREATE TABLE IF NOT EXISTS sol_usd_prices (
+    recorded_at TIMESTAMP WITH TIME ZONE PRIMARY KEY,
+    usd_price DOUBLE PRECISION NOT NULL
+);
+
+-- Price at sale time, captured when the sale is indexed
+ALTER TABLE sales ADD COLUMN IF NOT EXISTS sol_usd_price DOUBLE PRECISION;
+
+CREATE INDEX IF NOT EXISTS idx_sales_buyer_address_block_time ON sales(buyer_address, block_time);
+CREATE INDEX IF NOT EXISTS idx_sales_seller_address_block_time ON sales(seller_address, block_time);
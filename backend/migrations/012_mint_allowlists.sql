@@ -0,0 +1,19 @@
+-- Creator-uploaded allowlists for Merkle-gated mint phases, keyed by the
+-- on-chain mint-gating config account
+CREATE TABLE IF NOT EXISTS mint_allowlists (
+    config_address VARCHAR(44) PRIMARY KEY,
+    creator_address VARCHAR(44) NOT NULL,
+    merkle_root VARCHAR(64) NOT NULL,
+    entry_count BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Proofs are computed once at upload and served as-is
+CREATE TABLE IF NOT EXISTS mint_allowlist_entries (
+    config_address VARCHAR(44) NOT NULL REFERENCES mint_allowlists(config_address) ON DELETE CASCADE,
+    wallet_address VARCHAR(44) NOT NULL,
+    allocation BIGINT NOT NULL,
+    proof JSONB NOT NULL,
+    PRIMARY KEY (config_address, wallet_address)
+);
@@ -0,0 +1,39 @@
+-- Trading rewards: seasons carry the accrual rules, the ledger is append-only
+CREATE TABLE IF NOT EXISTS points_seasons (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(100) NOT NULL,
+    starts_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    ends_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    buy_points_per_sol DOUBLE PRECISION NOT NULL DEFAULT 10,
+    sell_points_per_sol DOUBLE PRECISION NOT NULL DEFAULT 10,
+    -- Awarded once per NFT, seller and day
+    listing_points DOUBLE PRECISION NOT NULL DEFAULT 1,
+    -- Extra fraction per consecutive trading day, e.g. 0.05 = +5% per day
+    streak_bonus DOUBLE PRECISION NOT NULL DEFAULT 0.05,
+    max_streak_days INTEGER NOT NULL DEFAULT 7,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS points_collection_multipliers (
+    season_id UUID NOT NULL REFERENCES points_seasons(id) ON DELETE CASCADE,
+    collection_id UUID NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    multiplier DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (season_id, collection_id)
+);
+
+CREATE TABLE IF NOT EXISTS points_ledger (
+    id BIGSERIAL PRIMARY KEY,
+    season_id UUID NOT NULL REFERENCES points_seasons(id) ON DELETE CASCADE,
+    wallet_address VARCHAR(44) NOT NULL,
+    -- buy, sell, list or adjustment
+    source VARCHAR(20) NOT NULL,
+    -- Transaction signature, listing key or adjustment id; makes accrual idempotent
+    reference VARCHAR(128) NOT NULL,
+    points DOUBLE PRECISION NOT NULL,
+    details JSONB,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (season_id, wallet_address, source, reference)
+);
+
+CREATE INDEX IF NOT EXISTS idx_points_ledger_season_wallet ON points_ledger(season_id, wallet_address);
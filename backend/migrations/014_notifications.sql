@@ -0,0 +1,44 @@
+-- In-app notifications; delivery channels (email, ...) fan out from here
+CREATE TABLE IF NOT EXISTS notifications (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    wallet_address VARCHAR(44) NOT NULL,
+    -- offer_received, item_sold, floor_alert
+    category VARCHAR(30) NOT NULL,
+    title TEXT NOT NULL,
+    body TEXT NOT NULL,
+    data JSONB,
+    -- Stops the same event from notifying twice, e.g. sale:<signature>
+    dedupe_key VARCHAR(150) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    read_at TIMESTAMP WITH TIME ZONE,
+    emailed_at TIMESTAMP WITH TIME ZONE,
+    UNIQUE (wallet_address, dedupe_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_wallet_created ON notifications(wallet_address, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_notifications_pending_email ON notifications(created_at) WHERE emailed_at IS NULL;
+
+-- Per wallet, category and channel: instant, digest or off. Missing rows use the defaults
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    wallet_address VARCHAR(44) NOT NULL,
+    category VARCHAR(30) NOT NULL,
+    channel VARCHAR(20) NOT NULL,
+    mode VARCHAR(10) NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (wallet_address, category, channel)
+);
+
+-- Collections a wallet follows, optionally alerting when the floor drops below a price
+CREATE TABLE IF NOT EXISTS collection_watchlist (
+    wallet_address VARCHAR(44) NOT NULL,
+    collection_id UUID NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    alert_floor_below BIGINT,
+    last_alerted_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (wallet_address, collection_id)
+);
+
+-- Email opt-in: nothing is sent until the address is verified
+ALTER TABLE users ADD COLUMN IF NOT EXISTS email_verified_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS email_verification_token_hash VARCHAR(64);
+ALTER TABLE users ADD COLUMN IF NOT EXISTS email_verification_sent_at TIMESTAMP WITH TIME ZONE;
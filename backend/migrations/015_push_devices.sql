@@ -0,0 +1,17 @@
+-- Mobile devices registered for push notifications
+CREATE TABLE IF NOT EXISTS push_devices (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    wallet_address VARCHAR(44) NOT NULL,
+    -- ios (APNs) or android (FCM)
+    platform VARCHAR(10) NOT NULL,
+    token TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    last_seen_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    -- Set when the provider reports the token as no longer valid
+    disabled_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_push_devices_wallet ON push_devices(wallet_address) WHERE disabled_at IS NULL;
+
+ALTER TABLE notifications ADD COLUMN IF NOT EXISTS pushed_at TIMESTAMP WITH TIME ZONE;
+CREATE INDEX IF NOT EXISTS idx_notifications_pending_push ON notifications(created_at) WHERE pushed_at IS NULL;
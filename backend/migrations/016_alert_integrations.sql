@@ -0,0 +1,37 @@
+-- Discord webhooks and Telegram chats that receive a collection's activity
+CREATE TABLE IF NOT EXISTS alert_integrations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    collection_id UUID NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    -- discord or telegram
+    kind VARCHAR(20) NOT NULL,
+    -- Discord webhook URL or Telegram chat id
+    target TEXT NOT NULL,
+    -- Any of sale, listing, floor_move
+    events TEXT[] NOT NULL,
+    -- Only post sales at or above this many lamports
+    min_sale_price BIGINT NOT NULL DEFAULT 0,
+    -- Post a floor move once the SOL floor changes by this much since the last one
+    floor_change_pct DOUBLE PRECISION NOT NULL DEFAULT 10,
+    last_floor_price BIGINT,
+    -- Hash of the token returned at creation; required to change or remove the integration
+    manage_token_hash VARCHAR(64) NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    last_delivered_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (collection_id, kind, target)
+);
+
+CREATE INDEX IF NOT EXISTS idx_alert_integrations_enabled ON alert_integrations(collection_id) WHERE enabled;
+
+-- Events already posted to an integration, so retries and overlapping runs don't repeat them
+CREATE TABLE IF NOT EXISTS alert_deliveries (
+    integration_id UUID NOT NULL REFERENCES alert_integrations(id) ON DELETE CASCADE,
+    event_key VARCHAR(150) NOT NULL,
+    delivered_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (integration_id, event_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_alert_deliveries_delivered_at ON alert_deliveries(delivered_at);
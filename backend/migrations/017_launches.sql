@@ -0,0 +1,52 @@
+-- Primary-sale launches hosted on solmint: either an existing Candy Machine v3
+-- or a launch minted through the marketplace program's MintNft instruction
+CREATE TABLE IF NOT EXISTS launches (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    -- candy_machine_v3 or solmint
+    kind VARCHAR(20) NOT NULL,
+    -- Candy machine account, or a generated id for solmint launches; also keys
+    -- the launch's allowlists in mint_allowlists
+    config_address VARCHAR(44) NOT NULL UNIQUE,
+    creator_address VARCHAR(44) NOT NULL,
+    collection_id UUID REFERENCES collections(id) ON DELETE SET NULL,
+    name VARCHAR(255) NOT NULL,
+    symbol VARCHAR(10),
+    description TEXT,
+    image_url TEXT,
+    -- solmint launches: item N uses `<base_uri>/<N>.json`, payments go to the treasury
+    base_uri TEXT,
+    items_available BIGINT,
+    treasury_address VARCHAR(44),
+    -- Next item index handed out when building a mint transaction
+    next_index BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Mint phases. For candy machines the label is the candy guard group label.
+CREATE TABLE IF NOT EXISTS launch_phases (
+    launch_id UUID NOT NULL REFERENCES launches(id) ON DELETE CASCADE,
+    label VARCHAR(6) NOT NULL,
+    starts_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    ends_at TIMESTAMP WITH TIME ZONE,
+    -- Lamports
+    price BIGINT NOT NULL DEFAULT 0,
+    per_wallet_limit INTEGER,
+    -- Restricted to the launch's allowlist
+    allowlist BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Candy guard solPayment destination
+    payment_destination VARCHAR(44),
+    PRIMARY KEY (launch_id, label)
+);
+
+-- Mint transactions handed out, for per-wallet limits on solmint launches
+CREATE TABLE IF NOT EXISTS launch_mints (
+    mint_address VARCHAR(44) PRIMARY KEY,
+    launch_id UUID NOT NULL REFERENCES launches(id) ON DELETE CASCADE,
+    wallet_address VARCHAR(44) NOT NULL,
+    phase_label VARCHAR(6) NOT NULL,
+    item_index BIGINT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_launch_mints_wallet ON launch_mints(launch_id, wallet_address);
@@ -0,0 +1,2 @@
+-- Creator-set cap on every wallet's allocation in an allowlist
+ALTER TABLE mint_allowlists ADD COLUMN IF NOT EXISTS max_allocation BIGINT;
@@ -0,0 +1,49 @@
+-- Fiat checkout: a card payment through the checkout provider, which then
+-- buys the listing on chain for the buyer's wallet
+CREATE TABLE IF NOT EXISTS checkout_orders (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    listing_id UUID NOT NULL REFERENCES listings(id),
+    nft_mint VARCHAR(44) NOT NULL,
+    seller_address VARCHAR(44) NOT NULL,
+    -- Listing price at checkout, in the listing currency's smallest unit
+    price BIGINT NOT NULL,
+    currency_mint VARCHAR(44) NOT NULL,
+    buyer_wallet VARCHAR(44) NOT NULL,
+    email VARCHAR(255),
+    -- pending_payment, paid, delivering, completed, payment_failed, expired,
+    -- cancelled, delivery_failed, refunded
+    status VARCHAR(20) NOT NULL DEFAULT 'pending_payment',
+    failure_reason TEXT,
+    provider_order_id VARCHAR(255) UNIQUE,
+    checkout_url TEXT,
+    -- Quoted by the provider, in minor units (e.g. cents)
+    fiat_amount BIGINT,
+    fiat_currency VARCHAR(3),
+    -- Purchase transaction, reported by the provider or matched from sales
+    sale_signature VARCHAR(88),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    paid_at TIMESTAMP WITH TIME ZONE,
+    completed_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- One checkout in flight per listing
+CREATE UNIQUE INDEX IF NOT EXISTS idx_checkout_orders_open_listing ON checkout_orders(listing_id)
+    WHERE status IN ('pending_payment', 'paid', 'delivering');
+CREATE INDEX IF NOT EXISTS idx_checkout_orders_status ON checkout_orders(status);
+CREATE INDEX IF NOT EXISTS idx_checkout_orders_buyer ON checkout_orders(buyer_wallet, created_at DESC);
+
+-- Audit trail of status changes
+CREATE TABLE IF NOT EXISTS checkout_order_events (
+    id BIGSERIAL PRIMARY KEY,
+    order_id UUID NOT NULL REFERENCES checkout_orders(id) ON DELETE CASCADE,
+    from_status VARCHAR(20),
+    to_status VARCHAR(20) NOT NULL,
+    -- webhook, reconciliation or api
+    source VARCHAR(20) NOT NULL,
+    detail TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_checkout_order_events_order ON checkout_order_events(order_id, id);
@@ -0,0 +1,106 @@
+-- Soft deletion for core records: rows stay in place with `deleted_at` set
+-- and model queries skip them
+ALTER TABLE nfts ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE collections ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE listings ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_nfts_deleted_at ON nfts(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_collections_deleted_at ON collections(deleted_at)
+    WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_listings_deleted_at ON listings(deleted_at)
+    WHERE deleted_at IS NOT NULL;
+
+-- Row-level change history for nfts, collections and listings, written by
+-- trigger. `old_data` is the full row before the change, so any update or
+-- hard delete can be reverted. Inserts aren't recorded; the row itself is the
+-- record of its creation.
+CREATE TABLE IF NOT EXISTS row_history (
+    id BIGSERIAL PRIMARY KEY,
+    table_name VARCHAR(32) NOT NULL,
+    row_id UUID NOT NULL,
+    -- UPDATE or DELETE
+    operation VARCHAR(6) NOT NULL,
+    old_data JSONB NOT NULL,
+    new_data JSONB,
+    -- Set per transaction with `set_config('solmint.actor', ..., true)`;
+    -- NULL for changes made by the indexer
+    actor TEXT,
+    reason TEXT,
+    changed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_row_history_row ON row_history(table_name, row_id, id);
+
+CREATE OR REPLACE FUNCTION record_row_history() RETURNS TRIGGER AS $$
+DECLARE
+    v_new JSONB;
+BEGIN
+    IF TG_OP = 'UPDATE' THEN
+        v_new := to_jsonb(NEW);
+        -- Timestamp-only touches aren't changes worth keeping
+        IF (to_jsonb(OLD) - 'updated_at') = (v_new - 'updated_at') THEN
+            RETURN NEW;
+        END IF;
+    END IF;
+
+    INSERT INTO row_history (table_name, row_id, operation, old_data, new_data, actor, reason)
+    VALUES (
+        TG_TABLE_NAME, OLD.id, TG_OP, to_jsonb(OLD), v_new,
+        NULLIF(current_setting('solmint.actor', true), ''),
+        NULLIF(current_setting('solmint.reason', true), '')
+    );
+
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS nfts_record_history ON nfts;
+CREATE TRIGGER nfts_record_history
+    AFTER UPDATE OR DELETE ON nfts
+    FOR EACH ROW EXECUTE FUNCTION record_row_history();
+
+DROP TRIGGER IF EXISTS collections_record_history ON collections;
+CREATE TRIGGER collections_record_history
+    AFTER UPDATE OR DELETE ON collections
+    FOR EACH ROW EXECUTE FUNCTION record_row_history();
+
+DROP TRIGGER IF EXISTS listings_record_history ON listings;
+CREATE TRIGGER listings_record_history
+    AFTER UPDATE OR DELETE ON listings
+    FOR EACH ROW EXECUTE FUNCTION record_row_history();
+
+-- Put a row back the way it was before a history entry: re-insert it after a
+-- hard delete, otherwise overwrite every column with the old values. The
+-- revert is itself recorded as a new history entry.
+CREATE OR REPLACE FUNCTION revert_row_history(p_history_id BIGINT) RETURNS BOOLEAN AS $$
+DECLARE
+    v_entry row_history%ROWTYPE;
+    v_columns TEXT;
+BEGIN
+    SELECT * INTO v_entry FROM row_history WHERE id = p_history_id;
+    IF NOT FOUND THEN
+        RETURN FALSE;
+    END IF;
+
+    IF v_entry.operation = 'DELETE' THEN
+        EXECUTE format(
+            'INSERT INTO %I SELECT * FROM jsonb_populate_record(NULL::%I, $1) ON CONFLICT DO NOTHING',
+            v_entry.table_name, v_entry.table_name
+        ) USING v_entry.old_data;
+    ELSE
+        SELECT string_agg(format('%I', key), ', ') INTO v_columns
+        FROM jsonb_object_keys(v_entry.old_data) AS key
+        WHERE key <> 'id';
+
+        EXECUTE format(
+            'UPDATE %I SET (%s) = (SELECT %s FROM jsonb_populate_record(NULL::%I, $1)) WHERE id = $2',
+            v_entry.table_name, v_columns, v_columns, v_entry.table_name
+        ) USING v_entry.old_data, v_entry.row_id;
+    END IF;
+
+    RETURN TRUE;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,48 @@
+-- Optimistic concurrency for nfts and listings. Every update bumps `version`,
+-- whoever makes it, so a writer holding a stale copy of the row can detect
+-- that someone else got there first (compare-and-swap on `version`).
+ALTER TABLE nfts ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE listings ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+
+CREATE OR REPLACE FUNCTION bump_row_version() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS nfts_bump_version ON nfts;
+CREATE TRIGGER nfts_bump_version
+    BEFORE UPDATE ON nfts
+    FOR EACH ROW EXECUTE FUNCTION bump_row_version();
+
+DROP TRIGGER IF EXISTS listings_bump_version ON listings;
+CREATE TRIGGER listings_bump_version
+    BEFORE UPDATE ON listings
+    FOR EACH ROW EXECUTE FUNCTION bump_row_version();
+
+-- A version bump alone isn't a change worth keeping in row_history either
+CREATE OR REPLACE FUNCTION record_row_history() RETURNS TRIGGER AS $$
+DECLARE
+    v_new JSONB;
+BEGIN
+    IF TG_OP = 'UPDATE' THEN
+        v_new := to_jsonb(NEW);
+        IF (to_jsonb(OLD) - 'updated_at' - 'version') = (v_new - 'updated_at' - 'version') THEN
+            RETURN NEW;
+        END IF;
+    END IF;
+
+    INSERT INTO row_history (table_name, row_id, operation, old_data, new_data, actor, reason)
+    VALUES (
+        TG_TABLE_NAME, OLD.id, TG_OP, to_jsonb(OLD), v_new,
+        NULLIF(current_setting('solmint.actor', true), ''),
+        NULLIF(current_setting('solmint.reason', true), '')
+    );
+
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,22 @@
+-- Transactional outbox: domain events are inserted in the same transaction as
+-- the rows they describe, then published to Redis and event webhooks by the
+-- outbox relay job. Delivery is at-least-once (consumers dedupe on `id`) and
+-- in `id` order per aggregate.
+CREATE TABLE IF NOT EXISTS outbox (
+    id BIGSERIAL PRIMARY KEY,
+    -- nft, listing or sale
+    aggregate_type VARCHAR(32) NOT NULL,
+    aggregate_id VARCHAR(88) NOT NULL,
+    -- e.g. nft.created, listing.updated, sale.created
+    event_type VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    published_at TIMESTAMP WITH TIME ZONE,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbox_pending ON outbox(id) WHERE published_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_outbox_published_at ON outbox(published_at)
+    WHERE published_at IS NOT NULL;
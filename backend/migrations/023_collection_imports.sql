@@ -0,0 +1,44 @@
+-- On-chain verified collection (Metaplex certified collection mint), when known
+ALTER TABLE collections ADD COLUMN IF NOT EXISTS collection_address VARCHAR(44);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_collections_collection_address
+    ON collections(collection_address) WHERE collection_address IS NOT NULL;
+
+-- Admin-triggered backfill of an existing collection from a mint hashlist or
+-- its verified collection address, processed in batches by the import job
+CREATE TABLE IF NOT EXISTS collection_imports (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    collection_id UUID NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    -- hashlist or collection
+    source VARCHAR(16) NOT NULL,
+    collection_address VARCHAR(44),
+    -- queued, running, completed or failed
+    status VARCHAR(16) NOT NULL DEFAULT 'queued',
+    -- Next getAssetsByGroup page to fetch; NULL once every mint is enqueued
+    next_page INTEGER,
+    total_items INTEGER NOT NULL DEFAULT 0,
+    imported_items INTEGER NOT NULL DEFAULT 0,
+    failed_items INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    -- Lease held by the worker processing the import, so replicas don't overlap
+    locked_until TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX IF NOT EXISTS idx_collection_imports_open ON collection_imports(created_at)
+    WHERE status IN ('queued', 'running');
+
+CREATE TABLE IF NOT EXISTS collection_import_items (
+    import_id UUID NOT NULL REFERENCES collection_imports(id) ON DELETE CASCADE,
+    mint_address VARCHAR(44) NOT NULL,
+    -- pending, imported or failed
+    status VARCHAR(16) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (import_id, mint_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_collection_import_items_pending
+    ON collection_import_items(import_id) WHERE status = 'pending';
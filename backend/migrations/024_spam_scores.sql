@@ -0,0 +1,20 @@
+-- Spam scoring: the spam job sets `spam_score` (0-1) and the reasons behind
+-- it; `is_spam` is the effective flag list endpoints filter on, i.e. the
+-- admin override when set, otherwise the score against the threshold
+ALTER TABLE collections ADD COLUMN IF NOT EXISTS spam_score REAL;
+ALTER TABLE collections ADD COLUMN IF NOT EXISTS spam_reasons TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE collections ADD COLUMN IF NOT EXISTS spam_override BOOLEAN;
+ALTER TABLE collections ADD COLUMN IF NOT EXISTS is_spam BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE collections ADD COLUMN IF NOT EXISTS spam_scored_at TIMESTAMP WITH TIME ZONE;
+-- 64-bit difference hash of the collection image, for spotting copies
+ALTER TABLE collections ADD COLUMN IF NOT EXISTS image_phash BIGINT;
+ALTER TABLE collections ADD COLUMN IF NOT EXISTS image_phash_at TIMESTAMP WITH TIME ZONE;
+
+ALTER TABLE nfts ADD COLUMN IF NOT EXISTS spam_score REAL;
+ALTER TABLE nfts ADD COLUMN IF NOT EXISTS spam_reasons TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE nfts ADD COLUMN IF NOT EXISTS spam_override BOOLEAN;
+ALTER TABLE nfts ADD COLUMN IF NOT EXISTS is_spam BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE nfts ADD COLUMN IF NOT EXISTS spam_scored_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX IF NOT EXISTS idx_collections_is_spam ON collections(is_spam) WHERE is_spam;
+CREATE INDEX IF NOT EXISTS idx_nfts_is_spam ON nfts(is_spam) WHERE is_spam;
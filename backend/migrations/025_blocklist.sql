@@ -0,0 +1,34 @@
+-- Moderator blocklist: blocked mints, collections and creators are left out of
+-- list, search, trending and stats responses. `value` is the mint or creator
+-- address, or the collection id.
+CREATE TABLE IF NOT EXISTS blocklist (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    -- mint, collection or creator
+    kind VARCHAR(16) NOT NULL,
+    value VARCHAR(64) NOT NULL,
+    reason TEXT,
+    created_by TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (kind, value)
+);
+
+-- The one place blocking is decided; queries call these rather than joining
+-- the blocklist themselves
+CREATE OR REPLACE FUNCTION nft_is_blocked(p_mint TEXT, p_collection_id UUID, p_creator TEXT)
+RETURNS BOOLEAN AS $$
+    SELECT EXISTS (
+        SELECT 1 FROM blocklist
+        WHERE (kind = 'mint' AND value = p_mint)
+           OR (kind = 'collection' AND value = p_collection_id::text)
+           OR (kind = 'creator' AND value = p_creator)
+    );
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION collection_is_blocked(p_collection_id UUID, p_creator TEXT)
+RETURNS BOOLEAN AS $$
+    SELECT EXISTS (
+        SELECT 1 FROM blocklist
+        WHERE (kind = 'collection' AND value = p_collection_id::text)
+           OR (kind = 'creator' AND value = p_creator)
+    );
+$$ LANGUAGE sql STABLE;
@@ -0,0 +1,13 @@
+-- Admin API keys managed with `solmint-admin api-keys`, accepted alongside
+-- ADMIN_API_KEY so keys can be issued and rotated without a redeploy. Only a
+-- hash of each key is stored.
+CREATE TABLE IF NOT EXISTS admin_api_keys (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name TEXT NOT NULL,
+    key_hash VARCHAR(64) NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    -- Set when the key is rotated out; it keeps working until then
+    expires_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    last_used_at TIMESTAMP WITH TIME ZONE
+);
@@ -0,0 +1,7 @@
+-- 020 created its row history trigger under the name `listings_record_history`,
+-- which replaced the listing_history trigger from 010 and stopped churn events
+-- from being recorded. Restore it under a name of its own.
+DROP TRIGGER IF EXISTS listings_record_listing_history ON listings;
+CREATE TRIGGER listings_record_listing_history
+    AFTER INSERT OR UPDATE OF status, price ON listings
+    FOR EACH ROW EXECUTE FUNCTION record_listing_history();
@@ -0,0 +1,25 @@
+-- Natural keys for indexed rows, so replaying a transaction (backfill
+-- overlapping live indexing, retries, duplicate webhooks) is a no-op.
+
+-- A transaction can settle several sales, one per instruction
+ALTER TABLE sales ADD COLUMN IF NOT EXISTS instruction_index INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE sales DROP CONSTRAINT IF EXISTS sales_transaction_signature_key;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sales_signature_instruction
+    ON sales(transaction_signature, instruction_index);
+
+ALTER TABLE listings ADD COLUMN IF NOT EXISTS instruction_index INTEGER NOT NULL DEFAULT 0;
+
+-- Soft-delete all but the newest of any listings already duplicated by
+-- replays; checkout orders may still reference the older rows
+UPDATE listings l
+SET deleted_at = NOW()
+FROM listings newer
+WHERE l.transaction_signature = newer.transaction_signature
+  AND l.instruction_index = newer.instruction_index
+  AND l.deleted_at IS NULL
+  AND newer.deleted_at IS NULL
+  AND (l.created_at, l.id) < (newer.created_at, newer.id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_listings_signature_instruction
+    ON listings(transaction_signature, instruction_index)
+    WHERE transaction_signature IS NOT NULL AND deleted_at IS NULL;
@@ -0,0 +1,160 @@
+-- Running per-collection aggregates, kept current by triggers on sales and
+-- listings so reads don't re-aggregate whole collections. Floors and listed
+-- counts cover active SOL listings; volume is summed in lamports across
+-- currencies. Anything the triggers can't see (an NFT moving between
+-- collections, deleted sales) is corrected by the reconciliation job.
+CREATE TABLE IF NOT EXISTS collection_stats (
+    collection_id UUID PRIMARY KEY REFERENCES collections(id) ON DELETE CASCADE,
+    floor_price BIGINT,
+    listed_count BIGINT NOT NULL DEFAULT 0,
+    total_volume BIGINT NOT NULL DEFAULT 0,
+    sales_count BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE OR REPLACE FUNCTION collection_floor_price(p_collection_id UUID) RETURNS BIGINT AS $$
+    SELECT MIN(l.price)
+    FROM listings l
+    JOIN nfts n ON n.mint_address = l.nft_mint
+    WHERE n.collection_id = p_collection_id
+      AND l.status = 'active'
+      AND l.deleted_at IS NULL
+      AND l.currency_mint = 'So11111111111111111111111111111111111111112';
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION collection_stats_record_sale() RETURNS TRIGGER AS $$
+DECLARE
+    v_collection_id UUID;
+BEGIN
+    SELECT collection_id INTO v_collection_id FROM nfts WHERE mint_address = NEW.nft_mint;
+    IF v_collection_id IS NULL THEN
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO collection_stats (collection_id, total_volume, sales_count)
+    VALUES (v_collection_id, NEW.base_price, 1)
+    ON CONFLICT (collection_id) DO UPDATE SET
+        total_volume = collection_stats.total_volume + EXCLUDED.total_volume,
+        sales_count = collection_stats.sales_count + 1,
+        updated_at = NOW();
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS sales_collection_stats ON sales;
+CREATE TRIGGER sales_collection_stats
+    AFTER INSERT ON sales
+    FOR EACH ROW EXECUTE FUNCTION collection_stats_record_sale();
+
+-- A listing is a floor candidate while it's an active, undeleted SOL listing.
+-- Entering or getting cheaper can only lower the floor; leaving or getting
+-- more expensive only moves it when the listing was at the floor, and then
+-- just that collection's floor is recomputed.
+CREATE OR REPLACE FUNCTION collection_stats_record_listing() RETURNS TRIGGER AS $$
+DECLARE
+    v_collection_id UUID;
+    v_was_candidate BOOLEAN := FALSE;
+    v_is_candidate BOOLEAN;
+    v_floor BIGINT;
+BEGIN
+    v_is_candidate := NEW.status = 'active'
+        AND NEW.deleted_at IS NULL
+        AND NEW.currency_mint = 'So11111111111111111111111111111111111111112';
+    IF TG_OP = 'UPDATE' THEN
+        v_was_candidate := OLD.status = 'active'
+            AND OLD.deleted_at IS NULL
+            AND OLD.currency_mint = 'So11111111111111111111111111111111111111112';
+    END IF;
+
+    IF NOT v_was_candidate AND NOT v_is_candidate THEN
+        RETURN NEW;
+    END IF;
+    IF v_was_candidate AND v_is_candidate AND NEW.price = OLD.price THEN
+        RETURN NEW;
+    END IF;
+
+    SELECT collection_id INTO v_collection_id FROM nfts WHERE mint_address = NEW.nft_mint;
+    IF v_collection_id IS NULL THEN
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO collection_stats (collection_id) VALUES (v_collection_id)
+    ON CONFLICT (collection_id) DO NOTHING;
+    SELECT floor_price INTO v_floor
+    FROM collection_stats WHERE collection_id = v_collection_id FOR UPDATE;
+
+    IF v_was_candidate AND (NOT v_is_candidate OR NEW.price > OLD.price)
+        AND OLD.price <= v_floor THEN
+        v_floor := collection_floor_price(v_collection_id);
+    ELSIF v_is_candidate THEN
+        v_floor := LEAST(v_floor, NEW.price);
+    END IF;
+
+    UPDATE collection_stats
+    SET floor_price = v_floor,
+        listed_count = listed_count
+            + CASE WHEN v_is_candidate THEN 1 ELSE 0 END
+            - CASE WHEN v_was_candidate THEN 1 ELSE 0 END,
+        updated_at = NOW()
+    WHERE collection_id = v_collection_id;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS listings_collection_stats ON listings;
+CREATE TRIGGER listings_collection_stats
+    AFTER INSERT OR UPDATE OF status, price, deleted_at, currency_mint ON listings
+    FOR EACH ROW EXECUTE FUNCTION collection_stats_record_listing();
+
+-- Rebuild every collection's row from the source tables, returning how many
+-- rows were missing or had drifted
+CREATE OR REPLACE FUNCTION reconcile_collection_stats() RETURNS BIGINT AS $$
+    WITH actual AS (
+        SELECT c.id AS collection_id,
+               f.floor_price,
+               COALESCE(f.listed_count, 0) AS listed_count,
+               COALESCE(s.total_volume, 0) AS total_volume,
+               COALESCE(s.sales_count, 0) AS sales_count
+        FROM collections c
+        LEFT JOIN (
+            SELECT n.collection_id, MIN(l.price) AS floor_price, COUNT(*) AS listed_count
+            FROM listings l
+            JOIN nfts n ON n.mint_address = l.nft_mint
+            WHERE l.status = 'active'
+              AND l.deleted_at IS NULL
+              AND l.currency_mint = 'So11111111111111111111111111111111111111112'
+            GROUP BY n.collection_id
+        ) f ON f.collection_id = c.id
+        LEFT JOIN (
+            SELECT n.collection_id, SUM(s.base_price)::BIGINT AS total_volume,
+                   COUNT(*) AS sales_count
+            FROM sales s
+            JOIN nfts n ON n.mint_address = s.nft_mint
+            GROUP BY n.collection_id
+        ) s ON s.collection_id = c.id
+    ),
+    corrected AS (
+        INSERT INTO collection_stats (
+            collection_id, floor_price, listed_count, total_volume, sales_count
+        )
+        SELECT collection_id, floor_price, listed_count, total_volume, sales_count
+        FROM actual
+        ON CONFLICT (collection_id) DO UPDATE SET
+            floor_price = EXCLUDED.floor_price,
+            listed_count = EXCLUDED.listed_count,
+            total_volume = EXCLUDED.total_volume,
+            sales_count = EXCLUDED.sales_count,
+            updated_at = NOW()
+        WHERE (collection_stats.floor_price, collection_stats.listed_count,
+               collection_stats.total_volume, collection_stats.sales_count)
+              IS DISTINCT FROM
+              (EXCLUDED.floor_price, EXCLUDED.listed_count,
+               EXCLUDED.total_volume, EXCLUDED.sales_count)
+        RETURNING 1
+    )
+    SELECT COUNT(*) FROM corrected;
+$$ LANGUAGE sql;
+
+SELECT reconcile_collection_stats();
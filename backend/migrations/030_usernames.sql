@@ -0,0 +1,15 @@
+-- Usernames are unique regardless of case, so a name can't be registered
+-- again with different capitalisation to impersonate its owner.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS twitter_verified_at TIMESTAMP WITH TIME ZONE;
+
+-- Existing names that break the rules, or duplicate an older account's name,
+-- are cleared for their owners to pick again
+UPDATE users SET username = NULL
+WHERE username IS NOT NULL AND username !~ '^[A-Za-z0-9_]{3,30}$';
+
+UPDATE users u SET username = NULL
+FROM users earlier
+WHERE LOWER(u.username) = LOWER(earlier.username)
+  AND (earlier.created_at, earlier.id) < (u.created_at, u.id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_username_lower ON users(LOWER(username));
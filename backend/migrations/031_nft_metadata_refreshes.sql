@@ -0,0 +1,14 @@
+-- When each NFT's metadata was last re-read from its source. Kept out of the
+-- nfts table so a refresh that finds nothing new doesn't bump the row's
+-- version or write row history.
+CREATE TABLE IF NOT EXISTS nft_metadata_refreshes (
+    mint_address VARCHAR(44) PRIMARY KEY REFERENCES nfts(mint_address) ON DELETE CASCADE,
+    metadata_uri TEXT,
+    refreshed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    -- Last time the refresh found different metadata
+    changed_at TIMESTAMP WITH TIME ZONE,
+    last_error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_nft_metadata_refreshes_refreshed_at
+    ON nft_metadata_refreshes(refreshed_at);
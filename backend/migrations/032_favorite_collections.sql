@@ -0,0 +1,11 @@
+-- Collections a user has favorited, alongside user_favorites for NFTs
+CREATE TABLE IF NOT EXISTS user_favorite_collections (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    collection_id UUID NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, collection_id)
+);
+
+-- Favorite counts on collection responses
+CREATE INDEX IF NOT EXISTS idx_user_favorite_collections_collection
+    ON user_favorite_collections(collection_id);
@@ -0,0 +1,144 @@
+-- Every indexed event (mints, listing changes, sales, transfers) in one
+-- append-only table for the firehose endpoint. Triggers record rows in write
+-- order (`id`); the activity sequencer job later assigns `sequence`, the
+-- firehose id, to committed rows one batch at a time under a lock. Ids from
+-- a BIGSERIAL alone commit out of order, so a consumer paging by them could
+-- skip a row that commits after it has read past it.
+CREATE TABLE IF NOT EXISTS activities (
+    id BIGSERIAL PRIMARY KEY,
+    sequence BIGINT,
+    -- mint, list, price_change, delist, sale, transfer
+    activity_type VARCHAR(20) NOT NULL,
+    nft_mint VARCHAR(44) NOT NULL,
+    collection_id UUID,
+    from_address VARCHAR(44),
+    to_address VARCHAR(44),
+    price BIGINT,
+    currency_mint VARCHAR(44),
+    transaction_signature VARCHAR(88),
+    occurred_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_activities_sequence ON activities(sequence);
+CREATE INDEX IF NOT EXISTS idx_activities_unsequenced ON activities(id) WHERE sequence IS NULL;
+
+-- Mints and ownership changes
+CREATE OR REPLACE FUNCTION record_nft_activity() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO activities (
+            activity_type, nft_mint, collection_id, to_address, occurred_at
+        )
+        VALUES (
+            'mint', NEW.mint_address, NEW.collection_id, NEW.current_owner,
+            COALESCE(NEW.created_at, NOW())
+        );
+    ELSIF NEW.current_owner IS DISTINCT FROM OLD.current_owner THEN
+        INSERT INTO activities (
+            activity_type, nft_mint, collection_id, from_address, to_address, occurred_at
+        )
+        VALUES (
+            'transfer', NEW.mint_address, NEW.collection_id, OLD.current_owner,
+            NEW.current_owner, NOW()
+        );
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS nfts_record_activity ON nfts;
+CREATE TRIGGER nfts_record_activity
+    AFTER INSERT OR UPDATE OF current_owner ON nfts
+    FOR EACH ROW EXECUTE FUNCTION record_nft_activity();
+
+-- Listing changes, as already classified by listing_history. Sold listings
+-- are covered by the sale itself.
+CREATE OR REPLACE FUNCTION record_listing_activity() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.event_type = 'sold' THEN
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO activities (
+        activity_type, nft_mint, collection_id, from_address, price, currency_mint,
+        transaction_signature, occurred_at
+    )
+    SELECT CASE NEW.event_type
+               WHEN 'listed' THEN 'list'
+               WHEN 'delisted' THEN 'delist'
+               ELSE NEW.event_type
+           END,
+           NEW.nft_mint, NEW.collection_id, NEW.seller_address, NEW.price, NEW.currency_mint,
+           (SELECT l.transaction_signature FROM listings l WHERE l.id = NEW.listing_id),
+           NEW.occurred_at;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS listing_history_record_activity ON listing_history;
+CREATE TRIGGER listing_history_record_activity
+    AFTER INSERT ON listing_history
+    FOR EACH ROW EXECUTE FUNCTION record_listing_activity();
+
+CREATE OR REPLACE FUNCTION record_sale_activity() RETURNS TRIGGER AS $$
+DECLARE
+    v_collection_id UUID;
+BEGIN
+    SELECT collection_id INTO v_collection_id FROM nfts WHERE mint_address = NEW.nft_mint;
+
+    INSERT INTO activities (
+        activity_type, nft_mint, collection_id, from_address, to_address, price,
+        currency_mint, transaction_signature, occurred_at
+    )
+    VALUES (
+        'sale', NEW.nft_mint, v_collection_id, NEW.seller_address, NEW.buyer_address,
+        NEW.price, NEW.currency_mint, NEW.transaction_signature, NEW.block_time
+    );
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS sales_record_activity ON sales;
+CREATE TRIGGER sales_record_activity
+    AFTER INSERT ON sales
+    FOR EACH ROW EXECUTE FUNCTION record_sale_activity();
+
+-- Seed the firehose with the history that already exists, oldest first.
+-- Earlier transfers aren't recorded anywhere, so history starts with mints,
+-- listing events and sales.
+INSERT INTO activities (
+    sequence, activity_type, nft_mint, collection_id, from_address, to_address, price,
+    currency_mint, transaction_signature, occurred_at
+)
+SELECT ROW_NUMBER() OVER (ORDER BY occurred_at, kind, key), activity_type, nft_mint,
+       collection_id, from_address, to_address, price, currency_mint, transaction_signature,
+       occurred_at
+FROM (
+    SELECT 0 AS kind, n.id::text AS key, 'mint' AS activity_type, n.mint_address AS nft_mint,
+           n.collection_id, NULL::varchar AS from_address, n.current_owner AS to_address,
+           NULL::bigint AS price, NULL::varchar AS currency_mint,
+           NULL::varchar AS transaction_signature, COALESCE(n.created_at, NOW()) AS occurred_at
+    FROM nfts n
+    UNION ALL
+    SELECT 1, h.id::text,
+           CASE h.event_type
+               WHEN 'listed' THEN 'list'
+               WHEN 'delisted' THEN 'delist'
+               ELSE h.event_type
+           END,
+           h.nft_mint, h.collection_id, h.seller_address, NULL, h.price, h.currency_mint,
+           l.transaction_signature, h.occurred_at
+    FROM listing_history h
+    LEFT JOIN listings l ON l.id = h.listing_id
+    WHERE h.event_type <> 'sold'
+    UNION ALL
+    SELECT 2, s.id::text, 'sale', s.nft_mint, n.collection_id, s.seller_address,
+           s.buyer_address, s.price, s.currency_mint, s.transaction_signature, s.block_time
+    FROM sales s
+    LEFT JOIN nfts n ON n.mint_address = s.nft_mint
+) history
+WHERE NOT EXISTS (SELECT 1 FROM activities);
@@ -0,0 +1,8 @@
+-- Referrer paid part of the marketplace fee on a sale, if any. The referral
+-- fee is included in marketplace_fee.
+ALTER TABLE sales ADD COLUMN IF NOT EXISTS referrer_address VARCHAR(44);
+ALTER TABLE sales ADD COLUMN IF NOT EXISTS referral_fee BIGINT NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_sales_referrer_address_block_time
+    ON sales(referrer_address, block_time)
+    WHERE referrer_address IS NOT NULL;
@@ -0,0 +1,45 @@
+-- NFT rentals indexed from the program's `NftRented` events, one row per
+-- rental period. `returned_at` is set when the NFT is reclaimed.
+CREATE TABLE IF NOT EXISTS rentals (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    nft_mint VARCHAR(44) NOT NULL,
+    owner_address VARCHAR(44) NOT NULL,
+    renter_address VARCHAR(44) NOT NULL,
+    days INTEGER NOT NULL,
+    -- Rent paid upfront for the whole period, in lamports
+    price BIGINT NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    returned_at TIMESTAMP WITH TIME ZONE,
+    transaction_signature VARCHAR(88) UNIQUE NOT NULL,
+    block_time TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_rentals_nft_mint_block_time ON rentals(nft_mint, block_time);
+CREATE INDEX IF NOT EXISTS idx_rentals_renter_address ON rentals(renter_address);
+
+-- Rentals in the activity firehose, as `rent` from the owner to the renter
+CREATE OR REPLACE FUNCTION record_rental_activity() RETURNS TRIGGER AS $$
+DECLARE
+    v_collection_id UUID;
+BEGIN
+    SELECT collection_id INTO v_collection_id FROM nfts WHERE mint_address = NEW.nft_mint;
+
+    INSERT INTO activities (
+        activity_type, nft_mint, collection_id, from_address, to_address, price,
+        currency_mint, transaction_signature, occurred_at
+    )
+    VALUES (
+        'rent', NEW.nft_mint, v_collection_id, NEW.owner_address, NEW.renter_address,
+        NEW.price, 'So11111111111111111111111111111111111111112', NEW.transaction_signature,
+        NEW.block_time
+    );
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS rentals_record_activity ON rentals;
+CREATE TRIGGER rentals_record_activity
+    AFTER INSERT ON rentals
+    FOR EACH ROW EXECUTE FUNCTION record_rental_activity();
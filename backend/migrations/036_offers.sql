@@ -0,0 +1,22 @@
+-- Offers on single NFTs, one row per offer account. The offer PDA is derived
+-- from the mint and bidder, so a bidder's new offer on the same NFT reuses
+-- its row. Rows are written when the make offer transaction is built, as
+-- `pending`, and move on once the transaction lands.
+CREATE TABLE IF NOT EXISTS offers (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    offer_address VARCHAR(44) UNIQUE NOT NULL,
+    nft_mint VARCHAR(44) NOT NULL REFERENCES nfts(mint_address),
+    bidder_address VARCHAR(44) NOT NULL,
+    marketplace_address VARCHAR(44) NOT NULL,
+    -- Offered lamports, held in the offer account
+    amount BIGINT NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE,
+    -- pending, active, accepted, cancelled
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    transaction_signature VARCHAR(88),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_offers_nft_mint_status ON offers(nft_mint, status);
+CREATE INDEX IF NOT EXISTS idx_offers_bidder_address ON offers(bidder_address);
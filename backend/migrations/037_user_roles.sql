@@ -0,0 +1,10 @@
+-- What a wallet may do beyond its own data: moderators handle verification,
+-- spam and blocklists, admins everything. Admins listed in ADMIN_WALLETS are
+-- promoted at startup.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS role VARCHAR(20) NOT NULL DEFAULT 'user';
+
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_role_check;
+ALTER TABLE users ADD CONSTRAINT users_role_check
+    CHECK (role IN ('user', 'moderator', 'admin'));
+
+CREATE INDEX IF NOT EXISTS idx_users_role ON users(role) WHERE role <> 'user';
@@ -0,0 +1,14 @@
+-- Wallets and IPs refused on write routes such as minting and uploads, added
+-- by moderators. Automatic blocks for repeated failures live in Redis.
+CREATE TABLE IF NOT EXISTS access_blocks (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    -- wallet or ip
+    kind VARCHAR(16) NOT NULL,
+    value VARCHAR(64) NOT NULL,
+    reason TEXT,
+    created_by TEXT,
+    -- Permanent when NULL
+    expires_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (kind, value)
+);
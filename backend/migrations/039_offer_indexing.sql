@@ -0,0 +1,18 @@
+-- Offers are also indexed from chain: offers not made through the API, and
+-- collection offers, which any NFT of the collection can fill. The
+-- marketplace of an offer first seen on chain isn't known.
+ALTER TABLE offers ALTER COLUMN nft_mint DROP NOT NULL;
+ALTER TABLE offers ALTER COLUMN marketplace_address DROP NOT NULL;
+ALTER TABLE offers ADD COLUMN IF NOT EXISTS collection_id UUID REFERENCES collections(id);
+-- Who accepted the offer and, for collection offers, the NFT they sold
+ALTER TABLE offers ADD COLUMN IF NOT EXISTS seller_address VARCHAR(44);
+ALTER TABLE offers ADD COLUMN IF NOT EXISTS filled_mint VARCHAR(44);
+
+ALTER TABLE offers DROP CONSTRAINT IF EXISTS offers_target_check;
+ALTER TABLE offers ADD CONSTRAINT offers_target_check
+    CHECK ((nft_mint IS NULL) <> (collection_id IS NULL));
+
+CREATE INDEX IF NOT EXISTS idx_offers_collection_status
+    ON offers(collection_id, status) WHERE collection_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_offers_seller_address
+    ON offers(seller_address) WHERE seller_address IS NOT NULL;
@@ -0,0 +1,42 @@
+-- Auctions and their bids, indexed from the program's events. The auction
+-- PDA is derived from the mint and seller, so a seller auctioning the same
+-- NFT again reuses the address; each run gets its own row and only one may
+-- be unsettled at a time.
+CREATE TABLE IF NOT EXISTS auctions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    auction_address VARCHAR(44) NOT NULL,
+    nft_mint VARCHAR(44) NOT NULL REFERENCES nfts(mint_address),
+    seller_address VARCHAR(44) NOT NULL,
+    -- Lamports
+    reserve_price BIGINT NOT NULL,
+    ends_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    highest_bid BIGINT,
+    highest_bidder VARCHAR(44),
+    bid_count INTEGER NOT NULL DEFAULT 0,
+    -- live, settled
+    status VARCHAR(20) NOT NULL DEFAULT 'live',
+    -- NULL when the auction ended without bids
+    winner_address VARCHAR(44),
+    transaction_signature VARCHAR(88) UNIQUE NOT NULL,
+    settle_signature VARCHAR(88),
+    settled_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_auctions_live_address
+    ON auctions(auction_address) WHERE status = 'live';
+CREATE INDEX IF NOT EXISTS idx_auctions_status_ends_at ON auctions(status, ends_at);
+CREATE INDEX IF NOT EXISTS idx_auctions_nft_mint ON auctions(nft_mint);
+
+CREATE TABLE IF NOT EXISTS bids (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    auction_id UUID NOT NULL REFERENCES auctions(id) ON DELETE CASCADE,
+    bidder_address VARCHAR(44) NOT NULL,
+    amount BIGINT NOT NULL,
+    transaction_signature VARCHAR(88) UNIQUE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_bids_auction_id ON bids(auction_id, amount DESC);
+CREATE INDEX IF NOT EXISTS idx_bids_bidder_address ON bids(bidder_address);
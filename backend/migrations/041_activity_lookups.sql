@@ -0,0 +1,40 @@
+-- Activity of one NFT or one wallet, newest first, and offers in the
+-- activity stream
+CREATE INDEX IF NOT EXISTS idx_activities_nft_mint_sequence
+    ON activities(nft_mint, sequence DESC) WHERE sequence IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_activities_from_address_sequence
+    ON activities(from_address, sequence DESC) WHERE sequence IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_activities_to_address_sequence
+    ON activities(to_address, sequence DESC) WHERE sequence IS NOT NULL;
+
+-- Offers on single NFTs as `offer` from the bidder at the amount offered,
+-- once they're active on chain. Collection offers aren't about one NFT and
+-- stay out of the stream.
+CREATE OR REPLACE FUNCTION record_offer_activity() RETURNS TRIGGER AS $$
+DECLARE
+    v_collection_id UUID;
+BEGIN
+    IF NEW.nft_mint IS NULL OR NEW.status <> 'active'
+        OR (TG_OP = 'UPDATE' AND OLD.status = 'active') THEN
+        RETURN NEW;
+    END IF;
+
+    SELECT collection_id INTO v_collection_id FROM nfts WHERE mint_address = NEW.nft_mint;
+
+    INSERT INTO activities (
+        activity_type, nft_mint, collection_id, from_address, price, currency_mint,
+        transaction_signature, occurred_at
+    )
+    VALUES (
+        'offer', NEW.nft_mint, v_collection_id, NEW.bidder_address, NEW.amount,
+        'So11111111111111111111111111111111111111112', NEW.transaction_signature, NOW()
+    );
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS offers_record_activity ON offers;
+CREATE TRIGGER offers_record_activity
+    AFTER INSERT OR UPDATE OF status ON offers
+    FOR EACH ROW EXECUTE FUNCTION record_offer_activity();
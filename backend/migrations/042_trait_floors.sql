@@ -0,0 +1,8 @@
+-- Per-trait floors: the cheapest active SOL listing among the collection's
+-- NFTs with each trait value, refreshed by the trait floor job. The `None`
+-- and `Trait Count` values the rarity job records have no floor.
+ALTER TABLE collection_traits ADD COLUMN IF NOT EXISTS floor_price BIGINT;
+ALTER TABLE collection_traits ADD COLUMN IF NOT EXISTS listed_count BIGINT NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_collection_traits_collection_trait_type
+    ON collection_traits(collection_id, trait_type);
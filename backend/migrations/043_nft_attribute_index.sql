@@ -0,0 +1,4 @@
+-- Attribute filters on the NFT list are `attributes @> '[{"trait_type": ..,
+-- "value": ..}]'` containment checks; jsonb_path_ops indexes exactly those
+CREATE INDEX IF NOT EXISTS idx_nfts_attributes
+    ON nfts USING GIN (attributes jsonb_path_ops);
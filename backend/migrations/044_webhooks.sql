@@ -0,0 +1,46 @@
+-- Integrator webhooks fed from the activity firehose. Every matching
+-- activity becomes a delivery, retried with backoff until it succeeds or runs
+-- out of attempts; deliveries double as the webhook's delivery log.
+CREATE TABLE IF NOT EXISTS webhooks (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    owner_address VARCHAR(44) NOT NULL,
+    url TEXT NOT NULL,
+    -- Any of sale, listing, mint, offer
+    events TEXT[] NOT NULL,
+    -- NULL for every collection
+    collection_id UUID REFERENCES collections(id) ON DELETE CASCADE,
+    -- Signs deliveries; returned once, when the webhook is registered
+    secret VARCHAR(64) NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    -- Firehose sequence deliveries have been created up to. Starts at the
+    -- head, so a new or re-enabled webhook only sees later activity.
+    last_sequence BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhooks_owner_address ON webhooks(owner_address);
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id BIGSERIAL PRIMARY KEY,
+    webhook_id UUID NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    -- Firehose sequence of the activity delivered
+    activity_id BIGINT NOT NULL,
+    event VARCHAR(20) NOT NULL,
+    payload JSONB NOT NULL,
+    -- pending, delivered, failed
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    -- Of the latest attempt
+    response_status INTEGER,
+    last_error TEXT,
+    delivered_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (webhook_id, activity_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due
+    ON webhook_deliveries(next_attempt_at) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook_id
+    ON webhook_deliveries(webhook_id, id DESC);
@@ -0,0 +1,8 @@
+-- Handed-out mint transactions reserve their item index and count towards
+-- wallet limits until they expire; once the mint is indexed they count for
+-- good. Expired reservations free their item for the next mint.
+ALTER TABLE launch_mints
+    ADD COLUMN IF NOT EXISTS expires_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW();
+ALTER TABLE launches DROP COLUMN IF EXISTS next_index;
+
+CREATE INDEX IF NOT EXISTS idx_launch_mints_item ON launch_mints(launch_id, item_index);
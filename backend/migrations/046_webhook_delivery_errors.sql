@@ -0,0 +1,6 @@
+-- Delivery errors no longer quote the receiver's response body, which the
+-- delivery log showed to whoever registered the URL. Drop the bodies already
+-- recorded, keeping the status.
+UPDATE webhook_deliveries
+SET last_error = 'HTTP ' || response_status
+WHERE response_status IS NOT NULL AND last_error IS NOT NULL;
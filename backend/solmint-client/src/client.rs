@@ -0,0 +1,324 @@
+use reqwest::{header::RETRY_AFTER, Method, RequestBuilder, Response, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::error::{ApiError, Error};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// When and how often failed requests are sent again. Rate limited requests
+/// and connection failures are always retried; other 5xx responses only for
+/// requests that are safe to repeat (GET, PUT, DELETE).
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts after the first one; 0 disables retries
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Exponential backoff for the given retry (0-based)
+    fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
+pub struct ClientBuilder {
+    base_url: String,
+    admin_api_key: Option<String>,
+    auth_token: Option<String>,
+    timeout: Duration,
+    retry: RetryPolicy,
+    http: Option<reqwest::Client>,
+}
+
+impl ClientBuilder {
+    /// Sent as `Authorization: Bearer <key>`; needed for `/api/v1/admin` endpoints
+    /// and admin-only filters such as `include_blocked`
+    pub fn admin_api_key(mut self, key: impl Into<String>) -> Self {
+        self.admin_api_key = Some(key.into());
+        self
+    }
+
+    /// Wallet token from [`Client::auth_verify`], sent as
+    /// `Authorization: Bearer <token>` when no admin key is set; needed for
+    /// requests acting as the wallet, from profile and notification changes
+    /// to offers, launches, allowlists and checkout orders, and to read its
+    /// notifications, watchlist, devices, tax report and orders
+    pub fn auth_token(mut self, token: impl Into<String>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Use a preconfigured HTTP client; `timeout` is then ignored
+    pub fn http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = Some(http);
+        self
+    }
+
+    pub fn build(self) -> Result<Client, Error> {
+        let http = match self.http {
+            Some(http) => http,
+            None => reqwest::Client::builder().timeout(self.timeout).build()?,
+        };
+
+        Ok(Client {
+            http,
+            base_url: self.base_url.trim_end_matches('/').to_string(),
+            admin_api_key: self.admin_api_key,
+            auth_token: self.auth_token,
+            retry: self.retry,
+        })
+    }
+}
+
+/// Client for the SolMint REST API. Cheap to clone; clones share a connection pool.
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    admin_api_key: Option<String>,
+    auth_token: Option<String>,
+    retry: RetryPolicy,
+}
+
+impl Client {
+    /// A client with the default timeout and retry policy
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(DEFAULT_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            admin_api_key: None,
+            auth_token: None,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    pub fn builder(base_url: impl Into<String>) -> ClientBuilder {
+        ClientBuilder {
+            base_url: base_url.into(),
+            admin_api_key: None,
+            auth_token: None,
+            timeout: DEFAULT_TIMEOUT,
+            retry: RetryPolicy::default(),
+            http: None,
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// A request with the admin key or wallet token attached, if one is
+    /// configured
+    pub(crate) fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self
+            .http
+            .request(method, format!("{}{}", self.base_url, path));
+        match self.admin_api_key.as_ref().or(self.auth_token.as_ref()) {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+
+    /// A request authorized with `token` instead of the admin key
+    pub(crate) fn request_with_token(
+        &self,
+        method: Method,
+        path: &str,
+        token: &str,
+    ) -> RequestBuilder {
+        self.http
+            .request(method, format!("{}{}", self.base_url, path))
+            .bearer_auth(token)
+    }
+
+    pub(crate) async fn get<R: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(String, String)],
+    ) -> Result<R, Error> {
+        self.send_json(self.request(Method::GET, path).query(query))
+            .await
+    }
+
+    pub(crate) async fn get_text(
+        &self,
+        path: &str,
+        query: &[(String, String)],
+    ) -> Result<String, Error> {
+        let response = self
+            .send(self.request(Method::GET, path).query(query))
+            .await?;
+        Ok(response.text().await?)
+    }
+
+    pub(crate) async fn post<B: Serialize, R: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<R, Error> {
+        self.send_json(self.request(Method::POST, path).json(body))
+            .await
+    }
+
+    pub(crate) async fn put<B: Serialize, R: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<R, Error> {
+        self.send_json(self.request(Method::PUT, path).json(body))
+            .await
+    }
+
+    pub(crate) async fn delete<R: DeserializeOwned>(&self, path: &str) -> Result<R, Error> {
+        self.send_json(self.request(Method::DELETE, path)).await
+    }
+
+    pub(crate) async fn send_json<R: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<R, Error> {
+        let response = self.send(request).await?;
+        let body = response.bytes().await?;
+        serde_json::from_slice(&body).map_err(Error::Decode)
+    }
+
+    /// Send a request, retrying per the retry policy, and turn error statuses
+    /// into `Error::Api`
+    pub(crate) async fn send(&self, request: RequestBuilder) -> Result<Response, Error> {
+        let (client, request) = request.build_split();
+        let request = request?;
+        let repeatable = request.method() != Method::POST;
+
+        let mut retry = 0;
+        loop {
+            // JSON and empty bodies always clone
+            let attempt = request.try_clone().expect("request bodies are buffered");
+            let result = client.execute(attempt).await;
+
+            let retry_after = match &result {
+                Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    Some(retry_after(response))
+                }
+                Ok(response) if repeatable && is_transient(response.status()) => {
+                    Some(retry_after(response))
+                }
+                Err(error) if error.is_connect() || (repeatable && error.is_timeout()) => {
+                    Some(None)
+                }
+                _ => None,
+            };
+
+            match retry_after {
+                Some(wait) if retry < self.retry.max_retries => {
+                    let backoff = self.retry.backoff(retry);
+                    tokio::time::sleep(
+                        wait.map_or(backoff, |wait| wait.min(self.retry.max_backoff)),
+                    )
+                    .await;
+                    retry += 1;
+                }
+                _ => return check_status(result?).await,
+            }
+        }
+    }
+}
+
+fn is_transient(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// `Retry-After` in seconds, when the server sent one
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+async fn check_status(response: Response) -> Result<Response, Error> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().await?;
+    match serde_json::from_str::<ApiError>(&body) {
+        Ok(error) => Err(Error::Api(error)),
+        Err(_) => Err(Error::Status {
+            status: status.as_u16(),
+            body,
+        }),
+    }
+}
+
+/// Flatten a query struct into `key=value` pairs, leaving out unset fields
+pub(crate) fn query_pairs<Q: Serialize>(query: &Q) -> Vec<(String, String)> {
+    let Ok(Value::Object(fields)) = serde_json::to_value(query) else {
+        return Vec::new();
+    };
+
+    fields
+        .into_iter()
+        .filter_map(|(key, value)| match value {
+            Value::Null => None,
+            Value::String(value) => Some((key, value)),
+            value => Some((key, value.to_string())),
+        })
+        .collect()
+}
+
+/// Percent-encode a path segment such as a wallet or `.sol` name
+pub(crate) fn segment(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
@@ -0,0 +1,29 @@
+use super::user_path;
+use crate::{
+    client::{query_pairs, segment, Client},
+    pagination::Pages,
+    queries::ActivityQuery,
+    types::Activity,
+};
+
+impl Client {
+    /// An NFT's activity, newest first
+    pub fn nft_activities(&self, mint: &str, query: &ActivityQuery) -> Pages<Activity> {
+        Pages::new(
+            self.clone(),
+            format!("/api/v1/nfts/{}/activities", segment(mint)),
+            query_pairs(query),
+            "activities",
+        )
+    }
+
+    /// Activity from or to a wallet, newest first
+    pub fn wallet_activities(&self, wallet: &str, query: &ActivityQuery) -> Pages<Activity> {
+        Pages::new(
+            self.clone(),
+            format!("{}/activities", user_path(wallet)),
+            query_pairs(query),
+            "activities",
+        )
+    }
+}
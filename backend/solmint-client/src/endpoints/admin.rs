@@ -0,0 +1,234 @@
+//! `/api/v1/admin` endpoints; the client must be built with an admin API key,
+//! or the auth token of an admin wallet
+
+use reqwest::Method;
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::field;
+use crate::{
+    client::{query_pairs, segment, Client},
+    error::Error,
+    pagination::Pages,
+    queries::BlocklistQuery,
+    requests::{
+        AdminChangeRequest, CollectionMultiplierRequest, CreateAccessBlockRequest,
+        CreateBlocklistEntryRequest, ImportCollectionRequest, PointsAdjustmentRequest,
+        PointsSeasonRequest, SetUserRoleRequest, SpamOverrideRequest,
+    },
+    responses::{
+        CollectionImportDetail, CollectionImportStarted, CollectionSpamOverride, NftSpamOverride,
+        PointsRecompute,
+    },
+    types::{
+        AccessBlock, BlocklistEntry, CollectionImport, PointsEntry, PointsSeason, Role, RowHistory,
+    },
+};
+
+impl Client {
+    pub async fn points_seasons(&self) -> Result<Vec<PointsSeason>, Error> {
+        let value: Value = self.get("/api/v1/admin/points/seasons", &[]).await?;
+        field(value, "seasons")
+    }
+
+    pub async fn create_points_season(
+        &self,
+        req: &PointsSeasonRequest,
+    ) -> Result<PointsSeason, Error> {
+        let value: Value = self.post("/api/v1/admin/points/seasons", req).await?;
+        field(value, "season")
+    }
+
+    pub async fn update_points_season(
+        &self,
+        id: Uuid,
+        req: &PointsSeasonRequest,
+    ) -> Result<PointsSeason, Error> {
+        let value: Value = self
+            .put(&format!("/api/v1/admin/points/seasons/{}", id), req)
+            .await?;
+        field(value, "season")
+    }
+
+    pub async fn set_points_multiplier(
+        &self,
+        season_id: Uuid,
+        collection_id: Uuid,
+        multiplier: f64,
+    ) -> Result<(), Error> {
+        let path = format!(
+            "/api/v1/admin/points/seasons/{}/multipliers/{}",
+            season_id, collection_id
+        );
+        self.put::<_, Value>(&path, &CollectionMultiplierRequest { multiplier })
+            .await?;
+        Ok(())
+    }
+
+    pub async fn adjust_points(
+        &self,
+        season_id: Uuid,
+        req: &PointsAdjustmentRequest,
+    ) -> Result<PointsEntry, Error> {
+        let path = format!("/api/v1/admin/points/seasons/{}/adjustments", season_id);
+        let value: Value = self.post(&path, req).await?;
+        field(value, "entry")
+    }
+
+    /// Rebuild a season's ledger from its sales and listings
+    pub async fn recompute_points_season(&self, season_id: Uuid) -> Result<PointsRecompute, Error> {
+        let path = format!("/api/v1/admin/points/seasons/{}/recompute", season_id);
+        self.send_json(self.request(Method::POST, &path)).await
+    }
+
+    /// Queue a backfill of a collection from a hashlist or collection address
+    pub async fn import_collection(
+        &self,
+        req: &ImportCollectionRequest,
+    ) -> Result<CollectionImportStarted, Error> {
+        self.post("/api/v1/admin/collections/import", req).await
+    }
+
+    pub fn collection_imports(&self) -> Pages<CollectionImport> {
+        Pages::new(
+            self.clone(),
+            "/api/v1/admin/collections/imports".to_string(),
+            Vec::new(),
+            "imports",
+        )
+    }
+
+    pub async fn collection_import(&self, id: Uuid) -> Result<CollectionImportDetail, Error> {
+        self.get(&format!("/api/v1/admin/collections/imports/{}", id), &[])
+            .await
+    }
+
+    /// Pin a collection's spam flag, or hand it back to the spam job with `None`
+    pub async fn override_collection_spam(
+        &self,
+        collection_id: Uuid,
+        is_spam: Option<bool>,
+    ) -> Result<CollectionSpamOverride, Error> {
+        self.put(
+            &format!("/api/v1/admin/collections/{}/spam", collection_id),
+            &SpamOverrideRequest { is_spam },
+        )
+        .await
+    }
+
+    pub async fn override_nft_spam(
+        &self,
+        mint: &str,
+        is_spam: Option<bool>,
+    ) -> Result<NftSpamOverride, Error> {
+        self.put(
+            &format!("/api/v1/admin/nfts/{}/spam", segment(mint)),
+            &SpamOverrideRequest { is_spam },
+        )
+        .await
+    }
+
+    pub fn blocklist(&self, query: &BlocklistQuery) -> Pages<BlocklistEntry> {
+        Pages::new(
+            self.clone(),
+            "/api/v1/admin/blocklist".to_string(),
+            query_pairs(query),
+            "entries",
+        )
+    }
+
+    pub async fn create_blocklist_entry(
+        &self,
+        req: &CreateBlocklistEntryRequest,
+    ) -> Result<BlocklistEntry, Error> {
+        let value: Value = self.post("/api/v1/admin/blocklist", req).await?;
+        field(value, "entry")
+    }
+
+    pub async fn delete_blocklist_entry(&self, id: Uuid) -> Result<(), Error> {
+        self.delete::<Value>(&format!("/api/v1/admin/blocklist/{}", id))
+            .await?;
+        Ok(())
+    }
+
+    /// Wallet and IP blocks in force; moderators' tokens are accepted too
+    pub fn access_blocks(&self, query: &BlocklistQuery) -> Pages<AccessBlock> {
+        Pages::new(
+            self.clone(),
+            "/api/v1/admin/access-blocks".to_string(),
+            query_pairs(query),
+            "blocks",
+        )
+    }
+
+    pub async fn create_access_block(
+        &self,
+        req: &CreateAccessBlockRequest,
+    ) -> Result<AccessBlock, Error> {
+        let value: Value = self.post("/api/v1/admin/access-blocks", req).await?;
+        field(value, "block")
+    }
+
+    pub async fn delete_access_block(&self, id: Uuid) -> Result<(), Error> {
+        self.delete::<Value>(&format!("/api/v1/admin/access-blocks/{}", id))
+            .await?;
+        Ok(())
+    }
+
+    /// Lift the automatic block on a wallet or IP (`kind` is `wallet` or `ip`)
+    pub async fn clear_automatic_block(&self, kind: &str, value: &str) -> Result<(), Error> {
+        let path = format!(
+            "/api/v1/admin/access-blocks/automatic/{}/{}",
+            segment(kind),
+            segment(value)
+        );
+        self.delete::<Value>(&path).await?;
+        Ok(())
+    }
+
+    /// Soft delete a row of `nfts`, `collections` or `listings`
+    pub async fn soft_delete_record(
+        &self,
+        table: &str,
+        id: Uuid,
+        req: &AdminChangeRequest,
+    ) -> Result<(), Error> {
+        let path = format!("/api/v1/admin/records/{}/{}/delete", segment(table), id);
+        self.post::<_, Value>(&path, req).await?;
+        Ok(())
+    }
+
+    pub async fn restore_record(
+        &self,
+        table: &str,
+        id: Uuid,
+        req: &AdminChangeRequest,
+    ) -> Result<(), Error> {
+        let path = format!("/api/v1/admin/records/{}/{}/restore", segment(table), id);
+        self.post::<_, Value>(&path, req).await?;
+        Ok(())
+    }
+
+    pub async fn record_history(&self, table: &str, id: Uuid) -> Result<Vec<RowHistory>, Error> {
+        let path = format!("/api/v1/admin/records/{}/{}/history", segment(table), id);
+        let value: Value = self.get(&path, &[]).await?;
+        field(value, "history")
+    }
+
+    /// Undo the change recorded by one history entry
+    pub async fn revert_history_entry(
+        &self,
+        history_id: i64,
+        req: &AdminChangeRequest,
+    ) -> Result<(), Error> {
+        let path = format!("/api/v1/admin/history/{}/revert", history_id);
+        self.post::<_, Value>(&path, req).await?;
+        Ok(())
+    }
+
+    pub async fn set_user_role(&self, wallet: &str, role: Role) -> Result<Role, Error> {
+        let path = format!("/api/v1/admin/users/{}/role", segment(wallet));
+        let value: Value = self.put(&path, &SetUserRoleRequest { role }).await?;
+        field(value, "role")
+    }
+}
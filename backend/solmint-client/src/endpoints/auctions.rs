@@ -0,0 +1,37 @@
+use super::user_path;
+use crate::{
+    client::{query_pairs, segment, Client},
+    error::Error,
+    pagination::Pages,
+    queries::AuctionQuery,
+    responses::AuctionDetail,
+    types::{ActiveBid, Auction},
+};
+
+impl Client {
+    /// Live auctions, soonest ending first, unless the query says otherwise
+    pub fn auctions(&self, query: &AuctionQuery) -> Pages<Auction> {
+        Pages::new(
+            self.clone(),
+            "/api/v1/auctions".to_string(),
+            query_pairs(query),
+            "auctions",
+        )
+    }
+
+    /// The latest run of the auction account with its bids
+    pub async fn auction(&self, address: &str) -> Result<AuctionDetail, Error> {
+        self.get(&format!("/api/v1/auctions/{}", segment(address)), &[])
+            .await
+    }
+
+    /// Unsettled auctions the wallet has bid on
+    pub fn wallet_bids(&self, wallet: &str) -> Pages<ActiveBid> {
+        Pages::new(
+            self.clone(),
+            format!("{}/bids", user_path(wallet)),
+            Vec::new(),
+            "bids",
+        )
+    }
+}
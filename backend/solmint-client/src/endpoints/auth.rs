@@ -0,0 +1,21 @@
+use crate::{
+    client::Client,
+    error::Error,
+    requests::{AuthChallengeRequest, AuthVerifyRequest},
+    responses::{AuthChallenge, AuthToken},
+};
+
+impl Client {
+    /// A message for `wallet` to sign to sign in
+    pub async fn auth_challenge(&self, wallet: &str) -> Result<AuthChallenge, Error> {
+        let req = AuthChallengeRequest {
+            wallet: wallet.to_string(),
+        };
+        self.post("/api/auth/challenge", &req).await
+    }
+
+    /// Exchange the wallet's signature of a challenge for a token
+    pub async fn auth_verify(&self, req: &AuthVerifyRequest) -> Result<AuthToken, Error> {
+        self.post("/api/auth/verify", req).await
+    }
+}
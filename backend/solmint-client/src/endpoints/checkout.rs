@@ -0,0 +1,37 @@
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::field;
+use crate::{
+    client::{segment, Client},
+    error::Error,
+    pagination::Pages,
+    requests::CreateCheckoutOrderRequest,
+    responses::CheckoutOrderDetail,
+    types::CheckoutOrder,
+};
+
+impl Client {
+    /// Start a card checkout; send the buyer to the order's `checkout_url`
+    pub async fn create_checkout_order(
+        &self,
+        req: &CreateCheckoutOrderRequest,
+    ) -> Result<CheckoutOrder, Error> {
+        let value: Value = self.post("/api/v1/checkout/orders", req).await?;
+        field(value, "order")
+    }
+
+    pub async fn checkout_order(&self, id: Uuid) -> Result<CheckoutOrderDetail, Error> {
+        self.get(&format!("/api/v1/checkout/orders/{}", id), &[])
+            .await
+    }
+
+    pub fn user_checkout_orders(&self, wallet: &str) -> Pages<CheckoutOrder> {
+        Pages::new(
+            self.clone(),
+            format!("/api/v1/users/{}/checkout-orders", segment(wallet)),
+            Vec::new(),
+            "orders",
+        )
+    }
+}
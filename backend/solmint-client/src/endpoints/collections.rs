@@ -0,0 +1,117 @@
+use reqwest::Method;
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::field;
+use crate::{
+    client::{query_pairs, Client},
+    error::Error,
+    pagination::Pages,
+    queries::{CandleQuery, CollectionListQuery, HoldersHistoryQuery, ListingChurnQuery},
+    requests::HolderSnapshotRequest,
+    responses::{Candles, HolderSnapshotResponse, HoldersHistory},
+    types::{Collection, CollectionStats, CollectionTrait, ListingChurn},
+};
+
+impl Client {
+    pub fn collections(&self, query: &CollectionListQuery) -> Pages<Collection> {
+        Pages::new(
+            self.clone(),
+            "/api/v1/collections".to_string(),
+            query_pairs(query),
+            "collections",
+        )
+    }
+
+    /// Running floor, listing and volume aggregates
+    pub async fn collection_stats(&self, collection_id: Uuid) -> Result<CollectionStats, Error> {
+        self.get(&format!("/api/v1/collections/{}/stats", collection_id), &[])
+            .await
+    }
+
+    /// Every trait value with its count, frequency and floor
+    pub async fn collection_traits(
+        &self,
+        collection_id: Uuid,
+    ) -> Result<Vec<CollectionTrait>, Error> {
+        let value: Value = self
+            .get(
+                &format!("/api/v1/collections/{}/traits", collection_id),
+                &[],
+            )
+            .await?;
+        field(value, "traits")
+    }
+
+    pub async fn holders_history(
+        &self,
+        collection_id: Uuid,
+        query: &HoldersHistoryQuery,
+    ) -> Result<HoldersHistory, Error> {
+        self.get(
+            &format!("/api/v1/collections/{}/holders-history", collection_id),
+            &query_pairs(query),
+        )
+        .await
+    }
+
+    /// Current holders, with Merkle proofs when `merkle` is set
+    pub async fn holder_snapshot(
+        &self,
+        collection_id: Uuid,
+        merkle: bool,
+    ) -> Result<HolderSnapshotResponse, Error> {
+        let req = HolderSnapshotRequest {
+            format: None,
+            merkle,
+        };
+        self.post(
+            &format!("/api/v1/collections/{}/snapshot", collection_id),
+            &req,
+        )
+        .await
+    }
+
+    /// The holder snapshot as CSV
+    pub async fn holder_snapshot_csv(
+        &self,
+        collection_id: Uuid,
+        merkle: bool,
+    ) -> Result<String, Error> {
+        let req = HolderSnapshotRequest {
+            format: Some("csv".to_string()),
+            merkle,
+        };
+        let path = format!("/api/v1/collections/{}/snapshot", collection_id);
+        let response = self
+            .send(self.request(Method::POST, &path).json(&req))
+            .await?;
+        Ok(response.text().await?)
+    }
+
+    pub async fn listing_churn(
+        &self,
+        collection_id: Uuid,
+        query: &ListingChurnQuery,
+    ) -> Result<ListingChurn, Error> {
+        let value: Value = self
+            .get(
+                &format!("/api/v1/collections/{}/churn", collection_id),
+                &query_pairs(query),
+            )
+            .await?;
+        field(value, "churn")
+    }
+
+    pub async fn candles(
+        &self,
+        collection_id: Uuid,
+        query: &CandleQuery,
+    ) -> Result<Candles, Error> {
+        self.get(
+            &format!("/api/v1/collections/{}/candles", collection_id),
+            &query_pairs(query),
+        )
+        .await
+    }
+}
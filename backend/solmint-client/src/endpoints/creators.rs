@@ -0,0 +1,42 @@
+use serde_json::Value;
+
+use super::field;
+use crate::{
+    client::{query_pairs, segment, Client},
+    error::Error,
+    queries::RoyaltyReportQuery,
+    responses::RoyaltyReport,
+    types::{Allowlist, CreatorDashboard},
+};
+
+impl Client {
+    pub async fn creator_royalties(
+        &self,
+        wallet: &str,
+        query: &RoyaltyReportQuery,
+    ) -> Result<RoyaltyReport, Error> {
+        self.get(
+            &format!("/api/v1/creators/{}/royalties", segment(wallet)),
+            &query_pairs(query),
+        )
+        .await
+    }
+
+    pub async fn creator_dashboard(&self, wallet: &str) -> Result<CreatorDashboard, Error> {
+        self.get(
+            &format!("/api/v1/creators/{}/dashboard", segment(wallet)),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn creator_allowlists(&self, wallet: &str) -> Result<Vec<Allowlist>, Error> {
+        let value: Value = self
+            .get(
+                &format!("/api/v1/creators/{}/allowlists", segment(wallet)),
+                &[],
+            )
+            .await?;
+        field(value, "allowlists")
+    }
+}
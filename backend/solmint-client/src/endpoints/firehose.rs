@@ -0,0 +1,14 @@
+use crate::{
+    client::{query_pairs, Client},
+    error::Error,
+    queries::FirehoseQuery,
+    responses::FirehosePage,
+};
+
+impl Client {
+    /// Activities after `query.since_id`; resume from `next_since_id`
+    pub async fn firehose(&self, query: &FirehoseQuery) -> Result<FirehosePage, Error> {
+        self.get("/api/v1/firehose/activities", &query_pairs(query))
+            .await
+    }
+}
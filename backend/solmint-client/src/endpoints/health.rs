@@ -0,0 +1,21 @@
+use reqwest::Method;
+
+use crate::{
+    client::Client,
+    error::Error,
+    responses::{HealthStatus, Readiness},
+};
+
+impl Client {
+    pub async fn health(&self) -> Result<HealthStatus, Error> {
+        self.get("/health", &[]).await
+    }
+
+    /// Readiness of the API's background tasks. Not ready is a 503 with the
+    /// same body, so it's returned rather than treated as an error or retried.
+    pub async fn readiness(&self) -> Result<Readiness, Error> {
+        let response = self.request(Method::GET, "/ready").send().await?;
+        let body = response.bytes().await?;
+        serde_json::from_slice(&body).map_err(Error::Decode)
+    }
+}
@@ -0,0 +1,63 @@
+use reqwest::Method;
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::field;
+use crate::{
+    client::Client,
+    error::Error,
+    requests::{CreateAlertIntegrationRequest, UpdateAlertIntegrationRequest},
+    responses::CreatedIntegration,
+    types::AlertIntegration,
+};
+
+impl Client {
+    /// Connect a Discord webhook or Telegram chat to a collection's activity.
+    /// Keep the returned `manage_token`; the other integration calls need it.
+    pub async fn create_integration(
+        &self,
+        collection_id: Uuid,
+        req: &CreateAlertIntegrationRequest,
+    ) -> Result<CreatedIntegration, Error> {
+        self.post(
+            &format!("/api/v1/collections/{}/integrations", collection_id),
+            req,
+        )
+        .await
+    }
+
+    pub async fn integration(
+        &self,
+        id: Uuid,
+        manage_token: &str,
+    ) -> Result<AlertIntegration, Error> {
+        let path = format!("/api/v1/integrations/{}", id);
+        let value: Value = self
+            .send_json(self.request_with_token(Method::GET, &path, manage_token))
+            .await?;
+        field(value, "integration")
+    }
+
+    pub async fn update_integration(
+        &self,
+        id: Uuid,
+        manage_token: &str,
+        req: &UpdateAlertIntegrationRequest,
+    ) -> Result<AlertIntegration, Error> {
+        let path = format!("/api/v1/integrations/{}", id);
+        let value: Value = self
+            .send_json(
+                self.request_with_token(Method::PUT, &path, manage_token)
+                    .json(req),
+            )
+            .await?;
+        field(value, "integration")
+    }
+
+    pub async fn delete_integration(&self, id: Uuid, manage_token: &str) -> Result<(), Error> {
+        let path = format!("/api/v1/integrations/{}", id);
+        self.send(self.request_with_token(Method::DELETE, &path, manage_token))
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::field;
+use crate::{
+    client::{query_pairs, Client},
+    error::Error,
+    pagination::Pages,
+    queries::LaunchListQuery,
+    requests::{BuildMintRequest, RegisterLaunchRequest, UpdateLaunchPhasesRequest},
+    responses::{LaunchDetail, MintTransaction, RegisteredLaunch},
+    types::{Launch, LaunchPhase},
+};
+
+impl Client {
+    /// Register a launch; needs the creator's [`Client::auth_token`]
+    pub async fn register_launch(
+        &self,
+        req: &RegisterLaunchRequest,
+    ) -> Result<RegisteredLaunch, Error> {
+        self.post("/api/v1/launches", req).await
+    }
+
+    pub fn launches(&self, query: &LaunchListQuery) -> Pages<Launch> {
+        Pages::new(
+            self.clone(),
+            "/api/v1/launches".to_string(),
+            query_pairs(query),
+            "launches",
+        )
+    }
+
+    /// A launch with its phases, live supply and status
+    pub async fn launch(&self, id: Uuid) -> Result<LaunchDetail, Error> {
+        self.get(&format!("/api/v1/launches/{}", id), &[]).await
+    }
+
+    /// Replace a launch's phases; needs the creator's [`Client::auth_token`]
+    pub async fn update_launch_phases(
+        &self,
+        id: Uuid,
+        req: &UpdateLaunchPhasesRequest,
+    ) -> Result<Vec<LaunchPhase>, Error> {
+        let value: Value = self
+            .put(&format!("/api/v1/launches/{}/phases", id), req)
+            .await?;
+        field(value, "phases")
+    }
+
+    /// A mint transaction for the live phase, for the wallet to sign and
+    /// submit; needs that wallet's [`Client::auth_token`]
+    pub async fn build_mint_transaction(
+        &self,
+        id: Uuid,
+        req: &BuildMintRequest,
+    ) -> Result<MintTransaction, Error> {
+        self.post(&format!("/api/v1/launches/{}/mint", id), req)
+            .await
+    }
+}
@@ -0,0 +1,101 @@
+use reqwest::Method;
+use serde_json::Value;
+
+use super::field;
+use crate::{
+    client::{query_pairs, segment, Client},
+    error::Error,
+    pagination::Pages,
+    queries::ImportAllowlistQuery,
+    requests::{AddAllowlistEntriesRequest, RemoveAllowlistEntriesRequest, UploadAllowlistRequest},
+    types::{Allowlist, AllowlistEntry, AllowlistProof},
+};
+
+fn allowlist_path(config: &str) -> String {
+    format!("/api/v1/mint/{}/allowlist", segment(config))
+}
+
+impl Client {
+    pub async fn allowlist(&self, config: &str) -> Result<Allowlist, Error> {
+        let value: Value = self.get(&allowlist_path(config), &[]).await?;
+        field(value, "allowlist")
+    }
+
+    /// Replace the allowlist of a mint config. This and the other allowlist
+    /// changes need the creator's [`Client::auth_token`].
+    pub async fn upload_allowlist(
+        &self,
+        config: &str,
+        req: &UploadAllowlistRequest,
+    ) -> Result<Allowlist, Error> {
+        let value: Value = self.put(&allowlist_path(config), req).await?;
+        field(value, "allowlist")
+    }
+
+    pub async fn delete_allowlist(&self, config: &str) -> Result<(), Error> {
+        let request = self.request(Method::DELETE, &allowlist_path(config));
+        self.send(request).await?;
+        Ok(())
+    }
+
+    /// Entries in wallet order
+    pub fn allowlist_entries(&self, config: &str) -> Pages<AllowlistEntry> {
+        Pages::new(
+            self.clone(),
+            format!("{}/entries", allowlist_path(config)),
+            Vec::new(),
+            "entries",
+        )
+    }
+
+    pub async fn add_allowlist_entries(
+        &self,
+        config: &str,
+        req: &AddAllowlistEntriesRequest,
+    ) -> Result<Allowlist, Error> {
+        let path = format!("{}/entries", allowlist_path(config));
+        let value: Value = self.post(&path, req).await?;
+        field(value, "allowlist")
+    }
+
+    pub async fn remove_allowlist_entries(
+        &self,
+        config: &str,
+        req: &RemoveAllowlistEntriesRequest,
+    ) -> Result<Allowlist, Error> {
+        let path = format!("{}/entries", allowlist_path(config));
+        let value: Value = self
+            .send_json(self.request(Method::DELETE, &path).json(req))
+            .await?;
+        field(value, "allowlist")
+    }
+
+    /// Import `wallet_address[,allocation]` CSV rows
+    pub async fn import_allowlist(
+        &self,
+        config: &str,
+        query: &ImportAllowlistQuery,
+        csv: impl Into<String>,
+    ) -> Result<Allowlist, Error> {
+        let path = format!("{}/import", allowlist_path(config));
+        let request = self
+            .request(Method::POST, &path)
+            .query(&query_pairs(query))
+            .header(reqwest::header::CONTENT_TYPE, "text/csv")
+            .body(csv.into());
+        let value: Value = self.send_json(request).await?;
+        field(value, "allowlist")
+    }
+
+    pub async fn allowlist_proof(
+        &self,
+        config: &str,
+        wallet: &str,
+    ) -> Result<AllowlistProof, Error> {
+        self.get(
+            &format!("/api/v1/mint/{}/proof/{}", segment(config), segment(wallet)),
+            &[],
+        )
+        .await
+    }
+}
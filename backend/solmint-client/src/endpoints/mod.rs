@@ -0,0 +1,38 @@
+//! One module per handler module of the backend, each adding its endpoints
+//! to [`Client`](crate::Client)
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{client::segment, error::Error};
+
+mod activities;
+mod admin;
+mod auctions;
+mod auth;
+mod checkout;
+mod collections;
+mod creators;
+mod firehose;
+mod health;
+mod integrations;
+mod launches;
+mod mint;
+mod nfts;
+mod notifications;
+mod offers;
+mod points;
+mod stats;
+mod upload;
+mod users;
+mod webhooks;
+
+/// Take `key` out of a `{ "<key>": ... }` response
+fn field<T: DeserializeOwned>(mut value: Value, key: &str) -> Result<T, Error> {
+    let field = value.get_mut(key).map(Value::take).unwrap_or(Value::Null);
+    serde_json::from_value(field).map_err(Error::Decode)
+}
+
+fn user_path(wallet: &str) -> String {
+    format!("/api/v1/users/{}", segment(wallet))
+}
@@ -0,0 +1,89 @@
+use reqwest::Method;
+use serde_json::Value;
+
+use super::field;
+use crate::{
+    client::{query_pairs, segment, Client},
+    error::Error,
+    pagination::Pages,
+    queries::{CandleQuery, NftListQuery},
+    requests::{BuyListingRequest, MintNftRequest, SendTransactionRequest},
+    responses::{
+        BuyListingResponse, MetadataRefresh, MintNftResponse, NftCandles, NftEstimate, NftProof,
+        SendTransactionResponse,
+    },
+    types::Nft,
+};
+
+impl Client {
+    pub fn nfts(&self, query: &NftListQuery) -> Pages<Nft> {
+        Pages::new(
+            self.clone(),
+            "/api/v1/nfts".to_string(),
+            query_pairs(query),
+            "nfts",
+        )
+    }
+
+    pub async fn nft(&self, mint: &str) -> Result<Nft, Error> {
+        let value: Value = self
+            .get(&format!("/api/v1/nfts/{}", segment(mint)), &[])
+            .await?;
+        field(value, "nft")
+    }
+
+    pub async fn nft_estimate(&self, mint: &str) -> Result<NftEstimate, Error> {
+        self.get(&format!("/api/v1/nfts/{}/estimate", segment(mint)), &[])
+            .await
+    }
+
+    /// OHLCV candles of the NFT's sales
+    pub async fn nft_price_history(
+        &self,
+        mint: &str,
+        query: &CandleQuery,
+    ) -> Result<NftCandles, Error> {
+        self.get(
+            &format!("/api/v1/nfts/{}/price-history", segment(mint)),
+            &query_pairs(query),
+        )
+        .await
+    }
+
+    /// Re-read the NFT's metadata now; at most once every few minutes per NFT
+    pub async fn refresh_nft_metadata(&self, mint: &str) -> Result<MetadataRefresh, Error> {
+        let path = format!("/api/v1/nfts/{}/refresh-metadata", segment(mint));
+        self.send_json(self.request(Method::POST, &path)).await
+    }
+
+    /// Merkle proof of a compressed NFT
+    pub async fn nft_proof(&self, mint: &str) -> Result<NftProof, Error> {
+        self.get(&format!("/api/v1/nfts/{}/proof", segment(mint)), &[])
+            .await
+    }
+
+    /// Build an unsigned mint transaction for the creator to sign
+    pub async fn mint_nft(&self, req: &MintNftRequest) -> Result<MintNftResponse, Error> {
+        self.post("/api/nft/mint", req).await
+    }
+
+    /// Build an unsigned transaction buying a listing for the buyer to sign
+    pub async fn buy_listing(
+        &self,
+        listing_address: &str,
+        req: &BuyListingRequest,
+    ) -> Result<BuyListingResponse, Error> {
+        self.post(
+            &format!("/api/listing/{}/buy", segment(listing_address)),
+            req,
+        )
+        .await
+    }
+
+    pub async fn send_transaction(
+        &self,
+        req: &SendTransactionRequest,
+    ) -> Result<SendTransactionResponse, Error> {
+        self.post("/api/nft/send-transaction", req).await
+    }
+}
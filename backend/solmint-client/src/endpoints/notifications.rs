@@ -0,0 +1,126 @@
+use reqwest::Method;
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::{field, user_path};
+use crate::{
+    client::{query_pairs, Client},
+    error::Error,
+    pagination::Pages,
+    queries::NotificationListQuery,
+    requests::{
+        ConfirmEmailRequest, NotificationPreferencesRequest, RegisterDeviceRequest,
+        WatchlistRequest,
+    },
+    responses::EmailConfirmation,
+    types::{Notification, NotificationPreference, PushDevice, WatchlistEntry},
+};
+
+impl Client {
+    /// Newest first, paged by cursor
+    pub fn notifications(
+        &self,
+        wallet: &str,
+        query: &NotificationListQuery,
+    ) -> Pages<Notification> {
+        Pages::new(
+            self.clone(),
+            format!("{}/notifications", user_path(wallet)),
+            query_pairs(query),
+            "notifications",
+        )
+    }
+
+    /// Mark every notification read; returns how many were unread
+    pub async fn mark_notifications_read(&self, wallet: &str) -> Result<u64, Error> {
+        let path = format!("{}/notifications/read", user_path(wallet));
+        let value: Value = self.send_json(self.request(Method::POST, &path)).await?;
+        field(value, "updated")
+    }
+
+    pub async fn notification_preferences(
+        &self,
+        wallet: &str,
+    ) -> Result<Vec<NotificationPreference>, Error> {
+        let path = format!("{}/notification-preferences", user_path(wallet));
+        let value: Value = self.get(&path, &[]).await?;
+        field(value, "preferences")
+    }
+
+    pub async fn update_notification_preferences(
+        &self,
+        wallet: &str,
+        req: &NotificationPreferencesRequest,
+    ) -> Result<Vec<NotificationPreference>, Error> {
+        let path = format!("{}/notification-preferences", user_path(wallet));
+        let value: Value = self.put(&path, req).await?;
+        field(value, "preferences")
+    }
+
+    pub async fn watchlist(&self, wallet: &str) -> Result<Vec<WatchlistEntry>, Error> {
+        let value: Value = self
+            .get(&format!("{}/watchlist", user_path(wallet)), &[])
+            .await?;
+        field(value, "watchlist")
+    }
+
+    /// Watch a collection, or change its floor alert
+    pub async fn upsert_watchlist(
+        &self,
+        wallet: &str,
+        req: &WatchlistRequest,
+    ) -> Result<(), Error> {
+        self.post::<_, Value>(&format!("{}/watchlist", user_path(wallet)), req)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn remove_watchlist(&self, wallet: &str, collection_id: Uuid) -> Result<(), Error> {
+        self.delete::<Value>(&format!(
+            "{}/watchlist/{}",
+            user_path(wallet),
+            collection_id
+        ))
+        .await?;
+        Ok(())
+    }
+
+    pub async fn devices(&self, wallet: &str) -> Result<Vec<PushDevice>, Error> {
+        let value: Value = self
+            .get(&format!("{}/devices", user_path(wallet)), &[])
+            .await?;
+        field(value, "devices")
+    }
+
+    /// Register or refresh a push token; apps should call this on every launch
+    pub async fn register_device(
+        &self,
+        wallet: &str,
+        req: &RegisterDeviceRequest,
+    ) -> Result<PushDevice, Error> {
+        let value: Value = self
+            .post(&format!("{}/devices", user_path(wallet)), req)
+            .await?;
+        field(value, "device")
+    }
+
+    pub async fn remove_device(&self, wallet: &str, device_id: Uuid) -> Result<(), Error> {
+        self.delete::<Value>(&format!("{}/devices/{}", user_path(wallet), device_id))
+            .await?;
+        Ok(())
+    }
+
+    /// Email a confirmation link to the address on the user's profile
+    pub async fn send_email_verification(&self, wallet: &str) -> Result<(), Error> {
+        let path = format!("{}/email/verify", user_path(wallet));
+        self.send(self.request(Method::POST, &path)).await?;
+        Ok(())
+    }
+
+    pub async fn confirm_email(
+        &self,
+        req: &ConfirmEmailRequest,
+    ) -> Result<EmailConfirmation, Error> {
+        self.post("/api/v1/email/confirm", req).await
+    }
+}
@@ -0,0 +1,69 @@
+use reqwest::Method;
+use uuid::Uuid;
+
+use crate::{
+    client::{query_pairs, segment, Client},
+    error::Error,
+    pagination::Pages,
+    queries::OfferQuery,
+    requests::{AcceptOfferRequest, ComputeBudgetOverride, MakeOfferRequest},
+    responses::OfferTransaction,
+    types::{EscrowBalance, Offer},
+};
+
+impl Client {
+    /// Offers on an NFT, including collection offers on its collection,
+    /// highest first
+    pub fn nft_offers(&self, mint: &str, query: &OfferQuery) -> Pages<Offer> {
+        Pages::new(
+            self.clone(),
+            format!("/api/v1/nfts/{}/offers", segment(mint)),
+            query_pairs(query),
+            "offers",
+        )
+    }
+
+    /// Offers a wallet made, or received with `direction: "received"`
+    pub fn wallet_offers(&self, wallet: &str, query: &OfferQuery) -> Pages<Offer> {
+        Pages::new(
+            self.clone(),
+            format!("/api/v1/users/{}/offers", segment(wallet)),
+            query_pairs(query),
+            "offers",
+        )
+    }
+
+    /// Lamports a wallet has locked in open offers and leading auction bids,
+    /// read from chain and cached for 30 seconds
+    pub async fn wallet_escrow(&self, wallet: &str) -> Result<EscrowBalance, Error> {
+        self.get(&format!("/api/v1/users/{}/escrow", segment(wallet)), &[])
+            .await
+    }
+
+    /// Record an offer and build its unsigned transaction for the bidder to sign
+    pub async fn make_offer(&self, req: &MakeOfferRequest) -> Result<OfferTransaction, Error> {
+        self.post("/api/offers", req).await
+    }
+
+    /// Build an unsigned transaction accepting an offer for the seller to sign
+    pub async fn accept_offer(
+        &self,
+        id: Uuid,
+        req: &AcceptOfferRequest,
+    ) -> Result<OfferTransaction, Error> {
+        self.post(&format!("/api/offers/{}/accept", id), req).await
+    }
+
+    /// Build an unsigned transaction cancelling an offer for the bidder to sign
+    pub async fn cancel_offer(
+        &self,
+        id: Uuid,
+        compute_budget: &ComputeBudgetOverride,
+    ) -> Result<OfferTransaction, Error> {
+        let path = format!("/api/offers/{}", id);
+        let request = self
+            .request(Method::DELETE, &path)
+            .query(&query_pairs(compute_budget));
+        self.send_json(request).await
+    }
+}
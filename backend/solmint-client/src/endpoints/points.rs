@@ -0,0 +1,40 @@
+use uuid::Uuid;
+
+use crate::{
+    client::{query_pairs, segment, Client},
+    error::Error,
+    pagination::Pages,
+    queries::PointsQuery,
+    responses::UserPoints,
+    types::LeaderboardEntry,
+};
+
+impl Client {
+    /// A wallet's points, rank and recent entries in a season
+    pub async fn user_points(
+        &self,
+        wallet: &str,
+        query: &PointsQuery,
+    ) -> Result<UserPoints, Error> {
+        self.get(
+            &format!("/api/v1/users/{}/points", segment(wallet)),
+            &query_pairs(query),
+        )
+        .await
+    }
+
+    /// Leaderboard of a season, the current one by default. Each page's
+    /// `extra["season"]` holds the season.
+    pub fn leaderboard(&self, season: Option<Uuid>) -> Pages<LeaderboardEntry> {
+        let query = PointsQuery {
+            season,
+            limit: None,
+        };
+        Pages::new(
+            self.clone(),
+            "/api/v1/points/leaderboard".to_string(),
+            query_pairs(&query),
+            "leaderboard",
+        )
+    }
+}
@@ -0,0 +1,17 @@
+use crate::{
+    client::{query_pairs, Client},
+    error::Error,
+    queries::DailyStatsQuery,
+    responses::DailyStatsResponse,
+    types::Dashboard,
+};
+
+impl Client {
+    pub async fn daily_stats(&self, query: &DailyStatsQuery) -> Result<DailyStatsResponse, Error> {
+        self.get("/api/v1/stats/daily", &query_pairs(query)).await
+    }
+
+    pub async fn dashboard(&self) -> Result<Dashboard, Error> {
+        self.get("/api/v1/stats/dashboard", &[]).await
+    }
+}
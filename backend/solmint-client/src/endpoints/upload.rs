@@ -0,0 +1,23 @@
+use crate::{
+    client::Client,
+    error::Error,
+    requests::{MetadataUploadRequest, PresignedUrlRequest},
+    responses::{MetadataUploadResponse, PresignedUrlResponse},
+};
+
+impl Client {
+    /// A presigned URL to PUT an image to
+    pub async fn presigned_upload_url(
+        &self,
+        req: &PresignedUrlRequest,
+    ) -> Result<PresignedUrlResponse, Error> {
+        self.post("/api/upload/presigned", req).await
+    }
+
+    pub async fn upload_metadata(
+        &self,
+        req: &MetadataUploadRequest,
+    ) -> Result<MetadataUploadResponse, Error> {
+        self.post("/api/upload/metadata", req).await
+    }
+}
@@ -0,0 +1,158 @@
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::{field, user_path};
+use crate::{
+    client::{query_pairs, segment, Client},
+    error::Error,
+    pagination::Pages,
+    queries::{TaxReportQuery, TwitterChallengeQuery},
+    requests::{
+        AddFavoriteCollectionRequest, AddFavoriteRequest, UpdateUserRequest, VerifyTwitterRequest,
+    },
+    responses::{TwitterChallenge, UserProfile, UsernameAvailability},
+    types::{Collection, Nft, TaxReport, User},
+};
+
+impl Client {
+    /// `wallet` may also be a `.sol` name
+    pub async fn user(&self, wallet: &str) -> Result<UserProfile, Error> {
+        self.get(&user_path(wallet), &[]).await
+    }
+
+    /// Look a profile up by username, ignoring case
+    pub async fn profile(&self, username: &str) -> Result<UserProfile, Error> {
+        self.get(&format!("/api/v1/profiles/{}", segment(username)), &[])
+            .await
+    }
+
+    pub async fn username_availability(
+        &self,
+        username: &str,
+    ) -> Result<UsernameAvailability, Error> {
+        self.get(
+            &format!("/api/v1/usernames/{}/availability", segment(username)),
+            &[],
+        )
+        .await
+    }
+
+    /// The message `wallet` signs before tweeting the signature from `handle`
+    pub async fn twitter_challenge(
+        &self,
+        wallet: &str,
+        handle: &str,
+    ) -> Result<TwitterChallenge, Error> {
+        let query = TwitterChallengeQuery {
+            handle: handle.to_string(),
+        };
+        self.get(
+            &format!("{}/twitter/challenge", user_path(wallet)),
+            &query_pairs(&query),
+        )
+        .await
+    }
+
+    /// Verify the handle that posted `tweet_url`
+    pub async fn verify_twitter(&self, wallet: &str, tweet_url: &str) -> Result<User, Error> {
+        let req = VerifyTwitterRequest {
+            tweet_url: tweet_url.to_string(),
+        };
+        let value: Value = self
+            .post(&format!("{}/twitter/verify", user_path(wallet)), &req)
+            .await?;
+        field(value, "user")
+    }
+
+    /// Create the user's profile, or update it if it exists
+    pub async fn upsert_user(&self, wallet: &str, req: &UpdateUserRequest) -> Result<User, Error> {
+        let value: Value = self.post(&user_path(wallet), req).await?;
+        field(value, "user")
+    }
+
+    pub fn favorites(&self, wallet: &str) -> Pages<Nft> {
+        Pages::new(
+            self.clone(),
+            format!("{}/favorites", user_path(wallet)),
+            Vec::new(),
+            "favorites",
+        )
+    }
+
+    pub async fn add_favorite(&self, wallet: &str, nft_mint: &str) -> Result<(), Error> {
+        let req = AddFavoriteRequest {
+            nft_mint: nft_mint.to_string(),
+        };
+        self.post::<_, Value>(&format!("{}/favorites", user_path(wallet)), &req)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn remove_favorite(&self, wallet: &str, nft_mint: &str) -> Result<(), Error> {
+        self.delete::<Value>(&format!(
+            "{}/favorites/{}",
+            user_path(wallet),
+            segment(nft_mint)
+        ))
+        .await?;
+        Ok(())
+    }
+
+    pub fn favorite_collections(&self, wallet: &str) -> Pages<Collection> {
+        Pages::new(
+            self.clone(),
+            format!("{}/favorites/collections", user_path(wallet)),
+            Vec::new(),
+            "collections",
+        )
+    }
+
+    pub async fn add_favorite_collection(
+        &self,
+        wallet: &str,
+        collection_id: Uuid,
+    ) -> Result<(), Error> {
+        let req = AddFavoriteCollectionRequest { collection_id };
+        self.post::<_, Value>(
+            &format!("{}/favorites/collections", user_path(wallet)),
+            &req,
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn remove_favorite_collection(
+        &self,
+        wallet: &str,
+        collection_id: Uuid,
+    ) -> Result<(), Error> {
+        self.delete::<Value>(&format!(
+            "{}/favorites/collections/{}",
+            user_path(wallet),
+            collection_id
+        ))
+        .await?;
+        Ok(())
+    }
+
+    pub async fn tax_report(&self, wallet: &str, year: i32) -> Result<TaxReport, Error> {
+        let query = TaxReportQuery { year, format: None };
+        self.get(
+            &format!("{}/tax-report", user_path(wallet)),
+            &query_pairs(&query),
+        )
+        .await
+    }
+
+    pub async fn tax_report_csv(&self, wallet: &str, year: i32) -> Result<String, Error> {
+        let query = TaxReportQuery {
+            year,
+            format: Some("csv".to_string()),
+        };
+        self.get_text(
+            &format!("{}/tax-report", user_path(wallet)),
+            &query_pairs(&query),
+        )
+        .await
+    }
+}
@@ -0,0 +1,83 @@
+use reqwest::Method;
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::{field, user_path};
+use crate::{
+    client::{query_pairs, Client},
+    error::Error,
+    pagination::Pages,
+    queries::WebhookDeliveryQuery,
+    requests::{CreateWebhookRequest, UpdateWebhookRequest},
+    responses::CreatedWebhook,
+    types::{Webhook, WebhookDelivery},
+};
+
+impl Client {
+    pub async fn webhooks(&self, wallet: &str) -> Result<Vec<Webhook>, Error> {
+        let value: Value = self
+            .get(&format!("{}/webhooks", user_path(wallet)), &[])
+            .await?;
+        field(value, "webhooks")
+    }
+
+    /// Register a webhook. Keep the returned `secret`; it's needed to verify
+    /// deliveries and isn't shown again.
+    pub async fn create_webhook(
+        &self,
+        wallet: &str,
+        req: &CreateWebhookRequest,
+    ) -> Result<CreatedWebhook, Error> {
+        self.post(&format!("{}/webhooks", user_path(wallet)), req)
+            .await
+    }
+
+    pub async fn update_webhook(
+        &self,
+        wallet: &str,
+        id: Uuid,
+        req: &UpdateWebhookRequest,
+    ) -> Result<Webhook, Error> {
+        let path = format!("{}/webhooks/{}", user_path(wallet), id);
+        let value: Value = self.put(&path, req).await?;
+        field(value, "webhook")
+    }
+
+    pub async fn delete_webhook(&self, wallet: &str, id: Uuid) -> Result<(), Error> {
+        self.delete::<Value>(&format!("{}/webhooks/{}", user_path(wallet), id))
+            .await?;
+        Ok(())
+    }
+
+    /// The webhook's delivery log, newest first, paged by cursor
+    pub fn webhook_deliveries(
+        &self,
+        wallet: &str,
+        id: Uuid,
+        query: &WebhookDeliveryQuery,
+    ) -> Pages<WebhookDelivery> {
+        Pages::new(
+            self.clone(),
+            format!("{}/webhooks/{}/deliveries", user_path(wallet), id),
+            query_pairs(query),
+            "deliveries",
+        )
+    }
+
+    /// Send a delivered or failed delivery again
+    pub async fn redeliver_webhook_delivery(
+        &self,
+        wallet: &str,
+        id: Uuid,
+        delivery_id: i64,
+    ) -> Result<WebhookDelivery, Error> {
+        let path = format!(
+            "{}/webhooks/{}/deliveries/{}/redeliver",
+            user_path(wallet),
+            id,
+            delivery_id
+        );
+        let value: Value = self.send_json(self.request(Method::POST, &path)).await?;
+        field(value, "delivery")
+    }
+}
@@ -0,0 +1,52 @@
+use serde::Deserialize;
+
+/// The `application/problem+json` body the API returns for every error
+#[derive(Debug, Clone, Deserialize, thiserror::Error)]
+#[error("{status} {code}: {detail}")]
+pub struct ApiError {
+    /// `urn:solmint:error:<CODE>`
+    #[serde(rename = "type")]
+    pub type_uri: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    /// Stable error code, e.g. `NOT_FOUND` or `LISTING_NOT_ACTIVE`
+    pub code: String,
+    /// Also sent as the `x-request-id` response header; quote it in bug reports
+    pub request_id: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Api(ApiError),
+
+    /// An error status without a problem body, e.g. from a proxy in front of the API
+    #[error("HTTP {status}: {body}")]
+    Status { status: u16, body: String },
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Unexpected response body: {0}")]
+    Decode(serde_json::Error),
+}
+
+impl Error {
+    /// The stable error code, for errors returned by the API
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Error::Api(error) => Some(&error.code),
+            _ => None,
+        }
+    }
+
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::Api(error) => Some(error.status),
+            Error::Status { status, .. } => Some(*status),
+            Error::Http(error) => error.status().map(|status| status.as_u16()),
+            Error::Decode(_) => None,
+        }
+    }
+}
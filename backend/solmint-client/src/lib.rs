@@ -0,0 +1,48 @@
+//! Typed client for the SolMint REST API.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), solmint_client::Error> {
+//! use solmint_client::{Client, NftListQuery};
+//!
+//! let client = Client::new("http://localhost:8080");
+//! let query = NftListQuery {
+//!     sort_by: Some("price".to_string()),
+//!     ..Default::default()
+//! };
+//! let mut pages = client.nfts(&query);
+//! while let Some(page) = pages.next_page().await? {
+//!     for nft in page.items {
+//!         println!("{} {}", nft.mint_address, nft.name);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Request bodies and [`PageInfo`] are shared with the backend, which builds
+//! this crate with `default-features = false` to get the types without the
+//! HTTP client.
+
+pub mod pagination;
+pub mod queries;
+pub mod requests;
+pub mod responses;
+pub mod types;
+
+#[cfg(feature = "client")]
+mod client;
+#[cfg(feature = "client")]
+mod endpoints;
+#[cfg(feature = "client")]
+mod error;
+
+pub use pagination::*;
+pub use queries::*;
+pub use requests::*;
+pub use responses::*;
+pub use types::*;
+
+#[cfg(feature = "client")]
+pub use client::{Client, ClientBuilder, RetryPolicy};
+#[cfg(feature = "client")]
+pub use error::{ApiError, Error};
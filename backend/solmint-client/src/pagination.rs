@@ -0,0 +1,143 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[cfg(feature = "client")]
+use crate::{client::Client, error::Error};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageInfo {
+    /// Absent for cursor pages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<i64>,
+    pub limit: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+    pub has_more: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// `?page=&limit=` or `?cursor=&limit=`; unset fields use the endpoint's defaults
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PageRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+/// One page of a list response
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub pagination: PageInfo,
+    /// Other top-level fields, e.g. the `season` of a leaderboard
+    pub extra: Map<String, Value>,
+}
+
+impl<T: DeserializeOwned> Page<T> {
+    /// Read a `{ "<key>": [...], "pagination": {...} }` response
+    pub fn from_value(key: &str, value: Value) -> Result<Self, serde_json::Error> {
+        let mut extra = match value {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        };
+        let items = extra.remove(key).unwrap_or(Value::Array(Vec::new()));
+        let pagination = extra.remove("pagination").unwrap_or(Value::Null);
+
+        Ok(Self {
+            items: serde_json::from_value(items)?,
+            pagination: serde_json::from_value(pagination)?,
+            extra,
+        })
+    }
+}
+
+impl<T> Page<T> {
+    /// The request for the page after this one, if there is one. Cursor
+    /// pages are followed by `next_cursor`, numbered pages by `page + 1`.
+    pub fn next_request(&self) -> Option<PageRequest> {
+        if !self.pagination.has_more {
+            return None;
+        }
+
+        let limit = Some(self.pagination.limit);
+        match (&self.pagination.next_cursor, self.pagination.page) {
+            (Some(cursor), _) => Some(PageRequest {
+                cursor: Some(cursor.clone()),
+                limit,
+                ..Default::default()
+            }),
+            (None, Some(page)) => Some(PageRequest {
+                page: Some(page + 1),
+                limit,
+                ..Default::default()
+            }),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Walks a list endpoint page by page. Nothing is requested until
+/// [`Pages::next_page`] is called.
+#[cfg(feature = "client")]
+pub struct Pages<T> {
+    client: Client,
+    path: String,
+    query: Vec<(String, String)>,
+    key: &'static str,
+    next: Option<PageRequest>,
+    _items: std::marker::PhantomData<T>,
+}
+
+#[cfg(feature = "client")]
+impl<T: DeserializeOwned> Pages<T> {
+    pub(crate) fn new(
+        client: Client,
+        path: String,
+        query: Vec<(String, String)>,
+        key: &'static str,
+    ) -> Self {
+        Self {
+            client,
+            path,
+            query,
+            key,
+            next: Some(PageRequest::default()),
+            _items: std::marker::PhantomData,
+        }
+    }
+
+    /// Start from another page, page size or cursor than the first default page
+    pub fn starting_at(mut self, request: PageRequest) -> Self {
+        self.next = Some(request);
+        self
+    }
+
+    /// Fetch the next page; `None` once the last page has been returned
+    pub async fn next_page(&mut self) -> Result<Option<Page<T>>, Error> {
+        let Some(request) = self.next.take() else {
+            return Ok(None);
+        };
+
+        let mut query = self.query.clone();
+        query.extend(crate::client::query_pairs(&request));
+        let value: Value = self.client.get(&self.path, &query).await?;
+        let page = Page::from_value(self.key, value).map_err(Error::Decode)?;
+
+        self.next = page.next_request();
+        Ok(Some(page))
+    }
+
+    /// Fetch every remaining page and return their items
+    pub async fn collect_all(mut self) -> Result<Vec<T>, Error> {
+        let mut items = Vec::new();
+        while let Some(page) = self.next_page().await? {
+            items.extend(page.items);
+        }
+
+        Ok(items)
+    }
+}
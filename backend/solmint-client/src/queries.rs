@@ -0,0 +1,163 @@
+//! Query string filters. Pagination is separate, see [`crate::PageRequest`].
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Mirrors the backend's `NftListQuery`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NftListQuery {
+    pub collection_id: Option<Uuid>,
+    pub owner: Option<String>,
+    pub creator: Option<String>,
+    pub min_price: Option<i64>,
+    pub max_price: Option<i64>,
+    pub rarity_rank_min: Option<i32>,
+    pub rarity_rank_max: Option<i32>,
+    /// JSON object of trait type to value, or to a list of values any of
+    /// which match, e.g. `{"Background":"Blue","Hat":["Cap","Crown"]}`
+    pub attributes: Option<String>,
+    /// "price", "rarity", "created_at"
+    pub sort_by: Option<String>,
+    /// "asc", "desc"
+    pub sort_order: Option<String>,
+    /// Include NFTs flagged as spam
+    pub include_spam: Option<bool>,
+    /// Include blocklisted NFTs; admin only
+    pub include_blocked: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CollectionListQuery {
+    pub creator: Option<String>,
+    pub verified: Option<bool>,
+    pub sort_by: Option<String>,    // "name", "rank", "created_at"
+    pub sort_order: Option<String>, // "asc", "desc"
+    /// Include collections flagged as spam
+    pub include_spam: Option<bool>,
+    /// Include blocklisted collections; admin only
+    pub include_blocked: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HoldersHistoryQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListingChurnQuery {
+    pub days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CandleQuery {
+    /// "1h", "4h" or "1d"; also read from `interval`
+    #[serde(alias = "interval")]
+    pub resolution: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoyaltyReportQuery {
+    pub period: Option<String>, // "day", "week", "month"
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DailyStatsQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportAllowlistQuery {
+    /// "merge" (default) adds to or updates the existing entries; "replace"
+    /// swaps the whole list
+    pub mode: Option<String>,
+    pub max_allocation: Option<u64>,
+    /// Needed until the mint config exists, to show it is the creator's
+    pub config_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LaunchListQuery {
+    /// "upcoming", "live" or "ended"
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwitterChallengeQuery {
+    /// With or without the leading `@`
+    pub handle: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxReportQuery {
+    pub year: i32,
+    /// "json" (default) or "csv"
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationListQuery {
+    pub unread: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PointsQuery {
+    /// Defaults to the season currently running
+    pub season: Option<Uuid>,
+    /// Recent entries returned with a wallet's points
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BlocklistQuery {
+    pub kind: Option<String>,
+}
+
+/// Without `status`, only offers that can be accepted now: active and
+/// unexpired. `direction` is for wallets' offers: `made` (the default) or
+/// `received`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OfferQuery {
+    pub status: Option<String>,
+    pub direction: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FirehoseQuery {
+    /// Return activities after this id; 0 (the default) starts from the beginning
+    pub since_id: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+/// `status` is "live" (the default), "ended" (waiting to be settled) or
+/// "settled"
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuctionQuery {
+    pub status: Option<String>,
+    pub collection_id: Option<Uuid>,
+    pub seller: Option<String>,
+    /// Only auctions ending within this many minutes
+    pub ending_within_minutes: Option<i64>,
+    /// "ends_at" (the default), "created_at" or "highest_bid"
+    pub sort_by: Option<String>,
+    /// "asc" or "desc"; ending soonest first by default, otherwise descending
+    pub sort_order: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActivityQuery {
+    /// Comma-separated activity types, e.g. "sale,transfer"; all when unset
+    pub types: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebhookDeliveryQuery {
+    /// "pending", "delivered" or "failed"; all when unset
+    pub status: Option<String>,
+}
@@ -0,0 +1,344 @@
+//! Request bodies. The backend's handlers deserialize these same types,
+//! except for the few that embed database rows (launch phases, notification
+//! preferences), which mirror the backend's own.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::types::{LaunchPhase, NotificationPreference, Role};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateUserRequest {
+    pub username: Option<String>,
+    pub email: Option<String>,
+    pub bio: Option<String>,
+    pub avatar_url: Option<String>,
+    pub twitter_handle: Option<String>,
+    pub discord_handle: Option<String>,
+}
+
+/// Start signing in as `wallet`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthChallengeRequest {
+    pub wallet: String,
+}
+
+/// The wallet's base58 signature of the challenge message for `nonce`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthVerifyRequest {
+    pub wallet: String,
+    pub nonce: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetUserRoleRequest {
+    pub role: Role,
+}
+
+/// Link to a tweet by the handle being verified. The tweet must contain the
+/// wallet's base58 signature of the message from the challenge endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyTwitterRequest {
+    pub tweet_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddFavoriteRequest {
+    pub nft_mint: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddFavoriteCollectionRequest {
+    pub collection_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HolderSnapshotRequest {
+    /// "json" (default) or "csv"
+    pub format: Option<String>,
+    /// Compute a Merkle root over `(wallet, mints held)` leaves
+    #[serde(default)]
+    pub merkle: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAlertIntegrationRequest {
+    /// "discord" or "telegram"
+    pub kind: String,
+    /// Discord webhook URL, or Telegram chat id / @channel the bot is a member of
+    pub target: String,
+    pub events: Vec<String>,
+    /// Lamports
+    pub min_sale_price: Option<i64>,
+    pub floor_change_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateAlertIntegrationRequest {
+    pub events: Option<Vec<String>>,
+    pub min_sale_price: Option<i64>,
+    pub floor_change_pct: Option<f64>,
+    /// Re-enabling also clears the failure count
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWebhookRequest {
+    /// https URL events are POSTed to
+    pub url: String,
+    /// Any of "sale", "listing", "mint", "offer"
+    pub events: Vec<String>,
+    /// Only activity in this collection; every collection when unset
+    pub collection_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    /// Re-enabled webhooks receive activity from then on, not what they missed
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllowlistEntryInput {
+    pub wallet_address: String,
+    /// Mints the wallet may claim in the phase (default: 1)
+    pub allocation: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadAllowlistRequest {
+    pub entries: Vec<AllowlistEntryInput>,
+    pub max_allocation: Option<u64>,
+    /// Needed until the mint config exists, to show it is the creator's
+    pub config_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddAllowlistEntriesRequest {
+    pub entries: Vec<AllowlistEntryInput>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoveAllowlistEntriesRequest {
+    pub wallets: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisterLaunchRequest {
+    /// "candy_machine_v3" or "solmint"
+    pub kind: String,
+    /// Candy machine address; generated for solmint launches
+    pub config_address: Option<String>,
+    pub creator_address: String,
+    pub collection_id: Option<Uuid>,
+    pub name: String,
+    pub symbol: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    /// solmint launches only
+    pub base_uri: Option<String>,
+    pub items_available: Option<i64>,
+    pub treasury_address: Option<String>,
+    pub phases: Vec<LaunchPhase>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateLaunchPhasesRequest {
+    pub phases: Vec<LaunchPhase>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildMintRequest {
+    pub wallet_address: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCheckoutOrderRequest {
+    pub listing_id: Uuid,
+    /// Wallet that receives the NFT
+    pub buyer_wallet: String,
+    /// Receipt email passed to the provider
+    pub email: Option<String>,
+}
+
+/// Compute budget of a transaction the API builds. Unset fields are
+/// estimated: the limit by simulating the transaction, the price from recent
+/// prioritization fees.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ComputeBudgetOverride {
+    /// Micro-lamports per compute unit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compute_unit_price: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compute_unit_limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintNftRequest {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub creator: String,
+    /// Collection to set and verify the NFT into. It must be tied to a
+    /// Metaplex collection whose update authority is `creator`.
+    #[serde(default)]
+    pub collection_id: Option<Uuid>,
+    #[serde(flatten)]
+    pub compute_budget: ComputeBudgetOverride,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuyListingRequest {
+    pub buyer: String,
+    /// Lamports; the price the buyer was shown. The transaction fails on
+    /// chain if the listing's price has changed since.
+    pub expected_price: u64,
+    /// Wallet paid the marketplace's referral share of the fee
+    #[serde(default)]
+    pub referrer: Option<String>,
+    #[serde(flatten)]
+    pub compute_budget: ComputeBudgetOverride,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MakeOfferRequest {
+    pub bidder: String,
+    pub nft_mint: String,
+    /// Marketplace the offer is made on
+    pub marketplace_address: String,
+    /// Lamports, held in the offer account until it is accepted or cancelled
+    pub amount: u64,
+    /// No expiry when unset
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    pub compute_budget: ComputeBudgetOverride,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcceptOfferRequest {
+    /// Current owner of the NFT
+    pub seller: String,
+    #[serde(flatten)]
+    pub compute_budget: ComputeBudgetOverride,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendTransactionRequest {
+    /// Bincode-serialized legacy or v0 transaction
+    pub signed_transaction: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresignedUrlRequest {
+    pub filename: String,
+    pub content_type: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataUploadRequest {
+    pub metadata: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationPreferencesRequest {
+    pub preferences: Vec<NotificationPreference>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchlistRequest {
+    pub collection_id: Uuid,
+    /// Lamports; alert when the SOL floor drops to or below this
+    pub alert_floor_below: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterDeviceRequest {
+    /// "ios" or "android"
+    pub platform: String,
+    /// APNs device token or FCM registration token
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfirmEmailRequest {
+    pub token: String,
+}
+
+/// Season fields settable by admins; omitted fields keep their current (or default) value
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PointsSeasonRequest {
+    pub name: Option<String>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub buy_points_per_sol: Option<f64>,
+    pub sell_points_per_sol: Option<f64>,
+    pub listing_points: Option<f64>,
+    pub streak_bonus: Option<f64>,
+    pub max_streak_days: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionMultiplierRequest {
+    pub multiplier: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PointsAdjustmentRequest {
+    pub wallet_address: String,
+    pub points: f64,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportCollectionRequest {
+    pub collection_id: Option<Uuid>,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub creator_address: Option<String>,
+    pub verified: Option<bool>,
+    /// Mint hashlist
+    pub mints: Option<Vec<String>>,
+    /// Verified collection address; its members are listed through DAS
+    pub collection_address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpamOverrideRequest {
+    /// `null` hands the decision back to the spam job
+    pub is_spam: Option<bool>,
+}
+
+/// `value` is a mint or creator address, or a collection id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateBlocklistEntryRequest {
+    pub kind: String,
+    pub value: String,
+    pub reason: Option<String>,
+    pub actor: Option<String>,
+}
+
+/// `kind` is `wallet` or `ip`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAccessBlockRequest {
+    pub kind: String,
+    pub value: String,
+    pub reason: Option<String>,
+    pub actor: Option<String>,
+    /// Permanent when unset
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Who made an admin change and why, stored on the history entries it writes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AdminChangeRequest {
+    pub actor: Option<String>,
+    pub reason: Option<String>,
+}
@@ -0,0 +1,294 @@
+//! Response bodies with more than one field. Endpoints returning a single
+//! wrapped resource, e.g. `{ "nft": {...} }`, are unwrapped by the client.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::types::{
+    Activity, AlertIntegration, Allowlist, Auction, Bid, Candle, CheckoutOrder, CheckoutOrderEvent,
+    Collection, CollectionImport, CurrencyVolume, DailyStats, EstimateInputs, FailedImportItem,
+    HolderSnapshot, Launch, LaunchPhase, LaunchSupply, Nft, Offer, PointsBySource, PointsEntry,
+    PointsSeason, PriceEstimate, RoyaltySummary, SnapshotHolder, TaskState, User, Webhook,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthStatus {
+    pub status: String,
+    pub timestamp: DateTime<Utc>,
+    pub cluster: String,
+    pub services: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub tasks: Vec<TaskState>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataRefresh {
+    pub nft: Nft,
+    /// Fields that differed from the stored metadata, e.g. `attributes`
+    pub changed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NftEstimate {
+    pub mint_address: String,
+    /// `None` when there wasn't enough market data
+    pub estimate: Option<PriceEstimate>,
+    pub inputs: EstimateInputs,
+}
+
+/// Merkle proof of a compressed NFT with the canopy nodes trimmed off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NftProof {
+    pub mint_address: String,
+    pub tree_id: String,
+    pub root: String,
+    pub leaf: String,
+    pub node_index: u64,
+    pub canopy_depth: u32,
+    pub full_proof_length: u64,
+    pub proof: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintNftResponse {
+    pub transaction: Vec<u8>,
+    pub mint_address: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuyListingResponse {
+    pub transaction: Vec<u8>,
+    pub listing_address: String,
+    pub nft_mint: String,
+    /// Lamports
+    pub price: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfferTransaction {
+    pub offer: Offer,
+    pub transaction: Vec<u8>,
+}
+
+/// An auction with its bids, highest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuctionDetail {
+    pub auction: Auction,
+    pub bids: Vec<Bid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendTransactionResponse {
+    pub signature: String,
+    pub mint_address: String,
+    pub explorer_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresignedUrlResponse {
+    pub upload_url: String,
+    pub image_url: String,
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataUploadResponse {
+    pub metadata_uri: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoldersHistory {
+    pub collection_id: Uuid,
+    pub history: Vec<HolderSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HolderSnapshotResponse {
+    pub collection_id: Uuid,
+    pub taken_at: DateTime<Utc>,
+    pub total_holders: u64,
+    pub total_supply: u64,
+    pub merkle_root: Option<String>,
+    pub holders: Vec<SnapshotHolder>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candles {
+    pub collection_id: Uuid,
+    pub resolution: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub candles: Vec<Candle>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NftCandles {
+    pub nft_mint: String,
+    pub resolution: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub candles: Vec<Candle>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedIntegration {
+    pub integration: AlertIntegration,
+    /// Shown once; needed to change or remove the integration
+    pub manage_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatedWebhook {
+    pub webhook: Webhook,
+    /// Shown once; verifies the `Solmint-Signature` header of deliveries
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoyaltyReport {
+    pub creator: String,
+    pub period: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub total_royalties: i64,
+    pub royalties: Vec<RoyaltySummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyStatsResponse {
+    pub stats: Vec<DailyStats>,
+    pub currency_breakdown: Vec<CurrencyVolume>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisteredLaunch {
+    pub launch: Launch,
+    pub phases: Vec<LaunchPhase>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchDetail {
+    pub launch: Launch,
+    pub phases: Vec<LaunchPhase>,
+    pub supply: LaunchSupply,
+    pub allowlist: Option<Allowlist>,
+    pub current_phase: Option<LaunchPhase>,
+    pub next_phase: Option<LaunchPhase>,
+    /// sold_out, live, upcoming or ended
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintTransaction {
+    /// Base64 bincode-serialized transaction, already signed by the new mint
+    pub transaction: String,
+    pub mint_address: String,
+    pub item_index: Option<i64>,
+    pub phase: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckoutOrderDetail {
+    pub order: CheckoutOrder,
+    pub events: Vec<CheckoutOrderEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserProfile {
+    pub user: Option<User>,
+    /// Primary `.sol` name, when the wallet has one
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsernameAvailability {
+    pub username: String,
+    pub available: bool,
+    /// Why the name can't be registered: invalid, reserved or taken
+    pub reason: Option<String>,
+}
+
+/// Message to sign to sign in, valid until `expires_at`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthChallenge {
+    pub message: String,
+    pub nonce: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Bearer token for requests made as `wallet_address`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthToken {
+    pub token: String,
+    pub wallet_address: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Message to sign for Twitter/X handle verification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwitterChallenge {
+    pub handle: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserPoints {
+    pub wallet_address: String,
+    pub season: PointsSeason,
+    pub points: f64,
+    pub rank: Option<i64>,
+    pub breakdown: Vec<PointsBySource>,
+    pub recent: Vec<PointsEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailConfirmation {
+    pub wallet_address: String,
+    pub verified: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PointsRecompute {
+    pub season_id: Uuid,
+    pub entries_accrued: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionImportStarted {
+    pub collection: Collection,
+    pub import: CollectionImport,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionImportDetail {
+    pub import: CollectionImport,
+    pub failed_items: Vec<FailedImportItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionSpamOverride {
+    pub collection_id: Uuid,
+    pub is_spam: bool,
+    pub overridden: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NftSpamOverride {
+    pub mint_address: String,
+    pub is_spam: bool,
+    pub overridden: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FirehosePage {
+    pub activities: Vec<Activity>,
+    /// Pass as `since_id` to resume after this page
+    pub next_since_id: i64,
+    pub has_more: bool,
+}
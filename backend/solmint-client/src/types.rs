@@ -0,0 +1,809 @@
+//! Resources returned by the API. These mirror the backend's models as they
+//! are serialized; fields the backend never returns (tokens, secrets) are left
+//! out. Prices are in lamports unless a `currency_mint` says otherwise.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Nft {
+    pub id: Uuid,
+    pub mint_address: String,
+    pub collection_id: Option<Uuid>,
+    pub name: String,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub animation_url: Option<String>,
+    pub external_url: Option<String>,
+    pub attributes: Option<serde_json::Value>,
+    pub creator_address: String,
+    pub current_owner: String,
+    pub is_compressed: bool,
+    pub rarity_rank: Option<i32>,
+    /// Decimal string
+    pub rarity_score: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub version: i32,
+    pub is_spam: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Collection {
+    pub id: Uuid,
+    pub name: String,
+    pub symbol: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub banner_url: Option<String>,
+    pub creator_address: String,
+    pub verified: bool,
+    pub rank_score: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub is_spam: bool,
+    pub favorite_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+    pub id: Uuid,
+    pub wallet_address: String,
+    pub username: Option<String>,
+    pub email: Option<String>,
+    pub bio: Option<String>,
+    pub avatar_url: Option<String>,
+    pub twitter_handle: Option<String>,
+    pub twitter_verified_at: Option<DateTime<Utc>>,
+    pub discord_handle: Option<String>,
+    pub verified: bool,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What a wallet may do beyond its own data, from least to most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    /// Verification, spam and blocklist moderation
+    Moderator,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "user" => Some(Role::User),
+            "moderator" => Some(Role::Moderator),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraitFloor {
+    pub trait_type: String,
+    pub value: String,
+    pub floor_price: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstimateInputs {
+    pub collection_floor: Option<i64>,
+    pub collection_floor_7d_ago: Option<i64>,
+    pub trait_floors: Vec<TraitFloor>,
+    pub comparable_sales: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstimateComponent {
+    pub name: String,
+    pub value: i64,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceEstimate {
+    pub model: String,
+    pub estimate: i64,
+    pub low: i64,
+    pub high: i64,
+    /// 0.0 - 1.0
+    pub confidence: f64,
+    pub components: Vec<EstimateComponent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionStats {
+    pub collection_id: Uuid,
+    pub floor_price: Option<i64>,
+    pub listed_count: i64,
+    pub total_volume: i64,
+    pub sales_count: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HolderSnapshot {
+    pub snapshot_date: NaiveDate,
+    pub unique_holders: i64,
+    pub total_supply: i64,
+    pub floor_price: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotHolder {
+    pub wallet_address: String,
+    pub count: i64,
+    pub mints: Vec<String>,
+    /// Only when the snapshot was taken with `merkle: true`
+    pub proof: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListingChurn {
+    pub collection_id: Uuid,
+    pub since: DateTime<Utc>,
+    pub tracked_listings: i64,
+    pub sales: i64,
+    pub avg_time_to_sale_seconds: Option<f64>,
+    pub price_cuts: i64,
+    pub price_cut_rate: f64,
+    pub below_floor_cut_rate: f64,
+    pub delists: i64,
+    pub relists: i64,
+    pub relist_rate: f64,
+}
+
+/// OHLC candle of sale prices
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candle {
+    pub bucket_start: DateTime<Utc>,
+    pub open: i64,
+    pub high: i64,
+    pub low: i64,
+    pub close: i64,
+    pub volume: i64,
+    pub sales_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertIntegration {
+    pub id: Uuid,
+    pub collection_id: Uuid,
+    pub kind: String,
+    pub events: Vec<String>,
+    pub min_sale_price: i64,
+    pub floor_change_pct: f64,
+    pub last_floor_price: Option<i64>,
+    pub enabled: bool,
+    pub consecutive_failures: i32,
+    pub last_error: Option<String>,
+    pub last_delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoyaltySummary {
+    pub collection_id: Option<Uuid>,
+    pub collection_name: Option<String>,
+    pub period_start: NaiveDate,
+    pub royalties_earned: i64,
+    pub sales_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatorCollection {
+    pub id: Uuid,
+    pub name: String,
+    pub image_url: Option<String>,
+    pub verified: Option<bool>,
+    pub items: i64,
+    pub holders: i64,
+    pub floor_price: Option<i64>,
+    pub volume_total: i64,
+    pub volume_7d: i64,
+    pub sales_total: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintProgress {
+    pub launch_id: Uuid,
+    pub name: String,
+    pub kind: String,
+    pub collection_id: Option<Uuid>,
+    pub items_available: Option<i64>,
+    pub items_redeemed: Option<i64>,
+    pub first_phase_starts_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatorSalesSummary {
+    pub volume_total: i64,
+    pub volume_24h: i64,
+    pub volume_7d: i64,
+    pub volume_30d: i64,
+    pub sales_total: i64,
+    pub sales_24h: i64,
+    pub unique_buyers_30d: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatorRoyalties {
+    pub total: i64,
+    pub last_30d: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopHolder {
+    pub wallet_address: String,
+    pub items: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatorActivity {
+    /// sale, listed, price_change or delisted
+    pub event_type: String,
+    pub nft_mint: String,
+    pub nft_name: Option<String>,
+    pub collection_id: Option<Uuid>,
+    pub price: i64,
+    pub currency_mint: String,
+    pub seller_address: String,
+    pub buyer_address: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatorDashboard {
+    pub creator: String,
+    pub collections: Vec<CreatorCollection>,
+    pub mint_progress: Vec<MintProgress>,
+    pub sales: CreatorSalesSummary,
+    pub royalties: CreatorRoyalties,
+    pub top_holders: Vec<TopHolder>,
+    pub recent_activity: Vec<CreatorActivity>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyStats {
+    pub date: NaiveDate,
+    pub total_volume: i64,
+    pub total_sales: i64,
+    pub unique_buyers: i64,
+    pub unique_sellers: i64,
+    pub average_price: i64,
+    pub new_listings: i64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrencyVolume {
+    pub currency_mint: String,
+    pub volume: i64,
+    pub base_volume: i64,
+    pub sales_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalStats {
+    pub total_volume: i64,
+    pub total_sales: i64,
+    pub total_collections: i64,
+    pub total_nfts: i64,
+    pub active_listings: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KpiDelta {
+    pub current: i64,
+    pub previous: i64,
+    pub change_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardDeltas {
+    pub volume: KpiDelta,
+    pub sales: KpiDelta,
+    pub unique_buyers: KpiDelta,
+    pub unique_sellers: KpiDelta,
+    pub new_listings: KpiDelta,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendingCollection {
+    pub id: Uuid,
+    pub name: String,
+    pub image_url: Option<String>,
+    pub rank_score: Option<f64>,
+    pub volume_24h: i64,
+    pub sales_24h: i64,
+    pub floor_price: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LargeSale {
+    pub nft_mint: String,
+    pub nft_name: Option<String>,
+    pub collection_id: Option<Uuid>,
+    pub buyer_address: String,
+    pub seller_address: String,
+    pub price: i64,
+    pub currency_mint: String,
+    pub base_price: i64,
+    pub transaction_signature: String,
+    pub block_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dashboard {
+    pub global: GlobalStats,
+    pub last_24h: DashboardDeltas,
+    pub trending_collections: Vec<TrendingCollection>,
+    pub recent_large_sales: Vec<LargeSale>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Allowlist {
+    pub config_address: String,
+    pub creator_address: String,
+    pub merkle_root: String,
+    pub entry_count: i64,
+    pub max_allocation: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllowlistEntry {
+    pub wallet_address: String,
+    pub allocation: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllowlistProof {
+    pub config_address: String,
+    pub wallet_address: String,
+    pub allocation: i64,
+    pub merkle_root: String,
+    /// Base58 sibling hashes from the leaf up
+    pub proof: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Launch {
+    pub id: Uuid,
+    pub kind: String,
+    pub config_address: String,
+    pub creator_address: String,
+    pub collection_id: Option<Uuid>,
+    pub name: String,
+    pub symbol: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub base_uri: Option<String>,
+    pub items_available: Option<i64>,
+    pub treasury_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchPhase {
+    /// Candy guard group label for candy machines; at most 6 characters
+    pub label: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub price: i64,
+    pub per_wallet_limit: Option<i32>,
+    #[serde(default)]
+    pub allowlist: bool,
+    /// Candy guard solPayment destination
+    pub payment_destination: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchSupply {
+    pub items_available: i64,
+    pub items_redeemed: i64,
+    pub remaining: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckoutOrder {
+    pub id: Uuid,
+    pub listing_id: Uuid,
+    pub nft_mint: String,
+    pub seller_address: String,
+    pub price: i64,
+    pub currency_mint: String,
+    pub buyer_wallet: String,
+    pub status: String,
+    pub failure_reason: Option<String>,
+    pub checkout_url: Option<String>,
+    pub fiat_amount: Option<i64>,
+    pub fiat_currency: Option<String>,
+    pub sale_signature: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckoutOrderEvent {
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub source: String,
+    pub detail: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxLedgerEntry {
+    pub date: DateTime<Utc>,
+    /// "acquisition" or "disposal"
+    pub entry_type: String,
+    pub nft_mint: String,
+    pub nft_name: Option<String>,
+    pub counterparty: String,
+    pub price: i64,
+    pub currency_mint: String,
+    pub value_sol: f64,
+    pub fees_sol: f64,
+    pub sol_usd_price: Option<f64>,
+    pub value_usd: Option<f64>,
+    pub fees_usd: Option<f64>,
+    pub cost_basis_sol: Option<f64>,
+    pub cost_basis_usd: Option<f64>,
+    pub gain_usd: Option<f64>,
+    pub transaction_signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxSummary {
+    pub acquisitions: u64,
+    pub disposals: u64,
+    pub acquired_sol: f64,
+    pub acquired_usd: f64,
+    pub disposed_sol: f64,
+    pub disposed_usd: f64,
+    pub fees_sol: f64,
+    pub fees_usd: f64,
+    pub realized_gain_usd: f64,
+    pub entries_missing_usd_price: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxReport {
+    pub wallet_address: String,
+    pub year: i32,
+    pub summary: TaxSummary,
+    pub entries: Vec<TaxLedgerEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: Uuid,
+    pub wallet_address: String,
+    pub category: String,
+    pub title: String,
+    pub body: String,
+    pub data: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationPreference {
+    pub category: String,
+    pub channel: String,
+    pub mode: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchlistEntry {
+    pub collection_id: Uuid,
+    pub collection_name: String,
+    pub alert_floor_below: Option<i64>,
+    pub floor_price: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PushDevice {
+    pub id: Uuid,
+    pub wallet_address: String,
+    pub platform: String,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PointsSeason {
+    pub id: Uuid,
+    pub name: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub buy_points_per_sol: f64,
+    pub sell_points_per_sol: f64,
+    pub listing_points: f64,
+    pub streak_bonus: f64,
+    pub max_streak_days: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PointsEntry {
+    pub source: String,
+    pub reference: String,
+    pub points: f64,
+    pub details: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PointsBySource {
+    pub source: String,
+    pub points: f64,
+    pub entries: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub rank: i64,
+    pub wallet_address: String,
+    /// Primary `.sol` name, when the wallet has one
+    pub display_name: Option<String>,
+    pub points: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionImport {
+    pub id: Uuid,
+    pub collection_id: Uuid,
+    pub source: String,
+    pub collection_address: Option<String>,
+    pub status: String,
+    pub total_items: i32,
+    pub imported_items: i32,
+    pub failed_items: i32,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedImportItem {
+    pub mint_address: String,
+    pub attempts: i32,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlocklistEntry {
+    pub id: Uuid,
+    pub kind: String,
+    pub value: String,
+    pub reason: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A wallet or IP refused on write routes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessBlock {
+    pub id: Uuid,
+    pub kind: String,
+    pub value: String,
+    pub reason: Option<String>,
+    pub created_by: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RowHistory {
+    pub id: i64,
+    pub table_name: String,
+    pub row_id: Uuid,
+    pub operation: String,
+    pub old_data: serde_json::Value,
+    pub new_data: Option<serde_json::Value>,
+    pub actor: Option<String>,
+    pub reason: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// A background task of the API process, as reported by `/ready`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskState {
+    pub name: String,
+    /// running, restarting or stopped
+    pub status: String,
+    pub restarts: u32,
+    pub last_error: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+}
+
+/// One event in the activity firehose
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Activity {
+    pub id: i64,
+    /// "mint", "list", "price_change", "delist", "sale", "rent", "transfer"
+    /// or "offer"
+    pub activity_type: String,
+    pub nft_mint: String,
+    pub collection_id: Option<Uuid>,
+    pub from_address: Option<String>,
+    pub to_address: Option<String>,
+    pub price: Option<i64>,
+    pub currency_mint: Option<String>,
+    pub transaction_signature: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// An offer on a single NFT (`nft_mint`) or on any NFT of a collection
+/// (`collection_id`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Offer {
+    pub id: Uuid,
+    pub offer_address: String,
+    pub nft_mint: Option<String>,
+    pub collection_id: Option<Uuid>,
+    pub bidder_address: String,
+    /// Unknown for offers first seen on chain
+    pub marketplace_address: Option<String>,
+    pub amount: i64,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// "pending" until the make offer transaction lands, then "active",
+    /// "accepted" or "cancelled"
+    pub status: String,
+    /// Who accepted the offer
+    pub seller_address: Option<String>,
+    /// The NFT sold into an accepted collection offer
+    pub filled_mint: Option<String>,
+    pub transaction_signature: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One run of an auction account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Auction {
+    pub id: Uuid,
+    pub auction_address: String,
+    pub nft_mint: String,
+    pub seller_address: String,
+    pub reserve_price: i64,
+    pub ends_at: DateTime<Utc>,
+    pub highest_bid: Option<i64>,
+    pub highest_bidder: Option<String>,
+    pub bid_count: i32,
+    /// "live" until settled, then "settled"
+    pub status: String,
+    /// `None` when the auction ended without bids
+    pub winner_address: Option<String>,
+    pub transaction_signature: String,
+    pub settle_signature: Option<String>,
+    pub settled_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bid {
+    pub id: Uuid,
+    pub auction_id: Uuid,
+    pub bidder_address: String,
+    pub amount: i64,
+    pub transaction_signature: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An unsettled auction a wallet has bid on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveBid {
+    pub auction: Auction,
+    /// The wallet's highest bid
+    pub amount: i64,
+    /// Whether the wallet is the highest bidder
+    pub leading: bool,
+}
+
+/// One account holding a wallet's lamports in the marketplace program
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscrowCommitment {
+    /// "offer", "collection_offer", "auction_bid" while bidding is open, or
+    /// "auction_bid_unsettled" once it has closed and the auction awaits
+    /// settlement
+    pub kind: String,
+    pub account: String,
+    pub marketplace: String,
+    /// The NFT, or the collection mint of a collection offer
+    pub mint: String,
+    /// Lamports locked, on top of the account's rent
+    pub amount: u64,
+    /// Unix timestamp an offer lapses or an auction's bidding closes at;
+    /// `None` for offers that don't expire
+    pub expires_at: Option<i64>,
+}
+
+/// Lamports a wallet has locked in open offers and leading auction bids,
+/// read from chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscrowBalance {
+    pub wallet_address: String,
+    pub total_locked: u64,
+    /// Largest first
+    pub commitments: Vec<EscrowCommitment>,
+}
+
+/// How common a trait value is in its collection, and its cheapest listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionTrait {
+    pub trait_type: String,
+    pub value: String,
+    pub count: i64,
+    /// Share of the collection with the value, 0 to 1
+    pub frequency: f64,
+    /// Lamports; `None` when none are listed in SOL
+    pub floor_price: Option<i64>,
+    pub listed_count: i64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// An integrator's webhook, posted matching marketplace activity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub owner_address: String,
+    pub url: String,
+    /// Any of "sale", "listing", "mint", "offer"
+    pub events: Vec<String>,
+    /// `None` for every collection
+    pub collection_id: Option<Uuid>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One activity posted (or being posted) to a webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: Uuid,
+    /// Firehose id of the activity
+    pub activity_id: i64,
+    pub event: String,
+    /// The activity, as in the firehose
+    pub payload: serde_json::Value,
+    /// "pending", "delivered" or "failed" (out of attempts)
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    /// HTTP status of the latest attempt
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,620 @@
+use axum::{
+    extract::DefaultBodyLimit,
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE},
+        HeaderName, HeaderValue, Method,
+    },
+    routing::{get, post, put},
+    Router,
+};
+use std::net::SocketAddr;
+use tower::ServiceBuilder;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::{
+    config::Config,
+    database::Database,
+    error::AppError,
+    handlers, middleware,
+    models::User,
+    runtime::RuntimeConfig,
+    services::{
+        self, email::Mailer, live_events::LiveEvents, price_estimator::HeuristicModel,
+        supervisor::Supervisor, twitter::TwitterVerifier,
+    },
+    startup,
+};
+
+/// Handle `--check-config`: print the resolved, redacted configuration and exit
+pub fn check_config_flag() -> Result<(), AppError> {
+    if !std::env::args().any(|arg| arg == "--check-config") {
+        return Ok(());
+    }
+
+    match Config::load() {
+        Ok(config) => {
+            println!("{}", serde_json::to_string_pretty(&config.redacted())?);
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Run the job scheduler and websocket indexer under the supervisor of the
+/// current process
+pub fn spawn_background_services(supervisor: &Supervisor, db: &Database, config: &Config) {
+    let scheduler_db = db.pool().clone();
+    let scheduler_config = config.clone();
+    supervisor.spawn("scheduler", move |shutdown| {
+        services::scheduler::run(scheduler_db.clone(), scheduler_config.clone(), shutdown)
+    });
+
+    let indexer_db = db.pool().clone();
+    let indexer_config = config.clone();
+    supervisor.spawn("websocket_indexer", move |_shutdown| {
+        services::websocket_indexer::start_websocket_indexer(
+            indexer_db.clone(),
+            indexer_config.clone(),
+        )
+    });
+}
+
+/// Resolve on Ctrl+C or SIGTERM
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut stream) => {
+                stream.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+
+    println!("Shutdown signal received");
+}
+
+/// Serve the HTTP API until a shutdown signal, then stop background tasks
+pub async fn serve_api(
+    config: Config,
+    db: Database,
+    supervisor: Supervisor,
+) -> Result<(), AppError> {
+    // Initialize Redis connection, waiting for it to come up
+    let redis_client = redis::Client::open(config.redis_url.clone())?;
+    let redis_conn = startup::retry(&config, "Redis", || async {
+        Ok(redis_client.get_multiplexed_async_connection().await?)
+    })
+    .await?;
+
+    // Wallets listed in ADMIN_WALLETS are admins, whatever role they were given
+    User::seed_admins(db.pool(), &config.admin_wallets()).await?;
+
+    // Hot-reloadable settings (CORS, rate limits, RPC endpoint, cache TTLs); SIGHUP reloads them
+    let runtime = RuntimeConfig::new(&config);
+    runtime.clone().spawn_sighup_listener();
+
+    // Every replica relays published events to its own `/ws` sockets
+    let live_events = LiveEvents::new();
+    let relay_events = live_events.clone();
+    let relay_db = db.pool().clone();
+    let relay_config = config.clone();
+    supervisor.spawn("live_events", move |shutdown| {
+        let relay_events = relay_events.clone();
+        let relay_db = relay_db.clone();
+        let relay_config = relay_config.clone();
+        async move { relay_events.run(relay_db, relay_config, shutdown).await }
+    });
+
+    // Create application state
+    let app_state = handlers::AppState {
+        db: db.pool().clone(),
+        redis: redis_conn,
+        runtime,
+        supervisor: supervisor.clone(),
+        price_model: std::sync::Arc::new(HeuristicModel),
+        mailer: Mailer::from_config(&config)?,
+        twitter: TwitterVerifier::from_config(&config),
+        live_events,
+        config: config.clone(),
+    };
+
+    let app = router(app_state);
+
+    // Start the server
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
+    println!("Server listening on {}", addr);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?;
+
+    // Stop accepting requests first, then background services
+    supervisor.shutdown().await;
+
+    Ok(())
+}
+
+/// Build the application router
+pub fn router(app_state: handlers::AppState) -> Router {
+    // Build CORS layer, checking origins against the current settings on every request
+    let cors_runtime = app_state.runtime.clone();
+    let cors = CorsLayer::new()
+        .allow_origin(AllowOrigin::predicate(
+            move |origin: &HeaderValue, _parts| {
+                cors_runtime
+                    .settings()
+                    .cors_allowed_origins
+                    .iter()
+                    .any(|allowed| allowed.as_bytes() == origin.as_bytes())
+            },
+        ))
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_headers([
+            CONTENT_TYPE,
+            AUTHORIZATION,
+            HeaderName::from_static(middleware::REQUEST_ID_HEADER),
+            HeaderName::from_static(middleware::IDEMPOTENCY_KEY_HEADER),
+        ])
+        .expose_headers([
+            HeaderName::from_static(middleware::REQUEST_ID_HEADER),
+            HeaderName::from_static(middleware::IDEMPOTENT_REPLAYED_HEADER),
+        ]);
+
+    let admin = Router::new()
+        .route(
+            "/api/v1/admin/points/seasons",
+            get(handlers::admin::list_points_seasons).post(handlers::admin::create_points_season),
+        )
+        .route(
+            "/api/v1/admin/points/seasons/{id}",
+            put(handlers::admin::update_points_season),
+        )
+        .route(
+            "/api/v1/admin/points/seasons/{id}/multipliers/{collection_id}",
+            put(handlers::admin::set_points_multiplier),
+        )
+        .route(
+            "/api/v1/admin/points/seasons/{id}/adjustments",
+            post(handlers::admin::adjust_points),
+        )
+        .route(
+            "/api/v1/admin/points/seasons/{id}/recompute",
+            post(handlers::admin::recompute_points_season),
+        )
+        .route(
+            "/api/v1/admin/collections/import",
+            post(handlers::admin::import_collection),
+        )
+        .route(
+            "/api/v1/admin/collections/imports",
+            get(handlers::admin::list_collection_imports),
+        )
+        .route(
+            "/api/v1/admin/collections/imports/{id}",
+            get(handlers::admin::get_collection_import),
+        )
+        .route(
+            "/api/v1/admin/collections/{id}/spam",
+            put(handlers::admin::override_collection_spam),
+        )
+        .route(
+            "/api/v1/admin/nfts/{mint}/spam",
+            put(handlers::admin::override_nft_spam),
+        )
+        .route(
+            "/api/v1/admin/blocklist",
+            get(handlers::admin::list_blocklist).post(handlers::admin::create_blocklist_entry),
+        )
+        .route(
+            "/api/v1/admin/blocklist/{id}",
+            axum::routing::delete(handlers::admin::delete_blocklist_entry),
+        )
+        .route(
+            "/api/v1/admin/users/{wallet}/role",
+            put(handlers::admin::set_user_role),
+        )
+        .route(
+            "/api/v1/admin/records/{table}/{id}/delete",
+            post(handlers::admin::soft_delete_record),
+        )
+        .route(
+            "/api/v1/admin/records/{table}/{id}/restore",
+            post(handlers::admin::restore_record),
+        )
+        .route(
+            "/api/v1/admin/records/{table}/{id}/history",
+            get(handlers::admin::get_record_history),
+        )
+        .route(
+            "/api/v1/admin/history/{id}/revert",
+            post(handlers::admin::revert_history_entry),
+        )
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            middleware::require_admin,
+        ));
+
+    // Write routes refuse blocked wallets and IPs
+    let abuse_guard =
+        axum::middleware::from_fn_with_state(app_state.clone(), middleware::abuse_guard);
+
+    // Moderators manage wallet and IP blocks alongside admins
+    let moderation = Router::new()
+        .route(
+            "/api/v1/admin/access-blocks",
+            get(handlers::admin::list_access_blocks).post(handlers::admin::create_access_block),
+        )
+        .route(
+            "/api/v1/admin/access-blocks/{id}",
+            axum::routing::delete(handlers::admin::delete_access_block),
+        )
+        .route(
+            "/api/v1/admin/access-blocks/automatic/{kind}/{value}",
+            axum::routing::delete(handlers::admin::clear_automatic_block),
+        )
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            middleware::require_moderator,
+        ));
+
+    Router::new()
+        .route("/health", get(handlers::health::health_check))
+        .route("/ready", get(handlers::health::readiness_check))
+        .route("/ws", get(handlers::live::live_events))
+        .route("/api/v1/nfts", get(handlers::nfts::list_nfts))
+        .route("/api/v1/nfts/{mint}", get(handlers::nfts::get_nft))
+        .route(
+            "/api/v1/nfts/{mint}/proof",
+            get(handlers::nfts::get_nft_proof),
+        )
+        .route(
+            "/api/v1/nfts/{mint}/refresh-metadata",
+            post(handlers::nfts::refresh_nft_metadata),
+        )
+        .route(
+            "/api/v1/nfts/{mint}/estimate",
+            get(handlers::nfts::get_nft_estimate),
+        )
+        .route(
+            "/api/v1/nfts/{mint}/price-history",
+            get(handlers::nfts::get_nft_price_history),
+        )
+        .route(
+            "/api/v1/nfts/{mint}/offers",
+            get(handlers::offers::list_nft_offers),
+        )
+        .route(
+            "/api/v1/nfts/{mint}/activities",
+            get(handlers::activities::list_nft_activities),
+        )
+        .route("/api/v1/auctions", get(handlers::auctions::list_auctions))
+        .route(
+            "/api/v1/auctions/{address}",
+            get(handlers::auctions::get_auction),
+        )
+        .route(
+            "/api/v1/collections",
+            get(handlers::collections::list_collections),
+        )
+        .route(
+            "/api/v1/collections/{id}/stats",
+            get(handlers::collections::get_collection_stats),
+        )
+        .route(
+            "/api/v1/collections/{id}/traits",
+            get(handlers::collections::get_collection_traits),
+        )
+        .route(
+            "/api/v1/collections/{id}/holders-history",
+            get(handlers::collections::get_holders_history),
+        )
+        .route(
+            "/api/v1/collections/{id}/snapshot",
+            post(handlers::collections::create_holder_snapshot),
+        )
+        .route(
+            "/api/v1/collections/{id}/churn",
+            get(handlers::collections::get_listing_churn),
+        )
+        .route(
+            "/api/v1/collections/{id}/candles",
+            get(handlers::collections::get_candles),
+        )
+        .route(
+            "/api/v1/collections/{id}/price-history",
+            get(handlers::collections::get_candles),
+        )
+        .route(
+            "/api/v1/collections/{id}/integrations",
+            post(handlers::integrations::create_integration),
+        )
+        .route(
+            "/api/v1/integrations/{id}",
+            get(handlers::integrations::get_integration)
+                .put(handlers::integrations::update_integration)
+                .delete(handlers::integrations::delete_integration),
+        )
+        .route(
+            "/api/v1/creators/{wallet}/royalties",
+            get(handlers::creators::get_creator_royalties),
+        )
+        .route(
+            "/api/v1/creators/{wallet}/dashboard",
+            get(handlers::creators::get_creator_dashboard),
+        )
+        .route(
+            "/api/v1/creators/{wallet}/allowlists",
+            get(handlers::mint::list_creator_allowlists),
+        )
+        .route(
+            "/api/v1/firehose/activities",
+            get(handlers::firehose::get_activities),
+        )
+        .route("/api/v1/stats/daily", get(handlers::stats::get_daily_stats))
+        .route(
+            "/api/v1/stats/dashboard",
+            get(handlers::stats::get_dashboard),
+        )
+        .route(
+            "/api/v1/mint/{config}/allowlist",
+            get(handlers::mint::get_allowlist)
+                .put(handlers::mint::upload_allowlist)
+                .delete(handlers::mint::delete_allowlist),
+        )
+        .route(
+            "/api/v1/mint/{config}/allowlist/entries",
+            get(handlers::mint::list_allowlist_entries)
+                .post(handlers::mint::add_allowlist_entries)
+                .delete(handlers::mint::remove_allowlist_entries),
+        )
+        .route(
+            "/api/v1/mint/{config}/allowlist/import",
+            post(handlers::mint::import_allowlist),
+        )
+        .route(
+            "/api/v1/mint/{config}/proof/{wallet}",
+            get(handlers::mint::get_allowlist_proof),
+        )
+        .route(
+            "/api/v1/launches",
+            get(handlers::launches::list_launches).post(handlers::launches::register_launch),
+        )
+        .route("/api/v1/launches/{id}", get(handlers::launches::get_launch))
+        .route(
+            "/api/v1/launches/{id}/phases",
+            put(handlers::launches::update_launch_phases),
+        )
+        .route(
+            "/api/v1/launches/{id}/mint",
+            post(handlers::launches::build_mint_transaction),
+        )
+        .route(
+            "/api/v1/checkout/orders",
+            post(handlers::checkout::create_checkout_order),
+        )
+        .route(
+            "/api/v1/checkout/orders/{id}",
+            get(handlers::checkout::get_checkout_order),
+        )
+        .route(
+            "/api/v1/checkout/webhook",
+            post(handlers::checkout::checkout_webhook),
+        )
+        .route(
+            "/api/auth/challenge",
+            post(handlers::auth::create_challenge),
+        )
+        .route(
+            "/api/auth/verify",
+            post(handlers::auth::verify).route_layer(abuse_guard.clone()),
+        )
+        .route(
+            "/api/nft/mint",
+            post(handlers::nfts::mint_nft)
+                .route_layer(axum::middleware::from_fn_with_state(
+                    app_state.clone(),
+                    middleware::idempotency,
+                ))
+                .route_layer(abuse_guard.clone()),
+        )
+        .route(
+            "/api/listing/{address}/buy",
+            post(handlers::nfts::buy_listing).route_layer(abuse_guard.clone()),
+        )
+        .route(
+            "/api/offers",
+            post(handlers::offers::make_offer).route_layer(abuse_guard.clone()),
+        )
+        .route(
+            "/api/offers/{id}",
+            axum::routing::delete(handlers::offers::cancel_offer),
+        )
+        .route(
+            "/api/offers/{id}/accept",
+            post(handlers::offers::accept_offer),
+        )
+        .route(
+            "/api/nft/send-transaction",
+            post(handlers::nfts::send_transaction)
+                .route_layer(axum::middleware::from_fn_with_state(
+                    app_state.clone(),
+                    middleware::idempotency,
+                ))
+                .route_layer(abuse_guard.clone()),
+        )
+        .route(
+            "/api/upload/presigned",
+            post(handlers::upload::generate_presigned_url).route_layer(abuse_guard.clone()),
+        )
+        .route(
+            "/api/upload/metadata",
+            post(handlers::upload::upload_metadata).route_layer(abuse_guard.clone()),
+        )
+        .route(
+            "/api/v1/profiles/{username}",
+            get(handlers::users::get_profile),
+        )
+        .route(
+            "/api/v1/usernames/{username}/availability",
+            get(handlers::users::get_username_availability),
+        )
+        .route("/api/v1/users/{wallet}", get(handlers::users::get_user))
+        .route(
+            "/api/v1/users/{wallet}/twitter/challenge",
+            get(handlers::users::get_twitter_challenge),
+        )
+        .route(
+            "/api/v1/users/{wallet}/twitter/verify",
+            post(handlers::users::verify_twitter),
+        )
+        .route(
+            "/api/v1/users/{wallet}",
+            post(handlers::users::create_or_update_user),
+        )
+        .route(
+            "/api/v1/users/{wallet}/points",
+            get(handlers::points::get_user_points),
+        )
+        .route(
+            "/api/v1/points/leaderboard",
+            get(handlers::points::get_leaderboard),
+        )
+        .route(
+            "/api/v1/users/{wallet}/tax-report",
+            get(handlers::users::get_tax_report),
+        )
+        .route(
+            "/api/v1/users/{wallet}/notifications",
+            get(handlers::notifications::list_notifications),
+        )
+        .route(
+            "/api/v1/users/{wallet}/notifications/read",
+            post(handlers::notifications::mark_notifications_read),
+        )
+        .route(
+            "/api/v1/users/{wallet}/notification-preferences",
+            get(handlers::notifications::get_preferences)
+                .put(handlers::notifications::update_preferences),
+        )
+        .route(
+            "/api/v1/users/{wallet}/watchlist",
+            get(handlers::notifications::get_watchlist)
+                .post(handlers::notifications::upsert_watchlist),
+        )
+        .route(
+            "/api/v1/users/{wallet}/watchlist/{collection_id}",
+            axum::routing::delete(handlers::notifications::remove_watchlist),
+        )
+        .route(
+            "/api/v1/users/{wallet}/devices",
+            get(handlers::notifications::list_devices)
+                .post(handlers::notifications::register_device),
+        )
+        .route(
+            "/api/v1/users/{wallet}/devices/{id}",
+            axum::routing::delete(handlers::notifications::remove_device),
+        )
+        .route(
+            "/api/v1/users/{wallet}/email/verify",
+            post(handlers::notifications::send_email_verification),
+        )
+        .route(
+            "/api/v1/email/confirm",
+            post(handlers::notifications::confirm_email),
+        )
+        .route(
+            "/api/v1/users/{wallet}/offers",
+            get(handlers::offers::list_wallet_offers),
+        )
+        .route(
+            "/api/v1/users/{wallet}/bids",
+            get(handlers::auctions::list_wallet_bids),
+        )
+        .route(
+            "/api/v1/users/{wallet}/escrow",
+            get(handlers::offers::get_wallet_escrow)
+                .route_layer(axum::middleware::from_fn(middleware::rpc_scan_limit))
+                .route_layer(abuse_guard.clone()),
+        )
+        .route(
+            "/api/v1/users/{wallet}/activities",
+            get(handlers::activities::list_wallet_activities),
+        )
+        .route(
+            "/api/v1/users/{wallet}/webhooks",
+            get(handlers::webhooks::list_webhooks).post(handlers::webhooks::create_webhook),
+        )
+        .route(
+            "/api/v1/users/{wallet}/webhooks/{id}",
+            put(handlers::webhooks::update_webhook).delete(handlers::webhooks::delete_webhook),
+        )
+        .route(
+            "/api/v1/users/{wallet}/webhooks/{id}/deliveries",
+            get(handlers::webhooks::list_webhook_deliveries),
+        )
+        .route(
+            "/api/v1/users/{wallet}/webhooks/{id}/deliveries/{delivery_id}/redeliver",
+            post(handlers::webhooks::redeliver_webhook_delivery),
+        )
+        .route(
+            "/api/v1/users/{wallet}/checkout-orders",
+            get(handlers::checkout::list_user_checkout_orders),
+        )
+        .route(
+            "/api/v1/users/{wallet}/favorites",
+            get(handlers::users::get_user_favorites),
+        )
+        .route(
+            "/api/v1/users/{wallet}/favorites",
+            post(handlers::users::add_favorite),
+        )
+        .route(
+            "/api/v1/users/{wallet}/favorites/{mint}",
+            axum::routing::delete(handlers::users::remove_favorite),
+        )
+        .route(
+            "/api/v1/users/{wallet}/favorites/collections",
+            get(handlers::users::get_favorite_collections),
+        )
+        .route(
+            "/api/v1/users/{wallet}/favorites/collections",
+            post(handlers::users::add_favorite_collection),
+        )
+        .route(
+            "/api/v1/users/{wallet}/favorites/collections/{collection_id}",
+            axum::routing::delete(handlers::users::remove_favorite_collection),
+        )
+        .merge(admin)
+        .merge(moderation)
+        .layer(
+            ServiceBuilder::new()
+                .layer(axum::middleware::from_fn(middleware::request_id))
+                .layer(cors)
+                .layer(axum::middleware::from_fn_with_state(
+                    app_state.clone(),
+                    middleware::rate_limit,
+                ))
+                .layer(DefaultBodyLimit::max(10 * 1024 * 1024)), // 10MB limit
+        )
+        .with_state(app_state)
+}
@@ -0,0 +1,317 @@
+use chrono::{Duration, NaiveDate};
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+use solmint_backend::{
+    config::Config,
+    database::Database,
+    error::AppError,
+    models::{
+        AdminApiKey, AdminChangeRequest, Collection, CollectionImport, ImportCollectionRequest,
+        IndexerState, MarketplaceStats, IMPORT_COMPLETED, IMPORT_FAILED,
+    },
+    services::{
+        analytics_export::AnalyticsExporter, collection_import::CollectionImporter, rarity,
+    },
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Pause between import batches when driving an import from the CLI
+const IMPORT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Operational tasks against the SolMint database, using the same
+/// configuration as the API and indexer
+#[derive(Parser)]
+#[command(name = "solmint-admin", version)]
+struct Cli {
+    /// Recorded as the actor in row history for changes that keep one
+    #[arg(long, global = true, default_value = "solmint-admin")]
+    actor: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Rebuild derived data
+    #[command(subcommand)]
+    Backfill(Backfill),
+    /// Inspect or move the indexer checkpoint
+    #[command(subcommand)]
+    Indexer(Indexer),
+    /// Queue a collection import from a hashlist or a verified collection address
+    ImportCollection(ImportCollection),
+    /// Recompute trait rarity for one collection, or every stale one
+    RecomputeRarity {
+        collection_id: Option<Uuid>,
+        #[arg(long, conflicts_with = "collection_id")]
+        stale: bool,
+    },
+    /// Mark a collection verified
+    VerifyCollection {
+        collection_id: Uuid,
+        /// Remove the verified badge instead
+        #[arg(long)]
+        unverify: bool,
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Manage admin API keys
+    #[command(subcommand)]
+    ApiKeys(ApiKeys),
+}
+
+#[derive(Subcommand)]
+enum Backfill {
+    /// Re-aggregate daily stats for a date range, or roll up missing days
+    DailyStats {
+        #[arg(long, requires = "to")]
+        from: Option<NaiveDate>,
+        #[arg(long, requires = "from")]
+        to: Option<NaiveDate>,
+    },
+    /// Export analytics partitions that haven't been written yet
+    AnalyticsExport,
+    /// Rebuild the running per-collection aggregates
+    CollectionStats,
+}
+
+#[derive(Subcommand)]
+enum Indexer {
+    /// Show the current checkpoint
+    Status,
+    /// Resume indexing after `slot`
+    ResetCheckpoint {
+        #[arg(long)]
+        slot: u64,
+    },
+}
+
+#[derive(Args)]
+struct ImportCollection {
+    /// File with one mint address per line
+    #[arg(
+        long,
+        conflicts_with = "collection_address",
+        required_unless_present = "collection_address"
+    )]
+    mints_file: Option<std::path::PathBuf>,
+    #[arg(long)]
+    collection_address: Option<String>,
+    /// Import into an existing collection
+    #[arg(long)]
+    collection_id: Option<Uuid>,
+    #[arg(long)]
+    name: Option<String>,
+    #[arg(long)]
+    symbol: Option<String>,
+    #[arg(long)]
+    creator_address: Option<String>,
+    #[arg(long)]
+    verified: bool,
+    /// Work through the import here instead of leaving it to the scheduler
+    #[arg(long)]
+    wait: bool,
+}
+
+#[derive(Subcommand)]
+enum ApiKeys {
+    /// Issue a key; it is printed once
+    Create {
+        #[arg(long)]
+        name: String,
+    },
+    List,
+    /// Issue a replacement; the old key keeps working for the grace period
+    Rotate {
+        id: Uuid,
+        #[arg(long, default_value_t = 24)]
+        grace_hours: i64,
+    },
+    /// Stop accepting a key immediately
+    Revoke {
+        id: Uuid,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<(), AppError> {
+    let cli = Cli::parse();
+    let config = Config::load()?;
+    let db = Database::new(&config).await?;
+
+    if config.run_migrations {
+        db.migrate().await?;
+    }
+
+    run(cli, db.pool(), &config).await
+}
+
+async fn run(cli: Cli, pool: &PgPool, config: &Config) -> Result<(), AppError> {
+    match cli.command {
+        Command::Backfill(Backfill::DailyStats {
+            from: Some(from),
+            to: Some(to),
+        }) => {
+            let mut date = from;
+            while date <= to {
+                MarketplaceStats::create_or_update_daily_stats(pool, date).await?;
+                date += Duration::days(1);
+            }
+            println!("Rolled up daily stats for {} to {}", from, to);
+        }
+        Command::Backfill(Backfill::DailyStats { .. }) => {
+            let days = MarketplaceStats::backfill_missing_days(pool).await?;
+            println!("Rolled up daily stats for {} missing days", days);
+        }
+        Command::Backfill(Backfill::AnalyticsExport) => {
+            let exporter = AnalyticsExporter::new(pool.clone(), config).await;
+            let exported = exporter.export_pending().await?;
+            println!("Exported {} analytics partitions", exported);
+        }
+        Command::Backfill(Backfill::CollectionStats) => {
+            let corrected = Collection::reconcile_stats(pool).await?;
+            println!("Corrected stats for {} collections", corrected);
+        }
+        Command::Indexer(Indexer::Status) => match IndexerState::checkpoint(pool).await? {
+            Some(checkpoint) => print_json(&checkpoint)?,
+            None => println!("The indexer has not run yet"),
+        },
+        Command::Indexer(Indexer::ResetCheckpoint { slot }) => {
+            IndexerState::reset_checkpoint(pool, slot).await?;
+            println!(
+                "Indexer checkpoint set to slot {}; restart the indexer to pick it up",
+                slot
+            );
+        }
+        Command::ImportCollection(args) => import_collection(pool, config, args).await?,
+        Command::RecomputeRarity {
+            collection_id: Some(collection_id),
+            ..
+        } => {
+            Collection::find_by_id(pool, collection_id)
+                .await?
+                .ok_or_else(|| solmint_backend::error::not_found_error("Collection"))?;
+            rarity::recompute_collection(pool, collection_id).await?;
+            println!("Recomputed rarity for {}", collection_id);
+        }
+        Command::RecomputeRarity { stale: true, .. } => {
+            let refreshed = rarity::refresh_stale_collections(pool).await?;
+            println!("Recomputed rarity for {} stale collections", refreshed);
+        }
+        Command::RecomputeRarity { .. } => {
+            return Err(solmint_backend::error::bad_request_error(
+                "pass a collection id or --stale",
+            ));
+        }
+        Command::VerifyCollection {
+            collection_id,
+            unverify,
+            reason,
+        } => {
+            let change = AdminChangeRequest {
+                actor: Some(cli.actor),
+                reason,
+            };
+            let collection =
+                Collection::set_verified(pool, collection_id, !unverify, &change).await?;
+            print_json(&collection)?;
+        }
+        Command::ApiKeys(ApiKeys::Create { name }) => {
+            let (key, secret) = AdminApiKey::create(pool, &name).await?;
+            print_new_key(&key, &secret)?;
+        }
+        Command::ApiKeys(ApiKeys::List) => print_json(&AdminApiKey::list(pool).await?)?,
+        Command::ApiKeys(ApiKeys::Rotate { id, grace_hours }) => {
+            let (key, secret) = AdminApiKey::rotate(pool, id, Duration::hours(grace_hours)).await?;
+            print_new_key(&key, &secret)?;
+            println!("{} stops working in {} hours", id, grace_hours);
+        }
+        Command::ApiKeys(ApiKeys::Revoke { id }) => {
+            if !AdminApiKey::revoke(pool, id).await? {
+                return Err(solmint_backend::error::not_found_error("Active API key"));
+            }
+            println!("Revoked {}", id);
+        }
+    }
+
+    Ok(())
+}
+
+async fn import_collection(
+    pool: &PgPool,
+    config: &Config,
+    args: ImportCollection,
+) -> Result<(), AppError> {
+    let mints = match &args.mints_file {
+        Some(path) => {
+            let contents = std::fs::read_to_string(path).map_err(|e| {
+                AppError::BadRequest(format!("could not read {}: {}", path.display(), e))
+            })?;
+            Some(
+                contents
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            )
+        }
+        None => None,
+    };
+
+    let importer = CollectionImporter::new(config);
+    let (collection, import) = importer
+        .start(
+            pool,
+            ImportCollectionRequest {
+                collection_id: args.collection_id,
+                name: args.name,
+                symbol: args.symbol,
+                description: None,
+                image_url: None,
+                creator_address: args.creator_address,
+                verified: Some(args.verified),
+                mints,
+                collection_address: args.collection_address,
+            },
+        )
+        .await?;
+    println!(
+        "Queued import {} into collection {} ({})",
+        import.id, collection.name, collection.id
+    );
+
+    if !args.wait {
+        return Ok(());
+    }
+
+    // Imports are worked oldest first, so earlier queued ones finish before this one
+    loop {
+        importer.run(pool).await?;
+        let import = CollectionImport::find_by_id(pool, import.id)
+            .await?
+            .ok_or_else(|| solmint_backend::error::not_found_error("Collection import"))?;
+        println!(
+            "{}: {}/{} imported, {} failed",
+            import.status, import.imported_items, import.total_items, import.failed_items
+        );
+
+        if import.status == IMPORT_COMPLETED || import.status == IMPORT_FAILED {
+            return Ok(());
+        }
+        tokio::time::sleep(IMPORT_POLL_INTERVAL).await;
+    }
+}
+
+fn print_new_key(key: &AdminApiKey, secret: &str) -> Result<(), AppError> {
+    print_json(key)?;
+    println!("Key (shown once): {}", secret);
+    Ok(())
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), AppError> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
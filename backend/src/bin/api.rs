@@ -0,0 +1,38 @@
+use solmint_backend::{
+    app, config::Config, database::Database, error::AppError, seed,
+    services::supervisor::Supervisor, startup,
+};
+
+#[tokio::main]
+async fn main() -> Result<(), AppError> {
+    app::check_config_flag()?;
+
+    // Load configuration
+    let config = Config::load()?;
+
+    // Initialize database, waiting for it to come up
+    let db = startup::retry(&config, "Postgres", || Database::new(&config)).await?;
+    startup::wait_for_solana_rpc(&config).await?;
+
+    // `--seed` loads demo data for local development and exits
+    let seed_only = std::env::args().any(|arg| arg == "--seed");
+
+    // Run migrations
+    if config.run_migrations || seed_only {
+        db.migrate().await?;
+    }
+
+    if seed_only {
+        seed::run(db.pool()).await?;
+        return Ok(());
+    }
+
+    let supervisor = Supervisor::new();
+
+    // Small deployments can keep the indexer and scheduled jobs in the API process
+    if config.embedded_indexer {
+        app::spawn_background_services(&supervisor, &db, &config);
+    }
+
+    app::serve_api(config, db, supervisor).await
+}
@@ -0,0 +1,136 @@
+use clap::{Parser, Subcommand};
+use solmint_backend::{
+    config::Config,
+    database::Database,
+    error::AppError,
+    loadtest::{self, LoadTest},
+    synthetic::{self, Scale},
+};
+use std::time::Duration;
+
+/// Development tools: synthetic data at scale and a load-test driver for
+/// the hot read endpoints. Built only with `--features dev-tools`.
+#[derive(Parser)]
+#[command(name = "solmint-dev")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Fill the configured database with synthetic marketplace data
+    Generate {
+        #[arg(long, default_value_t = 20)]
+        collections: u32,
+        #[arg(long, default_value_t = 1_000)]
+        nfts_per_collection: u32,
+        #[arg(long, default_value_t = 5_000)]
+        wallets: u32,
+        #[arg(long, default_value_t = 15)]
+        listed_percent: u32,
+        #[arg(long, default_value_t = 2_000)]
+        sales_per_collection: u32,
+        #[arg(long, default_value_t = 90)]
+        days: u32,
+        #[arg(long, default_value_t = 1)]
+        seed: u32,
+    },
+    /// Drive concurrent load against a running API and report latencies
+    Load {
+        #[arg(long, default_value = "http://localhost:8080")]
+        base_url: String,
+        #[arg(long, default_value_t = 16)]
+        concurrency: usize,
+        #[arg(long, default_value_t = 30)]
+        duration_secs: u64,
+        /// Run only these scenarios (repeatable)
+        #[arg(long = "scenario")]
+        scenarios: Vec<String>,
+        /// Exit with an error when any scenario's p95 exceeds this
+        #[arg(long)]
+        max_p95_ms: Option<f64>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<(), AppError> {
+    match Cli::parse().command {
+        Command::Generate {
+            collections,
+            nfts_per_collection,
+            wallets,
+            listed_percent,
+            sales_per_collection,
+            days,
+            seed,
+        } => {
+            let config = Config::load()?;
+            let db = Database::new(&config).await?;
+            db.migrate().await?;
+
+            let scale = Scale {
+                collections,
+                nfts_per_collection,
+                wallets,
+                listed_percent: listed_percent.min(100),
+                sales_per_collection,
+                days: days.max(1),
+                seed,
+            };
+            let summary = synthetic::generate(db.pool(), &scale).await?;
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        }
+        Command::Load {
+            base_url,
+            concurrency,
+            duration_secs,
+            scenarios,
+            max_p95_ms,
+        } => {
+            let report = loadtest::run(&LoadTest {
+                base_url,
+                concurrency,
+                duration: Duration::from_secs(duration_secs),
+                scenarios,
+            })
+            .await?;
+
+            println!(
+                "{:<22} {:>8} {:>7} {:>9} {:>9} {:>9} {:>9}",
+                "scenario", "requests", "errors", "p50 ms", "p95 ms", "p99 ms", "max ms"
+            );
+            for scenario in &report.scenarios {
+                println!(
+                    "{:<22} {:>8} {:>7} {:>9.1} {:>9.1} {:>9.1} {:>9.1}",
+                    scenario.name,
+                    scenario.requests,
+                    scenario.errors,
+                    scenario.p50_ms,
+                    scenario.p95_ms,
+                    scenario.p99_ms,
+                    scenario.max_ms
+                );
+            }
+            println!(
+                "{:.0} requests/s over {:.1}s",
+                report.requests_per_sec, report.duration_secs
+            );
+
+            if let Some(limit) = max_p95_ms {
+                let slow: Vec<&str> = report
+                    .scenarios
+                    .iter()
+                    .filter(|scenario| scenario.p95_ms > limit)
+                    .map(|scenario| scenario.name)
+                    .collect();
+                if !slow.is_empty() {
+                    eprintln!("p95 above {} ms: {}", limit, slow.join(", "));
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
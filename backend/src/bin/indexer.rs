@@ -0,0 +1,29 @@
+use solmint_backend::{
+    app, config::Config, database::Database, error::AppError, services::supervisor::Supervisor,
+    startup,
+};
+
+#[tokio::main]
+async fn main() -> Result<(), AppError> {
+    app::check_config_flag()?;
+
+    // Load configuration
+    let config = Config::load()?;
+
+    // Initialize database, waiting for it to come up
+    let db = startup::retry(&config, "Postgres", || Database::new(&config)).await?;
+    startup::wait_for_solana_rpc(&config).await?;
+
+    // Run migrations
+    if config.run_migrations {
+        db.migrate().await?;
+    }
+
+    let supervisor = Supervisor::new();
+    app::spawn_background_services(&supervisor, &db, &config);
+
+    app::shutdown_signal().await;
+    supervisor.shutdown().await;
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use redis::{aio::MultiplexedConnection, AsyncCommands};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::AppError;
+
+/// Read a JSON value from Redis. Misses and undecodable entries return `None`.
+pub async fn get_json<T: DeserializeOwned>(
+    redis: &MultiplexedConnection,
+    key: &str,
+) -> Result<Option<T>, AppError> {
+    let mut conn = redis.clone();
+    let cached: Option<String> = conn.get(key).await?;
+
+    Ok(cached.and_then(|value| serde_json::from_str(&value).ok()))
+}
+
+/// Store a JSON value in Redis with a TTL
+pub async fn set_json<T: Serialize>(
+    redis: &MultiplexedConnection,
+    key: &str,
+    value: &T,
+    ttl_seconds: u64,
+) -> Result<(), AppError> {
+    let mut conn = redis.clone();
+    let _: () = conn
+        .set_ex(key, serde_json::to_string(value)?, ttl_seconds)
+        .await?;
+
+    Ok(())
+}
+
+/// Read and delete a JSON value in one step, so only one caller gets it
+pub async fn take_json<T: DeserializeOwned>(
+    redis: &MultiplexedConnection,
+    key: &str,
+) -> Result<Option<T>, AppError> {
+    let mut conn = redis.clone();
+    let taken: Option<String> = redis::cmd("GETDEL").arg(key).query_async(&mut conn).await?;
+
+    Ok(taken.and_then(|value| serde_json::from_str(&value).ok()))
+}
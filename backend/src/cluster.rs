@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use solana_commitment_config::CommitmentConfig;
+use std::fmt;
+
+/// Solana cluster profile. Bundles the endpoints, commitment level and
+/// explorer links that belong together so they can't be mixed by accident.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SolanaCluster {
+    Localnet,
+    Devnet,
+    Testnet,
+    #[serde(alias = "mainnet")]
+    MainnetBeta,
+}
+
+impl SolanaCluster {
+    pub const ALL: [SolanaCluster; 4] = [
+        SolanaCluster::Localnet,
+        SolanaCluster::Devnet,
+        SolanaCluster::Testnet,
+        SolanaCluster::MainnetBeta,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SolanaCluster::Localnet => "localnet",
+            SolanaCluster::Devnet => "devnet",
+            SolanaCluster::Testnet => "testnet",
+            SolanaCluster::MainnetBeta => "mainnet-beta",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "localnet" => Some(SolanaCluster::Localnet),
+            "devnet" => Some(SolanaCluster::Devnet),
+            "testnet" => Some(SolanaCluster::Testnet),
+            "mainnet" | "mainnet-beta" => Some(SolanaCluster::MainnetBeta),
+            _ => None,
+        }
+    }
+
+    pub fn default_rpc_url(&self) -> &'static str {
+        match self {
+            SolanaCluster::Localnet => "http://127.0.0.1:8899",
+            SolanaCluster::Devnet => "https://api.devnet.solana.com",
+            SolanaCluster::Testnet => "https://api.testnet.solana.com",
+            SolanaCluster::MainnetBeta => "https://api.mainnet-beta.solana.com",
+        }
+    }
+
+    pub fn default_ws_url(&self) -> &'static str {
+        match self {
+            SolanaCluster::Localnet => "ws://127.0.0.1:8900",
+            SolanaCluster::Devnet => "wss://api.devnet.solana.com",
+            SolanaCluster::Testnet => "wss://api.testnet.solana.com",
+            SolanaCluster::MainnetBeta => "wss://api.mainnet-beta.solana.com",
+        }
+    }
+
+    /// Mainnet indexes finalized data; the test clusters favour latency
+    pub fn default_commitment(&self) -> &'static str {
+        match self {
+            SolanaCluster::MainnetBeta => "finalized",
+            _ => "confirmed",
+        }
+    }
+
+    pub fn explorer_tx_url(&self, signature: &str) -> String {
+        format!(
+            "https://explorer.solana.com/tx/{}{}",
+            signature,
+            self.explorer_query()
+        )
+    }
+
+    pub fn explorer_address_url(&self, address: &str) -> String {
+        format!(
+            "https://explorer.solana.com/address/{}{}",
+            address,
+            self.explorer_query()
+        )
+    }
+
+    fn explorer_query(&self) -> &'static str {
+        match self {
+            SolanaCluster::Localnet => "?cluster=custom&customUrl=http%3A%2F%2F127.0.0.1%3A8899",
+            SolanaCluster::Devnet => "?cluster=devnet",
+            SolanaCluster::Testnet => "?cluster=testnet",
+            SolanaCluster::MainnetBeta => "",
+        }
+    }
+}
+
+impl fmt::Display for SolanaCluster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parse a commitment level name as used in configuration
+pub fn parse_commitment(value: &str) -> Option<CommitmentConfig> {
+    match value {
+        "processed" => Some(CommitmentConfig::processed()),
+        "confirmed" => Some(CommitmentConfig::confirmed()),
+        "finalized" => Some(CommitmentConfig::finalized()),
+        _ => None,
+    }
+}
@@ -1,5 +1,22 @@
-use crate::error::AppError;
-use sqlx::{postgres::PgPoolOptions, PgPool};
+use crate::{config::Config, error::AppError};
+use sqlx::{
+    migrate::Migrator,
+    postgres::{PgConnectOptions, PgPoolOptions},
+    PgConnection, PgPool,
+};
+use std::{
+    future::Future,
+    str::FromStr,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+static SLOW_QUERY_THRESHOLD: OnceLock<Duration> = OnceLock::new();
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Advisory lock key guarding the migration step ("solmint" in ASCII)
+const MIGRATION_LOCK_KEY: i64 = 0x736f_6c6d_696e_74;
 
 #[derive(Clone)]
 pub struct Database {
@@ -7,10 +24,16 @@ pub struct Database {
 }
 
 impl Database {
-    pub async fn new(database_url: &str) -> Result<Self, AppError> {
+    pub async fn new(config: &Config) -> Result<Self, AppError> {
+        SLOW_QUERY_THRESHOLD.get_or_init(|| Duration::from_millis(config.slow_query_threshold_ms));
+
+        // Cap every statement server-side so a runaway search can't pin the database
+        let connect_options = PgConnectOptions::from_str(&config.database_url)?
+            .options([("statement_timeout", config.statement_timeout_ms.to_string())]);
+
         let pool = PgPoolOptions::new()
             .max_connections(20)
-            .connect(database_url)
+            .connect_with(connect_options)
             .await?;
 
         Ok(Database { pool })
@@ -20,11 +43,105 @@ impl Database {
         &self.pool
     }
 
+    /// Run pending migrations while holding a Postgres advisory lock.
+    ///
+    /// When several replicas start at once only the instance that wins the
+    /// lock migrates; the others wait for it to finish and then verify that
+    /// every embedded migration has been applied.
     pub async fn migrate(&self) -> Result<(), AppError> {
-        // Run migrations using sqlx migrate
-        // sqlx::migrate!("./migrations").run(&self.pool).await?;
+        let mut conn = self.pool.acquire().await?;
+
+        // Migrations and lock waits may legitimately outlast the pool's statement timeout
+        sqlx::query("SET statement_timeout = 0")
+            .execute(&mut *conn)
+            .await?;
+
+        let leader: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .fetch_one(&mut *conn)
+            .await?;
+
+        let result = if leader {
+            MIGRATOR.run(&mut *conn).await.map_err(AppError::from)
+        } else {
+            println!("Another instance is running migrations, waiting for it to finish...");
+            sqlx::query("SELECT pg_advisory_lock($1)")
+                .bind(MIGRATION_LOCK_KEY)
+                .execute(&mut *conn)
+                .await?;
+            verify_migrations(&mut conn).await
+        };
+
+        sqlx::query("SELECT pg_advisory_unlock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query("RESET statement_timeout")
+            .execute(&mut *conn)
+            .await?;
+        result?;
 
-        println!("Database migrations completed successfully");
+        if leader {
+            println!("Database migrations completed successfully");
+        } else {
+            println!("Database migrations verified");
+        }
         Ok(())
     }
 }
+
+/// Check that every embedded migration is recorded as successfully applied
+async fn verify_migrations(conn: &mut PgConnection) -> Result<(), AppError> {
+    let applied: Vec<i64> =
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success = TRUE")
+            .fetch_all(&mut *conn)
+            .await?;
+
+    let missing: Vec<String> = MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .filter(|migration| !applied.contains(&migration.version))
+        .map(|migration| format!("{} ({})", migration.version, migration.description))
+        .collect();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::ConfigError(format!(
+            "migrations not applied after waiting for the migration lock: {}",
+            missing.join(", ")
+        )))
+    }
+}
+
+/// Await a query and log it when it runs longer than the slow query threshold.
+///
+/// `summary` is only evaluated for slow queries, so it can afford to format
+/// the bound filters in detail.
+pub async fn instrument<T, F>(
+    label: &str,
+    summary: impl FnOnce() -> String,
+    query: F,
+) -> Result<T, sqlx::Error>
+where
+    F: Future<Output = Result<T, sqlx::Error>>,
+{
+    let started = Instant::now();
+    let result = query.await;
+    let elapsed = started.elapsed();
+
+    let threshold = SLOW_QUERY_THRESHOLD
+        .get()
+        .copied()
+        .unwrap_or(Duration::from_millis(500));
+    if elapsed >= threshold {
+        println!(
+            "Slow query [{}] took {}ms: {}",
+            label,
+            elapsed.as_millis(),
+            summary()
+        );
+    }
+
+    result
+}
@@ -1,9 +1,15 @@
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
+use nft_marketplace_sdk::MarketplaceError;
+use serde::Serialize;
 use serde_json::json;
+use solana_sdk::{
+    instruction::InstructionError, message::VersionedMessage, pubkey::Pubkey,
+    transaction::TransactionError,
+};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -11,18 +17,36 @@ pub enum AppError {
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 
+    #[error("Migration error: {0}")]
+    Migration(#[from] sqlx::migrate::MigrateError),
+
     #[error("Redis error: {0}")]
     Redis(#[from] redis::RedisError),
 
     #[error("Solana client error: {0}")]
     SolanaClient(#[from] solana_client::client_error::ClientError),
 
+    #[error("Scheduler error: {0}")]
+    Scheduler(#[from] tokio_cron_scheduler::JobSchedulerError),
+
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+
+    #[error("Object storage error: {0}")]
+    Storage(String),
+
+    #[error("Upstream service error: {0}")]
+    Upstream(String),
+
+    #[error("Email error: {0}")]
+    Email(String),
+
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
@@ -31,47 +55,333 @@ pub enum AppError {
 
     #[error("Bad request: {0}")]
     BadRequest(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+
+    #[error("Serialization error: {0}")]
+    SerializationError(String),
+
+    /// Refused for a reason clients can act on, e.g. a program error
+    #[error("Rejected: {1}")]
+    Rejected(ErrorCode, String),
+
+    #[error("Rate limit exceeded")]
+    RateLimited,
+
+    #[error("Unauthorized")]
+    Unauthorized,
+
+    /// Authenticated, but not allowed to do this
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+}
+
+/// Stable, machine-readable error codes sent as `code` in every error body.
+/// Clients branch on these, so existing codes must never be renamed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    InternalError,
+    DatabaseError,
+    CacheError,
+    ConfigError,
+    BlockchainError,
+    UpstreamError,
+    ValidationError,
+    NotFound,
+    Conflict,
+    RateLimited,
+    Unauthorized,
+    Forbidden,
+    // The wallet or IP is blocked, by a moderator or for repeated failures
+    AccessBlocked,
+    // The transaction was refused before relaying
+    TransactionNotAllowed,
+    // The transaction was rejected by the cluster
+    BlockhashExpired,
+    InsufficientFunds,
+    TransactionFailed,
+    // Marketplace program errors, see `solana-program/src/error.rs`
+    InvalidInstruction,
+    NotRentExempt,
+    PriceMismatch,
+    AmountOverflow,
+    InvalidAccountOwner,
+    AccountNotInitialized,
+    AccountAlreadyInitialized,
+    InvalidMarketplaceAuthority,
+    InvalidSeller,
+    InvalidBuyer,
+    ListingNotActive,
+    InvalidPrice,
+    InvalidFeePercentage,
+    FeeCalculationError,
+    OfferExpired,
+    AuctionEnded,
+    AuctionNotEnded,
+    BidTooLow,
+    InvalidCollection,
+    InvalidUpdateAuthority,
+    InvalidMetadata,
+    InvalidBundle,
+    RentalActive,
+    RentalNotExpired,
+    MintNotLive,
+    MintSoldOut,
+    NotOnAllowlist,
+    WalletLimitReached,
+    MarketplaceNeedsMigration,
+    InsufficientRewards,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::InternalError => "INTERNAL_ERROR",
+            ErrorCode::DatabaseError => "DATABASE_ERROR",
+            ErrorCode::CacheError => "CACHE_ERROR",
+            ErrorCode::ConfigError => "CONFIG_ERROR",
+            ErrorCode::BlockchainError => "BLOCKCHAIN_ERROR",
+            ErrorCode::UpstreamError => "UPSTREAM_ERROR",
+            ErrorCode::ValidationError => "VALIDATION_ERROR",
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::Conflict => "CONFLICT",
+            ErrorCode::RateLimited => "RATE_LIMITED",
+            ErrorCode::Unauthorized => "UNAUTHORIZED",
+            ErrorCode::Forbidden => "FORBIDDEN",
+            ErrorCode::AccessBlocked => "ACCESS_BLOCKED",
+            ErrorCode::TransactionNotAllowed => "TRANSACTION_NOT_ALLOWED",
+            ErrorCode::BlockhashExpired => "BLOCKHASH_EXPIRED",
+            ErrorCode::InsufficientFunds => "INSUFFICIENT_FUNDS",
+            ErrorCode::TransactionFailed => "TRANSACTION_FAILED",
+            ErrorCode::InvalidInstruction => "INVALID_INSTRUCTION",
+            ErrorCode::NotRentExempt => "NOT_RENT_EXEMPT",
+            ErrorCode::PriceMismatch => "PRICE_MISMATCH",
+            ErrorCode::AmountOverflow => "AMOUNT_OVERFLOW",
+            ErrorCode::InvalidAccountOwner => "INVALID_ACCOUNT_OWNER",
+            ErrorCode::AccountNotInitialized => "ACCOUNT_NOT_INITIALIZED",
+            ErrorCode::AccountAlreadyInitialized => "ACCOUNT_ALREADY_INITIALIZED",
+            ErrorCode::InvalidMarketplaceAuthority => "INVALID_MARKETPLACE_AUTHORITY",
+            ErrorCode::InvalidSeller => "INVALID_SELLER",
+            ErrorCode::InvalidBuyer => "INVALID_BUYER",
+            ErrorCode::ListingNotActive => "LISTING_NOT_ACTIVE",
+            ErrorCode::InvalidPrice => "INVALID_PRICE",
+            ErrorCode::InvalidFeePercentage => "INVALID_FEE_PERCENTAGE",
+            ErrorCode::FeeCalculationError => "FEE_CALCULATION_ERROR",
+            ErrorCode::OfferExpired => "OFFER_EXPIRED",
+            ErrorCode::AuctionEnded => "AUCTION_ENDED",
+            ErrorCode::AuctionNotEnded => "AUCTION_NOT_ENDED",
+            ErrorCode::BidTooLow => "BID_TOO_LOW",
+            ErrorCode::InvalidCollection => "INVALID_COLLECTION",
+            ErrorCode::InvalidUpdateAuthority => "INVALID_UPDATE_AUTHORITY",
+            ErrorCode::InvalidMetadata => "INVALID_METADATA",
+            ErrorCode::InvalidBundle => "INVALID_BUNDLE",
+            ErrorCode::RentalActive => "RENTAL_ACTIVE",
+            ErrorCode::RentalNotExpired => "RENTAL_NOT_EXPIRED",
+            ErrorCode::MintNotLive => "MINT_NOT_LIVE",
+            ErrorCode::MintSoldOut => "MINT_SOLD_OUT",
+            ErrorCode::NotOnAllowlist => "NOT_ON_ALLOWLIST",
+            ErrorCode::WalletLimitReached => "WALLET_LIMIT_REACHED",
+            ErrorCode::MarketplaceNeedsMigration => "MARKETPLACE_NEEDS_MIGRATION",
+            ErrorCode::InsufficientRewards => "INSUFFICIENT_REWARDS",
+        }
+    }
+
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorCode::InternalError
+            | ErrorCode::DatabaseError
+            | ErrorCode::CacheError
+            | ErrorCode::ConfigError => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::BlockchainError | ErrorCode::UpstreamError => StatusCode::BAD_GATEWAY,
+            ErrorCode::ValidationError => StatusCode::BAD_REQUEST,
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::Conflict
+            | ErrorCode::ListingNotActive
+            | ErrorCode::RentalActive
+            | ErrorCode::MintSoldOut => StatusCode::CONFLICT,
+            ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden | ErrorCode::AccessBlocked => StatusCode::FORBIDDEN,
+            _ => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+
+    /// The code for a `MarketplaceError`, which the program reports as
+    /// `Custom(discriminant)`
+    pub fn from_marketplace_error(code: u32) -> Option<Self> {
+        let code = match MarketplaceError::from_code(code)? {
+            MarketplaceError::InvalidInstruction => ErrorCode::InvalidInstruction,
+            MarketplaceError::NotRentExempt => ErrorCode::NotRentExempt,
+            MarketplaceError::ExpectedAmountMismatch => ErrorCode::PriceMismatch,
+            MarketplaceError::AmountOverflow => ErrorCode::AmountOverflow,
+            MarketplaceError::InvalidAccountOwner => ErrorCode::InvalidAccountOwner,
+            MarketplaceError::AccountNotInitialized => ErrorCode::AccountNotInitialized,
+            MarketplaceError::AccountAlreadyInitialized => ErrorCode::AccountAlreadyInitialized,
+            MarketplaceError::InvalidMarketplaceAuthority => ErrorCode::InvalidMarketplaceAuthority,
+            MarketplaceError::InvalidSeller => ErrorCode::InvalidSeller,
+            MarketplaceError::InvalidBuyer => ErrorCode::InvalidBuyer,
+            MarketplaceError::NftNotForSale => ErrorCode::ListingNotActive,
+            MarketplaceError::InsufficientFunds => ErrorCode::InsufficientFunds,
+            MarketplaceError::InvalidPrice => ErrorCode::InvalidPrice,
+            MarketplaceError::InvalidFeePercentage => ErrorCode::InvalidFeePercentage,
+            MarketplaceError::MarketplaceFeeCalculationError => ErrorCode::FeeCalculationError,
+            MarketplaceError::OfferExpired => ErrorCode::OfferExpired,
+            MarketplaceError::AuctionEnded => ErrorCode::AuctionEnded,
+            MarketplaceError::AuctionNotEnded => ErrorCode::AuctionNotEnded,
+            MarketplaceError::BidTooLow => ErrorCode::BidTooLow,
+            MarketplaceError::InvalidCollection => ErrorCode::InvalidCollection,
+            MarketplaceError::InvalidUpdateAuthority => ErrorCode::InvalidUpdateAuthority,
+            MarketplaceError::InvalidMetadata => ErrorCode::InvalidMetadata,
+            MarketplaceError::InvalidBundle => ErrorCode::InvalidBundle,
+            MarketplaceError::RentalActive => ErrorCode::RentalActive,
+            MarketplaceError::RentalNotExpired => ErrorCode::RentalNotExpired,
+            MarketplaceError::MintNotLive => ErrorCode::MintNotLive,
+            MarketplaceError::MintSoldOut => ErrorCode::MintSoldOut,
+            MarketplaceError::NotOnAllowlist => ErrorCode::NotOnAllowlist,
+            MarketplaceError::WalletLimitReached => ErrorCode::WalletLimitReached,
+            MarketplaceError::MarketplaceNeedsMigration => ErrorCode::MarketplaceNeedsMigration,
+            MarketplaceError::InsufficientRewards => ErrorCode::InsufficientRewards,
+        };
+        Some(code)
+    }
+
+    fn from_transaction_error(error: &TransactionError) -> Self {
+        match error {
+            TransactionError::BlockhashNotFound => ErrorCode::BlockhashExpired,
+            TransactionError::InsufficientFundsForFee
+            | TransactionError::InsufficientFundsForRent { .. }
+            | TransactionError::InstructionError(_, InstructionError::InsufficientFunds) => {
+                ErrorCode::InsufficientFunds
+            }
+            _ => ErrorCode::TransactionFailed,
+        }
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
 }
 
 impl IntoResponse for AppError {
+    /// An RFC 7807 problem document. `code` and `request_id` are extension
+    /// members; server-side failures get a generic `detail` so internals
+    /// don't leak.
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Database error"),
-            AppError::Redis(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Cache error"),
-            AppError::SolanaClient(_) => (StatusCode::BAD_GATEWAY, "Blockchain service error"),
-            AppError::Serialization(_) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, "Serialization error")
+        let code = self.code();
+        let status = code.status();
+        let detail = match &self {
+            AppError::Database(_) => "Database error".to_string(),
+            AppError::Migration(_) => "Migration error".to_string(),
+            AppError::Redis(_) => "Cache error".to_string(),
+            AppError::SolanaClient(e) => match e.get_transaction_error() {
+                Some(error) => error.to_string(),
+                None => "Blockchain service error".to_string(),
+            },
+            AppError::Scheduler(_) => "Scheduler error".to_string(),
+            AppError::Serialization(_) | AppError::SerializationError(_) => {
+                "Serialization error".to_string()
             }
-            AppError::Io(_) => (StatusCode::INTERNAL_SERVER_ERROR, "IO error"),
-            AppError::ConfigError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Configuration error"),
-            AppError::NotFound(ref msg) => (StatusCode::NOT_FOUND, msg.as_str()),
-            AppError::BadRequest(ref msg) => (StatusCode::BAD_REQUEST, msg.as_str()),
+            AppError::Io(_) => "IO error".to_string(),
+            AppError::Parquet(_) => "Export error".to_string(),
+            AppError::Storage(_) => "Object storage error".to_string(),
+            AppError::Upstream(_) => "Upstream service error".to_string(),
+            AppError::Email(_) => "Email delivery error".to_string(),
+            AppError::ConfigError(_) => "Configuration error".to_string(),
+            AppError::NotFound(msg)
+            | AppError::BadRequest(msg)
+            | AppError::ValidationError(msg)
+            | AppError::Conflict(msg)
+            | AppError::Forbidden(msg)
+            | AppError::Rejected(_, msg) => msg.clone(),
+            AppError::RateLimited => "Too many requests".to_string(),
+            AppError::Unauthorized => "Unauthorized".to_string(),
         };
 
         let body = Json(json!({
-            "error": {
-                "message": error_message,
-                "type": self.error_type(),
-            }
+            "type": format!("urn:solmint:error:{}", code.as_str()),
+            "title": status.canonical_reason().unwrap_or("Error"),
+            "status": status.as_u16(),
+            "detail": detail,
+            "code": code,
+            "request_id": crate::middleware::current_request_id(),
         }));
 
-        (status, body).into_response()
+        (
+            status,
+            [(header::CONTENT_TYPE, "application/problem+json")],
+            body,
+        )
+            .into_response()
     }
 }
 
 impl AppError {
-    fn error_type(&self) -> &'static str {
+    pub fn code(&self) -> ErrorCode {
         match self {
-            AppError::Database(_) => "database_error",
-            AppError::Redis(_) => "cache_error",
-            AppError::SolanaClient(_) => "blockchain_error",
-            AppError::Serialization(_) => "serialization_error",
-            AppError::Io(_) => "io_error",
-            AppError::ConfigError(_) => "config_error",
-            AppError::NotFound(_) => "not_found",
-            AppError::BadRequest(_) => "bad_request",
+            AppError::Database(_) => ErrorCode::DatabaseError,
+            AppError::Redis(_) => ErrorCode::CacheError,
+            AppError::SolanaClient(e) => match e.get_transaction_error() {
+                Some(error) => ErrorCode::from_transaction_error(&error),
+                None => ErrorCode::BlockchainError,
+            },
+            AppError::Migration(_)
+            | AppError::Scheduler(_)
+            | AppError::Serialization(_)
+            | AppError::SerializationError(_)
+            | AppError::Io(_)
+            | AppError::Parquet(_) => ErrorCode::InternalError,
+            AppError::Storage(_) | AppError::Upstream(_) | AppError::Email(_) => {
+                ErrorCode::UpstreamError
+            }
+            AppError::ConfigError(_) => ErrorCode::ConfigError,
+            AppError::NotFound(_) => ErrorCode::NotFound,
+            AppError::BadRequest(_) | AppError::ValidationError(_) => ErrorCode::ValidationError,
+            AppError::Conflict(_) => ErrorCode::Conflict,
+            AppError::Rejected(code, _) => *code,
+            AppError::RateLimited => ErrorCode::RateLimited,
+            AppError::Unauthorized => ErrorCode::Unauthorized,
+            AppError::Forbidden(_) => ErrorCode::Forbidden,
         }
     }
+
+    /// Map a failed send of `message` to the marketplace program's error code
+    /// when one of its instructions failed; other failures keep their
+    /// cluster-level code
+    pub fn from_transaction_failure(
+        error: solana_client::client_error::ClientError,
+        message: &VersionedMessage,
+        program_id: &Pubkey,
+    ) -> Self {
+        if let Some(TransactionError::InstructionError(index, InstructionError::Custom(custom))) =
+            error.get_transaction_error()
+        {
+            // Program ids are never loaded from lookup tables, so the static
+            // keys always cover them
+            let failed_program = message
+                .instructions()
+                .get(index as usize)
+                .map(|instruction| instruction.program_id(message.static_account_keys()));
+            if failed_program == Some(program_id) {
+                if let Some(code) = ErrorCode::from_marketplace_error(custom) {
+                    return AppError::Rejected(code, error.to_string());
+                }
+            }
+        }
+
+        AppError::SolanaClient(error)
+    }
+}
+
+impl From<::config::ConfigError> for AppError {
+    fn from(e: ::config::ConfigError) -> Self {
+        AppError::ConfigError(e.to_string())
+    }
 }
 
 // Helper function to create not found errors
@@ -83,3 +393,34 @@ pub fn not_found_error(resource: &str) -> AppError {
 pub fn bad_request_error(msg: &str) -> AppError {
     AppError::BadRequest(msg.to_string())
 }
+
+// Helper function for requests naming a wallet other than the signed-in one
+pub fn wrong_wallet_error() -> AppError {
+    AppError::Forbidden("signed in as a different wallet".to_string())
+}
+
+// Helper function to create optimistic concurrency conflict errors
+pub fn conflict_error(msg: &str) -> AppError {
+    AppError::Conflict(msg.to_string())
+}
+
+/// Attempts made by `retry_on_conflict` before giving up
+const CONFLICT_RETRIES: usize = 3;
+
+/// Run a read-modify-write closure again whenever it fails with
+/// `AppError::Conflict`, i.e. when a compare-and-swap update lost a race. The
+/// closure must re-read the row each time so it writes against the latest
+/// version.
+pub async fn retry_on_conflict<T, F, Fut>(mut attempt: F) -> Result<T, AppError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, AppError>>,
+{
+    let mut tries = 1;
+    loop {
+        match attempt().await {
+            Err(AppError::Conflict(_)) if tries < CONFLICT_RETRIES => tries += 1,
+            result => return result,
+        }
+    }
+}
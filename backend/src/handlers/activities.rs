@@ -0,0 +1,99 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+
+use super::AppState;
+use crate::{
+    error::AppError,
+    models::{Activity, ActivityQuery, ActivitySubject, Nft, ACTIVITY_TYPES},
+    utils::pagination::{PageParams, Paginated},
+};
+
+/// An NFT's activity, newest first. Pages by `page` or by `cursor`.
+pub async fn list_nft_activities(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Query(query): Query<ActivityQuery>,
+    page: PageParams,
+) -> Result<Json<Paginated<Activity>>, AppError> {
+    let types = activity_types(&query)?;
+    let nft = Nft::find_by_mint(&state.db, &mint)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("NFT"))?;
+
+    list(
+        &state,
+        ActivitySubject::Nft(&nft.mint_address),
+        &types,
+        &page,
+    )
+    .await
+}
+
+/// Activity from or to a wallet, newest first
+pub async fn list_wallet_activities(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+    Query(query): Query<ActivityQuery>,
+    page: PageParams,
+) -> Result<Json<Paginated<Activity>>, AppError> {
+    let types = activity_types(&query)?;
+    let wallet_address = state.sns().resolve_wallet_param(&wallet).await?;
+
+    list(
+        &state,
+        ActivitySubject::Wallet(&wallet_address),
+        &types,
+        &page,
+    )
+    .await
+}
+
+async fn list(
+    state: &AppState,
+    subject: ActivitySubject<'_>,
+    types: &[String],
+    page: &PageParams,
+) -> Result<Json<Paginated<Activity>>, AppError> {
+    let activities = Activity::list(
+        &state.db,
+        subject,
+        types,
+        page.cursor()?,
+        page.fetch_limit(),
+        page.offset(),
+    )
+    .await?;
+    let total = Activity::count(&state.db, subject, types).await?;
+
+    Ok(Json(
+        Paginated::new("activities", activities, page)
+            .with_total(total)
+            .with_cursor(|activity| activity.id),
+    ))
+}
+
+/// `types=sale,transfer` as a list, empty for all types
+fn activity_types(query: &ActivityQuery) -> Result<Vec<String>, AppError> {
+    let Some(types) = query.types.as_deref() else {
+        return Ok(Vec::new());
+    };
+
+    types
+        .split(',')
+        .map(str::trim)
+        .filter(|activity_type| !activity_type.is_empty())
+        .map(|activity_type| {
+            if ACTIVITY_TYPES.contains(&activity_type) {
+                Ok(activity_type.to_string())
+            } else {
+                Err(AppError::BadRequest(format!(
+                    "unknown activity type {}; expected one of {}",
+                    activity_type,
+                    ACTIVITY_TYPES.join(", ")
+                )))
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,344 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde_json::{json, Value};
+use solmint_client::SpamOverrideRequest;
+use uuid::Uuid;
+
+use super::AppState;
+use crate::{
+    error::AppError,
+    models::{
+        AccessBlock, AdminChangeRequest, BlocklistEntry, BlocklistQuery, Collection,
+        CollectionImport, CollectionMultiplierRequest, CreateAccessBlockRequest,
+        CreateBlocklistEntryRequest, Dashboard, ImportCollectionRequest, PointsAdjustmentRequest,
+        PointsSeason, PointsSeasonRequest, RowHistory, SetUserRoleRequest, User,
+    },
+    services::{abuse, collection_import::CollectionImporter, spam},
+    utils::pagination::{PageParams, Paginated},
+};
+
+pub async fn list_points_seasons(State(state): State<AppState>) -> Result<Json<Value>, AppError> {
+    let seasons = PointsSeason::list(&state.db).await?;
+
+    Ok(Json(json!({
+        "seasons": seasons
+    })))
+}
+
+pub async fn create_points_season(
+    State(state): State<AppState>,
+    Json(req): Json<PointsSeasonRequest>,
+) -> Result<Json<Value>, AppError> {
+    let season = PointsSeason::create(&state.db, req).await?;
+
+    Ok(Json(json!({
+        "season": season
+    })))
+}
+
+pub async fn update_points_season(
+    State(state): State<AppState>,
+    Path(season_id): Path<Uuid>,
+    Json(req): Json<PointsSeasonRequest>,
+) -> Result<Json<Value>, AppError> {
+    let season = PointsSeason::update(&state.db, season_id, req).await?;
+
+    Ok(Json(json!({
+        "season": season
+    })))
+}
+
+pub async fn set_points_multiplier(
+    State(state): State<AppState>,
+    Path((season_id, collection_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<CollectionMultiplierRequest>,
+) -> Result<Json<Value>, AppError> {
+    PointsSeason::find_by_id(&state.db, season_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Points season"))?;
+    Collection::find_by_id(&state.db, collection_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Collection"))?;
+
+    PointsSeason::set_collection_multiplier(&state.db, season_id, collection_id, req.multiplier)
+        .await?;
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
+
+pub async fn adjust_points(
+    State(state): State<AppState>,
+    Path(season_id): Path<Uuid>,
+    Json(req): Json<PointsAdjustmentRequest>,
+) -> Result<Json<Value>, AppError> {
+    PointsSeason::find_by_id(&state.db, season_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Points season"))?;
+
+    let entry = PointsSeason::adjust(&state.db, season_id, req).await?;
+
+    Ok(Json(json!({
+        "entry": entry
+    })))
+}
+
+pub async fn recompute_points_season(
+    State(state): State<AppState>,
+    Path(season_id): Path<Uuid>,
+) -> Result<Json<Value>, AppError> {
+    PointsSeason::find_by_id(&state.db, season_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Points season"))?;
+
+    let accrued = PointsSeason::recompute(&state.db, season_id).await?;
+
+    Ok(Json(json!({
+        "season_id": season_id,
+        "entries_accrued": accrued
+    })))
+}
+
+/// Soft delete an nft, collection or listing by id
+pub async fn soft_delete_record(
+    State(state): State<AppState>,
+    Path((table, id)): Path<(String, Uuid)>,
+    Json(req): Json<AdminChangeRequest>,
+) -> Result<Json<Value>, AppError> {
+    if !RowHistory::soft_delete(&state.db, &table, id, &req).await? {
+        return Err(crate::error::not_found_error("Record"));
+    }
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
+
+pub async fn restore_record(
+    State(state): State<AppState>,
+    Path((table, id)): Path<(String, Uuid)>,
+    Json(req): Json<AdminChangeRequest>,
+) -> Result<Json<Value>, AppError> {
+    if !RowHistory::restore(&state.db, &table, id, &req).await? {
+        return Err(crate::error::not_found_error("Deleted record"));
+    }
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
+
+pub async fn get_record_history(
+    State(state): State<AppState>,
+    Path((table, id)): Path<(String, Uuid)>,
+) -> Result<Json<Value>, AppError> {
+    let history = RowHistory::for_row(&state.db, &table, id).await?;
+
+    Ok(Json(json!({
+        "history": history
+    })))
+}
+
+/// Undo the change recorded by one history entry
+pub async fn revert_history_entry(
+    State(state): State<AppState>,
+    Path(history_id): Path<i64>,
+    Json(req): Json<AdminChangeRequest>,
+) -> Result<Json<Value>, AppError> {
+    let entry = RowHistory::find_by_id(&state.db, history_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("History entry"))?;
+    entry.revert(&state.db, &req).await?;
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
+
+/// Queue a backfill of a collection from a mint hashlist or its verified
+/// collection address; the import job fetches and indexes the mints
+pub async fn import_collection(
+    State(state): State<AppState>,
+    Json(req): Json<ImportCollectionRequest>,
+) -> Result<Json<Value>, AppError> {
+    let (collection, import) = CollectionImporter::new(&state.config)
+        .start(&state.db, req)
+        .await?;
+
+    Ok(Json(json!({
+        "collection": collection,
+        "import": import
+    })))
+}
+
+pub async fn list_collection_imports(
+    State(state): State<AppState>,
+    page: PageParams<50>,
+) -> Result<Json<Paginated<CollectionImport>>, AppError> {
+    let imports =
+        CollectionImport::list_recent(&state.db, page.fetch_limit(), page.offset()).await?;
+
+    Ok(Json(Paginated::new("imports", imports, &page)))
+}
+
+pub async fn get_collection_import(
+    State(state): State<AppState>,
+    Path(import_id): Path<Uuid>,
+) -> Result<Json<Value>, AppError> {
+    let import = CollectionImport::find_by_id(&state.db, import_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Collection import"))?;
+    let failed_items = import.failed_items(&state.db).await?;
+
+    Ok(Json(json!({
+        "import": import,
+        "failed_items": failed_items
+    })))
+}
+
+pub async fn override_collection_spam(
+    State(state): State<AppState>,
+    Path(collection_id): Path<Uuid>,
+    Json(req): Json<SpamOverrideRequest>,
+) -> Result<Json<Value>, AppError> {
+    let is_spam = spam::override_collection(&state.db, collection_id, req.is_spam).await?;
+
+    Ok(Json(json!({
+        "collection_id": collection_id,
+        "is_spam": is_spam,
+        "overridden": req.is_spam.is_some()
+    })))
+}
+
+pub async fn override_nft_spam(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Json(req): Json<SpamOverrideRequest>,
+) -> Result<Json<Value>, AppError> {
+    let is_spam = spam::override_nft(&state.db, &mint, req.is_spam).await?;
+
+    Ok(Json(json!({
+        "mint_address": mint,
+        "is_spam": is_spam,
+        "overridden": req.is_spam.is_some()
+    })))
+}
+
+pub async fn list_blocklist(
+    State(state): State<AppState>,
+    Query(query): Query<BlocklistQuery>,
+    page: PageParams,
+) -> Result<Json<Paginated<BlocklistEntry>>, AppError> {
+    let entries = BlocklistEntry::list(
+        &state.db,
+        query.kind.as_deref(),
+        page.fetch_limit(),
+        page.offset(),
+    )
+    .await?;
+
+    Ok(Json(Paginated::new("entries", entries, &page)))
+}
+
+pub async fn create_blocklist_entry(
+    State(state): State<AppState>,
+    Json(req): Json<CreateBlocklistEntryRequest>,
+) -> Result<Json<Value>, AppError> {
+    let entry = BlocklistEntry::create(&state.db, req).await?;
+    // The cached dashboard would show the blocked item until its next refresh
+    Dashboard::refresh_cache(&state.db, &state.redis).await?;
+
+    Ok(Json(json!({
+        "entry": entry
+    })))
+}
+
+pub async fn delete_blocklist_entry(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, AppError> {
+    if !BlocklistEntry::delete(&state.db, id).await? {
+        return Err(crate::error::not_found_error("Blocklist entry"));
+    }
+    Dashboard::refresh_cache(&state.db, &state.redis).await?;
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
+
+/// Change a wallet's role, e.g. to make it a moderator
+pub async fn set_user_role(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+    Json(req): Json<SetUserRoleRequest>,
+) -> Result<Json<Value>, AppError> {
+    let wallet_address = state.sns().resolve_wallet_param(&wallet).await?;
+    User::set_role(&state.db, &wallet_address, req.role).await?;
+
+    Ok(Json(json!({
+        "wallet_address": wallet_address,
+        "role": req.role
+    })))
+}
+
+/// Wallet and IP blocks in force, newest first. Automatic blocks aren't
+/// listed.
+pub async fn list_access_blocks(
+    State(state): State<AppState>,
+    Query(query): Query<BlocklistQuery>,
+    page: PageParams,
+) -> Result<Json<Paginated<AccessBlock>>, AppError> {
+    let blocks = AccessBlock::list(
+        &state.db,
+        query.kind.as_deref(),
+        page.fetch_limit(),
+        page.offset(),
+    )
+    .await?;
+
+    Ok(Json(Paginated::new("blocks", blocks, &page)))
+}
+
+pub async fn create_access_block(
+    State(state): State<AppState>,
+    Json(req): Json<CreateAccessBlockRequest>,
+) -> Result<Json<Value>, AppError> {
+    let block = AccessBlock::create(&state.db, req).await?;
+
+    Ok(Json(json!({
+        "block": block
+    })))
+}
+
+/// Unblock, lifting any automatic block on the same wallet or IP too
+pub async fn delete_access_block(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, AppError> {
+    let block = AccessBlock::delete(&state.db, id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Access block"))?;
+    abuse::clear(&state.redis, &block.kind, &block.value).await?;
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
+
+/// Lift an automatic block, e.g. one a shared IP ran into
+pub async fn clear_automatic_block(
+    State(state): State<AppState>,
+    Path((kind, value)): Path<(String, String)>,
+) -> Result<Json<Value>, AppError> {
+    if !abuse::clear(&state.redis, &kind, &value).await? {
+        return Err(crate::error::not_found_error("Automatic block"));
+    }
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
@@ -0,0 +1,83 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde_json::{json, Value};
+
+use super::AppState;
+use crate::{
+    error::AppError,
+    models::{ActiveBid, Auction, AuctionQuery},
+    utils::pagination::{PageParams, Paginated},
+};
+
+/// Live auctions, soonest ending first; `ending_within_minutes` narrows to
+/// the ones ending soon
+pub async fn list_auctions(
+    State(state): State<AppState>,
+    Query(query): Query<AuctionQuery>,
+    page: PageParams,
+) -> Result<Json<Paginated<Auction>>, AppError> {
+    if !matches!(
+        query.status.as_deref(),
+        None | Some("live" | "ended" | "settled")
+    ) {
+        return Err(crate::error::bad_request_error(
+            "status must be live, ended or settled",
+        ));
+    }
+    if !matches!(
+        query.sort_by.as_deref(),
+        None | Some("ends_at" | "created_at" | "highest_bid")
+    ) {
+        return Err(crate::error::bad_request_error(
+            "sort_by must be ends_at, created_at or highest_bid",
+        ));
+    }
+    if query
+        .ending_within_minutes
+        .is_some_and(|minutes| minutes <= 0)
+    {
+        return Err(crate::error::bad_request_error(
+            "ending_within_minutes must be positive",
+        ));
+    }
+
+    let auctions = Auction::list(&state.db, &query, page.fetch_limit(), page.offset()).await?;
+
+    Ok(Json(Paginated::new("auctions", auctions, &page)))
+}
+
+/// The latest run of an auction account, with its bids highest first
+pub async fn get_auction(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+) -> Result<Json<Value>, AppError> {
+    let auction = Auction::find_by_address(&state.db, &address)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Auction"))?;
+    let bids = auction.bids(&state.db).await?;
+
+    Ok(Json(json!({
+        "auction": auction,
+        "bids": bids,
+    })))
+}
+
+/// Unsettled auctions the wallet has bid on, soonest ending first
+pub async fn list_wallet_bids(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+    page: PageParams,
+) -> Result<Json<Paginated<ActiveBid>>, AppError> {
+    let wallet_address = state.sns().resolve_wallet_param(&wallet).await?;
+    let bids = Auction::active_bids(
+        &state.db,
+        &wallet_address,
+        page.fetch_limit(),
+        page.offset(),
+    )
+    .await?;
+
+    Ok(Json(Paginated::new("bids", bids, &page)))
+}
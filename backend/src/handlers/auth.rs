@@ -0,0 +1,124 @@
+use axum::{
+    extract::State,
+    http::{header::ORIGIN, HeaderMap},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use solmint_client::{AuthChallenge, AuthChallengeRequest, AuthToken, AuthVerifyRequest};
+use std::str::FromStr;
+use uuid::Uuid;
+
+use super::AppState;
+use crate::{
+    cache,
+    error::AppError,
+    services::auth::{self, CHALLENGE_TTL_SECONDS},
+};
+
+/// An issued challenge, kept until it is used or expires
+#[derive(Serialize, Deserialize)]
+struct PendingChallenge {
+    wallet_address: String,
+    /// The site the challenge was issued to
+    origin: String,
+    message: String,
+    expires_at: DateTime<Utc>,
+}
+
+fn challenge_key(nonce: &str) -> String {
+    format!("auth_challenge:{}", nonce)
+}
+
+/// Issue a challenge for the wallet to sign within `CHALLENGE_TTL_SECONDS`.
+/// Browsers' `Origin` must be an allowed CORS origin; other clients sign in
+/// for `PUBLIC_URL`.
+pub async fn create_challenge(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<AuthChallengeRequest>,
+) -> Result<Json<AuthChallenge>, AppError> {
+    Pubkey::from_str(&req.wallet)
+        .map_err(|_| AppError::ValidationError("Invalid wallet address".to_string()))?;
+
+    let origin = match request_origin(&headers) {
+        Some(origin)
+            if state
+                .runtime
+                .settings()
+                .cors_allowed_origins
+                .iter()
+                .any(|allowed| allowed == origin) =>
+        {
+            origin.to_string()
+        }
+        Some(origin) => {
+            return Err(AppError::Forbidden(format!(
+                "{} can't request sign-in challenges",
+                origin
+            )))
+        }
+        None => state.config.public_url.trim_end_matches('/').to_string(),
+    };
+
+    let nonce = Uuid::new_v4().simple().to_string();
+    let issued_at = Utc::now();
+    let expires_at = issued_at + Duration::seconds(CHALLENGE_TTL_SECONDS as i64);
+    let message = auth::challenge_message(&origin, &req.wallet, &nonce, issued_at, expires_at);
+    cache::set_json(
+        &state.redis,
+        &challenge_key(&nonce),
+        &PendingChallenge {
+            wallet_address: req.wallet,
+            origin,
+            message: message.clone(),
+            expires_at,
+        },
+        CHALLENGE_TTL_SECONDS,
+    )
+    .await?;
+
+    Ok(Json(AuthChallenge {
+        message,
+        nonce,
+        expires_at,
+    }))
+}
+
+/// Exchange the wallet's signature of a challenge for a token. The nonce is
+/// used up by the first attempt, whether or not the signature checks out.
+pub async fn verify(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<AuthVerifyRequest>,
+) -> Result<Json<AuthToken>, AppError> {
+    let challenge = cache::take_json::<PendingChallenge>(&state.redis, &challenge_key(&req.nonce))
+        .await?
+        .filter(|challenge| {
+            challenge.wallet_address == req.wallet && challenge.expires_at > Utc::now()
+        })
+        .ok_or_else(|| crate::error::bad_request_error("challenge is unknown, used or expired"))?;
+    if request_origin(&headers).is_some_and(|origin| origin != challenge.origin) {
+        return Err(AppError::Forbidden(
+            "challenge was issued to a different origin".to_string(),
+        ));
+    }
+    auth::verify_signature(
+        &challenge.wallet_address,
+        &challenge.message,
+        &req.signature,
+    )?;
+
+    let (token, expires_at) = auth::issue_token(&state.config, &challenge.wallet_address)?;
+
+    Ok(Json(AuthToken {
+        token,
+        wallet_address: challenge.wallet_address,
+        expires_at,
+    }))
+}
+
+fn request_origin(headers: &HeaderMap) -> Option<&str> {
+    headers.get(ORIGIN)?.to_str().ok()
+}
@@ -0,0 +1,100 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::HeaderMap,
+    Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use super::AppState;
+use crate::{
+    error::AppError,
+    middleware::AuthenticatedWallet,
+    models::{CheckoutOrder, CreateCheckoutOrderRequest},
+    services::checkout::{CheckoutProvider, ProviderEvent},
+    utils::{
+        pagination::{PageParams, Paginated},
+        validation::ValidatedJson,
+    },
+};
+
+/// Start a card checkout for a listing as the signed-in `buyer_wallet`; the
+/// buyer completes payment on the returned `checkout_url`
+pub async fn create_checkout_order(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    ValidatedJson(req): ValidatedJson<CreateCheckoutOrderRequest>,
+) -> Result<Json<Value>, AppError> {
+    let provider = provider(&state)?;
+    let buyer_wallet = state.sns().resolve_wallet_param(&req.buyer_wallet).await?;
+    if buyer_wallet != wallet_address {
+        return Err(crate::error::wrong_wallet_error());
+    }
+
+    let order = CheckoutOrder::create(&state.db, &req, &buyer_wallet).await?;
+    let order = provider.open(&state.db, &order).await?;
+
+    Ok(Json(json!({
+        "order": order
+    })))
+}
+
+/// An order and its events, for its buyer
+pub async fn get_checkout_order(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, AppError> {
+    let order = CheckoutOrder::find_by_id(&state.db, id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Checkout order"))?;
+    if order.buyer_wallet != wallet_address {
+        return Err(crate::error::wrong_wallet_error());
+    }
+    let events = order.events(&state.db).await?;
+
+    Ok(Json(json!({
+        "order": order,
+        "events": events
+    })))
+}
+
+pub async fn list_user_checkout_orders(
+    State(state): State<AppState>,
+    AuthenticatedWallet(buyer_wallet): AuthenticatedWallet,
+    page: PageParams<50>,
+) -> Result<Json<Paginated<CheckoutOrder>>, AppError> {
+    let orders =
+        CheckoutOrder::list_for_buyer(&state.db, &buyer_wallet, page.fetch_limit(), page.offset())
+            .await?;
+
+    Ok(Json(Paginated::new("orders", orders, &page)))
+}
+
+/// Payment and delivery updates from the checkout provider
+pub async fn checkout_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<Value>, AppError> {
+    let provider = provider(&state)?;
+    let signature = headers
+        .get("checkout-signature")
+        .and_then(|value| value.to_str().ok())
+        .ok_or(AppError::Unauthorized)?;
+    provider.verify_signature(signature, &body)?;
+
+    let event: ProviderEvent = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("invalid webhook payload: {}", e)))?;
+    provider.handle_event(&state.db, &event).await?;
+
+    Ok(Json(json!({
+        "received": true
+    })))
+}
+
+fn provider(state: &AppState) -> Result<CheckoutProvider, AppError> {
+    CheckoutProvider::from_config(&state.config)
+        .ok_or_else(|| crate::error::bad_request_error("fiat checkout is not configured"))
+}
@@ -0,0 +1,316 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Days, Duration, Utc};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use super::AppState;
+use crate::{
+    cache,
+    error::AppError,
+    middleware::{self, AuthenticatedWallet},
+    models::{
+        CandleQuery, CandleScope, Collection, CollectionListQuery, CollectionStats,
+        CollectionTrait, HolderSnapshotRequest, HoldersHistoryQuery, ListingChurn,
+        ListingChurnQuery, Nft, Role, Sale,
+    },
+    services::merkle::{self, MerkleTree},
+    utils::pagination::{PageParams, Paginated},
+};
+
+const MAX_CANDLES: i64 = 2_000;
+
+pub async fn list_collections(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<CollectionListQuery>,
+    page: PageParams,
+) -> Result<Json<Paginated<Collection>>, AppError> {
+    if query.include_blocked.unwrap_or(false)
+        && !middleware::has_role(&state, &headers, Role::Moderator).await
+    {
+        return Err(AppError::Unauthorized);
+    }
+
+    let collections = Collection::list(&state.db, query, page.fetch_limit(), page.offset()).await?;
+
+    Ok(Json(Paginated::new("collections", collections, &page)))
+}
+
+pub async fn get_collection_stats(
+    State(state): State<AppState>,
+    Path(collection_id): Path<Uuid>,
+) -> Result<Json<CollectionStats>, AppError> {
+    let stats = Collection::stats(&state.db, collection_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Collection"))?;
+
+    Ok(Json(stats))
+}
+
+/// Every trait value of the collection with its count, frequency and floor
+pub async fn get_collection_traits(
+    State(state): State<AppState>,
+    Path(collection_id): Path<Uuid>,
+) -> Result<Json<Value>, AppError> {
+    Collection::find_by_id(&state.db, collection_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Collection"))?;
+
+    let traits = CollectionTrait::list(&state.db, collection_id).await?;
+
+    Ok(Json(json!({
+        "collection_id": collection_id,
+        "traits": traits
+    })))
+}
+
+pub async fn get_holders_history(
+    State(state): State<AppState>,
+    Path(collection_id): Path<Uuid>,
+    Query(query): Query<HoldersHistoryQuery>,
+) -> Result<Json<Value>, AppError> {
+    Collection::find_by_id(&state.db, collection_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Collection"))?;
+
+    let to = query.to.unwrap_or_else(|| Utc::now().date_naive());
+    let from = query.from.unwrap_or(to - Days::new(89));
+    if from > to {
+        return Err(crate::error::bad_request_error("from must not be after to"));
+    }
+
+    let history = Collection::holders_history(&state.db, collection_id, from, to).await?;
+
+    Ok(Json(json!({
+        "collection_id": collection_id,
+        "history": history
+    })))
+}
+
+/// Point-in-time holder list, optionally with a Merkle root (and per-holder
+/// proofs) over `(wallet, mints held)` for airdrops and allowlists. Reading
+/// every holder is costly, so only signed-in wallets may take one.
+pub async fn create_holder_snapshot(
+    State(state): State<AppState>,
+    AuthenticatedWallet(_): AuthenticatedWallet,
+    Path(collection_id): Path<Uuid>,
+    Json(req): Json<HolderSnapshotRequest>,
+) -> Result<Response, AppError> {
+    Collection::find_by_id(&state.db, collection_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Collection"))?;
+
+    let taken_at = Utc::now();
+    let holders = Collection::holders(&state.db, collection_id).await?;
+    let total_supply: usize = holders.iter().map(|holder| holder.mints.len()).sum();
+
+    let tree = req.merkle.then(|| {
+        MerkleTree::new(
+            holders
+                .iter()
+                .filter_map(|holder| holder_leaf(&holder.wallet_address, holder.mints.len()))
+                .collect(),
+        )
+    });
+    let merkle_root = tree
+        .as_ref()
+        .and_then(MerkleTree::root)
+        .map(|root| merkle::encode(&root));
+    let proof_for = |wallet: &str, count: usize| {
+        let tree = tree.as_ref()?;
+        let proof = tree.proof(&holder_leaf(wallet, count)?)?;
+        Some(proof.iter().map(merkle::encode).collect::<Vec<_>>())
+    };
+
+    match req.format.as_deref() {
+        Some("json") | None => {
+            let entries: Vec<Value> = holders
+                .iter()
+                .map(|holder| {
+                    json!({
+                        "wallet_address": holder.wallet_address,
+                        "count": holder.mints.len(),
+                        "mints": holder.mints,
+                        "proof": proof_for(&holder.wallet_address, holder.mints.len())
+                    })
+                })
+                .collect();
+
+            Ok(Json(json!({
+                "collection_id": collection_id,
+                "taken_at": taken_at,
+                "total_holders": holders.len(),
+                "total_supply": total_supply,
+                "merkle_root": merkle_root,
+                "holders": entries
+            }))
+            .into_response())
+        }
+        Some("csv") => {
+            let mut csv = String::from("wallet_address,count,mints,proof\n");
+            for holder in &holders {
+                let proof = proof_for(&holder.wallet_address, holder.mints.len())
+                    .map(|proof| proof.join(" "))
+                    .unwrap_or_default();
+                csv.push_str(&format!(
+                    "{},{},{},{}\n",
+                    holder.wallet_address,
+                    holder.mints.len(),
+                    holder.mints.join(" "),
+                    proof
+                ));
+            }
+
+            let mut response = (
+                [
+                    (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        format!(
+                            "attachment; filename=\"holders-{}-{}.csv\"",
+                            collection_id,
+                            taken_at.format("%Y%m%dT%H%M%SZ")
+                        ),
+                    ),
+                ],
+                csv,
+            )
+                .into_response();
+            if let Some(root) = merkle_root.and_then(|root| root.parse::<HeaderValue>().ok()) {
+                response.headers_mut().insert("x-merkle-root", root);
+            }
+            Ok(response)
+        }
+        Some(_) => Err(crate::error::bad_request_error(
+            "format must be json or csv",
+        )),
+    }
+}
+
+/// Wallets that aren't valid public keys can't claim on-chain, so they're left out of the tree
+fn holder_leaf(wallet_address: &str, count: usize) -> Option<merkle::MerkleHash> {
+    let wallet = Pubkey::from_str(wallet_address).ok()?;
+    Some(merkle::leaf(&wallet, count as u64))
+}
+
+pub async fn get_listing_churn(
+    State(state): State<AppState>,
+    Path(collection_id): Path<Uuid>,
+    Query(query): Query<ListingChurnQuery>,
+) -> Result<Json<Value>, AppError> {
+    Collection::find_by_id(&state.db, collection_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Collection"))?;
+
+    let days = query.days.unwrap_or(30);
+    if !(1..=365).contains(&days) {
+        return Err(crate::error::bad_request_error(
+            "days must be between 1 and 365",
+        ));
+    }
+
+    let churn =
+        ListingChurn::for_collection(&state.db, collection_id, Utc::now() - Duration::days(days))
+            .await?;
+
+    Ok(Json(json!({ "churn": churn })))
+}
+
+/// OHLCV candles of the collection's sales, also served as `price-history`
+pub async fn get_candles(
+    State(state): State<AppState>,
+    Path(collection_id): Path<Uuid>,
+    Query(query): Query<CandleQuery>,
+) -> Result<Json<Value>, AppError> {
+    let body = candles(&state, CandleScope::Collection(collection_id), &query).await?;
+    Ok(Json(body))
+}
+
+/// The candles response for a collection or an NFT, cached for
+/// `cache_ttl_seconds`. 404s when the collection or NFT doesn't exist.
+pub(super) async fn candles(
+    state: &AppState,
+    scope: CandleScope<'_>,
+    query: &CandleQuery,
+) -> Result<Value, AppError> {
+    // (bucket size, default window)
+    let (bucket_seconds, default_window) = match query.resolution.as_deref() {
+        Some("1h") | None => (3_600, Duration::days(7)),
+        Some("4h") => (14_400, Duration::days(30)),
+        Some("1d") => (86_400, Duration::days(365)),
+        Some(_) => {
+            return Err(crate::error::bad_request_error(
+                "resolution must be one of 1h, 4h, 1d",
+            ))
+        }
+    };
+    let resolution = query.resolution.as_deref().unwrap_or("1h");
+
+    // Align the window to bucket boundaries so repeated requests share a cache entry
+    let align = |time: DateTime<Utc>| {
+        let timestamp = time.timestamp();
+        DateTime::from_timestamp(timestamp - timestamp.rem_euclid(bucket_seconds), 0)
+            .unwrap_or(time)
+    };
+    let to = align(query.to.unwrap_or_else(Utc::now)) + Duration::seconds(bucket_seconds);
+    let from = align(query.from.unwrap_or(to - default_window));
+    if from >= to {
+        return Err(crate::error::bad_request_error("from must be before to"));
+    }
+    if (to - from).num_seconds() / bucket_seconds > MAX_CANDLES {
+        return Err(crate::error::bad_request_error(
+            "time range too large for this resolution",
+        ));
+    }
+
+    let subject = match scope {
+        CandleScope::Collection(collection_id) => collection_id.to_string(),
+        CandleScope::Nft(mint) => format!("nft:{}", mint),
+    };
+    let cache_key = format!(
+        "candles:{}:{}:{}:{}",
+        subject,
+        resolution,
+        from.timestamp(),
+        to.timestamp()
+    );
+    if let Some(cached) = cache::get_json::<Value>(&state.redis, &cache_key).await? {
+        return Ok(cached);
+    }
+
+    let (key, id) = match scope {
+        CandleScope::Collection(collection_id) => {
+            Collection::find_by_id(&state.db, collection_id)
+                .await?
+                .ok_or_else(|| crate::error::not_found_error("Collection"))?;
+            ("collection_id", json!(collection_id))
+        }
+        CandleScope::Nft(mint) => {
+            Nft::find_by_mint(&state.db, mint)
+                .await?
+                .ok_or_else(|| crate::error::not_found_error("NFT"))?;
+            ("nft_mint", json!(mint))
+        }
+    };
+
+    let candles = Sale::candles(&state.db, scope, bucket_seconds, from, to).await?;
+    let body = json!({
+        key: id,
+        "resolution": resolution,
+        "from": from,
+        "to": to,
+        "candles": candles
+    });
+
+    let ttl = state.runtime.settings().cache_ttl_seconds;
+    cache::set_json(&state.redis, &cache_key, &body, ttl).await?;
+
+    Ok(body)
+}
@@ -0,0 +1,62 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+
+use super::AppState;
+use crate::{
+    error::AppError,
+    models::{CreatorDashboard, RoyaltyReportQuery, Sale},
+    services::launchpad::Launchpad,
+};
+
+pub async fn get_creator_royalties(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+    Query(query): Query<RoyaltyReportQuery>,
+) -> Result<Json<Value>, AppError> {
+    let wallet = state.sns().resolve_wallet_param(&wallet).await?;
+    let period = match query.period.as_deref() {
+        None | Some("month") => "month",
+        Some("week") => "week",
+        Some("day") => "day",
+        Some(_) => {
+            return Err(crate::error::bad_request_error(
+                "period must be one of day, week, month",
+            ))
+        }
+    };
+
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - Duration::days(365));
+    if from >= to {
+        return Err(crate::error::bad_request_error("from must be before to"));
+    }
+
+    let royalties = Sale::royalty_summary(&state.db, &wallet, period, from, to).await?;
+    let total: i64 = royalties.iter().map(|row| row.royalties_earned).sum();
+
+    Ok(Json(json!({
+        "creator": wallet,
+        "period": period,
+        "from": from,
+        "to": to,
+        "total_royalties": total,
+        "royalties": royalties
+    })))
+}
+
+/// Collections, mint progress, sales, royalties, top holders and recent
+/// activity for a creator, cached for a minute
+pub async fn get_creator_dashboard(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+) -> Result<Json<CreatorDashboard>, AppError> {
+    let wallet = state.sns().resolve_wallet_param(&wallet).await?;
+    let launchpad = Launchpad::new(state.runtime.settings().solana_rpc_url.clone());
+    let dashboard = CreatorDashboard::cached(&state.db, &state.redis, &launchpad, &wallet).await?;
+
+    Ok(Json(dashboard))
+}
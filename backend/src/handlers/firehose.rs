@@ -0,0 +1,38 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+
+use super::AppState;
+use crate::{
+    error::AppError,
+    models::{Activity, FirehosePage, FirehoseQuery},
+};
+
+const DEFAULT_FIREHOSE_LIMIT: i64 = 100;
+const MAX_FIREHOSE_LIMIT: i64 = 1000;
+
+/// Every indexed event in id order, for consumers replicating the dataset.
+/// Ids are contiguous and never appear behind a reader, so resuming from
+/// `next_since_id` misses nothing.
+pub async fn get_activities(
+    State(state): State<AppState>,
+    Query(query): Query<FirehoseQuery>,
+) -> Result<Json<FirehosePage>, AppError> {
+    let since_id = query.since_id.unwrap_or(0);
+    if since_id < 0 {
+        return Err(crate::error::bad_request_error(
+            "since_id must not be negative",
+        ));
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_FIREHOSE_LIMIT);
+    if !(1..=MAX_FIREHOSE_LIMIT).contains(&limit) {
+        return Err(AppError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_FIREHOSE_LIMIT
+        )));
+    }
+
+    let page = Activity::firehose(&state.db, since_id, limit).await?;
+    Ok(Json(page))
+}
@@ -1,4 +1,4 @@
-use axum::{extract::State, Json};
+use axum::{extract::State, http::StatusCode, Json};
 use serde_json::{json, Value};
 
 use super::AppState;
@@ -15,6 +15,7 @@ pub async fn health_check(State(state): State<AppState>) -> Result<Json<Value>,
     Ok(Json(json!({
         "status": "healthy",
         "timestamp": chrono::Utc::now(),
+        "cluster": state.config.solana_cluster,
         "services": {
             "database": "connected",
             "redis": "connected",
@@ -22,3 +23,21 @@ pub async fn health_check(State(state): State<AppState>) -> Result<Json<Value>,
         }
     })))
 }
+
+/// Readiness probe: fails while any supervised background task is down
+pub async fn readiness_check(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let ready = state.supervisor.is_ready();
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(json!({
+            "ready": ready,
+            "tasks": state.supervisor.snapshot(),
+        })),
+    )
+}
@@ -0,0 +1,106 @@
+use axum::{
+    extract::{Path, State},
+    http::{header::AUTHORIZATION, HeaderMap},
+    Json,
+};
+use serde_json::{json, Value};
+use solana_sdk::hash::hashv;
+use uuid::Uuid;
+
+use super::AppState;
+use crate::{
+    error::AppError,
+    models::{
+        AlertIntegration, Collection, CreateAlertIntegrationRequest, UpdateAlertIntegrationRequest,
+    },
+    services::alerts::AlertDispatcher,
+};
+
+/// Connect a Discord webhook or Telegram chat to a collection's activity.
+/// The returned `manage_token` is shown once and is needed to change or
+/// remove the integration.
+pub async fn create_integration(
+    State(state): State<AppState>,
+    Path(collection_id): Path<Uuid>,
+    Json(req): Json<CreateAlertIntegrationRequest>,
+) -> Result<Json<Value>, AppError> {
+    let collection = Collection::find_by_id(&state.db, collection_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Collection"))?;
+
+    AlertIntegration::validate_create(&req)?;
+    AlertDispatcher::new(&state.config)
+        .verify(&req.kind, req.target.trim(), &collection)
+        .await?;
+
+    let manage_token = Uuid::new_v4().simple().to_string();
+    let integration =
+        AlertIntegration::create(&state.db, collection_id, &req, &token_hash(&manage_token))
+            .await?;
+
+    Ok(Json(json!({
+        "integration": integration,
+        "manage_token": manage_token
+    })))
+}
+
+pub async fn get_integration(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, AppError> {
+    let integration = find_managed(&state, id, &headers).await?;
+
+    Ok(Json(json!({
+        "integration": integration
+    })))
+}
+
+pub async fn update_integration(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(req): Json<UpdateAlertIntegrationRequest>,
+) -> Result<Json<Value>, AppError> {
+    find_managed(&state, id, &headers).await?;
+    let integration = AlertIntegration::update(&state.db, id, &req).await?;
+
+    Ok(Json(json!({
+        "integration": integration
+    })))
+}
+
+pub async fn delete_integration(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, AppError> {
+    find_managed(&state, id, &headers).await?;
+    AlertIntegration::delete(&state.db, id).await?;
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
+
+/// Look up an integration by id and `Authorization: Bearer <manage_token>`.
+/// A wrong token and a missing integration both return 401.
+async fn find_managed(
+    state: &AppState,
+    id: Uuid,
+    headers: &HeaderMap,
+) -> Result<AlertIntegration, AppError> {
+    let token = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(AppError::Unauthorized)?;
+
+    AlertIntegration::find_managed(&state.db, id, &token_hash(token))
+        .await?
+        .ok_or(AppError::Unauthorized)
+}
+
+fn token_hash(token: &str) -> String {
+    hashv(&[token.as_bytes()]).to_string()
+}
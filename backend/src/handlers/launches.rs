@@ -0,0 +1,232 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::Utc;
+use serde_json::{json, Value};
+use solana_sdk::{signature::Keypair, signer::Signer};
+use uuid::Uuid;
+
+use super::AppState;
+use crate::{
+    error::AppError,
+    middleware::AuthenticatedWallet,
+    models::{
+        BuildMintRequest, Collection, Launch, LaunchListQuery, LaunchPhase, RegisterLaunchRequest,
+        UpdateLaunchPhasesRequest, LAUNCH_KIND_CANDY_MACHINE,
+    },
+    services::{allowlist, launchpad::Launchpad},
+    utils::pagination::{PageParams, Paginated},
+};
+
+/// Register an upcoming mint as the signed-in `creator_address`. Candy
+/// machines must be owned by it; solmint launches get a generated
+/// `config_address` that keys their allowlist under `/api/v1/mint/{config}`.
+pub async fn register_launch(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Json(mut req): Json<RegisterLaunchRequest>,
+) -> Result<Json<Value>, AppError> {
+    if req.creator_address != wallet_address {
+        return Err(crate::error::wrong_wallet_error());
+    }
+    if let Some(collection_id) = req.collection_id {
+        Collection::find_by_id(&state.db, collection_id)
+            .await?
+            .ok_or_else(|| crate::error::not_found_error("Collection"))?;
+    }
+
+    let config_address = if req.kind == LAUNCH_KIND_CANDY_MACHINE {
+        let config_address = req.config_address.clone().ok_or_else(|| {
+            crate::error::bad_request_error("candy machine launches need a config_address")
+        })?;
+        let candy_machine = launchpad(&state).candy_machine(&config_address).await?;
+        if candy_machine.authority.to_string() != req.creator_address {
+            return Err(crate::error::bad_request_error(
+                "creator_address is not the candy machine authority",
+            ));
+        }
+        req.items_available = Some(candy_machine.items_available as i64);
+        config_address
+    } else {
+        Keypair::new().pubkey().to_string()
+    };
+
+    let launch = Launch::create(&state.db, &req, &config_address).await?;
+    let phases = launch.phases(&state.db).await?;
+
+    Ok(Json(json!({
+        "launch": launch,
+        "phases": phases
+    })))
+}
+
+pub async fn list_launches(
+    State(state): State<AppState>,
+    Query(query): Query<LaunchListQuery>,
+    page: PageParams,
+) -> Result<Json<Paginated<Launch>>, AppError> {
+    let launches = Launch::list(&state.db, &query, page.fetch_limit(), page.offset()).await?;
+
+    Ok(Json(Paginated::new("launches", launches, &page)))
+}
+
+/// A launch with its phases and live supply, read from the candy machine
+/// for candy machine launches
+pub async fn get_launch(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Value>, AppError> {
+    let launch = find_launch(&state, id).await?;
+    let phases = launch.phases(&state.db).await?;
+
+    let (items_available, items_redeemed) = if launch.kind == LAUNCH_KIND_CANDY_MACHINE {
+        let candy_machine = launchpad(&state)
+            .candy_machine(&launch.config_address)
+            .await?;
+        (
+            candy_machine.items_available as i64,
+            candy_machine.items_redeemed as i64,
+        )
+    } else {
+        (
+            launch.items_available.unwrap_or_default(),
+            launch.minted_count(&state.db).await?,
+        )
+    };
+    let remaining = (items_available - items_redeemed).max(0);
+    // Root and size of the allowlist used by the launch's allowlist phases
+    let allowlist = allowlist::find(&state.db, &launch.config_address).await?;
+
+    let now = Utc::now();
+    let current_phase = phases.iter().find(|phase| phase.is_live(now));
+    let next_phase = phases.iter().find(|phase| phase.starts_at > now);
+    let status = if remaining == 0 {
+        "sold_out"
+    } else if current_phase.is_some() {
+        "live"
+    } else if next_phase.is_some() {
+        "upcoming"
+    } else {
+        "ended"
+    };
+
+    Ok(Json(json!({
+        "launch": launch,
+        "phases": phases,
+        "supply": {
+            "items_available": items_available,
+            "items_redeemed": items_redeemed,
+            "remaining": remaining
+        },
+        "allowlist": allowlist,
+        "current_phase": current_phase,
+        "next_phase": next_phase,
+        "status": status
+    })))
+}
+
+pub async fn update_launch_phases(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Path(id): Path<Uuid>,
+    Json(req): Json<UpdateLaunchPhasesRequest>,
+) -> Result<Json<Value>, AppError> {
+    let launch = find_launch(&state, id).await?;
+    let phases = launch
+        .replace_phases(&state.db, &wallet_address, &req)
+        .await?;
+
+    Ok(Json(json!({
+        "phases": phases
+    })))
+}
+
+/// Build a mint transaction for the live phase, for the signed-in wallet.
+/// The new mint has already signed; the wallet signs as payer and submits
+/// it. Allowlists and wallet limits are checked here, so on-chain guards
+/// remain the source of truth for candy machines. The item and the wallet's
+/// slot stay reserved until the transaction's blockhash has long expired.
+pub async fn build_mint_transaction(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Path(id): Path<Uuid>,
+    Json(req): Json<BuildMintRequest>,
+) -> Result<Json<Value>, AppError> {
+    let launch = find_launch(&state, id).await?;
+    if state
+        .sns()
+        .resolve_wallet_param(&req.wallet_address)
+        .await?
+        != wallet_address
+    {
+        return Err(crate::error::wrong_wallet_error());
+    }
+
+    let now = Utc::now();
+    let phases = launch.phases(&state.db).await?;
+    let phase: &LaunchPhase = phases
+        .iter()
+        .find(|phase| phase.is_live(now))
+        .ok_or_else(|| crate::error::bad_request_error("no mint phase is live"))?;
+
+    let mut limit = phase.per_wallet_limit.map(i64::from);
+    if phase.allowlist {
+        if launch.kind == LAUNCH_KIND_CANDY_MACHINE {
+            return Err(crate::error::bad_request_error(
+                "allowlist phases of candy machines can't be minted through solmint yet",
+            ));
+        }
+        let proof = allowlist::proof(&state.db, &launch.config_address, &wallet_address)
+            .await?
+            .ok_or_else(|| crate::error::bad_request_error("wallet is not on the allowlist"))?;
+        limit = Some(limit.map_or(proof.allocation, |limit| limit.min(proof.allocation)));
+    }
+
+    let mint_keypair = Keypair::new();
+    let mint_address = mint_keypair.pubkey().to_string();
+    let item_index = launch
+        .reserve_mint(
+            &state.db,
+            &mint_address,
+            &wallet_address,
+            &phase.label,
+            limit,
+        )
+        .await?;
+
+    let mint = match launchpad(&state)
+        .build_mint(
+            &launch,
+            phase,
+            &wallet_address,
+            &mint_keypair,
+            &state.config.marketplace_program_id,
+            item_index,
+        )
+        .await
+    {
+        Ok(mint) => mint,
+        Err(e) => {
+            launch.release_mint(&state.db, &mint_address).await?;
+            return Err(e);
+        }
+    };
+
+    Ok(Json(json!({
+        "transaction": mint.transaction,
+        "mint_address": mint.mint_address,
+        "item_index": mint.item_index,
+        "phase": phase.label
+    })))
+}
+
+async fn find_launch(state: &AppState, id: Uuid) -> Result<Launch, AppError> {
+    Launch::find_by_id(&state.db, id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Launch"))
+}
+
+fn launchpad(state: &AppState) -> Launchpad {
+    Launchpad::new(state.runtime.settings().solana_rpc_url.clone())
+}
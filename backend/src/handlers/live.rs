@@ -0,0 +1,158 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::{collections::BTreeSet, str::FromStr, sync::Arc};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use uuid::Uuid;
+
+use super::AppState;
+use crate::services::live_events::{LiveEvent, GLOBAL_CHANNEL};
+
+/// Channels one socket may subscribe to at once
+const MAX_CHANNELS: usize = 100;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum ClientMessage {
+    Subscribe { channels: Vec<String> },
+    Unsubscribe { channels: Vec<String> },
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage<'a> {
+    /// Sent after every (un)subscribe with the socket's channels
+    Subscribed {
+        channels: &'a BTreeSet<String>,
+    },
+    Event {
+        /// The socket's channels the event was delivered on
+        channels: Vec<&'a str>,
+        event: &'a LiveEvent,
+    },
+    /// The socket fell behind and `missed` events were dropped
+    Lagged {
+        missed: u64,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl ServerMessage<'_> {
+    fn into_message(self) -> Message {
+        Message::Text(serde_json::to_string(&self).unwrap_or_default().into())
+    }
+}
+
+/// Live listing, sale and mint events. Clients send
+/// `{"action": "subscribe", "channels": [...]}` (or `unsubscribe`) with
+/// `global-activity`, `collection:{id}` or `nft:{mint}` channels.
+pub async fn live_events(State(state): State<AppState>, ws: WebSocketUpgrade) -> Response {
+    let events = state.live_events.subscribe();
+    ws.on_upgrade(move |socket| serve_socket(socket, events))
+}
+
+async fn serve_socket(mut socket: WebSocket, mut events: Receiver<Arc<LiveEvent>>) {
+    let mut subscriptions = BTreeSet::new();
+
+    loop {
+        let reply = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    handle_client_message(text.as_str(), &mut subscriptions)
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                // Pings are answered by axum
+                Some(Ok(_)) => continue,
+            },
+            event = events.recv() => match event {
+                Ok(event) => {
+                    let channels: Vec<&str> = event
+                        .channels
+                        .iter()
+                        .filter(|channel| subscriptions.contains(*channel))
+                        .map(String::as_str)
+                        .collect();
+                    if channels.is_empty() {
+                        continue;
+                    }
+                    ServerMessage::Event {
+                        channels,
+                        event: &event,
+                    }
+                    .into_message()
+                }
+                Err(RecvError::Lagged(missed)) => ServerMessage::Lagged { missed }.into_message(),
+                Err(RecvError::Closed) => return,
+            },
+        };
+
+        if socket.send(reply).await.is_err() {
+            return;
+        }
+    }
+}
+
+fn handle_client_message(text: &str, subscriptions: &mut BTreeSet<String>) -> Message {
+    let result = serde_json::from_str::<ClientMessage>(text)
+        .map_err(|e| format!("invalid message: {}", e))
+        .and_then(|message| match message {
+            ClientMessage::Subscribe { channels } => {
+                let channels = channels
+                    .iter()
+                    .map(|channel| normalize_channel(channel))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let mut updated = subscriptions.clone();
+                updated.extend(channels);
+                if updated.len() > MAX_CHANNELS {
+                    return Err(format!(
+                        "at most {} channels may be subscribed to",
+                        MAX_CHANNELS
+                    ));
+                }
+                *subscriptions = updated;
+                Ok(())
+            }
+            ClientMessage::Unsubscribe { channels } => {
+                for channel in channels {
+                    if let Ok(channel) = normalize_channel(&channel) {
+                        subscriptions.remove(&channel);
+                    }
+                }
+                Ok(())
+            }
+        });
+
+    match result {
+        Ok(()) => ServerMessage::Subscribed {
+            channels: subscriptions,
+        }
+        .into_message(),
+        Err(message) => ServerMessage::Error { message }.into_message(),
+    }
+}
+
+/// The channel in the form events are tagged with
+fn normalize_channel(channel: &str) -> Result<String, String> {
+    let invalid = || format!("invalid channel: {}", channel);
+    if channel == GLOBAL_CHANNEL {
+        return Ok(channel.to_string());
+    }
+
+    match channel.split_once(':') {
+        Some(("collection", id)) => Uuid::parse_str(id)
+            .map(|id| format!("collection:{}", id))
+            .map_err(|_| invalid()),
+        Some(("nft", mint)) => Pubkey::from_str(mint)
+            .map(|mint| format!("nft:{}", mint))
+            .map_err(|_| invalid()),
+        _ => Err(invalid()),
+    }
+}
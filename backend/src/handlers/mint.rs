@@ -0,0 +1,230 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use nft_marketplace_sdk::{pda, MintConfig};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use super::{nfts, AppState};
+use crate::{
+    error::AppError,
+    middleware::AuthenticatedWallet,
+    models::Launch,
+    services::allowlist::{
+        self, AddAllowlistEntriesRequest, AllowlistEntry, ImportAllowlistQuery,
+        RemoveAllowlistEntriesRequest, UploadAllowlistRequest,
+    },
+    utils::pagination::{PageParams, Paginated},
+};
+
+pub async fn upload_allowlist(
+    State(state): State<AppState>,
+    AuthenticatedWallet(creator_address): AuthenticatedWallet,
+    Path(config_address): Path<String>,
+    Json(req): Json<UploadAllowlistRequest>,
+) -> Result<Json<Value>, AppError> {
+    check_config_creator(&state, &config_address, &creator_address, req.config_id).await?;
+    let allowlist = allowlist::store(&state.db, &config_address, &creator_address, req).await?;
+
+    Ok(Json(json!({
+        "allowlist": allowlist
+    })))
+}
+
+pub async fn get_allowlist(
+    State(state): State<AppState>,
+    Path(config_address): Path<String>,
+) -> Result<Json<Value>, AppError> {
+    let allowlist = allowlist::find(&state.db, &config_address)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Allowlist"))?;
+
+    Ok(Json(json!({
+        "allowlist": allowlist
+    })))
+}
+
+pub async fn delete_allowlist(
+    State(state): State<AppState>,
+    AuthenticatedWallet(creator_address): AuthenticatedWallet,
+    Path(config_address): Path<String>,
+) -> Result<Json<Value>, AppError> {
+    check_config_creator(&state, &config_address, &creator_address, None).await?;
+    allowlist::delete(&state.db, &config_address, &creator_address).await?;
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
+
+pub async fn list_allowlist_entries(
+    State(state): State<AppState>,
+    Path(config_address): Path<String>,
+    page: PageParams<100, 1000>,
+) -> Result<Json<Paginated<AllowlistEntry>>, AppError> {
+    let allowlist = allowlist::find(&state.db, &config_address)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Allowlist"))?;
+    let after: Option<String> = page.cursor()?;
+    let entries = allowlist::entries(
+        &state.db,
+        &config_address,
+        after.as_deref(),
+        page.fetch_limit(),
+    )
+    .await?;
+
+    Ok(Json(
+        Paginated::new("entries", entries, &page)
+            .with_total(allowlist.entry_count)
+            .with_cursor(|entry| entry.wallet_address.clone()),
+    ))
+}
+
+pub async fn add_allowlist_entries(
+    State(state): State<AppState>,
+    AuthenticatedWallet(creator_address): AuthenticatedWallet,
+    Path(config_address): Path<String>,
+    Json(req): Json<AddAllowlistEntriesRequest>,
+) -> Result<Json<Value>, AppError> {
+    check_config_creator(&state, &config_address, &creator_address, None).await?;
+    let allowlist =
+        allowlist::add_entries(&state.db, &config_address, &creator_address, req).await?;
+
+    Ok(Json(json!({
+        "allowlist": allowlist
+    })))
+}
+
+/// Removing the last wallet deletes the allowlist, returned as `null`
+pub async fn remove_allowlist_entries(
+    State(state): State<AppState>,
+    AuthenticatedWallet(creator_address): AuthenticatedWallet,
+    Path(config_address): Path<String>,
+    Json(req): Json<RemoveAllowlistEntriesRequest>,
+) -> Result<Json<Value>, AppError> {
+    check_config_creator(&state, &config_address, &creator_address, None).await?;
+    let allowlist =
+        allowlist::remove_entries(&state.db, &config_address, &creator_address, req).await?;
+
+    Ok(Json(json!({
+        "allowlist": allowlist
+    })))
+}
+
+/// Import a CSV body of `wallet_address[,allocation]` rows
+pub async fn import_allowlist(
+    State(state): State<AppState>,
+    AuthenticatedWallet(creator_address): AuthenticatedWallet,
+    Path(config_address): Path<String>,
+    Query(query): Query<ImportAllowlistQuery>,
+    body: String,
+) -> Result<Json<Value>, AppError> {
+    check_config_creator(&state, &config_address, &creator_address, query.config_id).await?;
+    let allowlist =
+        allowlist::import_csv(&state.db, &config_address, &creator_address, query, &body).await?;
+
+    Ok(Json(json!({
+        "allowlist": allowlist
+    })))
+}
+
+pub async fn get_allowlist_proof(
+    State(state): State<AppState>,
+    Path((config_address, wallet_address)): Path<(String, String)>,
+) -> Result<Json<Value>, AppError> {
+    let proof = allowlist::proof(&state.db, &config_address, &wallet_address)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Allowlist entry"))?;
+
+    Ok(Json(json!(proof)))
+}
+
+pub async fn list_creator_allowlists(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+) -> Result<Json<Value>, AppError> {
+    let creator_address = state.sns().resolve_wallet_param(&wallet).await?;
+    let allowlists = allowlist::list_for_creator(&state.db, &creator_address).await?;
+
+    Ok(Json(json!({
+        "allowlists": allowlists
+    })))
+}
+
+/// Allowlists of registered launches belong to the launch creator, which
+/// `allowlist` checks. Otherwise the config is a marketplace mint config and
+/// the allowlist its creator's. The config can only be created once its
+/// allowlist root is known, so until then a new allowlist's `config_id` must
+/// derive `config_address` from the creator.
+async fn check_config_creator(
+    state: &AppState,
+    config_address: &str,
+    creator_address: &str,
+    config_id: Option<u64>,
+) -> Result<(), AppError> {
+    if Launch::find_by_config_address(&state.db, config_address)
+        .await?
+        .is_some()
+    {
+        return Ok(());
+    }
+
+    let config = Pubkey::from_str(config_address).map_err(|_| {
+        AppError::BadRequest(format!(
+            "config '{}' is not a valid public key",
+            config_address
+        ))
+    })?;
+    let creator = Pubkey::from_str(creator_address)
+        .map_err(|_| AppError::BadRequest("invalid wallet address".to_string()))?;
+    let program_id = nfts::marketplace_program_id(state)?;
+    let account = state
+        .solana_client()
+        .get_multiple_accounts(&[config])
+        .await?
+        .remove(0);
+
+    let config_creator = match account {
+        Some(account) => {
+            let mint_config = MintConfig::from_account_data(&account.data)
+                .ok()
+                .filter(|_| account.owner == program_id)
+                .ok_or_else(|| {
+                    crate::error::bad_request_error("config is not a marketplace mint config")
+                })?;
+            Pubkey::new_from_array(mint_config.creator)
+        }
+        None => match config_id {
+            Some(config_id) => {
+                let (expected, _) = pda::mint_config_address(
+                    &program_id.to_bytes(),
+                    &creator.to_bytes(),
+                    config_id,
+                );
+                if expected != config.to_bytes() {
+                    return Err(AppError::Forbidden(
+                        "config is not the signed-in wallet's mint config".to_string(),
+                    ));
+                }
+                creator
+            }
+            // An existing allowlist's creator was checked when it was uploaded
+            None if allowlist::find(&state.db, config_address).await?.is_some() => creator,
+            None => {
+                return Err(crate::error::bad_request_error(
+                    "config_id is needed until the mint config is created",
+                ))
+            }
+        },
+    };
+    if config_creator != creator {
+        return Err(AppError::Forbidden(
+            "allowlist for this config belongs to another creator".to_string(),
+        ));
+    }
+
+    Ok(())
+}
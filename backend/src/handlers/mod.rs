@@ -3,17 +3,64 @@ use solana_client::rpc_client::RpcClient;
 use sqlx::PgPool;
 use std::sync::Arc;
 
+pub mod activities;
+pub mod admin;
+pub mod auctions;
+pub mod auth;
+pub mod checkout;
+pub mod collections;
+pub mod creators;
+pub mod firehose;
 pub mod health;
+pub mod integrations;
+pub mod launches;
+pub mod live;
+pub mod mint;
 pub mod nfts;
+pub mod notifications;
+pub mod offers;
+pub mod points;
+pub mod stats;
 pub mod upload;
 pub mod users;
+pub mod webhooks;
 
-use crate::config::Config;
+use crate::{
+    config::Config,
+    runtime::RuntimeConfig,
+    services::{
+        email::Mailer, live_events::LiveEvents, price_estimator::PriceModel, sns::SnsResolver,
+        supervisor::Supervisor, twitter::TwitterVerifier,
+    },
+};
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
     pub redis: MultiplexedConnection,
-    pub solana_client: Arc<RpcClient>,
+    pub runtime: RuntimeConfig,
+    pub supervisor: Supervisor,
+    pub price_model: Arc<dyn PriceModel>,
+    /// `None` when SMTP isn't configured
+    pub mailer: Option<Mailer>,
+    /// `None` without an X API bearer token
+    pub twitter: Option<TwitterVerifier>,
+    /// Events for this replica's `/ws` sockets
+    pub live_events: LiveEvents,
     pub config: Config,
 }
+
+impl AppState {
+    /// RPC client for the currently configured endpoint
+    pub fn solana_client(&self) -> Arc<RpcClient> {
+        self.runtime.solana_client()
+    }
+
+    /// `.sol` domain resolver for the currently configured endpoint
+    pub fn sns(&self) -> SnsResolver {
+        SnsResolver::new(
+            self.runtime.settings().solana_rpc_url.clone(),
+            self.redis.clone(),
+        )
+    }
+}
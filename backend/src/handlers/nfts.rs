@@ -1,39 +1,91 @@
 use axum::{
     extract::{Path, Query, State},
+    http::HeaderMap,
     Json,
 };
-use serde::{Deserialize, Serialize};
+use chrono::{Duration, Utc};
+use nft_marketplace_sdk::{
+    ids::{SYSTEM_PROGRAM_ID, TOKEN_METADATA_PROGRAM_ID},
+    instruction as marketplace_instruction,
+    metadata::Metadata,
+    pda, AccountMeta, Address, CollectionConfig, Marketplace,
+};
 use serde_json::{json, Value};
 use solana_sdk::{
+    instruction::Instruction,
+    message::{v0, AddressLookupTableAccount, Message, VersionedMessage},
+    pubkey,
     pubkey::Pubkey,
     signature::{Keypair, Signature},
     signer::Signer,
-    transaction::Transaction,
+    transaction::VersionedTransaction,
+};
+use solmint_client::{
+    BuyListingRequest, BuyListingResponse, ComputeBudgetOverride, MintNftRequest, MintNftResponse,
+    SendTransactionRequest, SendTransactionResponse,
 };
 use std::str::FromStr;
+use uuid::Uuid;
 
-use super::AppState;
+use super::{collections, AppState};
 use crate::{
-    error::AppError,
-    models::{CreateNftRequest, Nft, NftListQuery},
+    cache,
+    error::{AppError, ErrorCode},
+    middleware::{self, AuthenticatedWallet},
+    models::{
+        CandleQuery, CandleScope, Collection, CreateNftRequest, Listing, Nft, NftListQuery, Role,
+    },
+    services::{
+        das::DasClient, metadata_refresh::MetadataRefresher, price_estimator,
+        priority_fees::PriorityFeeEstimator, relay_guard,
+    },
+    utils::{
+        pagination::{PageParams, Paginated},
+        program,
+        validation::ValidatedJson,
+    },
 };
 
+/// Proofs go stale as soon as the tree changes, so keep them only briefly
+const ASSET_PROOF_CACHE_TTL_SECONDS: u64 = 10;
+/// Canopy depth is fixed when a tree is created
+const CANOPY_DEPTH_CACHE_TTL_SECONDS: u64 = 86_400;
+const ADDRESS_LOOKUP_TABLE_PROGRAM_ID: Pubkey =
+    pubkey!("AddressLookupTab1e1111111111111111111111111");
+/// Lookup table accounts start with this much metadata before their addresses
+const LOOKUP_TABLE_META_SIZE: usize = 56;
+/// Manual metadata refreshes of the same NFT are at least this far apart
+const METADATA_REFRESH_COOLDOWN_MINUTES: i64 = 5;
+
 pub async fn list_nfts(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Query(query): Query<NftListQuery>,
-) -> Result<Json<Value>, AppError> {
-    let nfts = Nft::list(&state.db, query.clone()).await?;
+    page: PageParams,
+) -> Result<Json<Paginated<Nft>>, AppError> {
+    if query.include_blocked.unwrap_or(false)
+        && !middleware::has_role(&state, &headers, Role::Moderator).await
+    {
+        return Err(AppError::Unauthorized);
+    }
+
+    let nfts = Nft::list(
+        &state.db,
+        query.clone(),
+        page.cursor()?,
+        page.fetch_limit(),
+        page.offset(),
+    )
+    .await?;
     let total = Nft::count(&state.db, &query).await?;
 
-    Ok(Json(json!({
-        "nfts": nfts,
-        "pagination": {
-            "total": total,
-            "page": query.page.unwrap_or(0),
-            "limit": query.limit.unwrap_or(20),
-            "has_more": (query.page.unwrap_or(0) + 1) * query.limit.unwrap_or(20) < total
-        }
-    })))
+    let nfts = Paginated::new("nfts", nfts, &page).with_total(total);
+    // Keyset cursors follow `created_at`, the default sort
+    if matches!(query.sort_by.as_deref(), None | Some("created_at")) {
+        return Ok(Json(nfts.with_cursor(|nft| (nft.created_at, nft.id))));
+    }
+
+    Ok(Json(nfts))
 }
 
 pub async fn get_nft(
@@ -49,34 +101,127 @@ pub async fn get_nft(
     })))
 }
 
-#[derive(Debug, Deserialize)]
-pub struct MintNftRequest {
-    pub name: String,
-    pub symbol: String,
-    pub uri: String,
-    pub creator: String,
+pub async fn get_nft_estimate(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+) -> Result<Json<Value>, AppError> {
+    let nft = Nft::find_by_mint(&state.db, &mint)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("NFT"))?;
+
+    let inputs = price_estimator::gather_inputs(&state.db, &nft).await?;
+    let estimate = state.price_model.estimate(&inputs);
+
+    Ok(Json(json!({
+        "mint_address": nft.mint_address,
+        "estimate": estimate,
+        "inputs": inputs
+    })))
 }
 
-#[derive(Debug, Serialize)]
-pub struct MintNftResponse {
-    pub transaction: Vec<u8>,
-    pub mint_address: String,
+/// OHLCV candles of the NFT's sales
+pub async fn get_nft_price_history(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Query(query): Query<CandleQuery>,
+) -> Result<Json<Value>, AppError> {
+    let body = collections::candles(&state, CandleScope::Nft(&mint), &query).await?;
+    Ok(Json(body))
 }
 
-#[derive(Debug, Deserialize)]
-pub struct SendTransactionRequest {
-    pub signed_transaction: Vec<u8>,
+/// Re-read the NFT's metadata now instead of waiting for the refresh job.
+/// Any signed-in wallet may, once per cooldown.
+pub async fn refresh_nft_metadata(
+    State(state): State<AppState>,
+    AuthenticatedWallet(_): AuthenticatedWallet,
+    Path(mint): Path<String>,
+) -> Result<Json<Value>, AppError> {
+    Nft::find_by_mint(&state.db, &mint)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("NFT"))?;
+    if let Some(refreshed_at) = Nft::metadata_refreshed_at(&state.db, &mint).await? {
+        if refreshed_at > Utc::now() - Duration::minutes(METADATA_REFRESH_COOLDOWN_MINUTES) {
+            return Err(AppError::RateLimited);
+        }
+    }
+
+    let (nft, changed) = MetadataRefresher::new(&state.config)
+        .refresh(&state.db, &mint)
+        .await?;
+
+    Ok(Json(json!({
+        "nft": nft,
+        "changed": changed
+    })))
 }
 
-#[derive(Debug, Serialize)]
-pub struct SendTransactionResponse {
-    pub signature: String,
-    pub mint_address: String,
+/// Current Merkle proof for a compressed NFT, with the nodes stored in the
+/// tree's on-chain canopy trimmed off so it fits in a transaction
+pub async fn get_nft_proof(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+) -> Result<Json<Value>, AppError> {
+    let nft = Nft::find_by_mint(&state.db, &mint)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("NFT"))?;
+    if !nft.is_compressed {
+        return Err(crate::error::bad_request_error(
+            "NFT is not compressed and has no Merkle proof",
+        ));
+    }
+
+    let das = DasClient::new(state.config.das_rpc_url().to_string());
+
+    let proof_key = format!("asset_proof:{}", mint);
+    let proof = match cache::get_json(&state.redis, &proof_key).await? {
+        Some(proof) => proof,
+        None => {
+            let proof = das.get_asset_proof(&mint).await?;
+            cache::set_json(
+                &state.redis,
+                &proof_key,
+                &proof,
+                ASSET_PROOF_CACHE_TTL_SECONDS,
+            )
+            .await?;
+            proof
+        }
+    };
+
+    let canopy_key = format!("tree_canopy:{}", proof.tree_id);
+    let canopy_depth: u32 = match cache::get_json(&state.redis, &canopy_key).await? {
+        Some(depth) => depth,
+        None => {
+            let depth = das.canopy_depth(&proof.tree_id).await?;
+            cache::set_json(
+                &state.redis,
+                &canopy_key,
+                &depth,
+                CANOPY_DEPTH_CACHE_TTL_SECONDS,
+            )
+            .await?;
+            depth
+        }
+    };
+
+    let full_proof_length = proof.proof.len();
+    let trimmed = full_proof_length.saturating_sub(canopy_depth as usize);
+
+    Ok(Json(json!({
+        "mint_address": mint,
+        "tree_id": proof.tree_id,
+        "root": proof.root,
+        "leaf": proof.leaf,
+        "node_index": proof.node_index,
+        "canopy_depth": canopy_depth,
+        "full_proof_length": full_proof_length,
+        "proof": &proof.proof[..trimmed]
+    })))
 }
 
 pub async fn mint_nft(
     State(state): State<AppState>,
-    Json(req): Json<MintNftRequest>,
+    ValidatedJson(req): ValidatedJson<MintNftRequest>,
 ) -> Result<Json<MintNftResponse>, AppError> {
     // Generate a new keypair for the mint account
     let mint_keypair = Keypair::new();
@@ -90,90 +235,391 @@ pub async fn mint_nft(
     let program_id = Pubkey::from_str(&state.config.marketplace_program_id)
         .map_err(|_| AppError::ConfigError("Invalid program ID".to_string()))?;
 
-    // Get associated token account
-    let associated_token_account =
-        spl_associated_token_account::get_associated_token_address(&creator_pubkey, &mint_address);
-
     // Create mint NFT instruction
-    let instruction = solana_program::instruction::Instruction {
-        program_id,
-        accounts: vec![
-            solana_program::instruction::AccountMeta::new(creator_pubkey, true),
-            solana_program::instruction::AccountMeta::new(mint_address, false),
-            solana_program::instruction::AccountMeta::new(associated_token_account, false),
-            solana_program::instruction::AccountMeta::new_readonly(spl_token::id(), false),
-            solana_program::instruction::AccountMeta::new_readonly(
-                spl_associated_token_account::id(),
-                false,
-            ),
-            solana_program::instruction::AccountMeta::new_readonly(
-                solana_program::system_program::id(),
-                false,
-            ),
-            solana_program::instruction::AccountMeta::new_readonly(
-                solana_program::sysvar::rent::id(),
-                false,
-            ),
-        ],
-        data: {
-            use borsh::BorshSerialize;
-            #[derive(BorshSerialize)]
-            enum MarketplaceInstruction {
-                MintNft {
-                    name: String,
-                    symbol: String,
-                    uri: String,
-                },
-            }
-            MarketplaceInstruction::MintNft {
-                name: req.name.clone(),
-                symbol: req.symbol.clone(),
-                uri: req.uri.clone(),
-            }
-            .try_to_vec()
-            .map_err(|e| AppError::SerializationError(e.to_string()))?
-        },
+    let creator = creator_pubkey.to_bytes();
+    let mint = mint_address.to_bytes();
+    let instruction = match req.collection_id {
+        Some(collection_id) => {
+            let collection_mint = collection_mint(&state, collection_id).await?;
+            marketplace_instruction::mint_nft_with_collection(
+                &program_id.to_bytes(),
+                &creator,
+                &mint,
+                &pda::associated_token_address(&creator, &mint),
+                &pda::metadata_address(&mint),
+                &pda::master_edition_address(&mint),
+                &collection_mint,
+                &pda::metadata_address(&collection_mint),
+                &pda::master_edition_address(&collection_mint),
+                &creator,
+                req.name.clone(),
+                req.symbol.clone(),
+                req.uri.clone(),
+            )
+        }
+        None => marketplace_instruction::mint_nft(
+            &program_id.to_bytes(),
+            &creator,
+            &mint,
+            &pda::associated_token_address(&creator, &mint),
+            &pda::metadata_address(&mint),
+            &pda::master_edition_address(&mint),
+            req.name.clone(),
+            req.symbol.clone(),
+            req.uri.clone(),
+        ),
     };
+    let instruction = program::instruction(instruction);
 
-    // Get recent blockhash
-    let recent_blockhash = state.solana_client.get_latest_blockhash().await?;
-
-    // Create transaction
-    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&creator_pubkey));
-    transaction.partial_sign(&[&mint_keypair], recent_blockhash);
+    // Create transaction, signed by the new mint
+    let mut transaction =
+        build_transaction(&state, &[instruction], &creator_pubkey, &req.compute_budget).await?;
+    partial_sign(&mut transaction, &mint_keypair)?;
 
     Ok(Json(MintNftResponse {
-        transaction: bincode::serialize(&transaction)
-            .map_err(|e| AppError::SerializationError(e.to_string()))?,
+        transaction: serialize_transaction(&transaction)?,
         mint_address: mint_address.to_string(),
     }))
 }
 
+/// The Metaplex collection mint of a collection, which minting into it needs
+async fn collection_mint(state: &AppState, collection_id: Uuid) -> Result<[u8; 32], AppError> {
+    Collection::find_by_id(&state.db, collection_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Collection"))?;
+    let collection_address = Collection::find_collection_address(&state.db, collection_id)
+        .await?
+        .ok_or_else(|| {
+            crate::error::bad_request_error("Collection has no on-chain collection mint")
+        })?;
+
+    Pubkey::from_str(&collection_address)
+        .map(|address| address.to_bytes())
+        .map_err(|_| AppError::ValidationError("Invalid collection address".to_string()))
+}
+
+/// Build a transaction buying a listing for the buyer to sign. The listing,
+/// fee recipient and escrow are read from chain rather than the index, so the
+/// accounts match the listing as it is now.
+pub async fn buy_listing(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Json(req): Json<BuyListingRequest>,
+) -> Result<Json<BuyListingResponse>, AppError> {
+    let listing = Listing::find_by_address(&state.db, &address)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Listing"))?;
+    if listing.status != "active" {
+        return Err(AppError::Rejected(
+            ErrorCode::ListingNotActive,
+            "listing is not active".to_string(),
+        ));
+    }
+    check_expected_price(listing.price as u64, req.expected_price)?;
+
+    let buyer_wallet = state.sns().resolve_wallet_param(&req.buyer).await?;
+    let buyer_pubkey = Pubkey::from_str(&buyer_wallet)
+        .map_err(|_| AppError::ValidationError("Invalid buyer address".to_string()))?;
+    let referrer = req
+        .referrer
+        .as_deref()
+        .map(Pubkey::from_str)
+        .transpose()
+        .map_err(|_| AppError::ValidationError("Invalid referrer address".to_string()))?;
+    let listing_pubkey = Pubkey::from_str(&address)
+        .map_err(|_| AppError::ValidationError("Invalid listing address".to_string()))?;
+    let mint_pubkey = Pubkey::from_str(&listing.nft_mint)
+        .map_err(|_| AppError::ValidationError("Invalid NFT mint address".to_string()))?;
+
+    let program_id = marketplace_program_id(&state)?;
+
+    // The listing, the mint's token program and the NFT's metadata, whose
+    // verified collection picks the collection config
+    let client = state.solana_client();
+    let mint = mint_pubkey.to_bytes();
+    let metadata = pda::metadata_address(&mint);
+    let accounts = client
+        .get_multiple_accounts(&[
+            listing_pubkey,
+            mint_pubkey,
+            Pubkey::new_from_array(metadata),
+        ])
+        .await?;
+    let onchain_listing = accounts[0]
+        .as_ref()
+        .filter(|account| account.owner == program_id)
+        .and_then(|account| nft_marketplace_sdk::Listing::from_account_data(&account.data).ok())
+        .ok_or_else(|| {
+            AppError::Rejected(
+                ErrorCode::ListingNotActive,
+                "listing is no longer on chain".to_string(),
+            )
+        })?;
+    check_expected_price(onchain_listing.price, req.expected_price)?;
+    let token_program = accounts[1]
+        .as_ref()
+        .map(|account| account.owner.to_bytes())
+        .ok_or_else(|| crate::error::not_found_error("NFT mint"))?;
+    let nft_metadata = accounts[2]
+        .as_ref()
+        .filter(|account| account.owner.to_bytes() == TOKEN_METADATA_PROGRAM_ID)
+        .and_then(|account| Metadata::from_account_data(&account.data));
+
+    let program = program_id.to_bytes();
+    let listing_address = listing_pubkey.to_bytes();
+    let marketplace = onchain_listing.marketplace;
+    let seller = onchain_listing.seller;
+    let escrow = pda::listing_escrow_address(&listing_address, &mint, &token_program);
+    let accounts = client
+        .get_multiple_accounts(&[
+            Pubkey::new_from_array(marketplace),
+            Pubkey::new_from_array(escrow),
+        ])
+        .await?;
+    let fee_recipient = accounts[0]
+        .as_ref()
+        .and_then(|account| Marketplace::from_account_data(&account.data).ok())
+        .map(|marketplace| marketplace.fee_recipient)
+        .ok_or_else(|| crate::error::not_found_error("Marketplace"))?;
+    // Without an escrow, the listing is delegated: the NFT stays frozen in
+    // the seller's wallet
+    let delegated = accounts[1].is_none();
+    let seller_token_account =
+        pda::associated_token_address_with_program(&seller, &mint, &token_program);
+
+    let (collection_config, royalty_creators) =
+        collection_config_accounts(&state, &program, &marketplace, nft_metadata.as_ref()).await?;
+    let buyer = buyer_pubkey.to_bytes();
+    let mut instruction = marketplace_instruction::buy_nft(
+        &program,
+        &buyer,
+        &seller,
+        &marketplace,
+        &fee_recipient,
+        &mint,
+        &listing_address,
+        if delegated {
+            &seller_token_account
+        } else {
+            &escrow
+        },
+        &pda::associated_token_address_with_program(&buyer, &mint, &token_program),
+        &token_program,
+        &metadata,
+        &collection_config,
+        &pda::marketplace_stats_address(&program, &marketplace).0,
+        &pda::referral_config_address(&program, &marketplace).0,
+        &referrer.map_or(fee_recipient, |referrer| referrer.to_bytes()),
+        &royalty_creators,
+        req.expected_price,
+    );
+    if delegated {
+        instruction.accounts.extend([
+            AccountMeta::new_readonly(pda::master_edition_address(&mint), false),
+            AccountMeta::new_readonly(TOKEN_METADATA_PROGRAM_ID, false),
+        ]);
+    }
+    let instruction = program::instruction(instruction);
+    let transaction =
+        build_transaction(&state, &[instruction], &buyer_pubkey, &req.compute_budget).await?;
+
+    Ok(Json(BuyListingResponse {
+        transaction: serialize_transaction(&transaction)?,
+        listing_address: address,
+        nft_mint: listing.nft_mint,
+        price: onchain_listing.price,
+    }))
+}
+
+/// The collection config account a sale of the NFT passes, and the creators
+/// the sale pays royalties to: the NFT's, if the config enforces royalties
+pub(super) async fn collection_config_accounts(
+    state: &AppState,
+    program: &Address,
+    marketplace: &Address,
+    nft_metadata: Option<&Metadata>,
+) -> Result<(Address, Vec<Address>), AppError> {
+    // Any account stands in for the collection config of an NFT without a
+    // verified collection
+    let Some((nft_metadata, collection_mint)) = nft_metadata.and_then(|nft_metadata| {
+        nft_metadata
+            .verified_collection()
+            .map(|collection_mint| (nft_metadata, collection_mint))
+    }) else {
+        return Ok((SYSTEM_PROGRAM_ID, Vec::new()));
+    };
+
+    let collection_config =
+        pda::collection_config_address(program, marketplace, &collection_mint).0;
+    let enforce_royalties = state
+        .solana_client()
+        .get_multiple_accounts(&[Pubkey::new_from_array(collection_config)])
+        .await?
+        .remove(0)
+        .filter(|account| account.owner.to_bytes() == *program)
+        .and_then(|account| CollectionConfig::from_account_data(&account.data).ok())
+        .is_some_and(|collection_config| collection_config.enforce_royalties);
+    let royalty_creators = if enforce_royalties {
+        nft_metadata
+            .creators()
+            .iter()
+            .map(|creator| creator.address)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok((collection_config, royalty_creators))
+}
+
+pub(super) fn marketplace_program_id(state: &AppState) -> Result<Pubkey, AppError> {
+    Pubkey::from_str(&state.config.marketplace_program_id)
+        .map_err(|_| AppError::ConfigError("Invalid program ID".to_string()))
+}
+
+/// An unsigned transaction of `instructions` for `payer` to sign, at the
+/// latest blockhash and behind compute budget instructions, so it still lands
+/// when the cluster is congested. With `address_lookup_table` configured it
+/// is a v0 transaction that references the table's accounts by index.
+pub(super) async fn build_transaction(
+    state: &AppState,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    compute_budget: &ComputeBudgetOverride,
+) -> Result<VersionedTransaction, AppError> {
+    let instructions = PriorityFeeEstimator::new(state.runtime.settings().solana_rpc_url.clone())
+        .with_compute_budget(instructions, payer, compute_budget)
+        .await?;
+    let recent_blockhash = state.solana_client().get_latest_blockhash().await?;
+
+    let message = match address_lookup_table(state).await? {
+        Some(table) => VersionedMessage::V0(
+            v0::Message::try_compile(payer, &instructions, &[table], recent_blockhash)
+                .map_err(|e| AppError::SerializationError(e.to_string()))?,
+        ),
+        None => VersionedMessage::Legacy(Message::new_with_blockhash(
+            &instructions,
+            Some(payer),
+            &recent_blockhash,
+        )),
+    };
+    let signatures = vec![Signature::default(); message.header().num_required_signatures as usize];
+
+    Ok(VersionedTransaction {
+        signatures,
+        message,
+    })
+}
+
+/// The configured lookup table and its addresses, or `None` when there is
+/// none or it is being deactivated
+async fn address_lookup_table(
+    state: &AppState,
+) -> Result<Option<AddressLookupTableAccount>, AppError> {
+    let Some(address) = &state.config.address_lookup_table else {
+        return Ok(None);
+    };
+    let key = Pubkey::from_str(address)
+        .map_err(|_| AppError::ConfigError("Invalid address lookup table".to_string()))?;
+    let account = state
+        .solana_client()
+        .get_multiple_accounts(&[key])
+        .await?
+        .pop()
+        .flatten()
+        .filter(|account| account.owner == ADDRESS_LOOKUP_TABLE_PROGRAM_ID)
+        .ok_or_else(|| AppError::ConfigError(format!("{} is not an address lookup table", key)))?;
+
+    let data = &account.data;
+    if data.len() < LOOKUP_TABLE_META_SIZE {
+        return Err(AppError::ConfigError(format!(
+            "{} is not an address lookup table",
+            key
+        )));
+    }
+    // A deactivating table can't be used once the deactivation completes
+    let deactivation_slot = u64::from_le_bytes(data[4..12].try_into().unwrap());
+    if deactivation_slot != u64::MAX {
+        return Ok(None);
+    }
+    let addresses = data[LOOKUP_TABLE_META_SIZE..]
+        .chunks_exact(32)
+        .map(|chunk| Pubkey::new_from_array(chunk.try_into().unwrap()))
+        .collect();
+
+    Ok(Some(AddressLookupTableAccount { key, addresses }))
+}
+
+/// Sign `transaction` as `signer`, leaving the other signatures for the wallet
+fn partial_sign(transaction: &mut VersionedTransaction, signer: &Keypair) -> Result<(), AppError> {
+    let required = transaction.message.header().num_required_signatures as usize;
+    let index = transaction.message.static_account_keys()[..required]
+        .iter()
+        .position(|key| *key == signer.pubkey())
+        .ok_or_else(|| {
+            AppError::SerializationError(format!("{} is not a signer", signer.pubkey()))
+        })?;
+    transaction.signatures[index] = signer.sign_message(&transaction.message.serialize());
+
+    Ok(())
+}
+
+pub(super) fn serialize_transaction(
+    transaction: &VersionedTransaction,
+) -> Result<Vec<u8>, AppError> {
+    bincode::serialize(transaction).map_err(|e| AppError::SerializationError(e.to_string()))
+}
+
+/// Refuse to build a purchase at a price other than the one the buyer saw
+fn check_expected_price(price: u64, expected_price: u64) -> Result<(), AppError> {
+    if price != expected_price {
+        return Err(AppError::Rejected(
+            ErrorCode::PriceMismatch,
+            format!(
+                "listing price is {} lamports, not the expected {}",
+                price, expected_price
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
 pub async fn send_transaction(
     State(state): State<AppState>,
     Json(req): Json<SendTransactionRequest>,
 ) -> Result<Json<SendTransactionResponse>, AppError> {
-    // Deserialize the signed transaction
-    let transaction: Transaction = bincode::deserialize(&req.signed_transaction).map_err(|e| {
-        AppError::SerializationError(format!("Failed to deserialize transaction: {}", e))
-    })?;
+    // Deserialize the signed transaction, legacy or v0
+    let transaction: VersionedTransaction =
+        bincode::deserialize(&req.signed_transaction).map_err(|e| {
+            AppError::SerializationError(format!("Failed to deserialize transaction: {}", e))
+        })?;
+
+    // Only relay marketplace transactions
+    let program_id = Pubkey::from_str(&state.config.marketplace_program_id)
+        .map_err(|_| AppError::ConfigError("Invalid program ID".to_string()))?;
+    relay_guard::check_relayable(&transaction.message, &program_id)?;
 
     // Send the transaction
     let signature = state
-        .solana_client
+        .solana_client()
         .send_and_confirm_transaction(&transaction)
-        .await?;
+        .await
+        .map_err(|e| AppError::from_transaction_failure(e, &transaction.message, &program_id))?;
 
     // Extract mint address from transaction (first account after payer)
-    let mint_address = if transaction.message.account_keys.len() > 1 {
-        transaction.message.account_keys[1].to_string()
+    let account_keys = transaction.message.static_account_keys();
+    let mint_address = if account_keys.len() > 1 {
+        account_keys[1].to_string()
     } else {
         return Err(AppError::ValidationError(
             "Invalid transaction structure".to_string(),
         ));
     };
 
+    let cluster = state.config.solana_cluster;
     Ok(Json(SendTransactionResponse {
+        explorer_url: cluster.explorer_tx_url(&signature.to_string()),
         signature: signature.to_string(),
         mint_address,
     }))
@@ -0,0 +1,185 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde_json::{json, Value};
+use solmint_client::ConfirmEmailRequest;
+use uuid::Uuid;
+
+use super::AppState;
+use crate::{
+    error::AppError,
+    middleware::AuthenticatedWallet,
+    models::{
+        Collection, Notification, NotificationListQuery, NotificationPreference,
+        NotificationPreferencesRequest, PushDevice, RegisterDeviceRequest, User, WatchlistEntry,
+        WatchlistRequest,
+    },
+    services::email::verification_token_hash,
+    utils::pagination::{PageParams, Paginated},
+};
+
+pub async fn list_notifications(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Query(query): Query<NotificationListQuery>,
+    page: PageParams<50, 200>,
+) -> Result<Json<Paginated<Notification>>, AppError> {
+    let notifications = Notification::list_for_wallet(
+        &state.db,
+        &wallet_address,
+        query.unread.unwrap_or(false),
+        page.cursor()?,
+        page.fetch_limit(),
+    )
+    .await?;
+
+    Ok(Json(
+        Paginated::new("notifications", notifications, &page)
+            .with_cursor(|notification| (notification.created_at, notification.id)),
+    ))
+}
+
+pub async fn mark_notifications_read(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+) -> Result<Json<Value>, AppError> {
+    let updated = Notification::mark_all_read(&state.db, &wallet_address).await?;
+
+    Ok(Json(json!({
+        "updated": updated
+    })))
+}
+
+pub async fn get_preferences(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+) -> Result<Json<Value>, AppError> {
+    let preferences = NotificationPreference::for_wallet(&state.db, &wallet_address).await?;
+
+    Ok(Json(json!({
+        "preferences": preferences
+    })))
+}
+
+pub async fn update_preferences(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Json(req): Json<NotificationPreferencesRequest>,
+) -> Result<Json<Value>, AppError> {
+    NotificationPreference::save(&state.db, &wallet_address, &req.preferences).await?;
+    let preferences = NotificationPreference::for_wallet(&state.db, &wallet_address).await?;
+
+    Ok(Json(json!({
+        "preferences": preferences
+    })))
+}
+
+pub async fn get_watchlist(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+) -> Result<Json<Value>, AppError> {
+    let watchlist = WatchlistEntry::list(&state.db, &wallet_address).await?;
+
+    Ok(Json(json!({
+        "watchlist": watchlist
+    })))
+}
+
+pub async fn upsert_watchlist(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Json(req): Json<WatchlistRequest>,
+) -> Result<Json<Value>, AppError> {
+    Collection::find_by_id(&state.db, req.collection_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Collection"))?;
+
+    WatchlistEntry::upsert(&state.db, &wallet_address, &req).await?;
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
+
+pub async fn remove_watchlist(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Path((_, collection_id)): Path<(String, Uuid)>,
+) -> Result<Json<Value>, AppError> {
+    if !WatchlistEntry::remove(&state.db, &wallet_address, collection_id).await? {
+        return Err(crate::error::not_found_error("Watchlist entry"));
+    }
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
+
+/// Email a confirmation link to the address on the user's profile
+pub async fn send_email_verification(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+) -> Result<Json<Value>, AppError> {
+    let mailer = state
+        .mailer
+        .as_ref()
+        .ok_or_else(|| crate::error::bad_request_error("email delivery is not configured"))?;
+    mailer.send_verification(&state.db, &wallet_address).await?;
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
+
+pub async fn confirm_email(
+    State(state): State<AppState>,
+    Json(req): Json<ConfirmEmailRequest>,
+) -> Result<Json<Value>, AppError> {
+    let wallet_address = User::confirm_email(&state.db, &verification_token_hash(&req.token))
+        .await?
+        .ok_or_else(|| crate::error::bad_request_error("invalid or expired token"))?;
+
+    Ok(Json(json!({
+        "wallet_address": wallet_address,
+        "verified": true
+    })))
+}
+
+pub async fn list_devices(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+) -> Result<Json<Value>, AppError> {
+    let devices = PushDevice::list_for_wallet(&state.db, &wallet_address).await?;
+
+    Ok(Json(json!({
+        "devices": devices
+    })))
+}
+
+/// Register (or refresh) a device token; apps should call this on every launch
+pub async fn register_device(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Json(req): Json<RegisterDeviceRequest>,
+) -> Result<Json<Value>, AppError> {
+    let device = PushDevice::register(&state.db, &wallet_address, &req).await?;
+
+    Ok(Json(json!({
+        "device": device
+    })))
+}
+
+pub async fn remove_device(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Path((_, device_id)): Path<(String, Uuid)>,
+) -> Result<Json<Value>, AppError> {
+    if !PushDevice::remove(&state.db, &wallet_address, device_id).await? {
+        return Err(crate::error::not_found_error("Device"));
+    }
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
@@ -0,0 +1,330 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::Utc;
+use nft_marketplace_sdk::{
+    ids::TOKEN_METADATA_PROGRAM_ID, instruction as marketplace_instruction, metadata::Metadata,
+    pda, Marketplace,
+};
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use super::{nfts, AppState};
+use crate::{
+    cache,
+    error::{AppError, ErrorCode},
+    middleware::AuthenticatedWallet,
+    models::{
+        AcceptOfferRequest, ComputeBudgetOverride, MakeOfferRequest, NewOffer, Nft, Offer,
+        OfferQuery,
+    },
+    services::escrow::{self, EscrowBalance},
+    utils::{
+        pagination::{PageParams, Paginated},
+        program,
+        validation::ValidatedJson,
+    },
+};
+
+/// Escrow balances are read with `getProgramAccounts`, which RPC providers
+/// meter; a wallet's is reused for this long
+const ESCROW_CACHE_TTL_SECONDS: u64 = 30;
+
+/// Offers on an NFT, including collection offers on its collection,
+/// highest first
+pub async fn list_nft_offers(
+    State(state): State<AppState>,
+    Path(mint): Path<String>,
+    Query(query): Query<OfferQuery>,
+    page: PageParams,
+) -> Result<Json<Paginated<Offer>>, AppError> {
+    let nft = Nft::find_by_mint(&state.db, &mint)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("NFT"))?;
+    let offers = Offer::list_for_nft(
+        &state.db,
+        &nft.mint_address,
+        nft.collection_id,
+        query.status.as_deref(),
+        page.fetch_limit(),
+        page.offset(),
+    )
+    .await?;
+
+    Ok(Json(Paginated::new("offers", offers, &page)))
+}
+
+/// Offers a wallet made, or with `direction=received` the offers on its
+/// NFTs and their collections and the ones it accepted; newest first
+pub async fn list_wallet_offers(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+    Query(query): Query<OfferQuery>,
+    page: PageParams,
+) -> Result<Json<Paginated<Offer>>, AppError> {
+    let received = match query.direction.as_deref() {
+        None | Some("made") => false,
+        Some("received") => true,
+        Some(_) => {
+            return Err(crate::error::bad_request_error(
+                "direction must be made or received",
+            ))
+        }
+    };
+    let wallet_address = state.sns().resolve_wallet_param(&wallet).await?;
+    let offers = Offer::list_for_wallet(
+        &state.db,
+        &wallet_address,
+        received,
+        query.status.as_deref(),
+        page.fetch_limit(),
+        page.offset(),
+    )
+    .await?;
+
+    Ok(Json(Paginated::new("offers", offers, &page)))
+}
+
+/// Lamports a wallet has locked in the program's offer, collection offer
+/// and auction accounts, with the account holding each amount
+pub async fn get_wallet_escrow(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+) -> Result<Json<EscrowBalance>, AppError> {
+    let wallet_address = state.sns().resolve_wallet_param(&wallet).await?;
+    let cache_key = format!("escrow:{}", wallet_address);
+    if let Some(balance) = cache::get_json(&state.redis, &cache_key).await? {
+        return Ok(Json(balance));
+    }
+
+    let wallet_pubkey = parse_address("wallet", &wallet_address)?;
+    let program_id = nfts::marketplace_program_id(&state)?;
+    let balance = escrow::locked_funds(&state.solana_client(), &program_id, &wallet_pubkey).await?;
+    cache::set_json(&state.redis, &cache_key, &balance, ESCROW_CACHE_TTL_SECONDS).await?;
+
+    Ok(Json(balance))
+}
+
+/// Record an offer and build the transaction making it for the bidder, the
+/// signed-in wallet, to sign. The offer stays `pending` until the
+/// transaction lands.
+pub async fn make_offer(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    ValidatedJson(req): ValidatedJson<MakeOfferRequest>,
+) -> Result<Json<Value>, AppError> {
+    // Validated to fit in an i64
+    let amount = req.amount as i64;
+
+    let bidder_wallet = state.sns().resolve_wallet_param(&req.bidder).await?;
+    if bidder_wallet != wallet_address {
+        return Err(crate::error::wrong_wallet_error());
+    }
+    let bidder_pubkey = parse_address("bidder", &bidder_wallet)?;
+    let marketplace_pubkey = parse_address("marketplace", &req.marketplace_address)?;
+    let nft = Nft::find_by_mint(&state.db, &req.nft_mint)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("NFT"))?;
+    let mint_pubkey = parse_address("NFT mint", &nft.mint_address)?;
+
+    let program_id = nfts::marketplace_program_id(&state)?.to_bytes();
+    let bidder = bidder_pubkey.to_bytes();
+    let mint = mint_pubkey.to_bytes();
+    let (offer_address, _) = pda::offer_address(&program_id, &mint, &bidder);
+    let instruction = program::instruction(marketplace_instruction::make_offer(
+        &program_id,
+        &bidder,
+        &marketplace_pubkey.to_bytes(),
+        &mint,
+        &offer_address,
+        req.amount,
+        req.expires_at
+            .map_or(0, |expires_at| expires_at.timestamp()),
+    ));
+    let transaction =
+        nfts::build_transaction(&state, &[instruction], &bidder_pubkey, &req.compute_budget)
+            .await?;
+
+    let offer = Offer::create_pending(
+        &state.db,
+        &NewOffer {
+            offer_address: Pubkey::new_from_array(offer_address).to_string(),
+            nft_mint: nft.mint_address,
+            bidder_address: bidder_wallet,
+            marketplace_address: req.marketplace_address,
+            amount,
+            expires_at: req.expires_at,
+        },
+    )
+    .await?;
+
+    Ok(Json(json!({
+        "offer": offer,
+        "transaction": nfts::serialize_transaction(&transaction)?
+    })))
+}
+
+/// Build the transaction accepting an offer for the seller, the NFT's owner
+/// and the signed-in wallet, to sign. The offer is read from chain, and the
+/// transaction fails if its amount has changed since.
+pub async fn accept_offer(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Path(id): Path<Uuid>,
+    Json(req): Json<AcceptOfferRequest>,
+) -> Result<Json<Value>, AppError> {
+    let offer = find_open_offer(&state, id).await?;
+    let seller_wallet = state.sns().resolve_wallet_param(&req.seller).await?;
+    if seller_wallet != wallet_address {
+        return Err(crate::error::wrong_wallet_error());
+    }
+    let seller_pubkey = parse_address("seller", &seller_wallet)?;
+    let offer_pubkey = parse_address("offer", &offer.offer_address)?;
+    let mint_pubkey = parse_address("NFT mint", offer_mint(&offer)?)?;
+    let program_id = nfts::marketplace_program_id(&state)?;
+
+    // The offer as it is now, the mint's token program and the NFT's
+    // metadata, which picks the collection config
+    let mint = mint_pubkey.to_bytes();
+    let metadata = pda::metadata_address(&mint);
+    let accounts = state
+        .solana_client()
+        .get_multiple_accounts(&[offer_pubkey, mint_pubkey, Pubkey::new_from_array(metadata)])
+        .await?;
+    let onchain_offer = accounts[0]
+        .as_ref()
+        .filter(|account| account.owner == program_id)
+        .and_then(|account| nft_marketplace_sdk::Offer::from_account_data(&account.data).ok())
+        .ok_or_else(|| crate::error::bad_request_error("offer is not on chain"))?;
+    if onchain_offer.is_expired(Utc::now().timestamp()) {
+        return Err(AppError::Rejected(
+            ErrorCode::OfferExpired,
+            "offer has expired".to_string(),
+        ));
+    }
+    if onchain_offer.amount != offer.amount as u64 {
+        return Err(AppError::Rejected(
+            ErrorCode::PriceMismatch,
+            format!(
+                "offer is for {} lamports on chain, not {}",
+                onchain_offer.amount, offer.amount
+            ),
+        ));
+    }
+    let token_program = accounts[1]
+        .as_ref()
+        .map(|account| account.owner.to_bytes())
+        .ok_or_else(|| crate::error::not_found_error("NFT mint"))?;
+    let nft_metadata = accounts[2]
+        .as_ref()
+        .filter(|account| account.owner.to_bytes() == TOKEN_METADATA_PROGRAM_ID)
+        .and_then(|account| Metadata::from_account_data(&account.data));
+    // The marketplace the offer was made on, for its fee recipient
+    let marketplace_pubkey = Pubkey::new_from_array(onchain_offer.marketplace);
+    let fee_recipient = state
+        .solana_client()
+        .get_multiple_accounts(&[marketplace_pubkey])
+        .await?
+        .remove(0)
+        .and_then(|account| Marketplace::from_account_data(&account.data).ok())
+        .map(|marketplace| marketplace.fee_recipient)
+        .ok_or_else(|| crate::error::not_found_error("Marketplace"))?;
+
+    let program = program_id.to_bytes();
+    let (collection_config, royalty_creators) = nfts::collection_config_accounts(
+        &state,
+        &program,
+        &onchain_offer.marketplace,
+        nft_metadata.as_ref(),
+    )
+    .await?;
+
+    let seller = seller_pubkey.to_bytes();
+    let bidder = onchain_offer.bidder;
+    let instruction = program::instruction(marketplace_instruction::accept_offer(
+        &program,
+        &seller,
+        &bidder,
+        &marketplace_pubkey.to_bytes(),
+        &fee_recipient,
+        &mint,
+        &offer_pubkey.to_bytes(),
+        &pda::associated_token_address_with_program(&seller, &mint, &token_program),
+        &pda::associated_token_address_with_program(&bidder, &mint, &token_program),
+        &token_program,
+        &metadata,
+        &collection_config,
+        &royalty_creators,
+        onchain_offer.amount,
+    ));
+    let transaction =
+        nfts::build_transaction(&state, &[instruction], &seller_pubkey, &req.compute_budget)
+            .await?;
+
+    Ok(Json(json!({
+        "offer": offer,
+        "transaction": nfts::serialize_transaction(&transaction)?
+    })))
+}
+
+/// Build the transaction cancelling an offer for the bidder, who must be
+/// signed in, to sign, which refunds the offered lamports
+pub async fn cancel_offer(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Path(id): Path<Uuid>,
+    Query(compute_budget): Query<ComputeBudgetOverride>,
+) -> Result<Json<Value>, AppError> {
+    let offer = find_open_offer(&state, id).await?;
+    if offer.bidder_address != wallet_address {
+        return Err(crate::error::wrong_wallet_error());
+    }
+    let bidder_pubkey = parse_address("bidder", &offer.bidder_address)?;
+    let mint_pubkey = parse_address("NFT mint", offer_mint(&offer)?)?;
+    let offer_pubkey = parse_address("offer", &offer.offer_address)?;
+
+    let program_id = nfts::marketplace_program_id(&state)?.to_bytes();
+    let instruction = program::instruction(marketplace_instruction::cancel_offer(
+        &program_id,
+        &bidder_pubkey.to_bytes(),
+        &mint_pubkey.to_bytes(),
+        &offer_pubkey.to_bytes(),
+    ));
+    let transaction =
+        nfts::build_transaction(&state, &[instruction], &bidder_pubkey, &compute_budget).await?;
+
+    Ok(Json(json!({
+        "offer": offer,
+        "transaction": nfts::serialize_transaction(&transaction)?
+    })))
+}
+
+async fn find_open_offer(state: &AppState, id: Uuid) -> Result<Offer, AppError> {
+    let offer = Offer::find_by_id(&state.db, id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Offer"))?;
+    if !offer.is_open() {
+        return Err(crate::error::conflict_error(&format!(
+            "offer is already {}",
+            offer.status
+        )));
+    }
+
+    Ok(offer)
+}
+
+/// The NFT an offer is for. Collection offers are only indexed; the API
+/// doesn't build their transactions.
+fn offer_mint(offer: &Offer) -> Result<&str, AppError> {
+    offer.nft_mint.as_deref().ok_or_else(|| {
+        crate::error::bad_request_error("collection offers can't be accepted or cancelled here")
+    })
+}
+
+fn parse_address(field: &str, address: &str) -> Result<Pubkey, AppError> {
+    Pubkey::from_str(address)
+        .map_err(|_| AppError::ValidationError(format!("Invalid {} address", field)))
+}
@@ -0,0 +1,76 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde_json::{json, Value};
+
+use super::AppState;
+use crate::{
+    error::AppError,
+    models::{PointsQuery, PointsSeason},
+    utils::pagination::{PageParams, Paginated},
+};
+
+const MAX_LEADERBOARD_LIMIT: i64 = 500;
+const RECENT_ENTRIES_LIMIT: i64 = 50;
+
+pub async fn get_user_points(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+    Query(query): Query<PointsQuery>,
+) -> Result<Json<Value>, AppError> {
+    let wallet_address = state.sns().resolve_wallet_param(&wallet).await?;
+    let season = PointsSeason::resolve(&state.db, query.season).await?;
+
+    let total = PointsSeason::wallet_total(&state.db, season.id, &wallet_address).await?;
+    let rank = PointsSeason::wallet_rank(&state.db, season.id, &wallet_address).await?;
+    let breakdown = PointsSeason::wallet_breakdown(&state.db, season.id, &wallet_address).await?;
+    let recent = PointsSeason::wallet_entries(
+        &state.db,
+        season.id,
+        &wallet_address,
+        query
+            .limit
+            .unwrap_or(RECENT_ENTRIES_LIMIT)
+            .clamp(1, RECENT_ENTRIES_LIMIT),
+    )
+    .await?;
+
+    Ok(Json(json!({
+        "wallet_address": wallet_address,
+        "season": season,
+        "points": total,
+        "rank": rank,
+        "breakdown": breakdown,
+        "recent": recent
+    })))
+}
+
+pub async fn get_leaderboard(
+    State(state): State<AppState>,
+    Query(query): Query<PointsQuery>,
+    page: PageParams<100, MAX_LEADERBOARD_LIMIT>,
+) -> Result<Json<Paginated<Value>>, AppError> {
+    let season = PointsSeason::resolve(&state.db, query.season).await?;
+
+    let leaderboard =
+        PointsSeason::leaderboard(&state.db, season.id, page.fetch_limit(), page.offset()).await?;
+    let leaderboard = Paginated::new("leaderboard", leaderboard, &page);
+    let wallets: Vec<String> = leaderboard
+        .items()
+        .iter()
+        .map(|entry| entry.wallet_address.clone())
+        .collect();
+    let display_names = state.sns().display_names(&wallets).await;
+
+    let leaderboard = leaderboard.map(|entry| {
+        json!({
+            "rank": entry.rank,
+            "wallet_address": entry.wallet_address,
+            "display_name": display_names.get(&entry.wallet_address),
+            "points": entry.points
+        })
+    });
+
+    Ok(Json(leaderboard.with_field("season", season)))
+}
@@ -0,0 +1,53 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{Days, Utc};
+use serde_json::{json, Value};
+
+use super::AppState;
+use crate::{
+    cache,
+    error::AppError,
+    models::{DailyStatsQuery, Dashboard, MarketplaceStats},
+};
+
+const MAX_DAILY_STATS_RANGE_DAYS: i64 = 366;
+
+pub async fn get_daily_stats(
+    State(state): State<AppState>,
+    Query(query): Query<DailyStatsQuery>,
+) -> Result<Json<Value>, AppError> {
+    let to = query.to.unwrap_or_else(|| Utc::now().date_naive());
+    let from = query.from.unwrap_or(to - Days::new(29));
+
+    if from > to {
+        return Err(crate::error::bad_request_error("from must not be after to"));
+    }
+    if (to - from).num_days() >= MAX_DAILY_STATS_RANGE_DAYS {
+        return Err(crate::error::bad_request_error(
+            "date range must not exceed 366 days",
+        ));
+    }
+
+    let stats = MarketplaceStats::get_daily_stats(&state.db, from, to).await?;
+    let currency_breakdown = MarketplaceStats::currency_breakdown(&state.db, from, to).await?;
+
+    Ok(Json(json!({
+        "stats": stats,
+        "currency_breakdown": currency_breakdown
+    })))
+}
+
+/// Served from the copy the scheduler refreshes every minute; built inline
+/// only when the cache is cold
+pub async fn get_dashboard(State(state): State<AppState>) -> Result<Json<Dashboard>, AppError> {
+    if let Some(dashboard) =
+        cache::get_json::<Dashboard>(&state.redis, Dashboard::CACHE_KEY).await?
+    {
+        return Ok(Json(dashboard));
+    }
+
+    let dashboard = Dashboard::refresh_cache(&state.db, &state.redis).await?;
+    Ok(Json(dashboard))
+}
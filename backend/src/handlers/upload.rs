@@ -1,35 +1,14 @@
 use aws_config::Region;
 use aws_sdk_s3::{presigning::PresigningConfig, Client};
 use axum::{extract::State, http::StatusCode, response::Json};
-use serde::{Deserialize, Serialize};
+use solmint_client::{
+    MetadataUploadRequest, MetadataUploadResponse, PresignedUrlRequest, PresignedUrlResponse,
+};
 use std::time::Duration;
 use uuid::Uuid;
 
 use crate::{error::AppError, handlers::AppState};
 
-#[derive(Debug, Deserialize)]
-pub struct PresignedUrlRequest {
-    pub filename: String,
-    pub content_type: String,
-}
-
-#[derive(Debug, Serialize)]
-pub struct PresignedUrlResponse {
-    pub upload_url: String,
-    pub image_url: String,
-    pub key: String,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct MetadataUploadRequest {
-    pub metadata: serde_json::Value,
-}
-
-#[derive(Debug, Serialize)]
-pub struct MetadataUploadResponse {
-    pub metadata_uri: String,
-}
-
 pub async fn generate_presigned_url(
     State(state): State<AppState>,
     Json(req): Json<PresignedUrlRequest>,
@@ -1,20 +1,109 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
     Json,
 };
+use chrono::{Datelike, Utc};
 use serde_json::{json, Value};
+use uuid::Uuid;
 
 use super::AppState;
 use crate::{
     error::AppError,
-    models::{CreateUserRequest, UpdateUserRequest, User},
+    middleware::AuthenticatedWallet,
+    models::{
+        AddFavoriteCollectionRequest, Collection, CreateUserRequest, Nft, TaxReport,
+        TaxReportQuery, TwitterChallenge, TwitterChallengeQuery, UpdateUserRequest, User,
+        UsernameAvailability, VerifyTwitterRequest,
+    },
+    services::twitter,
+    utils::{
+        pagination::{PageParams, Paginated},
+        validation::ValidatedJson,
+    },
 };
 
 pub async fn get_user(
     State(state): State<AppState>,
-    Path(wallet_address): Path<String>,
+    Path(wallet): Path<String>,
 ) -> Result<Json<Value>, AppError> {
+    let sns = state.sns();
+    let wallet_address = sns.resolve_wallet_param(&wallet).await?;
     let user = User::find_by_wallet(&state.db, &wallet_address).await?;
+    // Best effort: a failed lookup shouldn't fail the profile
+    let display_name = sns.reverse_lookup(&wallet_address).await.ok().flatten();
+
+    Ok(Json(json!({
+        "user": user,
+        "display_name": display_name
+    })))
+}
+
+/// Profile by username, case-insensitively
+pub async fn get_profile(
+    State(state): State<AppState>,
+    Path(username): Path<String>,
+) -> Result<Json<Value>, AppError> {
+    let user = User::find_by_username(&state.db, &username)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Profile"))?;
+    let display_name = state
+        .sns()
+        .reverse_lookup(&user.wallet_address)
+        .await
+        .ok()
+        .flatten();
+
+    Ok(Json(json!({
+        "user": user,
+        "display_name": display_name
+    })))
+}
+
+pub async fn get_username_availability(
+    State(state): State<AppState>,
+    Path(username): Path<String>,
+) -> Result<Json<UsernameAvailability>, AppError> {
+    Ok(Json(
+        User::username_availability(&state.db, &username).await?,
+    ))
+}
+
+/// The message the wallet signs; tweeting the signature from `handle` and
+/// posting the tweet's URL to the verify endpoint verifies the handle
+pub async fn get_twitter_challenge(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+    Query(query): Query<TwitterChallengeQuery>,
+) -> Result<Json<TwitterChallenge>, AppError> {
+    let wallet_address = state.sns().resolve_wallet_param(&wallet).await?;
+    let handle = query.handle.trim().trim_start_matches('@').to_string();
+    if handle.is_empty() {
+        return Err(crate::error::bad_request_error("handle is required"));
+    }
+
+    Ok(Json(TwitterChallenge {
+        message: twitter::challenge_message(&wallet_address, &handle),
+        handle,
+    }))
+}
+
+pub async fn verify_twitter(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Json(req): Json<VerifyTwitterRequest>,
+) -> Result<Json<Value>, AppError> {
+    let verifier = state
+        .twitter
+        .as_ref()
+        .ok_or_else(|| crate::error::bad_request_error("Twitter verification is not configured"))?;
+    User::find_by_wallet(&state.db, &wallet_address)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("User"))?;
+
+    let handle = verifier.verify(&wallet_address, &req.tweet_url).await?;
+    let user = User::set_verified_twitter(&state.db, &wallet_address, &handle).await?;
 
     Ok(Json(json!({
         "user": user
@@ -23,8 +112,8 @@ pub async fn get_user(
 
 pub async fn create_or_update_user(
     State(state): State<AppState>,
-    Path(wallet_address): Path<String>,
-    Json(payload): Json<UpdateUserRequest>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    ValidatedJson(payload): ValidatedJson<UpdateUserRequest>,
 ) -> Result<Json<Value>, AppError> {
     let user = match User::find_by_wallet(&state.db, &wallet_address).await? {
         Some(_) => User::update(&state.db, &wallet_address, payload).await?,
@@ -49,34 +138,39 @@ pub async fn create_or_update_user(
 
 pub async fn get_user_favorites(
     State(state): State<AppState>,
-    Path(wallet_address): Path<String>,
-) -> Result<Json<Value>, AppError> {
+    Path(wallet): Path<String>,
+    page: PageParams,
+) -> Result<Json<Paginated<Nft>>, AppError> {
+    let wallet_address = state.sns().resolve_wallet_param(&wallet).await?;
     let favorites = sqlx::query_as!(
-        crate::models::Nft,
+        Nft,
         r#"
         SELECT n.id, n.mint_address, n.collection_id, n.name, n.description, n.image_url,
                n.animation_url, n.external_url, n.attributes, n.creator_address,
                n.current_owner, n.is_compressed as "is_compressed!", n.rarity_rank, n.rarity_score,
-               n.created_at as "created_at!", n.updated_at as "updated_at!"
+               n.created_at as "created_at!", n.updated_at as "updated_at!", n.version,
+               n.is_spam as "is_spam!"
         FROM nfts n
         JOIN user_favorites uf ON n.mint_address = uf.nft_mint
         JOIN users u ON uf.user_id = u.id
-        WHERE u.wallet_address = $1
-        ORDER BY uf.created_at DESC
+        WHERE u.wallet_address = $1 AND n.deleted_at IS NULL
+          AND NOT nft_is_blocked(n.mint_address, n.collection_id, n.creator_address)
+        ORDER BY uf.created_at DESC, n.id
+        LIMIT $2 OFFSET $3
         "#,
-        wallet_address
+        wallet_address,
+        page.fetch_limit(),
+        page.offset()
     )
     .fetch_all(&state.db)
     .await?;
 
-    Ok(Json(json!({
-        "favorites": favorites
-    })))
+    Ok(Json(Paginated::new("favorites", favorites, &page)))
 }
 
 pub async fn add_favorite(
     State(state): State<AppState>,
-    Path(wallet_address): Path<String>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
     Json(payload): Json<serde_json::Value>,
 ) -> Result<Json<Value>, AppError> {
     let nft_mint = payload["nft_mint"]
@@ -102,7 +196,8 @@ pub async fn add_favorite(
 
 pub async fn remove_favorite(
     State(state): State<AppState>,
-    Path((wallet_address, nft_mint)): Path<(String, String)>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Path((_, nft_mint)): Path<(String, String)>,
 ) -> Result<Json<Value>, AppError> {
     let user = User::find_by_wallet(&state.db, &wallet_address)
         .await?
@@ -120,3 +215,120 @@ pub async fn remove_favorite(
         "success": true
     })))
 }
+
+pub async fn get_favorite_collections(
+    State(state): State<AppState>,
+    Path(wallet): Path<String>,
+    page: PageParams,
+) -> Result<Json<Paginated<Collection>>, AppError> {
+    let wallet_address = state.sns().resolve_wallet_param(&wallet).await?;
+    let collections = sqlx::query_as!(
+        Collection,
+        r#"
+        SELECT c.id, c.name, c.symbol, c.description, c.image_url, c.banner_url,
+               c.creator_address, c.verified as "verified!", c.rank_score,
+               c.created_at as "created_at!", c.updated_at as "updated_at!", c.is_spam,
+               (SELECT COUNT(*) FROM user_favorite_collections f WHERE f.collection_id = c.id)
+                   AS "favorite_count!"
+        FROM collections c
+        JOIN user_favorite_collections ufc ON c.id = ufc.collection_id
+        JOIN users u ON ufc.user_id = u.id
+        WHERE u.wallet_address = $1 AND c.deleted_at IS NULL
+          AND NOT collection_is_blocked(c.id, c.creator_address)
+        ORDER BY ufc.created_at DESC, c.id
+        LIMIT $2 OFFSET $3
+        "#,
+        wallet_address,
+        page.fetch_limit(),
+        page.offset()
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(Paginated::new("collections", collections, &page)))
+}
+
+pub async fn add_favorite_collection(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Json(payload): Json<AddFavoriteCollectionRequest>,
+) -> Result<Json<Value>, AppError> {
+    let user = User::find_by_wallet(&state.db, &wallet_address)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("User"))?;
+    Collection::find_by_id(&state.db, payload.collection_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Collection"))?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO user_favorite_collections (user_id, collection_id) VALUES ($1, $2)
+        ON CONFLICT DO NOTHING
+        "#,
+        user.id,
+        payload.collection_id
+    )
+    .execute(&state.db)
+    .await?;
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
+
+pub async fn remove_favorite_collection(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Path((_, collection_id)): Path<(String, Uuid)>,
+) -> Result<Json<Value>, AppError> {
+    let user = User::find_by_wallet(&state.db, &wallet_address)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("User"))?;
+
+    sqlx::query!(
+        "DELETE FROM user_favorite_collections WHERE user_id = $1 AND collection_id = $2",
+        user.id,
+        collection_id
+    )
+    .execute(&state.db)
+    .await?;
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
+
+pub async fn get_tax_report(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Query(query): Query<TaxReportQuery>,
+) -> Result<Response, AppError> {
+    if query.year < 2020 || query.year > Utc::now().year() {
+        return Err(crate::error::bad_request_error(
+            "year must be between 2020 and the current year",
+        ));
+    }
+
+    let report = TaxReport::build(&state.db, &wallet_address, query.year).await?;
+
+    match query.format.as_deref() {
+        Some("json") | None => Ok(Json(report).into_response()),
+        Some("csv") => {
+            let filename = format!("solmint-tax-report-{}-{}.csv", wallet_address, query.year);
+            Ok((
+                [
+                    (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        format!("attachment; filename=\"{}\"", filename),
+                    ),
+                ],
+                report.to_csv(),
+            )
+                .into_response())
+        }
+        Some(_) => Err(crate::error::bad_request_error(
+            "format must be json or csv",
+        )),
+    }
+}
@@ -0,0 +1,136 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use super::AppState;
+use crate::{
+    error::AppError,
+    middleware::AuthenticatedWallet,
+    models::{
+        Collection, CreateWebhookRequest, UpdateWebhookRequest, Webhook, WebhookDelivery,
+        WebhookDeliveryQuery, DELIVERY_STATUSES,
+    },
+    utils::pagination::{PageParams, Paginated},
+};
+
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+) -> Result<Json<Value>, AppError> {
+    let webhooks = Webhook::list_for_wallet(&state.db, &wallet_address).await?;
+
+    Ok(Json(json!({
+        "webhooks": webhooks
+    })))
+}
+
+/// Register a webhook for sale, listing, mint or offer activity. The
+/// returned `secret` signs deliveries and is only shown here.
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Json(req): Json<CreateWebhookRequest>,
+) -> Result<Json<Value>, AppError> {
+    if let Some(collection_id) = req.collection_id {
+        Collection::find_by_id(&state.db, collection_id)
+            .await?
+            .ok_or_else(|| crate::error::not_found_error("Collection"))?;
+    }
+
+    let webhook = Webhook::create(&state.db, &wallet_address, &req).await?;
+    let secret = webhook.secret.clone();
+
+    Ok(Json(json!({
+        "webhook": webhook,
+        "secret": secret
+    })))
+}
+
+pub async fn update_webhook(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Path((_, id)): Path<(String, Uuid)>,
+    Json(req): Json<UpdateWebhookRequest>,
+) -> Result<Json<Value>, AppError> {
+    let webhook = Webhook::update(&state.db, &wallet_address, id, &req)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Webhook"))?;
+
+    Ok(Json(json!({
+        "webhook": webhook
+    })))
+}
+
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Path((_, id)): Path<(String, Uuid)>,
+) -> Result<Json<Value>, AppError> {
+    if !Webhook::delete(&state.db, &wallet_address, id).await? {
+        return Err(crate::error::not_found_error("Webhook"));
+    }
+
+    Ok(Json(json!({
+        "success": true
+    })))
+}
+
+/// The webhook's delivery log, newest first
+pub async fn list_webhook_deliveries(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Path((_, id)): Path<(String, Uuid)>,
+    Query(query): Query<WebhookDeliveryQuery>,
+    page: PageParams<50, 200>,
+) -> Result<Json<Paginated<WebhookDelivery>>, AppError> {
+    if let Some(status) = query.status.as_deref() {
+        if !DELIVERY_STATUSES.contains(&status) {
+            return Err(AppError::BadRequest(format!(
+                "status must be one of {}",
+                DELIVERY_STATUSES.join(", ")
+            )));
+        }
+    }
+    Webhook::find_owned(&state.db, &wallet_address, id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Webhook"))?;
+
+    let deliveries = WebhookDelivery::list(
+        &state.db,
+        id,
+        query.status.as_deref(),
+        page.cursor()?,
+        page.fetch_limit(),
+    )
+    .await?;
+
+    Ok(Json(
+        Paginated::new("deliveries", deliveries, &page).with_cursor(|delivery| delivery.id),
+    ))
+}
+
+/// Send a delivered or failed delivery again
+pub async fn redeliver_webhook_delivery(
+    State(state): State<AppState>,
+    AuthenticatedWallet(wallet_address): AuthenticatedWallet,
+    Path((_, id, delivery_id)): Path<(String, Uuid, i64)>,
+) -> Result<Json<Value>, AppError> {
+    Webhook::find_owned(&state.db, &wallet_address, id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Webhook"))?;
+
+    WebhookDelivery::find(&state.db, id, delivery_id)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Delivery"))?;
+
+    let delivery = WebhookDelivery::redeliver(&state.db, id, delivery_id)
+        .await?
+        .ok_or_else(|| crate::error::conflict_error("delivery is already pending"))?;
+
+    Ok(Json(json!({
+        "delivery": delivery
+    })))
+}
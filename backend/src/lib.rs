@@ -0,0 +1,18 @@
+pub mod app;
+pub mod cache;
+pub mod cluster;
+pub mod config;
+pub mod database;
+pub mod error;
+pub mod handlers;
+#[cfg(feature = "dev-tools")]
+pub mod loadtest;
+pub mod middleware;
+pub mod models;
+pub mod runtime;
+pub mod seed;
+pub mod services;
+pub mod startup;
+#[cfg(feature = "dev-tools")]
+pub mod synthetic;
+pub mod utils;
@@ -0,0 +1,256 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+use crate::error::AppError;
+
+/// Load-test settings for [`run`]
+#[derive(Debug, Clone)]
+pub struct LoadTest {
+    pub base_url: String,
+    pub concurrency: usize,
+    pub duration: Duration,
+    /// Only run scenarios with these names; all of them when empty
+    pub scenarios: Vec<String>,
+}
+
+/// Latency summary for one scenario, in milliseconds
+#[derive(Debug, Clone, Serialize)]
+pub struct ScenarioReport {
+    pub name: &'static str,
+    pub requests: usize,
+    pub errors: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub duration_secs: f64,
+    pub requests_per_sec: f64,
+    pub scenarios: Vec<ScenarioReport>,
+}
+
+struct Scenario {
+    name: &'static str,
+    paths: Vec<String>,
+}
+
+/// Ids to aim the scenarios at, read from the API so the driver works
+/// against any deployment
+struct Targets {
+    collection_ids: Vec<String>,
+    mints: Vec<String>,
+}
+
+/// Hammer the hot read endpoints with `concurrency` workers for `duration`
+/// and report latency percentiles per endpoint. Workers take the scenarios
+/// in turn, so each gets a similar share of requests.
+pub async fn run(config: &LoadTest) -> Result<Report, AppError> {
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| AppError::ConfigError(e.to_string()))?;
+    let base_url = config.base_url.trim_end_matches('/').to_string();
+
+    let targets = discover_targets(&http, &base_url).await?;
+    let scenarios: Vec<Scenario> = scenarios(&targets)
+        .into_iter()
+        .filter(|scenario| {
+            config.scenarios.is_empty() || config.scenarios.iter().any(|name| name == scenario.name)
+        })
+        .filter(|scenario| !scenario.paths.is_empty())
+        .collect();
+    if scenarios.is_empty() {
+        return Err(crate::error::bad_request_error("no scenarios to run"));
+    }
+    let scenarios = std::sync::Arc::new(scenarios);
+
+    let started = Instant::now();
+    let deadline = started + config.duration;
+    let workers: Vec<_> = (0..config.concurrency.max(1))
+        .map(|worker| {
+            let http = http.clone();
+            let base_url = base_url.clone();
+            let scenarios = scenarios.clone();
+            tokio::spawn(async move {
+                // (scenario, latency, ok) per request
+                let mut samples = Vec::new();
+                let mut turn = worker;
+                while Instant::now() < deadline {
+                    let scenario_index = turn % scenarios.len();
+                    let scenario = &scenarios[scenario_index];
+                    let path = &scenario.paths[(turn / scenarios.len()) % scenario.paths.len()];
+                    turn += 1;
+
+                    let request_started = Instant::now();
+                    let ok = match http.get(format!("{}{}", base_url, path)).send().await {
+                        Ok(response) => {
+                            let ok = response.status().is_success();
+                            // Read the body so its transfer counts towards the latency
+                            ok && response.bytes().await.is_ok()
+                        }
+                        Err(_) => false,
+                    };
+                    samples.push((scenario_index, request_started.elapsed(), ok));
+                }
+                samples
+            })
+        })
+        .collect();
+
+    let mut latencies: Vec<Vec<Duration>> = vec![Vec::new(); scenarios.len()];
+    let mut errors = vec![0; scenarios.len()];
+    for worker in workers {
+        let samples = worker.await.map_err(std::io::Error::other)?;
+        for (scenario, latency, ok) in samples {
+            latencies[scenario].push(latency);
+            if !ok {
+                errors[scenario] += 1;
+            }
+        }
+    }
+    let elapsed = started.elapsed().as_secs_f64();
+    let total: usize = latencies.iter().map(Vec::len).sum();
+
+    let reports = scenarios
+        .iter()
+        .zip(latencies.iter_mut())
+        .zip(errors)
+        .map(|((scenario, latencies), errors)| {
+            latencies.sort();
+            ScenarioReport {
+                name: scenario.name,
+                requests: latencies.len(),
+                errors,
+                p50_ms: percentile(latencies, 0.50),
+                p95_ms: percentile(latencies, 0.95),
+                p99_ms: percentile(latencies, 0.99),
+                max_ms: percentile(latencies, 1.0),
+            }
+        })
+        .collect();
+
+    Ok(Report {
+        duration_secs: elapsed,
+        requests_per_sec: total as f64 / elapsed,
+        scenarios: reports,
+    })
+}
+
+/// Nearest-rank percentile of sorted latencies, in milliseconds
+fn percentile(sorted: &[Duration], quantile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((sorted.len() as f64 * quantile).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1].as_secs_f64() * 1000.0
+}
+
+async fn discover_targets(http: &reqwest::Client, base_url: &str) -> Result<Targets, AppError> {
+    let collections = get_json(http, &format!("{}/api/v1/collections?limit=20", base_url)).await?;
+    let nfts = get_json(http, &format!("{}/api/v1/nfts?limit=50", base_url)).await?;
+
+    let field = |value: &Value, list: &str, key: &str| -> Vec<String> {
+        value[list]
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item[key].as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    Ok(Targets {
+        collection_ids: field(&collections, "collections", "id"),
+        mints: field(&nfts, "nfts", "mint_address"),
+    })
+}
+
+async fn get_json(http: &reqwest::Client, url: &str) -> Result<Value, AppError> {
+    let response = http
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| AppError::Upstream(format!("GET {}: {}", url, e)))?;
+    if !response.status().is_success() {
+        return Err(AppError::Upstream(format!(
+            "GET {}: {}",
+            url,
+            response.status()
+        )));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| AppError::Upstream(format!("GET {}: {}", url, e)))
+}
+
+fn scenarios(targets: &Targets) -> Vec<Scenario> {
+    let per_collection = |format_path: fn(&str) -> String| -> Vec<String> {
+        targets
+            .collection_ids
+            .iter()
+            .map(|id| format_path(id))
+            .collect()
+    };
+
+    vec![
+        Scenario {
+            name: "nfts_recent",
+            paths: vec!["/api/v1/nfts?sort_by=created_at&sort_order=desc".to_string()],
+        },
+        Scenario {
+            name: "nfts_by_price",
+            paths: per_collection(|id| {
+                format!(
+                    "/api/v1/nfts?collection_id={}&sort_by=price&sort_order=asc",
+                    id
+                )
+            }),
+        },
+        Scenario {
+            name: "nfts_by_rarity",
+            paths: per_collection(|id| format!("/api/v1/nfts?collection_id={}&sort_by=rarity", id)),
+        },
+        Scenario {
+            name: "nfts_price_range",
+            paths: vec![
+                "/api/v1/nfts?min_price=100000000&max_price=2000000000&sort_by=price".to_string(),
+            ],
+        },
+        Scenario {
+            name: "nft_detail",
+            paths: targets
+                .mints
+                .iter()
+                .map(|mint| format!("/api/v1/nfts/{}", mint))
+                .collect(),
+        },
+        Scenario {
+            name: "collections_by_rank",
+            paths: vec!["/api/v1/collections?sort_by=rank".to_string()],
+        },
+        Scenario {
+            name: "collection_candles",
+            paths: per_collection(|id| format!("/api/v1/collections/{}/candles?resolution=1d", id)),
+        },
+        Scenario {
+            name: "collection_churn",
+            paths: per_collection(|id| format!("/api/v1/collections/{}/churn", id)),
+        },
+        Scenario {
+            name: "daily_stats",
+            paths: vec!["/api/v1/stats/daily".to_string()],
+        },
+        Scenario {
+            name: "dashboard",
+            paths: vec!["/api/v1/stats/dashboard".to_string()],
+        },
+    ]
+}
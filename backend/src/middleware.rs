@@ -0,0 +1,471 @@
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, FromRequestParts, RawPathParams, Request, State},
+    http::{
+        header::{AUTHORIZATION, CONTENT_TYPE},
+        request::Parts,
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use governor::{DefaultKeyedRateLimiter, Quota, RateLimiter};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    net::{IpAddr, SocketAddr},
+    num::NonZeroU32,
+    sync::OnceLock,
+};
+use uuid::Uuid;
+
+use crate::{
+    cache,
+    config::Config,
+    error::{AppError, ErrorCode},
+    handlers::AppState,
+    models::{AccessBlock, AdminApiKey, Role, User, BLOCK_IP, BLOCK_WALLET},
+    services::{abuse, auth},
+};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+/// Longer caller-supplied ids are replaced rather than echoed
+const MAX_REQUEST_ID_LENGTH: usize = 64;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Tag every request with an id, the caller's `X-Request-Id` when it sent a
+/// usable one, and echo it back. Error bodies include it so a failure report
+/// can be matched to the logs.
+pub async fn request_id(request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| {
+            !id.is_empty()
+                && id.len() <= MAX_REQUEST_ID_LENGTH
+                && id
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        })
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let mut response = REQUEST_ID
+        .scope(request_id.clone(), next.run(request))
+        .await;
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// The id of the request being handled, outside of a request `None`
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// Set on responses replayed for a repeated `Idempotency-Key`
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+/// How long a key's response is kept for replay
+const IDEMPOTENCY_TTL_SECONDS: u64 = 86_400;
+/// How long a key stays claimed by a request that hasn't finished, so a
+/// crashed request doesn't lock its key out for the full TTL
+const IDEMPOTENCY_CLAIM_SECONDS: u64 = 300;
+/// Request and response bodies larger than this aren't idempotent
+const MAX_IDEMPOTENT_BODY_BYTES: usize = 1024 * 1024;
+
+/// What is stored under an idempotency key: the request it was first used
+/// with and, once that finished, its response
+#[derive(Serialize, Deserialize)]
+struct IdempotencyRecord {
+    request_hash: String,
+    status: Option<u16>,
+    content_type: Option<String>,
+    body: Option<String>,
+}
+
+/// Requests carrying an `Idempotency-Key` header run at most once per key:
+/// a retry with the same key and body gets the first response back, marked
+/// `Idempotent-Replayed: true`, instead of building or submitting another
+/// transaction. Server errors aren't kept, so those can be retried. Keys are
+/// per wallet: a request is only replayed to the wallet whose token made it,
+/// and requests without a valid token run as usual, to be refused by the
+/// route.
+pub async fn idempotency(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(next.run(request).await);
+    };
+    let key = key
+        .to_str()
+        .ok()
+        .filter(|key| !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LENGTH)
+        .ok_or_else(|| {
+            AppError::ValidationError(format!(
+                "Idempotency-Key must be 1 to {} visible ASCII characters",
+                MAX_IDEMPOTENCY_KEY_LENGTH
+            ))
+        })?;
+
+    let Some(wallet) = bearer_token(request.headers())
+        .and_then(|token| auth::verify_token(&state.config, token).ok())
+        .map(|claims| claims.sub)
+    else {
+        return Ok(next.run(request).await);
+    };
+
+    let (parts, body) = request.into_parts();
+    let body = axum::body::to_bytes(body, MAX_IDEMPOTENT_BODY_BYTES)
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let request_hash = hex::encode(Sha256::digest(&body));
+    let redis_key = format!("idempotency:{}:{}:{}", wallet, parts.uri.path(), key);
+
+    // Claim the key; whoever holds it runs the request
+    let mut pending = IdempotencyRecord {
+        request_hash,
+        status: None,
+        content_type: None,
+        body: None,
+    };
+    let mut conn = state.redis.clone();
+    let claimed: Option<String> = redis::cmd("SET")
+        .arg(&redis_key)
+        .arg(serde_json::to_string(&pending)?)
+        .arg("NX")
+        .arg("EX")
+        .arg(IDEMPOTENCY_CLAIM_SECONDS)
+        .query_async(&mut conn)
+        .await?;
+    if claimed.is_none() {
+        let record: Option<IdempotencyRecord> = cache::get_json(&state.redis, &redis_key).await?;
+        return match record {
+            Some(record) if record.request_hash != pending.request_hash => {
+                Err(AppError::BadRequest(
+                    "Idempotency-Key was already used with a different request".to_string(),
+                ))
+            }
+            Some(IdempotencyRecord {
+                status: Some(status),
+                content_type,
+                body: Some(body),
+                ..
+            }) => Ok(replay(status, content_type, body)),
+            _ => Err(crate::error::conflict_error(
+                "a request with this Idempotency-Key is still in progress",
+            )),
+        };
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let status = response.status();
+    let (parts, body) = response.into_parts();
+    let body = axum::body::to_bytes(body, MAX_IDEMPOTENT_BODY_BYTES).await;
+    let stored = match &body {
+        Ok(bytes) if !status.is_server_error() => std::str::from_utf8(bytes).ok(),
+        _ => None,
+    };
+
+    match stored {
+        Some(stored) => {
+            pending.status = Some(status.as_u16());
+            pending.content_type = parts
+                .headers
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            pending.body = Some(stored.to_string());
+            cache::set_json(&state.redis, &redis_key, &pending, IDEMPOTENCY_TTL_SECONDS).await?;
+        }
+        // Release the key so the request can be retried
+        None => {
+            let _: () = redis::cmd("DEL")
+                .arg(&redis_key)
+                .query_async(&mut conn)
+                .await?;
+        }
+    }
+
+    let body = body.map_err(|e| AppError::SerializationError(e.to_string()))?;
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+fn replay(status: u16, content_type: Option<String>, body: String) -> Response {
+    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::OK);
+    let mut response = (status, body).into_response();
+    if let Some(value) = content_type.and_then(|value| HeaderValue::from_str(&value).ok()) {
+        response.headers_mut().insert(CONTENT_TYPE, value);
+    }
+    response
+        .headers_mut()
+        .insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+/// Per-IP request limit, using the currently configured quota
+pub async fn rate_limit(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if state.runtime.rate_limiter().check_key(&addr.ip()).is_err() {
+        return Err(AppError::RateLimited);
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Requests per minute an IP may make to routes that scan program accounts
+const RPC_SCAN_REQUESTS_PER_MINUTE: u32 = 10;
+
+static RPC_SCAN_LIMITER: OnceLock<DefaultKeyedRateLimiter<IpAddr>> = OnceLock::new();
+
+/// Per-IP limit, on top of `rate_limit`, for routes whose uncached requests
+/// make several `getProgramAccounts` calls to the RPC node
+pub async fn rpc_scan_limit(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let limiter = RPC_SCAN_LIMITER.get_or_init(|| {
+        let per_minute = NonZeroU32::new(RPC_SCAN_REQUESTS_PER_MINUTE).unwrap_or(NonZeroU32::MIN);
+        RateLimiter::keyed(Quota::per_minute(per_minute))
+    });
+    if limiter.check_key(&addr.ip()).is_err() {
+        return Err(AppError::RateLimited);
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// The router's body limit
+const MAX_GUARDED_BODY_BYTES: usize = 10 * 1024 * 1024;
+/// Body fields naming the wallet a write request acts for
+const WALLET_FIELDS: &[&str] = &[
+    "wallet",
+    "wallet_address",
+    "creator",
+    "buyer",
+    "buyer_wallet",
+    "bidder",
+    "seller",
+];
+
+/// Refuse requests from blocked wallets and IPs, and count failed requests
+/// towards an automatic block. The wallets checked are the signed-in wallet
+/// and any a JSON body names; failures only count against the IP and the
+/// signed-in wallet, so naming someone else's wallet can't get it blocked.
+pub async fn abuse_guard(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let (parts, body) = request.into_parts();
+    let body = axum::body::to_bytes(body, MAX_GUARDED_BODY_BYTES)
+        .await
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    let signed_in = bearer_token(&parts.headers)
+        .and_then(|token| auth::verify_token(&state.config, token).ok())
+        .map(|claims| claims.sub);
+    let mut wallets: Vec<String> = serde_json::from_slice::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|value| value.as_object().cloned())
+        .map(|fields| {
+            WALLET_FIELDS
+                .iter()
+                .filter_map(|field| fields.get(*field)?.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    wallets.extend(signed_in.clone());
+    wallets.sort_unstable();
+    wallets.dedup();
+
+    let ip = addr.ip().to_string();
+    if abuse::is_blocked(&state.redis, BLOCK_IP, &ip).await? {
+        return Err(blocked(BLOCK_IP, &ip, "too many failed requests"));
+    }
+    for wallet in &wallets {
+        if abuse::is_blocked(&state.redis, BLOCK_WALLET, wallet).await? {
+            return Err(blocked(BLOCK_WALLET, wallet, "too many failed requests"));
+        }
+    }
+    if let Some(block) = AccessBlock::find_active(&state.db, &wallets, addr.ip()).await? {
+        return Err(blocked(
+            &block.kind,
+            &block.value,
+            block.reason.as_deref().unwrap_or("blocked by a moderator"),
+        ));
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    if abuse::is_failure(response.status()) {
+        let mut failed = vec![(BLOCK_IP, ip.as_str())];
+        failed.extend(signed_in.as_deref().map(|wallet| (BLOCK_WALLET, wallet)));
+        for (kind, value) in failed {
+            if let Err(e) = abuse::record_failure(&state.redis, kind, value).await {
+                eprintln!("Failed to record a failed request: {}", e);
+            }
+        }
+    }
+
+    Ok(response)
+}
+
+fn blocked(kind: &str, value: &str, reason: &str) -> AppError {
+    AppError::Rejected(
+        ErrorCode::AccessBlocked,
+        format!("{} {} is blocked: {}", kind, value, reason),
+    )
+}
+
+/// Admin routes require `Authorization: Bearer` with `ADMIN_API_KEY`, a live
+/// key from `admin_api_keys`, or the token of a wallet with the admin role
+pub async fn require_admin(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    authorize(&state, request.headers(), Role::Admin).await?;
+
+    Ok(next.run(request).await)
+}
+
+/// Moderation routes take a moderator's token as well as admin credentials
+pub async fn require_moderator(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    authorize(&state, request.headers(), Role::Moderator).await?;
+
+    Ok(next.run(request).await)
+}
+
+/// Whether the request is authorized as `role` or above, for public routes
+/// with moderator-only options
+pub async fn has_role(state: &AppState, headers: &HeaderMap, role: Role) -> bool {
+    authorize(state, headers, role).await.is_ok()
+}
+
+/// Check the request's bearer credential is at least `required`. Admin API
+/// keys act as admins; wallet tokens carry their wallet's role.
+async fn authorize(state: &AppState, headers: &HeaderMap, required: Role) -> Result<(), AppError> {
+    let provided = bearer_token(headers)
+        .filter(|provided| !provided.is_empty())
+        .ok_or(AppError::Unauthorized)?;
+
+    let role = match auth::verify_token(&state.config, provided) {
+        Ok(claims) => User::role(&state.db, &claims.sub).await?,
+        Err(_) if is_admin_key(state, provided).await => Role::Admin,
+        Err(e) => return Err(e),
+    };
+    if role < required {
+        return Err(AppError::Forbidden(format!(
+            "requires the {} role",
+            required.as_str()
+        )));
+    }
+
+    Ok(())
+}
+
+async fn is_admin_key(state: &AppState, provided: &str) -> bool {
+    if is_configured_admin_key(&state.config, provided) {
+        return true;
+    }
+
+    match AdminApiKey::authenticate(&state.db, provided).await {
+        Ok(valid) => valid,
+        Err(e) => {
+            eprintln!("Failed to check admin API key: {}", e);
+            false
+        }
+    }
+}
+
+fn is_configured_admin_key(config: &Config, provided: &str) -> bool {
+    let Some(expected) = config.admin_api_key.as_deref() else {
+        return false;
+    };
+
+    // Compare without short-circuiting so timing doesn't leak the key
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// The wallet a request is signed in as, from the `Authorization: Bearer`
+/// token issued by `/api/auth/verify`. On routes with a `{wallet}` path
+/// parameter the token must be for that wallet (or the `.sol` domain
+/// resolving to it), so one wallet can't change another's data.
+pub struct AuthenticatedWallet(pub String);
+
+impl FromRequestParts<AppState> for AuthenticatedWallet {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, AppError> {
+        let token = bearer_token(&parts.headers).ok_or(AppError::Unauthorized)?;
+        let claims = auth::verify_token(&state.config, token)?;
+
+        let params = RawPathParams::from_request_parts(parts, state)
+            .await
+            .map_err(|e| AppError::BadRequest(e.body_text()))?;
+        if let Some((_, wallet)) = params.iter().find(|(name, _)| *name == "wallet") {
+            let wallet_address = state.sns().resolve_wallet_param(wallet).await?;
+            if wallet_address != claims.sub {
+                return Err(crate::error::wrong_wallet_error());
+            }
+        }
+
+        Ok(Self(claims.sub))
+    }
+}
+
+/// Requires an admin: for handlers outside the admin router, which checks
+/// this already
+pub struct AdminAuth;
+
+impl FromRequestParts<AppState> for AdminAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, AppError> {
+        authorize(state, &parts.headers, Role::Admin).await?;
+        Ok(Self)
+    }
+}
+
+/// Requires a moderator or an admin
+pub struct ModeratorAuth;
+
+impl FromRequestParts<AppState> for ModeratorAuth {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, AppError> {
+        authorize(state, &parts.headers, Role::Moderator).await?;
+        Ok(Self)
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use sqlx::{FromRow, PgPool};
+use std::{net::IpAddr, str::FromStr};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+pub use solmint_client::CreateAccessBlockRequest;
+
+pub const BLOCK_WALLET: &str = "wallet";
+pub const BLOCK_IP: &str = "ip";
+
+/// A wallet or IP a moderator has refused on write routes
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AccessBlock {
+    pub id: Uuid,
+    pub kind: String,
+    pub value: String,
+    pub reason: Option<String>,
+    pub created_by: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+const ACCESS_BLOCK_COLUMNS: &str = "id, kind, value, reason, created_by, expires_at, created_at";
+
+impl AccessBlock {
+    /// Block a wallet or IP. An expired block on the same value is replaced.
+    pub async fn create(pool: &PgPool, req: CreateAccessBlockRequest) -> Result<Self, AppError> {
+        let value = req.value.trim();
+        // Stored in the form requests are compared against
+        let value = match req.kind.as_str() {
+            BLOCK_WALLET => Pubkey::from_str(value)
+                .map_err(|_| AppError::BadRequest(format!("invalid address: {}", value)))?
+                .to_string(),
+            BLOCK_IP => IpAddr::from_str(value)
+                .map_err(|_| AppError::BadRequest(format!("invalid IP address: {}", value)))?
+                .to_string(),
+            _ => return Err(crate::error::bad_request_error("kind must be wallet or ip")),
+        };
+        if req
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+        {
+            return Err(crate::error::bad_request_error(
+                "expires_at must be in the future",
+            ));
+        }
+
+        let block = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            INSERT INTO access_blocks (kind, value, reason, created_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (kind, value) DO UPDATE
+                SET reason = EXCLUDED.reason, created_by = EXCLUDED.created_by,
+                    expires_at = EXCLUDED.expires_at, created_at = NOW()
+                WHERE access_blocks.expires_at <= NOW()
+            RETURNING {}
+            "#,
+            ACCESS_BLOCK_COLUMNS
+        ))
+        .bind(&req.kind)
+        .bind(&value)
+        .bind(&req.reason)
+        .bind(&req.actor)
+        .bind(req.expires_at)
+        .fetch_optional(pool)
+        .await?;
+
+        block.ok_or_else(|| crate::error::conflict_error("already blocked"))
+    }
+
+    /// Blocks in force, newest first
+    pub async fn list(
+        pool: &PgPool,
+        kind: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, AppError> {
+        let blocks = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            SELECT {} FROM access_blocks
+            WHERE ($1::text IS NULL OR kind = $1)
+              AND (expires_at IS NULL OR expires_at > NOW())
+            ORDER BY created_at DESC, id
+            LIMIT $2 OFFSET $3
+            "#,
+            ACCESS_BLOCK_COLUMNS
+        ))
+        .bind(kind)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(blocks)
+    }
+
+    /// The block in force on any of the wallets or on the IP, if there is one
+    pub async fn find_active(
+        pool: &PgPool,
+        wallets: &[String],
+        ip: IpAddr,
+    ) -> Result<Option<Self>, AppError> {
+        let block = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            SELECT {} FROM access_blocks
+            WHERE ((kind = 'wallet' AND value = ANY($1)) OR (kind = 'ip' AND value = $2))
+              AND (expires_at IS NULL OR expires_at > NOW())
+            LIMIT 1
+            "#,
+            ACCESS_BLOCK_COLUMNS
+        ))
+        .bind(wallets)
+        .bind(ip.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(block)
+    }
+
+    /// Unblock, returning the removed block
+    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<Option<Self>, AppError> {
+        let block = sqlx::query_as::<_, Self>(&format!(
+            "DELETE FROM access_blocks WHERE id = $1 RETURNING {}",
+            ACCESS_BLOCK_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(block)
+    }
+}
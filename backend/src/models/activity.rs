@@ -0,0 +1,208 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+pub use solmint_client::{ActivityQuery, FirehoseQuery};
+
+/// Advisory lock key so only one sequencer runs at a time ("actseq" in
+/// ASCII); interleaved batches could commit sequence numbers out of order
+const SEQUENCER_LOCK_KEY: i64 = 0x6163_7473_6571;
+/// Rows sequenced per run
+const SEQUENCER_BATCH_SIZE: i64 = 10_000;
+
+/// Values of `activity_type`
+pub const ACTIVITY_TYPES: &[&str] = &[
+    "mint",
+    "list",
+    "price_change",
+    "delist",
+    "sale",
+    "rent",
+    "transfer",
+    "offer",
+];
+
+/// One indexed event in the firehose. `id` is the firehose sequence number:
+/// contiguous, and assigned only once the event has committed.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Activity {
+    pub id: i64,
+    /// mint, list, price_change, delist, sale, rent, transfer or offer
+    pub activity_type: String,
+    pub nft_mint: String,
+    pub collection_id: Option<Uuid>,
+    pub from_address: Option<String>,
+    pub to_address: Option<String>,
+    pub price: Option<i64>,
+    pub currency_mint: Option<String>,
+    pub transaction_signature: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FirehosePage {
+    pub activities: Vec<Activity>,
+    /// Pass as `since_id` to resume after this page
+    pub next_since_id: i64,
+    pub has_more: bool,
+}
+
+/// Whose activity to list
+#[derive(Debug, Clone, Copy)]
+pub enum ActivitySubject<'a> {
+    Nft(&'a str),
+    /// Activities from or to the wallet
+    Wallet(&'a str),
+}
+
+impl ActivitySubject<'_> {
+    fn filter(&self) -> &'static str {
+        match self {
+            Self::Nft(_) => "nft_mint = $1",
+            Self::Wallet(_) => "(from_address = $1 OR to_address = $1)",
+        }
+    }
+
+    fn value(&self) -> &str {
+        match self {
+            Self::Nft(value) | Self::Wallet(value) => value,
+        }
+    }
+}
+
+impl Activity {
+    /// Activities of an NFT or wallet of the given types (all types when
+    /// empty), newest first, from before the activity `before` when paging
+    /// by cursor. Only sequenced activities are listed, as in the firehose.
+    pub async fn list(
+        pool: &PgPool,
+        subject: ActivitySubject<'_>,
+        types: &[String],
+        before: Option<i64>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, AppError> {
+        let activities = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            SELECT sequence AS id, activity_type, nft_mint, collection_id, from_address,
+                   to_address, price, currency_mint, transaction_signature, occurred_at
+            FROM activities
+            WHERE sequence IS NOT NULL
+              AND {}
+              AND (cardinality($2::text[]) = 0 OR activity_type = ANY($2))
+              AND ($3::bigint IS NULL OR sequence < $3)
+            ORDER BY sequence DESC
+            LIMIT $4 OFFSET $5
+            "#,
+            subject.filter()
+        ))
+        .bind(subject.value())
+        .bind(types)
+        .bind(before)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(activities)
+    }
+
+    /// Number of activities [`Activity::list`] pages through
+    pub async fn count(
+        pool: &PgPool,
+        subject: ActivitySubject<'_>,
+        types: &[String],
+    ) -> Result<i64, AppError> {
+        let count: i64 = sqlx::query_scalar(&format!(
+            r#"
+            SELECT COUNT(*) FROM activities
+            WHERE sequence IS NOT NULL
+              AND {}
+              AND (cardinality($2::text[]) = 0 OR activity_type = ANY($2))
+            "#,
+            subject.filter()
+        ))
+        .bind(subject.value())
+        .bind(types)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Activities after `since_id`, in order
+    pub async fn firehose(
+        pool: &PgPool,
+        since_id: i64,
+        limit: i64,
+    ) -> Result<FirehosePage, AppError> {
+        let mut activities = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT sequence AS id, activity_type, nft_mint, collection_id, from_address,
+                   to_address, price, currency_mint, transaction_signature, occurred_at
+            FROM activities
+            WHERE sequence > $1
+            ORDER BY sequence
+            LIMIT $2
+            "#,
+        )
+        .bind(since_id)
+        .bind(limit + 1)
+        .fetch_all(pool)
+        .await?;
+
+        let has_more = activities.len() as i64 > limit;
+        activities.truncate(limit as usize);
+        let next_since_id = activities.last().map_or(since_id, |activity| activity.id);
+
+        Ok(FirehosePage {
+            activities,
+            next_since_id,
+            has_more,
+        })
+    }
+
+    /// Number the next batch of committed activities in write order; returns
+    /// how many were numbered. Rows still uncommitted aren't visible here and
+    /// get later numbers when they are, so readers never see a number filled
+    /// in behind them.
+    pub async fn assign_sequence(pool: &PgPool) -> Result<u64, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(SEQUENCER_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+        if !locked {
+            return Ok(0);
+        }
+
+        let result = sqlx::query(
+            r#"
+            WITH last AS (
+                SELECT COALESCE(MAX(sequence), 0) AS sequence FROM activities
+            ),
+            batch AS (
+                SELECT id, ROW_NUMBER() OVER (ORDER BY id) AS n
+                FROM activities
+                WHERE sequence IS NULL
+                ORDER BY id
+                LIMIT $1
+            )
+            UPDATE activities a
+            SET sequence = last.sequence + batch.n
+            FROM last, batch
+            WHERE a.id = batch.id
+            "#,
+        )
+        .bind(SEQUENCER_BATCH_SIZE)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use solana_sdk::hash::hashv;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AdminApiKey {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    /// Set when the key was rotated out; it keeps working until then
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+const ADMIN_API_KEY_COLUMNS: &str = "id, name, created_at, expires_at, revoked_at, last_used_at";
+
+impl AdminApiKey {
+    /// Issue a key. The returned secret is not stored and can't be shown again.
+    pub async fn create(pool: &PgPool, name: &str) -> Result<(Self, String), AppError> {
+        let secret = new_secret();
+        let key = sqlx::query_as::<_, Self>(&format!(
+            "INSERT INTO admin_api_keys (name, key_hash) VALUES ($1, $2) RETURNING {}",
+            ADMIN_API_KEY_COLUMNS
+        ))
+        .bind(name)
+        .bind(key_hash(&secret))
+        .fetch_one(pool)
+        .await?;
+
+        Ok((key, secret))
+    }
+
+    pub async fn list(pool: &PgPool) -> Result<Vec<Self>, AppError> {
+        let keys = sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM admin_api_keys ORDER BY created_at DESC",
+            ADMIN_API_KEY_COLUMNS
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        Ok(keys)
+    }
+
+    /// Issue a replacement for key `id` under the same name. The old key
+    /// keeps working for `grace` so callers can switch over.
+    pub async fn rotate(
+        pool: &PgPool,
+        id: Uuid,
+        grace: Duration,
+    ) -> Result<(Self, String), AppError> {
+        let mut tx = pool.begin().await?;
+        let name: Option<String> = sqlx::query_scalar(
+            r#"
+            UPDATE admin_api_keys
+            SET expires_at = LEAST(COALESCE(expires_at, NOW() + $2), NOW() + $2)
+            WHERE id = $1 AND revoked_at IS NULL AND (expires_at IS NULL OR expires_at > NOW())
+            RETURNING name
+            "#,
+        )
+        .bind(id)
+        .bind(grace)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let name = name.ok_or_else(|| crate::error::not_found_error("Active API key"))?;
+
+        let secret = new_secret();
+        let key = sqlx::query_as::<_, Self>(&format!(
+            "INSERT INTO admin_api_keys (name, key_hash) VALUES ($1, $2) RETURNING {}",
+            ADMIN_API_KEY_COLUMNS
+        ))
+        .bind(&name)
+        .bind(key_hash(&secret))
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok((key, secret))
+    }
+
+    /// Stop accepting key `id` immediately
+    pub async fn revoke(pool: &PgPool, id: Uuid) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            "UPDATE admin_api_keys SET revoked_at = NOW() WHERE id = $1 AND revoked_at IS NULL",
+        )
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Whether `secret` is a live key, recording its use if so
+    pub async fn authenticate(pool: &PgPool, secret: &str) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE admin_api_keys SET last_used_at = NOW()
+            WHERE key_hash = $1 AND revoked_at IS NULL AND (expires_at IS NULL OR expires_at > NOW())
+            "#,
+        )
+        .bind(key_hash(secret))
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn new_secret() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+fn key_hash(secret: &str) -> String {
+    hashv(&[secret.as_bytes()]).to_string()
+}
@@ -0,0 +1,407 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::{error::AppError, services::price_oracle::NATIVE_SOL_MINT};
+
+pub use solmint_client::{CreateAlertIntegrationRequest, UpdateAlertIntegrationRequest};
+
+pub const ALERT_KIND_DISCORD: &str = "discord";
+pub const ALERT_KIND_TELEGRAM: &str = "telegram";
+
+pub const ALERT_EVENT_SALE: &str = "sale";
+pub const ALERT_EVENT_LISTING: &str = "listing";
+pub const ALERT_EVENT_FLOOR_MOVE: &str = "floor_move";
+pub const ALERT_EVENTS: &[&str] = &[
+    ALERT_EVENT_SALE,
+    ALERT_EVENT_LISTING,
+    ALERT_EVENT_FLOOR_MOVE,
+];
+
+/// Integrations are disabled after this many failed deliveries in a row
+const MAX_CONSECUTIVE_FAILURES: i32 = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AlertIntegration {
+    pub id: Uuid,
+    pub collection_id: Uuid,
+    pub kind: String,
+    /// Discord webhook URLs are credentials, so the target is never returned
+    #[serde(skip_serializing)]
+    pub target: String,
+    pub events: Vec<String>,
+    pub min_sale_price: i64,
+    pub floor_change_pct: f64,
+    pub last_floor_price: Option<i64>,
+    pub enabled: bool,
+    pub consecutive_failures: i32,
+    pub last_error: Option<String>,
+    pub last_delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A sale or new listing waiting to be posted
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AlertEvent {
+    /// Dedupe key, e.g. `sale:<signature>`
+    pub event_key: String,
+    /// "sale" or "listing"
+    pub event_type: String,
+    pub nft_mint: String,
+    pub nft_name: Option<String>,
+    pub image_url: Option<String>,
+    /// Price in `currency_mint` units
+    pub price: i64,
+    pub currency_mint: String,
+    /// Lamports, for SOL-denominated display and thresholds
+    pub base_price: Option<i64>,
+    pub seller_address: String,
+    pub buyer_address: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+const INTEGRATION_COLUMNS: &str = r#"
+    id, collection_id, kind, target, events, min_sale_price, floor_change_pct,
+    last_floor_price, enabled, consecutive_failures, last_error, last_delivered_at,
+    created_at, updated_at
+"#;
+
+fn validate_events(events: &[String]) -> Result<(), AppError> {
+    if events.is_empty() {
+        return Err(crate::error::bad_request_error("events must not be empty"));
+    }
+    if let Some(unknown) = events
+        .iter()
+        .find(|event| !ALERT_EVENTS.contains(&event.as_str()))
+    {
+        return Err(AppError::BadRequest(format!(
+            "unknown event {}; expected any of {}",
+            unknown,
+            ALERT_EVENTS.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+fn validate_thresholds(
+    min_sale_price: Option<i64>,
+    floor_change_pct: Option<f64>,
+) -> Result<(), AppError> {
+    if min_sale_price.is_some_and(|price| price < 0) {
+        return Err(crate::error::bad_request_error(
+            "min_sale_price must not be negative",
+        ));
+    }
+    if floor_change_pct.is_some_and(|pct| !(pct > 0.0 && pct <= 100.0)) {
+        return Err(crate::error::bad_request_error(
+            "floor_change_pct must be greater than 0 and at most 100",
+        ));
+    }
+    Ok(())
+}
+
+fn validate_target(kind: &str, target: &str) -> Result<(), AppError> {
+    match kind {
+        ALERT_KIND_DISCORD => {
+            if !target.starts_with("https://discord.com/api/webhooks/")
+                && !target.starts_with("https://discordapp.com/api/webhooks/")
+            {
+                return Err(crate::error::bad_request_error(
+                    "target must be a Discord webhook URL",
+                ));
+            }
+        }
+        ALERT_KIND_TELEGRAM => {
+            let is_chat_id = target.parse::<i64>().is_ok();
+            let is_channel = target.len() > 1
+                && target.starts_with('@')
+                && target[1..]
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !is_chat_id && !is_channel {
+                return Err(crate::error::bad_request_error(
+                    "target must be a Telegram chat id or @channel",
+                ));
+            }
+        }
+        _ => {
+            return Err(crate::error::bad_request_error(
+                "kind must be discord or telegram",
+            ))
+        }
+    }
+    Ok(())
+}
+
+impl AlertIntegration {
+    /// Check a request before anything is sent to the target
+    pub fn validate_create(req: &CreateAlertIntegrationRequest) -> Result<(), AppError> {
+        validate_target(&req.kind, req.target.trim())?;
+        validate_events(&req.events)?;
+        validate_thresholds(req.min_sale_price, req.floor_change_pct)
+    }
+
+    pub async fn create(
+        pool: &PgPool,
+        collection_id: Uuid,
+        req: &CreateAlertIntegrationRequest,
+        manage_token_hash: &str,
+    ) -> Result<Self, AppError> {
+        Self::validate_create(req)?;
+
+        let integration = sqlx::query_as::<_, AlertIntegration>(&format!(
+            r#"
+            INSERT INTO alert_integrations (
+                collection_id, kind, target, events, min_sale_price, floor_change_pct,
+                manage_token_hash
+            )
+            VALUES ($1, $2, $3, $4, COALESCE($5, 0), COALESCE($6, 10), $7)
+            ON CONFLICT (collection_id, kind, target) DO NOTHING
+            RETURNING {}
+            "#,
+            INTEGRATION_COLUMNS
+        ))
+        .bind(collection_id)
+        .bind(&req.kind)
+        .bind(req.target.trim())
+        .bind(&req.events)
+        .bind(req.min_sale_price)
+        .bind(req.floor_change_pct)
+        .bind(manage_token_hash)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| {
+            crate::error::bad_request_error("this target is already connected to the collection")
+        })?;
+
+        Ok(integration)
+    }
+
+    /// The integration, if `manage_token_hash` is the one issued for it
+    pub async fn find_managed(
+        pool: &PgPool,
+        id: Uuid,
+        manage_token_hash: &str,
+    ) -> Result<Option<Self>, AppError> {
+        let integration = sqlx::query_as::<_, AlertIntegration>(&format!(
+            "SELECT {} FROM alert_integrations WHERE id = $1 AND manage_token_hash = $2",
+            INTEGRATION_COLUMNS
+        ))
+        .bind(id)
+        .bind(manage_token_hash)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(integration)
+    }
+
+    pub async fn update(
+        pool: &PgPool,
+        id: Uuid,
+        req: &UpdateAlertIntegrationRequest,
+    ) -> Result<Self, AppError> {
+        if let Some(events) = &req.events {
+            validate_events(events)?;
+        }
+        validate_thresholds(req.min_sale_price, req.floor_change_pct)?;
+
+        let integration = sqlx::query_as::<_, AlertIntegration>(&format!(
+            r#"
+            UPDATE alert_integrations SET
+                events = COALESCE($2, events),
+                min_sale_price = COALESCE($3, min_sale_price),
+                floor_change_pct = COALESCE($4, floor_change_pct),
+                enabled = COALESCE($5, enabled),
+                consecutive_failures = CASE WHEN $5 THEN 0 ELSE consecutive_failures END,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            INTEGRATION_COLUMNS
+        ))
+        .bind(id)
+        .bind(&req.events)
+        .bind(req.min_sale_price)
+        .bind(req.floor_change_pct)
+        .bind(req.enabled)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(integration)
+    }
+
+    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM alert_integrations WHERE id = $1")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_enabled(pool: &PgPool) -> Result<Vec<Self>, AppError> {
+        let integrations = sqlx::query_as::<_, AlertIntegration>(&format!(
+            "SELECT {} FROM alert_integrations WHERE enabled ORDER BY created_at",
+            INTEGRATION_COLUMNS
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        Ok(integrations)
+    }
+
+    /// Undelivered sales and new listings from the last hour (and since the
+    /// integration was connected), oldest first
+    pub async fn pending_events(
+        &self,
+        pool: &PgPool,
+        limit: i64,
+    ) -> Result<Vec<AlertEvent>, AppError> {
+        let events = sqlx::query_as::<_, AlertEvent>(
+            r#"
+            SELECT * FROM (
+                SELECT 'sale:' || s.transaction_signature AS event_key, 'sale' AS event_type,
+                       s.nft_mint, n.name AS nft_name, n.image_url, s.price, s.currency_mint,
+                       s.base_price, s.seller_address, s.buyer_address,
+                       s.block_time AS occurred_at
+                FROM sales s
+                JOIN nfts n ON n.mint_address = s.nft_mint
+                WHERE 'sale' = ANY($2)
+                  AND n.collection_id = $1
+                  AND s.created_at >= GREATEST($3, NOW() - INTERVAL '1 hour')
+                  AND s.base_price >= $4
+                UNION ALL
+                SELECT 'listing:' || h.id, 'listing',
+                       h.nft_mint, n.name, n.image_url, h.price, h.currency_mint,
+                       CASE WHEN h.currency_mint = $6 THEN h.price END, h.seller_address, NULL,
+                       h.occurred_at
+                FROM listing_history h
+                JOIN nfts n ON n.mint_address = h.nft_mint
+                WHERE 'listing' = ANY($2)
+                  AND h.collection_id = $1
+                  AND h.event_type = 'listed'
+                  AND h.occurred_at >= GREATEST($3, NOW() - INTERVAL '1 hour')
+            ) events
+            WHERE NOT EXISTS (
+                SELECT 1 FROM alert_deliveries d
+                WHERE d.integration_id = $5 AND d.event_key = events.event_key
+            )
+            ORDER BY occurred_at
+            LIMIT $7
+            "#,
+        )
+        .bind(self.collection_id)
+        .bind(&self.events)
+        .bind(self.created_at)
+        .bind(self.min_sale_price)
+        .bind(self.id)
+        .bind(NATIVE_SOL_MINT)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    /// Lowest active SOL listing in the collection
+    pub async fn current_floor(&self, pool: &PgPool) -> Result<Option<i64>, AppError> {
+        let floor: Option<i64> = sqlx::query_scalar(
+            r#"
+            SELECT MIN(l.price)
+            FROM listings l
+            JOIN nfts n ON n.mint_address = l.nft_mint
+            WHERE n.collection_id = $1 AND l.status = 'active' AND l.deleted_at IS NULL
+              AND l.currency_mint = $2
+            "#,
+        )
+        .bind(self.collection_id)
+        .bind(NATIVE_SOL_MINT)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(floor)
+    }
+
+    /// Floor that later moves are measured against
+    pub async fn set_floor_baseline(&self, pool: &PgPool, floor: i64) -> Result<(), AppError> {
+        sqlx::query("UPDATE alert_integrations SET last_floor_price = $2 WHERE id = $1")
+            .bind(self.id)
+            .bind(floor)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn record_delivery(
+        &self,
+        pool: &PgPool,
+        event_keys: &[String],
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO alert_deliveries (integration_id, event_key)
+            SELECT $1, UNNEST($2::text[])
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(self.id)
+        .bind(event_keys)
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE alert_integrations
+            SET consecutive_failures = 0, last_error = NULL, last_delivered_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(self.id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a failed post. `permanent` failures (deleted webhook, bot removed
+    /// from the chat) disable the integration immediately.
+    pub async fn record_failure(
+        &self,
+        pool: &PgPool,
+        error: &str,
+        permanent: bool,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            UPDATE alert_integrations SET
+                consecutive_failures = consecutive_failures + 1,
+                last_error = $2,
+                enabled = NOT ($3 OR consecutive_failures + 1 >= $4),
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(self.id)
+        .bind(error)
+        .bind(permanent)
+        .bind(MAX_CONSECUTIVE_FAILURES)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Delivery records only need to outlive the event lookback window
+    pub async fn prune_deliveries(pool: &PgPool) -> Result<u64, AppError> {
+        let result = sqlx::query(
+            "DELETE FROM alert_deliveries WHERE delivered_at < NOW() - INTERVAL '1 day'",
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
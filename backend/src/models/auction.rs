@@ -0,0 +1,316 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+pub use solmint_client::AuctionQuery;
+
+/// Taking bids, or ended and waiting to be settled
+pub const AUCTION_STATUS_LIVE: &str = "live";
+pub const AUCTION_STATUS_SETTLED: &str = "settled";
+
+/// One run of an auction account, indexed from the program's events
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Auction {
+    pub id: Uuid,
+    pub auction_address: String,
+    pub nft_mint: String,
+    pub seller_address: String,
+    /// Lamports
+    pub reserve_price: i64,
+    pub ends_at: DateTime<Utc>,
+    pub highest_bid: Option<i64>,
+    pub highest_bidder: Option<String>,
+    pub bid_count: i32,
+    pub status: String,
+    /// `None` when the auction ended without bids
+    pub winner_address: Option<String>,
+    pub transaction_signature: String,
+    pub settle_signature: Option<String>,
+    pub settled_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Bid {
+    pub id: Uuid,
+    pub auction_id: Uuid,
+    pub bidder_address: String,
+    /// Lamports
+    pub amount: i64,
+    pub transaction_signature: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An unsettled auction a wallet has bid on
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ActiveBid {
+    #[sqlx(flatten)]
+    pub auction: Auction,
+    /// The wallet's highest bid
+    pub amount: i64,
+    /// Whether the wallet is the highest bidder
+    pub leading: bool,
+}
+
+/// An auction seen on chain by the indexer
+#[derive(Debug, Clone)]
+pub struct IndexedAuction {
+    pub auction_address: String,
+    pub nft_mint: String,
+    pub seller_address: String,
+    pub reserve_price: i64,
+    pub ends_at: DateTime<Utc>,
+    pub transaction_signature: String,
+}
+
+const AUCTION_COLUMNS: &str = r#"
+    id, auction_address, nft_mint, seller_address, reserve_price, ends_at, highest_bid,
+    highest_bidder, bid_count, status, winner_address, transaction_signature, settle_signature,
+    settled_at, created_at, updated_at
+"#;
+
+const BID_COLUMNS: &str =
+    "id, auction_id, bidder_address, amount, transaction_signature, created_at";
+
+impl Auction {
+    /// Record a new auction; returns `None` when it was already indexed
+    pub async fn record_created(
+        pool: &PgPool,
+        auction: &IndexedAuction,
+    ) -> Result<Option<Self>, AppError> {
+        let auction = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            INSERT INTO auctions (
+                auction_address, nft_mint, seller_address, reserve_price, ends_at,
+                transaction_signature
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT DO NOTHING
+            RETURNING {}
+            "#,
+            AUCTION_COLUMNS
+        ))
+        .bind(&auction.auction_address)
+        .bind(&auction.nft_mint)
+        .bind(&auction.seller_address)
+        .bind(auction.reserve_price)
+        .bind(auction.ends_at)
+        .bind(&auction.transaction_signature)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(auction)
+    }
+
+    /// Record a bid on the unsettled auction at `auction_address`, making it
+    /// the highest. Returns the updated auction, or `None` when the auction
+    /// isn't indexed or the bid already was.
+    pub async fn record_bid(
+        pool: &PgPool,
+        auction_address: &str,
+        bidder_address: &str,
+        amount: i64,
+        transaction_signature: &str,
+    ) -> Result<Option<Self>, AppError> {
+        let auction = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            WITH bid AS (
+                INSERT INTO bids (auction_id, bidder_address, amount, transaction_signature)
+                SELECT id, $2, $3, $4 FROM auctions
+                WHERE auction_address = $1 AND status = '{}'
+                ON CONFLICT (transaction_signature) DO NOTHING
+                RETURNING auction_id, bidder_address, amount
+            )
+            UPDATE auctions SET
+                highest_bid = bid.amount,
+                highest_bidder = bid.bidder_address,
+                bid_count = bid_count + 1,
+                updated_at = NOW()
+            FROM bid
+            WHERE auctions.id = bid.auction_id
+            RETURNING {}
+            "#,
+            AUCTION_STATUS_LIVE, AUCTION_COLUMNS
+        ))
+        .bind(auction_address)
+        .bind(bidder_address)
+        .bind(amount)
+        .bind(transaction_signature)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(auction)
+    }
+
+    /// Settle the unsettled auction at `auction_address`
+    pub async fn mark_settled(
+        pool: &PgPool,
+        auction_address: &str,
+        winner_address: Option<&str>,
+        transaction_signature: &str,
+    ) -> Result<Option<Self>, AppError> {
+        let auction = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            UPDATE auctions SET
+                status = '{}',
+                winner_address = $2,
+                settle_signature = $3,
+                settled_at = NOW(),
+                updated_at = NOW()
+            WHERE auction_address = $1 AND status = '{}'
+            RETURNING {}
+            "#,
+            AUCTION_STATUS_SETTLED, AUCTION_STATUS_LIVE, AUCTION_COLUMNS
+        ))
+        .bind(auction_address)
+        .bind(winner_address)
+        .bind(transaction_signature)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(auction)
+    }
+
+    /// The latest run of the auction at `auction_address`
+    pub async fn find_by_address(
+        pool: &PgPool,
+        auction_address: &str,
+    ) -> Result<Option<Self>, AppError> {
+        let auction = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            SELECT {} FROM auctions
+            WHERE auction_address = $1
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            AUCTION_COLUMNS
+        ))
+        .bind(auction_address)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(auction)
+    }
+
+    /// Auctions by status: `live` (the default) are taking bids, `ended`
+    /// are waiting to be settled. Soonest ending first unless sorted
+    /// otherwise.
+    pub async fn list(
+        pool: &PgPool,
+        query: &AuctionQuery,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, AppError> {
+        let sort_column = match query.sort_by.as_deref() {
+            Some("created_at") => "created_at",
+            Some("highest_bid") => "COALESCE(highest_bid, reserve_price)",
+            _ => "ends_at",
+        };
+        let sort_order = match (query.sort_by.as_deref(), query.sort_order.as_deref()) {
+            (_, Some("asc")) => "ASC",
+            (_, Some("desc")) => "DESC",
+            (None | Some("ends_at"), None) => "ASC",
+            _ => "DESC",
+        };
+
+        let mut query_builder =
+            sqlx::QueryBuilder::new(format!("SELECT {} FROM auctions WHERE ", AUCTION_COLUMNS));
+        match query.status.as_deref() {
+            None | Some("live") => {
+                query_builder.push("status = ");
+                query_builder.push_bind(AUCTION_STATUS_LIVE);
+                query_builder.push(" AND ends_at > NOW()");
+            }
+            Some("ended") => {
+                query_builder.push("status = ");
+                query_builder.push_bind(AUCTION_STATUS_LIVE);
+                query_builder.push(" AND ends_at <= NOW()");
+            }
+            Some(status) => {
+                query_builder.push("status = ");
+                query_builder.push_bind(status.to_string());
+            }
+        }
+
+        if let Some(minutes) = query.ending_within_minutes {
+            query_builder.push(" AND ends_at <= ");
+            query_builder.push_bind(Utc::now() + Duration::minutes(minutes));
+        }
+
+        if let Some(collection_id) = query.collection_id {
+            query_builder
+                .push(" AND nft_mint IN (SELECT mint_address FROM nfts WHERE collection_id = ");
+            query_builder.push_bind(collection_id);
+            query_builder.push(")");
+        }
+
+        if let Some(seller) = &query.seller {
+            query_builder.push(" AND seller_address = ");
+            query_builder.push_bind(seller.clone());
+        }
+
+        query_builder.push(" ORDER BY ");
+        query_builder.push(sort_column);
+        query_builder.push(" ");
+        query_builder.push(sort_order);
+        query_builder.push(", id LIMIT ");
+        query_builder.push_bind(limit);
+        query_builder.push(" OFFSET ");
+        query_builder.push_bind(offset);
+
+        let auctions = query_builder
+            .build_query_as::<Auction>()
+            .fetch_all(pool)
+            .await?;
+
+        Ok(auctions)
+    }
+
+    /// Bids on the auction, highest first
+    pub async fn bids(&self, pool: &PgPool) -> Result<Vec<Bid>, AppError> {
+        let bids = sqlx::query_as::<_, Bid>(&format!(
+            "SELECT {} FROM bids WHERE auction_id = $1 ORDER BY amount DESC, created_at",
+            BID_COLUMNS
+        ))
+        .bind(self.id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(bids)
+    }
+
+    /// Unsettled auctions the wallet has bid on, soonest ending first
+    pub async fn active_bids(
+        pool: &PgPool,
+        bidder_address: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ActiveBid>, AppError> {
+        let bids = sqlx::query_as::<_, ActiveBid>(&format!(
+            r#"
+            SELECT {}, b.amount, highest_bidder = $1 AS leading
+            FROM auctions
+            JOIN (
+                SELECT auction_id, MAX(amount) AS amount FROM bids
+                WHERE bidder_address = $1
+                GROUP BY auction_id
+            ) b ON b.auction_id = auctions.id
+            WHERE status = '{}'
+            ORDER BY ends_at, id
+            LIMIT $2 OFFSET $3
+            "#,
+            AUCTION_COLUMNS, AUCTION_STATUS_LIVE
+        ))
+        .bind(bidder_address)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(bids)
+    }
+}
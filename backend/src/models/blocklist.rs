@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use sqlx::{FromRow, PgPool};
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+pub use solmint_client::{BlocklistQuery, CreateBlocklistEntryRequest};
+
+pub const BLOCK_MINT: &str = "mint";
+pub const BLOCK_COLLECTION: &str = "collection";
+pub const BLOCK_CREATOR: &str = "creator";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BlocklistEntry {
+    pub id: Uuid,
+    pub kind: String,
+    pub value: String,
+    pub reason: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+const BLOCKLIST_COLUMNS: &str = "id, kind, value, reason, created_by, created_at";
+
+impl BlocklistEntry {
+    pub async fn create(pool: &PgPool, req: CreateBlocklistEntryRequest) -> Result<Self, AppError> {
+        let value = req.value.trim();
+        // Stored in the form the blocking functions compare against
+        let value = match req.kind.as_str() {
+            BLOCK_MINT | BLOCK_CREATOR => Pubkey::from_str(value)
+                .map_err(|_| AppError::BadRequest(format!("invalid address: {}", value)))?
+                .to_string(),
+            BLOCK_COLLECTION => Uuid::parse_str(value)
+                .map_err(|_| AppError::BadRequest(format!("invalid collection id: {}", value)))?
+                .to_string(),
+            _ => {
+                return Err(crate::error::bad_request_error(
+                    "kind must be one of mint, collection, creator",
+                ))
+            }
+        };
+
+        let entry = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            INSERT INTO blocklist (kind, value, reason, created_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (kind, value) DO NOTHING
+            RETURNING {}
+            "#,
+            BLOCKLIST_COLUMNS
+        ))
+        .bind(&req.kind)
+        .bind(&value)
+        .bind(&req.reason)
+        .bind(&req.actor)
+        .fetch_optional(pool)
+        .await?;
+
+        entry.ok_or_else(|| crate::error::conflict_error("already blocked"))
+    }
+
+    pub async fn list(
+        pool: &PgPool,
+        kind: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, AppError> {
+        let entries = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            SELECT {} FROM blocklist
+            WHERE $1::text IS NULL OR kind = $1
+            ORDER BY created_at DESC, id
+            LIMIT $2 OFFSET $3
+            "#,
+            BLOCKLIST_COLUMNS
+        ))
+        .bind(kind)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Unblock. Returns false when there was no such entry.
+    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool, AppError> {
+        let result = sqlx::query("DELETE FROM blocklist WHERE id = $1")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,391 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::error::{AppError, ErrorCode};
+
+pub use solmint_client::CreateCheckoutOrderRequest;
+
+pub const ORDER_PENDING_PAYMENT: &str = "pending_payment";
+pub const ORDER_PAID: &str = "paid";
+pub const ORDER_DELIVERING: &str = "delivering";
+pub const ORDER_COMPLETED: &str = "completed";
+pub const ORDER_PAYMENT_FAILED: &str = "payment_failed";
+pub const ORDER_EXPIRED: &str = "expired";
+pub const ORDER_CANCELLED: &str = "cancelled";
+pub const ORDER_DELIVERY_FAILED: &str = "delivery_failed";
+pub const ORDER_REFUNDED: &str = "refunded";
+
+pub const EVENT_SOURCE_API: &str = "api";
+pub const EVENT_SOURCE_WEBHOOK: &str = "webhook";
+pub const EVENT_SOURCE_RECONCILIATION: &str = "reconciliation";
+
+/// How long a buyer has to pay before the listing is released
+const PAYMENT_WINDOW_MINUTES: i64 = 30;
+
+/// Allowed status changes. Payment moves an order from `pending_payment` to
+/// `paid`; the provider then submits the purchase (`delivering`) and the
+/// order completes once the sale is indexed. Paid orders that can't be
+/// delivered end in `delivery_failed` until the provider confirms the refund.
+const TRANSITIONS: &[(&str, &[&str])] = &[
+    (
+        ORDER_PENDING_PAYMENT,
+        &[
+            ORDER_PAID,
+            ORDER_PAYMENT_FAILED,
+            ORDER_EXPIRED,
+            ORDER_CANCELLED,
+        ],
+    ),
+    (
+        ORDER_PAID,
+        &[ORDER_DELIVERING, ORDER_COMPLETED, ORDER_DELIVERY_FAILED],
+    ),
+    (ORDER_DELIVERING, &[ORDER_COMPLETED, ORDER_DELIVERY_FAILED]),
+    (ORDER_DELIVERY_FAILED, &[ORDER_REFUNDED]),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CheckoutOrder {
+    pub id: Uuid,
+    pub listing_id: Uuid,
+    pub nft_mint: String,
+    pub seller_address: String,
+    pub price: i64,
+    pub currency_mint: String,
+    pub buyer_wallet: String,
+    #[serde(skip_serializing)]
+    pub email: Option<String>,
+    pub status: String,
+    pub failure_reason: Option<String>,
+    #[serde(skip_serializing)]
+    pub provider_order_id: Option<String>,
+    pub checkout_url: Option<String>,
+    pub fiat_amount: Option<i64>,
+    pub fiat_currency: Option<String>,
+    pub sale_signature: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct CheckoutOrderEvent {
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub source: String,
+    pub detail: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Changes applied together with a status transition
+#[derive(Debug, Default)]
+pub struct OrderUpdate<'a> {
+    pub failure_reason: Option<&'a str>,
+    pub sale_signature: Option<&'a str>,
+}
+
+const ORDER_COLUMNS: &str = r#"
+    id, listing_id, nft_mint, seller_address, price, currency_mint, buyer_wallet, email, status,
+    failure_reason, provider_order_id, checkout_url, fiat_amount, fiat_currency, sale_signature,
+    expires_at, paid_at, completed_at, created_at, updated_at
+"#;
+
+pub fn can_transition(from: &str, to: &str) -> bool {
+    TRANSITIONS
+        .iter()
+        .any(|(status, next)| *status == from && next.contains(&to))
+}
+
+impl CheckoutOrder {
+    /// Start a checkout for an active listing. Only one checkout may be in
+    /// flight per listing.
+    pub async fn create(
+        pool: &PgPool,
+        req: &CreateCheckoutOrderRequest,
+        buyer_wallet: &str,
+    ) -> Result<Self, AppError> {
+        let order = sqlx::query_as::<_, CheckoutOrder>(&format!(
+            r#"
+            INSERT INTO checkout_orders (
+                listing_id, nft_mint, seller_address, price, currency_mint, buyer_wallet,
+                email, expires_at
+            )
+            SELECT id, nft_mint, seller_address, price, currency_mint, $2, $3, $4
+            FROM listings
+            WHERE id = $1 AND status = 'active' AND deleted_at IS NULL
+            ON CONFLICT (listing_id) WHERE status IN ('pending_payment', 'paid', 'delivering')
+                DO NOTHING
+            RETURNING {}
+            "#,
+            ORDER_COLUMNS
+        ))
+        .bind(req.listing_id)
+        .bind(buyer_wallet)
+        .bind(&req.email)
+        .bind(Utc::now() + Duration::minutes(PAYMENT_WINDOW_MINUTES))
+        .fetch_optional(pool)
+        .await?;
+
+        let Some(order) = order else {
+            return Err(AppError::Rejected(
+                ErrorCode::ListingNotActive,
+                "listing is not active or already has a checkout in progress".to_string(),
+            ));
+        };
+        record_event(
+            pool,
+            order.id,
+            None,
+            ORDER_PENDING_PAYMENT,
+            EVENT_SOURCE_API,
+            None,
+        )
+        .await?;
+
+        Ok(order)
+    }
+
+    /// Attach the provider's order and hosted checkout page
+    pub async fn attach_provider_order(
+        &self,
+        pool: &PgPool,
+        provider_order_id: &str,
+        checkout_url: &str,
+        fiat_amount: i64,
+        fiat_currency: &str,
+    ) -> Result<Self, AppError> {
+        let order = sqlx::query_as::<_, CheckoutOrder>(&format!(
+            r#"
+            UPDATE checkout_orders SET
+                provider_order_id = $2, checkout_url = $3, fiat_amount = $4,
+                fiat_currency = $5, updated_at = NOW()
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            ORDER_COLUMNS
+        ))
+        .bind(self.id)
+        .bind(provider_order_id)
+        .bind(checkout_url)
+        .bind(fiat_amount)
+        .bind(fiat_currency.to_uppercase())
+        .fetch_one(pool)
+        .await?;
+
+        Ok(order)
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, AppError> {
+        let order = sqlx::query_as::<_, CheckoutOrder>(&format!(
+            "SELECT {} FROM checkout_orders WHERE id = $1",
+            ORDER_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(order)
+    }
+
+    pub async fn find_by_provider_order(
+        pool: &PgPool,
+        provider_order_id: &str,
+    ) -> Result<Option<Self>, AppError> {
+        let order = sqlx::query_as::<_, CheckoutOrder>(&format!(
+            "SELECT {} FROM checkout_orders WHERE provider_order_id = $1",
+            ORDER_COLUMNS
+        ))
+        .bind(provider_order_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(order)
+    }
+
+    pub async fn list_for_buyer(
+        pool: &PgPool,
+        buyer_wallet: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, AppError> {
+        let orders = sqlx::query_as::<_, CheckoutOrder>(&format!(
+            r#"
+            SELECT {} FROM checkout_orders
+            WHERE buyer_wallet = $1
+            ORDER BY created_at DESC, id
+            LIMIT $2 OFFSET $3
+            "#,
+            ORDER_COLUMNS
+        ))
+        .bind(buyer_wallet)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(orders)
+    }
+
+    pub async fn events(&self, pool: &PgPool) -> Result<Vec<CheckoutOrderEvent>, AppError> {
+        let events = sqlx::query_as::<_, CheckoutOrderEvent>(
+            r#"
+            SELECT from_status, to_status, source, detail, created_at
+            FROM checkout_order_events
+            WHERE order_id = $1
+            ORDER BY id
+            "#,
+        )
+        .bind(self.id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    /// Move the order to `to` if it is still in the status it was read with.
+    /// Returns `None` when the transition isn't allowed or another update got
+    /// there first, so replayed webhooks are harmless.
+    pub async fn transition(
+        &self,
+        pool: &PgPool,
+        to: &str,
+        source: &str,
+        update: OrderUpdate<'_>,
+    ) -> Result<Option<Self>, AppError> {
+        if !can_transition(&self.status, to) {
+            return Ok(None);
+        }
+
+        let order = sqlx::query_as::<_, CheckoutOrder>(&format!(
+            r#"
+            UPDATE checkout_orders SET
+                status = $3,
+                failure_reason = COALESCE($4, failure_reason),
+                sale_signature = COALESCE($5, sale_signature),
+                paid_at = CASE WHEN $3 = 'paid' THEN NOW() ELSE paid_at END,
+                completed_at = CASE WHEN $3 = 'completed' THEN NOW() ELSE completed_at END,
+                updated_at = NOW()
+            WHERE id = $1 AND status = $2
+            RETURNING {}
+            "#,
+            ORDER_COLUMNS
+        ))
+        .bind(self.id)
+        .bind(&self.status)
+        .bind(to)
+        .bind(update.failure_reason)
+        .bind(update.sale_signature)
+        .fetch_optional(pool)
+        .await?;
+
+        if order.is_some() {
+            let detail = update.failure_reason.or(update.sale_signature);
+            record_event(pool, self.id, Some(&self.status), to, source, detail).await?;
+        }
+
+        Ok(order)
+    }
+
+    /// Unpaid orders past their payment window, or whose listing is no longer
+    /// available
+    pub async fn stale_unpaid(pool: &PgPool) -> Result<Vec<(Self, bool)>, AppError> {
+        let rows = sqlx::query_as::<_, StaleOrder>(&format!(
+            r#"
+            SELECT {}, o.expires_at <= NOW() AS expired
+            FROM checkout_orders o
+            JOIN listings l ON l.id = o.listing_id
+            WHERE o.status = 'pending_payment'
+              AND (o.expires_at <= NOW() OR l.status <> 'active')
+            "#,
+            qualified_columns("o")
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.order, row.expired))
+            .collect())
+    }
+
+    /// Paid orders waiting for their on-chain purchase
+    pub async fn awaiting_delivery(pool: &PgPool) -> Result<Vec<Self>, AppError> {
+        let orders = sqlx::query_as::<_, CheckoutOrder>(&format!(
+            "SELECT {} FROM checkout_orders WHERE status IN ('paid', 'delivering')",
+            ORDER_COLUMNS
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        Ok(orders)
+    }
+
+    /// The indexed sale of this order's NFT since checkout started, if any.
+    /// Returns the signature and buyer.
+    pub async fn matching_sale(&self, pool: &PgPool) -> Result<Option<(String, String)>, AppError> {
+        let sale: Option<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT transaction_signature, buyer_address
+            FROM sales
+            WHERE transaction_signature = $1
+               OR (nft_mint = $2 AND block_time >= $3)
+            ORDER BY (transaction_signature = $1) DESC NULLS LAST,
+                     (buyer_address = $4) DESC,
+                     block_time
+            LIMIT 1
+            "#,
+        )
+        .bind(&self.sale_signature)
+        .bind(&self.nft_mint)
+        .bind(self.created_at)
+        .bind(&self.buyer_wallet)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(sale)
+    }
+}
+
+#[derive(FromRow)]
+struct StaleOrder {
+    #[sqlx(flatten)]
+    order: CheckoutOrder,
+    expired: bool,
+}
+
+fn qualified_columns(alias: &str) -> String {
+    ORDER_COLUMNS
+        .split(',')
+        .map(|column| format!("{}.{}", alias, column.trim()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+async fn record_event(
+    pool: &PgPool,
+    order_id: Uuid,
+    from_status: Option<&str>,
+    to_status: &str,
+    source: &str,
+    detail: Option<&str>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO checkout_order_events (order_id, from_status, to_status, source, detail)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(order_id)
+    .bind(from_status)
+    .bind(to_status)
+    .bind(source)
+    .bind(detail)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,421 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{begin_change, AdminChangeRequest},
+    services::price_oracle::NATIVE_SOL_MINT,
+};
+
+pub use solmint_client::{CollectionListQuery, HolderSnapshotRequest, HoldersHistoryQuery};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Collection {
+    pub id: Uuid,
+    pub name: String,
+    pub symbol: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub banner_url: Option<String>,
+    pub creator_address: String,
+    pub verified: bool,
+    pub rank_score: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Flagged by the spam job or an admin; hidden from lists by default
+    pub is_spam: bool,
+    /// Users who have favorited the collection
+    pub favorite_count: i64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CreateCollectionRequest {
+    pub name: String,
+    pub symbol: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub creator_address: String,
+    pub verified: bool,
+    /// On-chain verified collection address, when there is one
+    pub collection_address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct HolderSnapshot {
+    pub snapshot_date: NaiveDate,
+    pub unique_holders: i64,
+    pub total_supply: i64,
+    pub floor_price: Option<i64>,
+}
+
+/// Running aggregates kept current by triggers on sales and listings
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CollectionStats {
+    pub collection_id: Uuid,
+    /// Cheapest active SOL listing, in lamports
+    pub floor_price: Option<i64>,
+    pub listed_count: i64,
+    /// All-time sales volume in lamports
+    pub total_volume: i64,
+    pub sales_count: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A wallet and the collection's NFTs it holds
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Holder {
+    pub wallet_address: String,
+    pub mints: Vec<String>,
+}
+
+impl Collection {
+    pub async fn create(pool: &PgPool, req: CreateCollectionRequest) -> Result<Self, AppError> {
+        let collection = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO collections (
+                name, symbol, description, image_url, creator_address, verified,
+                collection_address
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, name, symbol, description, image_url, banner_url, creator_address,
+                      verified, rank_score, created_at, updated_at, is_spam,
+                      (SELECT COUNT(*) FROM user_favorite_collections f
+                       WHERE f.collection_id = collections.id) AS favorite_count
+            "#,
+        )
+        .bind(req.name)
+        .bind(req.symbol)
+        .bind(req.description)
+        .bind(req.image_url)
+        .bind(req.creator_address)
+        .bind(req.verified)
+        .bind(req.collection_address)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(collection)
+    }
+
+    pub async fn find_by_collection_address(
+        pool: &PgPool,
+        collection_address: &str,
+    ) -> Result<Option<Self>, AppError> {
+        let collection = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT id, name, symbol, description, image_url, banner_url, creator_address,
+                   verified, rank_score, created_at, updated_at, is_spam,
+                   (SELECT COUNT(*) FROM user_favorite_collections f
+                    WHERE f.collection_id = collections.id) AS favorite_count
+            FROM collections WHERE collection_address = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(collection_address)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(collection)
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, AppError> {
+        let collection = sqlx::query_as!(
+            Collection,
+            r#"
+            SELECT id, name, symbol, description, image_url, banner_url, creator_address,
+                   verified as "verified!", rank_score,
+                   created_at as "created_at!", updated_at as "updated_at!", is_spam,
+                   (SELECT COUNT(*) FROM user_favorite_collections f
+                    WHERE f.collection_id = collections.id) AS "favorite_count!"
+            FROM collections WHERE id = $1 AND deleted_at IS NULL
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(collection)
+    }
+
+    /// The Metaplex collection mint a collection is tied to, if any. `None`
+    /// also when the collection doesn't exist.
+    pub async fn find_collection_address(
+        pool: &PgPool,
+        id: Uuid,
+    ) -> Result<Option<String>, AppError> {
+        let collection_address: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT collection_address FROM collections WHERE id = $1 AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(collection_address.flatten())
+    }
+
+    /// Mark a collection verified or not, recording the change in its history
+    pub async fn set_verified(
+        pool: &PgPool,
+        id: Uuid,
+        verified: bool,
+        change: &AdminChangeRequest,
+    ) -> Result<Self, AppError> {
+        let mut tx = begin_change(pool, change).await?;
+        let collection = sqlx::query_as::<_, Self>(
+            r#"
+            UPDATE collections SET verified = $2, updated_at = NOW()
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING id, name, symbol, description, image_url, banner_url, creator_address,
+                      verified, rank_score, created_at, updated_at, is_spam,
+                      (SELECT COUNT(*) FROM user_favorite_collections f
+                       WHERE f.collection_id = collections.id) AS favorite_count
+            "#,
+        )
+        .bind(id)
+        .bind(verified)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Collection"))?;
+        tx.commit().await?;
+
+        Ok(collection)
+    }
+
+    pub async fn list(
+        pool: &PgPool,
+        query: CollectionListQuery,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, AppError> {
+        let sort_column = match query.sort_by.as_deref() {
+            Some("name") => "name",
+            Some("rank") => "rank_score",
+            _ => "created_at",
+        };
+
+        let sort_order = match query.sort_order.as_deref() {
+            Some("asc") => "ASC",
+            _ => "DESC",
+        };
+
+        let mut query_builder = sqlx::QueryBuilder::new(
+            r#"
+            SELECT id, name, symbol, description, image_url, banner_url, creator_address,
+                   verified, rank_score, created_at, updated_at, is_spam,
+                   (SELECT COUNT(*) FROM user_favorite_collections f
+                    WHERE f.collection_id = collections.id) AS favorite_count
+            FROM collections
+            WHERE deleted_at IS NULL
+            "#,
+        );
+
+        if !query.include_spam.unwrap_or(false) {
+            query_builder.push(" AND is_spam = FALSE");
+        }
+
+        if !query.include_blocked.unwrap_or(false) {
+            query_builder.push(" AND NOT collection_is_blocked(id, creator_address)");
+        }
+
+        if let Some(creator) = query.creator {
+            query_builder.push(" AND creator_address = ");
+            query_builder.push_bind(creator);
+        }
+
+        if let Some(verified) = query.verified {
+            query_builder.push(" AND verified = ");
+            query_builder.push_bind(verified);
+        }
+
+        query_builder.push(" ORDER BY ");
+        query_builder.push(sort_column);
+        query_builder.push(" ");
+        query_builder.push(sort_order);
+        query_builder.push(" NULLS LAST, id LIMIT ");
+        query_builder.push_bind(limit);
+        query_builder.push(" OFFSET ");
+        query_builder.push_bind(offset);
+
+        let collections = query_builder
+            .build_query_as::<Collection>()
+            .fetch_all(pool)
+            .await?;
+
+        Ok(collections)
+    }
+
+    /// Recompute `rank_score` for every collection.
+    ///
+    /// The score is a weighted blend of log-scaled 24h and 7d volume (in SOL),
+    /// the floor change since yesterday's snapshot, 24h sales velocity relative
+    /// to the 7d daily average, and 7d holder growth. The change terms are
+    /// clamped to [-1, 1] so a single thin trade can't dominate the ranking.
+    pub async fn refresh_rank_scores(pool: &PgPool) -> Result<u64, AppError> {
+        let result = sqlx::query(
+            r#"
+            WITH volume AS (
+                SELECT n.collection_id,
+                       COALESCE(SUM(s.base_price) FILTER (
+                           WHERE s.block_time >= NOW() - INTERVAL '1 day'), 0) AS volume_24h,
+                       SUM(s.base_price) AS volume_7d,
+                       COUNT(*) FILTER (WHERE s.block_time >= NOW() - INTERVAL '1 day') AS sales_24h,
+                       COUNT(*) AS sales_7d
+                FROM sales s
+                JOIN nfts n ON n.mint_address = s.nft_mint
+                WHERE s.block_time >= NOW() - INTERVAL '7 days' AND n.collection_id IS NOT NULL
+                GROUP BY n.collection_id
+            ),
+            history AS (
+                SELECT collection_id,
+                       (array_agg(unique_holders ORDER BY snapshot_date DESC))[1] AS holders_now,
+                       (array_agg(unique_holders ORDER BY snapshot_date))[1] AS holders_week_ago,
+                       (array_agg(floor_price ORDER BY snapshot_date DESC)
+                           FILTER (WHERE snapshot_date < CURRENT_DATE))[1] AS floor_yesterday
+                FROM collection_holder_history
+                WHERE snapshot_date >= CURRENT_DATE - 7
+                GROUP BY collection_id
+            ),
+            scores AS (
+                SELECT c.id,
+                       0.35 * LN(1 + COALESCE(v.volume_24h, 0)::float8 / 1e9)
+                     + 0.20 * LN(1 + COALESCE(v.volume_7d, 0)::float8 / 1e9)
+                     + 0.15 * CASE WHEN h.floor_yesterday > 0 AND f.floor_price IS NOT NULL
+                           THEN GREATEST(-1, LEAST(1,
+                               (f.floor_price - h.floor_yesterday)::float8 / h.floor_yesterday))
+                           ELSE 0 END
+                     + 0.15 * CASE WHEN v.sales_7d > 0
+                           THEN GREATEST(-1, LEAST(1,
+                               v.sales_24h::float8 / (v.sales_7d / 7.0) - 1))
+                           ELSE 0 END
+                     + 0.15 * CASE WHEN h.holders_week_ago > 0
+                           THEN GREATEST(-1, LEAST(1,
+                               (h.holders_now - h.holders_week_ago)::float8
+                                   / h.holders_week_ago * 10))
+                           ELSE 0 END AS rank_score
+                FROM collections c
+                LEFT JOIN volume v ON v.collection_id = c.id
+                LEFT JOIN collection_stats f ON f.collection_id = c.id
+                LEFT JOIN history h ON h.collection_id = c.id
+            )
+            UPDATE collections c
+            SET rank_score = scores.rank_score, rank_updated_at = NOW()
+            FROM scores
+            WHERE c.id = scores.id
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Have the rarity job recompute the collection, e.g. after traits changed
+    pub async fn mark_rarity_stale(pool: &PgPool, id: Uuid) -> Result<(), AppError> {
+        sqlx::query("UPDATE collections SET rarity_stale = TRUE WHERE id = $1")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn stats(
+        pool: &PgPool,
+        collection_id: Uuid,
+    ) -> Result<Option<CollectionStats>, AppError> {
+        let stats = sqlx::query_as::<_, CollectionStats>(
+            r#"
+            SELECT collection_id, floor_price, listed_count, total_volume, sales_count, updated_at
+            FROM collection_stats
+            WHERE collection_id = $1
+            "#,
+        )
+        .bind(collection_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(stats)
+    }
+
+    /// Rebuild `collection_stats` from sales and listings, correcting drift the
+    /// triggers can't see (e.g. an NFT moved between collections). Returns the
+    /// number of rows that were missing or wrong.
+    pub async fn reconcile_stats(pool: &PgPool) -> Result<i64, AppError> {
+        let corrected = sqlx::query_scalar("SELECT reconcile_collection_stats()")
+            .fetch_one(pool)
+            .await?;
+
+        Ok(corrected)
+    }
+
+    /// Record distinct holders for every collection as of `date`.
+    /// Existing snapshots for that date are left untouched.
+    pub async fn snapshot_holders(pool: &PgPool, date: NaiveDate) -> Result<u64, AppError> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO collection_holder_history (
+                collection_id, snapshot_date, unique_holders, total_supply, floor_price
+            )
+            SELECT n.collection_id, $1, COUNT(DISTINCT n.current_owner), COUNT(*),
+                   MIN(l.price)
+            FROM nfts n
+            LEFT JOIN listings l
+                ON l.nft_mint = n.mint_address AND l.status = 'active' AND l.deleted_at IS NULL
+                  AND l.currency_mint = $2
+            WHERE n.collection_id IS NOT NULL
+            GROUP BY n.collection_id
+            ON CONFLICT (collection_id, snapshot_date) DO NOTHING
+            "#,
+            date,
+            NATIVE_SOL_MINT
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Current holders, largest first
+    pub async fn holders(pool: &PgPool, collection_id: Uuid) -> Result<Vec<Holder>, AppError> {
+        let holders = sqlx::query_as!(
+            Holder,
+            r#"
+            SELECT current_owner AS wallet_address,
+                   array_agg(mint_address ORDER BY mint_address) AS "mints!"
+            FROM nfts
+            WHERE collection_id = $1 AND deleted_at IS NULL
+            GROUP BY current_owner
+            ORDER BY COUNT(*) DESC, current_owner
+            "#,
+            collection_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(holders)
+    }
+
+    pub async fn holders_history(
+        pool: &PgPool,
+        collection_id: Uuid,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<HolderSnapshot>, AppError> {
+        let history = sqlx::query_as!(
+            HolderSnapshot,
+            r#"
+            SELECT snapshot_date, unique_holders, total_supply, floor_price
+            FROM collection_holder_history
+            WHERE collection_id = $1 AND snapshot_date BETWEEN $2 AND $3
+            ORDER BY snapshot_date
+            "#,
+            collection_id,
+            from,
+            to
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(history)
+    }
+}
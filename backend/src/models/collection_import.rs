@@ -0,0 +1,322 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+pub use solmint_client::ImportCollectionRequest;
+
+pub const IMPORT_QUEUED: &str = "queued";
+pub const IMPORT_RUNNING: &str = "running";
+pub const IMPORT_COMPLETED: &str = "completed";
+pub const IMPORT_FAILED: &str = "failed";
+
+pub const IMPORT_SOURCE_HASHLIST: &str = "hashlist";
+pub const IMPORT_SOURCE_COLLECTION: &str = "collection";
+
+/// Give up on a mint after this many failed metadata fetches
+const MAX_ITEM_ATTEMPTS: i32 = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CollectionImport {
+    pub id: Uuid,
+    pub collection_id: Uuid,
+    pub source: String,
+    pub collection_address: Option<String>,
+    pub status: String,
+    #[serde(skip)]
+    pub next_page: Option<i32>,
+    pub total_items: i32,
+    pub imported_items: i32,
+    pub failed_items: i32,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FailedImportItem {
+    pub mint_address: String,
+    pub attempts: i32,
+    pub error: Option<String>,
+}
+
+const COLLECTION_IMPORT_COLUMNS: &str = r#"
+    id, collection_id, source, collection_address, status, next_page, total_items,
+    imported_items, failed_items, error, created_at, updated_at, completed_at
+"#;
+
+impl CollectionImport {
+    /// Queue an import. Hashlist mints are enqueued straight away; collection
+    /// imports enqueue theirs page by page as the job lists them.
+    pub async fn create(
+        pool: &PgPool,
+        collection_id: Uuid,
+        mints: &[String],
+        collection_address: Option<&str>,
+    ) -> Result<Self, AppError> {
+        let source = if collection_address.is_some() {
+            IMPORT_SOURCE_COLLECTION
+        } else {
+            IMPORT_SOURCE_HASHLIST
+        };
+
+        let import = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            INSERT INTO collection_imports (collection_id, source, collection_address, next_page)
+            VALUES ($1, $2, $3, $4)
+            RETURNING {}
+            "#,
+            COLLECTION_IMPORT_COLUMNS
+        ))
+        .bind(collection_id)
+        .bind(source)
+        .bind(collection_address)
+        .bind(collection_address.map(|_| 1))
+        .fetch_one(pool)
+        .await?;
+
+        if mints.is_empty() {
+            return Ok(import);
+        }
+        import.add_items(pool, mints, None).await?;
+        Self::find_by_id(pool, import.id)
+            .await?
+            .ok_or_else(|| crate::error::not_found_error("Collection import"))
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, AppError> {
+        let import = sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM collection_imports WHERE id = $1",
+            COLLECTION_IMPORT_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(import)
+    }
+
+    pub async fn list_recent(
+        pool: &PgPool,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, AppError> {
+        let imports = sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM collection_imports ORDER BY created_at DESC, id LIMIT $1 OFFSET $2",
+            COLLECTION_IMPORT_COLUMNS
+        ))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(imports)
+    }
+
+    /// Take the lease on the oldest open import. An import whose worker died
+    /// becomes claimable again once its lease runs out, and resumes from its
+    /// remaining pending mints.
+    pub async fn claim_next(pool: &PgPool, lease: Duration) -> Result<Option<Self>, AppError> {
+        let import = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            UPDATE collection_imports
+            SET status = '{}', locked_until = $1, updated_at = NOW()
+            WHERE id = (
+                SELECT id FROM collection_imports
+                WHERE status IN ('{}', '{}')
+                  AND (locked_until IS NULL OR locked_until < NOW())
+                ORDER BY created_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {}
+            "#,
+            IMPORT_RUNNING, IMPORT_QUEUED, IMPORT_RUNNING, COLLECTION_IMPORT_COLUMNS
+        ))
+        .bind(Utc::now() + lease)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(import)
+    }
+
+    /// Enqueue mints, skipping ones already in the import, and move the page
+    /// cursor for collection imports. Returns how many were new.
+    pub async fn add_items(
+        &self,
+        pool: &PgPool,
+        mints: &[String],
+        next_page: Option<i32>,
+    ) -> Result<u64, AppError> {
+        let mut tx = pool.begin().await?;
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO collection_import_items (import_id, mint_address)
+            SELECT $1, mint FROM UNNEST($2::text[]) AS mint
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(self.id)
+        .bind(mints)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        sqlx::query(
+            r#"
+            UPDATE collection_imports
+            SET total_items = total_items + $2, next_page = $3, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(self.id)
+        .bind(inserted as i32)
+        .bind(next_page)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(inserted)
+    }
+
+    pub async fn pending_items(&self, pool: &PgPool, limit: i64) -> Result<Vec<String>, AppError> {
+        let mints = sqlx::query_scalar(
+            r#"
+            SELECT mint_address FROM collection_import_items
+            WHERE import_id = $1 AND status = 'pending'
+            ORDER BY attempts, mint_address
+            LIMIT $2
+            "#,
+        )
+        .bind(self.id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(mints)
+    }
+
+    pub async fn failed_items(&self, pool: &PgPool) -> Result<Vec<FailedImportItem>, AppError> {
+        let items = sqlx::query_as::<_, FailedImportItem>(
+            r#"
+            SELECT mint_address, attempts, error FROM collection_import_items
+            WHERE import_id = $1 AND status = 'failed'
+            ORDER BY mint_address
+            "#,
+        )
+        .bind(self.id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(items)
+    }
+
+    pub async fn item_imported(&self, pool: &PgPool, mint: &str) -> Result<(), AppError> {
+        self.finish_item(pool, mint, None).await
+    }
+
+    /// Record a failed attempt; the mint stays pending until it runs out of attempts
+    pub async fn item_failed(
+        &self,
+        pool: &PgPool,
+        mint: &str,
+        error: &str,
+    ) -> Result<(), AppError> {
+        self.finish_item(pool, mint, Some(error)).await
+    }
+
+    async fn finish_item(
+        &self,
+        pool: &PgPool,
+        mint: &str,
+        error: Option<&str>,
+    ) -> Result<(), AppError> {
+        let mut tx = pool.begin().await?;
+        let status: String = sqlx::query_scalar(
+            r#"
+            UPDATE collection_import_items
+            SET attempts = attempts + 1,
+                error = $3,
+                status = CASE
+                    WHEN $3 IS NULL THEN 'imported'
+                    WHEN attempts + 1 >= $4 THEN 'failed'
+                    ELSE 'pending'
+                END,
+                updated_at = NOW()
+            WHERE import_id = $1 AND mint_address = $2
+            RETURNING status
+            "#,
+        )
+        .bind(self.id)
+        .bind(mint)
+        .bind(error)
+        .bind(MAX_ITEM_ATTEMPTS)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE collection_imports
+            SET imported_items = imported_items + ($2 = 'imported')::int,
+                failed_items = failed_items + ($2 = 'failed')::int,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(self.id)
+        .bind(&status)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Note a problem that doesn't stop the import, e.g. a page fetch that will be retried
+    pub async fn record_error(&self, pool: &PgPool, error: &str) -> Result<(), AppError> {
+        sqlx::query("UPDATE collection_imports SET error = $2, updated_at = NOW() WHERE id = $1")
+            .bind(self.id)
+            .bind(error)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Close the import as completed or failed and drop its lease
+    pub async fn finish(
+        &self,
+        pool: &PgPool,
+        status: &str,
+        error: Option<&str>,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            UPDATE collection_imports
+            SET status = $2, error = COALESCE($3, error), locked_until = NULL,
+                completed_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(self.id)
+        .bind(status)
+        .bind(error)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Let the next run pick the import up again
+    pub async fn release(&self, pool: &PgPool) -> Result<(), AppError> {
+        sqlx::query("UPDATE collection_imports SET locked_until = NULL WHERE id = $1")
+            .bind(self.id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::{error::AppError, services::price_oracle::NATIVE_SOL_MINT};
+
+/// How common a trait value is in its collection, and its cheapest listing.
+/// Counts come from the rarity job, floors from the trait floor job.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CollectionTrait {
+    pub trait_type: String,
+    pub value: String,
+    /// NFTs with the value
+    pub count: i64,
+    /// Share of the collection with the value, 0 to 1
+    pub frequency: f64,
+    /// Lamports; `None` when none are listed in SOL
+    pub floor_price: Option<i64>,
+    pub listed_count: i64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl CollectionTrait {
+    /// The collection's traits by type, most common value first
+    pub async fn list(pool: &PgPool, collection_id: Uuid) -> Result<Vec<Self>, AppError> {
+        let traits = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT trait_type, value, count, frequency, floor_price, listed_count, updated_at
+            FROM collection_traits
+            WHERE collection_id = $1
+            ORDER BY trait_type, count DESC, value
+            "#,
+        )
+        .bind(collection_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(traits)
+    }
+
+    /// Recompute floors and listed counts from active SOL listings, for one
+    /// collection or all of them. Values are matched the way the rarity job
+    /// reads them: strings as-is, other JSON values as JSON text. Returns the
+    /// number of traits that changed.
+    pub async fn refresh_floors(
+        pool: &PgPool,
+        collection_id: Option<Uuid>,
+    ) -> Result<u64, AppError> {
+        let result = sqlx::query(
+            r#"
+            WITH listed AS (
+                SELECT n.collection_id, l.id AS listing_id, l.price,
+                       attr->>'trait_type' AS trait_type,
+                       CASE jsonb_typeof(attr->'value')
+                           WHEN 'string' THEN attr->>'value'
+                           ELSE (attr->'value')::text
+                       END AS value
+                FROM listings l
+                JOIN nfts n ON n.mint_address = l.nft_mint
+                CROSS JOIN LATERAL jsonb_array_elements(
+                    CASE jsonb_typeof(n.attributes) WHEN 'array' THEN n.attributes ELSE '[]' END
+                ) attr
+                WHERE l.status = 'active'
+                  AND l.deleted_at IS NULL
+                  AND l.currency_mint = $1
+                  AND n.collection_id IS NOT NULL
+                  AND ($2::uuid IS NULL OR n.collection_id = $2)
+                  AND jsonb_typeof(attr->'trait_type') = 'string'
+                  AND jsonb_typeof(attr->'value') <> 'null'
+            ),
+            floors AS (
+                SELECT collection_id, trait_type, value, MIN(price) AS floor_price,
+                       COUNT(DISTINCT listing_id) AS listed_count
+                FROM listed
+                GROUP BY collection_id, trait_type, value
+            )
+            UPDATE collection_traits ct SET
+                floor_price = f.floor_price,
+                listed_count = COALESCE(f.listed_count, 0)
+            FROM collection_traits t
+            LEFT JOIN floors f
+                ON f.collection_id = t.collection_id
+               AND f.trait_type = t.trait_type
+               AND f.value = t.value
+            WHERE ct.collection_id = t.collection_id
+              AND ct.trait_type = t.trait_type
+              AND ct.value = t.value
+              AND ($2::uuid IS NULL OR t.collection_id = $2)
+              AND (ct.floor_price IS DISTINCT FROM f.floor_price
+                   OR ct.listed_count <> COALESCE(f.listed_count, 0))
+            "#,
+        )
+        .bind(NATIVE_SOL_MINT)
+        .bind(collection_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
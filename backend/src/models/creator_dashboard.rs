@@ -0,0 +1,310 @@
+use chrono::{DateTime, Duration, Utc};
+use redis::aio::MultiplexedConnection;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    cache,
+    error::AppError,
+    models::{Launch, LAUNCH_KIND_CANDY_MACHINE},
+    services::{launchpad::Launchpad, price_oracle::NATIVE_SOL_MINT},
+};
+
+/// Short enough that mint progress still looks live during a drop
+const CACHE_TTL_SECONDS: u64 = 60;
+const TOP_HOLDERS_LIMIT: i64 = 10;
+const RECENT_ACTIVITY_LIMIT: i64 = 20;
+const LAUNCHES_LIMIT: i64 = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CreatorCollection {
+    pub id: Uuid,
+    pub name: String,
+    pub image_url: Option<String>,
+    pub verified: Option<bool>,
+    pub items: i64,
+    pub holders: i64,
+    /// Lowest active SOL listing, in lamports
+    pub floor_price: Option<i64>,
+    pub volume_total: i64,
+    pub volume_7d: i64,
+    pub sales_total: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintProgress {
+    pub launch_id: Uuid,
+    pub name: String,
+    pub kind: String,
+    pub collection_id: Option<Uuid>,
+    pub items_available: Option<i64>,
+    /// `None` when the candy machine couldn't be read
+    pub items_redeemed: Option<i64>,
+    pub first_phase_starts_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CreatorSalesSummary {
+    pub volume_total: i64,
+    pub volume_24h: i64,
+    pub volume_7d: i64,
+    pub volume_30d: i64,
+    pub sales_total: i64,
+    pub sales_24h: i64,
+    pub unique_buyers_30d: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CreatorRoyalties {
+    pub total: i64,
+    pub last_30d: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TopHolder {
+    pub wallet_address: String,
+    pub items: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CreatorActivity {
+    /// sale, listed, price_change or delisted
+    pub event_type: String,
+    pub nft_mint: String,
+    pub nft_name: Option<String>,
+    pub collection_id: Option<Uuid>,
+    pub price: i64,
+    pub currency_mint: String,
+    pub seller_address: String,
+    pub buyer_address: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// Everything creator tooling shows for one wallet, built in one go so it
+/// can be cached. Covers collections whose `creator_address` is the wallet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatorDashboard {
+    pub creator: String,
+    pub collections: Vec<CreatorCollection>,
+    pub mint_progress: Vec<MintProgress>,
+    pub sales: CreatorSalesSummary,
+    pub royalties: CreatorRoyalties,
+    pub top_holders: Vec<TopHolder>,
+    pub recent_activity: Vec<CreatorActivity>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl CreatorDashboard {
+    pub fn cache_key(creator: &str) -> String {
+        format!("creators:{}:dashboard", creator)
+    }
+
+    /// Serve the cached dashboard, rebuilding it when missing or expired
+    pub async fn cached(
+        pool: &PgPool,
+        redis: &MultiplexedConnection,
+        launchpad: &Launchpad,
+        creator: &str,
+    ) -> Result<Self, AppError> {
+        let key = Self::cache_key(creator);
+        if let Some(dashboard) = cache::get_json::<Self>(redis, &key).await? {
+            return Ok(dashboard);
+        }
+
+        let dashboard = Self::build(pool, launchpad, creator).await?;
+        cache::set_json(redis, &key, &dashboard, CACHE_TTL_SECONDS).await?;
+        Ok(dashboard)
+    }
+
+    pub async fn build(
+        pool: &PgPool,
+        launchpad: &Launchpad,
+        creator: &str,
+    ) -> Result<Self, AppError> {
+        let now = Utc::now();
+
+        let collections = sqlx::query_as::<_, CreatorCollection>(
+            r#"
+            SELECT c.id, c.name, c.image_url, c.verified,
+                   (SELECT COUNT(*) FROM nfts n WHERE n.collection_id = c.id) AS items,
+                   (SELECT COUNT(DISTINCT n.current_owner) FROM nfts n
+                    WHERE n.collection_id = c.id) AS holders,
+                   (SELECT MIN(l.price) FROM listings l
+                    JOIN nfts n ON n.mint_address = l.nft_mint
+                    WHERE n.collection_id = c.id AND l.status = 'active' AND l.deleted_at IS NULL
+                      AND l.currency_mint = $2) AS floor_price,
+                   COALESCE(s.volume_total, 0)::BIGINT AS volume_total,
+                   COALESCE(s.volume_7d, 0)::BIGINT AS volume_7d,
+                   COALESCE(s.sales_total, 0) AS sales_total
+            FROM collections c
+            LEFT JOIN LATERAL (
+                SELECT SUM(s.base_price) AS volume_total,
+                       SUM(s.base_price) FILTER (WHERE s.block_time >= $3) AS volume_7d,
+                       COUNT(*) AS sales_total
+                FROM sales s
+                JOIN nfts n ON n.mint_address = s.nft_mint
+                WHERE n.collection_id = c.id
+            ) s ON TRUE
+            WHERE c.creator_address = $1
+            ORDER BY volume_total DESC, c.created_at DESC
+            "#,
+        )
+        .bind(creator)
+        .bind(NATIVE_SOL_MINT)
+        .bind(now - Duration::days(7))
+        .fetch_all(pool)
+        .await?;
+
+        let sales = sqlx::query_as::<_, CreatorSalesSummary>(
+            r#"
+            SELECT COALESCE(SUM(s.base_price), 0)::BIGINT AS volume_total,
+                   COALESCE(SUM(s.base_price) FILTER (WHERE s.block_time >= $2), 0)::BIGINT
+                       AS volume_24h,
+                   COALESCE(SUM(s.base_price) FILTER (WHERE s.block_time >= $3), 0)::BIGINT
+                       AS volume_7d,
+                   COALESCE(SUM(s.base_price) FILTER (WHERE s.block_time >= $4), 0)::BIGINT
+                       AS volume_30d,
+                   COUNT(*) AS sales_total,
+                   COUNT(*) FILTER (WHERE s.block_time >= $2) AS sales_24h,
+                   COUNT(DISTINCT s.buyer_address) FILTER (WHERE s.block_time >= $4)
+                       AS unique_buyers_30d
+            FROM sales s
+            JOIN nfts n ON n.mint_address = s.nft_mint
+            JOIN collections c ON c.id = n.collection_id
+            WHERE c.creator_address = $1
+            "#,
+        )
+        .bind(creator)
+        .bind(now - Duration::days(1))
+        .bind(now - Duration::days(7))
+        .bind(now - Duration::days(30))
+        .fetch_one(pool)
+        .await?;
+
+        let royalties = sqlx::query_as::<_, CreatorRoyalties>(
+            r#"
+            SELECT COALESCE(SUM(r.amount), 0)::BIGINT AS total,
+                   COALESCE(SUM(r.amount) FILTER (WHERE s.block_time >= $2), 0)::BIGINT
+                       AS last_30d
+            FROM sale_royalties r
+            JOIN sales s ON s.id = r.sale_id
+            WHERE r.creator_address = $1
+            "#,
+        )
+        .bind(creator)
+        .bind(now - Duration::days(30))
+        .fetch_one(pool)
+        .await?;
+
+        let top_holders = sqlx::query_as::<_, TopHolder>(
+            r#"
+            SELECT n.current_owner AS wallet_address, COUNT(*) AS items
+            FROM nfts n
+            JOIN collections c ON c.id = n.collection_id
+            WHERE c.creator_address = $1
+            GROUP BY n.current_owner
+            ORDER BY items DESC, n.current_owner
+            LIMIT $2
+            "#,
+        )
+        .bind(creator)
+        .bind(TOP_HOLDERS_LIMIT)
+        .fetch_all(pool)
+        .await?;
+
+        let recent_activity = sqlx::query_as::<_, CreatorActivity>(
+            r#"
+            SELECT * FROM (
+                SELECT 'sale' AS event_type, s.nft_mint, n.name AS nft_name, n.collection_id,
+                       s.price, s.currency_mint, s.seller_address,
+                       s.buyer_address AS buyer_address, s.block_time AS occurred_at
+                FROM sales s
+                JOIN nfts n ON n.mint_address = s.nft_mint
+                JOIN collections c ON c.id = n.collection_id
+                WHERE c.creator_address = $1
+                UNION ALL
+                SELECT h.event_type, h.nft_mint, n.name, h.collection_id,
+                       h.price, h.currency_mint, h.seller_address,
+                       NULL, h.occurred_at
+                FROM listing_history h
+                JOIN collections c ON c.id = h.collection_id
+                LEFT JOIN nfts n ON n.mint_address = h.nft_mint
+                WHERE c.creator_address = $1 AND h.event_type <> 'sold'
+            ) activity
+            ORDER BY occurred_at DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(creator)
+        .bind(RECENT_ACTIVITY_LIMIT)
+        .fetch_all(pool)
+        .await?;
+
+        let mint_progress = mint_progress(pool, launchpad, creator).await?;
+
+        Ok(Self {
+            creator: creator.to_string(),
+            collections,
+            mint_progress,
+            sales,
+            royalties,
+            top_holders,
+            recent_activity,
+            generated_at: now,
+        })
+    }
+}
+
+/// The creator's latest launches, with supply read from the candy machine
+/// for candy machine launches
+async fn mint_progress(
+    pool: &PgPool,
+    launchpad: &Launchpad,
+    creator: &str,
+) -> Result<Vec<MintProgress>, AppError> {
+    let launches = Launch::list_for_creator(pool, creator, LAUNCHES_LIMIT).await?;
+
+    let mut progress = Vec::with_capacity(launches.len());
+    for launch in launches {
+        // Phases are ordered by start
+        let first_phase_starts_at = launch
+            .phases(pool)
+            .await?
+            .first()
+            .map(|phase| phase.starts_at);
+
+        let (items_available, items_redeemed) = if launch.kind == LAUNCH_KIND_CANDY_MACHINE {
+            match launchpad.candy_machine(&launch.config_address).await {
+                Ok(candy_machine) => (
+                    Some(candy_machine.items_available as i64),
+                    Some(candy_machine.items_redeemed as i64),
+                ),
+                Err(e) => {
+                    println!(
+                        "Could not read candy machine {}: {}",
+                        launch.config_address, e
+                    );
+                    (launch.items_available, None)
+                }
+            }
+        } else {
+            (
+                launch.items_available,
+                Some(launch.minted_count(pool).await?),
+            )
+        };
+
+        progress.push(MintProgress {
+            launch_id: launch.id,
+            name: launch.name,
+            kind: launch.kind,
+            collection_id: launch.collection_id,
+            items_available,
+            items_redeemed,
+            first_phase_starts_at,
+        });
+    }
+
+    Ok(progress)
+}
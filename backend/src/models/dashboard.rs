@@ -0,0 +1,241 @@
+use chrono::{DateTime, Duration, Utc};
+use redis::aio::MultiplexedConnection;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::{cache, error::AppError, services::price_oracle::NATIVE_SOL_MINT};
+
+const TRENDING_LIMIT: i64 = 10;
+/// Outlives several scheduler refreshes so a missed run doesn't empty the cache
+const CACHE_TTL_SECONDS: u64 = 300;
+const LARGE_SALES_LIMIT: i64 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GlobalStats {
+    pub total_volume: i64,
+    pub total_sales: i64,
+    pub total_collections: i64,
+    pub total_nfts: i64,
+    pub active_listings: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WindowStats {
+    pub volume: i64,
+    pub sales: i64,
+    pub unique_buyers: i64,
+    pub unique_sellers: i64,
+    pub new_listings: i64,
+}
+
+/// A KPI for the last 24h against the 24h before that
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KpiDelta {
+    pub current: i64,
+    pub previous: i64,
+    /// `None` when there is nothing to compare against
+    pub change_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardDeltas {
+    pub volume: KpiDelta,
+    pub sales: KpiDelta,
+    pub unique_buyers: KpiDelta,
+    pub unique_sellers: KpiDelta,
+    pub new_listings: KpiDelta,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TrendingCollection {
+    pub id: Uuid,
+    pub name: String,
+    pub image_url: Option<String>,
+    pub rank_score: Option<f64>,
+    pub volume_24h: i64,
+    pub sales_24h: i64,
+    pub floor_price: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LargeSale {
+    pub nft_mint: String,
+    pub nft_name: Option<String>,
+    pub collection_id: Option<Uuid>,
+    pub buyer_address: String,
+    pub seller_address: String,
+    pub price: i64,
+    pub currency_mint: String,
+    pub base_price: i64,
+    pub transaction_signature: String,
+    pub block_time: DateTime<Utc>,
+}
+
+/// Everything the ops dashboard shows, built in one go so it can be cached
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dashboard {
+    pub global: GlobalStats,
+    pub last_24h: DashboardDeltas,
+    pub trending_collections: Vec<TrendingCollection>,
+    pub recent_large_sales: Vec<LargeSale>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl Dashboard {
+    pub const CACHE_KEY: &'static str = "stats:dashboard";
+
+    /// Rebuild the dashboard and replace the cached copy
+    pub async fn refresh_cache(
+        pool: &PgPool,
+        redis: &MultiplexedConnection,
+    ) -> Result<Self, AppError> {
+        let dashboard = Self::build(pool).await?;
+        cache::set_json(redis, Self::CACHE_KEY, &dashboard, CACHE_TTL_SECONDS).await?;
+        Ok(dashboard)
+    }
+
+    /// Blocklisted collections, creators and mints are left out of every figure
+    pub async fn build(pool: &PgPool) -> Result<Self, AppError> {
+        let now = Utc::now();
+        let day_ago = now - Duration::days(1);
+
+        let global = sqlx::query_as::<_, GlobalStats>(
+            r#"
+            WITH visible_sales AS (
+                SELECT s.base_price FROM sales s
+                LEFT JOIN nfts n ON n.mint_address = s.nft_mint
+                WHERE NOT nft_is_blocked(s.nft_mint, n.collection_id, n.creator_address)
+            )
+            SELECT (SELECT COALESCE(SUM(base_price), 0)::BIGINT FROM visible_sales) AS total_volume,
+                   (SELECT COUNT(*) FROM visible_sales) AS total_sales,
+                   (SELECT COUNT(*) FROM collections
+                     WHERE deleted_at IS NULL AND NOT collection_is_blocked(id, creator_address))
+                       AS total_collections,
+                   (SELECT COUNT(*) FROM nfts
+                     WHERE deleted_at IS NULL
+                       AND NOT nft_is_blocked(mint_address, collection_id, creator_address))
+                       AS total_nfts,
+                   (SELECT COUNT(*) FROM listings l
+                     LEFT JOIN nfts n ON n.mint_address = l.nft_mint
+                     WHERE l.status = 'active' AND l.deleted_at IS NULL
+                       AND NOT nft_is_blocked(l.nft_mint, n.collection_id, n.creator_address))
+                       AS active_listings
+            "#,
+        )
+        .fetch_one(pool)
+        .await?;
+
+        let current = Self::window(pool, day_ago, now).await?;
+        let previous = Self::window(pool, day_ago - Duration::days(1), day_ago).await?;
+
+        let trending_collections = sqlx::query_as::<_, TrendingCollection>(
+            r#"
+            SELECT c.id, c.name, c.image_url, c.rank_score,
+                   COALESCE(v.volume_24h, 0)::BIGINT AS volume_24h,
+                   COALESCE(v.sales_24h, 0) AS sales_24h,
+                   f.floor_price
+            FROM collections c
+            LEFT JOIN LATERAL (
+                SELECT SUM(s.base_price) AS volume_24h, COUNT(*) AS sales_24h
+                FROM sales s
+                JOIN nfts n ON n.mint_address = s.nft_mint
+                WHERE n.collection_id = c.id AND s.block_time >= $1
+            ) v ON TRUE
+            LEFT JOIN LATERAL (
+                SELECT MIN(l.price) AS floor_price
+                FROM listings l
+                JOIN nfts n ON n.mint_address = l.nft_mint
+                WHERE n.collection_id = c.id AND l.status = 'active' AND l.deleted_at IS NULL
+                  AND l.currency_mint = $2
+            ) f ON TRUE
+            WHERE c.deleted_at IS NULL AND NOT collection_is_blocked(c.id, c.creator_address)
+            ORDER BY c.rank_score DESC NULLS LAST, volume_24h DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(day_ago)
+        .bind(NATIVE_SOL_MINT)
+        .bind(TRENDING_LIMIT)
+        .fetch_all(pool)
+        .await?;
+
+        let recent_large_sales = sqlx::query_as::<_, LargeSale>(
+            r#"
+            SELECT s.nft_mint, n.name AS nft_name, n.collection_id, s.buyer_address,
+                   s.seller_address, s.price, s.currency_mint, s.base_price,
+                   s.transaction_signature, s.block_time
+            FROM sales s
+            LEFT JOIN nfts n ON n.mint_address = s.nft_mint
+            WHERE s.block_time >= $1
+              AND NOT nft_is_blocked(s.nft_mint, n.collection_id, n.creator_address)
+            ORDER BY s.base_price DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(day_ago)
+        .bind(LARGE_SALES_LIMIT)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(Self {
+            global,
+            last_24h: DashboardDeltas {
+                volume: KpiDelta::new(current.volume, previous.volume),
+                sales: KpiDelta::new(current.sales, previous.sales),
+                unique_buyers: KpiDelta::new(current.unique_buyers, previous.unique_buyers),
+                unique_sellers: KpiDelta::new(current.unique_sellers, previous.unique_sellers),
+                new_listings: KpiDelta::new(current.new_listings, previous.new_listings),
+            },
+            trending_collections,
+            recent_large_sales,
+            generated_at: now,
+        })
+    }
+
+    async fn window(
+        pool: &PgPool,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<WindowStats, AppError> {
+        let stats = sqlx::query_as::<_, WindowStats>(
+            r#"
+            SELECT COALESCE(SUM(s.base_price), 0)::BIGINT AS volume,
+                   COUNT(*) AS sales,
+                   COUNT(DISTINCT s.buyer_address) AS unique_buyers,
+                   COUNT(DISTINCT s.seller_address) AS unique_sellers,
+                   (SELECT COUNT(*) FROM listings l
+                     LEFT JOIN nfts ln ON ln.mint_address = l.nft_mint
+                     WHERE l.created_at >= $1 AND l.created_at < $2
+                       AND NOT nft_is_blocked(l.nft_mint, ln.collection_id, ln.creator_address))
+                       AS new_listings
+            FROM sales s
+            LEFT JOIN nfts n ON n.mint_address = s.nft_mint
+            WHERE s.block_time >= $1 AND s.block_time < $2
+              AND NOT nft_is_blocked(s.nft_mint, n.collection_id, n.creator_address)
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(stats)
+    }
+}
+
+impl KpiDelta {
+    fn new(current: i64, previous: i64) -> Self {
+        let change_pct = if previous == 0 {
+            None
+        } else {
+            Some(((current - previous) as f64 / previous as f64 * 10_000.0).round() / 100.0)
+        };
+
+        Self {
+            current,
+            previous,
+            change_pct,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+
+use crate::{cluster::SolanaCluster, error::AppError};
+
+pub struct IndexerState;
+
+/// Where the indexer resumes from
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct IndexerCheckpoint {
+    pub last_processed_slot: i64,
+    pub last_processed_signature: Option<String>,
+    pub cluster: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl IndexerState {
+    /// Stamp the configured cluster into `indexer_state`, or refuse to start
+    /// if the database was already indexed from a different cluster.
+    pub async fn ensure_cluster(pool: &PgPool, cluster: SolanaCluster) -> Result<(), AppError> {
+        let state =
+            sqlx::query!("SELECT id, cluster FROM indexer_state ORDER BY updated_at DESC LIMIT 1")
+                .fetch_optional(pool)
+                .await?;
+
+        match state {
+            None => {
+                sqlx::query!(
+                    "INSERT INTO indexer_state (last_processed_slot, cluster) VALUES (0, $1)",
+                    cluster.as_str()
+                )
+                .execute(pool)
+                .await?;
+            }
+            Some(row) => match row.cluster {
+                None => {
+                    sqlx::query!(
+                        "UPDATE indexer_state SET cluster = $2, updated_at = NOW() WHERE id = $1",
+                        row.id,
+                        cluster.as_str()
+                    )
+                    .execute(pool)
+                    .await?;
+                }
+                Some(indexed) if indexed != cluster.as_str() => {
+                    return Err(AppError::ConfigError(format!(
+                        "database holds {} data but SOLANA_CLUSTER is {}; point DATABASE_URL at a {} database",
+                        indexed, cluster, cluster
+                    )));
+                }
+                Some(_) => {}
+            },
+        }
+
+        Ok(())
+    }
+
+    pub async fn checkpoint(pool: &PgPool) -> Result<Option<IndexerCheckpoint>, AppError> {
+        let checkpoint = sqlx::query_as::<_, IndexerCheckpoint>(
+            r#"
+            SELECT last_processed_slot, last_processed_signature, cluster, updated_at
+            FROM indexer_state ORDER BY updated_at DESC LIMIT 1
+            "#,
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(checkpoint)
+    }
+
+    /// Move the checkpoint to `slot` so the indexer re-processes (or skips)
+    /// everything after it. The recorded cluster is kept.
+    pub async fn reset_checkpoint(pool: &PgPool, slot: u64) -> Result<(), AppError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE indexer_state
+            SET last_processed_slot = $1, last_processed_signature = NULL, updated_at = NOW()
+            WHERE id = (SELECT id FROM indexer_state ORDER BY updated_at DESC LIMIT 1)
+            "#,
+        )
+        .bind(slot as i64)
+        .execute(pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            sqlx::query("INSERT INTO indexer_state (last_processed_slot) VALUES ($1)")
+                .bind(slot as i64)
+                .execute(pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,520 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use sqlx::{FromRow, PgPool};
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+pub use solmint_client::{BuildMintRequest, LaunchListQuery};
+
+pub const LAUNCH_KIND_CANDY_MACHINE: &str = "candy_machine_v3";
+pub const LAUNCH_KIND_SOLMINT: &str = "solmint";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Launch {
+    pub id: Uuid,
+    pub kind: String,
+    pub config_address: String,
+    pub creator_address: String,
+    pub collection_id: Option<Uuid>,
+    pub name: String,
+    pub symbol: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub base_uri: Option<String>,
+    pub items_available: Option<i64>,
+    pub treasury_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LaunchPhase {
+    /// Candy guard group label for candy machines; at most 6 characters
+    pub label: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Lamports
+    #[serde(default)]
+    pub price: i64,
+    pub per_wallet_limit: Option<i32>,
+    #[serde(default)]
+    pub allowlist: bool,
+    /// Candy guard solPayment destination
+    pub payment_destination: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterLaunchRequest {
+    /// "candy_machine_v3" or "solmint"
+    pub kind: String,
+    /// Candy machine address; generated for solmint launches
+    pub config_address: Option<String>,
+    pub creator_address: String,
+    pub collection_id: Option<Uuid>,
+    pub name: String,
+    pub symbol: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    /// solmint launches only
+    pub base_uri: Option<String>,
+    pub items_available: Option<i64>,
+    pub treasury_address: Option<String>,
+    pub phases: Vec<LaunchPhase>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateLaunchPhasesRequest {
+    pub phases: Vec<LaunchPhase>,
+}
+
+/// How long a handed-out mint transaction holds its item and counts towards
+/// the wallet's limit. Its blockhash expires well before this, so a mint
+/// that hasn't been indexed by then never landed.
+const MINT_RESERVATION_MINUTES: i32 = 5;
+
+/// `launch_mints` rows still counting: indexed mints and live reservations
+const LIVE_MINT: &str = r#"
+    (lm.expires_at > NOW()
+     OR EXISTS (SELECT 1 FROM nfts n WHERE n.mint_address = lm.mint_address))
+"#;
+
+const LAUNCH_COLUMNS: &str = r#"
+    id, kind, config_address, creator_address, collection_id, name, symbol, description,
+    image_url, base_uri, items_available, treasury_address, created_at, updated_at
+"#;
+
+fn parse_pubkey(field: &str, value: &str) -> Result<Pubkey, AppError> {
+    Pubkey::from_str(value).map_err(|_| {
+        AppError::BadRequest(format!("{} '{}' is not a valid public key", field, value))
+    })
+}
+
+fn validate_phases(kind: &str, phases: &[LaunchPhase]) -> Result<(), AppError> {
+    if phases.is_empty() {
+        return Err(crate::error::bad_request_error(
+            "a launch needs at least one phase",
+        ));
+    }
+
+    let mut sorted: Vec<&LaunchPhase> = phases.iter().collect();
+    sorted.sort_by_key(|phase| phase.starts_at);
+    for (i, phase) in sorted.iter().enumerate() {
+        if phase.label.is_empty() || phase.label.len() > 6 {
+            return Err(crate::error::bad_request_error(
+                "phase labels must be 1 to 6 characters",
+            ));
+        }
+        if sorted[..i].iter().any(|other| other.label == phase.label) {
+            return Err(AppError::BadRequest(format!(
+                "duplicate phase label {}",
+                phase.label
+            )));
+        }
+        if phase
+            .ends_at
+            .is_some_and(|ends_at| ends_at <= phase.starts_at)
+        {
+            return Err(AppError::BadRequest(format!(
+                "phase {} must end after it starts",
+                phase.label
+            )));
+        }
+        if phase.price < 0 || phase.per_wallet_limit.is_some_and(|limit| limit <= 0) {
+            return Err(AppError::BadRequest(format!(
+                "phase {} needs a non-negative price and a positive wallet limit",
+                phase.label
+            )));
+        }
+        // Phases may not overlap, so there's at most one live phase
+        if let Some(next) = sorted.get(i + 1) {
+            if phase
+                .ends_at
+                .map_or(true, |ends_at| ends_at > next.starts_at)
+            {
+                return Err(AppError::BadRequest(format!(
+                    "phase {} must end before phase {} starts",
+                    phase.label, next.label
+                )));
+            }
+        }
+        if let Some(destination) = &phase.payment_destination {
+            parse_pubkey("payment_destination", destination)?;
+        }
+        if kind == LAUNCH_KIND_CANDY_MACHINE
+            && phase.price > 0
+            && phase.payment_destination.is_none()
+        {
+            return Err(AppError::BadRequest(format!(
+                "phase {} has a price, so it needs the solPayment payment_destination",
+                phase.label
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+impl LaunchPhase {
+    pub fn is_live(&self, now: DateTime<Utc>) -> bool {
+        self.starts_at <= now && self.ends_at.map_or(true, |ends_at| now < ends_at)
+    }
+}
+
+impl Launch {
+    /// Validate and store a launch. Candy machine fields read from chain
+    /// (authority checks, supply) are the caller's responsibility.
+    pub async fn create(
+        pool: &PgPool,
+        req: &RegisterLaunchRequest,
+        config_address: &str,
+    ) -> Result<Self, AppError> {
+        parse_pubkey("creator_address", &req.creator_address)?;
+        if req.name.trim().is_empty() {
+            return Err(crate::error::bad_request_error("name is required"));
+        }
+        match req.kind.as_str() {
+            LAUNCH_KIND_CANDY_MACHINE => {}
+            LAUNCH_KIND_SOLMINT => {
+                let base_uri = req.base_uri.as_deref().unwrap_or_default();
+                if !base_uri.starts_with("https://") && !base_uri.starts_with("ar://") {
+                    return Err(crate::error::bad_request_error(
+                        "solmint launches need an https:// or ar:// base_uri",
+                    ));
+                }
+                if !req.items_available.is_some_and(|items| items > 0) {
+                    return Err(crate::error::bad_request_error(
+                        "solmint launches need a positive items_available",
+                    ));
+                }
+                let treasury = req.treasury_address.as_deref().ok_or_else(|| {
+                    crate::error::bad_request_error("solmint launches need a treasury_address")
+                })?;
+                parse_pubkey("treasury_address", treasury)?;
+            }
+            _ => {
+                return Err(crate::error::bad_request_error(
+                    "kind must be candy_machine_v3 or solmint",
+                ))
+            }
+        }
+        validate_phases(&req.kind, &req.phases)?;
+
+        let mut tx = pool.begin().await?;
+
+        let launch = sqlx::query_as::<_, Launch>(&format!(
+            r#"
+            INSERT INTO launches (
+                kind, config_address, creator_address, collection_id, name, symbol,
+                description, image_url, base_uri, items_available, treasury_address
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (config_address) DO NOTHING
+            RETURNING {}
+            "#,
+            LAUNCH_COLUMNS
+        ))
+        .bind(&req.kind)
+        .bind(config_address)
+        .bind(&req.creator_address)
+        .bind(req.collection_id)
+        .bind(req.name.trim())
+        .bind(&req.symbol)
+        .bind(&req.description)
+        .bind(&req.image_url)
+        .bind(req.base_uri.as_deref().map(|uri| uri.trim_end_matches('/')))
+        .bind(req.items_available)
+        .bind(&req.treasury_address)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| {
+            crate::error::bad_request_error("a launch is already registered for this config")
+        })?;
+
+        insert_phases(&mut tx, launch.id, &req.phases).await?;
+        tx.commit().await?;
+
+        Ok(launch)
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, AppError> {
+        let launch = sqlx::query_as::<_, Launch>(&format!(
+            "SELECT {} FROM launches WHERE id = $1",
+            LAUNCH_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(launch)
+    }
+
+    pub async fn find_by_config_address(
+        pool: &PgPool,
+        config_address: &str,
+    ) -> Result<Option<Self>, AppError> {
+        let launch = sqlx::query_as::<_, Launch>(&format!(
+            "SELECT {} FROM launches WHERE config_address = $1",
+            LAUNCH_COLUMNS
+        ))
+        .bind(config_address)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(launch)
+    }
+
+    /// Launches by schedule: `upcoming` (first phase in the future), `live`
+    /// (a phase running now) or `ended` (every phase over)
+    pub async fn list(
+        pool: &PgPool,
+        query: &LaunchListQuery,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, AppError> {
+        let condition = match query.status.as_deref() {
+            None => "TRUE",
+            Some("upcoming") => "MIN(p.starts_at) > NOW()",
+            Some("live") => {
+                "BOOL_OR(p.starts_at <= NOW() AND (p.ends_at IS NULL OR p.ends_at > NOW()))"
+            }
+            Some("ended") => "BOOL_AND(p.ends_at IS NOT NULL AND p.ends_at <= NOW())",
+            Some(_) => {
+                return Err(crate::error::bad_request_error(
+                    "status must be upcoming, live or ended",
+                ))
+            }
+        };
+
+        let launches = sqlx::query_as::<_, Launch>(&format!(
+            r#"
+            SELECT {}
+            FROM launches
+            WHERE id IN (
+                SELECT p.launch_id FROM launch_phases p
+                GROUP BY p.launch_id
+                HAVING {}
+            )
+            ORDER BY created_at DESC, id
+            LIMIT $1 OFFSET $2
+            "#,
+            LAUNCH_COLUMNS, condition
+        ))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(launches)
+    }
+
+    pub async fn list_for_creator(
+        pool: &PgPool,
+        creator_address: &str,
+        limit: i64,
+    ) -> Result<Vec<Self>, AppError> {
+        let launches = sqlx::query_as::<_, Launch>(&format!(
+            r#"
+            SELECT {} FROM launches
+            WHERE creator_address = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+            LAUNCH_COLUMNS
+        ))
+        .bind(creator_address)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(launches)
+    }
+
+    pub async fn phases(&self, pool: &PgPool) -> Result<Vec<LaunchPhase>, AppError> {
+        let phases = sqlx::query_as::<_, LaunchPhase>(
+            r#"
+            SELECT label, starts_at, ends_at, price, per_wallet_limit, allowlist,
+                   payment_destination
+            FROM launch_phases
+            WHERE launch_id = $1
+            ORDER BY starts_at
+            "#,
+        )
+        .bind(self.id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(phases)
+    }
+
+    /// Replace the phase schedule; only the launch's creator may
+    pub async fn replace_phases(
+        &self,
+        pool: &PgPool,
+        creator_address: &str,
+        req: &UpdateLaunchPhasesRequest,
+    ) -> Result<Vec<LaunchPhase>, AppError> {
+        if creator_address != self.creator_address {
+            return Err(AppError::Forbidden(
+                "only the launch creator may change its phases".to_string(),
+            ));
+        }
+        validate_phases(&self.kind, &req.phases)?;
+
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM launch_phases WHERE launch_id = $1")
+            .bind(self.id)
+            .execute(&mut *tx)
+            .await?;
+        insert_phases(&mut tx, self.id, &req.phases).await?;
+        sqlx::query("UPDATE launches SET updated_at = NOW() WHERE id = $1")
+            .bind(self.id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        self.phases(pool).await
+    }
+
+    /// Reserve a mint in `phase_label` for `wallet_address` under
+    /// `mint_address`, holding the lowest free item index of a solmint
+    /// launch, which is returned. The launch row is locked while the wallet's
+    /// live mints are counted against `wallet_limit`, so concurrent requests
+    /// can't both take its last slot.
+    pub async fn reserve_mint(
+        &self,
+        pool: &PgPool,
+        mint_address: &str,
+        wallet_address: &str,
+        phase_label: &str,
+        wallet_limit: Option<i64>,
+    ) -> Result<Option<i64>, AppError> {
+        let mut tx = pool.begin().await?;
+        sqlx::query("SELECT id FROM launches WHERE id = $1 FOR UPDATE")
+            .bind(self.id)
+            .execute(&mut *tx)
+            .await?;
+
+        if let Some(limit) = wallet_limit {
+            let minted: i64 = sqlx::query_scalar(&format!(
+                r#"
+                SELECT COUNT(*) FROM launch_mints lm
+                WHERE lm.launch_id = $1 AND lm.wallet_address = $2 AND lm.phase_label = $3
+                  AND {}
+                "#,
+                LIVE_MINT
+            ))
+            .bind(self.id)
+            .bind(wallet_address)
+            .bind(phase_label)
+            .fetch_one(&mut *tx)
+            .await?;
+            if minted >= limit {
+                return Err(crate::error::bad_request_error(
+                    "wallet has reached its mint limit for this phase",
+                ));
+            }
+        }
+
+        let item_index = if self.kind == LAUNCH_KIND_CANDY_MACHINE {
+            None
+        } else {
+            let index: Option<i64> = sqlx::query_scalar(&format!(
+                r#"
+                SELECT i FROM generate_series(0, $2 - 1) AS i
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM launch_mints lm
+                    WHERE lm.launch_id = $1 AND lm.item_index = i AND {}
+                )
+                ORDER BY i
+                LIMIT 1
+                "#,
+                LIVE_MINT
+            ))
+            .bind(self.id)
+            .bind(self.items_available.unwrap_or_default())
+            .fetch_optional(&mut *tx)
+            .await?;
+            Some(index.ok_or_else(|| crate::error::bad_request_error("the launch is sold out"))?)
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO launch_mints (
+                mint_address, launch_id, wallet_address, phase_label, item_index, expires_at
+            )
+            VALUES ($1, $2, $3, $4, $5, NOW() + make_interval(mins => $6))
+            "#,
+        )
+        .bind(mint_address)
+        .bind(self.id)
+        .bind(wallet_address)
+        .bind(phase_label)
+        .bind(item_index)
+        .bind(MINT_RESERVATION_MINUTES)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(item_index)
+    }
+
+    /// Drop a reservation whose transaction couldn't be built
+    pub async fn release_mint(&self, pool: &PgPool, mint_address: &str) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM launch_mints WHERE launch_id = $1 AND mint_address = $2")
+            .bind(self.id)
+            .bind(mint_address)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Handed-out mints that have landed on chain and been indexed
+    pub async fn minted_count(&self, pool: &PgPool) -> Result<i64, AppError> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM launch_mints lm
+            JOIN nfts n ON n.mint_address = lm.mint_address
+            WHERE lm.launch_id = $1
+            "#,
+        )
+        .bind(self.id)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count)
+    }
+}
+
+async fn insert_phases(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    launch_id: Uuid,
+    phases: &[LaunchPhase],
+) -> Result<(), AppError> {
+    for phase in phases {
+        sqlx::query(
+            r#"
+            INSERT INTO launch_phases (
+                launch_id, label, starts_at, ends_at, price, per_wallet_limit, allowlist,
+                payment_destination
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(launch_id)
+        .bind(&phase.label)
+        .bind(phase.starts_at)
+        .bind(phase.ends_at)
+        .bind(phase.price)
+        .bind(phase.per_wallet_limit)
+        .bind(phase.allowlist)
+        .bind(&phase.payment_destination)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
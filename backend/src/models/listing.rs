@@ -0,0 +1,234 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{OutboxEvent, AGGREGATE_LISTING},
+};
+
+pub use solmint_client::ListingChurnQuery;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Listing {
+    pub id: Uuid,
+    pub listing_address: Option<String>,
+    pub nft_mint: String,
+    pub seller_address: String,
+    pub price: i64,
+    pub currency_mint: String,
+    pub marketplace_address: Option<String>,
+    /// `active`, `sold`, or anything else for a delisted listing
+    pub status: String,
+    pub transaction_signature: Option<String>,
+    pub block_time: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Bumped on every update; pass it back to `Listing::update`
+    pub version: i32,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateListingRequest {
+    pub price: Option<i64>,
+    pub status: Option<String>,
+    pub transaction_signature: Option<String>,
+    pub block_time: Option<DateTime<Utc>>,
+}
+
+const LISTING_COLUMNS: &str = r#"
+    id, listing_address, nft_mint, seller_address, price, currency_mint, marketplace_address,
+    status, transaction_signature, block_time, created_at, updated_at, version
+"#;
+
+impl Listing {
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, AppError> {
+        let listing = sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM listings WHERE id = $1 AND deleted_at IS NULL",
+            LISTING_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(listing)
+    }
+
+    pub async fn find_by_address(
+        pool: &PgPool,
+        listing_address: &str,
+    ) -> Result<Option<Self>, AppError> {
+        let listing = sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM listings WHERE listing_address = $1 AND deleted_at IS NULL",
+            LISTING_COLUMNS
+        ))
+        .bind(listing_address)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(listing)
+    }
+
+    /// Apply `req` if the listing is still at `expected_version`. Returns
+    /// `AppError::Conflict` when another writer updated it first; re-read the
+    /// listing and retry (see `error::retry_on_conflict`).
+    pub async fn update(
+        pool: &PgPool,
+        id: Uuid,
+        expected_version: i32,
+        req: UpdateListingRequest,
+    ) -> Result<Self, AppError> {
+        let mut tx = pool.begin().await?;
+        let listing = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            UPDATE listings SET
+                price = COALESCE($3, price),
+                status = COALESCE($4, status),
+                transaction_signature = COALESCE($5, transaction_signature),
+                block_time = COALESCE($6, block_time),
+                updated_at = NOW()
+            WHERE id = $1 AND version = $2 AND deleted_at IS NULL
+            RETURNING {}
+            "#,
+            LISTING_COLUMNS
+        ))
+        .bind(id)
+        .bind(expected_version)
+        .bind(req.price)
+        .bind(req.status)
+        .bind(req.transaction_signature)
+        .bind(req.block_time)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        match listing {
+            Some(listing) => {
+                OutboxEvent::enqueue(
+                    &mut tx,
+                    AGGREGATE_LISTING,
+                    &listing.id.to_string(),
+                    "listing.updated",
+                    &listing,
+                )
+                .await?;
+                tx.commit().await?;
+                Ok(listing)
+            }
+            None if Self::find_by_id(pool, id).await?.is_some() => Err(
+                crate::error::conflict_error("Listing was modified concurrently"),
+            ),
+            None => Err(crate::error::not_found_error("Listing")),
+        }
+    }
+}
+
+/// Listing lifecycle counts for one collection, derived from `listing_history`
+#[derive(Debug, Clone, FromRow)]
+struct ChurnCounts {
+    tracked_listings: i64,
+    sales: i64,
+    avg_time_to_sale_seconds: Option<f64>,
+    price_cuts: i64,
+    cuts_below_floor: i64,
+    delists: i64,
+    relists: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ListingChurn {
+    pub collection_id: Uuid,
+    pub since: DateTime<Utc>,
+    /// Listings with any lifecycle event in the window
+    pub tracked_listings: i64,
+    pub sales: i64,
+    /// Mean time from (re)listing to sale for listings sold in the window
+    pub avg_time_to_sale_seconds: Option<f64>,
+    pub price_cuts: i64,
+    pub price_cut_rate: f64,
+    /// Share of tracked listings cut to below the collection floor at the time
+    pub below_floor_cut_rate: f64,
+    pub delists: i64,
+    pub relists: i64,
+    /// Share of delists followed by the same seller listing the NFT again
+    pub relist_rate: f64,
+}
+
+impl ListingChurn {
+    pub async fn for_collection(
+        pool: &PgPool,
+        collection_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<Self, AppError> {
+        let counts = sqlx::query_as::<_, ChurnCounts>(
+            r#"
+            WITH events AS (
+                SELECT * FROM listing_history
+                WHERE collection_id = $1 AND occurred_at >= $2
+            ),
+            sold AS (
+                SELECT h.occurred_at - (
+                    SELECT MAX(l.occurred_at) FROM listing_history l
+                    WHERE l.listing_id = h.listing_id
+                      AND l.event_type = 'listed'
+                      AND l.occurred_at <= h.occurred_at
+                ) AS time_to_sale
+                FROM events h
+                WHERE h.event_type = 'sold'
+            ),
+            cuts AS (
+                SELECT listing_id,
+                       BOOL_OR(floor_price IS NOT NULL AND price < floor_price) AS below_floor
+                FROM events
+                WHERE event_type = 'price_change' AND price < previous_price
+                GROUP BY listing_id
+            ),
+            delists AS (
+                SELECT id, nft_mint, seller_address, occurred_at
+                FROM events WHERE event_type = 'delisted'
+            )
+            SELECT
+                (SELECT COUNT(DISTINCT listing_id) FROM events) AS tracked_listings,
+                (SELECT COUNT(*) FROM sold) AS sales,
+                (SELECT EXTRACT(EPOCH FROM AVG(time_to_sale))::FLOAT8 FROM sold)
+                    AS avg_time_to_sale_seconds,
+                (SELECT COUNT(*) FROM cuts) AS price_cuts,
+                (SELECT COUNT(*) FILTER (WHERE below_floor) FROM cuts) AS cuts_below_floor,
+                (SELECT COUNT(*) FROM delists) AS delists,
+                (SELECT COUNT(*) FROM delists d WHERE EXISTS (
+                    SELECT 1 FROM listing_history l
+                    WHERE l.nft_mint = d.nft_mint
+                      AND l.seller_address = d.seller_address
+                      AND l.event_type = 'listed'
+                      AND l.occurred_at > d.occurred_at
+                )) AS relists
+            "#,
+        )
+        .bind(collection_id)
+        .bind(since)
+        .fetch_one(pool)
+        .await?;
+
+        let ratio = |part: i64, whole: i64| {
+            if whole == 0 {
+                0.0
+            } else {
+                part as f64 / whole as f64
+            }
+        };
+
+        Ok(Self {
+            collection_id,
+            since,
+            tracked_listings: counts.tracked_listings,
+            sales: counts.sales,
+            avg_time_to_sale_seconds: counts.avg_time_to_sale_seconds,
+            price_cuts: counts.price_cuts,
+            price_cut_rate: ratio(counts.price_cuts, counts.tracked_listings),
+            below_floor_cut_rate: ratio(counts.cuts_below_floor, counts.tracked_listings),
+            delists: counts.delists,
+            relists: counts.relists,
+            relist_rate: ratio(counts.relists, counts.delists),
+        })
+    }
+}
@@ -1,5 +1,57 @@
+pub mod access_block;
+pub mod activity;
+pub mod admin_api_key;
+pub mod alert_integration;
+pub mod auction;
+pub mod blocklist;
+pub mod checkout_order;
+pub mod collection;
+pub mod collection_import;
+pub mod collection_trait;
+pub mod creator_dashboard;
+pub mod dashboard;
+pub mod indexer_state;
+pub mod launch;
+pub mod listing;
 pub mod nft;
+pub mod notification;
+pub mod offer;
+pub mod outbox;
+pub mod points;
+pub mod push_device;
+pub mod rental;
+pub mod row_history;
+pub mod sale;
+pub mod stats;
+pub mod tax_report;
 pub mod user;
+pub mod webhook;
 
+pub use access_block::*;
+pub use activity::*;
+pub use admin_api_key::*;
+pub use alert_integration::*;
+pub use auction::*;
+pub use blocklist::*;
+pub use checkout_order::*;
+pub use collection::*;
+pub use collection_import::*;
+pub use collection_trait::*;
+pub use creator_dashboard::*;
+pub use dashboard::*;
+pub use indexer_state::*;
+pub use launch::*;
+pub use listing::*;
 pub use nft::*;
+pub use notification::*;
+pub use offer::*;
+pub use outbox::*;
+pub use points::*;
+pub use push_device::*;
+pub use rental::*;
+pub use row_history::*;
+pub use sale::*;
+pub use stats::*;
+pub use tax_report::*;
 pub use user::*;
+pub use webhook::*;
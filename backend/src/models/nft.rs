@@ -1,8 +1,14 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool};
+use serde_json::{json, Value};
+use sqlx::{types::Json, FromRow, PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
+use crate::models::{OutboxEvent, AGGREGATE_NFT};
+
+/// Most trait values an `attributes` filter may name, across trait types
+const MAX_ATTRIBUTE_FILTER_VALUES: usize = 50;
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Nft {
     pub id: Uuid,
@@ -21,6 +27,10 @@ pub struct Nft {
     pub rarity_score: Option<rust_decimal::Decimal>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Bumped on every update; pass it back to `Nft::update`
+    pub version: i32,
+    /// Flagged by the spam job or an admin; hidden from lists by default
+    pub is_spam: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -45,7 +55,7 @@ pub struct CreateNftRequest {
     pub is_compressed: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct UpdateNftRequest {
     pub name: Option<String>,
     pub description: Option<String>,
@@ -67,23 +77,137 @@ pub struct NftListQuery {
     pub max_price: Option<i64>,
     pub rarity_rank_min: Option<i32>,
     pub rarity_rank_max: Option<i32>,
-    pub attributes: Option<String>, // JSON string of attribute filters
+    /// JSON object of trait type to value, or to a list of values any of
+    /// which match, e.g. `{"Background":"Blue","Hat":["Cap","Crown"]}`
+    pub attributes: Option<String>,
     pub sort_by: Option<String>,    // "price", "rarity", "created_at"
     pub sort_order: Option<String>, // "asc", "desc"
-    pub page: Option<i64>,
-    pub limit: Option<i64>,
+    /// Include NFTs flagged as spam
+    pub include_spam: Option<bool>,
+    /// Include blocklisted NFTs; admin only
+    pub include_blocked: Option<bool>,
+}
+
+impl NftListQuery {
+    /// Compact description of the filters in use, for slow query logs
+    pub fn filter_summary(&self) -> String {
+        let mut filters = Vec::new();
+
+        if let Some(collection_id) = self.collection_id {
+            filters.push(format!("collection_id={}", collection_id));
+        }
+        if let Some(owner) = &self.owner {
+            filters.push(format!("owner={}", owner));
+        }
+        if let Some(creator) = &self.creator {
+            filters.push(format!("creator={}", creator));
+        }
+        if let Some(min_price) = self.min_price {
+            filters.push(format!("min_price={}", min_price));
+        }
+        if let Some(max_price) = self.max_price {
+            filters.push(format!("max_price={}", max_price));
+        }
+        if let Some(min_rank) = self.rarity_rank_min {
+            filters.push(format!("rarity_rank_min={}", min_rank));
+        }
+        if let Some(max_rank) = self.rarity_rank_max {
+            filters.push(format!("rarity_rank_max={}", max_rank));
+        }
+        if let Some(attributes) = &self.attributes {
+            filters.push(format!("attributes={}", attributes));
+        }
+        if let Some(sort_by) = &self.sort_by {
+            filters.push(format!(
+                "sort={} {}",
+                sort_by,
+                self.sort_order.as_deref().unwrap_or("desc")
+            ));
+        }
+        filters.join(", ")
+    }
+
+    /// The `attributes` filter as containment documents for `nfts.attributes`,
+    /// one list per trait type: an NFT must match one document of every list
+    pub fn attribute_filters(&self) -> Result<Vec<Vec<Value>>, crate::error::AppError> {
+        let Some(attributes) = &self.attributes else {
+            return Ok(Vec::new());
+        };
+        let invalid = || {
+            crate::error::bad_request_error(
+                "attributes must be a JSON object of trait types to a value or list of values",
+            )
+        };
+        let Ok(Value::Object(traits)) = serde_json::from_str::<Value>(attributes) else {
+            return Err(invalid());
+        };
+
+        let mut filters = Vec::with_capacity(traits.len());
+        let mut value_count = 0;
+        for (trait_type, values) in traits {
+            let values = match values {
+                Value::Array(values) => values,
+                value => vec![value],
+            };
+            if values.is_empty() {
+                return Err(invalid());
+            }
+            value_count += values.len();
+
+            let documents = values
+                .into_iter()
+                .map(|value| match value {
+                    Value::String(_) | Value::Number(_) | Value::Bool(_) => {
+                        Ok(json!([{ "trait_type": trait_type, "value": value }]))
+                    }
+                    _ => Err(invalid()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            filters.push(documents);
+        }
+        if value_count > MAX_ATTRIBUTE_FILTER_VALUES {
+            return Err(crate::error::AppError::BadRequest(format!(
+                "attributes may name at most {} values",
+                MAX_ATTRIBUTE_FILTER_VALUES
+            )));
+        }
+
+        Ok(filters)
+    }
+}
+
+/// `AND (n.attributes @> $a OR n.attributes @> $b ...)` per trait type, which
+/// the GIN index on `nfts.attributes` serves
+fn push_attribute_filters(
+    query_builder: &mut QueryBuilder<'_, Postgres>,
+    filters: Vec<Vec<Value>>,
+) {
+    for documents in filters {
+        query_builder.push(" AND (");
+        for (i, document) in documents.into_iter().enumerate() {
+            if i > 0 {
+                query_builder.push(" OR ");
+            }
+            query_builder.push("n.attributes @> ");
+            query_builder.push_bind(Json(document));
+        }
+        query_builder.push(")");
+    }
 }
 
 impl Nft {
+    /// Insert an NFT keyed on its mint. Returns `None` when the mint is already
+    /// indexed, so replayed events don't fail or emit a second `nft.created`.
     pub async fn create(
         pool: &PgPool,
         req: CreateNftRequest,
-    ) -> Result<Self, crate::error::AppError> {
+    ) -> Result<Option<Self>, crate::error::AppError> {
         let attributes_json = req
             .attributes
             .map(|attrs| serde_json::to_value(attrs))
             .transpose()?;
 
+        let mut tx = pool.begin().await?;
         let nft = sqlx::query_as!(
             Nft,
             r#"
@@ -93,10 +217,12 @@ impl Nft {
                 current_owner, is_compressed
             )
             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (mint_address) DO NOTHING
             RETURNING id, mint_address, collection_id, name, description, image_url,
                      animation_url, external_url, attributes, creator_address,
                      current_owner, is_compressed as "is_compressed!", rarity_rank, rarity_score,
-                     created_at as "created_at!", updated_at as "updated_at!"
+                     created_at as "created_at!", updated_at as "updated_at!", version,
+                     is_spam as "is_spam!"
             "#,
             req.mint_address,
             req.collection_id,
@@ -110,10 +236,33 @@ impl Nft {
             req.current_owner,
             req.is_compressed
         )
-        .fetch_one(pool)
+        .fetch_optional(&mut *tx)
         .await?;
+        let Some(nft) = nft else {
+            return Ok(None);
+        };
 
-        Ok(nft)
+        // New members change trait frequencies; the rarity job picks this up
+        if let Some(collection_id) = nft.collection_id {
+            sqlx::query!(
+                "UPDATE collections SET rarity_stale = TRUE WHERE id = $1",
+                collection_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        OutboxEvent::enqueue(
+            &mut tx,
+            AGGREGATE_NFT,
+            &nft.mint_address,
+            "nft.created",
+            &nft,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(Some(nft))
     }
 
     pub async fn find_by_mint(
@@ -126,8 +275,9 @@ impl Nft {
             SELECT id, mint_address, collection_id, name, description, image_url,
                    animation_url, external_url, attributes, creator_address,
                    current_owner, is_compressed as "is_compressed!", rarity_rank, rarity_score,
-                   created_at as "created_at!", updated_at as "updated_at!"
-            FROM nfts WHERE mint_address = $1
+                   created_at as "created_at!", updated_at as "updated_at!", version,
+                     is_spam as "is_spam!"
+            FROM nfts WHERE mint_address = $1 AND deleted_at IS NULL
             "#,
             mint_address
         )
@@ -137,9 +287,13 @@ impl Nft {
         Ok(nft)
     }
 
+    /// Apply `req` if the NFT is still at `expected_version`. Returns
+    /// `AppError::Conflict` when another writer updated it first; re-read the
+    /// NFT and retry (see `error::retry_on_conflict`).
     pub async fn update(
         pool: &PgPool,
         mint_address: &str,
+        expected_version: i32,
         req: UpdateNftRequest,
     ) -> Result<Self, crate::error::AppError> {
         let attributes_json = req
@@ -147,6 +301,7 @@ impl Nft {
             .map(|attrs| serde_json::to_value(attrs))
             .transpose()?;
 
+        let mut tx = pool.begin().await?;
         let nft = sqlx::query_as!(
             Nft,
             r#"
@@ -161,11 +316,12 @@ impl Nft {
                 rarity_rank = COALESCE($9, rarity_rank),
                 rarity_score = COALESCE($10, rarity_score),
                 updated_at = NOW()
-            WHERE mint_address = $1
+            WHERE mint_address = $1 AND version = $11 AND deleted_at IS NULL
             RETURNING id, mint_address, collection_id, name, description, image_url,
                      animation_url, external_url, attributes, creator_address,
                      current_owner, is_compressed as "is_compressed!", rarity_rank, rarity_score,
-                     created_at as "created_at!", updated_at as "updated_at!"
+                     created_at as "created_at!", updated_at as "updated_at!", version,
+                     is_spam as "is_spam!"
             "#,
             mint_address,
             req.name,
@@ -176,21 +332,177 @@ impl Nft {
             attributes_json,
             req.current_owner,
             req.rarity_rank,
-            req.rarity_score
+            req.rarity_score,
+            expected_version
         )
-        .fetch_one(pool)
+        .fetch_optional(&mut *tx)
         .await?;
 
-        Ok(nft)
+        match nft {
+            Some(nft) => {
+                OutboxEvent::enqueue(
+                    &mut tx,
+                    AGGREGATE_NFT,
+                    &nft.mint_address,
+                    "nft.updated",
+                    &nft,
+                )
+                .await?;
+                tx.commit().await?;
+                Ok(nft)
+            }
+            None if Self::find_by_mint(pool, mint_address).await?.is_some() => Err(
+                crate::error::conflict_error("NFT was modified concurrently"),
+            ),
+            None => Err(crate::error::not_found_error("NFT")),
+        }
+    }
+
+    /// NFTs whose metadata hasn't been re-read in `stale_days` (or ever),
+    /// recently traded ones first since their pages get the most views
+    pub async fn stale_metadata(
+        pool: &PgPool,
+        stale_days: i32,
+        limit: i64,
+    ) -> Result<Vec<String>, crate::error::AppError> {
+        let mints = sqlx::query_scalar(
+            r#"
+            SELECT n.mint_address
+            FROM nfts n
+            LEFT JOIN nft_metadata_refreshes r ON r.mint_address = n.mint_address
+            LEFT JOIN LATERAL (
+                SELECT MAX(s.block_time) AS last_sale FROM sales s WHERE s.nft_mint = n.mint_address
+            ) s ON TRUE
+            WHERE n.deleted_at IS NULL
+              AND (r.refreshed_at IS NULL OR r.refreshed_at < NOW() - make_interval(days => $1))
+            ORDER BY s.last_sale DESC NULLS LAST, r.refreshed_at NULLS FIRST, n.id
+            LIMIT $2
+            "#,
+        )
+        .bind(stale_days)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(mints)
+    }
+
+    pub async fn metadata_refreshed_at(
+        pool: &PgPool,
+        mint_address: &str,
+    ) -> Result<Option<DateTime<Utc>>, crate::error::AppError> {
+        let refreshed_at = sqlx::query_scalar(
+            "SELECT refreshed_at FROM nft_metadata_refreshes WHERE mint_address = $1",
+        )
+        .bind(mint_address)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(refreshed_at)
+    }
+
+    /// Note a metadata refresh attempt. Failed attempts count too, so one
+    /// broken URI doesn't hold up the rest of the queue.
+    pub async fn record_metadata_refresh(
+        pool: &PgPool,
+        mint_address: &str,
+        metadata_uri: Option<&str>,
+        changed: bool,
+        error: Option<&str>,
+    ) -> Result<(), crate::error::AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO nft_metadata_refreshes (
+                mint_address, metadata_uri, refreshed_at, changed_at, last_error
+            )
+            VALUES ($1, $2, NOW(), CASE WHEN $3 THEN NOW() END, $4)
+            ON CONFLICT (mint_address) DO UPDATE SET
+                metadata_uri = COALESCE(EXCLUDED.metadata_uri, nft_metadata_refreshes.metadata_uri),
+                refreshed_at = EXCLUDED.refreshed_at,
+                changed_at = COALESCE(EXCLUDED.changed_at, nft_metadata_refreshes.changed_at),
+                last_error = EXCLUDED.last_error
+            "#,
+        )
+        .bind(mint_address)
+        .bind(metadata_uri)
+        .bind(changed)
+        .bind(error)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Move the NFT into `collection_id`; both the old and new collection
+    /// need their rarity recomputed
+    pub async fn set_collection(
+        pool: &PgPool,
+        mint_address: &str,
+        collection_id: Uuid,
+    ) -> Result<Option<Self>, crate::error::AppError> {
+        let mut tx = pool.begin().await?;
+        let previous: Option<Option<Uuid>> = sqlx::query_scalar(
+            r#"
+            SELECT collection_id FROM nfts
+            WHERE mint_address = $1 AND deleted_at IS NULL
+            FOR UPDATE
+            "#,
+        )
+        .bind(mint_address)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(previous) = previous else {
+            return Ok(None);
+        };
+
+        let nft = sqlx::query_as::<_, Self>(
+            r#"
+            UPDATE nfts SET collection_id = $2, updated_at = NOW()
+            WHERE mint_address = $1
+            RETURNING id, mint_address, collection_id, name, description, image_url,
+                      animation_url, external_url, attributes, creator_address,
+                      current_owner, is_compressed, rarity_rank, rarity_score,
+                      created_at, updated_at, version, is_spam
+            "#,
+        )
+        .bind(mint_address)
+        .bind(collection_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE collections SET rarity_stale = TRUE WHERE id = ANY($1)")
+            .bind(
+                [Some(collection_id), previous]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>(),
+            )
+            .execute(&mut *tx)
+            .await?;
+
+        OutboxEvent::enqueue(
+            &mut tx,
+            AGGREGATE_NFT,
+            &nft.mint_address,
+            "nft.updated",
+            &nft,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(Some(nft))
     }
 
+    /// A page of NFTs matching `query`. `after` is the `(created_at, id)` of
+    /// the last NFT of the previous page, for cursor pages sorted by
+    /// `created_at`.
     pub async fn list(
         pool: &PgPool,
         query: NftListQuery,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        limit: i64,
+        offset: i64,
     ) -> Result<Vec<Self>, crate::error::AppError> {
-        let limit = query.limit.unwrap_or(20).min(100);
-        let offset = query.page.unwrap_or(0) * limit;
-
         let sort_column = match query.sort_by.as_deref() {
             Some("price") => "l.price",
             Some("rarity") => "n.rarity_rank",
@@ -203,17 +515,30 @@ impl Nft {
             _ => "DESC",
         };
 
+        let attribute_filters = query.attribute_filters()?;
+        let summary = query.clone();
         let mut query_builder = sqlx::QueryBuilder::new(
             r#"
             SELECT DISTINCT n.id, n.mint_address, n.collection_id, n.name, n.description, n.image_url,
                    n.animation_url, n.external_url, n.attributes, n.creator_address,
                    n.current_owner, n.is_compressed, n.rarity_rank, n.rarity_score,
-                   n.created_at, n.updated_at FROM nfts n
-            LEFT JOIN listings l ON n.mint_address = l.nft_mint AND l.status = 'active'
-            WHERE 1=1
+                   n.created_at, n.updated_at, n.version, n.is_spam FROM nfts n
+            LEFT JOIN listings l
+                ON n.mint_address = l.nft_mint AND l.status = 'active' AND l.deleted_at IS NULL
+            WHERE n.deleted_at IS NULL
             "#,
         );
 
+        if !query.include_spam.unwrap_or(false) {
+            query_builder.push(" AND n.is_spam = FALSE");
+        }
+
+        if !query.include_blocked.unwrap_or(false) {
+            query_builder.push(
+                " AND NOT nft_is_blocked(n.mint_address, n.collection_id, n.creator_address)",
+            );
+        }
+
         if let Some(collection_id) = query.collection_id {
             query_builder.push(" AND n.collection_id = ");
             query_builder.push_bind(collection_id);
@@ -249,32 +574,74 @@ impl Nft {
             query_builder.push_bind(max_rank);
         }
 
+        push_attribute_filters(&mut query_builder, attribute_filters);
+
+        if let Some((created_at, id)) = after {
+            if sort_column != "n.created_at" {
+                return Err(crate::error::bad_request_error(
+                    "cursor pages are only available sorted by created_at",
+                ));
+            }
+            query_builder.push(if sort_order == "ASC" {
+                " AND (n.created_at, n.id) > ("
+            } else {
+                " AND (n.created_at, n.id) < ("
+            });
+            query_builder.push_bind(created_at);
+            query_builder.push(", ");
+            query_builder.push_bind(id);
+            query_builder.push(")");
+        }
+
         query_builder.push(" ORDER BY ");
         query_builder.push(sort_column);
         query_builder.push(" ");
         query_builder.push(sort_order);
+        query_builder.push(", n.id ");
+        query_builder.push(sort_order);
         query_builder.push(" LIMIT ");
         query_builder.push_bind(limit);
         query_builder.push(" OFFSET ");
         query_builder.push_bind(offset);
 
-        let nfts = query_builder
-            .build_query_as::<Nft>()
-            .fetch_all(pool)
-            .await?;
+        let nfts = crate::database::instrument(
+            "nfts.list",
+            || {
+                format!(
+                    "{}, limit={} offset={}",
+                    summary.filter_summary(),
+                    limit,
+                    offset
+                )
+            },
+            query_builder.build_query_as::<Nft>().fetch_all(pool),
+        )
+        .await?;
 
         Ok(nfts)
     }
 
     pub async fn count(pool: &PgPool, query: &NftListQuery) -> Result<i64, crate::error::AppError> {
+        let attribute_filters = query.attribute_filters()?;
         let mut query_builder = sqlx::QueryBuilder::new(
             r#"
             SELECT COUNT(DISTINCT n.id) FROM nfts n
-            LEFT JOIN listings l ON n.mint_address = l.nft_mint AND l.status = 'active'
-            WHERE 1=1
+            LEFT JOIN listings l
+                ON n.mint_address = l.nft_mint AND l.status = 'active' AND l.deleted_at IS NULL
+            WHERE n.deleted_at IS NULL
             "#,
         );
 
+        if !query.include_spam.unwrap_or(false) {
+            query_builder.push(" AND n.is_spam = FALSE");
+        }
+
+        if !query.include_blocked.unwrap_or(false) {
+            query_builder.push(
+                " AND NOT nft_is_blocked(n.mint_address, n.collection_id, n.creator_address)",
+            );
+        }
+
         if let Some(collection_id) = query.collection_id {
             query_builder.push(" AND n.collection_id = ");
             query_builder.push_bind(collection_id);
@@ -300,7 +667,14 @@ impl Nft {
             query_builder.push_bind(max_price);
         }
 
-        let count: (i64,) = query_builder.build_query_as().fetch_one(pool).await?;
+        push_attribute_filters(&mut query_builder, attribute_filters);
+
+        let count: (i64,) = crate::database::instrument(
+            "nfts.count",
+            || query.filter_summary(),
+            query_builder.build_query_as().fetch_one(pool),
+        )
+        .await?;
 
         Ok(count.0)
     }
@@ -0,0 +1,499 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::{error::AppError, services::price_oracle::NATIVE_SOL_MINT};
+
+pub use solmint_client::{NotificationListQuery, WatchlistRequest};
+
+pub const CATEGORY_OFFER_RECEIVED: &str = "offer_received";
+pub const CATEGORY_ITEM_SOLD: &str = "item_sold";
+pub const CATEGORY_FLOOR_ALERT: &str = "floor_alert";
+pub const CATEGORIES: &[&str] = &[
+    CATEGORY_OFFER_RECEIVED,
+    CATEGORY_ITEM_SOLD,
+    CATEGORY_FLOOR_ALERT,
+];
+
+pub const CHANNEL_EMAIL: &str = "email";
+pub const CHANNEL_PUSH: &str = "push";
+pub const CHANNELS: &[&str] = &[CHANNEL_EMAIL, CHANNEL_PUSH];
+
+pub const MODES: &[&str] = &["instant", "digest", "off"];
+
+/// Join target giving the effective mode for `(category, channel)` when a
+/// wallet hasn't saved a preference
+pub const DEFAULT_MODES_SQL: &str = r#"
+    (VALUES ('offer_received', 'email', 'instant'),
+            ('item_sold', 'email', 'instant'),
+            ('floor_alert', 'email', 'digest'),
+            ('offer_received', 'push', 'instant'),
+            ('item_sold', 'push', 'instant'),
+            ('floor_alert', 'push', 'instant')) AS defaults(category, channel, mode)
+"#;
+
+/// Floor alerts for the same watchlist entry are at most this frequent
+const FLOOR_ALERT_COOLDOWN_HOURS: i32 = 24;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Notification {
+    pub id: Uuid,
+    pub wallet_address: String,
+    pub category: String,
+    pub title: String,
+    pub body: String,
+    pub data: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+/// A notification due for email, with the verified address to send it to
+#[derive(Debug, Clone, FromRow)]
+pub struct PendingEmail {
+    #[sqlx(flatten)]
+    pub notification: Notification,
+    pub email: String,
+}
+
+/// A notification due for push. Notifications sharing a `collapse_key`
+/// (same wallet, category and collection) are sent as one message.
+#[derive(Debug, Clone, FromRow)]
+pub struct PendingPush {
+    #[sqlx(flatten)]
+    pub notification: Notification,
+    pub collapse_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NotificationPreference {
+    pub category: String,
+    pub channel: String,
+    pub mode: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NotificationPreferencesRequest {
+    pub preferences: Vec<NotificationPreference>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WatchlistEntry {
+    pub collection_id: Uuid,
+    pub collection_name: String,
+    pub alert_floor_below: Option<i64>,
+    pub floor_price: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Notification {
+    /// Record a notification unless one with the same `dedupe_key` exists.
+    /// Returns whether it was created.
+    pub async fn notify(
+        pool: &PgPool,
+        wallet_address: &str,
+        category: &str,
+        title: &str,
+        body: &str,
+        data: serde_json::Value,
+        dedupe_key: &str,
+    ) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO notifications (wallet_address, category, title, body, data, dedupe_key)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (wallet_address, dedupe_key) DO NOTHING
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(category)
+        .bind(title)
+        .bind(body)
+        .bind(data)
+        .bind(dedupe_key)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Newest first; `before` is the `(created_at, id)` of the last
+    /// notification of the previous page
+    pub async fn list_for_wallet(
+        pool: &PgPool,
+        wallet_address: &str,
+        unread_only: bool,
+        before: Option<(DateTime<Utc>, Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<Self>, AppError> {
+        let (before_time, before_id) = before.unzip();
+        let notifications = sqlx::query_as::<_, Notification>(
+            r#"
+            SELECT id, wallet_address, category, title, body, data, created_at, read_at
+            FROM notifications
+            WHERE wallet_address = $1 AND (NOT $2 OR read_at IS NULL)
+              AND ($3::timestamptz IS NULL OR (created_at, id) < ($3, $4))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $5
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(unread_only)
+        .bind(before_time)
+        .bind(before_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(notifications)
+    }
+
+    pub async fn mark_all_read(pool: &PgPool, wallet_address: &str) -> Result<u64, AppError> {
+        let result = sqlx::query(
+            "UPDATE notifications SET read_at = NOW() WHERE wallet_address = $1 AND read_at IS NULL",
+        )
+        .bind(wallet_address)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Unsent notifications from the last `within_hours` whose email mode is
+    /// `mode`, for wallets with a verified address, grouped by wallet
+    pub async fn pending_email(
+        pool: &PgPool,
+        mode: &str,
+        within_hours: i32,
+    ) -> Result<Vec<PendingEmail>, AppError> {
+        let pending = sqlx::query_as::<_, PendingEmail>(&format!(
+            r#"
+            SELECT n.id, n.wallet_address, n.category, n.title, n.body, n.data,
+                   n.created_at, n.read_at, u.email
+            FROM notifications n
+            JOIN users u ON u.wallet_address = n.wallet_address
+            JOIN {} ON defaults.category = n.category AND defaults.channel = $1
+            LEFT JOIN notification_preferences p
+                ON p.wallet_address = n.wallet_address
+               AND p.category = n.category
+               AND p.channel = $1
+            WHERE n.emailed_at IS NULL
+              AND n.created_at >= NOW() - make_interval(hours => $3)
+              AND u.email IS NOT NULL
+              AND u.email_verified_at IS NOT NULL
+              AND COALESCE(p.mode, defaults.mode) = $2
+            ORDER BY n.wallet_address, n.created_at
+            "#,
+            DEFAULT_MODES_SQL
+        ))
+        .bind(CHANNEL_EMAIL)
+        .bind(mode)
+        .bind(within_hours)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(pending)
+    }
+
+    pub async fn mark_emailed(pool: &PgPool, ids: &[Uuid]) -> Result<(), AppError> {
+        sqlx::query("UPDATE notifications SET emailed_at = NOW() WHERE id = ANY($1)")
+            .bind(ids)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Unpushed notifications from the last `within_hours` with push set to
+    /// `instant`, for wallets with at least one active device
+    pub async fn pending_push(
+        pool: &PgPool,
+        within_hours: i32,
+    ) -> Result<Vec<PendingPush>, AppError> {
+        let pending = sqlx::query_as::<_, PendingPush>(&format!(
+            r#"
+            SELECT n.id, n.wallet_address, n.category, n.title, n.body, n.data,
+                   n.created_at, n.read_at,
+                   n.category || ':' || COALESCE(n.data->>'collection_id', n.id::text) AS collapse_key
+            FROM notifications n
+            JOIN {} ON defaults.category = n.category AND defaults.channel = $1
+            LEFT JOIN notification_preferences p
+                ON p.wallet_address = n.wallet_address
+               AND p.category = n.category
+               AND p.channel = $1
+            WHERE n.pushed_at IS NULL
+              AND n.created_at >= NOW() - make_interval(hours => $2)
+              AND COALESCE(p.mode, defaults.mode) = 'instant'
+              AND EXISTS (
+                  SELECT 1 FROM push_devices d
+                  WHERE d.wallet_address = n.wallet_address AND d.disabled_at IS NULL
+              )
+            ORDER BY n.wallet_address, n.created_at
+            "#,
+            DEFAULT_MODES_SQL
+        ))
+        .bind(CHANNEL_PUSH)
+        .bind(within_hours)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(pending)
+    }
+
+    pub async fn mark_pushed(pool: &PgPool, ids: &[Uuid]) -> Result<(), AppError> {
+        sqlx::query("UPDATE notifications SET pushed_at = NOW() WHERE id = ANY($1)")
+            .bind(ids)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Notify sellers with a profile or a push device of sales indexed in the last day
+    pub async fn generate_item_sold(pool: &PgPool) -> Result<u64, AppError> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO notifications (wallet_address, category, title, body, data, dedupe_key)
+            SELECT s.seller_address, $1,
+                   COALESCE(n.name, s.nft_mint) || ' sold',
+                   COALESCE(n.name, s.nft_mint) || ' sold for '
+                       || TO_CHAR(s.base_price / 1e9, 'FM999999990.0999') || ' SOL',
+                   jsonb_build_object(
+                       'nft_mint', s.nft_mint,
+                       'collection_id', n.collection_id,
+                       'buyer_address', s.buyer_address,
+                       'price', s.price,
+                       'currency_mint', s.currency_mint,
+                       'base_price', s.base_price,
+                       'transaction_signature', s.transaction_signature
+                   ),
+                   'sale:' || s.transaction_signature
+            FROM sales s
+            LEFT JOIN nfts n ON n.mint_address = s.nft_mint
+            WHERE s.created_at >= NOW() - INTERVAL '1 day'
+              AND (EXISTS (SELECT 1 FROM users u WHERE u.wallet_address = s.seller_address)
+                   OR EXISTS (SELECT 1 FROM push_devices d
+                              WHERE d.wallet_address = s.seller_address AND d.disabled_at IS NULL))
+            ON CONFLICT (wallet_address, dedupe_key) DO NOTHING
+            "#,
+        )
+        .bind(CATEGORY_ITEM_SOLD)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Alert watchers whose floor threshold has been reached
+    pub async fn generate_floor_alerts(pool: &PgPool) -> Result<u64, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let alerted: Vec<(String, Uuid)> = sqlx::query_as(
+            r#"
+            WITH floors AS (
+                SELECT n.collection_id, MIN(l.price) AS floor_price
+                FROM listings l
+                JOIN nfts n ON n.mint_address = l.nft_mint
+                WHERE l.status = 'active' AND l.deleted_at IS NULL AND l.currency_mint = $1
+                GROUP BY n.collection_id
+            ),
+            due AS (
+                SELECT w.wallet_address, w.collection_id, c.name, f.floor_price,
+                       w.alert_floor_below
+                FROM collection_watchlist w
+                JOIN collections c ON c.id = w.collection_id
+                JOIN floors f ON f.collection_id = w.collection_id
+                WHERE w.alert_floor_below IS NOT NULL
+                  AND f.floor_price <= w.alert_floor_below
+                  AND (w.last_alerted_at IS NULL
+                       OR w.last_alerted_at < NOW() - make_interval(hours => $3))
+            ),
+            inserted AS (
+                INSERT INTO notifications (wallet_address, category, title, body, data, dedupe_key)
+                SELECT wallet_address, $2,
+                       name || ' floor is ' || TO_CHAR(floor_price / 1e9, 'FM999999990.0999') || ' SOL',
+                       name || ' floor dropped to '
+                           || TO_CHAR(floor_price / 1e9, 'FM999999990.0999')
+                           || ' SOL, at or below your alert of '
+                           || TO_CHAR(alert_floor_below / 1e9, 'FM999999990.0999') || ' SOL',
+                       jsonb_build_object(
+                           'collection_id', collection_id,
+                           'floor_price', floor_price,
+                           'alert_floor_below', alert_floor_below
+                       ),
+                       'floor:' || collection_id || ':' || EXTRACT(EPOCH FROM NOW())::bigint
+                FROM due
+                ON CONFLICT (wallet_address, dedupe_key) DO NOTHING
+                RETURNING wallet_address, (data->>'collection_id')::uuid AS collection_id
+            )
+            SELECT wallet_address, collection_id FROM inserted
+            "#,
+        )
+        .bind(NATIVE_SOL_MINT)
+        .bind(CATEGORY_FLOOR_ALERT)
+        .bind(FLOOR_ALERT_COOLDOWN_HOURS)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for (wallet_address, collection_id) in &alerted {
+            sqlx::query(
+                "UPDATE collection_watchlist SET last_alerted_at = NOW() WHERE wallet_address = $1 AND collection_id = $2",
+            )
+            .bind(wallet_address)
+            .bind(collection_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(alerted.len() as u64)
+    }
+}
+
+impl NotificationPreference {
+    /// Effective preferences for every category and channel, defaults included
+    pub async fn for_wallet(pool: &PgPool, wallet_address: &str) -> Result<Vec<Self>, AppError> {
+        let preferences = sqlx::query_as::<_, NotificationPreference>(&format!(
+            r#"
+            SELECT defaults.category, defaults.channel, COALESCE(p.mode, defaults.mode) AS mode
+            FROM {}
+            LEFT JOIN notification_preferences p
+                ON p.wallet_address = $1
+               AND p.category = defaults.category
+               AND p.channel = defaults.channel
+            ORDER BY defaults.channel, defaults.category
+            "#,
+            DEFAULT_MODES_SQL
+        ))
+        .bind(wallet_address)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(preferences)
+    }
+
+    pub async fn save(
+        pool: &PgPool,
+        wallet_address: &str,
+        preferences: &[Self],
+    ) -> Result<(), AppError> {
+        for preference in preferences {
+            if !CATEGORIES.contains(&preference.category.as_str())
+                || !CHANNELS.contains(&preference.channel.as_str())
+                || !MODES.contains(&preference.mode.as_str())
+            {
+                return Err(AppError::BadRequest(format!(
+                    "invalid preference {}/{}/{}; categories: {}, channels: {}, modes: {}",
+                    preference.category,
+                    preference.channel,
+                    preference.mode,
+                    CATEGORIES.join(", "),
+                    CHANNELS.join(", "),
+                    MODES.join(", ")
+                )));
+            }
+        }
+
+        if preferences
+            .iter()
+            .any(|preference| preference.channel == CHANNEL_PUSH && preference.mode == "digest")
+        {
+            return Err(crate::error::bad_request_error(
+                "push notifications support instant or off",
+            ));
+        }
+
+        let mut tx = pool.begin().await?;
+        for preference in preferences {
+            sqlx::query(
+                r#"
+                INSERT INTO notification_preferences (wallet_address, category, channel, mode)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (wallet_address, category, channel) DO UPDATE SET
+                    mode = EXCLUDED.mode, updated_at = NOW()
+                "#,
+            )
+            .bind(wallet_address)
+            .bind(&preference.category)
+            .bind(&preference.channel)
+            .bind(&preference.mode)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+impl WatchlistEntry {
+    pub async fn list(pool: &PgPool, wallet_address: &str) -> Result<Vec<Self>, AppError> {
+        let entries = sqlx::query_as::<_, WatchlistEntry>(
+            r#"
+            SELECT w.collection_id, c.name AS collection_name, w.alert_floor_below,
+                   (SELECT MIN(l.price) FROM listings l
+                    JOIN nfts n ON n.mint_address = l.nft_mint
+                    WHERE n.collection_id = w.collection_id
+                      AND l.status = 'active' AND l.deleted_at IS NULL
+                      AND l.currency_mint = $2) AS floor_price,
+                   w.created_at
+            FROM collection_watchlist w
+            JOIN collections c ON c.id = w.collection_id
+            WHERE w.wallet_address = $1
+            ORDER BY w.created_at DESC
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(NATIVE_SOL_MINT)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Add a collection or update its alert threshold
+    pub async fn upsert(
+        pool: &PgPool,
+        wallet_address: &str,
+        req: &WatchlistRequest,
+    ) -> Result<(), AppError> {
+        if req.alert_floor_below.is_some_and(|price| price <= 0) {
+            return Err(crate::error::bad_request_error(
+                "alert_floor_below must be positive",
+            ));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO collection_watchlist (wallet_address, collection_id, alert_floor_below)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (wallet_address, collection_id) DO UPDATE SET
+                alert_floor_below = EXCLUDED.alert_floor_below,
+                last_alerted_at = NULL
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(req.collection_id)
+        .bind(req.alert_floor_below)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove(
+        pool: &PgPool,
+        wallet_address: &str,
+        collection_id: Uuid,
+    ) -> Result<bool, AppError> {
+        let result = sqlx::query(
+            "DELETE FROM collection_watchlist WHERE wallet_address = $1 AND collection_id = $2",
+        )
+        .bind(wallet_address)
+        .bind(collection_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,315 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+pub use solmint_client::{AcceptOfferRequest, ComputeBudgetOverride, MakeOfferRequest, OfferQuery};
+
+/// The make offer transaction was built but hasn't been seen on chain
+pub const OFFER_STATUS_PENDING: &str = "pending";
+pub const OFFER_STATUS_ACTIVE: &str = "active";
+pub const OFFER_STATUS_ACCEPTED: &str = "accepted";
+pub const OFFER_STATUS_CANCELLED: &str = "cancelled";
+
+/// An offer on a single NFT or on any NFT of a collection, keyed by its
+/// offer account
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Offer {
+    pub id: Uuid,
+    pub offer_address: String,
+    /// Set for offers on a single NFT
+    pub nft_mint: Option<String>,
+    /// Set for collection offers
+    pub collection_id: Option<Uuid>,
+    pub bidder_address: String,
+    /// Unknown for offers first seen on chain
+    pub marketplace_address: Option<String>,
+    /// Lamports
+    pub amount: i64,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub status: String,
+    pub seller_address: Option<String>,
+    /// The NFT sold into an accepted collection offer
+    pub filled_mint: Option<String>,
+    pub transaction_signature: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// An offer seen on chain by the indexer
+#[derive(Debug, Clone)]
+pub struct IndexedOffer {
+    pub offer_address: String,
+    pub nft_mint: Option<String>,
+    pub collection_id: Option<Uuid>,
+    pub bidder_address: String,
+    pub amount: i64,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub transaction_signature: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewOffer {
+    pub offer_address: String,
+    pub nft_mint: String,
+    pub bidder_address: String,
+    pub marketplace_address: String,
+    pub amount: i64,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+const OFFER_COLUMNS: &str = r#"
+    id, offer_address, nft_mint, collection_id, bidder_address, marketplace_address, amount,
+    expires_at, status, seller_address, filled_mint, transaction_signature, created_at,
+    updated_at
+"#;
+
+impl Offer {
+    /// Record an offer whose make offer transaction was just built. A bidder
+    /// re-offering on the same NFT gets the row of their previous offer back,
+    /// reset to `pending` with the new terms.
+    pub async fn create_pending(pool: &PgPool, offer: &NewOffer) -> Result<Self, AppError> {
+        let offer = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            INSERT INTO offers (
+                offer_address, nft_mint, bidder_address, marketplace_address, amount, expires_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (offer_address) DO UPDATE SET
+                marketplace_address = EXCLUDED.marketplace_address,
+                amount = EXCLUDED.amount,
+                expires_at = EXCLUDED.expires_at,
+                status = '{}',
+                seller_address = NULL,
+                filled_mint = NULL,
+                transaction_signature = NULL,
+                updated_at = NOW()
+            RETURNING {}
+            "#,
+            OFFER_STATUS_PENDING, OFFER_COLUMNS
+        ))
+        .bind(&offer.offer_address)
+        .bind(&offer.nft_mint)
+        .bind(&offer.bidder_address)
+        .bind(&offer.marketplace_address)
+        .bind(offer.amount)
+        .bind(offer.expires_at)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(offer)
+    }
+
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, AppError> {
+        let offer = sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM offers WHERE id = $1",
+            OFFER_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(offer)
+    }
+
+    /// Mark an offer active once its make offer transaction lands, recording
+    /// offers made outside the API too
+    pub async fn record_made(pool: &PgPool, offer: &IndexedOffer) -> Result<Self, AppError> {
+        let offer = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            INSERT INTO offers (
+                offer_address, nft_mint, collection_id, bidder_address, amount, expires_at,
+                status, transaction_signature
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, '{}', $7)
+            ON CONFLICT (offer_address) DO UPDATE SET
+                amount = EXCLUDED.amount,
+                expires_at = EXCLUDED.expires_at,
+                status = EXCLUDED.status,
+                seller_address = NULL,
+                filled_mint = NULL,
+                transaction_signature = EXCLUDED.transaction_signature,
+                updated_at = NOW()
+            RETURNING {}
+            "#,
+            OFFER_STATUS_ACTIVE, OFFER_COLUMNS
+        ))
+        .bind(&offer.offer_address)
+        .bind(&offer.nft_mint)
+        .bind(offer.collection_id)
+        .bind(&offer.bidder_address)
+        .bind(offer.amount)
+        .bind(offer.expires_at)
+        .bind(&offer.transaction_signature)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(offer)
+    }
+
+    /// Mark the bidder's open offer accepted. Single NFT offers are found by
+    /// mint, collection offers by collection, as the accept events don't
+    /// carry the offer account.
+    pub async fn mark_accepted(
+        pool: &PgPool,
+        target: OfferTarget<'_>,
+        bidder_address: &str,
+        seller_address: &str,
+        filled_mint: &str,
+        transaction_signature: &str,
+    ) -> Result<Option<Self>, AppError> {
+        let (nft_mint, collection_id) = match target {
+            OfferTarget::Nft(mint) => (Some(mint), None),
+            OfferTarget::Collection(collection_id) => (None, Some(collection_id)),
+        };
+        let offer = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            UPDATE offers SET
+                status = '{}',
+                seller_address = $4,
+                filled_mint = $5,
+                transaction_signature = $6,
+                updated_at = NOW()
+            WHERE id = (
+                SELECT id FROM offers
+                WHERE (nft_mint = $1 OR collection_id = $2)
+                  AND bidder_address = $3
+                  AND status IN ('{}', '{}')
+                ORDER BY updated_at DESC
+                LIMIT 1
+            )
+            RETURNING {}
+            "#,
+            OFFER_STATUS_ACCEPTED, OFFER_STATUS_ACTIVE, OFFER_STATUS_PENDING, OFFER_COLUMNS
+        ))
+        .bind(nft_mint)
+        .bind(collection_id)
+        .bind(bidder_address)
+        .bind(seller_address)
+        .bind(filled_mint)
+        .bind(transaction_signature)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(offer)
+    }
+
+    pub async fn mark_cancelled(
+        pool: &PgPool,
+        offer_address: &str,
+        transaction_signature: &str,
+    ) -> Result<Option<Self>, AppError> {
+        let offer = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            UPDATE offers SET status = '{}', transaction_signature = $2, updated_at = NOW()
+            WHERE offer_address = $1
+            RETURNING {}
+            "#,
+            OFFER_STATUS_CANCELLED, OFFER_COLUMNS
+        ))
+        .bind(offer_address)
+        .bind(transaction_signature)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(offer)
+    }
+
+    /// Offers on an NFT, directly or through its collection, highest first.
+    /// Without a status, the offers that can be accepted now.
+    pub async fn list_for_nft(
+        pool: &PgPool,
+        mint: &str,
+        collection_id: Option<Uuid>,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, AppError> {
+        let offers = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            SELECT {} FROM offers
+            WHERE (nft_mint = $1 OR collection_id = $2)
+              AND {}
+            ORDER BY amount DESC, created_at, id
+            LIMIT $4 OFFSET $5
+            "#,
+            OFFER_COLUMNS,
+            status_filter(3)
+        ))
+        .bind(mint)
+        .bind(collection_id)
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(offers)
+    }
+
+    /// Offers a wallet made, or received: offers on NFTs it holds or on
+    /// their collections, and offers it accepted. Newest first.
+    pub async fn list_for_wallet(
+        pool: &PgPool,
+        wallet_address: &str,
+        received: bool,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, AppError> {
+        let wallet_filter = if received {
+            r#"(
+                nft_mint IN (SELECT mint_address FROM nfts WHERE current_owner = $1)
+                OR collection_id IN (
+                    SELECT collection_id FROM nfts
+                    WHERE current_owner = $1 AND collection_id IS NOT NULL
+                )
+                OR seller_address = $1
+            )"#
+        } else {
+            "bidder_address = $1"
+        };
+        let offers = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            SELECT {} FROM offers
+            WHERE {} AND {}
+            ORDER BY created_at DESC, id
+            LIMIT $3 OFFSET $4
+            "#,
+            OFFER_COLUMNS,
+            wallet_filter,
+            status_filter(2)
+        ))
+        .bind(wallet_address)
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(offers)
+    }
+
+    /// Whether the offer may still be on chain, to be accepted or cancelled
+    pub fn is_open(&self) -> bool {
+        self.status == OFFER_STATUS_PENDING || self.status == OFFER_STATUS_ACTIVE
+    }
+}
+
+/// What an offer is for
+#[derive(Debug, Clone, Copy)]
+pub enum OfferTarget<'a> {
+    Nft(&'a str),
+    Collection(Uuid),
+}
+
+/// `status = $param`, or when `$param` is NULL the offers that can be
+/// accepted now: active and unexpired
+fn status_filter(param: usize) -> String {
+    format!(
+        "((${0}::text IS NULL AND status = '{1}' AND (expires_at IS NULL OR expires_at > NOW())) OR status = ${0})",
+        param, OFFER_STATUS_ACTIVE
+    )
+}
@@ -0,0 +1,123 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+
+use crate::error::AppError;
+
+pub const AGGREGATE_NFT: &str = "nft";
+pub const AGGREGATE_LISTING: &str = "listing";
+pub const AGGREGATE_SALE: &str = "sale";
+
+/// Published events are kept this long for debugging, then pruned
+const PUBLISHED_RETENTION_DAYS: i64 = 7;
+/// Retry backoff doubles per attempt up to this cap
+const MAX_BACKOFF_SECONDS: i64 = 3600;
+
+/// A domain event waiting in (or delivered from) the `outbox` table
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct OutboxEvent {
+    pub id: i64,
+    pub aggregate_type: String,
+    pub aggregate_id: String,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub attempts: i32,
+    #[serde(skip)]
+    pub next_attempt_at: DateTime<Utc>,
+}
+
+const OUTBOX_COLUMNS: &str = r#"
+    id, aggregate_type, aggregate_id, event_type, payload, created_at, attempts, next_attempt_at
+"#;
+
+impl OutboxEvent {
+    /// Record an event as part of `tx`, so it's published if and only if the
+    /// domain write commits
+    pub async fn enqueue<T: Serialize>(
+        tx: &mut Transaction<'_, Postgres>,
+        aggregate_type: &str,
+        aggregate_id: &str,
+        event_type: &str,
+        payload: &T,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO outbox (aggregate_type, aggregate_id, event_type, payload)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(aggregate_type)
+        .bind(aggregate_id)
+        .bind(event_type)
+        .bind(serde_json::to_value(payload)?)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Oldest unpublished events, including ones still backing off so the
+    /// relay can hold back later events for the same aggregate
+    pub async fn pending(
+        tx: &mut Transaction<'_, Postgres>,
+        limit: i64,
+    ) -> Result<Vec<Self>, AppError> {
+        let events = sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM outbox WHERE published_at IS NULL ORDER BY id LIMIT $1",
+            OUTBOX_COLUMNS
+        ))
+        .bind(limit)
+        .fetch_all(&mut **tx)
+        .await?;
+
+        Ok(events)
+    }
+
+    pub async fn mark_published(&self, tx: &mut Transaction<'_, Postgres>) -> Result<(), AppError> {
+        sqlx::query(
+            "UPDATE outbox SET published_at = NOW(), attempts = attempts + 1 WHERE id = $1",
+        )
+        .bind(self.id)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Schedule another attempt with exponential backoff
+    pub async fn mark_failed(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        error: &str,
+    ) -> Result<(), AppError> {
+        let backoff = 2_i64
+            .saturating_pow(self.attempts.clamp(0, 12) as u32)
+            .min(MAX_BACKOFF_SECONDS);
+
+        sqlx::query(
+            r#"
+            UPDATE outbox
+            SET attempts = attempts + 1, last_error = $2, next_attempt_at = $3
+            WHERE id = $1
+            "#,
+        )
+        .bind(self.id)
+        .bind(error)
+        .bind(Utc::now() + Duration::seconds(backoff))
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn prune_published(pool: &PgPool) -> Result<u64, AppError> {
+        let result = sqlx::query("DELETE FROM outbox WHERE published_at < $1")
+            .bind(Utc::now() - Duration::days(PUBLISHED_RETENTION_DAYS))
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
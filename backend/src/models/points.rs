@@ -0,0 +1,491 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+pub use solmint_client::{
+    CollectionMultiplierRequest, PointsAdjustmentRequest, PointsQuery, PointsSeasonRequest,
+};
+
+/// Accrues trade and listing points for season `$1` that aren't in the ledger yet.
+///
+/// Trades earn `points_per_sol * volume`, scaled by the collection multiplier
+/// and a streak bonus for each consecutive UTC day (up to `max_streak_days`)
+/// the wallet has traded. Listings earn `listing_points` once per NFT, seller
+/// and day so relisting can't be farmed.
+const ACCRUE_SQL: &str = r#"
+    WITH season AS (
+        SELECT * FROM points_seasons WHERE id = $1
+    ),
+    trades AS (
+        SELECT s.transaction_signature AS reference, s.buyer_address AS wallet_address,
+               'buy' AS source, s.base_price, s.block_time, n.collection_id
+        FROM sales s
+        JOIN season ON s.block_time >= season.starts_at AND s.block_time < season.ends_at
+        LEFT JOIN nfts n ON n.mint_address = s.nft_mint
+        UNION ALL
+        SELECT s.transaction_signature, s.seller_address, 'sell', s.base_price, s.block_time,
+               n.collection_id
+        FROM sales s
+        JOIN season ON s.block_time >= season.starts_at AND s.block_time < season.ends_at
+        LEFT JOIN nfts n ON n.mint_address = s.nft_mint
+    ),
+    trade_days AS (
+        SELECT DISTINCT wallet_address, (block_time AT TIME ZONE 'UTC')::date AS day FROM trades
+    ),
+    streaks AS (
+        SELECT wallet_address, day,
+               ROW_NUMBER() OVER (PARTITION BY wallet_address, island ORDER BY day) AS streak_day
+        FROM (
+            SELECT wallet_address, day,
+                   day - (ROW_NUMBER() OVER (PARTITION BY wallet_address ORDER BY day))::int
+                       AS island
+            FROM trade_days
+        ) numbered
+    ),
+    trade_points AS (
+        SELECT t.wallet_address, t.source, t.reference,
+               t.base_price / 1e9
+                   * CASE t.source WHEN 'buy' THEN season.buy_points_per_sol
+                                   ELSE season.sell_points_per_sol END
+                   * COALESCE(m.multiplier, 1)
+                   * (1 + LEAST(st.streak_day - 1, season.max_streak_days) * season.streak_bonus)
+                   AS points,
+               jsonb_build_object(
+                   'volume_lamports', t.base_price,
+                   'collection_id', t.collection_id,
+                   'collection_multiplier', COALESCE(m.multiplier, 1),
+                   'streak_day', st.streak_day
+               ) AS details
+        FROM trades t
+        CROSS JOIN season
+        JOIN streaks st
+            ON st.wallet_address = t.wallet_address
+           AND st.day = (t.block_time AT TIME ZONE 'UTC')::date
+        LEFT JOIN points_collection_multipliers m
+            ON m.season_id = season.id AND m.collection_id = t.collection_id
+    ),
+    listing_points AS (
+        SELECT DISTINCT ON (l.seller_address, l.nft_mint, listed_on)
+               l.seller_address AS wallet_address, 'list' AS source,
+               l.nft_mint || ':' || listed_on AS reference,
+               season.listing_points * COALESCE(m.multiplier, 1) AS points,
+               jsonb_build_object(
+                   'listing_id', l.id,
+                   'collection_id', n.collection_id,
+                   'collection_multiplier', COALESCE(m.multiplier, 1)
+               ) AS details
+        FROM listings l
+        CROSS JOIN season
+        CROSS JOIN LATERAL (
+            SELECT (COALESCE(l.block_time, l.created_at) AT TIME ZONE 'UTC')::date AS listed_on
+        ) d
+        LEFT JOIN nfts n ON n.mint_address = l.nft_mint
+        LEFT JOIN points_collection_multipliers m
+            ON m.season_id = season.id AND m.collection_id = n.collection_id
+        WHERE COALESCE(l.block_time, l.created_at) >= season.starts_at
+          AND COALESCE(l.block_time, l.created_at) < season.ends_at
+        ORDER BY l.seller_address, l.nft_mint, listed_on, l.created_at
+    )
+    INSERT INTO points_ledger (season_id, wallet_address, source, reference, points, details)
+    SELECT $1, wallet_address, source, reference, points, details
+    FROM (SELECT * FROM trade_points UNION ALL SELECT * FROM listing_points) earned
+    WHERE points <> 0
+    ON CONFLICT (season_id, wallet_address, source, reference) DO NOTHING
+"#;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PointsSeason {
+    pub id: Uuid,
+    pub name: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub buy_points_per_sol: f64,
+    pub sell_points_per_sol: f64,
+    pub listing_points: f64,
+    pub streak_bonus: f64,
+    pub max_streak_days: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct PointsEntry {
+    pub source: String,
+    pub reference: String,
+    pub points: f64,
+    pub details: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct PointsBySource {
+    pub source: String,
+    pub points: f64,
+    pub entries: i64,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct LeaderboardEntry {
+    pub rank: i64,
+    pub wallet_address: String,
+    pub points: f64,
+}
+
+impl PointsSeason {
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, AppError> {
+        let season =
+            sqlx::query_as::<_, PointsSeason>("SELECT * FROM points_seasons WHERE id = $1")
+                .bind(id)
+                .fetch_optional(pool)
+                .await?;
+
+        Ok(season)
+    }
+
+    /// The season running now, or the most recently started one
+    pub async fn current(pool: &PgPool) -> Result<Option<Self>, AppError> {
+        let season = sqlx::query_as::<_, PointsSeason>(
+            r#"
+            SELECT * FROM points_seasons
+            WHERE starts_at <= NOW()
+            ORDER BY (ends_at > NOW()) DESC, starts_at DESC
+            LIMIT 1
+            "#,
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(season)
+    }
+
+    /// `season` if given, otherwise the current season
+    pub async fn resolve(pool: &PgPool, season: Option<Uuid>) -> Result<Self, AppError> {
+        let season = match season {
+            Some(id) => Self::find_by_id(pool, id).await?,
+            None => Self::current(pool).await?,
+        };
+
+        season.ok_or_else(|| crate::error::not_found_error("Points season"))
+    }
+
+    pub async fn list(pool: &PgPool) -> Result<Vec<Self>, AppError> {
+        let seasons = sqlx::query_as::<_, PointsSeason>(
+            "SELECT * FROM points_seasons ORDER BY starts_at DESC",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(seasons)
+    }
+
+    pub async fn create(pool: &PgPool, req: PointsSeasonRequest) -> Result<Self, AppError> {
+        let (Some(name), Some(starts_at), Some(ends_at)) = (req.name, req.starts_at, req.ends_at)
+        else {
+            return Err(crate::error::bad_request_error(
+                "name, starts_at and ends_at are required",
+            ));
+        };
+
+        let mut tx = pool.begin().await?;
+
+        let season = sqlx::query_as::<_, PointsSeason>(
+            r#"
+            INSERT INTO points_seasons (
+                name, starts_at, ends_at, buy_points_per_sol, sell_points_per_sol,
+                listing_points, streak_bonus, max_streak_days
+            )
+            VALUES ($1, $2, $3, COALESCE($4, 10), COALESCE($5, 10), COALESCE($6, 1),
+                    COALESCE($7, 0.05), COALESCE($8, 7))
+            RETURNING *
+            "#,
+        )
+        .bind(name)
+        .bind(starts_at)
+        .bind(ends_at)
+        .bind(req.buy_points_per_sol)
+        .bind(req.sell_points_per_sol)
+        .bind(req.listing_points)
+        .bind(req.streak_bonus)
+        .bind(req.max_streak_days)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        season.validate()?;
+        tx.commit().await?;
+        Ok(season)
+    }
+
+    /// Change a season's rules. Points already accrued are kept; use
+    /// `recompute` to re-score the season under the new rules.
+    pub async fn update(
+        pool: &PgPool,
+        id: Uuid,
+        req: PointsSeasonRequest,
+    ) -> Result<Self, AppError> {
+        let mut tx = pool.begin().await?;
+
+        let season = sqlx::query_as::<_, PointsSeason>(
+            r#"
+            UPDATE points_seasons SET
+                name = COALESCE($2, name),
+                starts_at = COALESCE($3, starts_at),
+                ends_at = COALESCE($4, ends_at),
+                buy_points_per_sol = COALESCE($5, buy_points_per_sol),
+                sell_points_per_sol = COALESCE($6, sell_points_per_sol),
+                listing_points = COALESCE($7, listing_points),
+                streak_bonus = COALESCE($8, streak_bonus),
+                max_streak_days = COALESCE($9, max_streak_days),
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(req.name)
+        .bind(req.starts_at)
+        .bind(req.ends_at)
+        .bind(req.buy_points_per_sol)
+        .bind(req.sell_points_per_sol)
+        .bind(req.listing_points)
+        .bind(req.streak_bonus)
+        .bind(req.max_streak_days)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Points season"))?;
+
+        season.validate()?;
+        tx.commit().await?;
+        Ok(season)
+    }
+
+    /// Checked inside the write transaction so invalid rules are rolled back
+    fn validate(&self) -> Result<(), AppError> {
+        if self.ends_at <= self.starts_at {
+            return Err(crate::error::bad_request_error(
+                "ends_at must be after starts_at",
+            ));
+        }
+
+        let rates = [
+            self.buy_points_per_sol,
+            self.sell_points_per_sol,
+            self.listing_points,
+            self.streak_bonus,
+        ];
+        if rates.iter().any(|rate| !rate.is_finite() || *rate < 0.0) || self.max_streak_days < 0 {
+            return Err(crate::error::bad_request_error(
+                "point rates, streak_bonus and max_streak_days must not be negative",
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub async fn set_collection_multiplier(
+        pool: &PgPool,
+        season_id: Uuid,
+        collection_id: Uuid,
+        multiplier: f64,
+    ) -> Result<(), AppError> {
+        if !multiplier.is_finite() || multiplier < 0.0 {
+            return Err(crate::error::bad_request_error(
+                "multiplier must not be negative",
+            ));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO points_collection_multipliers (season_id, collection_id, multiplier)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (season_id, collection_id) DO UPDATE SET multiplier = EXCLUDED.multiplier
+            "#,
+        )
+        .bind(season_id)
+        .bind(collection_id)
+        .bind(multiplier)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Credit (or debit, with negative points) a wallet by hand
+    pub async fn adjust(
+        pool: &PgPool,
+        season_id: Uuid,
+        req: PointsAdjustmentRequest,
+    ) -> Result<PointsEntry, AppError> {
+        if !req.points.is_finite() || req.points == 0.0 {
+            return Err(crate::error::bad_request_error(
+                "points must be a non-zero number",
+            ));
+        }
+
+        let entry = sqlx::query_as::<_, PointsEntry>(
+            r#"
+            INSERT INTO points_ledger (season_id, wallet_address, source, reference, points, details)
+            VALUES ($1, $2, 'adjustment', $3, $4, jsonb_build_object('reason', $5::text))
+            RETURNING source, reference, points, details, created_at
+            "#,
+        )
+        .bind(season_id)
+        .bind(&req.wallet_address)
+        .bind(Uuid::new_v4().to_string())
+        .bind(req.points)
+        .bind(&req.reason)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    /// Accrue points for every season that is running or ended within the last day
+    pub async fn accrue_active(pool: &PgPool) -> Result<u64, AppError> {
+        let seasons: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM points_seasons WHERE starts_at <= NOW() AND ends_at > NOW() - INTERVAL '1 day'",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut accrued = 0;
+        for season_id in seasons {
+            accrued += sqlx::query(ACCRUE_SQL)
+                .bind(season_id)
+                .execute(pool)
+                .await?
+                .rows_affected();
+        }
+
+        Ok(accrued)
+    }
+
+    /// Re-score a season under its current rules, keeping manual adjustments
+    pub async fn recompute(pool: &PgPool, season_id: Uuid) -> Result<u64, AppError> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM points_ledger WHERE season_id = $1 AND source <> 'adjustment'")
+            .bind(season_id)
+            .execute(&mut *tx)
+            .await?;
+        let accrued = sqlx::query(ACCRUE_SQL)
+            .bind(season_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        tx.commit().await?;
+        Ok(accrued)
+    }
+
+    pub async fn wallet_total(
+        pool: &PgPool,
+        season_id: Uuid,
+        wallet: &str,
+    ) -> Result<f64, AppError> {
+        let total: Option<f64> = sqlx::query_scalar(
+            "SELECT SUM(points) FROM points_ledger WHERE season_id = $1 AND wallet_address = $2",
+        )
+        .bind(season_id)
+        .bind(wallet)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(total.unwrap_or_default())
+    }
+
+    /// 1-based position on the leaderboard, `None` without any points
+    pub async fn wallet_rank(
+        pool: &PgPool,
+        season_id: Uuid,
+        wallet: &str,
+    ) -> Result<Option<i64>, AppError> {
+        let rank: Option<i64> = sqlx::query_scalar(
+            r#"
+            WITH totals AS (
+                SELECT wallet_address, SUM(points) AS points
+                FROM points_ledger WHERE season_id = $1
+                GROUP BY wallet_address
+            )
+            SELECT 1 + (SELECT COUNT(*) FROM totals o WHERE o.points > t.points)
+            FROM totals t WHERE t.wallet_address = $2
+            "#,
+        )
+        .bind(season_id)
+        .bind(wallet)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(rank)
+    }
+
+    pub async fn wallet_breakdown(
+        pool: &PgPool,
+        season_id: Uuid,
+        wallet: &str,
+    ) -> Result<Vec<PointsBySource>, AppError> {
+        let breakdown = sqlx::query_as::<_, PointsBySource>(
+            r#"
+            SELECT source, SUM(points) AS points, COUNT(*) AS entries
+            FROM points_ledger WHERE season_id = $1 AND wallet_address = $2
+            GROUP BY source ORDER BY points DESC
+            "#,
+        )
+        .bind(season_id)
+        .bind(wallet)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(breakdown)
+    }
+
+    pub async fn wallet_entries(
+        pool: &PgPool,
+        season_id: Uuid,
+        wallet: &str,
+        limit: i64,
+    ) -> Result<Vec<PointsEntry>, AppError> {
+        let entries = sqlx::query_as::<_, PointsEntry>(
+            r#"
+            SELECT source, reference, points, details, created_at
+            FROM points_ledger WHERE season_id = $1 AND wallet_address = $2
+            ORDER BY created_at DESC, id DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(season_id)
+        .bind(wallet)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    pub async fn leaderboard(
+        pool: &PgPool,
+        season_id: Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<LeaderboardEntry>, AppError> {
+        let leaderboard = sqlx::query_as::<_, LeaderboardEntry>(
+            r#"
+            SELECT RANK() OVER (ORDER BY SUM(points) DESC) AS rank,
+                   wallet_address, SUM(points) AS points
+            FROM points_ledger WHERE season_id = $1
+            GROUP BY wallet_address
+            ORDER BY points DESC, wallet_address
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(season_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(leaderboard)
+    }
+}
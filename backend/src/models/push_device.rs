@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+pub use solmint_client::RegisterDeviceRequest;
+
+pub const PLATFORM_IOS: &str = "ios";
+pub const PLATFORM_ANDROID: &str = "android";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PushDevice {
+    pub id: Uuid,
+    pub wallet_address: String,
+    pub platform: String,
+    #[serde(skip_serializing)]
+    pub token: String,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+}
+
+impl PushDevice {
+    /// Register a token for a wallet. A token that was registered before
+    /// (including to another wallet, e.g. after switching accounts) moves to
+    /// this wallet and is re-enabled.
+    pub async fn register(
+        pool: &PgPool,
+        wallet_address: &str,
+        req: &RegisterDeviceRequest,
+    ) -> Result<Self, AppError> {
+        if req.platform != PLATFORM_IOS && req.platform != PLATFORM_ANDROID {
+            return Err(crate::error::bad_request_error(
+                "platform must be ios or android",
+            ));
+        }
+        let token = req.token.trim();
+        if token.is_empty() || token.len() > 4096 {
+            return Err(crate::error::bad_request_error("invalid device token"));
+        }
+
+        let device = sqlx::query_as::<_, PushDevice>(
+            r#"
+            INSERT INTO push_devices (wallet_address, platform, token)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (token) DO UPDATE SET
+                wallet_address = EXCLUDED.wallet_address,
+                platform = EXCLUDED.platform,
+                last_seen_at = NOW(),
+                disabled_at = NULL
+            RETURNING id, wallet_address, platform, token, created_at, last_seen_at
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(&req.platform)
+        .bind(token)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(device)
+    }
+
+    pub async fn list_for_wallet(
+        pool: &PgPool,
+        wallet_address: &str,
+    ) -> Result<Vec<Self>, AppError> {
+        let devices = sqlx::query_as::<_, PushDevice>(
+            r#"
+            SELECT id, wallet_address, platform, token, created_at, last_seen_at
+            FROM push_devices
+            WHERE wallet_address = $1 AND disabled_at IS NULL
+            ORDER BY last_seen_at DESC
+            "#,
+        )
+        .bind(wallet_address)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(devices)
+    }
+
+    pub async fn remove(pool: &PgPool, wallet_address: &str, id: Uuid) -> Result<bool, AppError> {
+        let result = sqlx::query("DELETE FROM push_devices WHERE id = $1 AND wallet_address = $2")
+            .bind(id)
+            .bind(wallet_address)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Stop sending to a token the provider rejected as unregistered or invalid
+    pub async fn disable(pool: &PgPool, id: Uuid) -> Result<(), AppError> {
+        sqlx::query("UPDATE push_devices SET disabled_at = NOW() WHERE id = $1")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/// One rental period of an NFT, indexed from the program's `NftRented` event
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Rental {
+    pub id: Uuid,
+    pub nft_mint: String,
+    pub owner_address: String,
+    pub renter_address: String,
+    pub days: i32,
+    /// Rent paid upfront for all `days`, in lamports
+    pub price: i64,
+    pub expires_at: DateTime<Utc>,
+    /// When the NFT was reclaimed from the renter
+    pub returned_at: Option<DateTime<Utc>>,
+    pub transaction_signature: String,
+    pub block_time: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRentalRequest {
+    pub nft_mint: String,
+    pub owner_address: String,
+    pub renter_address: String,
+    pub days: i32,
+    pub price: i64,
+    pub expires_at: DateTime<Utc>,
+    pub transaction_signature: String,
+    pub block_time: DateTime<Utc>,
+}
+
+impl Rental {
+    /// Insert a rental; returns `None` when it was already indexed
+    pub async fn create(pool: &PgPool, req: CreateRentalRequest) -> Result<Option<Self>, AppError> {
+        let rental = sqlx::query_as!(
+            Rental,
+            r#"
+            INSERT INTO rentals (
+                nft_mint, owner_address, renter_address, days, price, expires_at,
+                transaction_signature, block_time
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (transaction_signature) DO NOTHING
+            RETURNING id, nft_mint, owner_address, renter_address, days, price, expires_at,
+                      returned_at, transaction_signature, block_time, created_at
+            "#,
+            req.nft_mint,
+            req.owner_address,
+            req.renter_address,
+            req.days,
+            req.price,
+            req.expires_at,
+            req.transaction_signature,
+            req.block_time
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(rental)
+    }
+
+    /// Mark the open rental of `nft_mint` to `renter_address` returned
+    pub async fn mark_returned(
+        pool: &PgPool,
+        nft_mint: &str,
+        renter_address: &str,
+        returned_at: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE rentals
+            SET returned_at = $3
+            WHERE nft_mint = $1 AND renter_address = $2 AND returned_at IS NULL
+            "#,
+            nft_mint,
+            renter_address,
+            returned_at
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+}
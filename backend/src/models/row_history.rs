@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+pub use solmint_client::AdminChangeRequest;
+
+/// Tables with soft deletion and a history trigger (migration 020)
+pub const HISTORY_TABLES: &[&str] = &["nfts", "collections", "listings"];
+
+/// A row's value before (and after) one update or delete, written by the
+/// `record_row_history` trigger
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RowHistory {
+    pub id: i64,
+    pub table_name: String,
+    pub row_id: Uuid,
+    pub operation: String,
+    pub old_data: serde_json::Value,
+    pub new_data: Option<serde_json::Value>,
+    pub actor: Option<String>,
+    pub reason: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+const ROW_HISTORY_COLUMNS: &str = r#"
+    id, table_name, row_id, operation, old_data, new_data, actor, reason, changed_at
+"#;
+
+impl RowHistory {
+    pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<Self>, AppError> {
+        let entry = sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM row_history WHERE id = $1",
+            ROW_HISTORY_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    /// Every recorded change to a row, newest first
+    pub async fn for_row(pool: &PgPool, table: &str, row_id: Uuid) -> Result<Vec<Self>, AppError> {
+        let table = history_table(table)?;
+        let entries = sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM row_history WHERE table_name = $1 AND row_id = $2 ORDER BY id DESC",
+            ROW_HISTORY_COLUMNS
+        ))
+        .bind(table)
+        .bind(row_id)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Hide a row from every model query. Returns false when the row doesn't
+    /// exist or is already deleted.
+    pub async fn soft_delete(
+        pool: &PgPool,
+        table: &str,
+        row_id: Uuid,
+        change: &AdminChangeRequest,
+    ) -> Result<bool, AppError> {
+        let table = history_table(table)?;
+        let mut tx = begin_change(pool, change).await?;
+        let result = sqlx::query(&format!(
+            "UPDATE {} SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+            table
+        ))
+        .bind(row_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Undo a soft delete. Returns false when the row isn't deleted.
+    pub async fn restore(
+        pool: &PgPool,
+        table: &str,
+        row_id: Uuid,
+        change: &AdminChangeRequest,
+    ) -> Result<bool, AppError> {
+        let table = history_table(table)?;
+        let mut tx = begin_change(pool, change).await?;
+        let result = sqlx::query(&format!(
+            "UPDATE {} SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL",
+            table
+        ))
+        .bind(row_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Put the row back the way it was before this entry. The revert is
+    /// recorded as a new entry, so it can be reverted in turn.
+    pub async fn revert(&self, pool: &PgPool, change: &AdminChangeRequest) -> Result<(), AppError> {
+        let mut tx = begin_change(pool, change).await?;
+        sqlx::query("SELECT revert_row_history($1)")
+            .bind(self.id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+fn history_table(table: &str) -> Result<&'static str, AppError> {
+    HISTORY_TABLES
+        .iter()
+        .find(|name| **name == table)
+        .copied()
+        .ok_or_else(|| AppError::BadRequest(format!("unsupported table: {}", table)))
+}
+
+/// Open a transaction whose history entries are attributed to `change`
+pub async fn begin_change<'a>(
+    pool: &'a PgPool,
+    change: &AdminChangeRequest,
+) -> Result<Transaction<'a, Postgres>, AppError> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "SELECT set_config('solmint.actor', $1, true), set_config('solmint.reason', $2, true)",
+    )
+    .bind(change.actor.as_deref().unwrap_or("admin"))
+    .bind(change.reason.as_deref().unwrap_or(""))
+    .execute(&mut *tx)
+    .await?;
+
+    Ok(tx)
+}
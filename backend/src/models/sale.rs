@@ -0,0 +1,268 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{OutboxEvent, AGGREGATE_SALE},
+    services::price_oracle::PriceOracle,
+};
+
+pub use solmint_client::{CandleQuery, RoyaltyReportQuery};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Sale {
+    pub id: Uuid,
+    pub nft_mint: String,
+    pub seller_address: String,
+    pub buyer_address: String,
+    pub price: i64,
+    pub currency_mint: String,
+    /// Sale value in lamports at the time of sale
+    pub base_price: i64,
+    pub marketplace_fee: i64,
+    /// Aggregator or affiliate paid `referral_fee` out of `marketplace_fee`
+    pub referrer_address: Option<String>,
+    pub referral_fee: i64,
+    pub royalty_paid: i64,
+    /// USD price of one SOL when the sale was indexed
+    pub sol_usd_price: Option<f64>,
+    pub transaction_signature: String,
+    pub block_time: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSaleRequest {
+    pub nft_mint: String,
+    pub seller_address: String,
+    pub buyer_address: String,
+    pub price: i64,
+    pub currency_mint: String,
+    pub marketplace_fee: i64,
+    pub referrer_address: Option<String>,
+    /// Part of `marketplace_fee` paid to the referrer
+    pub referral_fee: i64,
+    pub transaction_signature: String,
+    /// Position of the sale's instruction in the transaction; with the
+    /// signature it identifies the sale
+    pub instruction_index: i32,
+    pub block_time: DateTime<Utc>,
+}
+
+/// Royalty paid to one creator as part of a sale
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoyaltyPayment {
+    pub creator_address: String,
+    pub amount: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RoyaltySummary {
+    pub collection_id: Option<Uuid>,
+    pub collection_name: Option<String>,
+    pub period_start: NaiveDate,
+    pub royalties_earned: i64,
+    pub sales_count: i64,
+}
+
+/// Whose sales [`Sale::candles`] aggregates
+#[derive(Debug, Clone, Copy)]
+pub enum CandleScope<'a> {
+    Collection(Uuid),
+    Nft(&'a str),
+}
+
+/// OHLC candle of sale prices in lamports
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Candle {
+    pub bucket_start: DateTime<Utc>,
+    pub open: i64,
+    pub high: i64,
+    pub low: i64,
+    pub close: i64,
+    pub volume: i64,
+    pub sales_count: i64,
+}
+
+impl Sale {
+    /// Insert a sale, valuing it in lamports with the oracle price at sale time.
+    /// The SOL/USD price is recorded when available but doesn't block indexing.
+    /// Returns `None` when the sale was already indexed.
+    pub async fn create(
+        pool: &PgPool,
+        oracle: &PriceOracle,
+        req: CreateSaleRequest,
+    ) -> Result<Option<Self>, AppError> {
+        let base_price = oracle.to_base_amount(&req.currency_mint, req.price).await?;
+        let sol_usd_price = oracle.sol_usd_price().await.ok();
+
+        let mut tx = pool.begin().await?;
+        let sale = sqlx::query_as!(
+            Sale,
+            r#"
+            INSERT INTO sales (
+                nft_mint, seller_address, buyer_address, price, currency_mint, base_price,
+                marketplace_fee, referrer_address, referral_fee, sol_usd_price,
+                transaction_signature, instruction_index, block_time
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ON CONFLICT (transaction_signature, instruction_index) DO NOTHING
+            RETURNING id, nft_mint, seller_address, buyer_address, price, currency_mint,
+                      base_price, marketplace_fee, referrer_address, referral_fee,
+                      royalty_paid, sol_usd_price, transaction_signature, block_time,
+                      created_at as "created_at!"
+            "#,
+            req.nft_mint,
+            req.seller_address,
+            req.buyer_address,
+            req.price,
+            req.currency_mint,
+            base_price,
+            req.marketplace_fee,
+            req.referrer_address,
+            req.referral_fee,
+            sol_usd_price,
+            req.transaction_signature,
+            req.instruction_index,
+            req.block_time
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(sale) = sale else {
+            return Ok(None);
+        };
+
+        OutboxEvent::enqueue(
+            &mut tx,
+            AGGREGATE_SALE,
+            &sale.id.to_string(),
+            "sale.created",
+            &sale,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(Some(sale))
+    }
+
+    /// Store the per-creator royalties parsed from a sale and total them on the sale row
+    pub async fn record_royalties(
+        pool: &PgPool,
+        sale_id: Uuid,
+        payments: &[RoyaltyPayment],
+    ) -> Result<(), AppError> {
+        let mut tx = pool.begin().await?;
+
+        for payment in payments {
+            sqlx::query!(
+                r#"
+                INSERT INTO sale_royalties (sale_id, creator_address, amount)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (sale_id, creator_address) DO UPDATE SET amount = EXCLUDED.amount
+                "#,
+                sale_id,
+                payment.creator_address,
+                payment.amount
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE sales SET royalty_paid = (
+                SELECT COALESCE(SUM(amount), 0) FROM sale_royalties WHERE sale_id = $1
+            )
+            WHERE id = $1
+            "#,
+            sale_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// OHLCV candles for a collection's or an NFT's sales in
+    /// `bucket_seconds` buckets aligned to the Unix epoch. Buckets without
+    /// sales are omitted.
+    pub async fn candles(
+        pool: &PgPool,
+        scope: CandleScope<'_>,
+        bucket_seconds: i64,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Candle>, AppError> {
+        let filter = match scope {
+            CandleScope::Collection(_) => "n.collection_id = $1",
+            CandleScope::Nft(_) => "s.nft_mint = $1",
+        };
+        let sql = format!(
+            r#"
+            SELECT to_timestamp(floor(extract(epoch FROM s.block_time) / $2) * $2) AS bucket_start,
+                   (array_agg(s.base_price ORDER BY s.block_time, s.id))[1] AS open,
+                   MAX(s.base_price) AS high,
+                   MIN(s.base_price) AS low,
+                   (array_agg(s.base_price ORDER BY s.block_time DESC, s.id DESC))[1] AS close,
+                   SUM(s.base_price)::BIGINT AS volume,
+                   COUNT(*) AS sales_count
+            FROM sales s
+            JOIN nfts n ON n.mint_address = s.nft_mint
+            WHERE {} AND s.block_time >= $3 AND s.block_time < $4
+            GROUP BY bucket_start
+            ORDER BY bucket_start
+            "#,
+            filter
+        );
+        let query = sqlx::query_as::<_, Candle>(&sql);
+        let query = match scope {
+            CandleScope::Collection(collection_id) => query.bind(collection_id),
+            CandleScope::Nft(mint) => query.bind(mint),
+        };
+        let candles = query
+            .bind(bucket_seconds as f64)
+            .bind(from)
+            .bind(to)
+            .fetch_all(pool)
+            .await?;
+
+        Ok(candles)
+    }
+
+    /// Royalties earned by `creator`, grouped by collection and period
+    pub async fn royalty_summary(
+        pool: &PgPool,
+        creator: &str,
+        period: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<RoyaltySummary>, AppError> {
+        let summary = sqlx::query_as::<_, RoyaltySummary>(
+            r#"
+            SELECT n.collection_id,
+                   c.name AS collection_name,
+                   date_trunc($2, s.block_time AT TIME ZONE 'UTC')::date AS period_start,
+                   SUM(r.amount)::BIGINT AS royalties_earned,
+                   COUNT(*) AS sales_count
+            FROM sale_royalties r
+            JOIN sales s ON s.id = r.sale_id
+            JOIN nfts n ON n.mint_address = s.nft_mint
+            LEFT JOIN collections c ON c.id = n.collection_id
+            WHERE r.creator_address = $1 AND s.block_time >= $3 AND s.block_time < $4
+            GROUP BY n.collection_id, c.name, period_start
+            ORDER BY period_start DESC, royalties_earned DESC
+            "#,
+        )
+        .bind(creator)
+        .bind(period)
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(summary)
+    }
+}
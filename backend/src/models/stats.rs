@@ -0,0 +1,175 @@
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+
+pub use solmint_client::DailyStatsQuery;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DailyStats {
+    pub date: NaiveDate,
+    pub total_volume: i64,
+    pub total_sales: i64,
+    pub unique_buyers: i64,
+    pub unique_sellers: i64,
+    pub average_price: i64,
+    pub new_listings: i64,
+    /// `None` when the day was aggregated on the fly instead of read from a rollup
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Volume for one payment currency. `volume` is in the currency's own
+/// smallest unit; `base_volume` is lamports valued at sale time.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CurrencyVolume {
+    pub currency_mint: String,
+    pub volume: i64,
+    pub base_volume: i64,
+    pub sales_count: i64,
+}
+
+// Aggregates one UTC day ($1) of sales and listings
+const AGGREGATE_DAY_SQL: &str = r#"
+    SELECT $1::date AS date,
+           COALESCE(SUM(s.base_price), 0)::BIGINT AS total_volume,
+           COUNT(s.id) AS total_sales,
+           COUNT(DISTINCT s.buyer_address) AS unique_buyers,
+           COUNT(DISTINCT s.seller_address) AS unique_sellers,
+           COALESCE(AVG(s.base_price), 0)::BIGINT AS average_price,
+           (SELECT COUNT(*) FROM listings l
+             WHERE l.created_at >= ($1::date)::timestamp AT TIME ZONE 'UTC'
+               AND l.created_at < ($1::date + 1)::timestamp AT TIME ZONE 'UTC') AS new_listings
+    FROM sales s
+    WHERE s.block_time >= ($1::date)::timestamp AT TIME ZONE 'UTC'
+      AND s.block_time < ($1::date + 1)::timestamp AT TIME ZONE 'UTC'
+"#;
+
+pub struct MarketplaceStats;
+
+impl MarketplaceStats {
+    /// Compute the rollup for `date` and store it, replacing any previous row
+    pub async fn create_or_update_daily_stats(
+        pool: &PgPool,
+        date: NaiveDate,
+    ) -> Result<DailyStats, AppError> {
+        let stats = sqlx::query_as::<_, DailyStats>(&format!(
+            r#"
+            INSERT INTO daily_stats (
+                date, total_volume, total_sales, unique_buyers, unique_sellers,
+                average_price, new_listings, updated_at
+            )
+            SELECT date, total_volume, total_sales, unique_buyers, unique_sellers,
+                   average_price, new_listings, NOW()
+            FROM ({}) AS day
+            ON CONFLICT (date) DO UPDATE SET
+                total_volume = EXCLUDED.total_volume,
+                total_sales = EXCLUDED.total_sales,
+                unique_buyers = EXCLUDED.unique_buyers,
+                unique_sellers = EXCLUDED.unique_sellers,
+                average_price = EXCLUDED.average_price,
+                new_listings = EXCLUDED.new_listings,
+                updated_at = NOW()
+            RETURNING *
+            "#,
+            AGGREGATE_DAY_SQL
+        ))
+        .bind(date)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(stats)
+    }
+
+    /// Aggregate a day straight from `sales` without storing it
+    pub async fn aggregate_day(pool: &PgPool, date: NaiveDate) -> Result<DailyStats, AppError> {
+        let stats = sqlx::query_as::<_, DailyStats>(&format!(
+            "SELECT *, NULL::timestamptz AS updated_at FROM ({}) AS day",
+            AGGREGATE_DAY_SQL
+        ))
+        .bind(date)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(stats)
+    }
+
+    /// Daily stats for `from..=to`, falling back to on-the-fly aggregation
+    /// for days that haven't been rolled up yet
+    pub async fn get_daily_stats(
+        pool: &PgPool,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DailyStats>, AppError> {
+        let rolled_up = sqlx::query_as::<_, DailyStats>(
+            "SELECT * FROM daily_stats WHERE date BETWEEN $1 AND $2 ORDER BY date",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await?;
+
+        let mut stats = Vec::new();
+        let mut rolled_up = rolled_up.into_iter().peekable();
+        let mut date = from;
+        while date <= to {
+            match rolled_up.next_if(|day| day.date == date) {
+                Some(day) => stats.push(day),
+                None => stats.push(Self::aggregate_day(pool, date).await?),
+            }
+            date = date + Days::new(1);
+        }
+
+        Ok(stats)
+    }
+
+    /// Per-currency volume for sales within `from..=to` (UTC days)
+    pub async fn currency_breakdown(
+        pool: &PgPool,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<CurrencyVolume>, AppError> {
+        let breakdown = sqlx::query_as::<_, CurrencyVolume>(
+            r#"
+            SELECT currency_mint,
+                   SUM(price)::BIGINT AS volume,
+                   SUM(base_price)::BIGINT AS base_volume,
+                   COUNT(*) AS sales_count
+            FROM sales
+            WHERE block_time >= ($1::date)::timestamp AT TIME ZONE 'UTC'
+              AND block_time < ($2::date + 1)::timestamp AT TIME ZONE 'UTC'
+            GROUP BY currency_mint
+            ORDER BY base_volume DESC
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(breakdown)
+    }
+
+    /// Roll up every completed day since the first sale that has no stats row yet
+    pub async fn backfill_missing_days(pool: &PgPool) -> Result<usize, AppError> {
+        let missing: Vec<NaiveDate> = sqlx::query_scalar(
+            r#"
+            SELECT day::date
+            FROM generate_series(
+                (SELECT MIN(block_time) AT TIME ZONE 'UTC' FROM sales)::date,
+                (NOW() AT TIME ZONE 'UTC')::date - 1,
+                INTERVAL '1 day'
+            ) AS day
+            WHERE NOT EXISTS (SELECT 1 FROM daily_stats d WHERE d.date = day::date)
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+
+        for date in &missing {
+            Self::create_or_update_daily_stats(pool, *date).await?;
+        }
+
+        Ok(missing.len())
+    }
+}
@@ -0,0 +1,283 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+
+use crate::error::AppError;
+
+pub use solmint_client::TaxReportQuery;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+#[derive(Debug, FromRow)]
+struct LedgerRow {
+    block_time: DateTime<Utc>,
+    entry_type: String,
+    nft_mint: String,
+    nft_name: Option<String>,
+    counterparty: String,
+    price: i64,
+    currency_mint: String,
+    base_price: i64,
+    fees: i64,
+    sol_usd_price: Option<f64>,
+    cost_basis: Option<i64>,
+    cost_basis_sol_usd_price: Option<f64>,
+    transaction_signature: String,
+}
+
+/// One acquisition or disposal. SOL amounts are valued at sale time; USD
+/// amounts use the SOL/USD price recorded closest before the block time.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxLedgerEntry {
+    pub date: DateTime<Utc>,
+    /// "acquisition" or "disposal"
+    pub entry_type: String,
+    pub nft_mint: String,
+    pub nft_name: Option<String>,
+    pub counterparty: String,
+    pub price: i64,
+    pub currency_mint: String,
+    pub value_sol: f64,
+    /// Marketplace fee and royalties paid by the wallet on disposals
+    pub fees_sol: f64,
+    pub sol_usd_price: Option<f64>,
+    pub value_usd: Option<f64>,
+    pub fees_usd: Option<f64>,
+    /// Price of the wallet's most recent purchase of this NFT, for disposals
+    pub cost_basis_sol: Option<f64>,
+    pub cost_basis_usd: Option<f64>,
+    /// Proceeds net of fees minus cost basis
+    pub gain_usd: Option<f64>,
+    pub transaction_signature: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxSummary {
+    pub acquisitions: usize,
+    pub disposals: usize,
+    pub acquired_sol: f64,
+    pub acquired_usd: f64,
+    pub disposed_sol: f64,
+    pub disposed_usd: f64,
+    pub fees_sol: f64,
+    pub fees_usd: f64,
+    pub realized_gain_usd: f64,
+    /// Entries without a USD price; their USD fields are null and excluded above
+    pub entries_missing_usd_price: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaxReport {
+    pub wallet_address: String,
+    pub year: i32,
+    pub summary: TaxSummary,
+    pub entries: Vec<TaxLedgerEntry>,
+}
+
+impl TaxReport {
+    pub async fn build(pool: &PgPool, wallet_address: &str, year: i32) -> Result<Self, AppError> {
+        let (Some(start), Some(end)) = (
+            NaiveDate::from_ymd_opt(year, 1, 1),
+            NaiveDate::from_ymd_opt(year + 1, 1, 1),
+        ) else {
+            return Err(crate::error::bad_request_error("Invalid year"));
+        };
+
+        let rows = sqlx::query_as::<_, LedgerRow>(
+            r#"
+            WITH ledger AS (
+                SELECT s.*,
+                       CASE WHEN s.buyer_address = $1 THEN 'acquisition' ELSE 'disposal' END
+                           AS entry_type
+                FROM sales s
+                WHERE (s.buyer_address = $1 OR s.seller_address = $1)
+                  AND s.block_time >= ($2::date)::timestamp AT TIME ZONE 'UTC'
+                  AND s.block_time < ($3::date)::timestamp AT TIME ZONE 'UTC'
+            )
+            SELECT l.block_time, l.entry_type, l.nft_mint, n.name AS nft_name,
+                   CASE WHEN l.entry_type = 'acquisition' THEN l.seller_address
+                        ELSE l.buyer_address END AS counterparty,
+                   l.price, l.currency_mint, l.base_price,
+                   CASE WHEN l.entry_type = 'disposal'
+                        THEN l.marketplace_fee + l.royalty_paid ELSE 0 END AS fees,
+                   COALESCE(l.sol_usd_price, p.usd_price) AS sol_usd_price,
+                   basis.base_price AS cost_basis,
+                   basis.sol_usd_price AS cost_basis_sol_usd_price,
+                   l.transaction_signature
+            FROM ledger l
+            LEFT JOIN nfts n ON n.mint_address = l.nft_mint
+            LEFT JOIN LATERAL (
+                SELECT usd_price FROM sol_usd_prices
+                WHERE recorded_at <= l.block_time AND recorded_at > l.block_time - INTERVAL '1 day'
+                ORDER BY recorded_at DESC LIMIT 1
+            ) p ON TRUE
+            LEFT JOIN LATERAL (
+                SELECT b.base_price, COALESCE(b.sol_usd_price, bp.usd_price) AS sol_usd_price
+                FROM sales b
+                LEFT JOIN LATERAL (
+                    SELECT usd_price FROM sol_usd_prices
+                    WHERE recorded_at <= b.block_time
+                      AND recorded_at > b.block_time - INTERVAL '1 day'
+                    ORDER BY recorded_at DESC LIMIT 1
+                ) bp ON TRUE
+                WHERE l.entry_type = 'disposal'
+                  AND b.buyer_address = $1
+                  AND b.nft_mint = l.nft_mint
+                  AND b.block_time < l.block_time
+                ORDER BY b.block_time DESC LIMIT 1
+            ) basis ON TRUE
+            ORDER BY l.block_time, l.transaction_signature
+            "#,
+        )
+        .bind(wallet_address)
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+        .await?;
+
+        let entries: Vec<TaxLedgerEntry> = rows.into_iter().map(TaxLedgerEntry::from).collect();
+
+        Ok(Self {
+            wallet_address: wallet_address.to_string(),
+            year,
+            summary: TaxSummary::from_entries(&entries),
+            entries,
+        })
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "date,type,nft_mint,nft_name,counterparty,price,currency_mint,value_sol,fees_sol,\
+             sol_usd_price,value_usd,fees_usd,cost_basis_sol,cost_basis_usd,gain_usd,\
+             transaction_signature\n",
+        );
+
+        for entry in &self.entries {
+            let fields = [
+                entry.date.to_rfc3339(),
+                entry.entry_type.clone(),
+                entry.nft_mint.clone(),
+                entry.nft_name.clone().unwrap_or_default(),
+                entry.counterparty.clone(),
+                entry.price.to_string(),
+                entry.currency_mint.clone(),
+                entry.value_sol.to_string(),
+                entry.fees_sol.to_string(),
+                optional(entry.sol_usd_price),
+                optional(entry.value_usd),
+                optional(entry.fees_usd),
+                optional(entry.cost_basis_sol),
+                optional(entry.cost_basis_usd),
+                optional(entry.gain_usd),
+                entry.transaction_signature.clone(),
+            ];
+            let line: Vec<String> = fields.iter().map(|field| csv_escape(field)).collect();
+            csv.push_str(&line.join(","));
+            csv.push('\n');
+        }
+
+        csv
+    }
+}
+
+impl From<LedgerRow> for TaxLedgerEntry {
+    fn from(row: LedgerRow) -> Self {
+        let value_sol = row.base_price as f64 / LAMPORTS_PER_SOL;
+        // Fees are charged in the sale currency; value them at the sale's SOL rate
+        let fees_sol = if row.price > 0 {
+            value_sol * row.fees as f64 / row.price as f64
+        } else {
+            0.0
+        };
+        let cost_basis_sol = row.cost_basis.map(|basis| basis as f64 / LAMPORTS_PER_SOL);
+
+        let value_usd = row.sol_usd_price.map(|usd| round_cents(value_sol * usd));
+        let fees_usd = row.sol_usd_price.map(|usd| round_cents(fees_sol * usd));
+        let cost_basis_usd = cost_basis_sol
+            .zip(row.cost_basis_sol_usd_price)
+            .map(|(sol, usd)| round_cents(sol * usd));
+        let gain_usd = match (value_usd, fees_usd, cost_basis_usd) {
+            (Some(value), Some(fees), Some(basis)) if row.entry_type == "disposal" => {
+                Some(round_cents(value - fees - basis))
+            }
+            _ => None,
+        };
+
+        Self {
+            date: row.block_time,
+            entry_type: row.entry_type,
+            nft_mint: row.nft_mint,
+            nft_name: row.nft_name,
+            counterparty: row.counterparty,
+            price: row.price,
+            currency_mint: row.currency_mint,
+            value_sol,
+            fees_sol,
+            sol_usd_price: row.sol_usd_price,
+            value_usd,
+            fees_usd,
+            cost_basis_sol,
+            cost_basis_usd,
+            gain_usd,
+            transaction_signature: row.transaction_signature,
+        }
+    }
+}
+
+impl TaxSummary {
+    fn from_entries(entries: &[TaxLedgerEntry]) -> Self {
+        let mut summary = Self {
+            acquisitions: 0,
+            disposals: 0,
+            acquired_sol: 0.0,
+            acquired_usd: 0.0,
+            disposed_sol: 0.0,
+            disposed_usd: 0.0,
+            fees_sol: 0.0,
+            fees_usd: 0.0,
+            realized_gain_usd: 0.0,
+            entries_missing_usd_price: 0,
+        };
+
+        for entry in entries {
+            if entry.sol_usd_price.is_none() {
+                summary.entries_missing_usd_price += 1;
+            }
+
+            if entry.entry_type == "acquisition" {
+                summary.acquisitions += 1;
+                summary.acquired_sol += entry.value_sol;
+                summary.acquired_usd += entry.value_usd.unwrap_or_default();
+            } else {
+                summary.disposals += 1;
+                summary.disposed_sol += entry.value_sol;
+                summary.disposed_usd += entry.value_usd.unwrap_or_default();
+                summary.fees_sol += entry.fees_sol;
+                summary.fees_usd += entry.fees_usd.unwrap_or_default();
+                summary.realized_gain_usd += entry.gain_usd.unwrap_or_default();
+            }
+        }
+
+        summary.acquired_usd = round_cents(summary.acquired_usd);
+        summary.disposed_usd = round_cents(summary.disposed_usd);
+        summary.fees_usd = round_cents(summary.fees_usd);
+        summary.realized_gain_usd = round_cents(summary.realized_gain_usd);
+        summary
+    }
+}
+
+fn round_cents(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn optional(value: Option<f64>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -3,6 +3,50 @@ use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, PgPool};
 use uuid::Uuid;
 
+use crate::error::AppError;
+
+pub use solmint_client::{
+    AddFavoriteCollectionRequest, Role, SetUserRoleRequest, TwitterChallenge,
+    TwitterChallengeQuery, UpdateUserRequest, UsernameAvailability, VerifyTwitterRequest,
+};
+
+pub const USERNAME_MIN_LEN: usize = 3;
+pub const USERNAME_MAX_LEN: usize = 30;
+
+/// Names that could pass for the marketplace itself or collide with routes
+pub const RESERVED_USERNAMES: &[&str] = &[
+    "about",
+    "admin",
+    "administrator",
+    "api",
+    "collection",
+    "collections",
+    "explore",
+    "help",
+    "launchpad",
+    "me",
+    "mod",
+    "moderator",
+    "null",
+    "official",
+    "profile",
+    "profiles",
+    "root",
+    "settings",
+    "solana",
+    "solmint",
+    "staff",
+    "support",
+    "system",
+    "team",
+    "undefined",
+    "user",
+    "users",
+];
+
+/// The unique index on `LOWER(username)`
+const USERNAME_INDEX: &str = "idx_users_username_lower";
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct User {
     pub id: Uuid,
@@ -12,8 +56,12 @@ pub struct User {
     pub bio: Option<String>,
     pub avatar_url: Option<String>,
     pub twitter_handle: Option<String>,
+    /// Set once a tweet from `twitter_handle` signed by the wallet was checked
+    pub twitter_verified_at: Option<DateTime<Utc>>,
     pub discord_handle: Option<String>,
     pub verified: bool,
+    /// Set once the address is confirmed; email notifications require it
+    pub email_verified_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -29,21 +77,15 @@ pub struct CreateUserRequest {
     pub discord_handle: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UpdateUserRequest {
-    pub username: Option<String>,
-    pub email: Option<String>,
-    pub bio: Option<String>,
-    pub avatar_url: Option<String>,
-    pub twitter_handle: Option<String>,
-    pub discord_handle: Option<String>,
-}
-
 impl User {
     pub async fn create(
         pool: &PgPool,
         req: CreateUserRequest,
     ) -> Result<Self, crate::error::AppError> {
+        if let Some(username) = &req.username {
+            validate_username(username)?;
+        }
+
         let user = sqlx::query_as!(
             User,
             r#"
@@ -53,8 +95,8 @@ impl User {
             )
             VALUES ($1, $2, $3, $4, $5, $6, $7)
             RETURNING id, wallet_address, username, email, bio, avatar_url, 
-                     twitter_handle, discord_handle, 
-                     verified as "verified!", 
+                     twitter_handle, twitter_verified_at, discord_handle,
+                     verified as "verified!", email_verified_at,
                      created_at as "created_at!", 
                      updated_at as "updated_at!"
             "#,
@@ -67,7 +109,8 @@ impl User {
             req.discord_handle
         )
         .fetch_one(pool)
-        .await?;
+        .await
+        .map_err(username_conflict)?;
 
         Ok(user)
     }
@@ -80,8 +123,8 @@ impl User {
             User,
             r#"
             SELECT id, wallet_address, username, email, bio, avatar_url, 
-                   twitter_handle, discord_handle, 
-                   verified as "verified!", 
+                   twitter_handle, twitter_verified_at, discord_handle,
+                   verified as "verified!", email_verified_at,
                    created_at as "created_at!", 
                    updated_at as "updated_at!"
             FROM users WHERE wallet_address = $1
@@ -94,11 +137,64 @@ impl User {
         Ok(user)
     }
 
+    /// Case-insensitive lookup for profile pages
+    pub async fn find_by_username(
+        pool: &PgPool,
+        username: &str,
+    ) -> Result<Option<Self>, crate::error::AppError> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id, wallet_address, username, email, bio, avatar_url,
+                   twitter_handle, twitter_verified_at, discord_handle,
+                   verified as "verified!", email_verified_at,
+                   created_at as "created_at!",
+                   updated_at as "updated_at!"
+            FROM users WHERE LOWER(username) = LOWER($1)
+            "#,
+            username
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    /// Whether `username` could be registered, and why not if it can't
+    pub async fn username_availability(
+        pool: &PgPool,
+        username: &str,
+    ) -> Result<UsernameAvailability, AppError> {
+        let reason = match validate_username(username) {
+            Err(AppError::ValidationError(reason)) => Some(reason),
+            Err(e) => return Err(e),
+            Ok(()) => {
+                let taken: bool = sqlx::query_scalar(
+                    "SELECT EXISTS (SELECT 1 FROM users WHERE LOWER(username) = LOWER($1))",
+                )
+                .bind(username)
+                .fetch_one(pool)
+                .await?;
+                taken.then(|| "username is taken".to_string())
+            }
+        };
+
+        Ok(UsernameAvailability {
+            username: username.to_string(),
+            available: reason.is_none(),
+            reason,
+        })
+    }
+
     pub async fn update(
         pool: &PgPool,
         wallet_address: &str,
         req: UpdateUserRequest,
     ) -> Result<Self, crate::error::AppError> {
+        if let Some(username) = &req.username {
+            validate_username(username)?;
+        }
+
         let user = sqlx::query_as!(
             User,
             r#"
@@ -109,11 +205,19 @@ impl User {
                 avatar_url = COALESCE($5, avatar_url),
                 twitter_handle = COALESCE($6, twitter_handle),
                 discord_handle = COALESCE($7, discord_handle),
+                -- So does a new handle
+                twitter_verified_at = CASE WHEN $6 IS DISTINCT FROM twitter_handle AND $6 IS NOT NULL
+                    THEN NULL ELSE twitter_verified_at END,
+                -- A new address has to be verified again
+                email_verified_at = CASE WHEN $3 IS DISTINCT FROM email AND $3 IS NOT NULL
+                    THEN NULL ELSE email_verified_at END,
+                email_verification_token_hash = CASE WHEN $3 IS DISTINCT FROM email AND $3 IS NOT NULL
+                    THEN NULL ELSE email_verification_token_hash END,
                 updated_at = NOW()
             WHERE wallet_address = $1
             RETURNING id, wallet_address, username, email, bio, avatar_url, 
-                     twitter_handle, discord_handle, 
-                     verified as "verified!", 
+                     twitter_handle, twitter_verified_at, discord_handle,
+                     verified as "verified!", email_verified_at,
                      created_at as "created_at!", 
                      updated_at as "updated_at!"
             "#,
@@ -126,8 +230,163 @@ impl User {
             req.discord_handle
         )
         .fetch_one(pool)
-        .await?;
+        .await
+        .map_err(username_conflict)?;
 
         Ok(user)
     }
+
+    /// Record `handle` as the wallet's verified Twitter/X handle
+    pub async fn set_verified_twitter(
+        pool: &PgPool,
+        wallet_address: &str,
+        handle: &str,
+    ) -> Result<Self, AppError> {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users SET
+                twitter_handle = $2,
+                twitter_verified_at = NOW(),
+                updated_at = NOW()
+            WHERE wallet_address = $1
+            RETURNING id, wallet_address, username, email, bio, avatar_url,
+                     twitter_handle, twitter_verified_at, discord_handle,
+                     verified as "verified!", email_verified_at,
+                     created_at as "created_at!",
+                     updated_at as "updated_at!"
+            "#,
+            wallet_address,
+            handle
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("User"))?;
+
+        Ok(user)
+    }
+
+    /// Store a new verification token hash unless one was issued in the last
+    /// `resend_seconds`. Returns whether a new token was stored.
+    pub async fn start_email_verification(
+        pool: &PgPool,
+        wallet_address: &str,
+        token_hash: &str,
+        resend_seconds: i64,
+    ) -> Result<bool, crate::error::AppError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE users SET
+                email_verification_token_hash = $2,
+                email_verification_sent_at = NOW()
+            WHERE wallet_address = $1
+              AND (email_verification_sent_at IS NULL
+                   OR email_verification_sent_at < NOW() - make_interval(secs => $3))
+            "#,
+            wallet_address,
+            token_hash,
+            resend_seconds as f64
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Mark the address verified if the token matches and is under a day old.
+    /// Returns the wallet it belonged to.
+    pub async fn confirm_email(
+        pool: &PgPool,
+        token_hash: &str,
+    ) -> Result<Option<String>, crate::error::AppError> {
+        let wallet = sqlx::query_scalar!(
+            r#"
+            UPDATE users SET
+                email_verified_at = NOW(),
+                email_verification_token_hash = NULL,
+                updated_at = NOW()
+            WHERE email_verification_token_hash = $1
+              AND email_verification_sent_at >= NOW() - INTERVAL '1 day'
+            RETURNING wallet_address
+            "#,
+            token_hash
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(wallet)
+    }
+
+    /// The wallet's role; wallets without a profile are plain users
+    pub async fn role(pool: &PgPool, wallet_address: &str) -> Result<Role, AppError> {
+        let role = sqlx::query_scalar!(
+            "SELECT role FROM users WHERE wallet_address = $1",
+            wallet_address
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(role.as_deref().and_then(Role::parse).unwrap_or(Role::User))
+    }
+
+    /// Give the wallet `role`, creating an empty profile if it has none
+    pub async fn set_role(pool: &PgPool, wallet_address: &str, role: Role) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO users (wallet_address, role) VALUES ($1, $2)
+            ON CONFLICT (wallet_address) DO UPDATE SET role = $2, updated_at = NOW()
+            "#,
+            wallet_address,
+            role.as_str()
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Make the configured admin wallets admins. Wallets removed from the
+    /// list keep their role until it is changed through the admin API.
+    pub async fn seed_admins(pool: &PgPool, wallet_addresses: &[String]) -> Result<(), AppError> {
+        for wallet_address in wallet_addresses {
+            Self::set_role(pool, wallet_address, Role::Admin).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 3-30 letters, digits or underscores, not a reserved name. Case is kept
+/// for display but ignored for uniqueness.
+pub fn validate_username(username: &str) -> Result<(), AppError> {
+    if !(USERNAME_MIN_LEN..=USERNAME_MAX_LEN).contains(&username.len()) {
+        return Err(AppError::ValidationError(format!(
+            "username must be {} to {} characters",
+            USERNAME_MIN_LEN, USERNAME_MAX_LEN
+        )));
+    }
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(AppError::ValidationError(
+            "username may only contain letters, digits and underscores".to_string(),
+        ));
+    }
+    if RESERVED_USERNAMES.contains(&username.to_ascii_lowercase().as_str()) {
+        return Err(AppError::ValidationError(
+            "username is reserved".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn username_conflict(error: sqlx::Error) -> AppError {
+    match &error {
+        sqlx::Error::Database(db_error) if db_error.constraint() == Some(USERNAME_INDEX) => {
+            crate::error::conflict_error("username is taken")
+        }
+        _ => error.into(),
+    }
 }
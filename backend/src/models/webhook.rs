@@ -0,0 +1,476 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+pub use solmint_client::{CreateWebhookRequest, UpdateWebhookRequest, WebhookDeliveryQuery};
+
+pub const WEBHOOK_EVENT_SALE: &str = "sale";
+pub const WEBHOOK_EVENT_LISTING: &str = "listing";
+pub const WEBHOOK_EVENT_MINT: &str = "mint";
+pub const WEBHOOK_EVENT_OFFER: &str = "offer";
+pub const WEBHOOK_EVENTS: &[&str] = &[
+    WEBHOOK_EVENT_SALE,
+    WEBHOOK_EVENT_LISTING,
+    WEBHOOK_EVENT_MINT,
+    WEBHOOK_EVENT_OFFER,
+];
+
+pub const DELIVERY_STATUS_PENDING: &str = "pending";
+pub const DELIVERY_STATUS_DELIVERED: &str = "delivered";
+pub const DELIVERY_STATUS_FAILED: &str = "failed";
+pub const DELIVERY_STATUSES: &[&str] = &[
+    DELIVERY_STATUS_PENDING,
+    DELIVERY_STATUS_DELIVERED,
+    DELIVERY_STATUS_FAILED,
+];
+
+const MAX_WEBHOOKS_PER_WALLET: i64 = 10;
+const MAX_URL_LENGTH: usize = 2048;
+/// A delivery is given up on after this many attempts, about five hours of retries
+const MAX_DELIVERY_ATTEMPTS: i32 = 12;
+/// Retry backoff doubles per attempt from this, up to `MAX_BACKOFF_SECONDS`
+const BASE_BACKOFF_SECONDS: i64 = 30;
+const MAX_BACKOFF_SECONDS: i64 = 3600;
+/// Claimed deliveries are retried after this long if the sender dies mid-batch
+const CLAIM_LEASE_SECONDS: i64 = 300;
+/// Activities fanned out per webhook per run
+const FAN_OUT_BATCH_SIZE: i64 = 1000;
+/// Finished deliveries are kept this long as the delivery log
+const DELIVERY_RETENTION_DAYS: i64 = 30;
+
+/// The webhook event an activity counts as; other activity (rentals,
+/// transfers) isn't delivered
+const ACTIVITY_EVENT_SQL: &str = r#"
+    CASE a.activity_type
+        WHEN 'sale' THEN 'sale'
+        WHEN 'list' THEN 'listing'
+        WHEN 'price_change' THEN 'listing'
+        WHEN 'delist' THEN 'listing'
+        WHEN 'mint' THEN 'mint'
+        WHEN 'offer' THEN 'offer'
+    END
+"#;
+
+/// An integrator's webhook, posted matching marketplace activity
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub owner_address: String,
+    pub url: String,
+    pub events: Vec<String>,
+    pub collection_id: Option<Uuid>,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One activity posted (or being posted) to a webhook
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: Uuid,
+    /// Firehose sequence of the activity
+    pub activity_id: i64,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A delivery claimed for sending, with where to send it
+#[derive(Debug, Clone, FromRow)]
+pub struct DueDelivery {
+    pub id: i64,
+    pub webhook_id: Uuid,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub created_at: DateTime<Utc>,
+    pub url: String,
+    pub secret: String,
+}
+
+const WEBHOOK_COLUMNS: &str =
+    "id, owner_address, url, events, collection_id, secret, enabled, created_at, updated_at";
+
+const DELIVERY_COLUMNS: &str = r#"
+    id, webhook_id, activity_id, event, payload, status, attempts, next_attempt_at,
+    response_status, last_error, delivered_at, created_at
+"#;
+
+async fn validate_url(url: &str) -> Result<(), AppError> {
+    if url.len() > MAX_URL_LENGTH {
+        return Err(crate::error::bad_request_error("url must be an https URL"));
+    }
+    crate::services::webhooks::check_url(url)
+        .await
+        .map_err(AppError::BadRequest)
+}
+
+fn validate_events(events: &[String]) -> Result<(), AppError> {
+    if events.is_empty() {
+        return Err(crate::error::bad_request_error("events must not be empty"));
+    }
+    if let Some(unknown) = events
+        .iter()
+        .find(|event| !WEBHOOK_EVENTS.contains(&event.as_str()))
+    {
+        return Err(AppError::BadRequest(format!(
+            "unknown event {}; expected any of {}",
+            unknown,
+            WEBHOOK_EVENTS.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+impl Webhook {
+    /// Register a webhook for the wallet. It receives activity from now on.
+    pub async fn create(
+        pool: &PgPool,
+        owner_address: &str,
+        req: &CreateWebhookRequest,
+    ) -> Result<Self, AppError> {
+        let url = req.url.trim();
+        validate_url(url).await?;
+        validate_events(&req.events)?;
+        let secret = format!("whsec_{}", Uuid::new_v4().simple());
+
+        let webhook = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            INSERT INTO webhooks (owner_address, url, events, collection_id, secret, last_sequence)
+            SELECT $1, $2, $3, $4, $5, (SELECT COALESCE(MAX(sequence), 0) FROM activities)
+            WHERE (SELECT COUNT(*) FROM webhooks WHERE owner_address = $1) < $6
+            RETURNING {}
+            "#,
+            WEBHOOK_COLUMNS
+        ))
+        .bind(owner_address)
+        .bind(url)
+        .bind(&req.events)
+        .bind(req.collection_id)
+        .bind(&secret)
+        .bind(MAX_WEBHOOKS_PER_WALLET)
+        .fetch_optional(pool)
+        .await?;
+
+        webhook.ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "a wallet may register at most {} webhooks",
+                MAX_WEBHOOKS_PER_WALLET
+            ))
+        })
+    }
+
+    /// The wallet's webhooks, oldest first
+    pub async fn list_for_wallet(
+        pool: &PgPool,
+        owner_address: &str,
+    ) -> Result<Vec<Self>, AppError> {
+        let webhooks = sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM webhooks WHERE owner_address = $1 ORDER BY created_at, id",
+            WEBHOOK_COLUMNS
+        ))
+        .bind(owner_address)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(webhooks)
+    }
+
+    /// The webhook, if the wallet owns it
+    pub async fn find_owned(
+        pool: &PgPool,
+        owner_address: &str,
+        id: Uuid,
+    ) -> Result<Option<Self>, AppError> {
+        let webhook = sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM webhooks WHERE id = $1 AND owner_address = $2",
+            WEBHOOK_COLUMNS
+        ))
+        .bind(id)
+        .bind(owner_address)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(webhook)
+    }
+
+    /// Change the wallet's webhook; `None` when it doesn't own one with this id.
+    /// Re-enabling skips the activity missed while disabled.
+    pub async fn update(
+        pool: &PgPool,
+        owner_address: &str,
+        id: Uuid,
+        req: &UpdateWebhookRequest,
+    ) -> Result<Option<Self>, AppError> {
+        let url = req.url.as_deref().map(str::trim);
+        if let Some(url) = url {
+            validate_url(url).await?;
+        }
+        if let Some(events) = &req.events {
+            validate_events(events)?;
+        }
+
+        let webhook = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            UPDATE webhooks SET
+                url = COALESCE($3, url),
+                events = COALESCE($4, events),
+                last_sequence = CASE WHEN $5 AND NOT enabled
+                    THEN (SELECT COALESCE(MAX(sequence), 0) FROM activities)
+                    ELSE last_sequence END,
+                enabled = COALESCE($5, enabled),
+                updated_at = NOW()
+            WHERE id = $1 AND owner_address = $2
+            RETURNING {}
+            "#,
+            WEBHOOK_COLUMNS
+        ))
+        .bind(id)
+        .bind(owner_address)
+        .bind(url)
+        .bind(&req.events)
+        .bind(req.enabled)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(webhook)
+    }
+
+    /// Remove the wallet's webhook and its delivery log. Returns whether it
+    /// existed.
+    pub async fn delete(pool: &PgPool, owner_address: &str, id: Uuid) -> Result<bool, AppError> {
+        let result = sqlx::query("DELETE FROM webhooks WHERE id = $1 AND owner_address = $2")
+            .bind(id)
+            .bind(owner_address)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+impl WebhookDelivery {
+    /// Create deliveries for newly sequenced activity, advancing each enabled
+    /// webhook's cursor in the same statement. Returns how many were created.
+    pub async fn fan_out(pool: &PgPool) -> Result<u64, AppError> {
+        let result = sqlx::query(&format!(
+            r#"
+            WITH due AS (
+                SELECT w.id, w.events, w.collection_id, w.last_sequence AS after,
+                       LEAST(head.sequence, w.last_sequence + $1) AS upto
+                FROM webhooks w,
+                     (SELECT COALESCE(MAX(sequence), 0) AS sequence FROM activities) head
+                WHERE w.enabled AND w.last_sequence < head.sequence
+                FOR UPDATE OF w SKIP LOCKED
+            ),
+            advanced AS (
+                UPDATE webhooks SET last_sequence = due.upto
+                FROM due
+                WHERE webhooks.id = due.id
+            )
+            INSERT INTO webhook_deliveries (webhook_id, activity_id, event, payload)
+            SELECT due.id, a.sequence, e.event,
+                   jsonb_build_object(
+                       'id', a.sequence,
+                       'activity_type', a.activity_type,
+                       'nft_mint', a.nft_mint,
+                       'collection_id', a.collection_id,
+                       'from_address', a.from_address,
+                       'to_address', a.to_address,
+                       'price', a.price,
+                       'currency_mint', a.currency_mint,
+                       'transaction_signature', a.transaction_signature,
+                       'occurred_at', a.occurred_at
+                   )
+            FROM due
+            JOIN activities a ON a.sequence > due.after AND a.sequence <= due.upto
+            CROSS JOIN LATERAL (SELECT {} AS event) e
+            WHERE e.event = ANY(due.events)
+              AND (due.collection_id IS NULL OR a.collection_id = due.collection_id)
+            ON CONFLICT (webhook_id, activity_id) DO NOTHING
+            "#,
+            ACTIVITY_EVENT_SQL
+        ))
+        .bind(FAN_OUT_BATCH_SIZE)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Claim pending deliveries that are due, oldest first. Claimed deliveries
+    /// aren't due again until the lease runs out, so concurrent senders don't
+    /// post them twice.
+    pub async fn claim_due(pool: &PgPool, limit: i64) -> Result<Vec<DueDelivery>, AppError> {
+        let deliveries = sqlx::query_as::<_, DueDelivery>(
+            r#"
+            UPDATE webhook_deliveries d SET next_attempt_at = $2
+            FROM webhooks w
+            WHERE w.id = d.webhook_id
+              AND d.id IN (
+                  SELECT p.id FROM webhook_deliveries p
+                  JOIN webhooks h ON h.id = p.webhook_id
+                  WHERE p.status = 'pending' AND p.next_attempt_at <= NOW() AND h.enabled
+                  ORDER BY p.next_attempt_at
+                  LIMIT $1
+                  FOR UPDATE OF p SKIP LOCKED
+              )
+            RETURNING d.id, d.webhook_id, d.event, d.payload, d.attempts, d.created_at,
+                      w.url, w.secret
+            "#,
+        )
+        .bind(limit)
+        .bind(Utc::now() + Duration::seconds(CLAIM_LEASE_SECONDS))
+        .fetch_all(pool)
+        .await?;
+
+        Ok(deliveries)
+    }
+
+    pub async fn mark_delivered(
+        pool: &PgPool,
+        id: i64,
+        response_status: i32,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries SET
+                status = 'delivered', attempts = attempts + 1, response_status = $2,
+                last_error = NULL, delivered_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(response_status)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Schedule another attempt with exponential backoff, or give up once out
+    /// of attempts
+    pub async fn mark_failed(
+        pool: &PgPool,
+        delivery: &DueDelivery,
+        response_status: Option<i32>,
+        error: &str,
+    ) -> Result<(), AppError> {
+        let backoff = BASE_BACKOFF_SECONDS
+            .saturating_mul(2_i64.saturating_pow(delivery.attempts.clamp(0, 12) as u32))
+            .min(MAX_BACKOFF_SECONDS);
+        let status = if delivery.attempts + 1 >= MAX_DELIVERY_ATTEMPTS {
+            DELIVERY_STATUS_FAILED
+        } else {
+            DELIVERY_STATUS_PENDING
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries SET
+                status = $2, attempts = attempts + 1, response_status = $3, last_error = $4,
+                next_attempt_at = $5
+            WHERE id = $1
+            "#,
+        )
+        .bind(delivery.id)
+        .bind(status)
+        .bind(response_status)
+        .bind(error)
+        .bind(Utc::now() + Duration::seconds(backoff))
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// The webhook's deliveries, newest first
+    pub async fn list(
+        pool: &PgPool,
+        webhook_id: Uuid,
+        status: Option<&str>,
+        before: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<Self>, AppError> {
+        let deliveries = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            SELECT {} FROM webhook_deliveries
+            WHERE webhook_id = $1
+              AND ($2::text IS NULL OR status = $2)
+              AND ($3::bigint IS NULL OR id < $3)
+            ORDER BY id DESC
+            LIMIT $4
+            "#,
+            DELIVERY_COLUMNS
+        ))
+        .bind(webhook_id)
+        .bind(status)
+        .bind(before)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(deliveries)
+    }
+
+    pub async fn find(pool: &PgPool, webhook_id: Uuid, id: i64) -> Result<Option<Self>, AppError> {
+        let delivery = sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM webhook_deliveries WHERE id = $1 AND webhook_id = $2",
+            DELIVERY_COLUMNS
+        ))
+        .bind(id)
+        .bind(webhook_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(delivery)
+    }
+
+    /// Queue a delivered or failed delivery to be sent again, with a fresh set
+    /// of attempts. `None` when it isn't the webhook's or is still pending.
+    pub async fn redeliver(
+        pool: &PgPool,
+        webhook_id: Uuid,
+        id: i64,
+    ) -> Result<Option<Self>, AppError> {
+        let delivery = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            UPDATE webhook_deliveries SET
+                status = 'pending', attempts = 0, next_attempt_at = NOW(), delivered_at = NULL
+            WHERE id = $1 AND webhook_id = $2 AND status <> 'pending'
+            RETURNING {}
+            "#,
+            DELIVERY_COLUMNS
+        ))
+        .bind(id)
+        .bind(webhook_id)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(delivery)
+    }
+
+    /// Drop finished deliveries older than the retention window
+    pub async fn prune(pool: &PgPool) -> Result<u64, AppError> {
+        let result = sqlx::query(
+            "DELETE FROM webhook_deliveries WHERE status <> 'pending' AND created_at < $1",
+        )
+        .bind(Utc::now() - Duration::days(DELIVERY_RETENTION_DAYS))
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
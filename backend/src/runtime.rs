@@ -0,0 +1,140 @@
+use governor::{DefaultKeyedRateLimiter, Quota, RateLimiter};
+use serde::Serialize;
+use solana_client::rpc_client::RpcClient;
+use std::{
+    net::IpAddr,
+    num::NonZeroU32,
+    sync::{Arc, RwLock},
+};
+
+use crate::config::Config;
+
+/// Settings that can change while the server is running
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuntimeSettings {
+    pub cors_allowed_origins: Vec<String>,
+    pub rate_limit_per_minute: u32,
+    pub solana_rpc_url: String,
+    pub cache_ttl_seconds: u64,
+}
+
+impl RuntimeSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            cors_allowed_origins: config.cors_origins(),
+            rate_limit_per_minute: config.rate_limit_per_minute,
+            solana_rpc_url: config.solana_rpc_url.clone(),
+            cache_ttl_seconds: config.cache_ttl_seconds,
+        }
+    }
+}
+
+struct RuntimeInner {
+    settings: Arc<RuntimeSettings>,
+    solana_client: Arc<RpcClient>,
+    rate_limiter: Arc<DefaultKeyedRateLimiter<IpAddr>>,
+}
+
+/// Shared handle to the hot-reloadable part of the configuration.
+///
+/// Readers take cheap `Arc` snapshots, so a reload never blocks in-flight
+/// requests. Background services such as the websocket indexer keep the
+/// `Config` they were started with and are not affected.
+#[derive(Clone)]
+pub struct RuntimeConfig {
+    inner: Arc<RwLock<RuntimeInner>>,
+}
+
+impl RuntimeConfig {
+    pub fn new(config: &Config) -> Self {
+        let settings = RuntimeSettings::from_config(config);
+
+        Self {
+            inner: Arc::new(RwLock::new(RuntimeInner {
+                solana_client: Arc::new(RpcClient::new(settings.solana_rpc_url.clone())),
+                rate_limiter: Arc::new(build_rate_limiter(settings.rate_limit_per_minute)),
+                settings: Arc::new(settings),
+            })),
+        }
+    }
+
+    pub fn settings(&self) -> Arc<RuntimeSettings> {
+        self.inner.read().unwrap().settings.clone()
+    }
+
+    pub fn solana_client(&self) -> Arc<RpcClient> {
+        self.inner.read().unwrap().solana_client.clone()
+    }
+
+    pub fn rate_limiter(&self) -> Arc<DefaultKeyedRateLimiter<IpAddr>> {
+        self.inner.read().unwrap().rate_limiter.clone()
+    }
+
+    /// Swap in the reloadable settings from `config`, returning the names of
+    /// the settings that changed
+    pub fn apply(&self, config: &Config) -> Vec<&'static str> {
+        let next = RuntimeSettings::from_config(config);
+        let mut inner = self.inner.write().unwrap();
+        let mut changed = Vec::new();
+
+        if next.cors_allowed_origins != inner.settings.cors_allowed_origins {
+            changed.push("cors_allowed_origins");
+        }
+        if next.rate_limit_per_minute != inner.settings.rate_limit_per_minute {
+            inner.rate_limiter = Arc::new(build_rate_limiter(next.rate_limit_per_minute));
+            changed.push("rate_limit_per_minute");
+        }
+        if next.solana_rpc_url != inner.settings.solana_rpc_url {
+            inner.solana_client = Arc::new(RpcClient::new(next.solana_rpc_url.clone()));
+            changed.push("solana_rpc_url");
+        }
+        if next.cache_ttl_seconds != inner.settings.cache_ttl_seconds {
+            changed.push("cache_ttl_seconds");
+        }
+
+        inner.settings = Arc::new(next);
+        changed
+    }
+
+    /// Reload configuration whenever the process receives SIGHUP
+    #[cfg(unix)]
+    pub fn spawn_sighup_listener(self) {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        tokio::spawn(async move {
+            let mut hangups = match signal(SignalKind::hangup()) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    println!("Failed to install SIGHUP handler: {:?}", e);
+                    return;
+                }
+            };
+
+            while hangups.recv().await.is_some() {
+                println!("SIGHUP received, reloading configuration");
+                match Config::load() {
+                    Ok(config) => {
+                        let changed = self.apply(&config);
+                        if changed.is_empty() {
+                            println!("Configuration reloaded, nothing changed");
+                        } else {
+                            println!("Configuration reloaded: {}", changed.join(", "));
+                        }
+                    }
+                    Err(e) => println!(
+                        "Configuration reload rejected, keeping current settings: {}",
+                        e
+                    ),
+                }
+            }
+        });
+    }
+
+    #[cfg(not(unix))]
+    pub fn spawn_sighup_listener(self) {}
+}
+
+fn build_rate_limiter(per_minute: u32) -> DefaultKeyedRateLimiter<IpAddr> {
+    let per_minute = NonZeroU32::new(per_minute).unwrap_or(NonZeroU32::MIN);
+    RateLimiter::keyed(Quota::per_minute(per_minute))
+}
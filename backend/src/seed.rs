@@ -0,0 +1,117 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+const DEMO_CREATOR: &str = "DemoCreator1111111111111111111111111111111";
+const DEMO_OWNER: &str = "DemoOwner11111111111111111111111111111111";
+
+struct DemoCollection {
+    name: &'static str,
+    symbol: &'static str,
+    description: &'static str,
+    nft_count: usize,
+}
+
+const DEMO_COLLECTIONS: &[DemoCollection] = &[
+    DemoCollection {
+        name: "Solmint Genesis",
+        symbol: "GEN",
+        description: "Demo collection seeded for local development",
+        nft_count: 6,
+    },
+    DemoCollection {
+        name: "Pixel Critters",
+        symbol: "PXC",
+        description: "Another demo collection with a handful of listings",
+        nft_count: 4,
+    },
+];
+
+/// Load demo collections, NFTs and listings for local development and tests.
+///
+/// Safe to run repeatedly: collections are matched by name and NFTs/listings
+/// by their deterministic mint addresses.
+pub async fn run(pool: &PgPool) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    for (collection_index, demo) in DEMO_COLLECTIONS.iter().enumerate() {
+        let existing = sqlx::query_scalar!("SELECT id FROM collections WHERE name = $1", demo.name)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let collection_id: Uuid = match existing {
+            Some(id) => id,
+            None => {
+                sqlx::query_scalar!(
+                    r#"
+                    INSERT INTO collections (name, symbol, description, creator_address, verified)
+                    VALUES ($1, $2, $3, $4, TRUE)
+                    RETURNING id
+                    "#,
+                    demo.name,
+                    demo.symbol,
+                    demo.description,
+                    DEMO_CREATOR
+                )
+                .fetch_one(&mut *tx)
+                .await?
+            }
+        };
+
+        for nft_index in 0..demo.nft_count {
+            let mint_address = format!("DemoMint{}{:0>35}", collection_index, nft_index);
+            let name = format!("{} #{}", demo.name, nft_index + 1);
+            let attributes = serde_json::json!([
+                { "trait_type": "Background", "value": ["Blue", "Red", "Green"][nft_index % 3] },
+                { "trait_type": "Edition", "value": nft_index + 1 }
+            ]);
+
+            sqlx::query!(
+                r#"
+                INSERT INTO nfts (
+                    mint_address, collection_id, name, description, image_url,
+                    attributes, creator_address, current_owner, rarity_rank
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT (mint_address) DO NOTHING
+                "#,
+                mint_address,
+                collection_id,
+                name,
+                demo.description,
+                format!("https://picsum.photos/seed/{}/512", mint_address),
+                attributes,
+                DEMO_CREATOR,
+                DEMO_OWNER,
+                (nft_index + 1) as i32
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            // List every other NFT so the marketplace has something to browse
+            if nft_index % 2 == 0 {
+                let price = 500_000_000i64 * (nft_index as i64 + 1); // 0.5 SOL steps
+                sqlx::query!(
+                    r#"
+                    INSERT INTO listings (nft_mint, seller_address, price, status)
+                    SELECT $1, $2, $3, 'active'
+                    WHERE NOT EXISTS (
+                        SELECT 1 FROM listings WHERE nft_mint = $1 AND status = 'active'
+                    )
+                    "#,
+                    mint_address,
+                    DEMO_OWNER,
+                    price
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+    }
+
+    tx.commit().await?;
+
+    println!("Seeded {} demo collections", DEMO_COLLECTIONS.len());
+    Ok(())
+}
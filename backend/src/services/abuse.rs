@@ -0,0 +1,99 @@
+//! Automatic blocks for wallets and IPs whose requests keep failing, e.g.
+//! bad signatures or malformed transactions, kept in Redis alongside the
+//! moderator-managed `access_blocks`
+
+use axum::http::StatusCode;
+use redis::aio::MultiplexedConnection;
+
+use crate::error::AppError;
+
+/// Failures are counted over this window
+const FAILURE_WINDOW_SECONDS: u64 = 600;
+/// Failures within the window that get an identity blocked
+const MAX_FAILURES: u64 = 30;
+/// How long an automatic block lasts
+const AUTO_BLOCK_SECONDS: u64 = 3_600;
+
+fn failures_key(kind: &str, value: &str) -> String {
+    format!("abuse:failures:{}:{}", kind, value)
+}
+
+fn block_key(kind: &str, value: &str) -> String {
+    format!("abuse:blocked:{}:{}", kind, value)
+}
+
+/// Whether a response counts against the caller: rejected input,
+/// credentials or transactions, not missing resources or server errors
+pub fn is_failure(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_REQUEST
+            | StatusCode::UNAUTHORIZED
+            | StatusCode::FORBIDDEN
+            | StatusCode::UNPROCESSABLE_ENTITY
+    )
+}
+
+/// Count a failure, blocking the identity once it reaches `MAX_FAILURES`
+pub async fn record_failure(
+    redis: &MultiplexedConnection,
+    kind: &str,
+    value: &str,
+) -> Result<(), AppError> {
+    let mut conn = redis.clone();
+    let key = failures_key(kind, value);
+    let failures: u64 = redis::cmd("INCR").arg(&key).query_async(&mut conn).await?;
+    if failures == 1 {
+        let _: () = redis::cmd("EXPIRE")
+            .arg(&key)
+            .arg(FAILURE_WINDOW_SECONDS)
+            .query_async(&mut conn)
+            .await?;
+    }
+
+    if failures >= MAX_FAILURES {
+        let _: () = redis::cmd("SET")
+            .arg(block_key(kind, value))
+            .arg(failures)
+            .arg("EX")
+            .arg(AUTO_BLOCK_SECONDS)
+            .query_async(&mut conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Whether the identity is automatically blocked
+pub async fn is_blocked(
+    redis: &MultiplexedConnection,
+    kind: &str,
+    value: &str,
+) -> Result<bool, AppError> {
+    let mut conn = redis.clone();
+    let blocked: bool = redis::cmd("EXISTS")
+        .arg(block_key(kind, value))
+        .query_async(&mut conn)
+        .await?;
+
+    Ok(blocked)
+}
+
+/// Lift an automatic block and forget the identity's failures. Returns
+/// whether it was blocked.
+pub async fn clear(
+    redis: &MultiplexedConnection,
+    kind: &str,
+    value: &str,
+) -> Result<bool, AppError> {
+    let mut conn = redis.clone();
+    let (blocked, _): (u64, u64) = redis::pipe()
+        .cmd("DEL")
+        .arg(block_key(kind, value))
+        .cmd("DEL")
+        .arg(failures_key(kind, value))
+        .query_async(&mut conn)
+        .await?;
+
+    Ok(blocked > 0)
+}
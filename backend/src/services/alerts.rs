@@ -0,0 +1,369 @@
+use serde_json::{json, Value};
+use sqlx::PgPool;
+
+use crate::{
+    config::Config,
+    error::AppError,
+    models::{
+        AlertEvent, AlertIntegration, Collection, ALERT_EVENT_FLOOR_MOVE, ALERT_KIND_DISCORD,
+        ALERT_KIND_TELEGRAM,
+    },
+    services::price_oracle::NATIVE_SOL_MINT,
+};
+
+/// Discord accepts up to 10 embeds per message; Telegram gets one message per
+/// event, so the same cap keeps a busy collection from tripping rate limits
+const MAX_EVENTS_PER_RUN: i64 = 10;
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+const COLOR_SALE: u32 = 0x14f195;
+const COLOR_LISTING: u32 = 0x9945ff;
+const COLOR_FLOOR: u32 = 0xf5a623;
+
+/// A message for one or more events, rendered for both platforms
+struct Post {
+    event_keys: Vec<String>,
+    embed: Value,
+    html: String,
+}
+
+enum PostError {
+    /// The webhook or chat is gone; retrying won't help
+    Permanent(String),
+    Transient(String),
+}
+
+/// Posts collection activity to connected Discord webhooks and Telegram chats
+#[derive(Clone)]
+pub struct AlertDispatcher {
+    http: reqwest::Client,
+    telegram_bot_token: Option<String>,
+    public_url: String,
+}
+
+impl AlertDispatcher {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            telegram_bot_token: config.telegram_bot_token.clone(),
+            public_url: config.public_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Post a confirmation so a mistyped webhook or a chat the bot can't
+    /// write to is caught when connecting rather than silently later
+    pub async fn verify(
+        &self,
+        kind: &str,
+        target: &str,
+        collection: &Collection,
+    ) -> Result<(), AppError> {
+        let post = Post {
+            event_keys: Vec::new(),
+            embed: json!({
+                "title": format!("Connected to {}", collection.name),
+                "url": self.collection_url(collection),
+                "description": "SolMint alerts for this collection will be posted here.",
+                "color": COLOR_LISTING
+            }),
+            html: format!(
+                "SolMint alerts for <a href=\"{}\">{}</a> will be posted here.",
+                self.collection_url(collection),
+                escape_html(&collection.name)
+            ),
+        };
+
+        let result = match kind {
+            ALERT_KIND_DISCORD => self.post_discord(target, &[post]).await,
+            _ => self.post_telegram(target, &post).await,
+        };
+        result.map_err(|e| match e {
+            PostError::Permanent(e) | PostError::Transient(e) => {
+                AppError::BadRequest(format!("could not post to {}: {}", kind, e))
+            }
+        })
+    }
+
+    /// Deliver pending events for every enabled integration; returns how many
+    /// events were posted
+    pub async fn dispatch(&self, pool: &PgPool) -> Result<u64, AppError> {
+        AlertIntegration::prune_deliveries(pool).await?;
+
+        let mut delivered = 0;
+        for integration in AlertIntegration::list_enabled(pool).await? {
+            if integration.kind == ALERT_KIND_TELEGRAM && self.telegram_bot_token.is_none() {
+                continue;
+            }
+            match self.dispatch_one(pool, &integration).await {
+                Ok(count) => delivered += count,
+                Err(e) => println!("Alert integration {} failed: {}", integration.id, e),
+            }
+        }
+
+        Ok(delivered)
+    }
+
+    async fn dispatch_one(
+        &self,
+        pool: &PgPool,
+        integration: &AlertIntegration,
+    ) -> Result<u64, AppError> {
+        let Some(collection) = Collection::find_by_id(pool, integration.collection_id).await?
+        else {
+            return Ok(0);
+        };
+
+        let mut posts: Vec<Post> = integration
+            .pending_events(pool, MAX_EVENTS_PER_RUN)
+            .await?
+            .iter()
+            .map(|event| self.render_event(&collection, event))
+            .collect();
+
+        // Floor moves are measured against the floor at the last post
+        let mut new_floor = None;
+        if integration
+            .events
+            .iter()
+            .any(|event| event == ALERT_EVENT_FLOOR_MOVE)
+        {
+            if let Some(floor) = integration.current_floor(pool).await? {
+                match integration.last_floor_price {
+                    None => integration.set_floor_baseline(pool, floor).await?,
+                    Some(previous) if previous > 0 => {
+                        let change_pct = (floor - previous) as f64 / previous as f64 * 100.0;
+                        if change_pct.abs() >= integration.floor_change_pct {
+                            posts.push(self.render_floor_move(&collection, previous, floor));
+                            new_floor = Some(floor);
+                        }
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        if posts.is_empty() {
+            return Ok(0);
+        }
+
+        let mut delivered = 0;
+        let result = match integration.kind.as_str() {
+            ALERT_KIND_DISCORD => {
+                let result = self.post_discord(&integration.target, &posts).await;
+                if result.is_ok() {
+                    let keys: Vec<String> = posts
+                        .iter()
+                        .flat_map(|post| post.event_keys.clone())
+                        .collect();
+                    integration.record_delivery(pool, &keys).await?;
+                    delivered = posts.len() as u64;
+                }
+                result
+            }
+            _ => {
+                // Record each message as it goes out so a mid-batch failure
+                // doesn't repeat the earlier ones
+                let mut result = Ok(());
+                for post in &posts {
+                    result = self.post_telegram(&integration.target, post).await;
+                    if result.is_err() {
+                        break;
+                    }
+                    integration.record_delivery(pool, &post.event_keys).await?;
+                    delivered += 1;
+                }
+                result
+            }
+        };
+
+        match result {
+            Ok(()) => {
+                if let Some(floor) = new_floor {
+                    integration.set_floor_baseline(pool, floor).await?;
+                }
+            }
+            Err(PostError::Permanent(e)) => integration.record_failure(pool, &e, true).await?,
+            Err(PostError::Transient(e)) => integration.record_failure(pool, &e, false).await?,
+        }
+
+        Ok(delivered)
+    }
+
+    fn render_event(&self, collection: &Collection, event: &AlertEvent) -> Post {
+        let name = event.nft_name.as_deref().unwrap_or(&event.nft_mint);
+        let url = format!("{}/nft/{}", self.public_url, event.nft_mint);
+        let price = format_price(event);
+
+        let (title, color, mut fields) = if event.event_type == "sale" {
+            (
+                format!("{} sold", name),
+                COLOR_SALE,
+                vec![
+                    json!({ "name": "Price", "value": price, "inline": true }),
+                    json!({
+                        "name": "Buyer",
+                        "value": short_address(event.buyer_address.as_deref().unwrap_or_default()),
+                        "inline": true
+                    }),
+                ],
+            )
+        } else {
+            (
+                format!("{} listed", name),
+                COLOR_LISTING,
+                vec![json!({ "name": "Price", "value": price, "inline": true })],
+            )
+        };
+        fields.push(json!({
+            "name": "Seller",
+            "value": short_address(&event.seller_address),
+            "inline": true
+        }));
+
+        let mut embed = json!({
+            "title": title,
+            "url": url,
+            "color": color,
+            "fields": fields,
+            "footer": { "text": collection.name },
+            "timestamp": event.occurred_at.to_rfc3339()
+        });
+        if let Some(image) = &event.image_url {
+            embed["thumbnail"] = json!({ "url": image });
+        }
+
+        let verb = if event.event_type == "sale" {
+            "sold for"
+        } else {
+            "listed for"
+        };
+        Post {
+            event_keys: vec![event.event_key.clone()],
+            embed,
+            html: format!(
+                "<b>{}</b> {} <b>{}</b>\n<a href=\"{}\">View on SolMint</a>",
+                escape_html(name),
+                verb,
+                escape_html(&price),
+                url
+            ),
+        }
+    }
+
+    fn render_floor_move(&self, collection: &Collection, previous: i64, floor: i64) -> Post {
+        let change_pct = (floor - previous) as f64 / previous as f64 * 100.0;
+        let direction = if floor > previous { "up" } else { "down" };
+        let summary = format!(
+            "Floor {} {:.1}%: {} → {}",
+            direction,
+            change_pct.abs(),
+            format_sol(previous),
+            format_sol(floor)
+        );
+
+        Post {
+            event_keys: Vec::new(),
+            embed: json!({
+                "title": format!("{} floor moved", collection.name),
+                "url": self.collection_url(collection),
+                "description": summary,
+                "color": COLOR_FLOOR
+            }),
+            html: format!(
+                "<b>{}</b>\n{}\n<a href=\"{}\">View on SolMint</a>",
+                escape_html(&collection.name),
+                escape_html(&summary),
+                self.collection_url(collection)
+            ),
+        }
+    }
+
+    fn collection_url(&self, collection: &Collection) -> String {
+        format!("{}/collection/{}", self.public_url, collection.id)
+    }
+
+    async fn post_discord(&self, webhook_url: &str, posts: &[Post]) -> Result<(), PostError> {
+        let embeds: Vec<&Value> = posts.iter().map(|post| &post.embed).collect();
+        let response = self
+            .http
+            .post(webhook_url)
+            .json(&json!({ "username": "SolMint", "embeds": embeds }))
+            .send()
+            .await
+            .map_err(|e| PostError::Transient(e.to_string()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let body = response.text().await.unwrap_or_default();
+        let message = format!("Discord returned {}: {}", status, body);
+        if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::UNAUTHORIZED {
+            Err(PostError::Permanent(message))
+        } else {
+            Err(PostError::Transient(message))
+        }
+    }
+
+    async fn post_telegram(&self, chat_id: &str, post: &Post) -> Result<(), PostError> {
+        let Some(token) = &self.telegram_bot_token else {
+            return Err(PostError::Transient(
+                "Telegram bot is not configured".to_string(),
+            ));
+        };
+
+        let response = self
+            .http
+            .post(format!("https://api.telegram.org/bot{}/sendMessage", token))
+            .json(&json!({
+                "chat_id": chat_id,
+                "text": post.html,
+                "parse_mode": "HTML",
+                "disable_web_page_preview": true
+            }))
+            .send()
+            .await
+            // The request URL carries the bot token; keep it out of logs
+            .map_err(|e| PostError::Transient(e.without_url().to_string()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let body = response.text().await.unwrap_or_default();
+        let message = format!("Telegram returned {}: {}", status, body);
+        // 403: the bot was removed from the chat; 400 "chat not found"
+        if status == reqwest::StatusCode::FORBIDDEN || body.contains("chat not found") {
+            Err(PostError::Permanent(message))
+        } else {
+            Err(PostError::Transient(message))
+        }
+    }
+}
+
+fn format_price(event: &AlertEvent) -> String {
+    match event.base_price {
+        Some(lamports) if event.currency_mint == NATIVE_SOL_MINT || event.event_type == "sale" => {
+            format_sol(lamports)
+        }
+        _ => format!("{} ({})", event.price, short_address(&event.currency_mint)),
+    }
+}
+
+fn format_sol(lamports: i64) -> String {
+    let sol = format!("{:.4}", lamports as f64 / LAMPORTS_PER_SOL);
+    format!("{} SOL", sol.trim_end_matches('0').trim_end_matches('.'))
+}
+
+fn short_address(address: &str) -> String {
+    if address.len() <= 10 {
+        return address.to_string();
+    }
+    format!("{}…{}", &address[..4], &address[address.len() - 4..])
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
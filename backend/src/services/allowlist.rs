@@ -0,0 +1,528 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+use std::{
+    collections::{BTreeMap, HashSet},
+    str::FromStr,
+};
+
+use crate::{
+    error::AppError,
+    services::merkle::{self, MerkleTree},
+};
+
+pub use solmint_client::{
+    AddAllowlistEntriesRequest, AllowlistEntryInput, ImportAllowlistQuery,
+    RemoveAllowlistEntriesRequest, UploadAllowlistRequest,
+};
+
+const MAX_ALLOWLIST_ENTRIES: usize = 100_000;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Allowlist {
+    pub config_address: String,
+    pub creator_address: String,
+    pub merkle_root: String,
+    pub entry_count: i64,
+    /// Cap applied to every wallet's allocation
+    pub max_allocation: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AllowlistEntry {
+    pub wallet_address: String,
+    pub allocation: i64,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AllowlistProof {
+    pub config_address: String,
+    pub wallet_address: String,
+    pub allocation: i64,
+    pub merkle_root: String,
+    /// Base58 sibling hashes from the leaf up
+    pub proof: serde_json::Value,
+}
+
+const ALLOWLIST_COLUMNS: &str = r#"
+    config_address, creator_address, merkle_root, entry_count, max_allocation,
+    created_at, updated_at
+"#;
+
+/// Replace the allowlist for a mint-gating config.
+///
+/// Leaves and nodes use the same scheme as holder snapshots
+/// (`services::merkle`), which the on-chain gate verifies with
+/// `sha256(0x00 || wallet || allocation_u64_le)` leaves and sorted-pair nodes.
+/// Only the creator that first uploaded a config's allowlist may replace it,
+/// and a launch's allowlist belongs to the launch creator; callers check the
+/// creator of an on-chain mint config.
+pub async fn store(
+    pool: &PgPool,
+    config_address: &str,
+    creator_address: &str,
+    req: UploadAllowlistRequest,
+) -> Result<Allowlist, AppError> {
+    parse_pubkey("config", config_address)?;
+    let entries = validate_entries(&req.entries)?;
+
+    let mut tx = pool.begin().await?;
+    lock_owned(&mut tx, config_address, creator_address).await?;
+    let allowlist = write(
+        &mut tx,
+        config_address,
+        creator_address,
+        req.max_allocation,
+        entries,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(allowlist)
+}
+
+/// Add wallets to an existing allowlist, updating the allocation of wallets
+/// already on it, and recompute the root
+pub async fn add_entries(
+    pool: &PgPool,
+    config_address: &str,
+    creator_address: &str,
+    req: AddAllowlistEntriesRequest,
+) -> Result<Allowlist, AppError> {
+    let additions = validate_entries(&req.entries)?;
+
+    let mut tx = pool.begin().await?;
+    let existing = lock_owned(&mut tx, config_address, creator_address)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Allowlist"))?;
+    let mut entries = load_entries(&mut tx, config_address).await?;
+    entries.extend(additions);
+    let allowlist = write(
+        &mut tx,
+        config_address,
+        creator_address,
+        existing.max_allocation.map(|cap| cap as u64),
+        entries,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(allowlist)
+}
+
+/// Remove wallets from an allowlist and recompute the root. Removing the
+/// last wallet deletes the allowlist.
+pub async fn remove_entries(
+    pool: &PgPool,
+    config_address: &str,
+    creator_address: &str,
+    req: RemoveAllowlistEntriesRequest,
+) -> Result<Option<Allowlist>, AppError> {
+    let mut tx = pool.begin().await?;
+    let existing = lock_owned(&mut tx, config_address, creator_address)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Allowlist"))?;
+    let mut entries = load_entries(&mut tx, config_address).await?;
+    for wallet in &req.wallets {
+        entries.remove(&parse_pubkey("wallet_address", wallet)?);
+    }
+
+    let allowlist = if entries.is_empty() {
+        delete_locked(&mut tx, config_address).await?;
+        None
+    } else {
+        Some(
+            write(
+                &mut tx,
+                config_address,
+                creator_address,
+                existing.max_allocation.map(|cap| cap as u64),
+                entries,
+            )
+            .await?,
+        )
+    };
+    tx.commit().await?;
+
+    Ok(allowlist)
+}
+
+/// Import `wallet_address[,allocation]` rows; a header row is skipped
+pub async fn import_csv(
+    pool: &PgPool,
+    config_address: &str,
+    creator_address: &str,
+    query: ImportAllowlistQuery,
+    csv: &str,
+) -> Result<Allowlist, AppError> {
+    let rows = parse_csv(csv)?;
+    match query.mode.as_deref() {
+        None | Some("merge") => {}
+        Some("replace") => {
+            return store(
+                pool,
+                config_address,
+                creator_address,
+                UploadAllowlistRequest {
+                    entries: rows,
+                    max_allocation: query.max_allocation,
+                    config_id: query.config_id,
+                },
+            )
+            .await
+        }
+        Some(_) => {
+            return Err(crate::error::bad_request_error(
+                "mode must be merge or replace",
+            ))
+        }
+    }
+
+    parse_pubkey("config", config_address)?;
+    let additions = validate_entries(&rows)?;
+
+    let mut tx = pool.begin().await?;
+    let existing = lock_owned(&mut tx, config_address, creator_address).await?;
+    let mut entries = load_entries(&mut tx, config_address).await?;
+    entries.extend(additions);
+    let existing_cap = existing.and_then(|allowlist| allowlist.max_allocation);
+    let max_allocation = query.max_allocation.or(existing_cap.map(|cap| cap as u64));
+    let allowlist = write(
+        &mut tx,
+        config_address,
+        creator_address,
+        max_allocation,
+        entries,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(allowlist)
+}
+
+pub async fn delete(
+    pool: &PgPool,
+    config_address: &str,
+    creator_address: &str,
+) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+    lock_owned(&mut tx, config_address, creator_address)
+        .await?
+        .ok_or_else(|| crate::error::not_found_error("Allowlist"))?;
+    delete_locked(&mut tx, config_address).await?;
+    tx.commit().await?;
+
+    println!("Deleted allowlist for {}", config_address);
+    Ok(())
+}
+
+pub async fn find(pool: &PgPool, config_address: &str) -> Result<Option<Allowlist>, AppError> {
+    let allowlist = sqlx::query_as::<_, Allowlist>(&format!(
+        "SELECT {} FROM mint_allowlists WHERE config_address = $1",
+        ALLOWLIST_COLUMNS
+    ))
+    .bind(config_address)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(allowlist)
+}
+
+pub async fn list_for_creator(
+    pool: &PgPool,
+    creator_address: &str,
+) -> Result<Vec<Allowlist>, AppError> {
+    let allowlists = sqlx::query_as::<_, Allowlist>(&format!(
+        "SELECT {} FROM mint_allowlists WHERE creator_address = $1 ORDER BY updated_at DESC",
+        ALLOWLIST_COLUMNS
+    ))
+    .bind(creator_address)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(allowlists)
+}
+
+/// Entries in wallet order, starting after the wallet `after`
+pub async fn entries(
+    pool: &PgPool,
+    config_address: &str,
+    after: Option<&str>,
+    limit: i64,
+) -> Result<Vec<AllowlistEntry>, AppError> {
+    let entries = sqlx::query_as::<_, AllowlistEntry>(
+        r#"
+        SELECT wallet_address, allocation
+        FROM mint_allowlist_entries
+        WHERE config_address = $1 AND ($2::text IS NULL OR wallet_address > $2)
+        ORDER BY wallet_address
+        LIMIT $3
+        "#,
+    )
+    .bind(config_address)
+    .bind(after)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(entries)
+}
+
+/// `None` when the wallet isn't on the config's allowlist
+pub async fn proof(
+    pool: &PgPool,
+    config_address: &str,
+    wallet_address: &str,
+) -> Result<Option<AllowlistProof>, AppError> {
+    let proof = sqlx::query_as::<_, AllowlistProof>(
+        r#"
+        SELECT e.config_address, e.wallet_address, e.allocation, a.merkle_root, e.proof
+        FROM mint_allowlist_entries e
+        JOIN mint_allowlists a ON a.config_address = e.config_address
+        WHERE e.config_address = $1 AND e.wallet_address = $2
+        "#,
+    )
+    .bind(config_address)
+    .bind(wallet_address)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(proof)
+}
+
+fn validate_entries(entries: &[AllowlistEntryInput]) -> Result<BTreeMap<Pubkey, u64>, AppError> {
+    let mut seen = HashSet::new();
+    let mut validated = BTreeMap::new();
+    for entry in entries {
+        let wallet = parse_pubkey("wallet_address", &entry.wallet_address)?;
+        if !seen.insert(wallet) {
+            return Err(AppError::BadRequest(format!(
+                "duplicate wallet {} in allowlist",
+                entry.wallet_address
+            )));
+        }
+        let allocation = entry.allocation.unwrap_or(1);
+        if allocation == 0 || allocation > i64::MAX as u64 {
+            return Err(AppError::BadRequest(format!(
+                "allocation for {} must be positive",
+                entry.wallet_address
+            )));
+        }
+        validated.insert(wallet, allocation);
+    }
+
+    Ok(validated)
+}
+
+fn parse_csv(csv: &str) -> Result<Vec<AllowlistEntryInput>, AppError> {
+    let mut rows = Vec::new();
+    for (index, line) in csv.lines().enumerate() {
+        let mut fields = line.split(',').map(str::trim);
+        let wallet = fields.next().unwrap_or_default().trim_matches('"');
+        if wallet.is_empty() {
+            continue;
+        }
+        if index == 0 && Pubkey::from_str(wallet).is_err() {
+            // Header row
+            continue;
+        }
+
+        let allocation = match fields.next().map(|field| field.trim_matches('"')) {
+            None | Some("") => None,
+            Some(field) => Some(field.parse::<u64>().map_err(|_| {
+                AppError::BadRequest(format!(
+                    "line {}: allocation '{}' is not a number",
+                    index + 1,
+                    field
+                ))
+            })?),
+        };
+        rows.push(AllowlistEntryInput {
+            wallet_address: wallet.to_string(),
+            allocation,
+        });
+    }
+
+    Ok(rows)
+}
+
+/// Lock a config's allowlist for changes by `creator_address`. Configs
+/// registered as launches belong to the launch creator even before an
+/// allowlist exists.
+async fn lock_owned(
+    tx: &mut Transaction<'_, Postgres>,
+    config_address: &str,
+    creator_address: &str,
+) -> Result<Option<Allowlist>, AppError> {
+    let launch_creator: Option<String> =
+        sqlx::query_scalar("SELECT creator_address FROM launches WHERE config_address = $1")
+            .bind(config_address)
+            .fetch_optional(&mut **tx)
+            .await?;
+    if launch_creator.is_some_and(|creator| creator != creator_address) {
+        return Err(AppError::Forbidden(
+            "allowlist for this config belongs to another creator".to_string(),
+        ));
+    }
+
+    let existing = sqlx::query_as::<_, Allowlist>(&format!(
+        "SELECT {} FROM mint_allowlists WHERE config_address = $1 FOR UPDATE",
+        ALLOWLIST_COLUMNS
+    ))
+    .bind(config_address)
+    .fetch_optional(&mut **tx)
+    .await?;
+    if existing
+        .as_ref()
+        .is_some_and(|allowlist| allowlist.creator_address != creator_address)
+    {
+        return Err(AppError::Forbidden(
+            "allowlist for this config belongs to another creator".to_string(),
+        ));
+    }
+
+    Ok(existing)
+}
+
+async fn load_entries(
+    tx: &mut Transaction<'_, Postgres>,
+    config_address: &str,
+) -> Result<BTreeMap<Pubkey, u64>, AppError> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT wallet_address, allocation FROM mint_allowlist_entries WHERE config_address = $1",
+    )
+    .bind(config_address)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    rows.into_iter()
+        .map(|(wallet, allocation)| {
+            Ok((parse_pubkey("wallet_address", &wallet)?, allocation as u64))
+        })
+        .collect()
+}
+
+/// Rebuild the tree over `entries` and replace the stored root and proofs.
+/// Allocations above `max_allocation` are lowered to it.
+async fn write(
+    tx: &mut Transaction<'_, Postgres>,
+    config_address: &str,
+    creator_address: &str,
+    max_allocation: Option<u64>,
+    entries: BTreeMap<Pubkey, u64>,
+) -> Result<Allowlist, AppError> {
+    if entries.is_empty() {
+        return Err(crate::error::bad_request_error(
+            "allowlist must have at least one entry",
+        ));
+    }
+    if entries.len() > MAX_ALLOWLIST_ENTRIES {
+        return Err(AppError::BadRequest(format!(
+            "allowlist must not exceed {} entries",
+            MAX_ALLOWLIST_ENTRIES
+        )));
+    }
+    if max_allocation.is_some_and(|cap| cap == 0 || cap > i64::MAX as u64) {
+        return Err(crate::error::bad_request_error(
+            "max_allocation must be positive",
+        ));
+    }
+
+    let leaves: Vec<(Pubkey, u64, merkle::MerkleHash)> = entries
+        .into_iter()
+        .map(|(wallet, allocation)| {
+            let allocation = max_allocation.map_or(allocation, |cap| allocation.min(cap));
+            (wallet, allocation, merkle::leaf(&wallet, allocation))
+        })
+        .collect();
+
+    let tree = MerkleTree::new(leaves.iter().map(|(_, _, leaf)| *leaf).collect());
+    let Some(root) = tree.root() else {
+        return Err(crate::error::bad_request_error(
+            "allowlist must have at least one entry",
+        ));
+    };
+
+    let mut wallets = Vec::with_capacity(leaves.len());
+    let mut allocations = Vec::with_capacity(leaves.len());
+    let mut proofs = Vec::with_capacity(leaves.len());
+    for (wallet, allocation, leaf) in &leaves {
+        let proof: Vec<String> = tree
+            .proof(leaf)
+            .unwrap_or_default()
+            .iter()
+            .map(merkle::encode)
+            .collect();
+        wallets.push(wallet.to_string());
+        allocations.push(*allocation as i64);
+        proofs.push(serde_json::to_value(proof)?);
+    }
+
+    let allowlist = sqlx::query_as::<_, Allowlist>(&format!(
+        r#"
+        INSERT INTO mint_allowlists (
+            config_address, creator_address, merkle_root, entry_count, max_allocation
+        )
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (config_address) DO UPDATE SET
+            merkle_root = EXCLUDED.merkle_root,
+            entry_count = EXCLUDED.entry_count,
+            max_allocation = EXCLUDED.max_allocation,
+            updated_at = NOW()
+        RETURNING {}
+        "#,
+        ALLOWLIST_COLUMNS
+    ))
+    .bind(config_address)
+    .bind(creator_address)
+    .bind(merkle::encode(&root))
+    .bind(leaves.len() as i64)
+    .bind(max_allocation.map(|cap| cap as i64))
+    .fetch_one(&mut **tx)
+    .await?;
+
+    sqlx::query("DELETE FROM mint_allowlist_entries WHERE config_address = $1")
+        .bind(config_address)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO mint_allowlist_entries (config_address, wallet_address, allocation, proof)
+        SELECT $1, * FROM UNNEST($2::varchar[], $3::bigint[], $4::jsonb[])
+        "#,
+    )
+    .bind(config_address)
+    .bind(&wallets)
+    .bind(&allocations)
+    .bind(&proofs)
+    .execute(&mut **tx)
+    .await?;
+
+    println!(
+        "Stored allowlist for {} with {} entries, root {}",
+        config_address, allowlist.entry_count, allowlist.merkle_root
+    );
+    Ok(allowlist)
+}
+
+async fn delete_locked(
+    tx: &mut Transaction<'_, Postgres>,
+    config_address: &str,
+) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM mint_allowlists WHERE config_address = $1")
+        .bind(config_address)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+fn parse_pubkey(field: &str, value: &str) -> Result<Pubkey, AppError> {
+    Pubkey::from_str(value).map_err(|_| {
+        AppError::BadRequest(format!("{} '{}' is not a valid public key", field, value))
+    })
+}
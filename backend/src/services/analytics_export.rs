@@ -0,0 +1,294 @@
+use arrow::{
+    array::{ArrayRef, Int64Array, StringArray, TimestampMicrosecondArray},
+    datatypes::{DataType, Field, Schema, TimeUnit},
+    record_batch::RecordBatch,
+};
+use aws_config::Region;
+use aws_sdk_s3::{primitives::ByteStream, Client};
+use chrono::{DateTime, NaiveDate, Utc};
+use parquet::{
+    arrow::ArrowWriter, basic::Compression, errors::ParquetError,
+    file::properties::WriterProperties,
+};
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+
+use crate::{config::Config, error::AppError};
+
+/// How far back the first run backfills partitions
+const BACKFILL_DAYS: i32 = 30;
+
+#[derive(Clone, Copy)]
+enum ColumnKind {
+    Text,
+    BigInt,
+    Timestamp,
+}
+
+struct Dataset {
+    name: &'static str,
+    columns: &'static [(&'static str, ColumnKind)],
+    /// Rows for one UTC day, bound as `$1` and `$2` (start inclusive, end exclusive)
+    sql: &'static str,
+}
+
+const DATASETS: &[Dataset] = &[
+    Dataset {
+        name: "sales",
+        columns: &[
+            ("id", ColumnKind::Text),
+            ("nft_mint", ColumnKind::Text),
+            ("collection_id", ColumnKind::Text),
+            ("seller_address", ColumnKind::Text),
+            ("buyer_address", ColumnKind::Text),
+            ("price", ColumnKind::BigInt),
+            ("currency_mint", ColumnKind::Text),
+            ("base_price", ColumnKind::BigInt),
+            ("marketplace_fee", ColumnKind::BigInt),
+            ("royalty_paid", ColumnKind::BigInt),
+            ("transaction_signature", ColumnKind::Text),
+            ("block_time", ColumnKind::Timestamp),
+        ],
+        sql: r#"
+            SELECT s.id::text AS id, s.nft_mint, n.collection_id::text AS collection_id,
+                   s.seller_address, s.buyer_address, s.price, s.currency_mint, s.base_price,
+                   s.marketplace_fee, s.royalty_paid, s.transaction_signature, s.block_time
+            FROM sales s
+            LEFT JOIN nfts n ON n.mint_address = s.nft_mint
+            WHERE s.block_time >= $1 AND s.block_time < $2
+            ORDER BY s.block_time
+        "#,
+    },
+    Dataset {
+        name: "listings",
+        columns: &[
+            ("id", ColumnKind::Text),
+            ("listing_address", ColumnKind::Text),
+            ("nft_mint", ColumnKind::Text),
+            ("collection_id", ColumnKind::Text),
+            ("seller_address", ColumnKind::Text),
+            ("price", ColumnKind::BigInt),
+            ("currency_mint", ColumnKind::Text),
+            ("status", ColumnKind::Text),
+            ("transaction_signature", ColumnKind::Text),
+            ("block_time", ColumnKind::Timestamp),
+            ("created_at", ColumnKind::Timestamp),
+            ("updated_at", ColumnKind::Timestamp),
+        ],
+        sql: r#"
+            SELECT l.id::text AS id, l.listing_address, l.nft_mint,
+                   n.collection_id::text AS collection_id, l.seller_address, l.price,
+                   l.currency_mint, l.status, l.transaction_signature, l.block_time,
+                   l.created_at, l.updated_at
+            FROM listings l
+            LEFT JOIN nfts n ON n.mint_address = l.nft_mint
+            WHERE l.created_at >= $1 AND l.created_at < $2
+            ORDER BY l.created_at
+        "#,
+    },
+    Dataset {
+        name: "activities",
+        columns: &[
+            ("activity_type", ColumnKind::Text),
+            ("nft_mint", ColumnKind::Text),
+            ("wallet_address", ColumnKind::Text),
+            ("counterparty_address", ColumnKind::Text),
+            ("price", ColumnKind::BigInt),
+            ("currency_mint", ColumnKind::Text),
+            ("transaction_signature", ColumnKind::Text),
+            ("occurred_at", ColumnKind::Timestamp),
+        ],
+        sql: r#"
+            SELECT 'list' AS activity_type, l.nft_mint, l.seller_address AS wallet_address,
+                   NULL::text AS counterparty_address, l.price, l.currency_mint,
+                   l.transaction_signature, COALESCE(l.block_time, l.created_at) AS occurred_at
+            FROM listings l
+            WHERE COALESCE(l.block_time, l.created_at) >= $1
+              AND COALESCE(l.block_time, l.created_at) < $2
+            UNION ALL
+            SELECT 'sale', s.nft_mint, s.seller_address, s.buyer_address, s.price,
+                   s.currency_mint, s.transaction_signature, s.block_time
+            FROM sales s
+            WHERE s.block_time >= $1 AND s.block_time < $2
+            ORDER BY occurred_at
+        "#,
+    },
+];
+
+/// Writes one Hive-style partition per dataset and UTC day,
+/// `<prefix>/<dataset>/date=YYYY-MM-DD/part-00000.parquet`, so the files can
+/// be queried directly from Athena or DuckDB.
+pub struct AnalyticsExporter {
+    db: PgPool,
+    s3: Client,
+    bucket: String,
+    prefix: String,
+}
+
+impl AnalyticsExporter {
+    pub async fn new(db: PgPool, config: &Config) -> Self {
+        let aws_config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(Region::new(config.s3_region.clone()))
+            .load()
+            .await;
+
+        Self {
+            db,
+            s3: Client::new(&aws_config),
+            bucket: config
+                .analytics_export_bucket
+                .clone()
+                .unwrap_or_else(|| config.s3_bucket.clone()),
+            prefix: config.analytics_export_prefix.trim_matches('/').to_string(),
+        }
+    }
+
+    /// Export every completed day within the backfill window that hasn't been
+    /// exported yet. Returns the number of partitions written.
+    pub async fn export_pending(&self) -> Result<usize, AppError> {
+        let mut exported = 0;
+
+        for dataset in DATASETS {
+            let pending: Vec<NaiveDate> = sqlx::query_scalar(
+                r#"
+                SELECT day::date
+                FROM generate_series(
+                    (NOW() AT TIME ZONE 'UTC')::date - $2::int,
+                    (NOW() AT TIME ZONE 'UTC')::date - 1,
+                    INTERVAL '1 day'
+                ) AS day
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM analytics_exports e
+                    WHERE e.dataset = $1 AND e.export_date = day::date
+                )
+                "#,
+            )
+            .bind(dataset.name)
+            .bind(BACKFILL_DAYS)
+            .fetch_all(&self.db)
+            .await?;
+
+            for date in pending {
+                self.export_day(dataset, date).await?;
+                exported += 1;
+            }
+        }
+
+        Ok(exported)
+    }
+
+    async fn export_day(&self, dataset: &Dataset, date: NaiveDate) -> Result<(), AppError> {
+        let start = date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        let end = start + chrono::Duration::days(1);
+
+        let rows = sqlx::query(dataset.sql)
+            .bind(start)
+            .bind(end)
+            .fetch_all(&self.db)
+            .await?;
+
+        let key = format!(
+            "{}/{}/date={}/part-00000.parquet",
+            self.prefix,
+            dataset.name,
+            date.format("%Y-%m-%d")
+        );
+
+        // Empty days are recorded but not uploaded, so they read as empty partitions
+        if !rows.is_empty() {
+            let body = write_parquet(dataset, &rows)?;
+            self.s3
+                .put_object()
+                .bucket(&self.bucket)
+                .key(&key)
+                .content_type("application/vnd.apache.parquet")
+                .body(ByteStream::from(body))
+                .send()
+                .await
+                .map_err(|e| AppError::Storage(e.to_string()))?;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO analytics_exports (dataset, export_date, object_key, row_count)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (dataset, export_date) DO UPDATE SET
+                object_key = EXCLUDED.object_key,
+                row_count = EXCLUDED.row_count,
+                exported_at = NOW()
+            "#,
+        )
+        .bind(dataset.name)
+        .bind(date)
+        .bind(&key)
+        .bind(rows.len() as i64)
+        .execute(&self.db)
+        .await?;
+
+        println!(
+            "Exported {} {} rows for {} to s3://{}/{}",
+            rows.len(),
+            dataset.name,
+            date,
+            self.bucket,
+            key
+        );
+        Ok(())
+    }
+}
+
+fn write_parquet(dataset: &Dataset, rows: &[PgRow]) -> Result<Vec<u8>, AppError> {
+    let fields: Vec<Field> = dataset
+        .columns
+        .iter()
+        .map(|(name, kind)| {
+            let data_type = match kind {
+                ColumnKind::Text => DataType::Utf8,
+                ColumnKind::BigInt => DataType::Int64,
+                ColumnKind::Timestamp => {
+                    DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+                }
+            };
+            Field::new(*name, data_type, true)
+        })
+        .collect();
+    let schema = Arc::new(Schema::new(fields));
+
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(dataset.columns.len());
+    for (name, kind) in dataset.columns {
+        let array: ArrayRef = match kind {
+            ColumnKind::Text => Arc::new(
+                rows.iter()
+                    .map(|row| row.try_get::<Option<String>, _>(*name))
+                    .collect::<Result<StringArray, _>>()?,
+            ),
+            ColumnKind::BigInt => Arc::new(
+                rows.iter()
+                    .map(|row| row.try_get::<Option<i64>, _>(*name))
+                    .collect::<Result<Int64Array, _>>()?,
+            ),
+            ColumnKind::Timestamp => Arc::new(
+                rows.iter()
+                    .map(|row| {
+                        row.try_get::<Option<DateTime<Utc>>, _>(*name)
+                            .map(|ts| ts.map(|ts| ts.timestamp_micros()))
+                    })
+                    .collect::<Result<TimestampMicrosecondArray, _>>()?
+                    .with_timezone("UTC"),
+            ),
+        };
+        arrays.push(array);
+    }
+
+    let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(ParquetError::from)?;
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+
+    let mut buffer = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut buffer, schema, Some(properties))?;
+    writer.write(&batch)?;
+    writer.close()?;
+
+    Ok(buffer)
+}
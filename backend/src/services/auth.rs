@@ -0,0 +1,101 @@
+//! Sign-In With Solana: a wallet proves it holds its key by signing a
+//! challenge message, and gets a JWT naming it for later requests
+
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::str::FromStr;
+
+use crate::{config::Config, error::AppError};
+
+/// How long a wallet has to sign a challenge
+pub const CHALLENGE_TTL_SECONDS: u64 = 300;
+/// How long an issued token stays valid
+const TOKEN_TTL_HOURS: i64 = 24;
+
+/// Claims of the tokens issued to signed-in wallets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletClaims {
+    /// The wallet address
+    pub sub: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+/// The message a wallet signs to sign in with `nonce` from `origin`. The
+/// wallet shows the origin's domain, so a signature collected by another
+/// site can't be replayed here.
+pub fn challenge_message(
+    origin: &str,
+    wallet_address: &str,
+    nonce: &str,
+    issued_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+) -> String {
+    format!(
+        "{} wants you to sign in with your Solana account:\n{}\n\nURI: {}\nNonce: {}\nIssued At: {}\nExpiration Time: {}",
+        domain(origin),
+        wallet_address,
+        origin,
+        nonce,
+        issued_at.to_rfc3339(),
+        expires_at.to_rfc3339()
+    )
+}
+
+/// `https://solmint.io/` to `solmint.io`
+fn domain(origin: &str) -> &str {
+    let without_scheme = origin.split_once("://").map_or(origin, |(_, rest)| rest);
+    without_scheme.split('/').next().unwrap_or(without_scheme)
+}
+
+/// Check `signature` (base58) is `wallet_address`'s signature of `message`
+pub fn verify_signature(
+    wallet_address: &str,
+    message: &str,
+    signature: &str,
+) -> Result<(), AppError> {
+    let wallet = Pubkey::from_str(wallet_address)
+        .map_err(|_| crate::error::bad_request_error("Invalid wallet address"))?;
+    let signature = Signature::from_str(signature)
+        .map_err(|_| crate::error::bad_request_error("signature must be base58"))?;
+    if !signature.verify(wallet.as_ref(), message.as_bytes()) {
+        return Err(AppError::Unauthorized);
+    }
+
+    Ok(())
+}
+
+/// A token for `wallet_address`, signed with `JWT_SECRET`, and when it expires
+pub fn issue_token(
+    config: &Config,
+    wallet_address: &str,
+) -> Result<(String, DateTime<Utc>), AppError> {
+    let issued_at = Utc::now();
+    let expires_at = issued_at + Duration::hours(TOKEN_TTL_HOURS);
+    let token = jsonwebtoken::encode(
+        &Header::new(Algorithm::HS256),
+        &WalletClaims {
+            sub: wallet_address.to_string(),
+            iat: issued_at.timestamp(),
+            exp: expires_at.timestamp(),
+        },
+        &EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+    )
+    .map_err(|e| AppError::SerializationError(e.to_string()))?;
+
+    Ok((token, expires_at))
+}
+
+/// The claims of a token issued by [`issue_token`]; expired, tampered or
+/// foreign tokens are `Unauthorized`
+pub fn verify_token(config: &Config, token: &str) -> Result<WalletClaims, AppError> {
+    jsonwebtoken::decode::<WalletClaims>(
+        token,
+        &DecodingKey::from_secret(config.jwt_secret.as_bytes()),
+        &Validation::new(Algorithm::HS256),
+    )
+    .map(|data| data.claims)
+    .map_err(|_| AppError::Unauthorized)
+}
@@ -0,0 +1,356 @@
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::Sha256;
+use sqlx::PgPool;
+
+use crate::{
+    config::Config,
+    error::AppError,
+    models::{
+        CheckoutOrder, OrderUpdate, EVENT_SOURCE_RECONCILIATION, EVENT_SOURCE_WEBHOOK,
+        ORDER_CANCELLED, ORDER_COMPLETED, ORDER_DELIVERING, ORDER_DELIVERY_FAILED, ORDER_EXPIRED,
+        ORDER_PAID, ORDER_PAYMENT_FAILED, ORDER_REFUNDED,
+    },
+};
+
+/// Webhooks older than this are rejected so a captured request can't be replayed later
+const WEBHOOK_TOLERANCE_SECONDS: i64 = 300;
+/// Paid orders without an indexed purchase after this long are refunded
+const DELIVERY_TIMEOUT_MINUTES: i64 = 30;
+
+#[derive(Debug, Deserialize)]
+struct ProviderOrder {
+    id: String,
+    checkout_url: String,
+    /// Minor units of `currency`
+    amount: i64,
+    currency: String,
+}
+
+/// Webhook payload from the checkout provider
+#[derive(Debug, Deserialize)]
+pub struct ProviderEvent {
+    /// payment.succeeded, payment.failed, delivery.submitted, delivery.failed
+    /// or refund.succeeded
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub order_id: String,
+    pub transaction_signature: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ReconcileSummary {
+    pub expired: u64,
+    pub cancelled: u64,
+    pub completed: u64,
+    pub failed: u64,
+}
+
+/// Client for a Crossmint-style fiat checkout provider: the buyer pays by
+/// card on the provider's hosted page and the provider buys the listing on
+/// chain for the buyer's wallet
+#[derive(Clone)]
+pub struct CheckoutProvider {
+    http: reqwest::Client,
+    api_url: String,
+    api_key: String,
+    webhook_secret: String,
+    public_url: String,
+}
+
+impl CheckoutProvider {
+    /// `None` when fiat checkout isn't configured
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let (Some(api_url), Some(api_key), Some(webhook_secret)) = (
+            &config.checkout_api_url,
+            &config.checkout_api_key,
+            &config.checkout_webhook_secret,
+        ) else {
+            return None;
+        };
+
+        Some(Self {
+            http: reqwest::Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_key: api_key.clone(),
+            webhook_secret: webhook_secret.clone(),
+            public_url: config.public_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Create the provider order and its hosted checkout page
+    pub async fn open(
+        &self,
+        pool: &PgPool,
+        order: &CheckoutOrder,
+    ) -> Result<CheckoutOrder, AppError> {
+        let listing_address: Option<String> =
+            sqlx::query_scalar("SELECT listing_address FROM listings WHERE id = $1")
+                .bind(order.listing_id)
+                .fetch_one(pool)
+                .await?;
+
+        let response = self
+            .http
+            .post(format!("{}/orders", self.api_url))
+            .header("X-API-KEY", &self.api_key)
+            .json(&json!({
+                "reference": order.id,
+                "recipient": {
+                    "wallet_address": order.buyer_wallet,
+                    "email": order.email
+                },
+                "line_item": {
+                    "chain": "solana",
+                    "listing_address": listing_address,
+                    "nft_mint": order.nft_mint,
+                    "seller_address": order.seller_address,
+                    "price": order.price,
+                    "currency_mint": order.currency_mint
+                },
+                "success_url": format!("{}/checkout/{}", self.public_url, order.id),
+                "cancel_url": format!("{}/nft/{}", self.public_url, order.nft_mint),
+                "expires_at": order.expires_at.to_rfc3339()
+            }))
+            .send()
+            .await
+            .map_err(|e| AppError::Upstream(format!("Checkout provider request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            // Release the listing straight away rather than at expiry
+            order
+                .transition(
+                    pool,
+                    ORDER_CANCELLED,
+                    EVENT_SOURCE_RECONCILIATION,
+                    OrderUpdate {
+                        failure_reason: Some("provider rejected the order"),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            return Err(AppError::Upstream(format!(
+                "Checkout provider returned {}: {}",
+                status, body
+            )));
+        }
+
+        let provider_order: ProviderOrder = response.json().await.map_err(|e| {
+            AppError::Upstream(format!("Invalid checkout provider response: {}", e))
+        })?;
+
+        order
+            .attach_provider_order(
+                pool,
+                &provider_order.id,
+                &provider_order.checkout_url,
+                provider_order.amount,
+                &provider_order.currency,
+            )
+            .await
+    }
+
+    /// Check a `Checkout-Signature: t=<unix seconds>,v1=<hex hmac>` header,
+    /// where the HMAC-SHA256 is over `<t>.<body>` keyed with the webhook secret
+    pub fn verify_signature(&self, header: &str, body: &[u8]) -> Result<(), AppError> {
+        let mut timestamp = None;
+        let mut signatures = Vec::new();
+        for part in header.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+                Some(("v1", value)) => signatures.push(value),
+                _ => {}
+            }
+        }
+
+        let Some(timestamp) = timestamp else {
+            return Err(AppError::Unauthorized);
+        };
+        if (Utc::now().timestamp() - timestamp).abs() > WEBHOOK_TOLERANCE_SECONDS {
+            return Err(AppError::Unauthorized);
+        }
+
+        let valid = signatures.iter().any(|signature| {
+            let Ok(expected) = hex::decode(signature) else {
+                return false;
+            };
+            let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(self.webhook_secret.as_bytes()) else {
+                return false;
+            };
+            mac.update(timestamp.to_string().as_bytes());
+            mac.update(b".");
+            mac.update(body);
+            mac.verify_slice(&expected).is_ok()
+        });
+
+        if valid {
+            Ok(())
+        } else {
+            Err(AppError::Unauthorized)
+        }
+    }
+
+    /// Apply a verified webhook. Unknown orders and event types, and events
+    /// that don't fit the order's current status (e.g. replays), are ignored.
+    pub async fn handle_event(&self, pool: &PgPool, event: &ProviderEvent) -> Result<(), AppError> {
+        let Some(order) = CheckoutOrder::find_by_provider_order(pool, &event.order_id).await?
+        else {
+            println!("Checkout webhook for unknown order {}", event.order_id);
+            return Ok(());
+        };
+
+        let (to, update) = match event.event_type.as_str() {
+            "payment.succeeded" => (ORDER_PAID, OrderUpdate::default()),
+            "payment.failed" => (
+                ORDER_PAYMENT_FAILED,
+                OrderUpdate {
+                    failure_reason: Some(event.reason.as_deref().unwrap_or("payment failed")),
+                    ..Default::default()
+                },
+            ),
+            "delivery.submitted" => (
+                ORDER_DELIVERING,
+                OrderUpdate {
+                    sale_signature: event.transaction_signature.as_deref(),
+                    ..Default::default()
+                },
+            ),
+            "delivery.failed" => (
+                ORDER_DELIVERY_FAILED,
+                OrderUpdate {
+                    failure_reason: Some(event.reason.as_deref().unwrap_or("purchase failed")),
+                    ..Default::default()
+                },
+            ),
+            "refund.succeeded" => (ORDER_REFUNDED, OrderUpdate::default()),
+            _ => return Ok(()),
+        };
+
+        if order
+            .transition(pool, to, EVENT_SOURCE_WEBHOOK, update)
+            .await?
+            .is_none()
+        {
+            println!(
+                "Ignored checkout webhook {} for order {} in status {}",
+                event.event_type, order.id, order.status
+            );
+            // A payment that lands after the order was released can't be fulfilled
+            if event.event_type == "payment.succeeded"
+                && (order.status == ORDER_EXPIRED || order.status == ORDER_CANCELLED)
+            {
+                self.provider_action(&order, "refund").await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Release unpaid orders, complete paid orders whose purchase has been
+    /// indexed and refund those whose purchase didn't happen
+    pub async fn reconcile(&self, pool: &PgPool) -> Result<ReconcileSummary, AppError> {
+        let mut summary = ReconcileSummary::default();
+
+        for (order, expired) in CheckoutOrder::stale_unpaid(pool).await? {
+            let (to, reason) = if expired {
+                (ORDER_EXPIRED, "payment window elapsed")
+            } else {
+                (ORDER_CANCELLED, "listing is no longer available")
+            };
+            let update = OrderUpdate {
+                failure_reason: Some(reason),
+                ..Default::default()
+            };
+            if order
+                .transition(pool, to, EVENT_SOURCE_RECONCILIATION, update)
+                .await?
+                .is_some()
+            {
+                self.provider_action(&order, "cancel").await;
+                if expired {
+                    summary.expired += 1;
+                } else {
+                    summary.cancelled += 1;
+                }
+            }
+        }
+
+        let timeout = Utc::now() - Duration::minutes(DELIVERY_TIMEOUT_MINUTES);
+        for order in CheckoutOrder::awaiting_delivery(pool).await? {
+            let failure = match order.matching_sale(pool).await? {
+                Some((signature, buyer))
+                    if buyer == order.buyer_wallet
+                        || order.sale_signature.as_deref() == Some(signature.as_str()) =>
+                {
+                    let update = OrderUpdate {
+                        sale_signature: Some(&signature),
+                        ..Default::default()
+                    };
+                    if order
+                        .transition(pool, ORDER_COMPLETED, EVENT_SOURCE_RECONCILIATION, update)
+                        .await?
+                        .is_some()
+                    {
+                        summary.completed += 1;
+                    }
+                    continue;
+                }
+                Some(_) => "sold to another buyer",
+                None if order.paid_at.is_some_and(|paid_at| paid_at < timeout) => {
+                    "no purchase was indexed in time"
+                }
+                None => continue,
+            };
+
+            let update = OrderUpdate {
+                failure_reason: Some(failure),
+                ..Default::default()
+            };
+            if order
+                .transition(
+                    pool,
+                    ORDER_DELIVERY_FAILED,
+                    EVENT_SOURCE_RECONCILIATION,
+                    update,
+                )
+                .await?
+                .is_some()
+            {
+                self.provider_action(&order, "refund").await;
+                summary.failed += 1;
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Ask the provider to cancel or refund an order. Failures are logged;
+    /// the order's status already records what's owed.
+    async fn provider_action(&self, order: &CheckoutOrder, action: &str) {
+        let Some(provider_order_id) = &order.provider_order_id else {
+            return;
+        };
+
+        let result = self
+            .http
+            .post(format!(
+                "{}/orders/{}/{}",
+                self.api_url, provider_order_id, action
+            ))
+            .header("X-API-KEY", &self.api_key)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = result {
+            println!(
+                "Checkout provider {} for order {} failed: {}",
+                action, order.id, e
+            );
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use chrono::Duration;
+use solana_sdk::pubkey::Pubkey;
+use sqlx::PgPool;
+use std::{collections::HashSet, str::FromStr};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    error::AppError,
+    models::{
+        Collection, CollectionImport, CreateCollectionRequest, CreateNftRequest,
+        ImportCollectionRequest, Nft, IMPORT_COMPLETED, IMPORT_FAILED,
+    },
+    services::{das::DasClient, rarity},
+};
+
+const MAX_HASHLIST_SIZE: usize = 25_000;
+/// Mints fetched per run; runs are 30 seconds apart
+const ITEMS_PER_RUN: i64 = 100;
+/// Collection listing pages fetched per run
+const PAGES_PER_RUN: i32 = 5;
+const PAGE_SIZE: u32 = 1000;
+/// Pause between DAS and metadata requests, keeping the import under 5 rps
+const REQUEST_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+const METADATA_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// Long enough for a full run; a crashed worker's import is resumed after this
+const LEASE_MINUTES: i64 = 5;
+
+/// Backfills collections that were minted before the indexer was watching:
+/// every mint's metadata is read through DAS, missing NFTs are created and
+/// existing ones are linked to the collection
+#[derive(Clone)]
+pub struct CollectionImporter {
+    das: DasClient,
+    http: reqwest::Client,
+}
+
+impl CollectionImporter {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            das: DasClient::new(config.das_rpc_url().to_string()),
+            http: reqwest::Client::builder()
+                .timeout(METADATA_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Validate a request, resolve or create the target collection and queue the import
+    pub async fn start(
+        &self,
+        pool: &PgPool,
+        req: ImportCollectionRequest,
+    ) -> Result<(Collection, CollectionImport), AppError> {
+        let mints = match (&req.mints, &req.collection_address) {
+            (Some(mints), None) => parse_hashlist(mints)?,
+            (None, Some(address)) => {
+                parse_address(address)?;
+                Vec::new()
+            }
+            _ => {
+                return Err(crate::error::bad_request_error(
+                    "provide either mints or collection_address",
+                ))
+            }
+        };
+
+        let collection = self.target_collection(pool, &req).await?;
+        let import = CollectionImport::create(
+            pool,
+            collection.id,
+            &mints,
+            req.collection_address.as_deref(),
+        )
+        .await?;
+
+        Ok((collection, import))
+    }
+
+    async fn target_collection(
+        &self,
+        pool: &PgPool,
+        req: &ImportCollectionRequest,
+    ) -> Result<Collection, AppError> {
+        if let Some(collection_id) = req.collection_id {
+            return Collection::find_by_id(pool, collection_id)
+                .await?
+                .ok_or_else(|| crate::error::not_found_error("Collection"));
+        }
+
+        // Default the collection's details to those of the collection NFT
+        let collection_asset = match &req.collection_address {
+            Some(address) => {
+                if let Some(collection) =
+                    Collection::find_by_collection_address(pool, address).await?
+                {
+                    return Ok(collection);
+                }
+                Some(self.das.get_asset(address).await?)
+            }
+            None => None,
+        };
+        let metadata = collection_asset
+            .as_ref()
+            .map(|asset| &asset.content.metadata);
+
+        let name = req
+            .name
+            .clone()
+            .or_else(|| metadata.and_then(|metadata| metadata.name.clone()))
+            .ok_or_else(|| crate::error::bad_request_error("name is required"))?;
+        let creator_address = req
+            .creator_address
+            .clone()
+            .or_else(|| {
+                collection_asset
+                    .as_ref()
+                    .and_then(|asset| asset.creator_address())
+                    .map(str::to_string)
+            })
+            .ok_or_else(|| crate::error::bad_request_error("creator_address is required"))?;
+        parse_address(&creator_address)?;
+
+        Collection::create(
+            pool,
+            CreateCollectionRequest {
+                name,
+                symbol: req
+                    .symbol
+                    .clone()
+                    .or_else(|| metadata.and_then(|metadata| metadata.symbol.clone())),
+                description: req
+                    .description
+                    .clone()
+                    .or_else(|| metadata.and_then(|metadata| metadata.description.clone())),
+                image_url: req.image_url.clone().or_else(|| {
+                    collection_asset
+                        .as_ref()
+                        .and_then(|asset| asset.content.links.image.clone())
+                }),
+                creator_address,
+                verified: req.verified.unwrap_or(false),
+                collection_address: req.collection_address.clone(),
+            },
+        )
+        .await
+    }
+
+    /// Advance the oldest open import by one batch. Returns the import worked
+    /// on, if there was one.
+    pub async fn run(&self, pool: &PgPool) -> Result<Option<CollectionImport>, AppError> {
+        let Some(import) =
+            CollectionImport::claim_next(pool, Duration::minutes(LEASE_MINUTES)).await?
+        else {
+            return Ok(None);
+        };
+
+        if let Err(e) = self.process(pool, &import).await {
+            import.record_error(pool, &e.to_string()).await?;
+            import.release(pool).await?;
+            return Err(e);
+        }
+
+        CollectionImport::find_by_id(pool, import.id).await
+    }
+
+    async fn process(&self, pool: &PgPool, import: &CollectionImport) -> Result<(), AppError> {
+        if let (Some(first_page), Some(address)) = (import.next_page, &import.collection_address) {
+            for page in first_page..first_page + PAGES_PER_RUN {
+                let assets = self
+                    .das
+                    .get_assets_by_collection(address, page as u32, PAGE_SIZE)
+                    .await?;
+                tokio::time::sleep(REQUEST_INTERVAL).await;
+
+                if page == 1 && assets.is_empty() {
+                    return import
+                        .finish(pool, IMPORT_FAILED, Some("no assets found for collection"))
+                        .await;
+                }
+
+                let last_page = assets.len() < PAGE_SIZE as usize;
+                let mints: Vec<String> = assets.into_iter().map(|asset| asset.id).collect();
+                import
+                    .add_items(pool, &mints, (!last_page).then_some(page + 1))
+                    .await?;
+                if last_page {
+                    break;
+                }
+            }
+        }
+
+        for mint in import.pending_items(pool, ITEMS_PER_RUN).await? {
+            match self.ingest(pool, import.collection_id, &mint).await {
+                Ok(()) => import.item_imported(pool, &mint).await?,
+                Err(e) => import.item_failed(pool, &mint, &e.to_string()).await?,
+            }
+            tokio::time::sleep(REQUEST_INTERVAL).await;
+        }
+
+        let import = CollectionImport::find_by_id(pool, import.id)
+            .await?
+            .ok_or_else(|| crate::error::not_found_error("Collection import"))?;
+        if import.next_page.is_some() || !import.pending_items(pool, 1).await?.is_empty() {
+            return import.release(pool).await;
+        }
+
+        rarity::recompute_collection(pool, import.collection_id).await?;
+        import.finish(pool, IMPORT_COMPLETED, None).await
+    }
+
+    /// Create or link one NFT
+    async fn ingest(&self, pool: &PgPool, collection_id: Uuid, mint: &str) -> Result<(), AppError> {
+        if let Some(nft) = Nft::find_by_mint(pool, mint).await? {
+            if nft.collection_id != Some(collection_id) {
+                Nft::set_collection(pool, mint, collection_id).await?;
+            }
+            return Ok(());
+        }
+
+        let asset = self.das.get_asset(mint).await?;
+        let attributes = asset.attributes(&self.http).await;
+        let metadata = asset.content.metadata.clone();
+        let links = asset.content.links.clone();
+        let creator_address = asset
+            .creator_address()
+            .unwrap_or(&asset.ownership.owner)
+            .to_string();
+
+        Nft::create(
+            pool,
+            CreateNftRequest {
+                mint_address: asset.id.clone(),
+                collection_id: Some(collection_id),
+                name: metadata.name.unwrap_or_else(|| asset.id.clone()),
+                description: metadata.description,
+                image_url: links.image,
+                animation_url: links.animation_url,
+                external_url: links.external_url,
+                attributes,
+                creator_address,
+                current_owner: asset.ownership.owner.clone(),
+                is_compressed: asset.compression.compressed,
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+fn parse_address(address: &str) -> Result<(), AppError> {
+    Pubkey::from_str(address)
+        .map(|_| ())
+        .map_err(|_| AppError::BadRequest(format!("invalid address: {}", address)))
+}
+
+/// Trimmed, de-duplicated mints in hashlist order
+fn parse_hashlist(mints: &[String]) -> Result<Vec<String>, AppError> {
+    if mints.is_empty() {
+        return Err(crate::error::bad_request_error("mints is empty"));
+    }
+    if mints.len() > MAX_HASHLIST_SIZE {
+        return Err(AppError::BadRequest(format!(
+            "hashlists are limited to {} mints",
+            MAX_HASHLIST_SIZE
+        )));
+    }
+
+    let mut seen = HashSet::new();
+    let mut parsed = Vec::with_capacity(mints.len());
+    for mint in mints {
+        let mint = mint.trim();
+        parse_address(mint)?;
+        if seen.insert(mint) {
+            parsed.push(mint.to_string());
+        }
+    }
+
+    Ok(parsed)
+}
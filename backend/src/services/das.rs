@@ -0,0 +1,273 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::{error::AppError, models::NftAttribute};
+
+/// `ConcurrentMerkleTreeHeader`: account type (1) + version (1) + V1 data (54)
+const TREE_HEADER_SIZE: usize = 56;
+const MAX_BUFFER_SIZE_OFFSET: usize = 2;
+const MAX_DEPTH_OFFSET: usize = 6;
+
+/// Response of the DAS `getAssetProof` method
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetProof {
+    pub root: String,
+    /// Sibling hashes from the leaf up to the root
+    pub proof: Vec<String>,
+    pub node_index: u64,
+    pub leaf: String,
+    pub tree_id: String,
+}
+
+/// Response of the DAS `getAsset` method, trimmed to what indexing needs
+#[derive(Debug, Clone, Deserialize)]
+pub struct DasAsset {
+    pub id: String,
+    #[serde(default)]
+    pub content: DasContent,
+    #[serde(default)]
+    pub grouping: Vec<DasGrouping>,
+    #[serde(default)]
+    pub creators: Vec<DasCreator>,
+    pub ownership: DasOwnership,
+    #[serde(default)]
+    pub compression: DasCompression,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DasContent {
+    pub json_uri: Option<String>,
+    #[serde(default)]
+    pub metadata: DasMetadata,
+    #[serde(default)]
+    pub links: DasLinks,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DasMetadata {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub description: Option<String>,
+    pub attributes: Option<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DasLinks {
+    pub image: Option<String>,
+    pub animation_url: Option<String>,
+    pub external_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DasGrouping {
+    pub group_key: String,
+    pub group_value: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DasCreator {
+    pub address: String,
+    #[serde(default)]
+    pub verified: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DasOwnership {
+    pub owner: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DasCompression {
+    #[serde(default)]
+    pub compressed: bool,
+}
+
+impl DasAsset {
+    /// Verified collection the asset belongs to, if any
+    pub fn collection_address(&self) -> Option<&str> {
+        self.grouping
+            .iter()
+            .find(|group| group.group_key == "collection")
+            .map(|group| group.group_value.as_str())
+    }
+
+    /// First verified creator, falling back to the first listed one
+    pub fn creator_address(&self) -> Option<&str> {
+        self.creators
+            .iter()
+            .find(|creator| creator.verified)
+            .or_else(|| self.creators.first())
+            .map(|creator| creator.address.as_str())
+    }
+
+    /// Inline attributes, or those in the off-chain JSON for DAS providers
+    /// that don't inline them
+    pub async fn attributes(&self, http: &reqwest::Client) -> Option<Vec<NftAttribute>> {
+        if let Some(attributes) = &self.content.metadata.attributes {
+            return Some(parse_attributes(attributes));
+        }
+
+        let uri = self
+            .content
+            .json_uri
+            .as_ref()
+            .filter(|uri| !uri.is_empty())?;
+        let metadata: serde_json::Value = http
+            .get(uri)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .ok()?
+            .json()
+            .await
+            .ok()?;
+
+        metadata
+            .get("attributes")
+            .and_then(|attributes| attributes.as_array())
+            .map(|attributes| parse_attributes(attributes))
+    }
+}
+
+fn parse_attributes(attributes: &[serde_json::Value]) -> Vec<NftAttribute> {
+    attributes
+        .iter()
+        .filter_map(|attribute| serde_json::from_value(attribute.clone()).ok())
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct AssetPage {
+    items: Vec<DasAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+/// Client for a Digital Asset Standard (Metaplex Read API) capable RPC node
+#[derive(Clone)]
+pub struct DasClient {
+    url: String,
+    http: reqwest::Client,
+}
+
+impl DasClient {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn get_asset_proof(&self, asset_id: &str) -> Result<AssetProof, AppError> {
+        self.call("getAssetProof", json!({ "id": asset_id }))
+            .await?
+            .ok_or_else(|| crate::error::not_found_error("Asset proof"))
+    }
+
+    pub async fn get_asset(&self, asset_id: &str) -> Result<DasAsset, AppError> {
+        self.call("getAsset", json!({ "id": asset_id }))
+            .await?
+            .ok_or_else(|| crate::error::not_found_error("Asset"))
+    }
+
+    /// One page (1-based) of the assets in a verified collection
+    pub async fn get_assets_by_collection(
+        &self,
+        collection_address: &str,
+        page: u32,
+        limit: u32,
+    ) -> Result<Vec<DasAsset>, AppError> {
+        let page: Option<AssetPage> = self
+            .call(
+                "getAssetsByGroup",
+                json!({
+                    "groupKey": "collection",
+                    "groupValue": collection_address,
+                    "page": page,
+                    "limit": limit
+                }),
+            )
+            .await?;
+
+        Ok(page.map(|page| page.items).unwrap_or_default())
+    }
+
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<Option<T>, AppError> {
+        let response: RpcResponse<T> = self
+            .http
+            .post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": "solmint",
+                "method": method,
+                "params": params
+            }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AppError::Upstream(format!("DAS request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::Upstream(format!("Invalid DAS response: {}", e)))?;
+
+        match (response.result, response.error) {
+            (Some(result), _) => Ok(Some(result)),
+            (None, Some(error)) => Err(AppError::Upstream(format!(
+                "DAS {} failed: {}",
+                method, error.message
+            ))),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Canopy depth of a concurrent Merkle tree, read from the size of its account
+    pub async fn canopy_depth(&self, tree_id: &str) -> Result<u32, AppError> {
+        let tree = Pubkey::from_str(tree_id)
+            .map_err(|_| AppError::Upstream(format!("Invalid tree id {}", tree_id)))?;
+        let data = RpcClient::new(self.url.clone())
+            .get_account_data(&tree)
+            .await?;
+
+        canopy_depth_from_account(&data).ok_or_else(|| {
+            AppError::Upstream(format!("{} is not a concurrent Merkle tree", tree_id))
+        })
+    }
+}
+
+/// The account holds the header, the tree (`sequence`, `active_index` and
+/// `buffer_size`, a change log per buffer slot and the rightmost path, each
+/// `40 + 32 * max_depth` bytes) and then `2^(canopy + 1) - 2` cached nodes.
+fn canopy_depth_from_account(data: &[u8]) -> Option<u32> {
+    let read_u32 = |offset: usize| {
+        data.get(offset..offset + 4)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u32::from_le_bytes)
+    };
+    let max_buffer_size = read_u32(MAX_BUFFER_SIZE_OFFSET)? as usize;
+    let max_depth = read_u32(MAX_DEPTH_OFFSET)? as usize;
+
+    let path_size = 40 + 32 * max_depth;
+    let tree_size = 24 + max_buffer_size * path_size + path_size;
+    let canopy_bytes = data.len().checked_sub(TREE_HEADER_SIZE + tree_size)?;
+    let canopy_nodes = canopy_bytes / 32;
+
+    // Solve 2^(depth + 1) - 2 = nodes
+    let depth = (canopy_nodes + 2).checked_ilog2()?.checked_sub(1)?;
+    Some(depth.min(max_depth as u32))
+}
@@ -0,0 +1,253 @@
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use solana_sdk::hash::hashv;
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    error::AppError,
+    models::{
+        Notification, PendingEmail, User, CATEGORY_FLOOR_ALERT, CATEGORY_ITEM_SOLD,
+        CATEGORY_OFFER_RECEIVED,
+    },
+};
+
+/// Instant emails are only attempted for this long; after that the
+/// notification stays in-app only
+const INSTANT_WINDOW_HOURS: i32 = 1;
+const DIGEST_WINDOW_HOURS: i32 = 24;
+/// Minimum gap between verification emails to the same wallet
+const VERIFICATION_RESEND_SECONDS: i64 = 60;
+
+/// Sends notification email over SMTP. Amazon SES works through its SMTP
+/// interface, e.g. `smtps://<user>:<password>@email-smtp.us-east-1.amazonaws.com`.
+#[derive(Clone)]
+pub struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    public_url: String,
+}
+
+struct Rendered {
+    subject: String,
+    body: String,
+}
+
+impl Mailer {
+    /// `None` when `smtp_url` isn't configured
+    pub fn from_config(config: &Config) -> Result<Option<Self>, AppError> {
+        let Some(smtp_url) = &config.smtp_url else {
+            return Ok(None);
+        };
+
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::from_url(smtp_url)
+            .map_err(|e| AppError::ConfigError(format!("invalid SMTP_URL: {}", e)))?
+            .build();
+        let from = config
+            .email_from
+            .parse()
+            .map_err(|e| AppError::ConfigError(format!("invalid EMAIL_FROM: {}", e)))?;
+
+        Ok(Some(Self {
+            transport,
+            from,
+            public_url: config.public_url.trim_end_matches('/').to_string(),
+        }))
+    }
+
+    async fn send(&self, to: &str, email: Rendered) -> Result<(), AppError> {
+        let to: Mailbox = to
+            .parse()
+            .map_err(|e| AppError::Email(format!("invalid recipient {}: {}", to, e)))?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(email.body)
+            .map_err(|e| AppError::Email(e.to_string()))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| AppError::Email(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Email a confirmation link to the wallet's stored address. Only a hash
+    /// of the token is kept, so a database leak can't verify addresses.
+    pub async fn send_verification(&self, pool: &PgPool, wallet: &str) -> Result<(), AppError> {
+        let user = User::find_by_wallet(pool, wallet)
+            .await?
+            .ok_or_else(|| crate::error::not_found_error("User"))?;
+        let email = user
+            .email
+            .ok_or_else(|| crate::error::bad_request_error("no email address on file"))?;
+        if user.email_verified_at.is_some() {
+            return Err(crate::error::bad_request_error("email is already verified"));
+        }
+
+        let token = Uuid::new_v4().simple().to_string();
+        let started = User::start_email_verification(
+            pool,
+            wallet,
+            &verification_token_hash(&token),
+            VERIFICATION_RESEND_SECONDS,
+        )
+        .await?;
+        if !started {
+            return Err(crate::error::bad_request_error(
+                "a verification email was sent recently; try again in a minute",
+            ));
+        }
+
+        let link = format!("{}/verify-email?token={}", self.public_url, token);
+        self.send(
+            &email,
+            Rendered {
+                subject: "Confirm your SolMint email".to_string(),
+                body: format!(
+                    "Confirm this address to receive SolMint notifications:\n\n{}\n\n\
+                     If you didn't request this, you can ignore this email.\n",
+                    link
+                ),
+            },
+        )
+        .await
+    }
+
+    /// Send everything set to `instant`; returns how many emails went out
+    pub async fn deliver_instant(&self, pool: &PgPool) -> Result<u64, AppError> {
+        let pending = Notification::pending_email(pool, "instant", INSTANT_WINDOW_HOURS).await?;
+        let mut sent = 0;
+
+        for PendingEmail {
+            notification,
+            email,
+        } in pending
+        {
+            // Failures are retried on the next run until the window closes
+            match self.send(&email, self.render(&notification)).await {
+                Ok(()) => {
+                    Notification::mark_emailed(pool, &[notification.id]).await?;
+                    sent += 1;
+                }
+                Err(e) => println!(
+                    "Failed to email notification {} to {}: {}",
+                    notification.id, notification.wallet_address, e
+                ),
+            }
+        }
+
+        Ok(sent)
+    }
+
+    /// One email per wallet with the last day's `digest` notifications
+    pub async fn send_digests(&self, pool: &PgPool) -> Result<u64, AppError> {
+        let pending = Notification::pending_email(pool, "digest", DIGEST_WINDOW_HOURS).await?;
+
+        let mut by_wallet: BTreeMap<String, (String, Vec<Notification>)> = BTreeMap::new();
+        for PendingEmail {
+            notification,
+            email,
+        } in pending
+        {
+            by_wallet
+                .entry(notification.wallet_address.clone())
+                .or_insert_with(|| (email, Vec::new()))
+                .1
+                .push(notification);
+        }
+
+        let mut sent = 0;
+        for (wallet, (email, notifications)) in by_wallet {
+            let ids: Vec<Uuid> = notifications.iter().map(|n| n.id).collect();
+            match self.send(&email, self.render_digest(&notifications)).await {
+                Ok(()) => {
+                    Notification::mark_emailed(pool, &ids).await?;
+                    sent += 1;
+                }
+                Err(e) => println!("Failed to send digest to {}: {}", wallet, e),
+            }
+        }
+
+        Ok(sent)
+    }
+
+    fn render(&self, notification: &Notification) -> Rendered {
+        let subject = match notification.category.as_str() {
+            CATEGORY_FLOOR_ALERT => format!("Floor alert: {}", notification.title),
+            _ => notification.title.clone(),
+        };
+
+        let mut body = format!("{}\n", notification.body);
+        if let Some(link) = self.link_for(notification) {
+            body.push_str(&format!("\nView on SolMint: {}\n", link));
+        }
+        body.push_str(&self.footer());
+
+        Rendered { subject, body }
+    }
+
+    fn render_digest(&self, notifications: &[Notification]) -> Rendered {
+        let mut body = String::from("Here's what happened on SolMint in the last day:\n");
+
+        for (category, heading) in [
+            (CATEGORY_OFFER_RECEIVED, "Offers received"),
+            (CATEGORY_ITEM_SOLD, "Items sold"),
+            (CATEGORY_FLOOR_ALERT, "Floor alerts"),
+        ] {
+            let section: Vec<&Notification> = notifications
+                .iter()
+                .filter(|n| n.category == category)
+                .collect();
+            if section.is_empty() {
+                continue;
+            }
+
+            body.push_str(&format!("\n{}\n", heading));
+            for notification in section {
+                body.push_str(&format!("  - {}", notification.body));
+                if let Some(link) = self.link_for(notification) {
+                    body.push_str(&format!(" ({})", link));
+                }
+                body.push('\n');
+            }
+        }
+        body.push_str(&self.footer());
+
+        Rendered {
+            subject: format!(
+                "Your SolMint daily digest ({} updates)",
+                notifications.len()
+            ),
+            body,
+        }
+    }
+
+    fn link_for(&self, notification: &Notification) -> Option<String> {
+        let data = notification.data.as_ref()?;
+        if let Some(mint) = data["nft_mint"].as_str() {
+            return Some(format!("{}/nft/{}", self.public_url, mint));
+        }
+        data["collection_id"]
+            .as_str()
+            .map(|id| format!("{}/collection/{}", self.public_url, id))
+    }
+
+    fn footer(&self) -> String {
+        format!(
+            "\n--\nManage email notifications: {}/settings/notifications\n",
+            self.public_url
+        )
+    }
+}
+
+pub fn verification_token_hash(token: &str) -> String {
+    hashv(&[token.as_bytes()]).to_string()
+}
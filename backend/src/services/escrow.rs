@@ -0,0 +1,162 @@
+//! Lamports a wallet has locked in the marketplace program: open offers and
+//! collection offers it made, and auctions where it holds the highest bid,
+//! whether bidding is still open or has closed and the auction awaits
+//! settlement. Read straight from the program's accounts, so commitments the indexer
+//! hasn't caught up with are included.
+
+use chrono::Utc;
+use nft_marketplace_sdk::{
+    ids::{COLLECTION_OFFER_SEED, OFFER_SEED},
+    pda, Auction, CollectionOffer, Offer,
+};
+use serde::{Deserialize, Serialize};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::AppError;
+
+/// One account holding a wallet's lamports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscrowCommitment {
+    /// "offer", "collection_offer", "auction_bid" while bidding is open, or
+    /// "auction_bid_unsettled" once it has closed and the bid waits for
+    /// someone to settle the auction
+    pub kind: String,
+    pub account: String,
+    pub marketplace: String,
+    /// The NFT, or the collection mint of a collection offer
+    pub mint: String,
+    /// Lamports locked, on top of the account's rent
+    pub amount: u64,
+    /// When an offer lapses or an auction's bidding closes; `None` for
+    /// offers that don't expire
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscrowBalance {
+    pub wallet_address: String,
+    pub total_locked: u64,
+    pub commitments: Vec<EscrowCommitment>,
+}
+
+/// Everything `wallet` has locked in `program_id`'s accounts, largest first
+pub async fn locked_funds(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+) -> Result<EscrowBalance, AppError> {
+    let program = program_id.to_bytes();
+    let now = Utc::now().timestamp();
+    let mut commitments = Vec::new();
+
+    // Offers and collection offers share a size and layout; the address
+    // they're derived at tells them apart
+    for (address, account) in
+        program_accounts(rpc, program_id, Offer::LEN, Offer::BIDDER_OFFSET, wallet).await?
+    {
+        let key = address.to_bytes();
+        if let Ok(offer) = Offer::from_account_data(&account.data) {
+            let seeds: [&[u8]; 4] = [OFFER_SEED, &offer.nft_mint, &offer.bidder, &[offer.bump]];
+            if offer.is_initialized && pda::create_program_address(&seeds, &program) == Some(key) {
+                commitments.push(EscrowCommitment {
+                    kind: "offer".to_string(),
+                    account: address.to_string(),
+                    marketplace: Pubkey::new_from_array(offer.marketplace).to_string(),
+                    mint: Pubkey::new_from_array(offer.nft_mint).to_string(),
+                    amount: offer.amount,
+                    expires_at: (offer.expires_at != 0).then_some(offer.expires_at),
+                });
+                continue;
+            }
+        }
+        if let Ok(offer) = CollectionOffer::from_account_data(&account.data) {
+            let seeds: [&[u8]; 4] = [
+                COLLECTION_OFFER_SEED,
+                &offer.collection_mint,
+                &offer.bidder,
+                &[offer.bump],
+            ];
+            if offer.is_initialized && pda::create_program_address(&seeds, &program) == Some(key) {
+                commitments.push(EscrowCommitment {
+                    kind: "collection_offer".to_string(),
+                    account: address.to_string(),
+                    marketplace: Pubkey::new_from_array(offer.marketplace).to_string(),
+                    mint: Pubkey::new_from_array(offer.collection_mint).to_string(),
+                    amount: offer.amount,
+                    expires_at: (offer.expires_at != 0).then_some(offer.expires_at),
+                });
+            }
+        }
+    }
+
+    for (address, account) in program_accounts(
+        rpc,
+        program_id,
+        Auction::LEN,
+        Auction::HIGHEST_BIDDER_OFFSET,
+        wallet,
+    )
+    .await?
+    {
+        let Ok(auction) = Auction::from_account_data(&account.data) else {
+            continue;
+        };
+        // An auction without bids has a zeroed highest bidder. A won bid
+        // stays locked after bidding closes until the auction is settled.
+        if auction.is_initialized && auction.highest_bid > 0 {
+            let kind = if auction.end_time <= now {
+                "auction_bid_unsettled"
+            } else {
+                "auction_bid"
+            };
+            commitments.push(EscrowCommitment {
+                kind: kind.to_string(),
+                account: address.to_string(),
+                marketplace: Pubkey::new_from_array(auction.marketplace).to_string(),
+                mint: Pubkey::new_from_array(auction.nft_mint).to_string(),
+                amount: auction.highest_bid,
+                expires_at: Some(auction.end_time),
+            });
+        }
+    }
+
+    commitments.sort_by(|a, b| b.amount.cmp(&a.amount));
+    Ok(EscrowBalance {
+        wallet_address: wallet.to_string(),
+        total_locked: commitments.iter().fold(0u64, |total, commitment| {
+            total.saturating_add(commitment.amount)
+        }),
+        commitments,
+    })
+}
+
+/// The program's accounts of `size` bytes with `wallet` at `offset`
+async fn program_accounts(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    size: usize,
+    offset: usize,
+    wallet: &Pubkey,
+) -> Result<Vec<(Pubkey, solana_sdk::account::Account)>, AppError> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::DataSize(size as u64),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(offset, wallet.as_ref())),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    Ok(rpc
+        .get_program_accounts_with_config(program_id, config)
+        .await?)
+}
@@ -0,0 +1,327 @@
+use base64::Engine;
+use nft_marketplace_sdk::instruction as marketplace_instruction;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    hash::hashv,
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    transaction::Transaction,
+};
+use std::str::FromStr;
+
+use crate::{
+    error::AppError,
+    models::{Launch, LaunchPhase, LAUNCH_KIND_CANDY_MACHINE},
+    services::priority_fees::set_compute_unit_limit,
+    utils::program,
+};
+
+const CANDY_MACHINE_PROGRAM_ID: Pubkey = pubkey!("CndyV3LdqHUfDLmE5naZjVN8rBZz4tqhdefbAnjHG3JR");
+const CANDY_GUARD_PROGRAM_ID: Pubkey = pubkey!("Guard1JwRhJkVH6XZhzoYxeBVQe872VH6QggF4BWmS9g");
+const TOKEN_METADATA_PROGRAM_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+const SYSTEM_PROGRAM_ID: Pubkey = pubkey!("11111111111111111111111111111111");
+const SYSVAR_INSTRUCTIONS_ID: Pubkey = pubkey!("Sysvar1nstructions1111111111111111111111111");
+const SYSVAR_SLOT_HASHES_ID: Pubkey = pubkey!("SysvarS1otHashes111111111111111111111111111");
+
+/// Candy guard mints with collection verification run close to the default
+/// 200k compute unit budget
+const MINT_COMPUTE_UNIT_LIMIT: u32 = 400_000;
+
+/// Candy Machine v3 account layout: discriminator (8), version (1), token
+/// standard (1), features (6), then authority, mint authority (the candy
+/// guard) and collection mint, items redeemed and `data.items_available`
+const CM_AUTHORITY_OFFSET: usize = 16;
+const CM_MINT_AUTHORITY_OFFSET: usize = 48;
+const CM_COLLECTION_MINT_OFFSET: usize = 80;
+const CM_ITEMS_REDEEMED_OFFSET: usize = 112;
+const CM_ITEMS_AVAILABLE_OFFSET: usize = 120;
+
+#[derive(Debug, Clone)]
+pub struct CandyMachineState {
+    pub authority: Pubkey,
+    pub candy_guard: Pubkey,
+    pub collection_mint: Pubkey,
+    pub items_redeemed: u64,
+    pub items_available: u64,
+}
+
+/// A mint transaction for the user to sign; already signed by the new mint
+#[derive(Debug, Clone)]
+pub struct MintTransaction {
+    /// Base64 bincode-serialized transaction
+    pub transaction: String,
+    pub mint_address: String,
+    pub item_index: Option<i64>,
+}
+
+/// Reads launch accounts and builds mint transactions against the
+/// configured RPC endpoint
+pub struct Launchpad {
+    rpc: RpcClient,
+}
+
+impl Launchpad {
+    pub fn new(rpc_url: String) -> Self {
+        Self {
+            rpc: RpcClient::new(rpc_url),
+        }
+    }
+
+    pub async fn candy_machine(&self, address: &str) -> Result<CandyMachineState, AppError> {
+        let address = parse_pubkey("config_address", address)?;
+        let account = self.rpc.get_account(&address).await?;
+        if account.owner != CANDY_MACHINE_PROGRAM_ID {
+            return Err(AppError::BadRequest(format!(
+                "{} is not a Candy Machine v3 account",
+                address
+            )));
+        }
+
+        candy_machine_from_account(&account.data).ok_or_else(|| {
+            AppError::Upstream(format!("Could not decode candy machine {}", address))
+        })
+    }
+
+    /// Build a transaction minting one item of `launch` in `phase` to
+    /// `minter` as `mint_keypair`
+    pub async fn build_mint(
+        &self,
+        launch: &Launch,
+        phase: &LaunchPhase,
+        minter: &str,
+        mint_keypair: &Keypair,
+        marketplace_program_id: &str,
+        item_index: Option<i64>,
+    ) -> Result<MintTransaction, AppError> {
+        let minter = parse_pubkey("wallet_address", minter)?;
+        let mint = mint_keypair.pubkey();
+
+        let mut instructions = vec![set_compute_unit_limit(MINT_COMPUTE_UNIT_LIMIT)];
+        if launch.kind == LAUNCH_KIND_CANDY_MACHINE {
+            instructions.push(self.candy_guard_mint(launch, phase, &minter, &mint).await?);
+        } else {
+            let program_id = Pubkey::from_str(marketplace_program_id)
+                .map_err(|_| AppError::ConfigError("Invalid program ID".to_string()))?;
+            let index = item_index
+                .ok_or_else(|| crate::error::bad_request_error("the launch is sold out"))?;
+            if phase.price > 0 {
+                let treasury = parse_pubkey(
+                    "treasury_address",
+                    launch.treasury_address.as_deref().unwrap_or_default(),
+                )?;
+                instructions.push(system_transfer(&minter, &treasury, phase.price as u64));
+            }
+            instructions.push(solmint_mint(launch, index, program_id, &minter, &mint));
+        }
+
+        let blockhash = self.rpc.get_latest_blockhash().await?;
+        let mut transaction = Transaction::new_with_payer(&instructions, Some(&minter));
+        transaction.partial_sign(&[mint_keypair], blockhash);
+
+        let bytes = bincode::serialize(&transaction)
+            .map_err(|e| AppError::BadRequest(format!("could not serialize transaction: {}", e)))?;
+
+        Ok(MintTransaction {
+            transaction: base64::engine::general_purpose::STANDARD.encode(bytes),
+            mint_address: mint.to_string(),
+            item_index,
+        })
+    }
+
+    /// Candy guard `mint_v2` for the phase's guard group. Only the solPayment
+    /// guard's remaining account is supplied, so phases using other guards
+    /// that need extra accounts are rejected by the caller.
+    async fn candy_guard_mint(
+        &self,
+        launch: &Launch,
+        phase: &LaunchPhase,
+        minter: &Pubkey,
+        mint: &Pubkey,
+    ) -> Result<Instruction, AppError> {
+        let state = self.candy_machine(&launch.config_address).await?;
+        let candy_machine = parse_pubkey("config_address", &launch.config_address)?;
+
+        let collection_metadata = metadata_pda(&state.collection_mint);
+        let collection_data = self.rpc.get_account_data(&collection_metadata).await?;
+        // Metadata: key (1), update authority (32), ...
+        let collection_update_authority = collection_data
+            .get(1..33)
+            .and_then(|bytes| Pubkey::try_from(bytes).ok())
+            .ok_or_else(|| {
+                AppError::Upstream(format!(
+                    "Could not decode collection metadata {}",
+                    collection_metadata
+                ))
+            })?;
+
+        let (authority_pda, _) = Pubkey::find_program_address(
+            &[b"candy_machine", candy_machine.as_ref()],
+            &CANDY_MACHINE_PROGRAM_ID,
+        );
+        let (collection_delegate_record, _) = Pubkey::find_program_address(
+            &[
+                b"metadata",
+                TOKEN_METADATA_PROGRAM_ID.as_ref(),
+                state.collection_mint.as_ref(),
+                b"collection_delegate",
+                collection_update_authority.as_ref(),
+                authority_pda.as_ref(),
+            ],
+            &TOKEN_METADATA_PROGRAM_ID,
+        );
+
+        // Optional accounts that don't apply are passed as the guard program id
+        let none = AccountMeta::new_readonly(CANDY_GUARD_PROGRAM_ID, false);
+        let mut accounts = vec![
+            AccountMeta::new_readonly(state.candy_guard, false),
+            AccountMeta::new_readonly(CANDY_MACHINE_PROGRAM_ID, false),
+            AccountMeta::new(candy_machine, false),
+            AccountMeta::new(authority_pda, false),
+            AccountMeta::new(*minter, true),
+            AccountMeta::new(*minter, true),
+            AccountMeta::new(*mint, true),
+            AccountMeta::new_readonly(*minter, true),
+            AccountMeta::new(metadata_pda(mint), false),
+            AccountMeta::new(master_edition_pda(mint), false),
+            AccountMeta::new(associated_token_address(minter, mint), false),
+            none.clone(),
+            AccountMeta::new_readonly(collection_delegate_record, false),
+            AccountMeta::new_readonly(state.collection_mint, false),
+            AccountMeta::new(collection_metadata, false),
+            AccountMeta::new_readonly(master_edition_pda(&state.collection_mint), false),
+            AccountMeta::new_readonly(collection_update_authority, false),
+            AccountMeta::new_readonly(TOKEN_METADATA_PROGRAM_ID, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(SYSVAR_INSTRUCTIONS_ID, false),
+            AccountMeta::new_readonly(SYSVAR_SLOT_HASHES_ID, false),
+            none.clone(),
+            none,
+        ];
+        if phase.price > 0 {
+            let destination = parse_pubkey(
+                "payment_destination",
+                phase.payment_destination.as_deref().unwrap_or_default(),
+            )?;
+            accounts.push(AccountMeta::new(destination, false));
+        }
+
+        // Anchor discriminator, empty `mint_args`, `Some(label)`
+        let mut data = hashv(&[b"global:mint_v2"]).to_bytes()[..8].to_vec();
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.push(1);
+        push_borsh_string(&mut data, &phase.label);
+
+        Ok(Instruction {
+            program_id: CANDY_GUARD_PROGRAM_ID,
+            accounts,
+            data,
+        })
+    }
+}
+
+/// Our marketplace program's `MintNft`, naming item `index` after the launch
+fn solmint_mint(
+    launch: &Launch,
+    index: i64,
+    program_id: Pubkey,
+    minter: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    let number = index + 1;
+    program::instruction(marketplace_instruction::mint_nft(
+        &program_id.to_bytes(),
+        &minter.to_bytes(),
+        &mint.to_bytes(),
+        &associated_token_address(minter, mint).to_bytes(),
+        &metadata_pda(mint).to_bytes(),
+        &master_edition_pda(mint).to_bytes(),
+        format!("{} #{}", launch.name, number),
+        launch.symbol.clone().unwrap_or_default(),
+        format!(
+            "{}/{}.json",
+            launch.base_uri.as_deref().unwrap_or_default(),
+            index
+        ),
+    ))
+}
+
+fn candy_machine_from_account(data: &[u8]) -> Option<CandyMachineState> {
+    let read_pubkey = |offset: usize| {
+        data.get(offset..offset + 32)
+            .and_then(|bytes| Pubkey::try_from(bytes).ok())
+    };
+    let read_u64 = |offset: usize| {
+        data.get(offset..offset + 8)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u64::from_le_bytes)
+    };
+
+    Some(CandyMachineState {
+        authority: read_pubkey(CM_AUTHORITY_OFFSET)?,
+        candy_guard: read_pubkey(CM_MINT_AUTHORITY_OFFSET)?,
+        collection_mint: read_pubkey(CM_COLLECTION_MINT_OFFSET)?,
+        items_redeemed: read_u64(CM_ITEMS_REDEEMED_OFFSET)?,
+        items_available: read_u64(CM_ITEMS_AVAILABLE_OFFSET)?,
+    })
+}
+
+fn system_transfer(from: &Pubkey, to: &Pubkey, lamports: u64) -> Instruction {
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&lamports.to_le_bytes());
+    Instruction {
+        program_id: SYSTEM_PROGRAM_ID,
+        accounts: vec![AccountMeta::new(*from, true), AccountMeta::new(*to, false)],
+        data,
+    }
+}
+
+fn metadata_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            mint.as_ref(),
+        ],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+fn master_edition_pda(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            mint.as_ref(),
+            b"edition",
+        ],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+fn push_borsh_string(data: &mut Vec<u8>, value: &str) {
+    data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    data.extend_from_slice(value.as_bytes());
+}
+
+fn parse_pubkey(field: &str, value: &str) -> Result<Pubkey, AppError> {
+    Pubkey::from_str(value).map_err(|_| {
+        AppError::BadRequest(format!("{} '{}' is not a valid public key", field, value))
+    })
+}
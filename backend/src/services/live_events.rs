@@ -0,0 +1,130 @@
+//! Live marketplace events for `/ws` clients.
+//!
+//! The outbox relay publishes each event once, on Redis. Every API replica
+//! subscribes and fans events out to its own sockets, so a client sees every
+//! event whichever replica it's connected to.
+
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    error::AppError,
+    models::Nft,
+    services::{outbox_relay::EVENTS_CHANNEL, supervisor::ShutdownSignal},
+};
+
+/// Channel every event is delivered on
+pub const GLOBAL_CHANNEL: &str = "global-activity";
+/// Events a slow socket may fall behind by before it starts missing them
+const BUFFER_SIZE: usize = 1024;
+
+/// An outbox event as the relay publishes it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveEvent {
+    pub id: i64,
+    pub aggregate_type: String,
+    pub aggregate_id: String,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    /// `global-activity`, plus `nft:{mint}` and `collection:{id}` when the
+    /// event concerns an NFT
+    #[serde(skip)]
+    pub channels: Vec<String>,
+}
+
+/// Fans events received from Redis out to this replica's sockets
+#[derive(Clone)]
+pub struct LiveEvents {
+    sender: broadcast::Sender<Arc<LiveEvent>>,
+}
+
+impl Default for LiveEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LiveEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(BUFFER_SIZE);
+        Self { sender }
+    }
+
+    /// Every event received from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<LiveEvent>> {
+        self.sender.subscribe()
+    }
+
+    /// Relay events from Redis until shutdown. Events published while the
+    /// subscription is down are missed; clients catch up through the REST API.
+    pub async fn run(
+        &self,
+        db: PgPool,
+        config: Config,
+        mut shutdown: ShutdownSignal,
+    ) -> Result<(), AppError> {
+        let mut pubsub = redis::Client::open(config.redis_url.clone())?
+            .get_async_connection()
+            .await?
+            .into_pubsub();
+        pubsub.subscribe(EVENTS_CHANNEL).await?;
+        let mut messages = pubsub.on_message();
+
+        loop {
+            let message = tokio::select! {
+                message = messages.next() => message,
+                _ = shutdown.changed() => return Ok(()),
+            };
+            let Some(message) = message else {
+                return Err(AppError::Upstream("Redis subscription closed".to_string()));
+            };
+
+            let body: String = message.get_payload()?;
+            let mut event = match serde_json::from_str::<LiveEvent>(&body) {
+                Ok(event) => event,
+                Err(e) => {
+                    println!("Ignoring malformed event on {}: {}", EVENTS_CHANNEL, e);
+                    continue;
+                }
+            };
+            event.channels = channels(&db, &event).await?;
+
+            // Nobody listening isn't an error
+            let _ = self.sender.send(Arc::new(event));
+        }
+    }
+}
+
+/// The channels an event is delivered on. NFT events carry their collection;
+/// listing and sale events only the mint, so theirs is looked up.
+async fn channels(db: &PgPool, event: &LiveEvent) -> Result<Vec<String>, AppError> {
+    let mut channels = vec![GLOBAL_CHANNEL.to_string()];
+    let mint = event
+        .payload
+        .get("mint_address")
+        .or_else(|| event.payload.get("nft_mint"))
+        .and_then(|mint| mint.as_str());
+    let Some(mint) = mint else {
+        return Ok(channels);
+    };
+    channels.push(format!("nft:{}", mint));
+
+    let collection_id = match event.payload.get("collection_id") {
+        Some(id) => id.as_str().and_then(|id| Uuid::parse_str(id).ok()),
+        None => Nft::find_by_mint(db, mint)
+            .await?
+            .and_then(|nft| nft.collection_id),
+    };
+    if let Some(collection_id) = collection_id {
+        channels.push(format!("collection:{}", collection_id));
+    }
+
+    Ok(channels)
+}
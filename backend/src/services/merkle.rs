@@ -0,0 +1,198 @@
+use nft_marketplace_sdk::allowlist::{self, sha256v};
+use solana_sdk::pubkey::Pubkey;
+
+pub type MerkleHash = allowlist::MerkleHash;
+
+/// `sha256(0x00 || wallet || amount_le)`; `amount` is the holding or
+/// allocation. The scheme is the SDK's, so the program accepts these proofs.
+pub fn leaf(wallet: &Pubkey, amount: u64) -> MerkleHash {
+    allowlist::leaf(sha256v, &wallet.to_bytes(), amount)
+}
+
+/// `sha256(0x01 || min(a, b) || max(a, b))`
+fn node(a: &MerkleHash, b: &MerkleHash) -> MerkleHash {
+    allowlist::node(sha256v, a, b)
+}
+
+/// Binary SHA-256 Merkle tree over a set of leaves. Leaves are sorted so the
+/// root only depends on the set, and an odd node is promoted unchanged.
+pub struct MerkleTree {
+    layers: Vec<Vec<MerkleHash>>,
+}
+
+impl MerkleTree {
+    pub fn new(mut leaves: Vec<MerkleHash>) -> Self {
+        leaves.sort_unstable();
+        leaves.dedup();
+
+        let mut layers = vec![leaves];
+        while layers.last().map_or(0, Vec::len) > 1 {
+            let next = layers
+                .last()
+                .map(|layer| {
+                    layer
+                        .chunks(2)
+                        .map(|pair| match pair {
+                            [a, b] => node(a, b),
+                            [a] => *a,
+                            _ => unreachable!(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            layers.push(next);
+        }
+
+        Self { layers }
+    }
+
+    /// `None` for an empty tree
+    pub fn root(&self) -> Option<MerkleHash> {
+        self.layers.last().and_then(|layer| layer.first()).copied()
+    }
+
+    /// Sibling hashes from the leaf up to the root, or `None` if `leaf` isn't in the tree
+    pub fn proof(&self, leaf: &MerkleHash) -> Option<Vec<MerkleHash>> {
+        let mut index = self.layers.first()?.binary_search(leaf).ok()?;
+        let mut proof = Vec::new();
+
+        for layer in &self.layers[..self.layers.len() - 1] {
+            let sibling = index ^ 1;
+            if let Some(hash) = layer.get(sibling) {
+                proof.push(*hash);
+            }
+            index /= 2;
+        }
+
+        Some(proof)
+    }
+}
+
+pub fn verify(proof: &[MerkleHash], root: &MerkleHash, leaf: &MerkleHash) -> bool {
+    proof
+        .iter()
+        .fold(*leaf, |hash, sibling| node(&hash, sibling))
+        == *root
+}
+
+/// Base58, the way Solana displays hashes
+pub fn encode(hash: &MerkleHash) -> String {
+    solana_sdk::hash::Hash::new_from_array(*hash).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wallets(count: usize) -> Vec<Pubkey> {
+        (0..count).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    #[test]
+    fn proofs_verify_with_the_program_scheme() {
+        let wallets = wallets(5);
+        let tree = MerkleTree::new(
+            wallets
+                .iter()
+                .enumerate()
+                .map(|(i, wallet)| leaf(wallet, i as u64 + 1))
+                .collect(),
+        );
+        let root = tree.root().unwrap();
+
+        for (i, wallet) in wallets.iter().enumerate() {
+            let allocation = i as u64 + 1;
+            let proof = tree.proof(&leaf(wallet, allocation)).unwrap();
+            assert!(allowlist::verify_proof(
+                sha256v,
+                &wallet.to_bytes(),
+                allocation,
+                &proof,
+                &root
+            ));
+        }
+    }
+    #[test]
+    fn empty_and_single_leaf_trees() {
+        let tree = MerkleTree::new(Vec::new());
+        assert_eq!(tree.root(), None);
+        assert_eq!(tree.proof(&[0; 32]), None);
+
+        let only = leaf(&Pubkey::new_unique(), 1);
+        let tree = MerkleTree::new(vec![only]);
+        assert_eq!(tree.root(), Some(only));
+        assert_eq!(tree.proof(&only), Some(Vec::new()));
+    }
+
+    #[test]
+    fn root_depends_only_on_the_set_of_leaves() {
+        let leaves: Vec<_> = wallets(4).iter().map(|wallet| leaf(wallet, 1)).collect();
+        let mut shuffled = leaves.clone();
+        shuffled.reverse();
+        shuffled.push(leaves[0]);
+
+        assert_eq!(
+            MerkleTree::new(leaves).root(),
+            MerkleTree::new(shuffled).root()
+        );
+    }
+
+    #[test]
+    fn pairs_are_hashed_sorted() {
+        let (a, b) = (
+            leaf(&Pubkey::new_unique(), 1),
+            leaf(&Pubkey::new_unique(), 1),
+        );
+        assert_eq!(node(&a, &b), node(&b, &a));
+        assert_eq!(MerkleTree::new(vec![a, b]).root(), Some(node(&a, &b)));
+    }
+
+    #[test]
+    fn odd_leaf_is_promoted_unchanged() {
+        let mut leaves: Vec<_> = wallets(3).iter().map(|wallet| leaf(wallet, 1)).collect();
+        leaves.sort_unstable();
+        let tree = MerkleTree::new(leaves.clone());
+
+        assert_eq!(
+            tree.root(),
+            Some(node(&node(&leaves[0], &leaves[1]), &leaves[2]))
+        );
+        // The promoted leaf has no sibling on the first layer
+        assert_eq!(
+            tree.proof(&leaves[2]),
+            Some(vec![node(&leaves[0], &leaves[1])])
+        );
+    }
+
+    #[test]
+    fn proofs_verify_for_every_leaf() {
+        let leaves: Vec<_> = wallets(7).iter().map(|wallet| leaf(wallet, 1)).collect();
+        let tree = MerkleTree::new(leaves.clone());
+        let root = tree.root().unwrap();
+
+        for leaf in &leaves {
+            let proof = tree.proof(leaf).unwrap();
+            assert!(verify(&proof, &root, leaf));
+        }
+        assert_eq!(tree.proof(&[0; 32]), None);
+    }
+
+    #[test]
+    fn proof_fails_for_the_wrong_allocation() {
+        let wallet = Pubkey::new_unique();
+        let mut leaves: Vec<_> = wallets(3).iter().map(|wallet| leaf(wallet, 1)).collect();
+        leaves.push(leaf(&wallet, 2));
+        let tree = MerkleTree::new(leaves);
+        let root = tree.root().unwrap();
+        let proof = tree.proof(&leaf(&wallet, 2)).unwrap();
+
+        assert!(!verify(&proof, &root, &leaf(&wallet, 3)));
+        assert!(!allowlist::verify_proof(
+            sha256v,
+            &wallet.to_bytes(),
+            3,
+            &proof,
+            &root
+        ));
+    }
+}
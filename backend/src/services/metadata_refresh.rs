@@ -0,0 +1,151 @@
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::{
+    config::Config,
+    error::{retry_on_conflict, AppError},
+    models::{Collection, Nft, UpdateNftRequest},
+    services::das::{DasAsset, DasClient},
+};
+
+/// NFTs re-read per run
+const ITEMS_PER_RUN: i64 = 100;
+/// Pause between DAS and metadata requests, keeping refreshes under 5 rps
+const REQUEST_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+const METADATA_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Debug, Default, Serialize)]
+pub struct RefreshSummary {
+    pub checked: usize,
+    pub changed: usize,
+    pub failed: usize,
+}
+
+/// Re-reads NFT metadata through DAS so mutable metadata (reveals, dynamic
+/// traits, re-hosted images) doesn't go stale, and has rarity recomputed
+/// when traits change
+#[derive(Clone)]
+pub struct MetadataRefresher {
+    das: DasClient,
+    http: reqwest::Client,
+    stale_days: i32,
+}
+
+impl MetadataRefresher {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            das: DasClient::new(config.das_rpc_url().to_string()),
+            http: reqwest::Client::builder()
+                .timeout(METADATA_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            stale_days: config.metadata_refresh_days as i32,
+        }
+    }
+
+    /// Refresh the next batch of NFTs not re-read in `metadata_refresh_days`
+    pub async fn run(&self, pool: &PgPool) -> Result<RefreshSummary, AppError> {
+        let mut summary = RefreshSummary::default();
+
+        for mint in Nft::stale_metadata(pool, self.stale_days, ITEMS_PER_RUN).await? {
+            summary.checked += 1;
+            match self.refresh(pool, &mint).await {
+                Ok((_, changed)) if !changed.is_empty() => summary.changed += 1,
+                Ok(_) => {}
+                Err(e) => {
+                    summary.failed += 1;
+                    Nft::record_metadata_refresh(pool, &mint, None, false, Some(&e.to_string()))
+                        .await?;
+                }
+            }
+            tokio::time::sleep(REQUEST_INTERVAL).await;
+        }
+
+        Ok(summary)
+    }
+
+    /// Re-read one NFT's metadata and apply whatever changed. Returns the NFT
+    /// and the names of the changed fields.
+    pub async fn refresh(
+        &self,
+        pool: &PgPool,
+        mint_address: &str,
+    ) -> Result<(Nft, Vec<&'static str>), AppError> {
+        let asset = self.das.get_asset(mint_address).await?;
+        let attributes = asset
+            .attributes(&self.http)
+            .await
+            .map(serde_json::to_value)
+            .transpose()?;
+
+        let (nft, changed) =
+            retry_on_conflict(|| self.apply(pool, mint_address, &asset, attributes.clone()))
+                .await?;
+
+        if changed.contains(&"attributes") {
+            if let Some(collection_id) = nft.collection_id {
+                Collection::mark_rarity_stale(pool, collection_id).await?;
+            }
+        }
+        Nft::record_metadata_refresh(
+            pool,
+            mint_address,
+            asset.content.json_uri.as_deref(),
+            !changed.is_empty(),
+            None,
+        )
+        .await?;
+
+        Ok((nft, changed))
+    }
+
+    /// Diff the stored NFT against `asset` and update the fields that differ
+    async fn apply(
+        &self,
+        pool: &PgPool,
+        mint_address: &str,
+        asset: &DasAsset,
+        attributes: Option<serde_json::Value>,
+    ) -> Result<(Nft, Vec<&'static str>), AppError> {
+        let nft = Nft::find_by_mint(pool, mint_address)
+            .await?
+            .ok_or_else(|| crate::error::not_found_error("NFT"))?;
+        let metadata = &asset.content.metadata;
+        let links = &asset.content.links;
+
+        let mut changed = Vec::new();
+        let mut req = UpdateNftRequest::default();
+        // Fields missing from the source are left alone rather than cleared
+        if let Some(name) = metadata.name.as_ref().filter(|name| **name != nft.name) {
+            req.name = Some(name.clone());
+            changed.push("name");
+        }
+        if metadata.description.is_some() && metadata.description != nft.description {
+            req.description = metadata.description.clone();
+            changed.push("description");
+        }
+        if links.image.is_some() && links.image != nft.image_url {
+            req.image_url = links.image.clone();
+            changed.push("image_url");
+        }
+        if links.animation_url.is_some() && links.animation_url != nft.animation_url {
+            req.animation_url = links.animation_url.clone();
+            changed.push("animation_url");
+        }
+        if links.external_url.is_some() && links.external_url != nft.external_url {
+            req.external_url = links.external_url.clone();
+            changed.push("external_url");
+        }
+        if attributes.is_some() && attributes != nft.attributes {
+            req.attributes = attributes.map(serde_json::from_value).transpose()?;
+            changed.push("attributes");
+        }
+
+        if changed.is_empty() {
+            return Ok((nft, changed));
+        }
+        let nft = Nft::update(pool, mint_address, nft.version, req).await?;
+
+        Ok((nft, changed))
+    }
+}
@@ -1,2 +1,29 @@
+pub mod abuse;
+pub mod alerts;
+pub mod allowlist;
+pub mod analytics_export;
+pub mod auth;
+pub mod checkout;
+pub mod collection_import;
+pub mod das;
+pub mod email;
+pub mod escrow;
+pub mod launchpad;
+pub mod live_events;
+pub mod merkle;
+pub mod metadata_refresh;
+pub mod outbox_relay;
 // pub mod polling_indexer;
+pub mod price_estimator;
+pub mod price_oracle;
+pub mod priority_fees;
+pub mod push;
+pub mod rarity;
+pub mod relay_guard;
+pub mod scheduler;
+pub mod sns;
+pub mod spam;
+pub mod supervisor;
+pub mod twitter;
+pub mod webhooks;
 pub mod websocket_indexer;
@@ -0,0 +1,130 @@
+use chrono::Utc;
+use redis::aio::MultiplexedConnection;
+use sqlx::PgPool;
+use std::{collections::HashSet, time::Duration};
+
+use crate::{config::Config, error::AppError, models::OutboxEvent, services::webhooks};
+
+/// Redis pub/sub channel every outbox event is published on
+pub const EVENTS_CHANNEL: &str = "solmint:events";
+
+/// Advisory lock key so only one relay runs at a time ("outbox" in ASCII);
+/// concurrent relays could publish an aggregate's events out of order
+const RELAY_LOCK_KEY: i64 = 0x6f75_7462_6f78;
+const BATCH_SIZE: i64 = 200;
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default)]
+pub struct RelaySummary {
+    pub published: u64,
+    pub failed: u64,
+    /// Left for a later run behind an earlier event of the same aggregate
+    pub held: u64,
+}
+
+/// Publishes committed outbox events to Redis and to the configured event
+/// webhooks
+#[derive(Clone)]
+pub struct OutboxRelay {
+    redis: MultiplexedConnection,
+    http: reqwest::Client,
+    webhook_urls: Vec<String>,
+    webhook_secret: Option<String>,
+}
+
+impl OutboxRelay {
+    pub fn new(config: &Config, redis: MultiplexedConnection) -> Self {
+        Self {
+            redis,
+            http: reqwest::Client::builder()
+                .timeout(WEBHOOK_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            webhook_urls: config.event_webhook_urls(),
+            webhook_secret: config.event_webhook_secret.clone(),
+        }
+    }
+
+    /// Publish the next batch of pending events.
+    ///
+    /// Events are marked published in the same transaction that holds the
+    /// relay lock, so a crash mid-batch republishes rather than drops them.
+    /// Once an event fails, later events for the same aggregate wait until it
+    /// has been delivered.
+    pub async fn relay(&self, pool: &PgPool) -> Result<RelaySummary, AppError> {
+        let mut summary = RelaySummary::default();
+        let mut tx = pool.begin().await?;
+
+        let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(RELAY_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+        if !locked {
+            return Ok(summary);
+        }
+
+        let now = Utc::now();
+        let mut blocked = HashSet::new();
+        for event in OutboxEvent::pending(&mut tx, BATCH_SIZE).await? {
+            let aggregate = (event.aggregate_type.clone(), event.aggregate_id.clone());
+            if blocked.contains(&aggregate) {
+                summary.held += 1;
+                continue;
+            }
+            if event.next_attempt_at > now {
+                blocked.insert(aggregate);
+                continue;
+            }
+
+            match self.publish(&event).await {
+                Ok(()) => {
+                    event.mark_published(&mut tx).await?;
+                    summary.published += 1;
+                }
+                Err(e) => {
+                    println!("Outbox event {} not delivered: {}", event.id, e);
+                    event.mark_failed(&mut tx, &e).await?;
+                    blocked.insert(aggregate);
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(summary)
+    }
+
+    async fn publish(&self, event: &OutboxEvent) -> Result<(), String> {
+        let body = serde_json::to_string(event).map_err(|e| e.to_string())?;
+
+        let mut conn = self.redis.clone();
+        redis::cmd("PUBLISH")
+            .arg(EVENTS_CHANNEL)
+            .arg(&body)
+            .query_async::<_, i64>(&mut conn)
+            .await
+            .map_err(|e| format!("redis: {}", e))?;
+
+        for url in &self.webhook_urls {
+            let mut request = self
+                .http
+                .post(url)
+                .header("Content-Type", "application/json");
+            if let Some(signature) = self
+                .webhook_secret
+                .as_deref()
+                .and_then(|secret| webhooks::signature(secret, &body))
+            {
+                request = request.header("Solmint-Signature", signature);
+            }
+            request
+                .body(body.clone())
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| format!("{}: {}", url, e))?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+
+use crate::{error::AppError, models::Nft, services::price_oracle::NATIVE_SOL_MINT};
+
+const COMPARABLE_SALES_DAYS: i32 = 30;
+const MAX_COMPARABLE_SALES: i64 = 20;
+/// Comparable sales are NFTs within this fraction of the target's rarity rank
+const COMPARABLE_RANK_WINDOW: f64 = 0.10;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TraitFloor {
+    pub trait_type: String,
+    pub value: String,
+    pub floor_price: i64,
+}
+
+/// Market data an estimate is derived from. All prices are lamports.
+#[derive(Debug, Clone, Serialize)]
+pub struct EstimateInputs {
+    pub collection_floor: Option<i64>,
+    pub collection_floor_7d_ago: Option<i64>,
+    pub trait_floors: Vec<TraitFloor>,
+    pub comparable_sales: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EstimateComponent {
+    pub name: &'static str,
+    pub value: i64,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PriceEstimate {
+    pub model: &'static str,
+    pub estimate: i64,
+    pub low: i64,
+    pub high: i64,
+    /// 0.0 - 1.0, driven by how much market data backed the estimate
+    pub confidence: f64,
+    pub components: Vec<EstimateComponent>,
+}
+
+/// A fair-value model. Implement this to swap the heuristic for e.g. an ML model.
+pub trait PriceModel: Send + Sync {
+    fn estimate(&self, inputs: &EstimateInputs) -> Option<PriceEstimate>;
+}
+
+/// Transparent weighted blend of the highest trait floor, the median of
+/// recent comparable sales and the trend-adjusted collection floor. Missing
+/// components are dropped and the remaining weights renormalized; the result
+/// never goes below the collection floor.
+pub struct HeuristicModel;
+
+impl PriceModel for HeuristicModel {
+    fn estimate(&self, inputs: &EstimateInputs) -> Option<PriceEstimate> {
+        let mut components = Vec::new();
+
+        if let Some(trait_floor) = inputs.trait_floors.iter().map(|t| t.floor_price).max() {
+            components.push(EstimateComponent {
+                name: "trait_floor",
+                value: trait_floor,
+                weight: 0.4,
+            });
+        }
+
+        let median_sale = median(&inputs.comparable_sales);
+        if let Some(median_sale) = median_sale {
+            components.push(EstimateComponent {
+                name: "comparable_sales",
+                value: median_sale,
+                weight: 0.4,
+            });
+        }
+
+        if let Some(floor) = inputs.collection_floor {
+            // Lean half-way into the weekly floor trend, capped at +/-50%
+            let trend = match inputs.collection_floor_7d_ago {
+                Some(previous) if previous > 0 => (floor as f64 / previous as f64).clamp(0.5, 1.5),
+                _ => 1.0,
+            };
+            components.push(EstimateComponent {
+                name: "collection_floor",
+                value: (floor as f64 * (1.0 + (trend - 1.0) / 2.0)).round() as i64,
+                weight: 0.2,
+            });
+        }
+
+        if components.is_empty() {
+            return None;
+        }
+
+        let total_weight: f64 = components.iter().map(|c| c.weight).sum();
+        for component in &mut components {
+            component.weight /= total_weight;
+        }
+
+        let mut estimate = components
+            .iter()
+            .map(|c| c.value as f64 * c.weight)
+            .sum::<f64>()
+            .round() as i64;
+        if let Some(floor) = inputs.collection_floor {
+            estimate = estimate.max(floor);
+        }
+
+        // Spread from comparable sales dispersion, defaulting to +/-20%
+        let spread = match (inputs.comparable_sales.len(), median_sale) {
+            (n, Some(median)) if n >= 3 && median > 0 => {
+                let mad = median_absolute_deviation(&inputs.comparable_sales, median);
+                (mad as f64 / median as f64).clamp(0.05, 0.5)
+            }
+            _ => 0.2,
+        };
+
+        let confidence = (components.len() as f64 / 3.0) * 0.6
+            + (inputs.comparable_sales.len() as f64 / MAX_COMPARABLE_SALES as f64).min(1.0) * 0.4;
+
+        Some(PriceEstimate {
+            model: "heuristic-v1",
+            estimate,
+            low: (estimate as f64 * (1.0 - spread)).round() as i64,
+            high: (estimate as f64 * (1.0 + spread)).round() as i64,
+            confidence: (confidence * 100.0).round() / 100.0,
+            components,
+        })
+    }
+}
+
+/// Collect the market data the models need for `nft`
+pub async fn gather_inputs(pool: &PgPool, nft: &Nft) -> Result<EstimateInputs, AppError> {
+    let Some(collection_id) = nft.collection_id else {
+        return Ok(EstimateInputs {
+            collection_floor: None,
+            collection_floor_7d_ago: None,
+            trait_floors: Vec::new(),
+            comparable_sales: Vec::new(),
+        });
+    };
+
+    let collection_floor: Option<i64> = sqlx::query_scalar(
+        r#"
+        SELECT MIN(l.price) FROM listings l
+        JOIN nfts n ON n.mint_address = l.nft_mint
+        WHERE n.collection_id = $1 AND l.status = 'active' AND l.deleted_at IS NULL
+          AND l.currency_mint = $2
+        "#,
+    )
+    .bind(collection_id)
+    .bind(NATIVE_SOL_MINT)
+    .fetch_one(pool)
+    .await?;
+
+    let collection_floor_7d_ago: Option<i64> = sqlx::query_scalar(
+        r#"
+        SELECT floor_price FROM collection_holder_history
+        WHERE collection_id = $1 AND snapshot_date <= CURRENT_DATE - 7
+        ORDER BY snapshot_date DESC LIMIT 1
+        "#,
+    )
+    .bind(collection_id)
+    .fetch_optional(pool)
+    .await?
+    .flatten();
+
+    let trait_floors = sqlx::query_as::<_, TraitFloor>(
+        r#"
+        SELECT a->>'trait_type' AS trait_type, a->>'value' AS value, MIN(l.price) AS floor_price
+        FROM nfts target
+        CROSS JOIN LATERAL jsonb_array_elements(target.attributes) AS a
+        JOIN nfts n ON n.collection_id = target.collection_id
+            AND n.attributes @> jsonb_build_array(
+                jsonb_build_object('trait_type', a->'trait_type', 'value', a->'value'))
+        JOIN listings l ON l.nft_mint = n.mint_address
+            AND l.status = 'active' AND l.deleted_at IS NULL AND l.currency_mint = $2
+        WHERE target.mint_address = $1 AND jsonb_typeof(target.attributes) = 'array'
+        GROUP BY 1, 2
+        "#,
+    )
+    .bind(&nft.mint_address)
+    .bind(NATIVE_SOL_MINT)
+    .fetch_all(pool)
+    .await?;
+
+    let (rank_min, rank_max) = match nft.rarity_rank {
+        Some(rank) => {
+            let window = ((rank as f64) * COMPARABLE_RANK_WINDOW).ceil() as i32;
+            (Some(rank - window), Some(rank + window))
+        }
+        None => (None, None),
+    };
+
+    let comparable_sales: Vec<i64> = sqlx::query_scalar(
+        r#"
+        SELECT s.base_price FROM sales s
+        JOIN nfts n ON n.mint_address = s.nft_mint
+        WHERE n.collection_id = $1
+          AND s.block_time >= NOW() - make_interval(days => $2)
+          AND ($3::int IS NULL OR n.rarity_rank BETWEEN $3 AND $4)
+        ORDER BY s.block_time DESC
+        LIMIT $5
+        "#,
+    )
+    .bind(collection_id)
+    .bind(COMPARABLE_SALES_DAYS)
+    .bind(rank_min)
+    .bind(rank_max)
+    .bind(MAX_COMPARABLE_SALES)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(EstimateInputs {
+        collection_floor,
+        collection_floor_7d_ago,
+        trait_floors,
+        comparable_sales,
+    })
+}
+
+fn median(values: &[i64]) -> Option<i64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let mid = sorted.len() / 2;
+    Some(if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2
+    } else {
+        sorted[mid]
+    })
+}
+
+fn median_absolute_deviation(values: &[i64], median_value: i64) -> i64 {
+    let deviations: Vec<i64> = values.iter().map(|v| (v - median_value).abs()).collect();
+    median(&deviations).unwrap_or(0)
+}
@@ -0,0 +1,91 @@
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+use crate::error::AppError;
+
+/// Wrapped SOL mint, used as the currency of native SOL prices
+pub const NATIVE_SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenPrice {
+    usd_price: f64,
+    decimals: u8,
+}
+
+/// Converts SPL token amounts into lamports using USD prices from a
+/// Jupiter-compatible price API (`GET {url}?ids=mint1,mint2`).
+#[derive(Clone)]
+pub struct PriceOracle {
+    url: String,
+    http: reqwest::Client,
+}
+
+impl PriceOracle {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Value of `amount` (in the smallest unit of `currency_mint`) in lamports
+    pub async fn to_base_amount(&self, currency_mint: &str, amount: i64) -> Result<i64, AppError> {
+        if currency_mint == NATIVE_SOL_MINT {
+            return Ok(amount);
+        }
+
+        let prices = self.fetch_prices(&[currency_mint, NATIVE_SOL_MINT]).await?;
+
+        let (Some(token), Some(sol)) = (prices.get(currency_mint), prices.get(NATIVE_SOL_MINT))
+        else {
+            return Err(AppError::BadRequest(format!(
+                "No oracle price for currency {}",
+                currency_mint
+            )));
+        };
+
+        let token_amount = amount as f64 / 10f64.powi(token.decimals as i32);
+        let lamports = token_amount * token.usd_price / sol.usd_price * LAMPORTS_PER_SOL;
+
+        Ok(lamports.round() as i64)
+    }
+
+    /// Current USD price of one SOL
+    pub async fn sol_usd_price(&self) -> Result<f64, AppError> {
+        self.fetch_prices(&[NATIVE_SOL_MINT])
+            .await?
+            .get(NATIVE_SOL_MINT)
+            .map(|price| price.usd_price)
+            .ok_or_else(|| AppError::BadRequest("No oracle price for SOL".to_string()))
+    }
+
+    /// Append the current SOL/USD price to `sol_usd_prices`
+    pub async fn record_sol_usd_price(&self, pool: &PgPool) -> Result<f64, AppError> {
+        let usd_price = self.sol_usd_price().await?;
+
+        sqlx::query!(
+            "INSERT INTO sol_usd_prices (recorded_at, usd_price) VALUES (NOW(), $1) ON CONFLICT DO NOTHING",
+            usd_price
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(usd_price)
+    }
+
+    async fn fetch_prices(&self, mints: &[&str]) -> Result<HashMap<String, TokenPrice>, AppError> {
+        self.http
+            .get(&self.url)
+            .query(&[("ids", mints.join(","))])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AppError::BadRequest(format!("Price oracle request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::BadRequest(format!("Invalid price oracle response: {}", e)))
+    }
+}
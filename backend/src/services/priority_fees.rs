@@ -0,0 +1,146 @@
+//! Compute budget instructions for the transactions the API builds, so they
+//! still land when the cluster is congested: a compute unit limit from
+//! simulating the transaction, and a compute unit price from the fees
+//! recently paid to write the same accounts
+
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcSimulateTransactionConfig};
+use solana_sdk::{
+    instruction::Instruction, message::Message, pubkey, pubkey::Pubkey, transaction::Transaction,
+};
+use solmint_client::ComputeBudgetOverride;
+
+use crate::error::AppError;
+
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// The most compute units a transaction can request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+/// Requested when simulation fails, e.g. because the payer isn't funded yet
+const FALLBACK_COMPUTE_UNIT_LIMIT: u32 = 400_000;
+/// Headroom over the simulated units, which vary with account state
+const COMPUTE_UNIT_MARGIN_PERCENT: u64 = 20;
+/// Percentile of recent per-slot fees to pay
+const PRIORITY_FEE_PERCENTILE: usize = 75;
+/// Micro-lamports per compute unit. Caps what a fee spike can cost a user
+/// who didn't set a price: 2,000,000 lamports at the fallback limit.
+const MAX_COMPUTE_UNIT_PRICE: u64 = 5_000_000;
+/// `getRecentPrioritizationFees` takes at most this many accounts
+const MAX_FEE_ACCOUNTS: usize = 128;
+
+/// Estimates compute budgets against the configured RPC endpoint
+pub struct PriorityFeeEstimator {
+    rpc: RpcClient,
+}
+
+impl PriorityFeeEstimator {
+    pub fn new(rpc_url: String) -> Self {
+        Self {
+            rpc: RpcClient::new(rpc_url),
+        }
+    }
+
+    /// `instructions` behind compute budget instructions, with the limit and
+    /// price from `overrides` where set and estimated otherwise
+    pub async fn with_compute_budget(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        overrides: &ComputeBudgetOverride,
+    ) -> Result<Vec<Instruction>, AppError> {
+        let limit = match overrides.compute_unit_limit {
+            Some(limit) => limit.min(MAX_COMPUTE_UNIT_LIMIT),
+            None => self.compute_unit_limit(instructions, payer).await,
+        };
+        let price = match overrides.compute_unit_price {
+            Some(price) => price,
+            None => self.compute_unit_price(instructions).await?,
+        };
+
+        let mut budgeted = vec![set_compute_unit_limit(limit), set_compute_unit_price(price)];
+        budgeted.extend_from_slice(instructions);
+        Ok(budgeted)
+    }
+
+    /// Compute units the instructions use in simulation, plus a margin
+    pub async fn compute_unit_limit(&self, instructions: &[Instruction], payer: &Pubkey) -> u32 {
+        let mut simulated = vec![
+            set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+            set_compute_unit_price(0),
+        ];
+        simulated.extend_from_slice(instructions);
+        // Unsigned: the simulation skips signature checks and supplies a blockhash
+        let transaction = Transaction::new_unsigned(Message::new(&simulated, Some(payer)));
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            ..Default::default()
+        };
+
+        match self
+            .rpc
+            .simulate_transaction_with_config(&transaction, config)
+            .await
+        {
+            Ok(response) if response.value.err.is_none() => {
+                response
+                    .value
+                    .units_consumed
+                    .map_or(FALLBACK_COMPUTE_UNIT_LIMIT, |units| {
+                        let units = units + units * COMPUTE_UNIT_MARGIN_PERCENT / 100;
+                        units.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+                    })
+            }
+            _ => FALLBACK_COMPUTE_UNIT_LIMIT,
+        }
+    }
+
+    /// Micro-lamports per compute unit: a high percentile of the lowest fees
+    /// that landed transactions writing the same accounts in recent slots
+    pub async fn compute_unit_price(&self, instructions: &[Instruction]) -> Result<u64, AppError> {
+        let mut writable: Vec<Pubkey> = instructions
+            .iter()
+            .flat_map(|instruction| &instruction.accounts)
+            .filter(|meta| meta.is_writable)
+            .map(|meta| meta.pubkey)
+            .collect();
+        writable.sort_unstable();
+        writable.dedup();
+        writable.truncate(MAX_FEE_ACCOUNTS);
+
+        let mut fees: Vec<u64> = self
+            .rpc
+            .get_recent_prioritization_fees(&writable)
+            .await?
+            .into_iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect();
+        if fees.is_empty() {
+            return Ok(0);
+        }
+        fees.sort_unstable();
+
+        let index = (fees.len() - 1) * PRIORITY_FEE_PERCENTILE / 100;
+        Ok(fees[index].min(MAX_COMPUTE_UNIT_PRICE))
+    }
+}
+
+pub fn set_compute_unit_limit(units: u32) -> Instruction {
+    let mut data = vec![2];
+    data.extend_from_slice(&units.to_le_bytes());
+    Instruction {
+        program_id: COMPUTE_BUDGET_PROGRAM_ID,
+        accounts: Vec::new(),
+        data,
+    }
+}
+
+pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+    let mut data = vec![3];
+    data.extend_from_slice(&micro_lamports.to_le_bytes());
+    Instruction {
+        program_id: COMPUTE_BUDGET_PROGRAM_ID,
+        accounts: Vec::new(),
+        data,
+    }
+}
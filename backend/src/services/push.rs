@@ -0,0 +1,449 @@
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::PgPool;
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    error::AppError,
+    models::{
+        Notification, PendingPush, PushDevice, CATEGORY_FLOOR_ALERT, CATEGORY_ITEM_SOLD,
+        CATEGORY_OFFER_RECEIVED, PLATFORM_ANDROID, PLATFORM_IOS,
+    },
+};
+
+/// Pushes are only attempted for this long after the notification is created
+const PUSH_WINDOW_HOURS: i32 = 1;
+const FCM_SCOPE: &str = "https://www.googleapis.com/auth/firebase.messaging";
+/// Both providers accept tokens for an hour; refresh a little early
+const AUTH_TOKEN_LIFETIME: Duration = Duration::from_secs(50 * 60);
+
+/// Outcome of sending to one device
+enum Delivery {
+    Sent,
+    /// The provider no longer recognises the token
+    InvalidToken,
+    Failed(String),
+}
+
+struct Message {
+    title: String,
+    body: String,
+    collapse_key: String,
+    category: String,
+    count: usize,
+}
+
+#[derive(Deserialize)]
+struct ServiceAccount {
+    project_id: String,
+    client_email: String,
+    private_key: String,
+    #[serde(default = "default_token_uri")]
+    token_uri: String,
+}
+
+fn default_token_uri() -> String {
+    "https://oauth2.googleapis.com/token".to_string()
+}
+
+#[derive(Serialize)]
+struct FcmClaims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: i64,
+    exp: i64,
+}
+
+#[derive(Deserialize)]
+struct AccessToken {
+    access_token: String,
+}
+
+#[derive(Serialize)]
+struct ApnsClaims<'a> {
+    iss: &'a str,
+    iat: i64,
+}
+
+/// Firebase Cloud Messaging over the HTTP v1 API, authenticated with a
+/// service account
+struct Fcm {
+    account: ServiceAccount,
+    key: EncodingKey,
+    access_token: Mutex<Option<(String, Instant)>>,
+}
+
+/// Apple Push Notification service with token-based (.p8 key) authentication
+struct Apns {
+    key: EncodingKey,
+    key_id: String,
+    team_id: String,
+    topic: String,
+    host: &'static str,
+    http: reqwest::Client,
+    provider_token: Mutex<Option<(String, Instant)>>,
+}
+
+/// Delivers pending notifications to registered devices. Android tokens go
+/// to FCM and iOS tokens to APNs; either provider may be left unconfigured.
+#[derive(Clone)]
+pub struct PushSender {
+    fcm: Option<Arc<Fcm>>,
+    apns: Option<Arc<Apns>>,
+    http: reqwest::Client,
+}
+
+impl PushSender {
+    /// `None` when neither FCM nor APNs is configured
+    pub fn from_config(config: &Config) -> Result<Option<Self>, AppError> {
+        let fcm = match &config.fcm_credentials_file {
+            Some(path) => {
+                let account: ServiceAccount = serde_json::from_str(&std::fs::read_to_string(path)?)
+                    .map_err(|e| {
+                        AppError::ConfigError(format!("invalid FCM_CREDENTIALS_FILE: {}", e))
+                    })?;
+                let key =
+                    EncodingKey::from_rsa_pem(account.private_key.as_bytes()).map_err(|e| {
+                        AppError::ConfigError(format!("invalid FCM service account key: {}", e))
+                    })?;
+                Some(Arc::new(Fcm {
+                    account,
+                    key,
+                    access_token: Mutex::new(None),
+                }))
+            }
+            None => None,
+        };
+
+        let apns = match (
+            &config.apns_key_file,
+            &config.apns_key_id,
+            &config.apns_team_id,
+            &config.apns_topic,
+        ) {
+            (Some(path), Some(key_id), Some(team_id), Some(topic)) => {
+                let key = EncodingKey::from_ec_pem(&std::fs::read(path)?)
+                    .map_err(|e| AppError::ConfigError(format!("invalid APNS_KEY_FILE: {}", e)))?;
+                // APNs only speaks HTTP/2
+                let http = reqwest::Client::builder()
+                    .http2_prior_knowledge()
+                    .build()
+                    .map_err(|e| AppError::ConfigError(format!("APNs client: {}", e)))?;
+                Some(Arc::new(Apns {
+                    key,
+                    key_id: key_id.clone(),
+                    team_id: team_id.clone(),
+                    topic: topic.clone(),
+                    host: if config.apns_sandbox {
+                        "https://api.sandbox.push.apple.com"
+                    } else {
+                        "https://api.push.apple.com"
+                    },
+                    http,
+                    provider_token: Mutex::new(None),
+                }))
+            }
+            _ => None,
+        };
+
+        if fcm.is_none() && apns.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            fcm,
+            apns,
+            http: reqwest::Client::new(),
+        }))
+    }
+
+    /// Push everything pending, one message per wallet and collapse key.
+    /// Returns how many messages were delivered to at least one device.
+    pub async fn deliver(&self, pool: &PgPool) -> Result<u64, AppError> {
+        let pending = Notification::pending_push(pool, PUSH_WINDOW_HOURS).await?;
+
+        let mut groups: BTreeMap<(String, String), Vec<Notification>> = BTreeMap::new();
+        for PendingPush {
+            notification,
+            collapse_key,
+        } in pending
+        {
+            groups
+                .entry((notification.wallet_address.clone(), collapse_key))
+                .or_default()
+                .push(notification);
+        }
+
+        let mut devices_by_wallet: BTreeMap<String, Vec<PushDevice>> = BTreeMap::new();
+        let mut delivered = 0;
+
+        for ((wallet, collapse_key), notifications) in groups {
+            if !devices_by_wallet.contains_key(&wallet) {
+                let devices = PushDevice::list_for_wallet(pool, &wallet).await?;
+                devices_by_wallet.insert(wallet.clone(), devices);
+            }
+            let devices = &devices_by_wallet[&wallet];
+            let message = render(collapse_key, &notifications);
+
+            // Retry on the next run unless every device either accepted the
+            // message or no longer exists
+            let mut settled = true;
+            let mut sent = false;
+            // Devices on a platform without a configured provider are skipped
+            for device in devices
+                .iter()
+                .filter(|device| self.supports(&device.platform))
+            {
+                match self.send(device, &message).await {
+                    Delivery::Sent => sent = true,
+                    Delivery::InvalidToken => PushDevice::disable(pool, device.id).await?,
+                    Delivery::Failed(e) => {
+                        settled = false;
+                        println!("Push to device {} failed: {}", device.id, e);
+                    }
+                }
+            }
+
+            if settled || sent {
+                let ids: Vec<Uuid> = notifications.iter().map(|n| n.id).collect();
+                Notification::mark_pushed(pool, &ids).await?;
+            }
+            if sent {
+                delivered += 1;
+            }
+        }
+
+        Ok(delivered)
+    }
+
+    fn supports(&self, platform: &str) -> bool {
+        match platform {
+            PLATFORM_ANDROID => self.fcm.is_some(),
+            PLATFORM_IOS => self.apns.is_some(),
+            _ => false,
+        }
+    }
+
+    async fn send(&self, device: &PushDevice, message: &Message) -> Delivery {
+        let result = match (device.platform.as_str(), &self.fcm, &self.apns) {
+            (PLATFORM_ANDROID, Some(fcm), _) => fcm.send(&self.http, &device.token, message).await,
+            (PLATFORM_IOS, _, Some(apns)) => apns.send(&device.token, message).await,
+            (platform, _, _) => {
+                return Delivery::Failed(format!("no push provider for {}", platform))
+            }
+        };
+
+        result.unwrap_or_else(|e| Delivery::Failed(e.to_string()))
+    }
+}
+
+impl Fcm {
+    async fn send(
+        &self,
+        http: &reqwest::Client,
+        token: &str,
+        message: &Message,
+    ) -> Result<Delivery, AppError> {
+        let access_token = self.access_token(http).await?;
+        let response = http
+            .post(format!(
+                "https://fcm.googleapis.com/v1/projects/{}/messages:send",
+                self.account.project_id
+            ))
+            .bearer_auth(access_token)
+            .json(&json!({
+                "message": {
+                    "token": token,
+                    "notification": { "title": message.title, "body": message.body },
+                    "data": {
+                        "category": message.category,
+                        "collapse_key": message.collapse_key,
+                        "count": message.count.to_string()
+                    },
+                    "android": { "collapse_key": message.collapse_key }
+                }
+            }))
+            .send()
+            .await
+            .map_err(|e| AppError::Upstream(format!("FCM request failed: {}", e)))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(Delivery::Sent);
+        }
+        let body = response.text().await.unwrap_or_default();
+        if status == reqwest::StatusCode::NOT_FOUND
+            || body.contains("UNREGISTERED")
+            || body.contains("registration token is not a valid")
+        {
+            return Ok(Delivery::InvalidToken);
+        }
+
+        Ok(Delivery::Failed(format!(
+            "FCM returned {}: {}",
+            status, body
+        )))
+    }
+
+    /// OAuth access token from the service account, cached until near expiry
+    async fn access_token(&self, http: &reqwest::Client) -> Result<String, AppError> {
+        let mut cached = self.access_token.lock().await;
+        if let Some((token, issued)) = cached.as_ref() {
+            if issued.elapsed() < AUTH_TOKEN_LIFETIME {
+                return Ok(token.clone());
+            }
+        }
+
+        let now = Utc::now().timestamp();
+        let assertion = jsonwebtoken::encode(
+            &Header::new(Algorithm::RS256),
+            &FcmClaims {
+                iss: &self.account.client_email,
+                scope: FCM_SCOPE,
+                aud: &self.account.token_uri,
+                iat: now,
+                exp: now + 3600,
+            },
+            &self.key,
+        )
+        .map_err(|e| AppError::Upstream(format!("FCM auth: {}", e)))?;
+
+        let token: AccessToken = http
+            .post(&self.account.token_uri)
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", assertion.as_str()),
+            ])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AppError::Upstream(format!("FCM auth failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::Upstream(format!("Invalid FCM auth response: {}", e)))?;
+
+        *cached = Some((token.access_token.clone(), Instant::now()));
+        Ok(token.access_token)
+    }
+}
+
+impl Apns {
+    async fn send(&self, token: &str, message: &Message) -> Result<Delivery, AppError> {
+        let provider_token = self.provider_token().await?;
+        let response = self
+            .http
+            .post(format!("{}/3/device/{}", self.host, token))
+            .header("authorization", format!("bearer {}", provider_token))
+            .header("apns-topic", &self.topic)
+            .header("apns-push-type", "alert")
+            // APNs caps the collapse id at 64 bytes
+            .header(
+                "apns-collapse-id",
+                truncate(&message.collapse_key, 64).to_string(),
+            )
+            .json(&json!({
+                "aps": {
+                    "alert": { "title": message.title, "body": message.body },
+                    "thread-id": message.collapse_key,
+                    "sound": "default"
+                },
+                "category": message.category,
+                "count": message.count
+            }))
+            .send()
+            .await
+            .map_err(|e| AppError::Upstream(format!("APNs request failed: {}", e)))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(Delivery::Sent);
+        }
+        let body = response.text().await.unwrap_or_default();
+        if status == reqwest::StatusCode::GONE
+            || body.contains("BadDeviceToken")
+            || body.contains("Unregistered")
+        {
+            return Ok(Delivery::InvalidToken);
+        }
+
+        Ok(Delivery::Failed(format!(
+            "APNs returned {}: {}",
+            status, body
+        )))
+    }
+
+    /// Signed provider JWT, reused until near expiry as Apple asks
+    async fn provider_token(&self) -> Result<String, AppError> {
+        let mut cached = self.provider_token.lock().await;
+        if let Some((token, issued)) = cached.as_ref() {
+            if issued.elapsed() < AUTH_TOKEN_LIFETIME {
+                return Ok(token.clone());
+            }
+        }
+
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some(self.key_id.clone());
+        let token = jsonwebtoken::encode(
+            &header,
+            &ApnsClaims {
+                iss: &self.team_id,
+                iat: Utc::now().timestamp(),
+            },
+            &self.key,
+        )
+        .map_err(|e| AppError::Upstream(format!("APNs auth: {}", e)))?;
+
+        *cached = Some((token.clone(), Instant::now()));
+        Ok(token)
+    }
+}
+
+/// One message for a burst of notifications sharing a collapse key
+fn render(collapse_key: String, notifications: &[Notification]) -> Message {
+    let latest = notifications
+        .last()
+        .expect("notification groups are never empty");
+    let count = notifications.len();
+
+    // Only the latest floor matters
+    let (title, body) = if count == 1 || latest.category == CATEGORY_FLOOR_ALERT {
+        (latest.title.clone(), latest.body.clone())
+    } else {
+        let title = match latest.category.as_str() {
+            CATEGORY_ITEM_SOLD => format!("{} of your NFTs sold", count),
+            CATEGORY_OFFER_RECEIVED => format!("{} new offers", count),
+            _ => format!("{} new notifications", count),
+        };
+        (title, format!("{} and {} more", latest.body, count - 1))
+    };
+
+    Message {
+        title,
+        body,
+        collapse_key,
+        category: latest.category.clone(),
+        count,
+    }
+}
+
+/// `value` cut to at most `max_bytes`, on a character boundary
+pub(crate) fn truncate(value: &str, max_bytes: usize) -> &str {
+    if value.len() <= max_bytes {
+        return value;
+    }
+    let mut end = max_bytes;
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    &value[..end]
+}
@@ -0,0 +1,228 @@
+use serde_json::Value;
+use sqlx::PgPool;
+use std::collections::{BTreeSet, HashMap};
+use uuid::Uuid;
+
+use crate::{error::AppError, models::CollectionTrait};
+
+/// Value recorded for NFTs that don't have a given trait type
+const MISSING_TRAIT: &str = "None";
+/// Pseudo trait type scoring the number of traits an NFT has
+const TRAIT_COUNT_TYPE: &str = "Trait Count";
+
+struct TraitFrequency {
+    trait_type: String,
+    value: String,
+    count: i64,
+    frequency: f64,
+}
+
+struct NftRarity {
+    mint_address: String,
+    score: f64,
+    statistical: f64,
+    rank: i32,
+}
+
+/// Recompute rarity for every collection flagged as stale
+pub async fn refresh_stale_collections(pool: &PgPool) -> Result<usize, AppError> {
+    let stale: Vec<Uuid> =
+        sqlx::query_scalar!("SELECT id FROM collections WHERE rarity_stale = TRUE")
+            .fetch_all(pool)
+            .await?;
+
+    for collection_id in &stale {
+        recompute_collection(pool, *collection_id).await?;
+    }
+
+    Ok(stale.len())
+}
+
+/// Compute trait frequencies, rarity scores and ranks for one collection.
+///
+/// `rarity_score` is the normalized trait rarity (as popularised by
+/// HowRare/rarity.tools): the sum over trait types of `1 / frequency`,
+/// weighted so trait types with many possible values don't dominate. Missing
+/// traits count as a `None` value and the number of traits is scored as its
+/// own trait type. Ties are broken by statistical rarity, the product of the
+/// trait frequencies.
+pub async fn recompute_collection(pool: &PgPool, collection_id: Uuid) -> Result<(), AppError> {
+    let rows = sqlx::query!(
+        "SELECT mint_address, attributes FROM nfts WHERE collection_id = $1",
+        collection_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let nfts: Vec<(String, HashMap<String, String>)> = rows
+        .into_iter()
+        .map(|row| (row.mint_address, parse_traits(row.attributes.as_ref())))
+        .collect();
+
+    let (frequencies, rarities) = compute(&nfts);
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        "DELETE FROM collection_traits WHERE collection_id = $1",
+        collection_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO collection_traits (collection_id, trait_type, value, count, frequency)
+        SELECT $1, * FROM UNNEST($2::text[], $3::text[], $4::bigint[], $5::float8[])
+        "#,
+        collection_id,
+        &frequencies
+            .iter()
+            .map(|t| t.trait_type.clone())
+            .collect::<Vec<_>>(),
+        &frequencies
+            .iter()
+            .map(|t| t.value.clone())
+            .collect::<Vec<_>>(),
+        &frequencies.iter().map(|t| t.count).collect::<Vec<_>>(),
+        &frequencies.iter().map(|t| t.frequency).collect::<Vec<_>>()
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE nfts SET
+            rarity_score = data.score::numeric(10, 4),
+            rarity_rank = data.rank,
+            updated_at = NOW()
+        FROM UNNEST($1::text[], $2::float8[], $3::int[]) AS data(mint_address, score, rank)
+        WHERE nfts.mint_address = data.mint_address
+        "#,
+        &rarities
+            .iter()
+            .map(|r| r.mint_address.clone())
+            .collect::<Vec<_>>(),
+        &rarities.iter().map(|r| r.score).collect::<Vec<_>>(),
+        &rarities.iter().map(|r| r.rank).collect::<Vec<_>>()
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE collections SET rarity_stale = FALSE WHERE id = $1",
+        collection_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    // The trait rows were just rewritten without their floors
+    CollectionTrait::refresh_floors(pool, Some(collection_id)).await?;
+
+    println!(
+        "Recomputed rarity for collection {} ({} NFTs, {} trait values)",
+        collection_id,
+        rarities.len(),
+        frequencies.len()
+    );
+    Ok(())
+}
+
+fn parse_traits(attributes: Option<&Value>) -> HashMap<String, String> {
+    let mut traits = HashMap::new();
+
+    if let Some(Value::Array(attrs)) = attributes {
+        for attr in attrs {
+            let Some(trait_type) = attr.get("trait_type").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let value = match attr.get("value") {
+                Some(Value::String(s)) => s.clone(),
+                Some(Value::Null) | None => continue,
+                Some(other) => other.to_string(),
+            };
+            traits.insert(trait_type.to_string(), value);
+        }
+    }
+
+    let trait_count = traits.len().to_string();
+    traits.insert(TRAIT_COUNT_TYPE.to_string(), trait_count);
+    traits
+}
+
+fn compute(nfts: &[(String, HashMap<String, String>)]) -> (Vec<TraitFrequency>, Vec<NftRarity>) {
+    let total = nfts.len() as f64;
+    if nfts.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+
+    let trait_types: BTreeSet<&str> = nfts
+        .iter()
+        .flat_map(|(_, traits)| traits.keys().map(String::as_str))
+        .collect();
+
+    // Count every (trait type, value) pair, treating absent traits as "None"
+    let mut counts: HashMap<(&str, &str), i64> = HashMap::new();
+    for (_, traits) in nfts {
+        for trait_type in &trait_types {
+            let value = traits
+                .get(*trait_type)
+                .map(String::as_str)
+                .unwrap_or(MISSING_TRAIT);
+            *counts.entry((trait_type, value)).or_default() += 1;
+        }
+    }
+
+    let mut values_per_type: HashMap<&str, usize> = HashMap::new();
+    for (trait_type, _) in counts.keys() {
+        *values_per_type.entry(trait_type).or_default() += 1;
+    }
+    let mean_values = values_per_type.values().sum::<usize>() as f64 / values_per_type.len() as f64;
+
+    let mut rarities: Vec<NftRarity> = nfts
+        .iter()
+        .map(|(mint_address, traits)| {
+            let mut score = 0.0;
+            let mut statistical = 1.0;
+            for trait_type in &trait_types {
+                let value = traits
+                    .get(*trait_type)
+                    .map(String::as_str)
+                    .unwrap_or(MISSING_TRAIT);
+                let frequency = counts[&(*trait_type, value)] as f64 / total;
+                let weight = mean_values / values_per_type[trait_type] as f64;
+                score += weight / frequency;
+                statistical *= frequency;
+            }
+            NftRarity {
+                mint_address: mint_address.clone(),
+                score,
+                statistical,
+                rank: 0,
+            }
+        })
+        .collect();
+
+    rarities.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.statistical.total_cmp(&b.statistical))
+    });
+    for (index, rarity) in rarities.iter_mut().enumerate() {
+        rarity.rank = index as i32 + 1;
+    }
+
+    let frequencies = counts
+        .into_iter()
+        .map(|((trait_type, value), count)| TraitFrequency {
+            trait_type: trait_type.to_string(),
+            value: value.to_string(),
+            count,
+            frequency: count as f64 / total,
+        })
+        .collect();
+
+    (frequencies, rarities)
+}
@@ -0,0 +1,217 @@
+//! Checks on transactions submitted to `/api/nft/send-transaction`, so the
+//! API only relays marketplace transactions rather than anything a client
+//! signs. Every instruction must be for an allowlisted program and match one
+//! of the account layouts that program's instructions take.
+
+use nft_marketplace_sdk::{
+    ids::{ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID},
+    instruction::MarketplaceInstruction,
+};
+use solana_sdk::{instruction::CompiledInstruction, message::VersionedMessage, pubkey::Pubkey};
+
+use super::priority_fees::COMPUTE_BUDGET_PROGRAM_ID;
+use crate::error::{AppError, ErrorCode};
+
+/// Accounts an instruction takes after its fixed ones
+enum Remaining {
+    Fixed,
+    /// Either none or exactly this many, e.g. `MintNft`'s collection accounts
+    Optional(usize),
+    /// Any number, e.g. transfer hook accounts
+    Any,
+    /// Any number of groups of this many, e.g. one per bundled NFT
+    Groups(usize),
+}
+
+/// The account layout of one instruction
+struct Template {
+    name: &'static str,
+    accounts: usize,
+    remaining: Remaining,
+    /// Positions of the accounts that must sign
+    signers: &'static [usize],
+}
+
+impl Template {
+    const fn new(
+        name: &'static str,
+        accounts: usize,
+        remaining: Remaining,
+        signers: &'static [usize],
+    ) -> Self {
+        Self {
+            name,
+            accounts,
+            remaining,
+            signers,
+        }
+    }
+
+    fn accepts_account_count(&self, count: usize) -> bool {
+        let Some(extra) = count.checked_sub(self.accounts) else {
+            return false;
+        };
+        match self.remaining {
+            Remaining::Fixed => extra == 0,
+            Remaining::Optional(n) => extra == 0 || extra == n,
+            Remaining::Any => true,
+            Remaining::Groups(n) => extra % n == 0,
+        }
+    }
+}
+
+/// Refuse `message` unless every instruction is one the marketplace relays
+pub fn check_relayable(message: &VersionedMessage, program_id: &Pubkey) -> Result<(), AppError> {
+    message
+        .sanitize()
+        .map_err(|e| AppError::ValidationError(format!("Malformed transaction: {}", e)))?;
+
+    let account_keys = message.static_account_keys();
+    for (position, instruction) in message.instructions().iter().enumerate() {
+        // Sanitized messages only invoke static keys
+        let invoked = account_keys[instruction.program_id_index as usize];
+        let template = if invoked == *program_id {
+            marketplace_template(&instruction.data)
+        } else if invoked == COMPUTE_BUDGET_PROGRAM_ID {
+            compute_budget_template(&instruction.data)
+        } else if invoked.to_bytes() == ASSOCIATED_TOKEN_PROGRAM_ID {
+            associated_token_template(&instruction.data)
+        } else if invoked.to_bytes() == TOKEN_PROGRAM_ID
+            || invoked.to_bytes() == TOKEN_2022_PROGRAM_ID
+        {
+            token_template(&instruction.data)
+        } else {
+            return Err(not_allowed(format!(
+                "instruction {} calls {}, which isn't relayed",
+                position, invoked
+            )));
+        };
+        let template = template.ok_or_else(|| {
+            not_allowed(format!(
+                "instruction {} isn't an instruction {} relays for",
+                position, invoked
+            ))
+        })?;
+
+        check_shape(message, instruction, &template)
+            .map_err(|problem| not_allowed(format!("instruction {}: {}", position, problem)))?;
+    }
+
+    Ok(())
+}
+
+fn check_shape(
+    message: &VersionedMessage,
+    instruction: &CompiledInstruction,
+    template: &Template,
+) -> Result<(), String> {
+    if !template.accepts_account_count(instruction.accounts.len()) {
+        return Err(format!(
+            "{} can't take {} accounts",
+            template.name,
+            instruction.accounts.len()
+        ));
+    }
+    for &signer in template.signers {
+        if !message.is_signer(instruction.accounts[signer] as usize) {
+            return Err(format!("{} account {} must sign", template.name, signer));
+        }
+    }
+
+    Ok(())
+}
+
+/// Layouts of the instructions wallets send, as documented on
+/// `MarketplaceInstruction`. Authority-only instructions aren't relayed.
+fn marketplace_template(data: &[u8]) -> Option<Template> {
+    use MarketplaceInstruction::*;
+    use Remaining::*;
+
+    let template = match MarketplaceInstruction::unpack(data).ok()? {
+        MintNft { .. } => Template::new("MintNft", 10, Optional(4), &[0, 1]),
+        MintMasterEdition { .. } => Template::new("MintMasterEdition", 10, Optional(4), &[0, 1]),
+        PrintEdition { .. } => Template::new("PrintEdition", 16, Fixed, &[0, 1]),
+        MintCompressedNft { .. } => Template::new("MintCompressedNft", 8, Fixed, &[0, 1]),
+        UpdateNftMetadata { .. } => Template::new("UpdateNftMetadata", 4, Fixed, &[0]),
+        ListNft { .. } => Template::new("ListNft", 11, Any, &[0]),
+        ListNftPrivate { .. } => Template::new("ListNftPrivate", 11, Any, &[0]),
+        ListNftDelegated { .. } => Template::new("ListNftDelegated", 11, Fixed, &[0]),
+        BuyNft { .. } => Template::new("BuyNft", 16, Any, &[0]),
+        CancelListing => Template::new("CancelListing", 8, Any, &[0]),
+        MakeOffer { .. } => Template::new("MakeOffer", 6, Fixed, &[0]),
+        AcceptOffer { .. } => Template::new("AcceptOffer", 13, Any, &[0]),
+        CancelOffer => Template::new("CancelOffer", 3, Fixed, &[0]),
+        CreateAuction { .. } => Template::new("CreateAuction", 10, Any, &[0]),
+        PlaceBid { .. } => Template::new("PlaceBid", 4, Fixed, &[0]),
+        SettleAuction => Template::new("SettleAuction", 14, Any, &[0]),
+        MakeCollectionOffer { .. } => Template::new("MakeCollectionOffer", 6, Fixed, &[0]),
+        AcceptCollectionOffer { .. } => Template::new("AcceptCollectionOffer", 13, Any, &[0]),
+        CancelCollectionOffer => Template::new("CancelCollectionOffer", 3, Fixed, &[0]),
+        ListBundle { .. } => Template::new("ListBundle", 8, Groups(3), &[0]),
+        BuyBundle { .. } => Template::new("BuyBundle", 9, Groups(5), &[0]),
+        CancelBundle => Template::new("CancelBundle", 5, Groups(3), &[0]),
+        ListNftForRent { .. } => Template::new("ListNftForRent", 10, Fixed, &[0]),
+        RentNft { .. } => Template::new("RentNft", 10, Fixed, &[0]),
+        ReclaimRental => Template::new("ReclaimRental", 10, Fixed, &[]),
+        CancelRental => Template::new("CancelRental", 8, Fixed, &[0]),
+        CreateMintConfig { .. } => Template::new("CreateMintConfig", 5, Fixed, &[0]),
+        MintFromConfig { .. } => Template::new("MintFromConfig", 13, Fixed, &[0, 4]),
+        _ => return None,
+    };
+
+    Some(template)
+}
+
+/// `SetComputeUnitLimit`, `SetComputeUnitPrice` and
+/// `SetLoadedAccountsDataSizeLimit`
+fn compute_budget_template(data: &[u8]) -> Option<Template> {
+    match (data.first()?, data.len()) {
+        (2, 5) => Some(Template::new(
+            "SetComputeUnitLimit",
+            0,
+            Remaining::Fixed,
+            &[],
+        )),
+        (3, 9) => Some(Template::new(
+            "SetComputeUnitPrice",
+            0,
+            Remaining::Fixed,
+            &[],
+        )),
+        (4, 5) => Some(Template::new(
+            "SetLoadedAccountsDataSizeLimit",
+            0,
+            Remaining::Fixed,
+            &[],
+        )),
+        _ => None,
+    }
+}
+
+/// `Create` and `CreateIdempotent`, e.g. for a buyer's token account
+fn associated_token_template(data: &[u8]) -> Option<Template> {
+    match data {
+        [] | [0] | [1] => Some(Template::new(
+            "CreateAssociatedTokenAccount",
+            6,
+            Remaining::Fixed,
+            &[0],
+        )),
+        _ => None,
+    }
+}
+
+/// `CloseAccount` and `SyncNative`, for wrapping and unwrapping SOL around
+/// a purchase
+fn token_template(data: &[u8]) -> Option<Template> {
+    match data {
+        // Multisig owners pass their signers after the owner
+        [9] => Some(Template::new("CloseAccount", 3, Remaining::Any, &[])),
+        [17] => Some(Template::new("SyncNative", 1, Remaining::Fixed, &[])),
+        _ => None,
+    }
+}
+
+fn not_allowed(detail: String) -> AppError {
+    AppError::Rejected(ErrorCode::TransactionNotAllowed, detail)
+}
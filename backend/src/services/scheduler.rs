@@ -0,0 +1,485 @@
+use chrono::{Days, Utc};
+use sqlx::PgPool;
+use std::{future::Future, sync::Arc};
+use tokio_cron_scheduler::{Job, JobScheduler};
+
+use crate::{
+    config::Config,
+    error::AppError,
+    models::{
+        Activity, Collection, CollectionTrait, Dashboard, MarketplaceStats, Notification,
+        OutboxEvent, PointsSeason, WebhookDelivery,
+    },
+    services::{
+        alerts::AlertDispatcher, analytics_export::AnalyticsExporter, checkout::CheckoutProvider,
+        collection_import::CollectionImporter, email::Mailer, metadata_refresh::MetadataRefresher,
+        outbox_relay::OutboxRelay, price_oracle::PriceOracle, push::PushSender, rarity,
+        spam::SpamScorer, supervisor::ShutdownSignal, webhooks::WebhookSender,
+    },
+};
+
+/// Run the periodic jobs until shutdown is signalled. Schedules are UTC.
+pub async fn run(db: PgPool, config: Config, mut shutdown: ShutdownSignal) -> Result<(), AppError> {
+    let backfilled = MarketplaceStats::backfill_missing_days(&db).await?;
+    if backfilled > 0 {
+        println!("Backfilled daily stats for {} days", backfilled);
+    }
+
+    // Holder counts can't be derived retroactively, so take today's snapshot now if missing
+    Collection::snapshot_holders(&db, Utc::now().date_naive()).await?;
+
+    let redis = redis::Client::open(config.redis_url.clone())?
+        .get_multiplexed_async_connection()
+        .await?;
+
+    let scheduler = JobScheduler::new().await?;
+
+    // Shortly after midnight: finalize yesterday's rollup
+    add_job(
+        &scheduler,
+        "0 5 0 * * *",
+        "daily_stats_rollup",
+        &db,
+        |db| async move {
+            let yesterday = Utc::now().date_naive() - Days::new(1);
+            MarketplaceStats::create_or_update_daily_stats(&db, yesterday).await?;
+            Ok(())
+        },
+    )
+    .await?;
+
+    // Hourly: keep the current day's partial rollup fresh
+    add_job(
+        &scheduler,
+        "0 0 * * * *",
+        "daily_stats_today",
+        &db,
+        |db| async move {
+            MarketplaceStats::create_or_update_daily_stats(&db, Utc::now().date_naive()).await?;
+            Ok(())
+        },
+    )
+    .await?;
+
+    // Nightly: snapshot distinct holders per collection
+    add_job(
+        &scheduler,
+        "0 10 0 * * *",
+        "holder_snapshot",
+        &db,
+        |db| async move {
+            Collection::snapshot_holders(&db, Utc::now().date_naive()).await?;
+            Ok(())
+        },
+    )
+    .await?;
+
+    // Every 15 minutes: refresh the composite collection ranking
+    add_job(
+        &scheduler,
+        "0 */15 * * * *",
+        "collection_rank_scores",
+        &db,
+        |db| async move {
+            Collection::refresh_rank_scores(&db).await?;
+            Ok(())
+        },
+    )
+    .await?;
+
+    // Hourly: correct any drift in the trigger-maintained collection aggregates
+    add_job(
+        &scheduler,
+        "0 40 * * * *",
+        "collection_stats_reconcile",
+        &db,
+        |db| async move {
+            let corrected = Collection::reconcile_stats(&db).await?;
+            if corrected > 0 {
+                println!("Reconciled stats for {} collections", corrected);
+            }
+            Ok(())
+        },
+    )
+    .await?;
+
+    // Every 5 minutes: re-read metadata that hasn't been refreshed in a while
+    let refresher = MetadataRefresher::new(&config);
+    add_job(
+        &scheduler,
+        "0 */5 * * * *",
+        "metadata_refresh",
+        &db,
+        move |db| {
+            let refresher = refresher.clone();
+            async move {
+                let summary = refresher.run(&db).await?;
+                if summary.changed + summary.failed > 0 {
+                    println!(
+                        "Metadata refresh: {} checked, {} changed, {} failed",
+                        summary.checked, summary.changed, summary.failed
+                    );
+                }
+                Ok(())
+            }
+        },
+    )
+    .await?;
+
+    // Every 30 seconds: advance queued collection imports by one batch
+    let importer = CollectionImporter::new(&config);
+    add_job(
+        &scheduler,
+        "*/30 * * * * *",
+        "collection_import",
+        &db,
+        move |db| {
+            let importer = importer.clone();
+            async move {
+                if let Some(import) = importer.run(&db).await? {
+                    println!(
+                        "Collection import {}: {} of {} imported, {} failed ({})",
+                        import.id,
+                        import.imported_items,
+                        import.total_items,
+                        import.failed_items,
+                        import.status
+                    );
+                }
+                Ok(())
+            }
+        },
+    )
+    .await?;
+
+    // Every 10 minutes: rescore collections and NFTs for spam
+    let spam_scorer = SpamScorer::new();
+    add_job(
+        &scheduler,
+        "0 */10 * * * *",
+        "spam_scoring",
+        &db,
+        move |db| {
+            let spam_scorer = spam_scorer.clone();
+            async move {
+                spam_scorer.run(&db).await?;
+                Ok(())
+            }
+        },
+    )
+    .await?;
+
+    // Every 5 minutes: recompute rarity for collections that gained NFTs
+    add_job(
+        &scheduler,
+        "0 */5 * * * *",
+        "rarity_refresh",
+        &db,
+        |db| async move {
+            rarity::refresh_stale_collections(&db).await?;
+            Ok(())
+        },
+    )
+    .await?;
+
+    // Every 5 minutes: refresh per-trait floors from the active listings
+    add_job(
+        &scheduler,
+        "0 */5 * * * *",
+        "trait_floors",
+        &db,
+        |db| async move {
+            CollectionTrait::refresh_floors(&db, None).await?;
+            Ok(())
+        },
+    )
+    .await?;
+
+    // Every 5 minutes: record SOL/USD for fiat valuations such as tax reports
+    let oracle = PriceOracle::new(config.price_oracle_url.clone());
+    add_job(
+        &scheduler,
+        "0 */5 * * * *",
+        "sol_usd_price",
+        &db,
+        move |db| {
+            let oracle = oracle.clone();
+            async move {
+                oracle.record_sol_usd_price(&db).await?;
+                Ok(())
+            }
+        },
+    )
+    .await?;
+
+    // Every 5 minutes: accrue trading points for running seasons
+    add_job(
+        &scheduler,
+        "0 */5 * * * *",
+        "points_accrual",
+        &db,
+        |db| async move {
+            PointsSeason::accrue_active(&db).await?;
+            Ok(())
+        },
+    )
+    .await?;
+
+    // Every 5 seconds: publish committed outbox events to Redis and event webhooks
+    let relay = OutboxRelay::new(&config, redis.clone());
+    add_job(
+        &scheduler,
+        "*/5 * * * * *",
+        "outbox_relay",
+        &db,
+        move |db| {
+            let relay = relay.clone();
+            async move {
+                let summary = relay.relay(&db).await?;
+                if summary.failed > 0 {
+                    println!(
+                        "Outbox relay: {} published, {} failed, {} held back",
+                        summary.published, summary.failed, summary.held
+                    );
+                }
+                Ok(())
+            }
+        },
+    )
+    .await?;
+
+    // Every 5 seconds: fan new activity out to integrator webhooks and post
+    // deliveries that are due
+    let webhooks = WebhookSender::new();
+    add_job(
+        &scheduler,
+        "*/5 * * * * *",
+        "webhook_delivery",
+        &db,
+        move |db| {
+            let webhooks = webhooks.clone();
+            async move {
+                let summary = webhooks.deliver(&db).await?;
+                if summary.failed > 0 {
+                    println!(
+                        "Webhooks: {} queued, {} delivered, {} failed",
+                        summary.created, summary.delivered, summary.failed
+                    );
+                }
+                Ok(())
+            }
+        },
+    )
+    .await?;
+
+    // Every 2 seconds: number newly committed activities for the firehose
+    add_job(
+        &scheduler,
+        "*/2 * * * * *",
+        "activity_sequencer",
+        &db,
+        |db| async move {
+            Activity::assign_sequence(&db).await?;
+            Ok(())
+        },
+    )
+    .await?;
+
+    // Nightly: drop outbox events published more than a week ago
+    add_job(
+        &scheduler,
+        "0 20 0 * * *",
+        "outbox_prune",
+        &db,
+        |db| async move {
+            OutboxEvent::prune_published(&db).await?;
+            Ok(())
+        },
+    )
+    .await?;
+
+    // Nightly: drop webhook deliveries that finished more than 30 days ago
+    add_job(
+        &scheduler,
+        "0 25 0 * * *",
+        "webhook_delivery_prune",
+        &db,
+        |db| async move {
+            WebhookDelivery::prune(&db).await?;
+            Ok(())
+        },
+    )
+    .await?;
+
+    // Every minute: rebuild the cached KPI dashboard
+    add_job(
+        &scheduler,
+        "0 * * * * *",
+        "dashboard_refresh",
+        &db,
+        move |db| {
+            let redis = redis.clone();
+            async move {
+                Dashboard::refresh_cache(&db, &redis).await?;
+                Ok(())
+            }
+        },
+    )
+    .await?;
+
+    // Every minute: turn new sales and floor moves into notifications
+    add_job(
+        &scheduler,
+        "0 * * * * *",
+        "notifications_generate",
+        &db,
+        |db| async move {
+            Notification::generate_item_sold(&db).await?;
+            Notification::generate_floor_alerts(&db).await?;
+            Ok(())
+        },
+    )
+    .await?;
+
+    // Every minute: post collection activity to Discord and Telegram
+    let alerts = AlertDispatcher::new(&config);
+    add_job(
+        &scheduler,
+        "15 * * * * *",
+        "alert_dispatch",
+        &db,
+        move |db| {
+            let alerts = alerts.clone();
+            async move {
+                alerts.dispatch(&db).await?;
+                Ok(())
+            }
+        },
+    )
+    .await?;
+
+    if let Some(mailer) = Mailer::from_config(&config)? {
+        // Every minute, offset from generation: email instant notifications
+        let instant_mailer = mailer.clone();
+        add_job(
+            &scheduler,
+            "30 * * * * *",
+            "email_instant",
+            &db,
+            move |db| {
+                let mailer = instant_mailer.clone();
+                async move {
+                    mailer.deliver_instant(&db).await?;
+                    Ok(())
+                }
+            },
+        )
+        .await?;
+
+        // Daily at 08:00: one digest per wallet
+        add_job(&scheduler, "0 0 8 * * *", "email_digest", &db, move |db| {
+            let mailer = mailer.clone();
+            async move {
+                let sent = mailer.send_digests(&db).await?;
+                println!("Sent {} notification digests", sent);
+                Ok(())
+            }
+        })
+        .await?;
+    }
+
+    if let Some(push) = PushSender::from_config(&config)? {
+        // Every minute: push instant notifications, collapsing bursts
+        add_job(&scheduler, "45 * * * * *", "push_deliver", &db, move |db| {
+            let push = push.clone();
+            async move {
+                push.deliver(&db).await?;
+                Ok(())
+            }
+        })
+        .await?;
+    }
+
+    if let Some(checkout) = CheckoutProvider::from_config(&config) {
+        // Every minute: release unpaid checkouts and settle paid ones against indexed sales
+        add_job(
+            &scheduler,
+            "20 * * * * *",
+            "checkout_reconcile",
+            &db,
+            move |db| {
+                let checkout = checkout.clone();
+                async move {
+                    let summary = checkout.reconcile(&db).await?;
+                    if summary.completed + summary.failed > 0 {
+                        println!(
+                            "Checkout reconciliation: {} completed, {} failed",
+                            summary.completed, summary.failed
+                        );
+                    }
+                    Ok(())
+                }
+            },
+        )
+        .await?;
+    }
+
+    // Nightly, after the rollup: write yesterday's (and any missed) Parquet partitions
+    if config.analytics_export_enabled {
+        let exporter = Arc::new(AnalyticsExporter::new(db.clone(), &config).await);
+        add_job(
+            &scheduler,
+            "0 30 0 * * *",
+            "analytics_export",
+            &db,
+            move |_db| {
+                let exporter = exporter.clone();
+                async move {
+                    let exported = exporter.export_pending().await?;
+                    println!("Analytics export wrote {} partitions", exported);
+                    Ok(())
+                }
+            },
+        )
+        .await?;
+    }
+
+    scheduler.start().await?;
+    println!("Job scheduler started");
+
+    let _ = shutdown.changed().await;
+    let mut scheduler = scheduler;
+    scheduler.shutdown().await?;
+
+    Ok(())
+}
+
+/// Register a job that logs, rather than propagates, its failures
+async fn add_job<F, Fut>(
+    scheduler: &JobScheduler,
+    schedule: &str,
+    name: &'static str,
+    db: &PgPool,
+    job: F,
+) -> Result<(), AppError>
+where
+    F: Fn(PgPool) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), AppError>> + Send + 'static,
+{
+    let db = db.clone();
+    let job = Arc::new(job);
+
+    scheduler
+        .add(Job::new_async(schedule, move |_id, _scheduler| {
+            let db = db.clone();
+            let job = job.clone();
+            Box::pin(async move {
+                if let Err(e) = job(db).await {
+                    println!("Scheduled job {} failed: {}", name, e);
+                }
+            })
+        })?)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use redis::aio::MultiplexedConnection;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::{hash::hashv, pubkey, pubkey::Pubkey};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+
+use crate::{cache, error::AppError};
+
+/// Bonfida / SPL name service program
+const NAME_PROGRAM_ID: Pubkey = pubkey!("namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX");
+/// Parent of every `.sol` domain
+const SOL_TLD_AUTHORITY: Pubkey = pubkey!("58PwtjSDuFHuUkYjH9BYnnQKHfwo9reZhC2zMJv9JPkx");
+/// Class of the reverse-lookup records maintained by Bonfida
+const REVERSE_LOOKUP_CLASS: Pubkey = pubkey!("33m47vH6Eav6jJvRC8CXSf7dvJk1D7GR3jbKQ2YAKZv");
+const HASH_PREFIX: &str = "SPL Name Service";
+/// `NameRecordHeader`: parent (32) + owner (32) + class (32)
+const NAME_RECORD_HEADER_SIZE: usize = 96;
+const CACHE_TTL_SECONDS: u64 = 3_600;
+
+/// Resolves `.sol` domains to wallets and wallets to their primary domain,
+/// caching both directions (including misses) in Redis
+#[derive(Clone)]
+pub struct SnsResolver {
+    rpc: Arc<RpcClient>,
+    redis: MultiplexedConnection,
+}
+
+impl SnsResolver {
+    pub fn new(rpc_url: String, redis: MultiplexedConnection) -> Self {
+        Self {
+            rpc: Arc::new(RpcClient::new_with_commitment(
+                rpc_url,
+                CommitmentConfig::confirmed(),
+            )),
+            redis,
+        }
+    }
+
+    /// Owner of `domain` (with or without the `.sol` suffix)
+    pub async fn resolve_domain(&self, domain: &str) -> Result<Option<String>, AppError> {
+        let name = domain.trim_end_matches(".sol").to_lowercase();
+        let cache_key = format!("sns:domain:{}", name);
+        if let Some(owner) = cache::get_json::<Option<String>>(&self.redis, &cache_key).await? {
+            return Ok(owner);
+        }
+
+        let key = name_account_key(&name, None, Some(&SOL_TLD_AUTHORITY));
+        let owner = self
+            .fetch_record(&key)
+            .await?
+            .and_then(|data| {
+                data.get(32..64)
+                    .and_then(|bytes| Pubkey::try_from(bytes).ok())
+            })
+            .map(|owner| owner.to_string());
+
+        cache::set_json(&self.redis, &cache_key, &owner, CACHE_TTL_SECONDS).await?;
+        Ok(owner)
+    }
+
+    /// Primary `.sol` domain of `wallet`, e.g. `bonfida.sol`
+    pub async fn reverse_lookup(&self, wallet: &str) -> Result<Option<String>, AppError> {
+        let cache_key = format!("sns:wallet:{}", wallet);
+        if let Some(name) = cache::get_json::<Option<String>>(&self.redis, &cache_key).await? {
+            return Ok(name);
+        }
+
+        let name = match Pubkey::from_str(wallet) {
+            Ok(wallet) => {
+                let key = name_account_key(&wallet.to_string(), Some(&REVERSE_LOOKUP_CLASS), None);
+                self.fetch_record(&key)
+                    .await?
+                    .and_then(|data| parse_reverse_record(&data[NAME_RECORD_HEADER_SIZE..]))
+                    .map(|name| format!("{}.sol", name))
+            }
+            Err(_) => None,
+        };
+
+        cache::set_json(&self.redis, &cache_key, &name, CACHE_TTL_SECONDS).await?;
+        Ok(name)
+    }
+
+    /// Display names for the wallets that have a domain; lookup failures are skipped
+    pub async fn display_names(&self, wallets: &[String]) -> HashMap<String, String> {
+        let mut names = HashMap::new();
+        for wallet in wallets {
+            if names.contains_key(wallet) {
+                continue;
+            }
+            if let Ok(Some(name)) = self.reverse_lookup(wallet).await {
+                names.insert(wallet.clone(), name);
+            }
+        }
+        names
+    }
+
+    /// Accept either a wallet address or a `.sol` domain in a `{wallet}` path parameter
+    pub async fn resolve_wallet_param(&self, wallet: &str) -> Result<String, AppError> {
+        if !wallet.ends_with(".sol") {
+            return Ok(wallet.to_string());
+        }
+
+        self.resolve_domain(wallet)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Domain {} not found", wallet)))
+    }
+
+    /// Record data after the header, or `None` if the account doesn't exist
+    async fn fetch_record(&self, key: &Pubkey) -> Result<Option<Vec<u8>>, AppError> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(key, self.rpc.commitment())
+            .await?
+            .value;
+
+        Ok(account
+            .filter(|account| account.owner == NAME_PROGRAM_ID)
+            .map(|account| account.data)
+            .filter(|data| data.len() >= NAME_RECORD_HEADER_SIZE))
+    }
+}
+
+fn name_account_key(name: &str, class: Option<&Pubkey>, parent: Option<&Pubkey>) -> Pubkey {
+    let hashed_name = hashv(&[HASH_PREFIX.as_bytes(), name.as_bytes()]);
+    let default = Pubkey::default();
+
+    Pubkey::find_program_address(
+        &[
+            hashed_name.as_ref(),
+            class.unwrap_or(&default).as_ref(),
+            parent.unwrap_or(&default).as_ref(),
+        ],
+        &NAME_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Reverse records store the domain as a borsh string (`u32` length + UTF-8)
+fn parse_reverse_record(data: &[u8]) -> Option<String> {
+    let len = u32::from_le_bytes(data.get(..4)?.try_into().ok()?) as usize;
+    let name = std::str::from_utf8(data.get(4..4 + len)?).ok()?;
+    (!name.is_empty()).then(|| name.to_string())
+}
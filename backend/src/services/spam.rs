@@ -0,0 +1,433 @@
+use image::imageops::FilterType;
+use sqlx::{FromRow, PgPool};
+use std::{collections::HashSet, time::Duration};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/// Score at or above which an item is flagged, absent an admin override
+pub const SPAM_THRESHOLD: f32 = 0.5;
+
+pub const REASON_NAME_COPY: &str = "name_copy";
+pub const REASON_IMAGE_COPY: &str = "image_copy";
+pub const REASON_ZERO_ENGAGEMENT_AIRDROP: &str = "zero_engagement_airdrop";
+pub const REASON_SCAM_KEYWORDS: &str = "scam_keywords";
+
+const NAME_COPY_WEIGHT: f32 = 0.4;
+const IMAGE_COPY_WEIGHT: f32 = 0.5;
+const AIRDROP_WEIGHT: f32 = 0.4;
+const SCAM_KEYWORDS_WEIGHT: f32 = 0.6;
+
+/// Images whose hashes differ in at most this many of 64 bits are copies
+const IMAGE_COPY_MAX_DISTANCE: u32 = 8;
+/// An airdrop needs at least this many items...
+const AIRDROP_MIN_ITEMS: i64 = 25;
+/// ...spread over nearly as many wallets, with no sales or listings
+const AIRDROP_MIN_HOLDER_RATIO: f64 = 0.9;
+/// Phishing drops advertise a site to "claim" at. A Postgres regex, where
+/// `\y` is a word boundary.
+const SCAM_KEYWORDS_PATTERN: &str = concat!(
+    r"(https?://|www\.|\.(com|io|xyz|net|org|app|site|fun)\y",
+    r"|\yclaim|\yairdrop|\yreward|\yvisit\y|\yfree mint)"
+);
+
+const IMAGES_PER_RUN: i64 = 50;
+const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+const IMAGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default)]
+pub struct SpamSummary {
+    pub images_hashed: u64,
+    pub collections_flagged: u64,
+    pub nfts_flagged: u64,
+}
+
+#[derive(Debug, FromRow)]
+struct CollectionSignals {
+    id: Uuid,
+    name: String,
+    verified: bool,
+    image_phash: Option<i64>,
+    items: i64,
+    holders: i64,
+    sales: i64,
+    listings: i64,
+    scam_keywords: bool,
+}
+
+/// Flags likely spam and scam collections, and the NFTs in them or airdropped
+/// without one, so wallet views can hide them
+#[derive(Clone)]
+pub struct SpamScorer {
+    http: reqwest::Client,
+}
+
+impl Default for SpamScorer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpamScorer {
+    pub fn new() -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(IMAGE_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    pub async fn run(&self, pool: &PgPool) -> Result<SpamSummary, AppError> {
+        let images_hashed = self.hash_images(pool).await?;
+        let collections_flagged = score_collections(pool).await?;
+        let nfts_flagged = score_nfts(pool).await?;
+
+        Ok(SpamSummary {
+            images_hashed,
+            collections_flagged,
+            nfts_flagged,
+        })
+    }
+
+    /// Fingerprint collection images that haven't been tried yet, or changed
+    /// since. Images that can't be fetched are not retried until they change.
+    async fn hash_images(&self, pool: &PgPool) -> Result<u64, AppError> {
+        let pending: Vec<(Uuid, String)> = sqlx::query_as(
+            r#"
+            SELECT id, image_url FROM collections
+            WHERE image_url IS NOT NULL AND deleted_at IS NULL
+              AND (image_phash_at IS NULL OR image_phash_at < updated_at)
+            ORDER BY verified DESC, created_at
+            LIMIT $1
+            "#,
+        )
+        .bind(IMAGES_PER_RUN)
+        .fetch_all(pool)
+        .await?;
+
+        let mut hashed = 0;
+        for (id, image_url) in pending {
+            let hash = self
+                .fetch_image(&image_url)
+                .await
+                .and_then(|bytes| dhash(&bytes));
+            if hash.is_some() {
+                hashed += 1;
+            }
+            // Leaves updated_at alone, which would queue the image again
+            sqlx::query(
+                "UPDATE collections SET image_phash = $2, image_phash_at = NOW() WHERE id = $1",
+            )
+            .bind(id)
+            .bind(hash)
+            .execute(pool)
+            .await?;
+        }
+
+        Ok(hashed)
+    }
+
+    async fn fetch_image(&self, url: &str) -> Option<Vec<u8>> {
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .ok()?;
+        if response
+            .content_length()
+            .is_some_and(|length| length as usize > MAX_IMAGE_BYTES)
+        {
+            return None;
+        }
+
+        let bytes = response.bytes().await.ok()?;
+        (bytes.len() <= MAX_IMAGE_BYTES).then(|| bytes.to_vec())
+    }
+}
+
+/// 64-bit difference hash: shrink to 9x8 greyscale and record whether each
+/// pixel is brighter than its right-hand neighbour. Survives re-encoding,
+/// resizing and small edits, which is how copied artwork usually arrives.
+pub fn dhash(bytes: &[u8]) -> Option<i64> {
+    let image = image::load_from_memory(bytes)
+        .ok()?
+        .resize_exact(9, 8, FilterType::Triangle)
+        .to_luma8();
+
+    let mut hash: u64 = 0;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if image.get_pixel(x, y)[0] > image.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+
+    Some(hash as i64)
+}
+
+/// Lowercase letters and digits only, so "Mad Lads", "MADLADS" and
+/// "Mad-Lads" compare equal
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Score every collection and refresh `is_spam`; returns how many are flagged
+async fn score_collections(pool: &PgPool) -> Result<u64, AppError> {
+    let collections = sqlx::query_as::<_, CollectionSignals>(
+        r#"
+        SELECT c.id, c.name, c.verified, c.image_phash,
+               COUNT(n.id) AS items,
+               COUNT(DISTINCT n.current_owner) AS holders,
+               (SELECT COUNT(*) FROM sales s
+                JOIN nfts sn ON sn.mint_address = s.nft_mint
+                WHERE sn.collection_id = c.id) AS sales,
+               (SELECT COUNT(*) FROM listings l
+                JOIN nfts ln ON ln.mint_address = l.nft_mint
+                WHERE ln.collection_id = c.id AND l.deleted_at IS NULL) AS listings,
+               c.name ~* $1 AS scam_keywords
+        FROM collections c
+        LEFT JOIN nfts n ON n.collection_id = c.id AND n.deleted_at IS NULL
+        WHERE c.deleted_at IS NULL
+        GROUP BY c.id
+        "#,
+    )
+    .bind(SCAM_KEYWORDS_PATTERN)
+    .fetch_all(pool)
+    .await?;
+
+    // Verified collections are the originals that spam copies
+    let mut original_names = HashSet::new();
+    let mut original_hashes = Vec::new();
+    for collection in collections.iter().filter(|c| c.verified) {
+        original_names.insert(normalize_name(&collection.name));
+        if let Some(hash) = collection.image_phash {
+            original_hashes.push((collection.id, hash));
+        }
+    }
+
+    let mut ids = Vec::with_capacity(collections.len());
+    let mut scores = Vec::with_capacity(collections.len());
+    let mut reasons = Vec::with_capacity(collections.len());
+    for collection in &collections {
+        let mut score = 0.0;
+        let mut why = Vec::new();
+
+        if !collection.verified {
+            let name = normalize_name(&collection.name);
+            if !name.is_empty() && original_names.contains(&name) {
+                score += NAME_COPY_WEIGHT;
+                why.push(REASON_NAME_COPY);
+            }
+
+            if let Some(hash) = collection.image_phash {
+                let copied = original_hashes.iter().any(|(original, original_hash)| {
+                    *original != collection.id
+                        && (hash ^ original_hash).count_ones() <= IMAGE_COPY_MAX_DISTANCE
+                });
+                if copied {
+                    score += IMAGE_COPY_WEIGHT;
+                    why.push(REASON_IMAGE_COPY);
+                }
+            }
+
+            if collection.items >= AIRDROP_MIN_ITEMS
+                && collection.sales == 0
+                && collection.listings == 0
+                && collection.holders as f64 >= collection.items as f64 * AIRDROP_MIN_HOLDER_RATIO
+            {
+                score += AIRDROP_WEIGHT;
+                why.push(REASON_ZERO_ENGAGEMENT_AIRDROP);
+            }
+
+            if collection.scam_keywords {
+                score += SCAM_KEYWORDS_WEIGHT;
+                why.push(REASON_SCAM_KEYWORDS);
+            }
+        }
+
+        ids.push(collection.id);
+        scores.push(f32::min(score, 1.0));
+        // Postgres has no arrays of arrays of differing lengths, so pass them joined
+        reasons.push(why.join(","));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE collections c
+        SET spam_score = data.score,
+            spam_reasons = COALESCE(string_to_array(NULLIF(data.reasons, ''), ','), '{}'),
+            is_spam = COALESCE(c.spam_override, data.score >= $4),
+            spam_scored_at = NOW()
+        FROM UNNEST($1::uuid[], $2::real[], $3::text[]) AS data(id, score, reasons)
+        WHERE c.id = data.id
+          AND (c.spam_score IS DISTINCT FROM data.score
+               OR c.is_spam IS DISTINCT FROM COALESCE(c.spam_override, data.score >= $4))
+        "#,
+    )
+    .bind(&ids)
+    .bind(&scores)
+    .bind(&reasons)
+    .bind(SPAM_THRESHOLD)
+    .execute(pool)
+    .await?;
+
+    let flagged: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM collections WHERE is_spam AND deleted_at IS NULL")
+            .fetch_one(pool)
+            .await?;
+
+    Ok(flagged as u64)
+}
+
+/// NFTs in a collection take its score and flag. Loose NFTs are scored on
+/// their own name and on their creator's drops: many loose NFTs to as many
+/// wallets with nothing ever sold or listed.
+async fn score_nfts(pool: &PgPool) -> Result<u64, AppError> {
+    sqlx::query(
+        r#"
+        WITH creator_drops AS (
+            SELECT n.creator_address
+            FROM nfts n
+            WHERE n.collection_id IS NULL AND n.deleted_at IS NULL
+            GROUP BY n.creator_address
+            HAVING COUNT(*) >= $3
+               AND COUNT(DISTINCT n.current_owner) >= COUNT(*) * $4
+               AND NOT EXISTS (
+                   SELECT 1 FROM sales s
+                   JOIN nfts sn ON sn.mint_address = s.nft_mint
+                   WHERE sn.creator_address = n.creator_address AND sn.collection_id IS NULL
+               )
+               AND NOT EXISTS (
+                   SELECT 1 FROM listings l
+                   JOIN nfts ln ON ln.mint_address = l.nft_mint
+                   WHERE ln.creator_address = n.creator_address AND ln.collection_id IS NULL
+               )
+        ),
+        scored AS (
+            SELECT n.id, c.spam_score AS score, c.spam_reasons AS reasons, c.is_spam AS flagged
+            FROM nfts n
+            JOIN collections c ON c.id = n.collection_id
+            UNION ALL
+            SELECT n.id, s.score, s.reasons, s.score >= $1
+            FROM nfts n
+            CROSS JOIN LATERAL (
+                SELECT LEAST(
+                           CASE WHEN n.name ~* $2 THEN $5::real ELSE 0 END
+                           + CASE WHEN d.creator_address IS NOT NULL THEN $6::real ELSE 0 END,
+                           1
+                       )::real AS score,
+                       array_remove(ARRAY[
+                           CASE WHEN n.name ~* $2 THEN 'scam_keywords' END,
+                           CASE WHEN d.creator_address IS NOT NULL
+                                THEN 'zero_engagement_airdrop' END
+                       ], NULL) AS reasons
+                FROM (SELECT 1) one
+                LEFT JOIN creator_drops d ON d.creator_address = n.creator_address
+            ) s
+            WHERE n.collection_id IS NULL
+        )
+        UPDATE nfts n
+        SET spam_score = scored.score,
+            spam_reasons = scored.reasons,
+            is_spam = COALESCE(n.spam_override, scored.flagged, FALSE),
+            spam_scored_at = NOW()
+        FROM scored
+        WHERE n.id = scored.id AND n.deleted_at IS NULL
+          AND (n.spam_score IS DISTINCT FROM scored.score
+               OR n.is_spam IS DISTINCT FROM COALESCE(n.spam_override, scored.flagged, FALSE))
+        "#,
+    )
+    .bind(SPAM_THRESHOLD)
+    .bind(SCAM_KEYWORDS_PATTERN)
+    .bind(AIRDROP_MIN_ITEMS)
+    .bind(AIRDROP_MIN_HOLDER_RATIO)
+    .bind(SCAM_KEYWORDS_WEIGHT)
+    .bind(AIRDROP_WEIGHT)
+    .execute(pool)
+    .await?;
+
+    let flagged: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM nfts WHERE is_spam AND deleted_at IS NULL")
+            .fetch_one(pool)
+            .await?;
+
+    Ok(flagged as u64)
+}
+
+/// Pin a collection's flag (or clear the pin with `None`) and apply it to its
+/// NFTs straight away rather than at the next scoring run
+pub async fn override_collection(
+    pool: &PgPool,
+    collection_id: Uuid,
+    is_spam: Option<bool>,
+) -> Result<bool, AppError> {
+    let mut tx = pool.begin().await?;
+    let flagged: Option<bool> = sqlx::query_scalar(
+        r#"
+        UPDATE collections
+        SET spam_override = $2,
+            is_spam = COALESCE($2, spam_score >= $3, FALSE)
+        WHERE id = $1 AND deleted_at IS NULL
+        RETURNING is_spam
+        "#,
+    )
+    .bind(collection_id)
+    .bind(is_spam)
+    .bind(SPAM_THRESHOLD)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(flagged) = flagged else {
+        return Err(crate::error::not_found_error("Collection"));
+    };
+
+    sqlx::query(
+        r#"
+        UPDATE nfts SET is_spam = COALESCE(spam_override, $2)
+        WHERE collection_id = $1 AND is_spam IS DISTINCT FROM COALESCE(spam_override, $2)
+        "#,
+    )
+    .bind(collection_id)
+    .bind(flagged)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(flagged)
+}
+
+/// Pin one NFT's flag, or clear the pin with `None`
+pub async fn override_nft(
+    pool: &PgPool,
+    mint_address: &str,
+    is_spam: Option<bool>,
+) -> Result<bool, AppError> {
+    let flagged: Option<bool> = sqlx::query_scalar(
+        r#"
+        UPDATE nfts n
+        SET spam_override = $2,
+            is_spam = COALESCE(
+                $2,
+                (SELECT c.is_spam FROM collections c WHERE c.id = n.collection_id),
+                n.spam_score >= $3,
+                FALSE
+            )
+        WHERE n.mint_address = $1 AND n.deleted_at IS NULL
+        RETURNING n.is_spam
+        "#,
+    )
+    .bind(mint_address)
+    .bind(is_spam)
+    .bind(SPAM_THRESHOLD)
+    .fetch_optional(pool)
+    .await?;
+
+    flagged.ok_or_else(|| crate::error::not_found_error("NFT"))
+}
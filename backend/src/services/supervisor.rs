@@ -0,0 +1,183 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+use tokio::{sync::watch, task::JoinHandle};
+
+use crate::error::AppError;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// A task that stays up this long is considered healthy again and its backoff resets
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Running,
+    Restarting,
+    Stopped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskState {
+    pub name: &'static str,
+    pub status: TaskStatus,
+    pub restarts: u32,
+    pub last_error: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+}
+
+/// Receiver a supervised task can watch to stop gracefully
+pub type ShutdownSignal = watch::Receiver<bool>;
+
+struct SupervisedTask {
+    name: &'static str,
+    shutdown: watch::Sender<bool>,
+    handle: JoinHandle<()>,
+}
+
+/// Keeps background services alive.
+///
+/// Crashed tasks (errors or panics) are restarted with exponential backoff,
+/// their state is exposed for the readiness endpoint, and on shutdown tasks
+/// are stopped in reverse start order so dependents go before what they use.
+#[derive(Clone, Default)]
+pub struct Supervisor {
+    states: Arc<RwLock<BTreeMap<&'static str, TaskState>>>,
+    tasks: Arc<Mutex<Vec<SupervisedTask>>>,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a supervised task. `factory` is called again for every restart.
+    pub fn spawn<F, Fut>(&self, name: &'static str, factory: F)
+    where
+        F: Fn(ShutdownSignal) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), AppError>> + Send + 'static,
+    {
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        self.states.write().unwrap().insert(
+            name,
+            TaskState {
+                name,
+                status: TaskStatus::Running,
+                restarts: 0,
+                last_error: None,
+                started_at: None,
+            },
+        );
+
+        let states = self.states.clone();
+        let handle = tokio::spawn(async move {
+            let mut backoff = INITIAL_BACKOFF;
+            let mut shutdown = shutdown_rx;
+
+            loop {
+                update(&states, name, |state| {
+                    state.status = TaskStatus::Running;
+                    state.started_at = Some(Utc::now());
+                });
+
+                let started = Instant::now();
+                let mut attempt = tokio::spawn(factory(shutdown.clone()));
+
+                let outcome = tokio::select! {
+                    result = &mut attempt => result,
+                    _ = shutdown.changed() => {
+                        // Give the task a chance to observe the signal before aborting it
+                        if tokio::time::timeout(SHUTDOWN_TIMEOUT, &mut attempt).await.is_err() {
+                            attempt.abort();
+                        }
+                        update(&states, name, |state| state.status = TaskStatus::Stopped);
+                        return;
+                    }
+                };
+
+                let error = match outcome {
+                    Ok(Ok(())) => {
+                        println!("Task {} finished", name);
+                        update(&states, name, |state| state.status = TaskStatus::Stopped);
+                        return;
+                    }
+                    Ok(Err(e)) => format!("{}", e),
+                    Err(e) if e.is_panic() => "task panicked".to_string(),
+                    Err(e) => format!("{}", e),
+                };
+
+                if started.elapsed() >= STABLE_AFTER {
+                    backoff = INITIAL_BACKOFF;
+                }
+
+                println!(
+                    "Task {} crashed: {}; restarting in {}s",
+                    name,
+                    error,
+                    backoff.as_secs()
+                );
+                update(&states, name, |state| {
+                    state.status = TaskStatus::Restarting;
+                    state.restarts += 1;
+                    state.last_error = Some(error);
+                });
+
+                tokio::select! {
+                    _ = tokio::time::sleep(backoff) => {}
+                    _ = shutdown.changed() => {
+                        update(&states, name, |state| state.status = TaskStatus::Stopped);
+                        return;
+                    }
+                }
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        });
+
+        self.tasks.lock().unwrap().push(SupervisedTask {
+            name,
+            shutdown: shutdown_tx,
+            handle,
+        });
+    }
+
+    pub fn snapshot(&self) -> Vec<TaskState> {
+        self.states.read().unwrap().values().cloned().collect()
+    }
+
+    /// True when every supervised task is currently running
+    pub fn is_ready(&self) -> bool {
+        self.states
+            .read()
+            .unwrap()
+            .values()
+            .all(|state| state.status == TaskStatus::Running)
+    }
+
+    /// Stop all tasks, most recently started first
+    pub async fn shutdown(&self) {
+        let tasks: Vec<SupervisedTask> = self.tasks.lock().unwrap().drain(..).collect();
+
+        for task in tasks.into_iter().rev() {
+            println!("Stopping task {}", task.name);
+            let _ = task.shutdown.send(true);
+            let _ = task.handle.await;
+        }
+    }
+}
+
+fn update(
+    states: &RwLock<BTreeMap<&'static str, TaskState>>,
+    name: &'static str,
+    apply: impl FnOnce(&mut TaskState),
+) {
+    if let Some(state) = states.write().unwrap().get_mut(name) {
+        apply(state);
+    }
+}
@@ -0,0 +1,119 @@
+use serde::Deserialize;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::str::FromStr;
+
+use crate::{config::Config, error::AppError};
+
+const API_URL: &str = "https://api.twitter.com/2";
+
+#[derive(Debug, Deserialize)]
+struct TweetResponse {
+    data: Option<TweetData>,
+    includes: Option<TweetIncludes>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TweetData {
+    text: String,
+    author_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TweetIncludes {
+    #[serde(default)]
+    users: Vec<TweetUser>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TweetUser {
+    id: String,
+    username: String,
+}
+
+/// Verifies Twitter/X handles with a signed tweet: the wallet signs
+/// [`challenge_message`] and the handle tweets the signature. Binding the
+/// handle into the message stops another account reposting the same tweet.
+#[derive(Clone)]
+pub struct TwitterVerifier {
+    http: reqwest::Client,
+    bearer_token: String,
+}
+
+impl TwitterVerifier {
+    pub fn from_config(config: &Config) -> Option<Self> {
+        Some(Self {
+            http: reqwest::Client::new(),
+            bearer_token: config.twitter_bearer_token.clone()?,
+        })
+    }
+
+    /// Check the tweet at `tweet_url` and return its author's handle if the
+    /// tweet carries `wallet_address`'s signature of the challenge for it
+    pub async fn verify(&self, wallet_address: &str, tweet_url: &str) -> Result<String, AppError> {
+        let wallet = Pubkey::from_str(wallet_address)
+            .map_err(|_| crate::error::bad_request_error("Invalid wallet address"))?;
+        let tweet_id = tweet_id(tweet_url)
+            .ok_or_else(|| crate::error::bad_request_error("tweet_url is not a link to a tweet"))?;
+
+        let response = self
+            .http
+            .get(format!("{}/tweets/{}", API_URL, tweet_id))
+            .bearer_auth(&self.bearer_token)
+            .query(&[("expansions", "author_id"), ("user.fields", "username")])
+            .send()
+            .await
+            .map_err(|e| AppError::Upstream(format!("Twitter API: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::Upstream(format!(
+                "Twitter API: {}",
+                response.status()
+            )));
+        }
+        let body: TweetResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::Upstream(format!("Twitter API: {}", e)))?;
+
+        let tweet = body
+            .data
+            .ok_or_else(|| crate::error::not_found_error("Tweet"))?;
+        let handle = body
+            .includes
+            .into_iter()
+            .flat_map(|includes| includes.users)
+            .find(|user| user.id == tweet.author_id)
+            .map(|user| user.username)
+            .ok_or_else(|| AppError::Upstream("Twitter API: tweet author missing".to_string()))?;
+
+        let message = challenge_message(wallet_address, &handle);
+        let signed = tweet
+            .text
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter_map(|word| Signature::from_str(word).ok())
+            .any(|signature| signature.verify(wallet.as_ref(), message.as_bytes()));
+        if !signed {
+            return Err(crate::error::bad_request_error(
+                "tweet does not contain the wallet's signature of the challenge for its author",
+            ));
+        }
+
+        Ok(handle)
+    }
+}
+
+/// What the wallet signs to prove it belongs with `handle`. Handles are
+/// case-insensitive, so the message uses the lowercase form.
+pub fn challenge_message(wallet_address: &str, handle: &str) -> String {
+    format!(
+        "SolMint: @{} is the Twitter/X account of wallet {}",
+        handle.trim_start_matches('@').to_ascii_lowercase(),
+        wallet_address
+    )
+}
+
+/// `https://x.com/<user>/status/<id>` (or twitter.com, with any query string)
+fn tweet_id(url: &str) -> Option<&str> {
+    let path = url.split(['?', '#']).next()?;
+    let (_, id) = path.trim_end_matches('/').rsplit_once("/status/")?;
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_digit())).then_some(id)
+}
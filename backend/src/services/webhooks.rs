@@ -0,0 +1,226 @@
+//! Delivery of integrator webhooks. Activity is fanned out into
+//! `webhook_deliveries`, then each delivery is posted, retried with backoff
+//! until it succeeds or runs out of attempts.
+
+use chrono::Utc;
+use futures_util::{stream, StreamExt};
+use hmac::{Hmac, Mac};
+use serde_json::json;
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use crate::{
+    error::AppError,
+    models::{DueDelivery, WebhookDelivery},
+};
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+const BATCH_SIZE: i64 = 200;
+/// Deliveries posted at once
+const CONCURRENCY: usize = 16;
+
+#[derive(Debug, Default)]
+pub struct DeliverySummary {
+    pub created: u64,
+    pub delivered: u64,
+    pub failed: u64,
+}
+
+/// `t=<unix seconds>,v1=<hex hmac>`, the HMAC-SHA256 of `<t>.<body>`; the
+/// same scheme the checkout provider uses for its webhooks to us
+pub fn signature(secret: &str, body: &str) -> Option<String> {
+    let timestamp = Utc::now().timestamp();
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).ok()?;
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+
+    Some(format!(
+        "t={},v1={}",
+        timestamp,
+        hex::encode(mac.finalize().into_bytes())
+    ))
+}
+
+/// Whether the address is on the public internet. Webhook URLs are chosen by
+/// any wallet, so they mustn't reach the backend's own network: loopback,
+/// private, carrier-grade NAT, link-local (cloud metadata), unique-local and
+/// the like are refused.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                || a >= 240
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_unspecified()
+                    || ip.is_loopback()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+/// Resolve a webhook host, failing unless every address is public
+async fn resolve_public(host: &str, port: u16) -> Result<Vec<SocketAddr>, String> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|_| format!("{} doesn't resolve", host))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(format!("{} doesn't resolve", host));
+    }
+    if addrs.iter().any(|addr| !is_public(addr.ip())) {
+        return Err(format!("{} resolves to a non-public address", host));
+    }
+    Ok(addrs)
+}
+
+/// Check a webhook URL is https and reaches only public addresses. Run when a
+/// webhook is registered or changed, and again before each delivery since
+/// its DNS can change.
+pub async fn check_url(url: &str) -> Result<(), String> {
+    resolve_url(url).await.map(|_| ())
+}
+
+/// The host of a checked webhook URL and its public addresses, or `None` for
+/// a public IP address host
+async fn resolve_url(url: &str) -> Result<Option<(String, Vec<SocketAddr>)>, String> {
+    let url = reqwest::Url::parse(url).map_err(|_| "url must be an https URL".to_string())?;
+    if url.scheme() != "https" || !url.username().is_empty() || url.password().is_some() {
+        return Err("url must be an https URL".to_string());
+    }
+
+    let host = url
+        .host_str()
+        .ok_or_else(|| "url must be an https URL".to_string())?;
+    let port = url.port_or_known_default().unwrap_or(443);
+    // IPv6 hosts are bracketed in URLs
+    match host.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(ip) if is_public(ip) => Ok(None),
+        Ok(_) => Err("url must not point at a non-public address".to_string()),
+        Err(_) => Ok(Some((host.to_string(), resolve_public(host, port).await?))),
+    }
+}
+
+/// Posts pending webhook deliveries
+#[derive(Clone, Default)]
+pub struct WebhookSender;
+
+impl WebhookSender {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Create deliveries for new activity, then post the ones that are due
+    pub async fn deliver(&self, pool: &PgPool) -> Result<DeliverySummary, AppError> {
+        let created = WebhookDelivery::fan_out(pool).await?;
+        let delivered = AtomicU64::new(0);
+        let failed = AtomicU64::new(0);
+
+        let due = WebhookDelivery::claim_due(pool, BATCH_SIZE).await?;
+        stream::iter(due)
+            .for_each_concurrent(CONCURRENCY, |delivery| {
+                let (delivered, failed) = (&delivered, &failed);
+                async move {
+                    let result = match self.post(&delivery).await {
+                        Ok(status) => {
+                            delivered.fetch_add(1, Ordering::Relaxed);
+                            WebhookDelivery::mark_delivered(pool, delivery.id, status).await
+                        }
+                        Err((status, error)) => {
+                            failed.fetch_add(1, Ordering::Relaxed);
+                            WebhookDelivery::mark_failed(pool, &delivery, status, &error).await
+                        }
+                    };
+                    if let Err(e) = result {
+                        println!("Failed to record webhook delivery {}: {}", delivery.id, e);
+                    }
+                }
+            })
+            .await;
+
+        Ok(DeliverySummary {
+            created,
+            delivered: delivered.into_inner(),
+            failed: failed.into_inner(),
+        })
+    }
+
+    /// Post one delivery, returning the response status, or the status (if
+    /// there was a response) and error of a failed attempt. Response bodies
+    /// aren't recorded, since the delivery log shows the error to the wallet.
+    async fn post(&self, delivery: &DueDelivery) -> Result<i32, (Option<i32>, String)> {
+        // Each delivery connects to the addresses just checked, so a host
+        // whose DNS changes after the check can't point it elsewhere, and
+        // redirects aren't followed, since one could point anywhere
+        let mut http = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none());
+        if let Some((host, addrs)) = resolve_url(&delivery.url).await.map_err(|e| (None, e))? {
+            http = http.resolve_to_addrs(&host, &addrs);
+        }
+        let http = http
+            .build()
+            .map_err(|_| (None, "request failed".to_string()))?;
+
+        let body = json!({
+            "id": delivery.id,
+            "webhook_id": delivery.webhook_id,
+            "event": delivery.event,
+            "activity": delivery.payload,
+            "created_at": delivery.created_at,
+        })
+        .to_string();
+
+        let mut request = http
+            .post(&delivery.url)
+            .header("Content-Type", "application/json")
+            .header("Solmint-Delivery", delivery.id.to_string());
+        if let Some(signature) = signature(&delivery.secret, &body) {
+            request = request.header("Solmint-Signature", signature);
+        }
+
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| (None, delivery_error(&e)))?;
+        let status = i32::from(response.status().as_u16());
+        if response.status().is_success() {
+            return Ok(status);
+        }
+
+        Err((Some(status), format!("HTTP {}", response.status())))
+    }
+}
+
+/// What went wrong sending a delivery, without reqwest's source chain, which
+/// can carry resolver and connection details
+fn delivery_error(e: &reqwest::Error) -> String {
+    if e.is_timeout() {
+        "timed out".to_string()
+    } else if e.is_connect() {
+        "couldn't connect".to_string()
+    } else {
+        "request failed".to_string()
+    }
+}
@@ -1,27 +1,30 @@
 use crate::{
     config::Config,
     error::AppError,
-    models::{CreateNftRequest, Nft},
+    models::{
+        Auction, Collection, CreateNftRequest, CreateRentalRequest, IndexedAuction, IndexedOffer,
+        IndexerState, Nft, Offer, OfferTarget, Rental,
+    },
+};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use nft_marketplace_sdk::{
+    events::{
+        AuctionCreateEvent, AuctionSettleEvent, BidEvent, CollectionOfferAcceptEvent,
+        CollectionOfferEvent, MarketplaceEvent, MintEvent, OfferAcceptEvent, OfferEvent, RentEvent,
+        RentalReclaimEvent,
+    },
+    pda,
 };
-use serde::{Deserialize, Serialize};
 use solana_client::{
     pubsub_client::PubsubClient,
     rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter},
 };
-use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use solana_sdk::pubkey::Pubkey;
 use sqlx::PgPool;
 use std::{str::FromStr, sync::Arc};
 use tokio::sync::mpsc;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NftMintedEvent {
-    pub mint: String,
-    pub name: String,
-    pub symbol: String,
-    pub uri: String,
-    pub creator: String,
-}
-
 pub struct WebsocketIndexer {
     db: PgPool,
     config: Config,
@@ -41,7 +44,9 @@ impl WebsocketIndexer {
     }
 
     pub async fn start(&self) -> Result<(), AppError> {
-        let ws_url = self.config.solana_rpc_url.replace("https://", "wss://");
+        IndexerState::ensure_cluster(&self.db, self.config.solana_cluster).await?;
+
+        let ws_url = self.config.solana_ws_url.clone();
 
         loop {
             if let Err(e) = self.run_indexer(&ws_url).await {
@@ -54,15 +59,15 @@ impl WebsocketIndexer {
 
     async fn run_indexer(&self, ws_url: &str) -> Result<(), AppError> {
         println!(
-            "Starting websocket indexer for program: {}",
-            self.program_id
+            "Starting websocket indexer for program {} on {}",
+            self.program_id, self.config.solana_cluster
         );
 
         let (mut notifications, _unsubscribe) = PubsubClient::logs_subscribe(
             ws_url,
             RpcTransactionLogsFilter::Mentions(vec![self.program_id.to_string()]),
             RpcTransactionLogsConfig {
-                commitment: Some(CommitmentConfig::confirmed()),
+                commitment: Some(self.config.commitment()),
             },
         )
         .map_err(|e| AppError::SolanaError(format!("Failed to subscribe to logs: {}", e)))?;
@@ -80,77 +85,115 @@ impl WebsocketIndexer {
         &self,
         log: &solana_client::rpc_response::RpcLogsResponse,
     ) -> Result<(), AppError> {
-        // Look for NFT_MINTED events in the logs
+        // Mint, rental, offer and auction events are indexed from the stream
+        // so far
+        let signature = &log.value.signature;
         for log_line in &log.value.logs {
-            if log_line.contains("NFT_MINTED:") {
-                if let Some(event_data) = self.extract_nft_event(log_line) {
-                    self.handle_nft_minted_event(event_data, &log.value.signature)
+            match decode_event(log_line) {
+                Some(MarketplaceEvent::NftMinted(event)) => {
+                    self.handle_nft_minted_event(event, signature).await?;
+                }
+                Some(MarketplaceEvent::NftRented(event)) => {
+                    self.handle_nft_rented_event(event, signature).await?;
+                }
+                Some(MarketplaceEvent::RentalReclaimed(event)) => {
+                    self.handle_rental_reclaimed_event(event).await?;
+                }
+                Some(MarketplaceEvent::OfferMade(event)) => {
+                    self.handle_offer_made_event(event, signature).await?;
+                }
+                Some(MarketplaceEvent::OfferAccepted(event)) => {
+                    self.handle_offer_accepted_event(event, signature).await?;
+                }
+                Some(MarketplaceEvent::OfferCancelled(event)) => {
+                    self.handle_offer_cancelled_event(&event.offer, signature)
                         .await?;
                 }
+                Some(MarketplaceEvent::CollectionOfferMade(event)) => {
+                    self.handle_collection_offer_made_event(event, signature)
+                        .await?;
+                }
+                Some(MarketplaceEvent::CollectionOfferAccepted(event)) => {
+                    self.handle_collection_offer_accepted_event(event, signature)
+                        .await?;
+                }
+                Some(MarketplaceEvent::CollectionOfferCancelled(event)) => {
+                    self.handle_offer_cancelled_event(&event.offer, signature)
+                        .await?;
+                }
+                Some(MarketplaceEvent::AuctionCreated(event)) => {
+                    self.handle_auction_created_event(event, signature).await?;
+                }
+                Some(MarketplaceEvent::BidPlaced(event)) => {
+                    self.handle_bid_placed_event(event, signature).await?;
+                }
+                Some(MarketplaceEvent::AuctionSettled(event)) => {
+                    self.handle_auction_settled_event(event, signature).await?;
+                }
+                _ => {}
             }
         }
 
         Ok(())
     }
 
-    fn extract_nft_event(&self, log_line: &str) -> Option<NftMintedEvent> {
-        // Extract JSON from log line: "Program log: NFT_MINTED:{...}"
-        if let Some(json_start) = log_line.find("NFT_MINTED:") {
-            let json_str = &log_line[json_start + 11..]; // Skip "NFT_MINTED:"
-
-            match serde_json::from_str::<NftMintedEvent>(json_str) {
-                Ok(event) => Some(event),
-                Err(e) => {
-                    println!("Failed to parse NFT event JSON: {}", e);
-                    None
-                }
-            }
-        } else {
-            None
-        }
-    }
-
     async fn handle_nft_minted_event(
         &self,
-        event: NftMintedEvent,
+        event: MintEvent,
         signature: &str,
     ) -> Result<(), AppError> {
+        let mint = Pubkey::new_from_array(event.mint).to_string();
+        let creator = Pubkey::new_from_array(event.creator).to_string();
         println!(
             "Processing NFT minted event: mint={}, name={}, creator={}",
-            event.mint, event.name, event.creator
+            mint, event.name, creator
         );
 
-        // Check if NFT already exists in database
-        if let Some(_existing_nft) = Nft::find_by_mint(&self.db, &event.mint).await? {
-            println!("NFT {} already exists in database", event.mint);
+        // Skip the metadata fetch for NFTs that are already indexed
+        if let Some(_existing_nft) = Nft::find_by_mint(&self.db, &mint).await? {
+            println!("NFT {} already exists in database", mint);
             return Ok(());
         }
 
         // Fetch additional metadata from the URI if needed
         let (image_url, description, attributes) = self.fetch_metadata(&event.uri).await?;
 
+        // Tie the NFT to the collection it was verified into, if we index it
+        let collection_id = match event.collection {
+            Some(collection_mint) => {
+                let collection_mint = Pubkey::new_from_array(collection_mint).to_string();
+                Collection::find_by_collection_address(&self.db, &collection_mint)
+                    .await?
+                    .map(|collection| collection.id)
+            }
+            None => None,
+        };
+
         // Create NFT record in database
         let create_request = CreateNftRequest {
-            mint_address: event.mint.clone(),
-            collection_id: None, // Could be extracted from metadata if available
+            mint_address: mint.clone(),
+            collection_id,
             name: event.name,
             description,
             image_url,
             animation_url: None,
             external_url: None,
             attributes,
-            creator_address: event.creator.clone(),
-            current_owner: event.creator, // Initially owned by creator
-            is_compressed: false,
+            creator_address: creator.clone(),
+            current_owner: creator, // Initially owned by creator
+            is_compressed: event.compressed.is_some(),
         };
 
         match Nft::create(&self.db, create_request).await {
-            Ok(nft) => {
+            Ok(Some(nft)) => {
                 println!(
                     "Successfully indexed NFT: {} ({})",
                     nft.name, nft.mint_address
                 );
             }
+            Ok(None) => {
+                println!("NFT {} already exists in database", mint);
+            }
             Err(e) => {
                 println!("Failed to create NFT record: {:?}", e);
                 return Err(e);
@@ -160,6 +203,264 @@ impl WebsocketIndexer {
         Ok(())
     }
 
+    async fn handle_nft_rented_event(
+        &self,
+        event: RentEvent,
+        signature: &str,
+    ) -> Result<(), AppError> {
+        let mint = Pubkey::new_from_array(event.mint).to_string();
+        let renter = Pubkey::new_from_array(event.renter).to_string();
+        println!(
+            "Processing NFT rented event: mint={}, renter={}, days={}",
+            mint, renter, event.days
+        );
+
+        // Log notifications carry no block time; they arrive as the
+        // transaction lands
+        let create_request = CreateRentalRequest {
+            nft_mint: mint.clone(),
+            owner_address: Pubkey::new_from_array(event.owner).to_string(),
+            renter_address: renter,
+            days: i32::from(event.days),
+            price: event.price as i64,
+            expires_at: DateTime::from_timestamp(event.expires_at, 0).unwrap_or_else(Utc::now),
+            transaction_signature: signature.to_string(),
+            block_time: Utc::now(),
+        };
+
+        if Rental::create(&self.db, create_request).await?.is_none() {
+            println!("Rental of {} in {} already indexed", mint, signature);
+        }
+
+        Ok(())
+    }
+
+    async fn handle_rental_reclaimed_event(
+        &self,
+        event: RentalReclaimEvent,
+    ) -> Result<(), AppError> {
+        let mint = Pubkey::new_from_array(event.mint).to_string();
+        let renter = Pubkey::new_from_array(event.renter).to_string();
+        println!(
+            "Processing rental reclaimed event: mint={}, renter={}",
+            mint, renter
+        );
+
+        Rental::mark_returned(&self.db, &mint, &renter, Utc::now()).await
+    }
+
+    async fn handle_offer_made_event(
+        &self,
+        event: OfferEvent,
+        signature: &str,
+    ) -> Result<(), AppError> {
+        let mint = Pubkey::new_from_array(event.mint).to_string();
+        // Offers reference the NFT's row
+        if Nft::find_by_mint(&self.db, &mint).await?.is_none() {
+            println!("Skipping offer on {}, which isn't indexed", mint);
+            return Ok(());
+        }
+
+        let offer = Offer::record_made(
+            &self.db,
+            &IndexedOffer {
+                offer_address: Pubkey::new_from_array(event.offer).to_string(),
+                nft_mint: Some(mint),
+                collection_id: None,
+                bidder_address: Pubkey::new_from_array(event.bidder).to_string(),
+                amount: event.amount as i64,
+                expires_at: offer_expiry(event.expires_at),
+                transaction_signature: signature.to_string(),
+            },
+        )
+        .await?;
+        println!("Indexed offer {}", offer.offer_address);
+
+        Ok(())
+    }
+
+    async fn handle_collection_offer_made_event(
+        &self,
+        event: CollectionOfferEvent,
+        signature: &str,
+    ) -> Result<(), AppError> {
+        let collection_mint = Pubkey::new_from_array(event.collection).to_string();
+        let Some(collection) =
+            Collection::find_by_collection_address(&self.db, &collection_mint).await?
+        else {
+            println!(
+                "Skipping offer on collection {}, which isn't indexed",
+                collection_mint
+            );
+            return Ok(());
+        };
+
+        let offer = Offer::record_made(
+            &self.db,
+            &IndexedOffer {
+                offer_address: Pubkey::new_from_array(event.offer).to_string(),
+                nft_mint: None,
+                collection_id: Some(collection.id),
+                bidder_address: Pubkey::new_from_array(event.bidder).to_string(),
+                amount: event.amount as i64,
+                expires_at: offer_expiry(event.expires_at),
+                transaction_signature: signature.to_string(),
+            },
+        )
+        .await?;
+        println!("Indexed collection offer {}", offer.offer_address);
+
+        Ok(())
+    }
+
+    async fn handle_offer_accepted_event(
+        &self,
+        event: OfferAcceptEvent,
+        signature: &str,
+    ) -> Result<(), AppError> {
+        let mint = Pubkey::new_from_array(event.mint).to_string();
+        let accepted = Offer::mark_accepted(
+            &self.db,
+            OfferTarget::Nft(&mint),
+            &Pubkey::new_from_array(event.bidder).to_string(),
+            &Pubkey::new_from_array(event.seller).to_string(),
+            &mint,
+            signature,
+        )
+        .await?;
+        if accepted.is_none() {
+            println!("No open offer on {} to mark accepted", mint);
+        }
+
+        Ok(())
+    }
+
+    async fn handle_collection_offer_accepted_event(
+        &self,
+        event: CollectionOfferAcceptEvent,
+        signature: &str,
+    ) -> Result<(), AppError> {
+        let collection_mint = Pubkey::new_from_array(event.collection).to_string();
+        let Some(collection) =
+            Collection::find_by_collection_address(&self.db, &collection_mint).await?
+        else {
+            return Ok(());
+        };
+
+        let accepted = Offer::mark_accepted(
+            &self.db,
+            OfferTarget::Collection(collection.id),
+            &Pubkey::new_from_array(event.bidder).to_string(),
+            &Pubkey::new_from_array(event.seller).to_string(),
+            &Pubkey::new_from_array(event.mint).to_string(),
+            signature,
+        )
+        .await?;
+        if accepted.is_none() {
+            println!(
+                "No open offer on collection {} to mark accepted",
+                collection_mint
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn handle_offer_cancelled_event(
+        &self,
+        offer: &[u8; 32],
+        signature: &str,
+    ) -> Result<(), AppError> {
+        let offer_address = Pubkey::new_from_array(*offer).to_string();
+        if Offer::mark_cancelled(&self.db, &offer_address, signature)
+            .await?
+            .is_none()
+        {
+            println!("Cancelled offer {} isn't indexed", offer_address);
+        }
+
+        Ok(())
+    }
+
+    async fn handle_auction_created_event(
+        &self,
+        event: AuctionCreateEvent,
+        signature: &str,
+    ) -> Result<(), AppError> {
+        let mint = Pubkey::new_from_array(event.mint).to_string();
+        // Auctions reference the NFT's row
+        if Nft::find_by_mint(&self.db, &mint).await?.is_none() {
+            println!("Skipping auction of {}, which isn't indexed", mint);
+            return Ok(());
+        }
+
+        let auction_address = Pubkey::new_from_array(event.auction).to_string();
+        let created = Auction::record_created(
+            &self.db,
+            &IndexedAuction {
+                auction_address: auction_address.clone(),
+                nft_mint: mint,
+                seller_address: Pubkey::new_from_array(event.seller).to_string(),
+                reserve_price: event.reserve_price as i64,
+                ends_at: DateTime::from_timestamp(event.end_time, 0).unwrap_or_else(Utc::now),
+                transaction_signature: signature.to_string(),
+            },
+        )
+        .await?;
+        match created {
+            Some(auction) => println!("Indexed auction {}", auction.auction_address),
+            None => println!("Auction {} already indexed", auction_address),
+        }
+
+        Ok(())
+    }
+
+    async fn handle_bid_placed_event(
+        &self,
+        event: BidEvent,
+        signature: &str,
+    ) -> Result<(), AppError> {
+        let auction_address = Pubkey::new_from_array(event.auction).to_string();
+        let auction = Auction::record_bid(
+            &self.db,
+            &auction_address,
+            &Pubkey::new_from_array(event.bidder).to_string(),
+            event.amount as i64,
+            signature,
+        )
+        .await?;
+        if auction.is_none() {
+            println!(
+                "Bid in {} on auction {} not recorded: auction not indexed or bid already was",
+                signature, auction_address
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn handle_auction_settled_event(
+        &self,
+        event: AuctionSettleEvent,
+        signature: &str,
+    ) -> Result<(), AppError> {
+        // The settle event doesn't carry the auction account; it's derived
+        // from the mint and seller
+        let (auction, _) =
+            pda::auction_address(&self.program_id.to_bytes(), &event.mint, &event.seller);
+        let auction_address = Pubkey::new_from_array(auction).to_string();
+        let winner = (event.amount > 0).then(|| Pubkey::new_from_array(event.winner).to_string());
+
+        if Auction::mark_settled(&self.db, &auction_address, winner.as_deref(), signature)
+            .await?
+            .is_none()
+        {
+            println!("Settled auction {} isn't indexed", auction_address);
+        }
+
+        Ok(())
+    }
+
     async fn fetch_metadata(
         &self,
         uri: &str,
@@ -234,6 +535,25 @@ impl WebsocketIndexer {
     }
 }
 
+/// Offer events use 0 for offers that don't expire
+fn offer_expiry(expires_at: i64) -> Option<DateTime<Utc>> {
+    if expires_at == 0 {
+        return None;
+    }
+    DateTime::from_timestamp(expires_at, 0)
+}
+
+/// Decode a marketplace event from a `Program data: <base64>...` log line
+fn decode_event(log_line: &str) -> Option<MarketplaceEvent> {
+    let data = log_line.strip_prefix("Program data: ")?;
+    let fields = data
+        .split_whitespace()
+        .map(|field| base64::engine::general_purpose::STANDARD.decode(field))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    MarketplaceEvent::from_log_data(&fields)
+}
+
 pub async fn start_websocket_indexer(db: PgPool, config: Config) -> Result<(), AppError> {
     let indexer = WebsocketIndexer::new(db, config)?;
     indexer.start().await
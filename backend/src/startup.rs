@@ -0,0 +1,70 @@
+use solana_client::rpc_client::RpcClient;
+use std::{future::Future, time::Duration};
+
+use crate::{config::Config, error::AppError};
+
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Retry `connect` with exponential backoff until it succeeds or the
+/// configured number of attempts is used up.
+///
+/// Lets the service start before Postgres, Redis or the RPC node are ready,
+/// which docker-compose and Kubernetes don't guarantee.
+pub async fn retry<T, F, Fut>(
+    config: &Config,
+    dependency: &str,
+    mut connect: F,
+) -> Result<T, AppError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, AppError>>,
+{
+    let attempts = config.startup_max_retries.max(1);
+    let mut delay = Duration::from_millis(config.startup_retry_base_delay_ms);
+
+    for attempt in 1..=attempts {
+        match connect().await {
+            Ok(value) => {
+                if attempt > 1 {
+                    println!("Connected to {} after {} attempts", dependency, attempt);
+                }
+                return Ok(value);
+            }
+            Err(e) if attempt < attempts => {
+                println!(
+                    "Waiting for {} (attempt {}/{}): {}; retrying in {}ms",
+                    dependency,
+                    attempt,
+                    attempts,
+                    e,
+                    delay.as_millis()
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+            Err(e) => {
+                println!(
+                    "Giving up on {} after {} attempts: {}",
+                    dependency, attempts, e
+                );
+                return Err(e);
+            }
+        }
+    }
+
+    unreachable!("retry loop always returns")
+}
+
+/// Wait until the configured Solana RPC endpoint answers a health check
+pub async fn wait_for_solana_rpc(config: &Config) -> Result<(), AppError> {
+    retry(config, "Solana RPC", || {
+        let rpc_url = config.solana_rpc_url.clone();
+        async move {
+            tokio::task::spawn_blocking(move || RpcClient::new(rpc_url).get_health())
+                .await
+                .map_err(|e| AppError::ConfigError(format!("RPC health check panicked: {}", e)))?
+                .map_err(AppError::from)
+        }
+    })
+    .await
+}
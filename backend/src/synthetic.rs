@@ -0,0 +1,232 @@
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{Collection, MarketplaceStats},
+    services::rarity,
+};
+
+/// How much synthetic data to generate. Addresses are deterministic
+/// (`Syn...`), so generating again with the same scale tops up rather than
+/// duplicates.
+#[derive(Debug, Clone)]
+pub struct Scale {
+    pub collections: u32,
+    pub nfts_per_collection: u32,
+    /// Distinct owner and buyer wallets
+    pub wallets: u32,
+    /// Share of NFTs with an active listing, 0-100
+    pub listed_percent: u32,
+    pub sales_per_collection: u32,
+    /// Sales and listings are spread over this many days before now
+    pub days: u32,
+    /// Seeds Postgres' `random()` so runs are repeatable
+    pub seed: u32,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Summary {
+    pub collections: u64,
+    pub nfts: u64,
+    pub listings: u64,
+    pub sales: u64,
+}
+
+/// Generate collections, NFTs with skewed trait distributions, active
+/// listings and a sales history, then roll up the derived tables (daily
+/// stats, rank scores, holder snapshots, rarity) the way the scheduler would.
+pub async fn generate(pool: &PgPool, scale: &Scale) -> Result<Summary, AppError> {
+    let mut summary = Summary::default();
+
+    for index in 0..scale.collections {
+        let mut tx = pool.begin().await?;
+        // Seeded per collection so a collection's data doesn't depend on
+        // how many were generated before it
+        sqlx::query("SELECT setseed($1)")
+            .bind(collection_seed(scale.seed, index))
+            .execute(&mut *tx)
+            .await?;
+
+        let (collection_id, created) = upsert_collection(&mut tx, index).await?;
+        if created {
+            summary.collections += 1;
+        }
+        summary.nfts += insert_nfts(&mut tx, scale, index, collection_id).await?;
+        summary.listings += insert_listings(&mut tx, scale, index, collection_id).await?;
+        summary.sales += insert_sales(&mut tx, scale, index).await?;
+        tx.commit().await?;
+    }
+
+    let today = Utc::now().date_naive();
+    for days_ago in 1..=scale.days as i64 {
+        MarketplaceStats::create_or_update_daily_stats(pool, today - Duration::days(days_ago))
+            .await?;
+    }
+    Collection::snapshot_holders(pool, today).await?;
+    Collection::refresh_rank_scores(pool).await?;
+    rarity::refresh_stale_collections(pool).await?;
+
+    Ok(summary)
+}
+
+fn collection_seed(seed: u32, index: u32) -> f64 {
+    ((seed as u64 * 7919 + index as u64) % 20_000) as f64 / 10_000.0 - 1.0
+}
+
+/// Floor in lamports, between 0.02 and 5 SOL
+fn floor_price(index: u32) -> i64 {
+    20_000_000 * (1 + (index as i64 * 7919) % 250)
+}
+
+fn mint_prefix(index: u32) -> String {
+    format!("Syn{:05}", index)
+}
+
+async fn upsert_collection(
+    tx: &mut Transaction<'_, Postgres>,
+    index: u32,
+) -> Result<(Uuid, bool), AppError> {
+    let name = format!("Synthetic {:05}", index);
+    let existing: Option<Uuid> =
+        sqlx::query_scalar("SELECT id FROM collections WHERE name = $1 AND deleted_at IS NULL")
+            .bind(&name)
+            .fetch_optional(&mut **tx)
+            .await?;
+    if let Some(id) = existing {
+        return Ok((id, false));
+    }
+
+    let id = sqlx::query_scalar(
+        r#"
+        INSERT INTO collections (name, symbol, description, creator_address, verified)
+        VALUES ($1, $2, 'Synthetic collection for load testing', $3, $4)
+        RETURNING id
+        "#,
+    )
+    .bind(&name)
+    .bind(format!("SYN{}", index % 1000))
+    .bind(format!("SynCreator{:0>34}", index))
+    .bind(index % 3 == 0)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok((id, true))
+}
+
+async fn insert_nfts(
+    tx: &mut Transaction<'_, Postgres>,
+    scale: &Scale,
+    index: u32,
+    collection_id: Uuid,
+) -> Result<u64, AppError> {
+    // Squaring (and cubing...) random() makes the later trait values rare
+    let result = sqlx::query(
+        r#"
+        INSERT INTO nfts (
+            mint_address, collection_id, name, image_url, attributes, creator_address,
+            current_owner, created_at
+        )
+        SELECT $2 || lpad(i::text, 36, '0'), $1, $3 || ' #' || (i + 1),
+               'https://picsum.photos/seed/' || $2 || i || '/512',
+               jsonb_build_array(
+                   jsonb_build_object('trait_type', 'Background', 'value',
+                       (ARRAY['Blue', 'Red', 'Green', 'Purple', 'Gold'])
+                           [1 + floor(power(random(), 2) * 5)::int]),
+                   jsonb_build_object('trait_type', 'Eyes', 'value',
+                       (ARRAY['Normal', 'Sleepy', 'Laser', 'Diamond'])
+                           [1 + floor(power(random(), 3) * 4)::int]),
+                   jsonb_build_object('trait_type', 'Hat', 'value',
+                       (ARRAY['None', 'Cap', 'Beanie', 'Crown'])
+                           [1 + floor(power(random(), 4) * 4)::int])
+               ),
+               $4, 'SynWallet' || lpad(floor(random() * $6)::bigint::text, 35, '0'),
+               NOW() - random() * make_interval(days => $7)
+        FROM generate_series(0, $5 - 1) AS i
+        ON CONFLICT (mint_address) DO NOTHING
+        "#,
+    )
+    .bind(collection_id)
+    .bind(mint_prefix(index))
+    .bind(format!("Synthetic {:05}", index))
+    .bind(format!("SynCreator{:0>34}", index))
+    .bind(scale.nfts_per_collection as i32)
+    .bind(scale.wallets.max(1) as i32)
+    .bind(scale.days as i32)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+async fn insert_listings(
+    tx: &mut Transaction<'_, Postgres>,
+    scale: &Scale,
+    index: u32,
+    collection_id: Uuid,
+) -> Result<u64, AppError> {
+    // Most listings sit near the floor with a long tail above it
+    let result = sqlx::query(
+        r#"
+        INSERT INTO listings (nft_mint, seller_address, price, status, block_time)
+        SELECT n.mint_address, n.current_owner,
+               ($2 * (1 + power(random(), 3) * 10))::bigint, 'active',
+               NOW() - random() * make_interval(days => $4)
+        FROM nfts n
+        WHERE n.collection_id = $1
+          AND random() * 100 < $3
+          AND NOT EXISTS (
+              SELECT 1 FROM listings l WHERE l.nft_mint = n.mint_address AND l.status = 'active'
+          )
+        "#,
+    )
+    .bind(collection_id)
+    .bind(floor_price(index))
+    .bind(scale.listed_percent as f64)
+    .bind(scale.days as i32)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+async fn insert_sales(
+    tx: &mut Transaction<'_, Postgres>,
+    scale: &Scale,
+    index: u32,
+) -> Result<u64, AppError> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO sales (
+            nft_mint, seller_address, buyer_address, price, base_price, marketplace_fee,
+            royalty_paid, transaction_signature, block_time
+        )
+        SELECT n.mint_address, n.current_owner,
+               'SynWallet' || lpad(floor(random() * $3)::bigint::text, 35, '0'),
+               s.price, s.price, s.price * 25 / 1000, s.price * 5 / 100,
+               $2 || lpad(s.i::text, 40, '0'), s.block_time
+        FROM (
+            SELECT i,
+                   $1 || lpad(floor(random() * $4)::bigint::text, 36, '0') AS mint_address,
+                   ($5 * (0.7 + random() * 0.6))::bigint AS price,
+                   NOW() - random() * make_interval(days => $6) AS block_time
+            FROM generate_series(0, $7 - 1) AS i
+        ) s
+        JOIN nfts n ON n.mint_address = s.mint_address
+        ON CONFLICT (transaction_signature, instruction_index) DO NOTHING
+        "#,
+    )
+    .bind(mint_prefix(index))
+    .bind(format!("SynSale{:05}", index))
+    .bind(scale.wallets.max(1) as i32)
+    .bind(scale.nfts_per_collection.max(1) as i32)
+    .bind(floor_price(index))
+    .bind(scale.days as i32)
+    .bind(scale.sales_per_collection as i32)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,3 @@
+pub mod pagination;
+pub mod program;
+pub mod validation;
@@ -0,0 +1,240 @@
+use axum::{
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+};
+use base64::Engine;
+use serde::{de::DeserializeOwned, ser::SerializeMap, Deserialize, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::error::AppError;
+
+pub use solmint_client::PageInfo;
+
+pub const DEFAULT_LIMIT: i64 = 20;
+pub const MAX_LIMIT: i64 = 100;
+
+#[derive(Debug, Deserialize)]
+struct RawPageParams {
+    page: Option<i64>,
+    limit: Option<i64>,
+    cursor: Option<String>,
+}
+
+/// `?page=&limit=` or `?cursor=&limit=` from the query string. `DEFAULT` and
+/// `MAX` are the endpoint's default and largest page size; a limit outside
+/// `1..=MAX` is rejected rather than clamped.
+#[derive(Debug, Clone)]
+pub struct PageParams<const DEFAULT: i64 = { DEFAULT_LIMIT }, const MAX: i64 = { MAX_LIMIT }> {
+    pub page: i64,
+    pub limit: i64,
+    /// Opaque position returned as `next_cursor` by the previous page
+    pub cursor: Option<String>,
+}
+
+impl<S, const DEFAULT: i64, const MAX: i64> FromRequestParts<S> for PageParams<DEFAULT, MAX>
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Query(raw) = Query::<RawPageParams>::try_from_uri(&parts.uri)
+            .map_err(|e| AppError::BadRequest(e.body_text()))?;
+
+        let page = raw.page.unwrap_or(0);
+        if page < 0 {
+            return Err(crate::error::bad_request_error("page must not be negative"));
+        }
+        let limit = raw.limit.unwrap_or(DEFAULT);
+        if !(1..=MAX).contains(&limit) {
+            return Err(AppError::BadRequest(format!(
+                "limit must be between 1 and {}",
+                MAX
+            )));
+        }
+        if raw.cursor.is_some() && raw.page.is_some() {
+            return Err(crate::error::bad_request_error(
+                "pass either page or cursor, not both",
+            ));
+        }
+
+        Ok(Self {
+            page,
+            limit,
+            cursor: raw.cursor,
+        })
+    }
+}
+
+impl<const DEFAULT: i64, const MAX: i64> PageParams<DEFAULT, MAX> {
+    pub fn offset(&self) -> i64 {
+        self.page * self.limit
+    }
+
+    /// Rows to fetch: one more than the page holds, to tell whether another
+    /// page follows without counting
+    pub fn fetch_limit(&self) -> i64 {
+        self.limit + 1
+    }
+
+    /// The decoded cursor, for endpoints that page by key
+    pub fn cursor<C: DeserializeOwned>(&self) -> Result<Option<C>, AppError> {
+        self.cursor.as_deref().map(decode_cursor).transpose()
+    }
+}
+
+fn encode_cursor<C: Serialize>(cursor: &C) -> Option<String> {
+    let json = serde_json::to_vec(cursor).ok()?;
+    Some(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(json))
+}
+
+fn decode_cursor<C: DeserializeOwned>(cursor: &str) -> Result<C, AppError> {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .ok_or_else(|| crate::error::bad_request_error("invalid cursor"))
+}
+
+/// One page of a list response, serialized as `{ "<key>": [...],
+/// "pagination": {...} }` plus any extra fields
+#[derive(Debug, Clone)]
+pub struct Paginated<T> {
+    key: &'static str,
+    items: Vec<T>,
+    pagination: PageInfo,
+    extra: Vec<(&'static str, Value)>,
+}
+
+impl<T> Paginated<T> {
+    /// `rows` must have been fetched with `PageParams::fetch_limit`
+    pub fn new<const DEFAULT: i64, const MAX: i64>(
+        key: &'static str,
+        mut rows: Vec<T>,
+        params: &PageParams<DEFAULT, MAX>,
+    ) -> Self {
+        let has_more = rows.len() as i64 > params.limit;
+        rows.truncate(params.limit as usize);
+
+        Self {
+            key,
+            items: rows,
+            pagination: PageInfo {
+                page: params.cursor.is_none().then_some(params.page),
+                limit: params.limit,
+                total: None,
+                has_more,
+                next_cursor: None,
+            },
+            extra: Vec::new(),
+        }
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Convert the items, keeping the page info
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Paginated<U> {
+        Paginated {
+            key: self.key,
+            items: self.items.into_iter().map(f).collect(),
+            pagination: self.pagination,
+            extra: self.extra,
+        }
+    }
+
+    pub fn with_total(mut self, total: i64) -> Self {
+        self.pagination.total = Some(total);
+        self
+    }
+
+    /// Set `next_cursor` from the last item when there's another page.
+    /// Offset pages keep `page`, so clients can follow either.
+    pub fn with_cursor<C: Serialize>(mut self, cursor: impl Fn(&T) -> C) -> Self {
+        if self.pagination.has_more {
+            self.pagination.next_cursor = self
+                .items
+                .last()
+                .map(cursor)
+                .as_ref()
+                .and_then(encode_cursor);
+        }
+        self
+    }
+
+    /// Add a top-level field next to the items, e.g. the season of a leaderboard
+    pub fn with_field(mut self, key: &'static str, value: impl Serialize) -> Self {
+        self.extra
+            .push((key, serde_json::to_value(value).unwrap_or(Value::Null)));
+        self
+    }
+}
+
+impl<T: Serialize> Serialize for Paginated<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.extra.len() + 2))?;
+        for (key, value) in &self.extra {
+            map.serialize_entry(key, value)?;
+        }
+        map.serialize_entry(self.key, &self.items)?;
+        map.serialize_entry("pagination", &self.pagination)?;
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn params(page: i64, limit: i64, cursor: Option<String>) -> PageParams {
+        PageParams {
+            page,
+            limit,
+            cursor,
+        }
+    }
+
+    #[test]
+    fn cursor_round_trips() {
+        let cursor = (Utc::now(), Uuid::new_v4());
+        let encoded = encode_cursor(&cursor).unwrap();
+
+        let decoded: Option<(DateTime<Utc>, Uuid)> = params(0, 20, Some(encoded)).cursor().unwrap();
+        assert_eq!(decoded, Some(cursor));
+    }
+
+    #[test]
+    fn invalid_cursor_is_rejected() {
+        assert!(params(0, 20, Some("not a cursor".into()))
+            .cursor::<i64>()
+            .is_err());
+        assert_eq!(params(0, 20, None).cursor::<i64>().unwrap(), None);
+    }
+
+    #[test]
+    fn next_cursor_points_at_the_last_item() {
+        let page =
+            Paginated::new("items", vec![1i64, 2, 3], &params(4, 2, None)).with_cursor(|n| *n);
+
+        assert_eq!(page.items(), [1, 2]);
+        assert!(page.pagination.has_more);
+        // An offset page keeps its page number
+        assert_eq!(page.pagination.page, Some(4));
+        let next = params(0, 2, page.pagination.next_cursor);
+        assert_eq!(next.cursor::<i64>().unwrap(), Some(2));
+    }
+
+    #[test]
+    fn cursor_pages_omit_the_page_number() {
+        let cursor = encode_cursor(&2i64);
+        let page = Paginated::new("items", vec![3i64], &params(0, 2, cursor)).with_cursor(|n| *n);
+
+        assert_eq!(page.pagination.page, None);
+        assert!(!page.pagination.has_more);
+        assert_eq!(page.pagination.next_cursor, None);
+    }
+}
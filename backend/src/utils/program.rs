@@ -0,0 +1,23 @@
+//! Conversions between `nft-marketplace-sdk`'s plain-byte types and the
+//! `solana-sdk` ones used throughout the backend
+
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+pub fn instruction(instruction: nft_marketplace_sdk::Instruction) -> Instruction {
+    Instruction {
+        program_id: Pubkey::new_from_array(instruction.program_id),
+        accounts: instruction
+            .accounts
+            .into_iter()
+            .map(|meta| AccountMeta {
+                pubkey: Pubkey::new_from_array(meta.pubkey),
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect(),
+        data: instruction.data,
+    }
+}
@@ -0,0 +1,165 @@
+//! Field checks for request bodies, run by the [`ValidatedJson`] extractor
+//! before a handler sees the request
+
+use axum::{
+    extract::{FromRequest, Request},
+    Json,
+};
+use chrono::Utc;
+use nft_marketplace_sdk::metadata::{MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH};
+use serde::de::DeserializeOwned;
+use solana_sdk::pubkey::Pubkey;
+use solmint_client::MintNftRequest;
+use std::str::FromStr;
+
+use crate::{
+    error::AppError,
+    models::{validate_username, CreateCheckoutOrderRequest, MakeOfferRequest, UpdateUserRequest},
+};
+
+/// Longest email address, the `users.email` column width
+const MAX_EMAIL_LENGTH: usize = 255;
+/// `users.twitter_handle` and `users.discord_handle` column width
+const MAX_HANDLE_LENGTH: usize = 50;
+const MAX_BIO_LENGTH: usize = 500;
+const MAX_URL_LENGTH: usize = 2048;
+
+/// A request body that can check its own fields
+pub trait Validate {
+    fn validate(&self) -> Result<(), AppError>;
+}
+
+/// `Json<T>` that rejects bodies failing [`Validate`] with a 400
+pub struct ValidatedJson<T>(pub T);
+
+impl<S, T> FromRequest<S> for ValidatedJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+{
+    type Rejection = AppError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(request, state)
+            .await
+            .map_err(|e| AppError::BadRequest(e.body_text()))?;
+        value.validate()?;
+
+        Ok(Self(value))
+    }
+}
+
+pub fn pubkey(field: &str, value: &str) -> Result<(), AppError> {
+    Pubkey::from_str(value)
+        .map(|_| ())
+        .map_err(|_| AppError::ValidationError(format!("{} must be a base58 address", field)))
+}
+
+/// `value` is between `min` and `max` bytes long
+pub fn length(field: &str, value: &str, min: usize, max: usize) -> Result<(), AppError> {
+    if !(min..=max).contains(&value.len()) {
+        return Err(AppError::ValidationError(format!(
+            "{} must be {} to {} characters",
+            field, min, max
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn http_url(field: &str, value: &str) -> Result<(), AppError> {
+    length(field, value, 1, MAX_URL_LENGTH)?;
+    if !value.starts_with("https://") && !value.starts_with("http://") {
+        return Err(AppError::ValidationError(format!(
+            "{} must be an http(s) URL",
+            field
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn email(field: &str, value: &str) -> Result<(), AppError> {
+    length(field, value, 3, MAX_EMAIL_LENGTH)?;
+    match value.split_once('@') {
+        Some((local, domain)) if !local.is_empty() && domain.contains('.') => Ok(()),
+        _ => Err(AppError::ValidationError(format!(
+            "{} must be an email address",
+            field
+        ))),
+    }
+}
+
+pub fn positive(field: &str, value: u64) -> Result<(), AppError> {
+    if value == 0 {
+        return Err(AppError::ValidationError(format!(
+            "{} must be positive",
+            field
+        )));
+    }
+
+    Ok(())
+}
+
+impl Validate for MintNftRequest {
+    fn validate(&self) -> Result<(), AppError> {
+        length("name", &self.name, 1, MAX_NAME_LENGTH)?;
+        length("symbol", &self.symbol, 0, MAX_SYMBOL_LENGTH)?;
+        length("uri", &self.uri, 1, MAX_URI_LENGTH)?;
+        pubkey("creator", &self.creator)
+    }
+}
+
+impl Validate for UpdateUserRequest {
+    fn validate(&self) -> Result<(), AppError> {
+        if let Some(username) = &self.username {
+            validate_username(username)?;
+        }
+        if let Some(address) = &self.email {
+            email("email", address)?;
+        }
+        if let Some(bio) = &self.bio {
+            length("bio", bio, 0, MAX_BIO_LENGTH)?;
+        }
+        if let Some(avatar_url) = &self.avatar_url {
+            http_url("avatar_url", avatar_url)?;
+        }
+        if let Some(handle) = &self.twitter_handle {
+            length("twitter_handle", handle, 1, MAX_HANDLE_LENGTH)?;
+        }
+        if let Some(handle) = &self.discord_handle {
+            length("discord_handle", handle, 1, MAX_HANDLE_LENGTH)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Bidders may be `.sol` domains, which the handler resolves
+impl Validate for MakeOfferRequest {
+    fn validate(&self) -> Result<(), AppError> {
+        positive("amount", self.amount)?;
+        if i64::try_from(self.amount).is_err() {
+            return Err(AppError::ValidationError("amount is too large".to_string()));
+        }
+        if self
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+        {
+            return Err(AppError::ValidationError(
+                "expires_at must be in the future".to_string(),
+            ));
+        }
+        pubkey("nft_mint", &self.nft_mint)?;
+        pubkey("marketplace_address", &self.marketplace_address)
+    }
+}
+
+impl Validate for CreateCheckoutOrderRequest {
+    fn validate(&self) -> Result<(), AppError> {
+        match &self.email {
+            Some(address) => email("email", address),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Mint config allowlist Merkle proofs, shared by the program, which checks
+//! them, and the backend, which builds the trees. Leaves and inner nodes are
+//! hashed with different prefixes so a leaf can never pass for a node.
+//!
+//! The hash is passed in so the program can use the `sol_sha256` syscall;
+//! off-chain, use [`sha256v`].
+
+use crate::Address;
+
+pub type MerkleHash = [u8; 32];
+
+/// SHA-256 of the concatenation of its inputs, like `solana_program::hash::hashv`
+pub type Hashv = fn(&[&[u8]]) -> MerkleHash;
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// `sha256(0x00 || wallet || allocation_le)`; `allocation` is the most NFTs
+/// the wallet may mint
+pub fn leaf(hashv: Hashv, wallet: &Address, allocation: u64) -> MerkleHash {
+    hashv(&[LEAF_PREFIX, wallet, &allocation.to_le_bytes()])
+}
+
+/// `sha256(0x01 || min(a, b) || max(a, b))`. Sorting the pair means proofs
+/// don't need to carry left/right flags.
+pub fn node(hashv: Hashv, a: &MerkleHash, b: &MerkleHash) -> MerkleHash {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, left, right])
+}
+
+/// Whether `proof`, the sibling hashes from the leaf up, leads from the leaf
+/// of `wallet` and `allocation` to `root`
+pub fn verify_proof(
+    hashv: Hashv,
+    wallet: &Address,
+    allocation: u64,
+    proof: &[MerkleHash],
+    root: &MerkleHash,
+) -> bool {
+    proof
+        .iter()
+        .fold(leaf(hashv, wallet, allocation), |hash, sibling| {
+            node(hashv, &hash, sibling)
+        })
+        == *root
+}
+
+/// [`Hashv`] over the `sha2` crate, for use off-chain
+#[cfg(feature = "pda")]
+pub fn sha256v(values: &[&[u8]]) -> MerkleHash {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    for value in values {
+        hasher.update(value);
+    }
+    hasher.finalize().into()
+}
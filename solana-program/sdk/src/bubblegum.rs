@@ -0,0 +1,101 @@
+//! Just enough of Metaplex Bubblegum to mint a compressed NFT into an existing
+//! tree, without depending on the Bubblegum crate
+
+use alloc::{string::String, vec, vec::Vec};
+use borsh::BorshSerialize;
+
+use crate::{
+    ids::{ACCOUNT_COMPRESSION_PROGRAM_ID, BUBBLEGUM_PROGRAM_ID, SYSTEM_PROGRAM_ID},
+    instruction::{AccountMeta, Instruction},
+    metadata::{Collection, Creator},
+    Address,
+};
+
+/// Anchor discriminator of `mint_v1`
+const MINT_V1: [u8; 8] = [145, 98, 192, 118, 184, 147, 118, 104];
+
+/// Offset of `num_minted` in a tree config account: after the Anchor
+/// discriminator, `tree_creator`, `tree_delegate` and `total_mint_capacity`
+const TREE_CONFIG_NUM_MINTED_OFFSET: usize = 8 + 32 + 32 + 8;
+
+/// `TokenStandard::NonFungible`
+const NON_FUNGIBLE: u8 = 0;
+/// `TokenProgramVersion::Original`
+const TOKEN_PROGRAM_ORIGINAL: u8 = 0;
+
+/// Bubblegum's `MetadataArgs`
+#[derive(BorshSerialize)]
+struct MetadataArgs {
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    primary_sale_happened: bool,
+    is_mutable: bool,
+    edition_nonce: Option<u8>,
+    token_standard: Option<u8>,
+    collection: Option<Collection>,
+    /// Always `None`, which encodes the same whatever the type
+    uses: Option<u8>,
+    token_program_version: u8,
+    creators: Vec<Creator>,
+}
+
+/// Leaves minted into the tree so far, which is also the leaf index the next
+/// mint gets; `None` if `data` is too short to be a tree config
+pub fn tree_config_num_minted(data: &[u8]) -> Option<u64> {
+    data.get(TREE_CONFIG_NUM_MINTED_OFFSET..TREE_CONFIG_NUM_MINTED_OFFSET + 8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+}
+
+/// `mint_v1`: mint a compressed NFT owned by `leaf_owner` into `merkle_tree`.
+/// `tree_delegate` is the tree's creator or delegate, or any signer for a
+/// public tree. Like `MintNft`, the metadata is mutable and carries no
+/// creators, collection or royalty. `log_wrapper` is the SPL Noop program.
+#[allow(clippy::too_many_arguments)]
+pub fn mint_v1(
+    tree_config: &Address,
+    leaf_owner: &Address,
+    leaf_delegate: &Address,
+    merkle_tree: &Address,
+    payer: &Address,
+    tree_delegate: &Address,
+    log_wrapper: &Address,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Instruction {
+    let args = MetadataArgs {
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points: 0,
+        primary_sale_happened: false,
+        is_mutable: true,
+        edition_nonce: None,
+        token_standard: Some(NON_FUNGIBLE),
+        collection: None,
+        uses: None,
+        token_program_version: TOKEN_PROGRAM_ORIGINAL,
+        creators: Vec::new(),
+    };
+    let mut data = MINT_V1.to_vec();
+    data.extend(borsh::to_vec(&args).expect("instructions serialize into a Vec"));
+
+    Instruction {
+        program_id: BUBBLEGUM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*tree_config, false),
+            AccountMeta::new_readonly(*leaf_owner, false),
+            AccountMeta::new_readonly(*leaf_delegate, false),
+            AccountMeta::new(*merkle_tree, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*tree_delegate, true),
+            AccountMeta::new_readonly(*log_wrapper, false),
+            AccountMeta::new_readonly(ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data,
+    }
+}
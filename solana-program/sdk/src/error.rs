@@ -0,0 +1,129 @@
+use core::fmt;
+
+/// Errors the program returns as `ProgramError::Custom(code)`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u32)]
+pub enum MarketplaceError {
+    InvalidInstruction,
+    NotRentExempt,
+    ExpectedAmountMismatch,
+    AmountOverflow,
+    InvalidAccountOwner,
+    AccountNotInitialized,
+    AccountAlreadyInitialized,
+    InvalidMarketplaceAuthority,
+    InvalidSeller,
+    InvalidBuyer,
+    NftNotForSale,
+    InsufficientFunds,
+    InvalidPrice,
+    InvalidFeePercentage,
+    MarketplaceFeeCalculationError,
+    OfferExpired,
+    AuctionEnded,
+    AuctionNotEnded,
+    BidTooLow,
+    InvalidCollection,
+    InvalidUpdateAuthority,
+    InvalidMetadata,
+    InvalidBundle,
+    RentalActive,
+    RentalNotExpired,
+    MintNotLive,
+    MintSoldOut,
+    NotOnAllowlist,
+    WalletLimitReached,
+    MarketplaceNeedsMigration,
+    InsufficientRewards,
+}
+
+impl MarketplaceError {
+    /// The error for a `Custom(code)` returned by the program
+    pub fn from_code(code: u32) -> Option<Self> {
+        let error = match code {
+            0 => Self::InvalidInstruction,
+            1 => Self::NotRentExempt,
+            2 => Self::ExpectedAmountMismatch,
+            3 => Self::AmountOverflow,
+            4 => Self::InvalidAccountOwner,
+            5 => Self::AccountNotInitialized,
+            6 => Self::AccountAlreadyInitialized,
+            7 => Self::InvalidMarketplaceAuthority,
+            8 => Self::InvalidSeller,
+            9 => Self::InvalidBuyer,
+            10 => Self::NftNotForSale,
+            11 => Self::InsufficientFunds,
+            12 => Self::InvalidPrice,
+            13 => Self::InvalidFeePercentage,
+            14 => Self::MarketplaceFeeCalculationError,
+            15 => Self::OfferExpired,
+            16 => Self::AuctionEnded,
+            17 => Self::AuctionNotEnded,
+            18 => Self::BidTooLow,
+            19 => Self::InvalidCollection,
+            20 => Self::InvalidUpdateAuthority,
+            21 => Self::InvalidMetadata,
+            22 => Self::InvalidBundle,
+            23 => Self::RentalActive,
+            24 => Self::RentalNotExpired,
+            25 => Self::MintNotLive,
+            26 => Self::MintSoldOut,
+            27 => Self::NotOnAllowlist,
+            28 => Self::WalletLimitReached,
+            29 => Self::MarketplaceNeedsMigration,
+            30 => Self::InsufficientRewards,
+            _ => return None,
+        };
+        Some(error)
+    }
+
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+}
+
+impl fmt::Display for MarketplaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::InvalidInstruction => "Invalid instruction",
+            Self::NotRentExempt => "Not rent exempt",
+            Self::ExpectedAmountMismatch => "Expected amount mismatch",
+            Self::AmountOverflow => "Amount overflow",
+            Self::InvalidAccountOwner => "Invalid account owner",
+            Self::AccountNotInitialized => "Account not initialized",
+            Self::AccountAlreadyInitialized => "Account already initialized",
+            Self::InvalidMarketplaceAuthority => "Invalid marketplace authority",
+            Self::InvalidSeller => "Invalid seller",
+            Self::InvalidBuyer => "Invalid buyer",
+            Self::NftNotForSale => "NFT not for sale",
+            Self::InsufficientFunds => "Insufficient funds",
+            Self::InvalidPrice => "Invalid price",
+            Self::InvalidFeePercentage => "Invalid fee percentage",
+            Self::MarketplaceFeeCalculationError => "Marketplace fee calculation error",
+            Self::OfferExpired => "Offer expired",
+            Self::AuctionEnded => "Auction ended",
+            Self::AuctionNotEnded => "Auction not ended",
+            Self::BidTooLow => "Bid too low",
+            Self::InvalidCollection => "Invalid collection",
+            Self::InvalidUpdateAuthority => "Invalid update authority",
+            Self::InvalidMetadata => "Invalid metadata",
+            Self::InvalidBundle => "Invalid bundle",
+            Self::RentalActive => "NFT is rented out",
+            Self::RentalNotExpired => "Rental not expired",
+            Self::MintNotLive => "Minting has not started",
+            Self::MintSoldOut => "Mint sold out",
+            Self::NotOnAllowlist => "Wallet not on the allowlist",
+            Self::WalletLimitReached => "Wallet mint limit reached",
+            Self::MarketplaceNeedsMigration => "Marketplace needs migrating",
+            Self::InsufficientRewards => "Reward vault can't cover the rewards due",
+        };
+        f.write_str(message)
+    }
+}
+
+#[cfg(feature = "program")]
+impl From<MarketplaceError> for solana_program::program_error::ProgramError {
+    fn from(e: MarketplaceError) -> Self {
+        solana_program::program_error::ProgramError::Custom(e as u32)
+    }
+}
@@ -0,0 +1,320 @@
+//! Events the program emits for indexers. Each is logged with
+//! `sol_log_data` as two fields, `EVENT_TAG` and the borsh-encoded
+//! `MarketplaceEvent`, whose first byte is the variant; RPC logs show them as
+//! `Program data: <base64 tag> <base64 event>`.
+
+use alloc::{string::String, vec::Vec};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::Address;
+
+/// First field of every event, telling them apart from data other programs
+/// log in the same transaction
+pub const EVENT_TAG: &[u8] = b"solmint:event";
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum MarketplaceEvent {
+    NftMinted(MintEvent),
+    NftMetadataUpdated(MetadataUpdateEvent),
+    EditionPrinted(EditionPrintEvent),
+    NftListed(ListEvent),
+    NftSold(SaleEvent),
+    NftDelisted(DelistEvent),
+    OfferMade(OfferEvent),
+    OfferAccepted(OfferAcceptEvent),
+    OfferCancelled(OfferCancelEvent),
+    AuctionCreated(AuctionCreateEvent),
+    BidPlaced(BidEvent),
+    AuctionSettled(AuctionSettleEvent),
+    CollectionOfferMade(CollectionOfferEvent),
+    CollectionOfferAccepted(CollectionOfferAcceptEvent),
+    CollectionOfferCancelled(CollectionOfferCancelEvent),
+    /// Emitted after `NftSold` when part of the fee went to a referrer
+    ReferralPaid(ReferralEvent),
+    BundleListed(BundleListEvent),
+    BundleSold(BundleSaleEvent),
+    BundleDelisted(BundleDelistEvent),
+    /// Emitted instead of `NftListed` for a private sale
+    NftListedPrivately(PrivateListEvent),
+    NftStaked(StakeEvent),
+    /// Emitted after paying out any rewards due, which `RewardsClaimed`
+    /// doesn't repeat
+    NftUnstaked(UnstakeEvent),
+    RewardsClaimed(RewardClaimEvent),
+    RentalListed(RentalListEvent),
+    NftRented(RentEvent),
+    RentalReclaimed(RentalReclaimEvent),
+    RentalCancelled(RentalCancelEvent),
+}
+
+impl MarketplaceEvent {
+    /// The fields to log with `sol_log_data`
+    pub fn to_log_data(&self) -> [Vec<u8>; 2] {
+        [
+            EVENT_TAG.to_vec(),
+            borsh::to_vec(self).expect("events serialize into a Vec"),
+        ]
+    }
+
+    /// Decode the fields of a `Program data:` log line, already base64
+    /// decoded; `None` if they aren't a marketplace event
+    pub fn from_log_data<T: AsRef<[u8]>>(fields: &[T]) -> Option<Self> {
+        match fields {
+            [tag, event] if tag.as_ref() == EVENT_TAG => Self::try_from_slice(event.as_ref()).ok(),
+            _ => None,
+        }
+    }
+
+    /// Log the event
+    #[cfg(feature = "program")]
+    pub fn emit(&self) {
+        let [tag, event] = self.to_log_data();
+        solana_program::log::sol_log_data(&[&tag, &event]);
+    }
+}
+
+/// An NFT minted, by `MintNft`, `MintMasterEdition` or `MintCompressedNft`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MintEvent {
+    /// The mint, or the asset id of a compressed NFT
+    pub mint: Address,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub creator: Address,
+    /// Verified Metaplex collection mint, if minted into one
+    pub collection: Option<Address>,
+    /// Where a compressed NFT was minted
+    pub compressed: Option<CompressedLeaf>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressedLeaf {
+    pub tree: Address,
+    pub leaf_index: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MetadataUpdateEvent {
+    pub mint: Address,
+    /// Name and URI after the update
+    pub name: String,
+    pub uri: String,
+    pub update_authority: Address,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EditionPrintEvent {
+    pub mint: Address,
+    pub master_mint: Address,
+    pub edition: u64,
+    pub owner: Address,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListEvent {
+    pub mint: Address,
+    pub seller: Address,
+    pub price: u64,
+    pub listing: Address,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrivateListEvent {
+    pub mint: Address,
+    pub seller: Address,
+    pub price: u64,
+    pub listing: Address,
+    pub allowed_buyer: Address,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaleEvent {
+    pub mint: Address,
+    pub seller: Address,
+    pub buyer: Address,
+    pub price: u64,
+    pub fee: u64,
+}
+
+/// The referrer's part of the fee of the sale of `mint`, included in the
+/// sale's `fee`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReferralEvent {
+    pub mint: Address,
+    pub referrer: Address,
+    pub amount: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DelistEvent {
+    pub mint: Address,
+    pub seller: Address,
+    pub listing: Address,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OfferEvent {
+    pub mint: Address,
+    pub bidder: Address,
+    pub amount: u64,
+    /// 0 if the offer doesn't expire
+    pub expires_at: i64,
+    pub offer: Address,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OfferAcceptEvent {
+    pub mint: Address,
+    pub seller: Address,
+    pub bidder: Address,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OfferCancelEvent {
+    pub mint: Address,
+    pub bidder: Address,
+    pub offer: Address,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuctionCreateEvent {
+    pub mint: Address,
+    pub seller: Address,
+    pub reserve_price: u64,
+    pub end_time: i64,
+    pub auction: Address,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BidEvent {
+    pub auction: Address,
+    pub bidder: Address,
+    pub amount: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuctionSettleEvent {
+    pub mint: Address,
+    pub seller: Address,
+    /// The seller if the auction ended without bids
+    pub winner: Address,
+    /// 0 if the auction ended without bids
+    pub amount: u64,
+    pub fee: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollectionOfferEvent {
+    pub collection: Address,
+    pub bidder: Address,
+    pub amount: u64,
+    /// 0 if the offer doesn't expire
+    pub expires_at: i64,
+    pub offer: Address,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollectionOfferAcceptEvent {
+    pub collection: Address,
+    pub mint: Address,
+    pub seller: Address,
+    pub bidder: Address,
+    pub amount: u64,
+    pub fee: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollectionOfferCancelEvent {
+    pub collection: Address,
+    pub bidder: Address,
+    pub offer: Address,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BundleListEvent {
+    pub bundle: Address,
+    pub seller: Address,
+    pub mints: Vec<Address>,
+    pub price: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BundleSaleEvent {
+    pub bundle: Address,
+    pub seller: Address,
+    pub buyer: Address,
+    pub price: u64,
+    pub fee: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BundleDelistEvent {
+    pub bundle: Address,
+    pub seller: Address,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StakeEvent {
+    pub mint: Address,
+    pub owner: Address,
+    pub collection: Address,
+    pub stake: Address,
+}
+
+/// A staked NFT back with its owner, who was paid `rewards` of the rewards
+/// due; the rest is forfeited if the reward vault ran short
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnstakeEvent {
+    pub mint: Address,
+    pub owner: Address,
+    pub collection: Address,
+    pub rewards: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RewardClaimEvent {
+    pub mint: Address,
+    pub owner: Address,
+    pub reward_mint: Address,
+    pub amount: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RentalListEvent {
+    pub mint: Address,
+    pub owner: Address,
+    pub daily_rate: u64,
+    pub max_days: u16,
+    pub rental: Address,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RentEvent {
+    pub mint: Address,
+    pub owner: Address,
+    pub renter: Address,
+    pub days: u16,
+    /// Rent paid upfront for all `days`, held until the NFT is reclaimed
+    pub price: u64,
+    pub expires_at: i64,
+}
+
+/// An expired rental's NFT back in escrow; the owner was paid the rent minus
+/// `fee`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RentalReclaimEvent {
+    pub mint: Address,
+    pub owner: Address,
+    pub renter: Address,
+    pub fee: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RentalCancelEvent {
+    pub mint: Address,
+    pub owner: Address,
+    pub rental: Address,
+}
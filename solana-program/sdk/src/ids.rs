@@ -0,0 +1,95 @@
+//! Addresses of the programs and sysvars the marketplace instructions use
+
+use five8_const::decode_32_const;
+
+use crate::Address;
+
+pub const SYSTEM_PROGRAM_ID: Address = decode_32_const("11111111111111111111111111111111");
+pub const SYSVAR_RENT_ID: Address = decode_32_const("SysvarRent111111111111111111111111111111111");
+pub const TOKEN_PROGRAM_ID: Address =
+    decode_32_const("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const TOKEN_2022_PROGRAM_ID: Address =
+    decode_32_const("TokenzQdBNbLqP5VEhdkAS6EPFLC1PBnBqCXEpPxuEb");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Address =
+    decode_32_const("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+pub const TOKEN_METADATA_PROGRAM_ID: Address =
+    decode_32_const("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bfTvXeUs");
+pub const BUBBLEGUM_PROGRAM_ID: Address =
+    decode_32_const("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+pub const ACCOUNT_COMPRESSION_PROGRAM_ID: Address =
+    decode_32_const("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+
+/// Seed of the marketplace PDA, followed by the authority's address
+pub const MARKETPLACE_SEED: &[u8] = b"marketplace";
+
+/// Seed of a listing PDA, followed by the NFT mint and the seller's address.
+/// The listed NFT is held in the listing's associated token account.
+pub const LISTING_SEED: &[u8] = b"listing";
+
+/// Seed of an offer PDA, followed by the NFT mint and the bidder's address.
+/// The offer account holds the offered lamports on top of its rent.
+pub const OFFER_SEED: &[u8] = b"offer";
+
+/// Seed of an auction PDA, followed by the NFT mint and the seller's address.
+/// The auctioned NFT is held in the auction's associated token account and
+/// the highest bid in the auction account, on top of its rent.
+pub const AUCTION_SEED: &[u8] = b"auction";
+
+/// Seed of a collection offer PDA, followed by the collection mint and the
+/// bidder's address. Like an offer, it holds the offered lamports on top of
+/// its rent.
+pub const COLLECTION_OFFER_SEED: &[u8] = b"collection_offer";
+
+/// Seed of a collection config PDA, followed by the marketplace account and
+/// the collection mint
+pub const COLLECTION_CONFIG_SEED: &[u8] = b"collection_config";
+
+/// Seed of a pending marketplace authority transfer PDA, followed by the
+/// marketplace account
+pub const AUTHORITY_TRANSFER_SEED: &[u8] = b"authority_transfer";
+
+/// Seed of a marketplace's cumulative stats PDA, followed by the marketplace
+/// account
+pub const MARKETPLACE_STATS_SEED: &[u8] = b"marketplace_stats";
+
+/// Seed of a marketplace's referral config PDA, followed by the marketplace
+/// account
+pub const REFERRAL_CONFIG_SEED: &[u8] = b"referral_config";
+
+/// Seed of a bundle PDA, followed by the seller's address and the bundle id
+/// (little-endian `u64`). Each bundled NFT is held in the bundle's associated
+/// token account for its mint.
+pub const BUNDLE_SEED: &[u8] = b"bundle";
+
+/// Seed of a staking config PDA, followed by the collection mint. Rewards
+/// are paid from the config's associated token account for its reward mint.
+pub const STAKING_CONFIG_SEED: &[u8] = b"staking_config";
+
+/// Seed of a stake PDA, followed by the NFT mint and the owner's address.
+/// The staked NFT is held in the stake's associated token account.
+pub const STAKE_SEED: &[u8] = b"stake";
+
+/// Seed of a rental PDA, followed by the NFT mint and the owner's address.
+/// The NFT is held in the rental's associated token account while it isn't
+/// rented out.
+pub const RENTAL_SEED: &[u8] = b"rental";
+
+/// Seed of a mint config PDA, followed by the creator's address and the
+/// config id (little-endian `u64`). The config is the update authority of the
+/// NFTs minted from it.
+pub const MINT_CONFIG_SEED: &[u8] = b"mint_config";
+
+/// Seed of a mint record PDA, followed by the mint config account and the
+/// minting wallet. It counts the NFTs the wallet minted from the config.
+pub const MINT_RECORD_SEED: &[u8] = b"mint_record";
+
+/// Seed of a Metaplex metadata PDA, followed by the metadata program id and
+/// the mint
+pub const METADATA_SEED: &[u8] = b"metadata";
+
+/// Last seed of a Metaplex master edition PDA, after the metadata PDA's seeds
+pub const MASTER_EDITION_SEED: &[u8] = b"edition";
+
+/// Seed of a Bubblegum asset id, followed by the Merkle tree and the leaf
+/// index as a little-endian `u64`
+pub const BUBBLEGUM_ASSET_SEED: &[u8] = b"asset";
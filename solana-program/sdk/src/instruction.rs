@@ -0,0 +1,2327 @@
+use alloc::{string::String, vec, vec::Vec};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{
+    error::MarketplaceError,
+    ids::{
+        ACCOUNT_COMPRESSION_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID, BUBBLEGUM_PROGRAM_ID,
+        SYSTEM_PROGRAM_ID, SYSVAR_RENT_ID, TOKEN_METADATA_PROGRAM_ID, TOKEN_PROGRAM_ID,
+    },
+    Address,
+};
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum MarketplaceInstruction {
+    /// Initialize a new marketplace
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Marketplace authority
+    /// 1. `[writable]` Marketplace account to initialize
+    /// 2. `[]` System program
+    /// 3. `[]` Rent sysvar
+    /// 4. `[writable]` Marketplace stats account (PDA) to initialize
+    InitializeMarketplace {
+        fee_percentage: u16, // Fee percentage in basis points (e.g., 250 = 2.5%)
+    },
+
+    /// Update marketplace fee
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Marketplace authority
+    /// 1. `[writable]` Marketplace account
+    UpdateMarketplaceFee { new_fee_percentage: u16 },
+
+    /// Mint NFT: one token with Metaplex metadata and a master edition, which
+    /// takes over the mint authority so the supply stays at one
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Mint authority/fee payer
+    /// 1. `[writable, signer]` Mint account to create
+    /// 2. `[writable]` Associated token account to create
+    /// 3. `[]` Token program
+    /// 4. `[]` Associated token program
+    /// 5. `[]` System program
+    /// 6. `[]` Rent sysvar
+    /// 7. `[writable]` Metadata account (Metaplex PDA) to create
+    /// 8. `[writable]` Master edition account (Metaplex PDA) to create
+    /// 9. `[]` Token metadata program
+    ///
+    /// Optionally, to set and verify the NFT into a Metaplex collection:
+    /// 10. `[]` Collection mint
+    /// 11. `[writable]` Collection metadata account
+    /// 12. `[]` Collection master edition account
+    /// 13. `[writable, signer]` Collection update authority
+    MintNft {
+        name: String,
+        symbol: String,
+        uri: String,
+    },
+
+    /// List an NFT for sale, moving it into an escrow token account owned by
+    /// the listing PDA
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Seller
+    /// 1. `[]` Marketplace account
+    /// 2. `[]` NFT mint
+    /// 3. `[writable]` Seller's token account
+    /// 4. `[writable]` Listing account (PDA) to create
+    /// 5. `[writable]` Listing escrow token account to create
+    /// 6. `[]` Token program: SPL Token or Token-2022
+    /// 7. `[]` Associated token program
+    /// 8. `[]` System program
+    /// 9. `[]` Rent sysvar
+    /// 10. `[writable]` Marketplace stats account (PDA)
+    ///
+    /// Remaining: for Token-2022 mints with a transfer hook, the accounts the
+    ///    hook needs, including its program and validation account
+    ListNft {
+        price: u64, // Price in lamports
+    },
+
+    /// Buy a listed NFT. The seller receives the price minus the marketplace
+    /// fee and any royalties, the fee recipient the fee, and the seller gets
+    /// the listing and escrow rent back when both are closed.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Buyer
+    /// 1. `[writable]` Seller
+    /// 2. `[]` Marketplace account
+    /// 3. `[writable]` Marketplace fee recipient
+    /// 4. `[]` NFT mint
+    /// 5. `[writable]` Listing account (PDA)
+    /// 6. `[writable]` Listing escrow token account; for a `ListNftDelegated`
+    ///    listing, the seller's token account
+    /// 7. `[writable]` Buyer's associated token account, created if missing
+    /// 8. `[]` Token program: SPL Token or Token-2022
+    /// 9. `[]` Associated token program
+    /// 10. `[]` System program
+    /// 11. `[]` NFT metadata account (Metaplex PDA), which may not exist
+    /// 12. `[]` Collection config account (PDA) for the NFT's verified
+    ///     collection. It overrides the marketplace fee and enforces royalties
+    ///     if it exists; if the NFT has no verified collection, any account.
+    /// 13. `[writable]` Marketplace stats account (PDA)
+    /// 14. `[]` Referral config account (PDA), which may not exist
+    /// 15. `[writable]` Referrer, paid the referral share of the fee; the fee
+    ///     recipient if there is none
+    ///
+    /// Remaining: if the collection config enforces royalties, the NFT's
+    ///    creators, `[writable]` and in metadata order. Then, for Token-2022
+    ///    mints with a transfer hook, the accounts the hook needs, including
+    ///    its program and validation account. For a `ListNftDelegated`
+    ///    listing, the NFT edition account and the token metadata program.
+    BuyNft {
+        /// Fails with `ExpectedAmountMismatch` unless the listing is at this
+        /// price, so a relisting can't raise the price under the buyer
+        expected_price: u64,
+    },
+
+    /// Cancel a listing, returning the escrowed NFT to the seller and the
+    /// listing and escrow rent with it
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Seller
+    /// 1. `[]` NFT mint
+    /// 2. `[writable]` Listing account (PDA)
+    /// 3. `[writable]` Listing escrow token account; for a `ListNftDelegated`
+    ///    listing, the seller's token account
+    /// 4. `[writable]` Seller's associated token account, created if missing;
+    ///    unused for a `ListNftDelegated` listing
+    /// 5. `[]` Token program: SPL Token or Token-2022
+    /// 6. `[]` Associated token program
+    /// 7. `[]` System program
+    ///
+    /// Remaining: for Token-2022 mints with a transfer hook, the accounts the
+    ///    hook needs, including its program and validation account. For a
+    ///    `ListNftDelegated` listing, the NFT edition account and the token
+    ///    metadata program.
+    CancelListing,
+
+    /// Offer lamports for an NFT, whether or not it is listed. The amount is
+    /// held in the offer PDA until the offer is accepted or cancelled.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Bidder
+    /// 1. `[]` Marketplace account
+    /// 2. `[]` NFT mint
+    /// 3. `[writable]` Offer account (PDA) to create
+    /// 4. `[]` System program
+    /// 5. `[]` Rent sysvar
+    MakeOffer {
+        amount: u64,     // Offered lamports
+        expires_at: i64, // Unix timestamp; 0 for no expiry
+    },
+
+    /// Accept an offer, selling the NFT to the bidder. The seller receives the
+    /// amount minus the marketplace fee and any royalties, and the bidder the
+    /// offer's rent.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Seller (NFT owner)
+    /// 1. `[writable]` Bidder
+    /// 2. `[]` Marketplace account
+    /// 3. `[writable]` Marketplace fee recipient
+    /// 4. `[]` NFT mint
+    /// 5. `[writable]` Offer account (PDA)
+    /// 6. `[writable]` Seller's token account
+    /// 7. `[writable]` Bidder's associated token account, created if missing
+    /// 8. `[]` Token program: SPL Token or Token-2022
+    /// 9. `[]` Associated token program
+    /// 10. `[]` System program
+    /// 11. `[]` NFT metadata account (Metaplex PDA), which may not exist
+    /// 12. `[]` Collection config account (PDA) for the NFT's verified
+    ///     collection, as for `BuyNft`
+    ///
+    /// Remaining: if the collection config enforces royalties, the NFT's
+    ///    creators, `[writable]` and in metadata order. Then, for Token-2022
+    ///    mints with a transfer hook, the accounts the hook needs, including
+    ///    its program and validation account.
+    AcceptOffer {
+        /// Fails with `ExpectedAmountMismatch` unless the offer is for this
+        /// amount, so a replaced offer can't lower it under the seller
+        expected_amount: u64,
+    },
+
+    /// Cancel an offer, refunding the offered lamports and the offer's rent
+    /// to the bidder
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Bidder
+    /// 1. `[]` NFT mint
+    /// 2. `[writable]` Offer account (PDA)
+    CancelOffer,
+
+    /// Start an English auction, moving the NFT into an escrow token account
+    /// owned by the auction PDA
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Seller
+    /// 1. `[]` Marketplace account
+    /// 2. `[]` NFT mint
+    /// 3. `[writable]` Seller's token account
+    /// 4. `[writable]` Auction account (PDA) to create
+    /// 5. `[writable]` Auction escrow token account to create
+    /// 6. `[]` Token program: SPL Token or Token-2022
+    /// 7. `[]` Associated token program
+    /// 8. `[]` System program
+    /// 9. `[]` Rent sysvar
+    ///
+    /// Remaining: for Token-2022 mints with a transfer hook, the accounts the
+    ///    hook needs, including its program and validation account
+    CreateAuction {
+        reserve_price: u64, // Lowest acceptable bid in lamports
+        end_time: i64,      // Unix timestamp bidding closes at
+    },
+
+    /// Bid on an auction. The bid is held in the auction account and the
+    /// bid it beats is refunded.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Bidder
+    /// 1. `[writable]` Auction account (PDA)
+    /// 2. `[writable]` Current highest bidder, refunded; any account if
+    ///    there are no bids yet
+    /// 3. `[]` System program
+    PlaceBid { amount: u64 },
+
+    /// Settle an auction once it has ended; anyone may call it. The winner
+    /// receives the NFT, the seller the highest bid minus the marketplace fee
+    /// and any royalties, and the fee recipient the fee. Without bids the NFT
+    /// returns to the seller. The escrow and auction accounts are closed with
+    /// their rent returned to the seller.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Payer for the recipient's token account
+    /// 1. `[writable]` Seller
+    /// 2. `[]` Marketplace account
+    /// 3. `[writable]` Marketplace fee recipient
+    /// 4. `[]` NFT mint
+    /// 5. `[writable]` Auction account (PDA)
+    /// 6. `[writable]` Auction escrow token account
+    /// 7. `[]` Recipient: the highest bidder, or the seller without bids
+    /// 8. `[writable]` Recipient's associated token account, created if missing
+    /// 9. `[]` Token program: SPL Token or Token-2022
+    /// 10. `[]` Associated token program
+    /// 11. `[]` System program
+    /// 12. `[]` NFT metadata account (Metaplex PDA), which may not exist
+    /// 13. `[]` Collection config account (PDA) for the NFT's verified
+    ///     collection, as for `BuyNft`
+    ///
+    /// Remaining: if the collection config enforces royalties and there were
+    ///    bids, the NFT's creators, `[writable]` and in metadata order. Then,
+    ///    for Token-2022 mints with a transfer hook, the accounts the hook
+    ///    needs, including its program and validation account.
+    SettleAuction,
+
+    /// Offer lamports for any NFT verified into a Metaplex collection. The
+    /// amount is held in the collection offer PDA until the offer is accepted
+    /// or cancelled.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Bidder
+    /// 1. `[]` Marketplace account
+    /// 2. `[]` Collection mint
+    /// 3. `[writable]` Collection offer account (PDA) to create
+    /// 4. `[]` System program
+    /// 5. `[]` Rent sysvar
+    MakeCollectionOffer {
+        amount: u64,     // Offered lamports
+        expires_at: i64, // Unix timestamp; 0 for no expiry
+    },
+
+    /// Accept a collection offer with an NFT verified into the collection,
+    /// selling it to the bidder. Paid out like `AcceptOffer`, at the
+    /// collection config's fee and royalties if the collection has one.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Seller (NFT owner)
+    /// 1. `[writable]` Bidder
+    /// 2. `[]` Marketplace account
+    /// 3. `[writable]` Marketplace fee recipient
+    /// 4. `[]` NFT mint
+    /// 5. `[]` NFT metadata account (Metaplex)
+    /// 6. `[writable]` Collection offer account (PDA)
+    /// 7. `[writable]` Seller's token account
+    /// 8. `[writable]` Bidder's associated token account, created if missing
+    /// 9. `[]` Token program: SPL Token or Token-2022
+    /// 10. `[]` Associated token program
+    /// 11. `[]` System program
+    /// 12. `[]` Collection config account (PDA) for the offer's collection,
+    ///     which may not exist
+    ///
+    /// Remaining: if the collection config enforces royalties, the NFT's
+    ///    creators, `[writable]` and in metadata order. Then, for Token-2022
+    ///    mints with a transfer hook, the accounts the hook needs, including
+    ///    its program and validation account.
+    AcceptCollectionOffer {
+        /// Fails with `ExpectedAmountMismatch` unless the offer is for this
+        /// amount
+        expected_amount: u64,
+    },
+
+    /// Cancel a collection offer, refunding the offered lamports and the
+    /// offer's rent to the bidder
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Bidder
+    /// 1. `[]` Collection mint
+    /// 2. `[writable]` Collection offer account (PDA)
+    CancelCollectionOffer,
+
+    /// Mint a compressed NFT into an existing Bubblegum tree, owned by the
+    /// minter
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Minter, paying and owning the NFT
+    /// 1. `[signer]` Tree delegate: the tree's creator or delegate, or any
+    ///    signer if the tree is public
+    /// 2. `[writable]` Tree config account (Bubblegum PDA of the tree)
+    /// 3. `[writable]` Merkle tree
+    /// 4. `[]` SPL Noop program
+    /// 5. `[]` Account compression program
+    /// 6. `[]` Bubblegum program
+    /// 7. `[]` System program
+    MintCompressedNft {
+        name: String,
+        symbol: String,
+        uri: String,
+    },
+
+    /// Change an NFT's name and/or URI through the metadata program, keeping
+    /// its other metadata. Names must be non-empty and at most
+    /// `metadata::MAX_NAME_LENGTH` bytes, URIs at most `metadata::MAX_URI_LENGTH`
+    /// bytes.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The metadata's update authority
+    /// 1. `[]` NFT mint
+    /// 2. `[writable]` NFT metadata account
+    /// 3. `[]` Token metadata program
+    UpdateNftMetadata {
+        name: Option<String>,
+        uri: Option<String>,
+    },
+
+    /// Mint an NFT like `MintNft`, but with a master edition that can print up
+    /// to `max_supply` numbered editions, or any number if `None`. Takes the
+    /// same accounts as `MintNft`, including the optional collection.
+    MintMasterEdition {
+        name: String,
+        symbol: String,
+        uri: String,
+        max_supply: Option<u64>,
+    },
+
+    /// Print edition number `edition` of a master edition into a new mint
+    /// owned by the master edition's holder. Editions start at 1 and can't
+    /// exceed the master edition's max supply.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Holder of the master edition token, paying and
+    ///    owning the print
+    /// 1. `[writable, signer]` New mint account to create
+    /// 2. `[writable]` Holder's associated token account for the new mint, to
+    ///    create
+    /// 3. `[writable]` New metadata account (Metaplex PDA) to create
+    /// 4. `[writable]` New edition account (Metaplex PDA) to create
+    /// 5. `[]` Master mint
+    /// 6. `[writable]` Master edition account
+    /// 7. `[]` Master metadata account
+    /// 8. `[]` Holder's token account holding the master edition token
+    /// 9. `[writable]` Edition marker account (Metaplex PDA) for `edition`
+    /// 10. `[]` Master metadata's update authority
+    /// 11. `[]` Token program
+    /// 12. `[]` Associated token program
+    /// 13. `[]` System program
+    /// 14. `[]` Rent sysvar
+    /// 15. `[]` Token metadata program
+    PrintEdition { edition: u64 },
+
+    /// Change where marketplace fees are paid
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Marketplace authority
+    /// 1. `[writable]` Marketplace account
+    /// 2. `[]` New fee recipient
+    SetFeeRecipient,
+
+    /// Propose a new marketplace authority, which takes over once it accepts.
+    /// Proposing again replaces the pending proposal.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Marketplace authority, paying the transfer
+    ///    account's rent
+    /// 1. `[]` Marketplace account
+    /// 2. `[]` Proposed new authority
+    /// 3. `[writable]` Authority transfer account (PDA)
+    /// 4. `[]` System program
+    /// 5. `[]` Rent sysvar
+    ProposeMarketplaceAuthority,
+
+    /// Accept a pending authority transfer, becoming the marketplace
+    /// authority. The marketplace account keeps its address.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Proposed new authority
+    /// 1. `[writable]` Marketplace account
+    /// 2. `[writable]` Authority transfer account (PDA)
+    /// 3. `[writable]` Current marketplace authority, refunded the transfer
+    ///    account's rent
+    AcceptMarketplaceAuthority,
+
+    /// Cancel a pending authority transfer, refunding its rent
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Marketplace authority
+    /// 1. `[]` Marketplace account
+    /// 2. `[writable]` Authority transfer account (PDA)
+    CancelMarketplaceAuthorityTransfer,
+
+    /// Create or update the config of the NFTs verified into a collection
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Marketplace authority, paying the config's rent
+    /// 1. `[]` Marketplace account
+    /// 2. `[]` Collection mint
+    /// 3. `[writable]` Collection config account (PDA)
+    /// 4. `[]` System program
+    /// 5. `[]` Rent sysvar
+    SetCollectionConfig {
+        /// Fee in basis points for this collection's sales, or `None` for the
+        /// marketplace fee
+        fee_percentage: Option<u16>,
+        /// Pay the NFT's creators their metadata royalties from each sale,
+        /// out of the seller's proceeds
+        enforce_royalties: bool,
+    },
+
+    /// Create the stats account of a marketplace initialized before the
+    /// program kept stats. Its totals start from zero.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Payer of the stats account's rent
+    /// 1. `[]` Marketplace account
+    /// 2. `[writable]` Marketplace stats account (PDA) to initialize
+    /// 3. `[]` System program
+    /// 4. `[]` Rent sysvar
+    InitializeMarketplaceStats,
+
+    /// Create or update the share of the marketplace fee paid to referrers
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Marketplace authority, paying the config's rent
+    /// 1. `[]` Marketplace account
+    /// 2. `[writable]` Referral config account (PDA)
+    /// 3. `[]` System program
+    /// 4. `[]` Rent sysvar
+    SetReferralShare {
+        referral_share: u16, // Basis points of the marketplace fee (max 10000)
+    },
+
+    /// List from `MIN_BUNDLE_SIZE` to `MAX_BUNDLE_SIZE` NFTs for sale
+    /// together at one price, moving each into an escrow token account owned
+    /// by the bundle PDA. All the NFTs must be under the same token program;
+    /// mints with a transfer hook can't be bundled.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Seller
+    /// 1. `[]` Marketplace account
+    /// 2. `[writable]` Bundle account (PDA) to create
+    /// 3. `[]` Token program: SPL Token or Token-2022
+    /// 4. `[]` Associated token program
+    /// 5. `[]` System program
+    /// 6. `[]` Rent sysvar
+    /// 7. `[writable]` Marketplace stats account (PDA)
+    ///
+    /// Remaining: for each NFT, in order:
+    ///    0. `[]` NFT mint
+    ///    1. `[writable]` Bundle escrow token account to create
+    ///    2. `[writable]` Seller's token account
+    ListBundle {
+        bundle_id: u64,
+        price: u64, // Price in lamports for the whole bundle
+    },
+
+    /// Buy a bundle. Like `BuyNft`, the seller receives the price minus the
+    /// marketplace fee and gets the bundle and escrow rent back. The price
+    /// isn't split between the NFTs, so the sale fails with `InvalidBundle`
+    /// if any NFT's collection config sets a fee override or enforces
+    /// royalties.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Buyer
+    /// 1. `[writable]` Seller
+    /// 2. `[]` Marketplace account
+    /// 3. `[writable]` Marketplace fee recipient
+    /// 4. `[writable]` Bundle account (PDA)
+    /// 5. `[]` Token program: SPL Token or Token-2022
+    /// 6. `[]` Associated token program
+    /// 7. `[]` System program
+    /// 8. `[writable]` Marketplace stats account (PDA)
+    ///
+    /// Remaining: for each NFT, in the bundle's order:
+    ///    0. `[]` NFT mint
+    ///    1. `[writable]` Bundle escrow token account
+    ///    2. `[writable]` Buyer's associated token account, created if missing
+    ///    3. `[]` NFT metadata account (Metaplex PDA), which may not exist
+    ///    4. `[]` Collection config account (PDA) for the NFT's verified
+    ///       collection, as for `BuyNft`
+    BuyBundle {
+        expected_price: u64, // The bundle price the buyer agreed to pay
+    },
+
+    /// Cancel a bundle, returning its NFTs to the seller and the bundle and
+    /// escrow rent with them
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Seller
+    /// 1. `[writable]` Bundle account (PDA)
+    /// 2. `[]` Token program: SPL Token or Token-2022
+    /// 3. `[]` Associated token program
+    /// 4. `[]` System program
+    ///
+    /// Remaining: for each NFT, in the bundle's order:
+    ///    0. `[]` NFT mint
+    ///    1. `[writable]` Bundle escrow token account
+    ///    2. `[writable]` Seller's associated token account, created if
+    ///       missing
+    CancelBundle,
+
+    /// List an NFT like `ListNft`, as a private sale only `allowed_buyer` can
+    /// buy. Takes the same accounts as `ListNft`.
+    ListNftPrivate { price: u64, allowed_buyer: Address },
+
+    /// List an NFT without escrow: it stays in the seller's token account,
+    /// which approves the listing PDA as delegate and is frozen through the
+    /// NFT's edition. `BuyNft` and `CancelListing` thaw it again; pass the
+    /// seller's token account as their escrow token account and the NFT
+    /// edition and token metadata program as their remaining accounts. The
+    /// NFT must have a master or print edition, which holds its freeze
+    /// authority.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Seller
+    /// 1. `[]` Marketplace account
+    /// 2. `[]` NFT mint
+    /// 3. `[writable]` Seller's token account
+    /// 4. `[writable]` Listing account (PDA) to create
+    /// 5. `[]` NFT edition account (Metaplex PDA)
+    /// 6. `[]` Token program: SPL Token only
+    /// 7. `[]` Token metadata program
+    /// 8. `[]` System program
+    /// 9. `[]` Rent sysvar
+    /// 10. `[writable]` Marketplace stats account (PDA)
+    ListNftDelegated {
+        price: u64, // Price in lamports
+    },
+
+    /// Create or update the staking campaign of a collection, signed by the
+    /// collection's update authority. Staked NFTs earn `reward_rate` base
+    /// units of the reward mint per day, paid from the reward vault, which
+    /// anyone can fund by sending it reward tokens. The reward mint can't
+    /// change once the config exists. A new rate applies from when it's set;
+    /// rewards earned before it keep the old rate. Reward mints with a
+    /// transfer hook aren't supported.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Collection update authority
+    /// 1. `[]` Collection mint
+    /// 2. `[]` Collection metadata account (Metaplex PDA)
+    /// 3. `[writable]` Staking config account (PDA), created if missing
+    /// 4. `[]` Reward mint
+    /// 5. `[writable]` Reward vault: the staking config's associated token
+    ///    account for the reward mint, created if missing
+    /// 6. `[]` Reward token program: SPL Token or Token-2022
+    /// 7. `[]` Associated token program
+    /// 8. `[]` System program
+    /// 9. `[]` Rent sysvar
+    SetStakingConfig {
+        reward_rate: u64, // Reward base units per staked NFT per day
+    },
+
+    /// Stake an NFT verified into the staking config's collection, moving it
+    /// into an escrow token account owned by the stake PDA. Rewards accrue
+    /// from now.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Owner
+    /// 1. `[]` Staking config account (PDA)
+    /// 2. `[]` NFT mint
+    /// 3. `[]` NFT metadata account (Metaplex PDA)
+    /// 4. `[writable]` Owner's token account
+    /// 5. `[writable]` Stake account (PDA) to create
+    /// 6. `[writable]` Stake escrow token account to create
+    /// 7. `[]` Token program: SPL Token or Token-2022
+    /// 8. `[]` Associated token program
+    /// 9. `[]` System program
+    /// 10. `[]` Rent sysvar
+    ///
+    /// Remaining: for Token-2022 mints with a transfer hook, the accounts the
+    ///    hook needs, including its program and validation account
+    StakeNft,
+
+    /// Return a staked NFT to its owner, paying out the rewards due. Fails
+    /// with `InsufficientRewards` if the reward vault can't cover them, so
+    /// rewards are never forfeited; the NFT can be unstaked once the vault is
+    /// funded. The escrow and stake accounts are closed with their rent
+    /// returned to the owner.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Owner
+    /// 1. `[]` Staking config account (PDA)
+    /// 2. `[]` NFT mint
+    /// 3. `[writable]` Stake account (PDA)
+    /// 4. `[writable]` Stake escrow token account
+    /// 5. `[writable]` Owner's associated token account, created if missing
+    /// 6. `[]` Token program: SPL Token or Token-2022
+    /// 7. `[]` Reward mint
+    /// 8. `[writable]` Reward vault
+    /// 9. `[writable]` Owner's associated token account for the reward mint,
+    ///    created if missing
+    /// 10. `[]` Reward token program
+    /// 11. `[]` Associated token program
+    /// 12. `[]` System program
+    ///
+    /// Remaining: for Token-2022 mints with a transfer hook, the accounts the
+    ///    hook needs, including its program and validation account
+    UnstakeNft,
+
+    /// Pay a staked NFT's owner the rewards earned since the last claim.
+    /// Fails with `InsufficientRewards` if the reward vault can't cover them.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Owner
+    /// 1. `[]` Staking config account (PDA)
+    /// 2. `[]` NFT mint
+    /// 3. `[writable]` Stake account (PDA)
+    /// 4. `[]` Reward mint
+    /// 5. `[writable]` Reward vault
+    /// 6. `[writable]` Owner's associated token account for the reward mint,
+    ///    created if missing
+    /// 7. `[]` Reward token program
+    /// 8. `[]` Associated token program
+    /// 9. `[]` System program
+    ClaimRewards,
+
+    /// Offer an NFT for rent by the day, moving it into an escrow token
+    /// account owned by the rental PDA. SPL Token NFTs with a master or print
+    /// edition only, which rentals freeze through.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Owner
+    /// 1. `[]` Marketplace account
+    /// 2. `[]` NFT mint
+    /// 3. `[writable]` Owner's token account
+    /// 4. `[writable]` Rental account (PDA) to create
+    /// 5. `[writable]` Rental escrow token account to create
+    /// 6. `[]` Token program: SPL Token only
+    /// 7. `[]` Associated token program
+    /// 8. `[]` System program
+    /// 9. `[]` Rent sysvar
+    ListNftForRent {
+        daily_rate: u64, // Rent in lamports per day
+        max_days: u16,   // Longest rental accepted
+    },
+
+    /// Rent an NFT for `days` days, paying the rent upfront into the rental
+    /// account. The NFT moves to the renter's token account, which approves
+    /// the rental PDA as delegate and is frozen until the NFT is reclaimed.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Renter
+    /// 1. `[writable]` Rental account (PDA)
+    /// 2. `[]` NFT mint
+    /// 3. `[writable]` Rental escrow token account
+    /// 4. `[writable]` Renter's associated token account, created if missing
+    /// 5. `[]` NFT edition account (Metaplex PDA)
+    /// 6. `[]` Token program: SPL Token only
+    /// 7. `[]` Associated token program
+    /// 8. `[]` Token metadata program
+    /// 9. `[]` System program
+    RentNft {
+        days: u16, // At most the rental's `max_days`
+        /// Fails with `ExpectedAmountMismatch` unless the rental is at this
+        /// daily rate
+        expected_daily_rate: u64,
+    },
+
+    /// Take back a rented NFT once the rental has expired; anyone can send it.
+    /// The NFT returns to escrow, available to rent again, and the owner is
+    /// paid the rent minus the marketplace fee.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` Owner
+    /// 1. `[]` Marketplace account
+    /// 2. `[writable]` Marketplace fee recipient
+    /// 3. `[writable]` Rental account (PDA)
+    /// 4. `[]` NFT mint
+    /// 5. `[writable]` Rental escrow token account
+    /// 6. `[writable]` Renter's token account
+    /// 7. `[]` Token program: SPL Token only
+    /// 8. `[]` NFT edition account (Metaplex PDA)
+    /// 9. `[]` Token metadata program
+    ReclaimRental,
+
+    /// Stop offering an NFT for rent while it isn't rented out, returning it
+    /// to the owner and the rental and escrow rent with it
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Owner
+    /// 1. `[]` NFT mint
+    /// 2. `[writable]` Rental account (PDA)
+    /// 3. `[writable]` Rental escrow token account
+    /// 4. `[writable]` Owner's associated token account, created if missing
+    /// 5. `[]` Token program: SPL Token only
+    /// 6. `[]` Associated token program
+    /// 7. `[]` System program
+    CancelRental,
+
+    /// Create a mint config for a drop of up to `max_supply` NFTs, minted
+    /// with `MintFromConfig` for `price` each. NFT `n` is named
+    /// `{name_prefix} #{n}` with URI `{base_uri}{n}.json`.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Creator
+    /// 1. `[writable]` Mint config account (PDA) to create
+    /// 2. `[]` Treasury receiving the mint price
+    /// 3. `[]` System program
+    /// 4. `[]` Rent sysvar
+    CreateMintConfig {
+        config_id: u64,  // Chosen by the creator to tell their drops apart
+        price: u64,      // Price in lamports per NFT
+        max_supply: u64, // Most NFTs the drop mints
+        go_live: i64,    // Unix timestamp minting opens at
+        /// Merkle root of the wallets allowed to mint and their allocations
+        /// (see `allowlist`); `None` for a public drop
+        allowlist_root: Option<[u8; 32]>,
+        wallet_limit: u16, // Most NFTs one wallet can mint; 0 for no limit
+        name_prefix: String,
+        symbol: String,
+        base_uri: String,
+    },
+
+    /// Mint the next NFT of a drop to the minter, paying the mint price to the
+    /// config's treasury. The NFT gets Metaplex metadata and a master edition
+    /// with the mint config as update authority.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Minter
+    /// 1. `[writable]` Mint config account (PDA)
+    /// 2. `[writable]` Treasury
+    /// 3. `[writable]` Minter's mint record account (PDA), created on their
+    ///    first mint from the config
+    /// 4. `[writable, signer]` Mint account to create
+    /// 5. `[writable]` Minter's associated token account to create
+    /// 6. `[writable]` Metadata account (Metaplex PDA) to create
+    /// 7. `[writable]` Master edition account (Metaplex PDA) to create
+    /// 8. `[]` Token program
+    /// 9. `[]` Associated token program
+    /// 10. `[]` Token metadata program
+    /// 11. `[]` System program
+    /// 12. `[]` Rent sysvar
+    MintFromConfig {
+        /// Fails with `ExpectedAmountMismatch` unless the config is at this
+        /// price
+        expected_price: u64,
+        /// The minter's allocation on the allowlist, which caps their mints
+        /// along with the wallet limit; ignored for a public drop
+        allocation: u64,
+        /// Sibling hashes from the minter's leaf up to the allowlist root;
+        /// empty for a public drop
+        allowlist_proof: Vec<[u8; 32]>,
+    },
+
+    /// Grow a marketplace created before versioning to the current layout,
+    /// with the authority paying the extra rent. Marketplaces already at the
+    /// current version are left as they are.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` Marketplace authority
+    /// 1. `[writable]` Marketplace account
+    /// 2. `[]` System program
+    MigrateMarketplace,
+}
+
+impl MarketplaceInstruction {
+    pub fn unpack(input: &[u8]) -> Result<Self, MarketplaceError> {
+        Self::try_from_slice(input).map_err(|_| MarketplaceError::InvalidInstruction)
+    }
+
+    pub fn pack(&self) -> Vec<u8> {
+        borsh::to_vec(self).expect("instructions serialize into a Vec")
+    }
+}
+
+/// An account passed to an instruction, mirroring Solana's `AccountMeta`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountMeta {
+    pub pubkey: Address,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl AccountMeta {
+    pub const fn new(pubkey: Address, is_signer: bool) -> Self {
+        Self {
+            pubkey,
+            is_signer,
+            is_writable: true,
+        }
+    }
+
+    pub const fn new_readonly(pubkey: Address, is_signer: bool) -> Self {
+        Self {
+            pubkey,
+            is_signer,
+            is_writable: false,
+        }
+    }
+}
+
+/// The accounts of one NFT in a bundle instruction. `token_account` is the
+/// seller's for `ListBundle` and `CancelBundle` and the buyer's for
+/// `BuyBundle`, which takes it in `BundlePurchaseItemAccounts`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BundleItemAccounts {
+    pub nft_mint: Address,
+    pub escrow_token_account: Address,
+    pub token_account: Address,
+}
+
+impl BundleItemAccounts {
+    fn account_metas(items: &[Self]) -> impl Iterator<Item = AccountMeta> + '_ {
+        items.iter().flat_map(|item| {
+            [
+                AccountMeta::new_readonly(item.nft_mint, false),
+                AccountMeta::new(item.escrow_token_account, false),
+                AccountMeta::new(item.token_account, false),
+            ]
+        })
+    }
+}
+
+/// The accounts of one NFT in `BuyBundle`: its bundle accounts, with
+/// `token_account` the buyer's, then the accounts its collection config is
+/// checked with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BundlePurchaseItemAccounts {
+    pub item: BundleItemAccounts,
+    pub nft_metadata: Address,
+    pub collection_config_account: Address,
+}
+
+impl BundlePurchaseItemAccounts {
+    fn account_metas(items: &[Self]) -> impl Iterator<Item = AccountMeta> + '_ {
+        items.iter().flat_map(|purchase_item| {
+            let item = &purchase_item.item;
+            [
+                AccountMeta::new_readonly(item.nft_mint, false),
+                AccountMeta::new(item.escrow_token_account, false),
+                AccountMeta::new(item.token_account, false),
+                AccountMeta::new_readonly(purchase_item.nft_metadata, false),
+                AccountMeta::new_readonly(purchase_item.collection_config_account, false),
+            ]
+        })
+    }
+}
+
+/// The creators a sale pays royalties to, for instructions that take them
+/// after their own accounts
+fn royalty_creator_metas(royalty_creators: &[Address]) -> impl Iterator<Item = AccountMeta> + '_ {
+    royalty_creators
+        .iter()
+        .map(|creator| AccountMeta::new(*creator, false))
+}
+
+/// An instruction ready to be converted into the caller's SDK `Instruction`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub program_id: Address,
+    pub accounts: Vec<AccountMeta>,
+    pub data: Vec<u8>,
+}
+
+/// Create an initialize marketplace instruction
+pub fn initialize_marketplace(
+    program_id: &Address,
+    marketplace_authority: &Address,
+    marketplace_account: &Address,
+    marketplace_stats_account: &Address,
+    fee_percentage: u16,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*marketplace_authority, true),
+        AccountMeta::new(*marketplace_account, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+        AccountMeta::new(*marketplace_stats_account, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::InitializeMarketplace { fee_percentage }.pack(),
+    }
+}
+
+/// Create an update marketplace fee instruction
+pub fn update_marketplace_fee(
+    program_id: &Address,
+    marketplace_authority: &Address,
+    marketplace_account: &Address,
+    new_fee_percentage: u16,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*marketplace_authority, true),
+        AccountMeta::new(*marketplace_account, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::UpdateMarketplaceFee { new_fee_percentage }.pack(),
+    }
+}
+
+/// Create a mint NFT instruction. The new mint account signs, since the
+/// program creates it with `create_account`.
+#[allow(clippy::too_many_arguments)]
+pub fn mint_nft(
+    program_id: &Address,
+    mint_authority: &Address,
+    mint_account: &Address,
+    associated_token_account: &Address,
+    metadata_account: &Address,
+    master_edition_account: &Address,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*mint_authority, true),
+        AccountMeta::new(*mint_account, true),
+        AccountMeta::new(*associated_token_account, false),
+        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+        AccountMeta::new(*metadata_account, false),
+        AccountMeta::new(*master_edition_account, false),
+        AccountMeta::new_readonly(TOKEN_METADATA_PROGRAM_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::MintNft { name, symbol, uri }.pack(),
+    }
+}
+
+/// Create a mint NFT instruction that also sets and verifies the NFT into
+/// the collection of `collection_mint`, signed by the collection's update
+/// authority
+#[allow(clippy::too_many_arguments)]
+pub fn mint_nft_with_collection(
+    program_id: &Address,
+    mint_authority: &Address,
+    mint_account: &Address,
+    associated_token_account: &Address,
+    metadata_account: &Address,
+    master_edition_account: &Address,
+    collection_mint: &Address,
+    collection_metadata_account: &Address,
+    collection_master_edition_account: &Address,
+    collection_authority: &Address,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Instruction {
+    let mut instruction = mint_nft(
+        program_id,
+        mint_authority,
+        mint_account,
+        associated_token_account,
+        metadata_account,
+        master_edition_account,
+        name,
+        symbol,
+        uri,
+    );
+    instruction.accounts.extend([
+        AccountMeta::new_readonly(*collection_mint, false),
+        AccountMeta::new(*collection_metadata_account, false),
+        AccountMeta::new_readonly(*collection_master_edition_account, false),
+        AccountMeta::new(*collection_authority, true),
+    ]);
+    instruction
+}
+
+/// Create a list NFT instruction
+#[allow(clippy::too_many_arguments)]
+pub fn list_nft(
+    program_id: &Address,
+    seller: &Address,
+    marketplace_account: &Address,
+    nft_mint: &Address,
+    seller_token_account: &Address,
+    listing_account: &Address,
+    escrow_token_account: &Address,
+    token_program: &Address,
+    marketplace_stats_account: &Address,
+    price: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*seller, true),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*seller_token_account, false),
+        AccountMeta::new(*listing_account, false),
+        AccountMeta::new(*escrow_token_account, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+        AccountMeta::new(*marketplace_stats_account, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::ListNft { price }.pack(),
+    }
+}
+
+/// Create a list NFT instruction for a private sale to `allowed_buyer`
+#[allow(clippy::too_many_arguments)]
+pub fn list_nft_private(
+    program_id: &Address,
+    seller: &Address,
+    marketplace_account: &Address,
+    nft_mint: &Address,
+    seller_token_account: &Address,
+    listing_account: &Address,
+    escrow_token_account: &Address,
+    token_program: &Address,
+    marketplace_stats_account: &Address,
+    price: u64,
+    allowed_buyer: &Address,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*seller, true),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*seller_token_account, false),
+        AccountMeta::new(*listing_account, false),
+        AccountMeta::new(*escrow_token_account, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+        AccountMeta::new(*marketplace_stats_account, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::ListNftPrivate {
+            price,
+            allowed_buyer: *allowed_buyer,
+        }
+        .pack(),
+    }
+}
+
+/// Create a buy NFT instruction
+#[allow(clippy::too_many_arguments)]
+pub fn buy_nft(
+    program_id: &Address,
+    buyer: &Address,
+    seller: &Address,
+    marketplace_account: &Address,
+    fee_recipient: &Address,
+    nft_mint: &Address,
+    listing_account: &Address,
+    escrow_token_account: &Address,
+    buyer_token_account: &Address,
+    token_program: &Address,
+    nft_metadata_account: &Address,
+    collection_config_account: &Address,
+    marketplace_stats_account: &Address,
+    referral_config_account: &Address,
+    referrer: &Address,
+    royalty_creators: &[Address],
+    expected_price: u64,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*buyer, true),
+        AccountMeta::new(*seller, false),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new(*fee_recipient, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*listing_account, false),
+        AccountMeta::new(*escrow_token_account, false),
+        AccountMeta::new(*buyer_token_account, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(*nft_metadata_account, false),
+        AccountMeta::new_readonly(*collection_config_account, false),
+        AccountMeta::new(*marketplace_stats_account, false),
+        AccountMeta::new_readonly(*referral_config_account, false),
+        AccountMeta::new(*referrer, false),
+    ];
+    accounts.extend(royalty_creator_metas(royalty_creators));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::BuyNft { expected_price }.pack(),
+    }
+}
+
+/// Create a cancel listing instruction
+pub fn cancel_listing(
+    program_id: &Address,
+    seller: &Address,
+    nft_mint: &Address,
+    listing_account: &Address,
+    escrow_token_account: &Address,
+    seller_token_account: &Address,
+    token_program: &Address,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*seller, true),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*listing_account, false),
+        AccountMeta::new(*escrow_token_account, false),
+        AccountMeta::new(*seller_token_account, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::CancelListing.pack(),
+    }
+}
+
+/// Create a list NFT instruction that leaves the NFT in the seller's token
+/// account, frozen with the listing as delegate
+#[allow(clippy::too_many_arguments)]
+pub fn list_nft_delegated(
+    program_id: &Address,
+    seller: &Address,
+    marketplace_account: &Address,
+    nft_mint: &Address,
+    seller_token_account: &Address,
+    listing_account: &Address,
+    nft_edition_account: &Address,
+    marketplace_stats_account: &Address,
+    price: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*seller, true),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*seller_token_account, false),
+        AccountMeta::new(*listing_account, false),
+        AccountMeta::new_readonly(*nft_edition_account, false),
+        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(TOKEN_METADATA_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+        AccountMeta::new(*marketplace_stats_account, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::ListNftDelegated { price }.pack(),
+    }
+}
+
+/// Create a buy NFT instruction for a `ListNftDelegated` listing
+#[allow(clippy::too_many_arguments)]
+pub fn buy_delegated_nft(
+    program_id: &Address,
+    buyer: &Address,
+    seller: &Address,
+    marketplace_account: &Address,
+    fee_recipient: &Address,
+    nft_mint: &Address,
+    listing_account: &Address,
+    seller_token_account: &Address,
+    buyer_token_account: &Address,
+    nft_metadata_account: &Address,
+    nft_edition_account: &Address,
+    collection_config_account: &Address,
+    marketplace_stats_account: &Address,
+    referral_config_account: &Address,
+    referrer: &Address,
+    royalty_creators: &[Address],
+    expected_price: u64,
+) -> Instruction {
+    let mut instruction = buy_nft(
+        program_id,
+        buyer,
+        seller,
+        marketplace_account,
+        fee_recipient,
+        nft_mint,
+        listing_account,
+        seller_token_account,
+        buyer_token_account,
+        &TOKEN_PROGRAM_ID,
+        nft_metadata_account,
+        collection_config_account,
+        marketplace_stats_account,
+        referral_config_account,
+        referrer,
+        royalty_creators,
+        expected_price,
+    );
+    instruction.accounts.extend([
+        AccountMeta::new_readonly(*nft_edition_account, false),
+        AccountMeta::new_readonly(TOKEN_METADATA_PROGRAM_ID, false),
+    ]);
+    instruction
+}
+
+/// Create a cancel listing instruction for a `ListNftDelegated` listing
+pub fn cancel_delegated_listing(
+    program_id: &Address,
+    seller: &Address,
+    nft_mint: &Address,
+    listing_account: &Address,
+    seller_token_account: &Address,
+    nft_edition_account: &Address,
+) -> Instruction {
+    let mut instruction = cancel_listing(
+        program_id,
+        seller,
+        nft_mint,
+        listing_account,
+        seller_token_account,
+        seller_token_account,
+        &TOKEN_PROGRAM_ID,
+    );
+    instruction.accounts.extend([
+        AccountMeta::new_readonly(*nft_edition_account, false),
+        AccountMeta::new_readonly(TOKEN_METADATA_PROGRAM_ID, false),
+    ]);
+    instruction
+}
+
+/// Create a set staking config instruction
+#[allow(clippy::too_many_arguments)]
+pub fn set_staking_config(
+    program_id: &Address,
+    authority: &Address,
+    collection_mint: &Address,
+    collection_metadata_account: &Address,
+    staking_config_account: &Address,
+    reward_mint: &Address,
+    reward_vault: &Address,
+    reward_token_program: &Address,
+    reward_rate: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(*collection_mint, false),
+        AccountMeta::new_readonly(*collection_metadata_account, false),
+        AccountMeta::new(*staking_config_account, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new(*reward_vault, false),
+        AccountMeta::new_readonly(*reward_token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::SetStakingConfig { reward_rate }.pack(),
+    }
+}
+
+/// Create a stake NFT instruction
+#[allow(clippy::too_many_arguments)]
+pub fn stake_nft(
+    program_id: &Address,
+    owner: &Address,
+    staking_config_account: &Address,
+    nft_mint: &Address,
+    nft_metadata_account: &Address,
+    owner_token_account: &Address,
+    stake_account: &Address,
+    escrow_token_account: &Address,
+    token_program: &Address,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(*staking_config_account, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new_readonly(*nft_metadata_account, false),
+        AccountMeta::new(*owner_token_account, false),
+        AccountMeta::new(*stake_account, false),
+        AccountMeta::new(*escrow_token_account, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::StakeNft.pack(),
+    }
+}
+
+/// Create an unstake NFT instruction
+#[allow(clippy::too_many_arguments)]
+pub fn unstake_nft(
+    program_id: &Address,
+    owner: &Address,
+    staking_config_account: &Address,
+    nft_mint: &Address,
+    stake_account: &Address,
+    escrow_token_account: &Address,
+    owner_token_account: &Address,
+    token_program: &Address,
+    reward_mint: &Address,
+    reward_vault: &Address,
+    owner_reward_token_account: &Address,
+    reward_token_program: &Address,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(*staking_config_account, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*stake_account, false),
+        AccountMeta::new(*escrow_token_account, false),
+        AccountMeta::new(*owner_token_account, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new(*reward_vault, false),
+        AccountMeta::new(*owner_reward_token_account, false),
+        AccountMeta::new_readonly(*reward_token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::UnstakeNft.pack(),
+    }
+}
+
+/// Create a claim rewards instruction
+#[allow(clippy::too_many_arguments)]
+pub fn claim_rewards(
+    program_id: &Address,
+    owner: &Address,
+    staking_config_account: &Address,
+    nft_mint: &Address,
+    stake_account: &Address,
+    reward_mint: &Address,
+    reward_vault: &Address,
+    owner_reward_token_account: &Address,
+    reward_token_program: &Address,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(*staking_config_account, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*stake_account, false),
+        AccountMeta::new_readonly(*reward_mint, false),
+        AccountMeta::new(*reward_vault, false),
+        AccountMeta::new(*owner_reward_token_account, false),
+        AccountMeta::new_readonly(*reward_token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::ClaimRewards.pack(),
+    }
+}
+
+/// Create a list NFT for rent instruction
+#[allow(clippy::too_many_arguments)]
+pub fn list_nft_for_rent(
+    program_id: &Address,
+    owner: &Address,
+    marketplace_account: &Address,
+    nft_mint: &Address,
+    owner_token_account: &Address,
+    rental_account: &Address,
+    escrow_token_account: &Address,
+    daily_rate: u64,
+    max_days: u16,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*owner_token_account, false),
+        AccountMeta::new(*rental_account, false),
+        AccountMeta::new(*escrow_token_account, false),
+        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::ListNftForRent {
+            daily_rate,
+            max_days,
+        }
+        .pack(),
+    }
+}
+
+/// Create a rent NFT instruction
+#[allow(clippy::too_many_arguments)]
+pub fn rent_nft(
+    program_id: &Address,
+    renter: &Address,
+    rental_account: &Address,
+    nft_mint: &Address,
+    escrow_token_account: &Address,
+    renter_token_account: &Address,
+    nft_edition_account: &Address,
+    days: u16,
+    expected_daily_rate: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*renter, true),
+        AccountMeta::new(*rental_account, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*escrow_token_account, false),
+        AccountMeta::new(*renter_token_account, false),
+        AccountMeta::new_readonly(*nft_edition_account, false),
+        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(TOKEN_METADATA_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::RentNft {
+            days,
+            expected_daily_rate,
+        }
+        .pack(),
+    }
+}
+
+/// Create a reclaim rental instruction
+#[allow(clippy::too_many_arguments)]
+pub fn reclaim_rental(
+    program_id: &Address,
+    owner: &Address,
+    marketplace_account: &Address,
+    fee_recipient: &Address,
+    rental_account: &Address,
+    nft_mint: &Address,
+    escrow_token_account: &Address,
+    renter_token_account: &Address,
+    nft_edition_account: &Address,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*owner, false),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new(*fee_recipient, false),
+        AccountMeta::new(*rental_account, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*escrow_token_account, false),
+        AccountMeta::new(*renter_token_account, false),
+        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(*nft_edition_account, false),
+        AccountMeta::new_readonly(TOKEN_METADATA_PROGRAM_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::ReclaimRental.pack(),
+    }
+}
+
+/// Create a cancel rental instruction
+pub fn cancel_rental(
+    program_id: &Address,
+    owner: &Address,
+    nft_mint: &Address,
+    rental_account: &Address,
+    escrow_token_account: &Address,
+    owner_token_account: &Address,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*rental_account, false),
+        AccountMeta::new(*escrow_token_account, false),
+        AccountMeta::new(*owner_token_account, false),
+        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::CancelRental.pack(),
+    }
+}
+
+/// Create a create mint config instruction
+#[allow(clippy::too_many_arguments)]
+pub fn create_mint_config(
+    program_id: &Address,
+    creator: &Address,
+    mint_config_account: &Address,
+    treasury: &Address,
+    config_id: u64,
+    price: u64,
+    max_supply: u64,
+    go_live: i64,
+    allowlist_root: Option<[u8; 32]>,
+    wallet_limit: u16,
+    name_prefix: String,
+    symbol: String,
+    base_uri: String,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*creator, true),
+        AccountMeta::new(*mint_config_account, false),
+        AccountMeta::new_readonly(*treasury, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::CreateMintConfig {
+            config_id,
+            price,
+            max_supply,
+            go_live,
+            allowlist_root,
+            wallet_limit,
+            name_prefix,
+            symbol,
+            base_uri,
+        }
+        .pack(),
+    }
+}
+
+/// Create a mint from config instruction
+#[allow(clippy::too_many_arguments)]
+pub fn mint_from_config(
+    program_id: &Address,
+    minter: &Address,
+    mint_config_account: &Address,
+    treasury: &Address,
+    mint_record_account: &Address,
+    mint: &Address,
+    minter_token_account: &Address,
+    metadata_account: &Address,
+    master_edition_account: &Address,
+    expected_price: u64,
+    allocation: u64,
+    allowlist_proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*minter, true),
+        AccountMeta::new(*mint_config_account, false),
+        AccountMeta::new(*treasury, false),
+        AccountMeta::new(*mint_record_account, false),
+        AccountMeta::new(*mint, true),
+        AccountMeta::new(*minter_token_account, false),
+        AccountMeta::new(*metadata_account, false),
+        AccountMeta::new(*master_edition_account, false),
+        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(TOKEN_METADATA_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::MintFromConfig {
+            expected_price,
+            allocation,
+            allowlist_proof,
+        }
+        .pack(),
+    }
+}
+
+/// Create a migrate marketplace instruction
+pub fn migrate_marketplace(
+    program_id: &Address,
+    authority: &Address,
+    marketplace_account: &Address,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*authority, true),
+        AccountMeta::new(*marketplace_account, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::MigrateMarketplace.pack(),
+    }
+}
+
+/// Create a make offer instruction
+pub fn make_offer(
+    program_id: &Address,
+    bidder: &Address,
+    marketplace_account: &Address,
+    nft_mint: &Address,
+    offer_account: &Address,
+    amount: u64,
+    expires_at: i64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*bidder, true),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*offer_account, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::MakeOffer { amount, expires_at }.pack(),
+    }
+}
+
+/// Create an accept offer instruction
+#[allow(clippy::too_many_arguments)]
+pub fn accept_offer(
+    program_id: &Address,
+    seller: &Address,
+    bidder: &Address,
+    marketplace_account: &Address,
+    fee_recipient: &Address,
+    nft_mint: &Address,
+    offer_account: &Address,
+    seller_token_account: &Address,
+    bidder_token_account: &Address,
+    token_program: &Address,
+    nft_metadata_account: &Address,
+    collection_config_account: &Address,
+    royalty_creators: &[Address],
+    expected_amount: u64,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*seller, true),
+        AccountMeta::new(*bidder, false),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new(*fee_recipient, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*offer_account, false),
+        AccountMeta::new(*seller_token_account, false),
+        AccountMeta::new(*bidder_token_account, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(*nft_metadata_account, false),
+        AccountMeta::new_readonly(*collection_config_account, false),
+    ];
+    accounts.extend(royalty_creator_metas(royalty_creators));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::AcceptOffer { expected_amount }.pack(),
+    }
+}
+
+/// Create a cancel offer instruction
+pub fn cancel_offer(
+    program_id: &Address,
+    bidder: &Address,
+    nft_mint: &Address,
+    offer_account: &Address,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*bidder, true),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*offer_account, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::CancelOffer.pack(),
+    }
+}
+
+/// Create a create auction instruction
+#[allow(clippy::too_many_arguments)]
+pub fn create_auction(
+    program_id: &Address,
+    seller: &Address,
+    marketplace_account: &Address,
+    nft_mint: &Address,
+    seller_token_account: &Address,
+    auction_account: &Address,
+    escrow_token_account: &Address,
+    token_program: &Address,
+    reserve_price: u64,
+    end_time: i64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*seller, true),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*seller_token_account, false),
+        AccountMeta::new(*auction_account, false),
+        AccountMeta::new(*escrow_token_account, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::CreateAuction {
+            reserve_price,
+            end_time,
+        }
+        .pack(),
+    }
+}
+
+/// Create a place bid instruction. `highest_bidder` is the auction's current
+/// highest bidder, `None` before the first bid.
+pub fn place_bid(
+    program_id: &Address,
+    bidder: &Address,
+    auction_account: &Address,
+    highest_bidder: Option<&Address>,
+    amount: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*bidder, true),
+        AccountMeta::new(*auction_account, false),
+        AccountMeta::new(*highest_bidder.unwrap_or(bidder), false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::PlaceBid { amount }.pack(),
+    }
+}
+
+/// Create a settle auction instruction. `recipient` is the highest bidder, or
+/// the seller if there were no bids.
+#[allow(clippy::too_many_arguments)]
+pub fn settle_auction(
+    program_id: &Address,
+    payer: &Address,
+    seller: &Address,
+    marketplace_account: &Address,
+    fee_recipient: &Address,
+    nft_mint: &Address,
+    auction_account: &Address,
+    escrow_token_account: &Address,
+    recipient: &Address,
+    recipient_token_account: &Address,
+    token_program: &Address,
+    nft_metadata_account: &Address,
+    collection_config_account: &Address,
+    royalty_creators: &[Address],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(*seller, false),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new(*fee_recipient, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*auction_account, false),
+        AccountMeta::new(*escrow_token_account, false),
+        AccountMeta::new_readonly(*recipient, false),
+        AccountMeta::new(*recipient_token_account, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(*nft_metadata_account, false),
+        AccountMeta::new_readonly(*collection_config_account, false),
+    ];
+    accounts.extend(royalty_creator_metas(royalty_creators));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::SettleAuction.pack(),
+    }
+}
+
+/// Create a make collection offer instruction
+pub fn make_collection_offer(
+    program_id: &Address,
+    bidder: &Address,
+    marketplace_account: &Address,
+    collection_mint: &Address,
+    collection_offer_account: &Address,
+    amount: u64,
+    expires_at: i64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*bidder, true),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new_readonly(*collection_mint, false),
+        AccountMeta::new(*collection_offer_account, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::MakeCollectionOffer { amount, expires_at }.pack(),
+    }
+}
+
+/// Create an accept collection offer instruction
+#[allow(clippy::too_many_arguments)]
+pub fn accept_collection_offer(
+    program_id: &Address,
+    seller: &Address,
+    bidder: &Address,
+    marketplace_account: &Address,
+    fee_recipient: &Address,
+    nft_mint: &Address,
+    nft_metadata: &Address,
+    collection_offer_account: &Address,
+    seller_token_account: &Address,
+    bidder_token_account: &Address,
+    token_program: &Address,
+    collection_config_account: &Address,
+    royalty_creators: &[Address],
+    expected_amount: u64,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*seller, true),
+        AccountMeta::new(*bidder, false),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new(*fee_recipient, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new_readonly(*nft_metadata, false),
+        AccountMeta::new(*collection_offer_account, false),
+        AccountMeta::new(*seller_token_account, false),
+        AccountMeta::new(*bidder_token_account, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(*collection_config_account, false),
+    ];
+    accounts.extend(royalty_creator_metas(royalty_creators));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::AcceptCollectionOffer { expected_amount }.pack(),
+    }
+}
+
+/// Create a cancel collection offer instruction
+pub fn cancel_collection_offer(
+    program_id: &Address,
+    bidder: &Address,
+    collection_mint: &Address,
+    collection_offer_account: &Address,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*bidder, true),
+        AccountMeta::new_readonly(*collection_mint, false),
+        AccountMeta::new(*collection_offer_account, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::CancelCollectionOffer.pack(),
+    }
+}
+
+/// Create a mint compressed NFT instruction. `log_wrapper` is the SPL Noop
+/// program.
+#[allow(clippy::too_many_arguments)]
+pub fn mint_compressed_nft(
+    program_id: &Address,
+    minter: &Address,
+    tree_delegate: &Address,
+    tree_config: &Address,
+    merkle_tree: &Address,
+    log_wrapper: &Address,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*minter, true),
+        AccountMeta::new_readonly(*tree_delegate, true),
+        AccountMeta::new(*tree_config, false),
+        AccountMeta::new(*merkle_tree, false),
+        AccountMeta::new_readonly(*log_wrapper, false),
+        AccountMeta::new_readonly(ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+        AccountMeta::new_readonly(BUBBLEGUM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::MintCompressedNft { name, symbol, uri }.pack(),
+    }
+}
+
+/// Create an update NFT metadata instruction; `None` leaves a field unchanged
+pub fn update_nft_metadata(
+    program_id: &Address,
+    update_authority: &Address,
+    nft_mint: &Address,
+    metadata_account: &Address,
+    name: Option<String>,
+    uri: Option<String>,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*update_authority, true),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*metadata_account, false),
+        AccountMeta::new_readonly(TOKEN_METADATA_PROGRAM_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::UpdateNftMetadata { name, uri }.pack(),
+    }
+}
+
+/// Create a mint master edition instruction: `mint_nft` with a master edition
+/// that can print up to `max_supply` editions, or unlimited if `None`
+#[allow(clippy::too_many_arguments)]
+pub fn mint_master_edition(
+    program_id: &Address,
+    mint_authority: &Address,
+    mint_account: &Address,
+    associated_token_account: &Address,
+    metadata_account: &Address,
+    master_edition_account: &Address,
+    name: String,
+    symbol: String,
+    uri: String,
+    max_supply: Option<u64>,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*mint_authority, true),
+        AccountMeta::new(*mint_account, true),
+        AccountMeta::new(*associated_token_account, false),
+        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+        AccountMeta::new(*metadata_account, false),
+        AccountMeta::new(*master_edition_account, false),
+        AccountMeta::new_readonly(TOKEN_METADATA_PROGRAM_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::MintMasterEdition {
+            name,
+            symbol,
+            uri,
+            max_supply,
+        }
+        .pack(),
+    }
+}
+
+/// Create a print edition instruction. The new mint account signs, since the
+/// program creates it with `create_account`.
+#[allow(clippy::too_many_arguments)]
+pub fn print_edition(
+    program_id: &Address,
+    holder: &Address,
+    new_mint_account: &Address,
+    new_associated_token_account: &Address,
+    new_metadata_account: &Address,
+    new_edition_account: &Address,
+    master_mint: &Address,
+    master_edition_account: &Address,
+    master_metadata_account: &Address,
+    master_token_account: &Address,
+    edition_marker_account: &Address,
+    update_authority: &Address,
+    edition: u64,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*holder, true),
+        AccountMeta::new(*new_mint_account, true),
+        AccountMeta::new(*new_associated_token_account, false),
+        AccountMeta::new(*new_metadata_account, false),
+        AccountMeta::new(*new_edition_account, false),
+        AccountMeta::new_readonly(*master_mint, false),
+        AccountMeta::new(*master_edition_account, false),
+        AccountMeta::new_readonly(*master_metadata_account, false),
+        AccountMeta::new_readonly(*master_token_account, false),
+        AccountMeta::new(*edition_marker_account, false),
+        AccountMeta::new_readonly(*update_authority, false),
+        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+        AccountMeta::new_readonly(TOKEN_METADATA_PROGRAM_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::PrintEdition { edition }.pack(),
+    }
+}
+
+/// Create a set fee recipient instruction
+pub fn set_fee_recipient(
+    program_id: &Address,
+    marketplace_authority: &Address,
+    marketplace_account: &Address,
+    new_fee_recipient: &Address,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*marketplace_authority, true),
+        AccountMeta::new(*marketplace_account, false),
+        AccountMeta::new_readonly(*new_fee_recipient, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::SetFeeRecipient.pack(),
+    }
+}
+
+/// Create a propose marketplace authority instruction
+pub fn propose_marketplace_authority(
+    program_id: &Address,
+    marketplace_authority: &Address,
+    marketplace_account: &Address,
+    new_authority: &Address,
+    authority_transfer_account: &Address,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*marketplace_authority, true),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new_readonly(*new_authority, false),
+        AccountMeta::new(*authority_transfer_account, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::ProposeMarketplaceAuthority.pack(),
+    }
+}
+
+/// Create an accept marketplace authority instruction
+pub fn accept_marketplace_authority(
+    program_id: &Address,
+    new_authority: &Address,
+    marketplace_account: &Address,
+    authority_transfer_account: &Address,
+    marketplace_authority: &Address,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*new_authority, true),
+        AccountMeta::new(*marketplace_account, false),
+        AccountMeta::new(*authority_transfer_account, false),
+        AccountMeta::new(*marketplace_authority, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::AcceptMarketplaceAuthority.pack(),
+    }
+}
+
+/// Create a cancel marketplace authority transfer instruction
+pub fn cancel_marketplace_authority_transfer(
+    program_id: &Address,
+    marketplace_authority: &Address,
+    marketplace_account: &Address,
+    authority_transfer_account: &Address,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*marketplace_authority, true),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new(*authority_transfer_account, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::CancelMarketplaceAuthorityTransfer.pack(),
+    }
+}
+
+/// Create a set collection config instruction
+pub fn set_collection_config(
+    program_id: &Address,
+    marketplace_authority: &Address,
+    marketplace_account: &Address,
+    collection_mint: &Address,
+    collection_config_account: &Address,
+    fee_percentage: Option<u16>,
+    enforce_royalties: bool,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*marketplace_authority, true),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new_readonly(*collection_mint, false),
+        AccountMeta::new(*collection_config_account, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::SetCollectionConfig {
+            fee_percentage,
+            enforce_royalties,
+        }
+        .pack(),
+    }
+}
+
+/// Create an initialize marketplace stats instruction
+pub fn initialize_marketplace_stats(
+    program_id: &Address,
+    payer: &Address,
+    marketplace_account: &Address,
+    marketplace_stats_account: &Address,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new(*marketplace_stats_account, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::InitializeMarketplaceStats.pack(),
+    }
+}
+
+/// Create a set referral share instruction
+pub fn set_referral_share(
+    program_id: &Address,
+    marketplace_authority: &Address,
+    marketplace_account: &Address,
+    referral_config_account: &Address,
+    referral_share: u16,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*marketplace_authority, true),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new(*referral_config_account, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+    ];
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::SetReferralShare { referral_share }.pack(),
+    }
+}
+
+/// Create a list bundle instruction
+#[allow(clippy::too_many_arguments)]
+pub fn list_bundle(
+    program_id: &Address,
+    seller: &Address,
+    marketplace_account: &Address,
+    bundle_account: &Address,
+    token_program: &Address,
+    marketplace_stats_account: &Address,
+    items: &[BundleItemAccounts],
+    bundle_id: u64,
+    price: u64,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*seller, true),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new(*bundle_account, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+        AccountMeta::new(*marketplace_stats_account, false),
+    ];
+    accounts.extend(BundleItemAccounts::account_metas(items));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::ListBundle { bundle_id, price }.pack(),
+    }
+}
+
+/// Create a buy bundle instruction
+#[allow(clippy::too_many_arguments)]
+pub fn buy_bundle(
+    program_id: &Address,
+    buyer: &Address,
+    seller: &Address,
+    marketplace_account: &Address,
+    fee_recipient: &Address,
+    bundle_account: &Address,
+    token_program: &Address,
+    marketplace_stats_account: &Address,
+    items: &[BundlePurchaseItemAccounts],
+    expected_price: u64,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*buyer, true),
+        AccountMeta::new(*seller, false),
+        AccountMeta::new_readonly(*marketplace_account, false),
+        AccountMeta::new(*fee_recipient, false),
+        AccountMeta::new(*bundle_account, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        AccountMeta::new(*marketplace_stats_account, false),
+    ];
+    accounts.extend(BundlePurchaseItemAccounts::account_metas(items));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::BuyBundle { expected_price }.pack(),
+    }
+}
+
+/// Create a cancel bundle instruction
+pub fn cancel_bundle(
+    program_id: &Address,
+    seller: &Address,
+    bundle_account: &Address,
+    token_program: &Address,
+    items: &[BundleItemAccounts],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*seller, true),
+        AccountMeta::new(*bundle_account, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+    ];
+    accounts.extend(BundleItemAccounts::account_metas(items));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: MarketplaceInstruction::CancelBundle.pack(),
+    }
+}
+
+#[cfg(feature = "program")]
+impl From<Instruction> for solana_program::instruction::Instruction {
+    fn from(instruction: Instruction) -> Self {
+        use solana_program::pubkey::Pubkey;
+
+        solana_program::instruction::Instruction {
+            program_id: Pubkey::new_from_array(instruction.program_id),
+            accounts: instruction
+                .accounts
+                .into_iter()
+                .map(|meta| solana_program::instruction::AccountMeta {
+                    pubkey: Pubkey::new_from_array(meta.pubkey),
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: instruction.data,
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Client-side types for the NFT marketplace program: the instruction enum
+//! and its builders, account state, error codes, events and PDA helpers.
+//!
+//! The crate is `no_std` (it needs `alloc`) and independent of any Solana SDK
+//! version, so addresses are plain `[u8; 32]`. Convert with
+//! `Pubkey::new_from_array` / `Pubkey::to_bytes`; the `program` feature adds
+//! `From` impls for the `solana-program` version the program is built with.
+//!
+//! ```
+//! use nft_marketplace_sdk::{instruction, pda};
+//!
+//! let program_id = [7u8; 32];
+//! let authority = [1u8; 32];
+//! let mint = [2u8; 32];
+//!
+//! let ix = instruction::mint_nft(
+//!     &program_id,
+//!     &authority,
+//!     &mint,
+//!     &pda::associated_token_address(&authority, &mint),
+//!     &pda::metadata_address(&mint),
+//!     &pda::master_edition_address(&mint),
+//!     "Solmint #1".into(),
+//!     "SOLM".into(),
+//!     "https://example.com/1.json".into(),
+//! );
+//! assert_eq!(ix.accounts.len(), 10);
+//! ```
+
+#![no_std]
+
+extern crate alloc;
+
+pub mod allowlist;
+pub mod bubblegum;
+pub mod error;
+pub mod events;
+pub mod ids;
+pub mod instruction;
+pub mod metadata;
+#[cfg(feature = "pda")]
+pub mod pda;
+pub mod state;
+
+pub use error::MarketplaceError;
+pub use events::MarketplaceEvent;
+pub use instruction::{AccountMeta, Instruction, MarketplaceInstruction};
+pub use state::{
+    Auction, AuthorityTransfer, Bundle, CollectionConfig, CollectionOffer, Listing, Marketplace,
+    MarketplaceTotals, MintConfig, MintRecord, Offer, ReferralConfig, Rental, Stake, StakingConfig,
+};
+
+/// A 32-byte account address
+pub type Address = [u8; 32];
@@ -0,0 +1,407 @@
+//! Just enough of the Metaplex Token Metadata program to create and update an
+//! NFT's metadata, create its master edition, print editions from it, freeze
+//! and thaw it for a delegate and read its collection and creators, without
+//! depending on the metadata program's crate
+
+use alloc::{string::String, vec, vec::Vec};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{
+    ids::{SYSTEM_PROGRAM_ID, SYSVAR_RENT_ID, TOKEN_METADATA_PROGRAM_ID, TOKEN_PROGRAM_ID},
+    instruction::{AccountMeta, Instruction},
+    Address,
+};
+
+/// `Key::MetadataV1`, the first byte of every metadata account
+const METADATA_V1_KEY: u8 = 4;
+
+/// Instruction discriminators of the metadata program
+const MINT_NEW_EDITION_FROM_MASTER_EDITION_VIA_TOKEN: u8 = 11;
+const UPDATE_METADATA_ACCOUNT_V2: u8 = 15;
+const CREATE_MASTER_EDITION_V3: u8 = 17;
+const SET_AND_VERIFY_COLLECTION: u8 = 25;
+const FREEZE_DELEGATED_ACCOUNT: u8 = 26;
+const THAW_DELEGATED_ACCOUNT: u8 = 27;
+const CREATE_METADATA_ACCOUNT_V3: u8 = 33;
+
+/// Longest name, symbol and URI the metadata program accepts, in bytes
+pub const MAX_NAME_LENGTH: usize = 32;
+pub const MAX_SYMBOL_LENGTH: usize = 10;
+pub const MAX_URI_LENGTH: usize = 200;
+
+/// Editions tracked by one edition marker account, one bit each
+pub const EDITION_MARKER_BIT_SIZE: u64 = 248;
+
+/// A creator of the NFT, who receives `share` percent of its royalties
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Creator {
+    pub address: Address,
+    pub verified: bool,
+    pub share: u8,
+}
+
+/// A collection the NFT claims to belong to
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Collection {
+    /// Set once the collection's update authority has signed off on the NFT
+    pub verified: bool,
+    /// The collection NFT's mint
+    pub key: Address,
+}
+
+/// Limited uses of an NFT, kept as-is when the metadata is updated
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Uses {
+    _use_method: u8,
+    _remaining: u64,
+    _total: u64,
+}
+
+/// The fields of a metadata account up to and including `uses`, in account
+/// order; the rest of the account is ignored
+#[derive(BorshDeserialize)]
+struct MetadataPrefix {
+    _key: u8,
+    update_authority: Address,
+    mint: Address,
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    creators: Option<Vec<Creator>>,
+    _primary_sale_happened: bool,
+    is_mutable: bool,
+    _edition_nonce: Option<u8>,
+    _token_standard: Option<u8>,
+    collection: Option<Collection>,
+    uses: Option<Uses>,
+}
+
+/// Metadata of an NFT, as far as the marketplace needs it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    pub update_authority: Address,
+    pub mint: Address,
+    pub is_mutable: bool,
+    pub collection: Option<Collection>,
+    /// The updatable fields, with the padding the metadata program stores
+    /// strings with removed
+    pub data: DataV2,
+}
+
+impl Metadata {
+    /// Decode a metadata account; `None` if it isn't one
+    pub fn from_account_data(data: &[u8]) -> Option<Self> {
+        if data.first() != Some(&METADATA_V1_KEY) {
+            return None;
+        }
+
+        let prefix = MetadataPrefix::deserialize(&mut &data[..]).ok()?;
+        Some(Self {
+            update_authority: prefix.update_authority,
+            mint: prefix.mint,
+            is_mutable: prefix.is_mutable,
+            collection: prefix.collection,
+            data: DataV2 {
+                name: unpad(prefix.name),
+                symbol: unpad(prefix.symbol),
+                uri: unpad(prefix.uri),
+                seller_fee_basis_points: prefix.seller_fee_basis_points,
+                creators: prefix.creators,
+                collection: prefix.collection,
+                uses: prefix.uses,
+            },
+        })
+    }
+
+    /// The collection mint the NFT is verified into, if any
+    pub fn verified_collection(&self) -> Option<Address> {
+        self.collection
+            .filter(|collection| collection.verified)
+            .map(|collection| collection.key)
+    }
+
+    pub fn creators(&self) -> &[Creator] {
+        self.data.creators.as_deref().unwrap_or_default()
+    }
+
+    /// What a sale at `price` owes each creator, in metadata order:
+    /// `seller_fee_basis_points` of the price, split by share. Rounding
+    /// leaves the remainder with the seller.
+    pub fn royalties(&self, price: u64) -> Vec<(Address, u64)> {
+        let total =
+            u128::from(price) * u128::from(self.data.seller_fee_basis_points.min(10_000)) / 10_000;
+        self.creators()
+            .iter()
+            .map(|creator| {
+                // At most `total`, itself at most `price`
+                let amount = total * u128::from(creator.share.min(100)) / 100;
+                (creator.address, amount as u64)
+            })
+            .collect()
+    }
+}
+
+/// Strip the NUL bytes the metadata program pads stored strings with
+fn unpad(value: String) -> String {
+    value.trim_end_matches('\0').into()
+}
+
+/// `DataV2`, the metadata fields set on creation and replaced as a whole on
+/// update
+#[derive(BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct DataV2 {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    creators: Option<Vec<Creator>>,
+    collection: Option<Collection>,
+    uses: Option<Uses>,
+}
+
+#[derive(BorshSerialize)]
+struct CreateMetadataAccountArgsV3 {
+    data: DataV2,
+    is_mutable: bool,
+    /// Always `None`: the NFT isn't a collection parent
+    collection_details: Option<u8>,
+}
+
+/// `CreateMetadataAccountV3` for `mint`, with `update_authority` signing.
+/// The metadata is mutable and carries no creators, collection or royalty.
+#[allow(clippy::too_many_arguments)]
+pub fn create_metadata_account_v3(
+    metadata: &Address,
+    mint: &Address,
+    mint_authority: &Address,
+    payer: &Address,
+    update_authority: &Address,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Instruction {
+    let args = CreateMetadataAccountArgsV3 {
+        data: DataV2 {
+            name,
+            symbol,
+            uri,
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        },
+        is_mutable: true,
+        collection_details: None,
+    };
+    let mut data = vec![CREATE_METADATA_ACCOUNT_V3];
+    data.extend(borsh::to_vec(&args).expect("instructions serialize into a Vec"));
+
+    Instruction {
+        program_id: TOKEN_METADATA_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*metadata, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*mint_authority, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*update_authority, true),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+        ],
+        data,
+    }
+}
+
+#[derive(BorshSerialize)]
+struct UpdateMetadataAccountArgsV2 {
+    data: Option<DataV2>,
+    /// Always `None`, like the other fields below: only `data` is updated
+    update_authority: Option<Address>,
+    primary_sale_happened: Option<bool>,
+    is_mutable: Option<bool>,
+}
+
+/// `UpdateMetadataAccountV2` replacing the updatable fields of `metadata`
+/// with `data`, with `update_authority` signing
+pub fn update_metadata_account_v2(
+    metadata: &Address,
+    update_authority: &Address,
+    data: DataV2,
+) -> Instruction {
+    let args = UpdateMetadataAccountArgsV2 {
+        data: Some(data),
+        update_authority: None,
+        primary_sale_happened: None,
+        is_mutable: None,
+    };
+    let mut data = vec![UPDATE_METADATA_ACCOUNT_V2];
+    data.extend(borsh::to_vec(&args).expect("instructions serialize into a Vec"));
+
+    Instruction {
+        program_id: TOKEN_METADATA_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*metadata, false),
+            AccountMeta::new_readonly(*update_authority, true),
+        ],
+        data,
+    }
+}
+
+/// `CreateMasterEditionV3` for `mint`. The metadata program takes over the
+/// mint and freeze authorities, so with `max_supply` `Some(0)` the supply is
+/// locked at the one token already minted.
+pub fn create_master_edition_v3(
+    edition: &Address,
+    mint: &Address,
+    update_authority: &Address,
+    mint_authority: &Address,
+    payer: &Address,
+    metadata: &Address,
+    max_supply: Option<u64>,
+) -> Instruction {
+    let mut data = vec![CREATE_MASTER_EDITION_V3];
+    data.extend(borsh::to_vec(&max_supply).expect("instructions serialize into a Vec"));
+
+    Instruction {
+        program_id: TOKEN_METADATA_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*edition, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(*update_authority, true),
+            AccountMeta::new_readonly(*mint_authority, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*metadata, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+        ],
+        data,
+    }
+}
+
+/// `SetAndVerifyCollection`: set `collection_mint` as the collection of the
+/// NFT behind `metadata` and verify it, with the collection's update
+/// authority signing
+pub fn set_and_verify_collection(
+    metadata: &Address,
+    collection_authority: &Address,
+    payer: &Address,
+    update_authority: &Address,
+    collection_mint: &Address,
+    collection_metadata: &Address,
+    collection_master_edition: &Address,
+) -> Instruction {
+    Instruction {
+        program_id: TOKEN_METADATA_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*metadata, false),
+            AccountMeta::new(*collection_authority, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*update_authority, false),
+            AccountMeta::new_readonly(*collection_mint, false),
+            AccountMeta::new(*collection_metadata, false),
+            AccountMeta::new_readonly(*collection_master_edition, false),
+        ],
+        data: vec![SET_AND_VERIFY_COLLECTION],
+    }
+}
+
+/// `MintNewEditionFromMasterEditionViaToken`: print edition number `edition`
+/// of the master edition of `master_mint` into `new_mint`, which must hold
+/// exactly one token. `token_account` holds the master edition's token and
+/// `token_account_owner` signs for it. The metadata program takes over the
+/// new mint's authorities.
+#[allow(clippy::too_many_arguments)]
+pub fn mint_new_edition_from_master_edition_via_token(
+    new_metadata: &Address,
+    new_edition: &Address,
+    master_edition: &Address,
+    new_mint: &Address,
+    edition_marker: &Address,
+    new_mint_authority: &Address,
+    payer: &Address,
+    token_account_owner: &Address,
+    token_account: &Address,
+    update_authority: &Address,
+    master_metadata: &Address,
+    edition: u64,
+) -> Instruction {
+    let mut data = vec![MINT_NEW_EDITION_FROM_MASTER_EDITION_VIA_TOKEN];
+    data.extend(edition.to_le_bytes());
+
+    Instruction {
+        program_id: TOKEN_METADATA_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*new_metadata, false),
+            AccountMeta::new(*new_edition, false),
+            AccountMeta::new(*master_edition, false),
+            AccountMeta::new(*new_mint, false),
+            AccountMeta::new(*edition_marker, false),
+            AccountMeta::new_readonly(*new_mint_authority, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*token_account_owner, true),
+            AccountMeta::new_readonly(*token_account, false),
+            AccountMeta::new_readonly(*update_authority, false),
+            AccountMeta::new_readonly(*master_metadata, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(SYSVAR_RENT_ID, false),
+        ],
+        data,
+    }
+}
+
+/// `FreezeDelegatedAccount`: freeze `token_account`, which holds an NFT with
+/// an edition, through the edition's freeze authority. `delegate` must be the
+/// token account's delegate and sign. SPL Token accounts only.
+pub fn freeze_delegated_account(
+    delegate: &Address,
+    token_account: &Address,
+    edition: &Address,
+    mint: &Address,
+) -> Instruction {
+    delegated_account_instruction(
+        FREEZE_DELEGATED_ACCOUNT,
+        delegate,
+        token_account,
+        edition,
+        mint,
+    )
+}
+
+/// `ThawDelegatedAccount`: thaw a token account frozen by
+/// `freeze_delegated_account`, signed by the same delegate
+pub fn thaw_delegated_account(
+    delegate: &Address,
+    token_account: &Address,
+    edition: &Address,
+    mint: &Address,
+) -> Instruction {
+    delegated_account_instruction(
+        THAW_DELEGATED_ACCOUNT,
+        delegate,
+        token_account,
+        edition,
+        mint,
+    )
+}
+
+fn delegated_account_instruction(
+    discriminator: u8,
+    delegate: &Address,
+    token_account: &Address,
+    edition: &Address,
+    mint: &Address,
+) -> Instruction {
+    Instruction {
+        program_id: TOKEN_METADATA_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*delegate, true),
+            AccountMeta::new(*token_account, false),
+            AccountMeta::new_readonly(*edition, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        ],
+        data: vec![discriminator],
+    }
+}
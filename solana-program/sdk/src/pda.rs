@@ -0,0 +1,309 @@
+//! Off-chain program address derivation. Inside a program, use
+//! `Pubkey::find_program_address`, which goes through a cheap syscall.
+
+use alloc::{string::ToString, vec::Vec};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    ids::{
+        ASSOCIATED_TOKEN_PROGRAM_ID, AUCTION_SEED, AUTHORITY_TRANSFER_SEED, BUBBLEGUM_ASSET_SEED,
+        BUBBLEGUM_PROGRAM_ID, BUNDLE_SEED, COLLECTION_CONFIG_SEED, COLLECTION_OFFER_SEED,
+        LISTING_SEED, MARKETPLACE_SEED, MARKETPLACE_STATS_SEED, MASTER_EDITION_SEED, METADATA_SEED,
+        MINT_CONFIG_SEED, MINT_RECORD_SEED, OFFER_SEED, REFERRAL_CONFIG_SEED, RENTAL_SEED,
+        STAKE_SEED, STAKING_CONFIG_SEED, TOKEN_METADATA_PROGRAM_ID, TOKEN_PROGRAM_ID,
+    },
+    metadata::EDITION_MARKER_BIT_SIZE,
+    Address,
+};
+
+const MAX_SEEDS: usize = 16;
+const MAX_SEED_LEN: usize = 32;
+const PDA_MARKER: &[u8] = b"ProgramDerivedAddress";
+
+/// `Pubkey::create_program_address`: `None` when the seeds are invalid or
+/// the hash lands on the ed25519 curve
+pub fn create_program_address(seeds: &[&[u8]], program_id: &Address) -> Option<Address> {
+    if seeds.len() > MAX_SEEDS || seeds.iter().any(|seed| seed.len() > MAX_SEED_LEN) {
+        return None;
+    }
+
+    let mut hasher = Sha256::new();
+    for seed in seeds {
+        hasher.update(seed);
+    }
+    hasher.update(program_id);
+    hasher.update(PDA_MARKER);
+    let address: Address = hasher.finalize().into();
+
+    match CompressedEdwardsY(address).decompress() {
+        Some(_) => None,
+        None => Some(address),
+    }
+}
+
+/// `Pubkey::find_program_address`: the address for the highest bump seed
+/// that is off the curve
+pub fn find_program_address(seeds: &[&[u8]], program_id: &Address) -> (Address, u8) {
+    for bump in (1..=u8::MAX).rev() {
+        let bump_seed = [bump];
+        let mut seeds_with_bump: Vec<&[u8]> = seeds.to_vec();
+        seeds_with_bump.push(&bump_seed);
+        if let Some(address) = create_program_address(&seeds_with_bump, program_id) {
+            return (address, bump);
+        }
+    }
+    panic!("Unable to find a viable program address bump seed");
+}
+
+/// Marketplace account of `authority`
+pub fn marketplace_address(program_id: &Address, authority: &Address) -> (Address, u8) {
+    find_program_address(&[MARKETPLACE_SEED, &authority[..]], program_id)
+}
+
+/// Listing account of `seller` for `nft_mint`
+pub fn listing_address(
+    program_id: &Address,
+    nft_mint: &Address,
+    seller: &Address,
+) -> (Address, u8) {
+    find_program_address(&[LISTING_SEED, &nft_mint[..], &seller[..]], program_id)
+}
+
+/// Escrow token account holding the NFT of `listing`, under the mint's
+/// token program
+pub fn listing_escrow_address(
+    listing: &Address,
+    nft_mint: &Address,
+    token_program: &Address,
+) -> Address {
+    associated_token_address_with_program(listing, nft_mint, token_program)
+}
+
+/// Offer account of `bidder` for `nft_mint`
+pub fn offer_address(program_id: &Address, nft_mint: &Address, bidder: &Address) -> (Address, u8) {
+    find_program_address(&[OFFER_SEED, &nft_mint[..], &bidder[..]], program_id)
+}
+
+/// Auction account of `seller` for `nft_mint`
+pub fn auction_address(
+    program_id: &Address,
+    nft_mint: &Address,
+    seller: &Address,
+) -> (Address, u8) {
+    find_program_address(&[AUCTION_SEED, &nft_mint[..], &seller[..]], program_id)
+}
+
+/// Escrow token account holding the NFT of `auction`, under the mint's
+/// token program
+pub fn auction_escrow_address(
+    auction: &Address,
+    nft_mint: &Address,
+    token_program: &Address,
+) -> Address {
+    associated_token_address_with_program(auction, nft_mint, token_program)
+}
+
+/// Bundle account `bundle_id` of `seller`
+pub fn bundle_address(program_id: &Address, seller: &Address, bundle_id: u64) -> (Address, u8) {
+    find_program_address(
+        &[BUNDLE_SEED, &seller[..], &bundle_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Escrow token account holding `nft_mint` for `bundle`, under the mint's
+/// token program
+pub fn bundle_escrow_address(
+    bundle: &Address,
+    nft_mint: &Address,
+    token_program: &Address,
+) -> Address {
+    associated_token_address_with_program(bundle, nft_mint, token_program)
+}
+
+/// Staking config account of the collection `collection_mint`
+pub fn staking_config_address(program_id: &Address, collection_mint: &Address) -> (Address, u8) {
+    find_program_address(&[STAKING_CONFIG_SEED, &collection_mint[..]], program_id)
+}
+
+/// Token account of `staking_config` holding the rewards it pays, under the
+/// reward mint's token program
+pub fn staking_reward_vault_address(
+    staking_config: &Address,
+    reward_mint: &Address,
+    token_program: &Address,
+) -> Address {
+    associated_token_address_with_program(staking_config, reward_mint, token_program)
+}
+
+/// Stake account of `owner` for `nft_mint`
+pub fn stake_address(program_id: &Address, nft_mint: &Address, owner: &Address) -> (Address, u8) {
+    find_program_address(&[STAKE_SEED, &nft_mint[..], &owner[..]], program_id)
+}
+
+/// Escrow token account holding the NFT of `stake`, under the mint's token
+/// program
+pub fn stake_escrow_address(
+    stake: &Address,
+    nft_mint: &Address,
+    token_program: &Address,
+) -> Address {
+    associated_token_address_with_program(stake, nft_mint, token_program)
+}
+
+/// Rental account of `owner` for `nft_mint`
+pub fn rental_address(program_id: &Address, nft_mint: &Address, owner: &Address) -> (Address, u8) {
+    find_program_address(&[RENTAL_SEED, &nft_mint[..], &owner[..]], program_id)
+}
+
+/// Escrow token account holding the NFT of `rental` while it isn't rented
+/// out. Rentals are SPL Token only.
+pub fn rental_escrow_address(rental: &Address, nft_mint: &Address) -> Address {
+    associated_token_address_with_program(rental, nft_mint, &TOKEN_PROGRAM_ID)
+}
+
+/// Mint config account `config_id` of `creator`
+pub fn mint_config_address(
+    program_id: &Address,
+    creator: &Address,
+    config_id: u64,
+) -> (Address, u8) {
+    find_program_address(
+        &[MINT_CONFIG_SEED, &creator[..], &config_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Mint record counting the NFTs `wallet` minted from `mint_config`
+pub fn mint_record_address(
+    program_id: &Address,
+    mint_config: &Address,
+    wallet: &Address,
+) -> (Address, u8) {
+    find_program_address(
+        &[MINT_RECORD_SEED, &mint_config[..], &wallet[..]],
+        program_id,
+    )
+}
+
+/// Collection offer account of `bidder` for NFTs verified into
+/// `collection_mint`
+pub fn collection_offer_address(
+    program_id: &Address,
+    collection_mint: &Address,
+    bidder: &Address,
+) -> (Address, u8) {
+    find_program_address(
+        &[COLLECTION_OFFER_SEED, &collection_mint[..], &bidder[..]],
+        program_id,
+    )
+}
+
+/// Config account of `marketplace` for NFTs verified into `collection_mint`
+pub fn collection_config_address(
+    program_id: &Address,
+    marketplace: &Address,
+    collection_mint: &Address,
+) -> (Address, u8) {
+    find_program_address(
+        &[
+            COLLECTION_CONFIG_SEED,
+            &marketplace[..],
+            &collection_mint[..],
+        ],
+        program_id,
+    )
+}
+
+/// Pending authority transfer account of `marketplace`
+pub fn authority_transfer_address(program_id: &Address, marketplace: &Address) -> (Address, u8) {
+    find_program_address(&[AUTHORITY_TRANSFER_SEED, &marketplace[..]], program_id)
+}
+
+/// Cumulative stats account of `marketplace`
+pub fn marketplace_stats_address(program_id: &Address, marketplace: &Address) -> (Address, u8) {
+    find_program_address(&[MARKETPLACE_STATS_SEED, &marketplace[..]], program_id)
+}
+
+/// Referral config account of `marketplace`
+pub fn referral_config_address(program_id: &Address, marketplace: &Address) -> (Address, u8) {
+    find_program_address(&[REFERRAL_CONFIG_SEED, &marketplace[..]], program_id)
+}
+
+/// Metaplex metadata account of `mint`
+pub fn metadata_address(mint: &Address) -> Address {
+    find_program_address(
+        &[METADATA_SEED, &TOKEN_METADATA_PROGRAM_ID, &mint[..]],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Metaplex master edition account of `mint`
+pub fn master_edition_address(mint: &Address) -> Address {
+    find_program_address(
+        &[
+            METADATA_SEED,
+            &TOKEN_METADATA_PROGRAM_ID,
+            &mint[..],
+            MASTER_EDITION_SEED,
+        ],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Metaplex edition marker account recording whether edition `edition` of
+/// the master edition of `master_mint` has been printed
+pub fn edition_marker_address(master_mint: &Address, edition: u64) -> Address {
+    let marker = (edition / EDITION_MARKER_BIT_SIZE).to_string();
+    find_program_address(
+        &[
+            METADATA_SEED,
+            &TOKEN_METADATA_PROGRAM_ID,
+            &master_mint[..],
+            MASTER_EDITION_SEED,
+            marker.as_bytes(),
+        ],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Bubblegum tree config account of `merkle_tree`
+pub fn tree_config_address(merkle_tree: &Address) -> Address {
+    find_program_address(&[&merkle_tree[..]], &BUBBLEGUM_PROGRAM_ID).0
+}
+
+/// Asset id of the compressed NFT at `leaf_index` in `merkle_tree`
+pub fn compressed_asset_address(merkle_tree: &Address, leaf_index: u64) -> Address {
+    find_program_address(
+        &[
+            BUBBLEGUM_ASSET_SEED,
+            &merkle_tree[..],
+            &leaf_index.to_le_bytes(),
+        ],
+        &BUBBLEGUM_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Associated token account of `wallet` for `mint` under the SPL Token program
+pub fn associated_token_address(wallet: &Address, mint: &Address) -> Address {
+    associated_token_address_with_program(wallet, mint, &TOKEN_PROGRAM_ID)
+}
+
+/// Associated token account of `wallet` for `mint` under `token_program`,
+/// SPL Token or Token-2022
+pub fn associated_token_address_with_program(
+    wallet: &Address,
+    mint: &Address,
+    token_program: &Address,
+) -> Address {
+    find_program_address(
+        &[&wallet[..], &token_program[..], &mint[..]],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
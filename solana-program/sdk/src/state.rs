@@ -0,0 +1,1222 @@
+use alloc::{format, string::String, vec::Vec};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{
+    error::MarketplaceError,
+    metadata::{MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH},
+    Address,
+};
+
+/// Bytes of a marketplace account kept free for fields added later
+pub const MARKETPLACE_RESERVED_LEN: usize = 128;
+
+/// Marketplace account data. New fields take their bytes from `reserved`, so
+/// the account size stays put and accounts written before a field existed
+/// read it as zero; `version` says which fields the account was written with.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Marketplace {
+    pub is_initialized: bool,
+    pub authority: Address,
+    pub fee_percentage: u16, // Fee percentage in basis points (e.g., 250 = 2.5%)
+    pub fee_recipient: Address,
+    pub version: u8, // 0 for legacy marketplaces not yet migrated
+    pub reserved: [u8; MARKETPLACE_RESERVED_LEN],
+}
+
+impl Marketplace {
+    pub const LEN: usize = 1 + 32 + 2 + 32 + 1 + MARKETPLACE_RESERVED_LEN; // 196 bytes
+    /// Size of marketplaces created before versioning, which end before
+    /// `version`. `MigrateMarketplace` grows them to `LEN`.
+    pub const LEGACY_LEN: usize = 65;
+    /// Version of marketplaces written by this program
+    pub const CURRENT_VERSION: u8 = 1;
+
+    pub fn new(authority: Address, fee_percentage: u16, fee_recipient: Address) -> Self {
+        Self {
+            is_initialized: true,
+            authority,
+            fee_percentage,
+            fee_recipient,
+            version: Self::CURRENT_VERSION,
+            reserved: [0; MARKETPLACE_RESERVED_LEN],
+        }
+    }
+
+    /// Decode the account data of a marketplace account, including legacy
+    /// marketplaces, which read as version 0
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MarketplaceError> {
+        if data.len() == Self::LEGACY_LEN {
+            let mut padded = [0; Self::LEN];
+            padded[..Self::LEGACY_LEN].copy_from_slice(data);
+            return Self::from_account_data(&padded);
+        }
+
+        let mut data = data
+            .get(..Self::LEN)
+            .ok_or(MarketplaceError::AccountNotInitialized)?;
+        Self::deserialize(&mut data).map_err(|_| MarketplaceError::AccountNotInitialized)
+    }
+
+    /// Whether the account must be migrated before the program can write it
+    pub fn needs_migration(&self) -> bool {
+        self.version < Self::CURRENT_VERSION
+    }
+
+    pub fn calculate_fee(&self, price: u64) -> Result<u64, MarketplaceError> {
+        let fee = (price as u128)
+            .checked_mul(self.fee_percentage as u128)
+            .ok_or(MarketplaceError::AmountOverflow)?
+            .checked_div(10000) // Basis points conversion
+            .ok_or(MarketplaceError::MarketplaceFeeCalculationError)?;
+
+        if fee > u64::MAX as u128 {
+            return Err(MarketplaceError::AmountOverflow);
+        }
+
+        Ok(fee as u64)
+    }
+
+    pub fn calculate_seller_proceeds(&self, price: u64) -> Result<u64, MarketplaceError> {
+        let fee = self.calculate_fee(price)?;
+        price
+            .checked_sub(fee)
+            .ok_or(MarketplaceError::AmountOverflow)
+    }
+}
+
+/// Listing account data. The listing PDA owns the escrow token account
+/// holding the NFT until it is bought or the listing is cancelled.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Listing {
+    pub is_initialized: bool,
+    pub marketplace: Address,
+    pub seller: Address,
+    pub nft_mint: Address,
+    pub price: u64, // Price in lamports
+    pub created_at: i64,
+    pub bump: u8,
+    pub allowed_buyer: Option<Address>, // The only buyer of a private sale
+}
+
+impl Listing {
+    pub const LEN: usize = 1 + 32 + 32 + 32 + 8 + 8 + 1 + 33; // 147 bytes
+    /// Size of listings created before private sales, which end before
+    /// `allowed_buyer`
+    pub const LEGACY_LEN: usize = 114;
+
+    pub fn new(
+        marketplace: Address,
+        seller: Address,
+        nft_mint: Address,
+        price: u64,
+        created_at: i64,
+        bump: u8,
+        allowed_buyer: Option<Address>,
+    ) -> Self {
+        Self {
+            is_initialized: true,
+            marketplace,
+            seller,
+            nft_mint,
+            price,
+            created_at,
+            bump,
+            allowed_buyer,
+        }
+    }
+
+    /// Decode the account data of a listing account, including legacy
+    /// listings, which have no allowed buyer
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MarketplaceError> {
+        if data.len() == Self::LEGACY_LEN {
+            let mut padded = [0; Self::LEN];
+            padded[..Self::LEGACY_LEN].copy_from_slice(data);
+            return Self::from_account_data(&padded);
+        }
+
+        let mut data = data
+            .get(..Self::LEN)
+            .ok_or(MarketplaceError::AccountNotInitialized)?;
+        Self::deserialize(&mut data).map_err(|_| MarketplaceError::AccountNotInitialized)
+    }
+}
+
+/// Offer account data. The offered lamports are held in the offer account
+/// itself, on top of its rent, until it is accepted or cancelled.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Offer {
+    pub is_initialized: bool,
+    pub marketplace: Address,
+    pub bidder: Address,
+    pub nft_mint: Address,
+    pub amount: u64,     // Offered lamports
+    pub expires_at: i64, // Unix timestamp; 0 if the offer doesn't expire
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Offer {
+    pub const LEN: usize = 1 + 32 + 32 + 32 + 8 + 8 + 8 + 1; // 122 bytes
+    /// Where `bidder` starts, for `getProgramAccounts` memcmp filters
+    pub const BIDDER_OFFSET: usize = 1 + 32;
+
+    pub fn new(
+        marketplace: Address,
+        bidder: Address,
+        nft_mint: Address,
+        amount: u64,
+        expires_at: i64,
+        created_at: i64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            is_initialized: true,
+            marketplace,
+            bidder,
+            nft_mint,
+            amount,
+            expires_at,
+            created_at,
+            bump,
+        }
+    }
+
+    /// Decode the account data of an offer account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MarketplaceError> {
+        let mut data = data
+            .get(..Self::LEN)
+            .ok_or(MarketplaceError::AccountNotInitialized)?;
+        Self::deserialize(&mut data).map_err(|_| MarketplaceError::AccountNotInitialized)
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+}
+
+/// Collection offer account data: an offer any holder of an NFT verified into
+/// `collection_mint` can accept. The offered lamports are held in the account
+/// on top of its rent.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CollectionOffer {
+    pub is_initialized: bool,
+    pub marketplace: Address,
+    pub bidder: Address,
+    pub collection_mint: Address, // Verified Metaplex collection
+    pub amount: u64,              // Offered lamports
+    pub expires_at: i64,          // Unix timestamp; 0 if the offer doesn't expire
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl CollectionOffer {
+    pub const LEN: usize = 1 + 32 + 32 + 32 + 8 + 8 + 8 + 1; // 122 bytes
+    /// Where `bidder` starts, for `getProgramAccounts` memcmp filters
+    pub const BIDDER_OFFSET: usize = 1 + 32;
+
+    pub fn new(
+        marketplace: Address,
+        bidder: Address,
+        collection_mint: Address,
+        amount: u64,
+        expires_at: i64,
+        created_at: i64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            is_initialized: true,
+            marketplace,
+            bidder,
+            collection_mint,
+            amount,
+            expires_at,
+            created_at,
+            bump,
+        }
+    }
+
+    /// Decode the account data of a collection offer account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MarketplaceError> {
+        let mut data = data
+            .get(..Self::LEN)
+            .ok_or(MarketplaceError::AccountNotInitialized)?;
+        Self::deserialize(&mut data).map_err(|_| MarketplaceError::AccountNotInitialized)
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+}
+
+/// English auction account data. The auction PDA owns the escrow token
+/// account holding the NFT, and holds the highest bid's lamports on top of
+/// its rent until the auction is settled.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Auction {
+    pub is_initialized: bool,
+    pub marketplace: Address,
+    pub seller: Address,
+    pub nft_mint: Address,
+    pub reserve_price: u64, // Lowest acceptable bid in lamports
+    pub end_time: i64,      // Unix timestamp bidding closes at
+    pub highest_bid: u64,   // 0 until the first bid
+    pub highest_bidder: Address,
+    pub bump: u8,
+}
+
+impl Auction {
+    pub const LEN: usize = 1 + 32 + 32 + 32 + 8 + 8 + 8 + 32 + 1; // 154 bytes
+    /// Where `highest_bidder` starts, for `getProgramAccounts` memcmp filters
+    pub const HIGHEST_BIDDER_OFFSET: usize = 1 + 32 + 32 + 32 + 8 + 8 + 8;
+
+    pub fn new(
+        marketplace: Address,
+        seller: Address,
+        nft_mint: Address,
+        reserve_price: u64,
+        end_time: i64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            is_initialized: true,
+            marketplace,
+            seller,
+            nft_mint,
+            reserve_price,
+            end_time,
+            highest_bid: 0,
+            highest_bidder: [0; 32],
+            bump,
+        }
+    }
+
+    /// Decode the account data of an auction account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MarketplaceError> {
+        let mut data = data
+            .get(..Self::LEN)
+            .ok_or(MarketplaceError::AccountNotInitialized)?;
+        Self::deserialize(&mut data).map_err(|_| MarketplaceError::AccountNotInitialized)
+    }
+
+    pub fn has_bids(&self) -> bool {
+        self.highest_bid > 0
+    }
+
+    /// The least a new bid must be: the reserve, or more than the highest bid
+    pub fn minimum_bid(&self) -> Result<u64, MarketplaceError> {
+        if self.has_bids() {
+            self.highest_bid
+                .checked_add(1)
+                .ok_or(MarketplaceError::AmountOverflow)
+        } else {
+            Ok(self.reserve_price)
+        }
+    }
+}
+
+/// Marketplace settings for the NFTs verified into `collection_mint`, set by
+/// the marketplace authority
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CollectionConfig {
+    pub is_initialized: bool,
+    pub marketplace: Address,
+    pub collection_mint: Address,
+    pub fee_percentage: Option<u16>, // Overrides the marketplace fee if set
+    pub enforce_royalties: bool,     // Sales pay the NFT's creators their royalties
+    pub bump: u8,
+}
+
+impl CollectionConfig {
+    pub const LEN: usize = 1 + 32 + 32 + 3 + 1 + 1; // 70 bytes
+
+    pub fn new(
+        marketplace: Address,
+        collection_mint: Address,
+        fee_percentage: Option<u16>,
+        enforce_royalties: bool,
+        bump: u8,
+    ) -> Self {
+        Self {
+            is_initialized: true,
+            marketplace,
+            collection_mint,
+            fee_percentage,
+            enforce_royalties,
+            bump,
+        }
+    }
+
+    /// Decode the account data of a collection config account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MarketplaceError> {
+        let mut data = data
+            .get(..Self::LEN)
+            .ok_or(MarketplaceError::AccountNotInitialized)?;
+        Self::deserialize(&mut data).map_err(|_| MarketplaceError::AccountNotInitialized)
+    }
+}
+
+/// A proposed transfer of a marketplace's authority, completed when
+/// `new_authority` accepts it. The proposing authority pays its rent and
+/// gets it back when the transfer is accepted or cancelled.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuthorityTransfer {
+    pub is_initialized: bool,
+    pub marketplace: Address,
+    pub new_authority: Address,
+    pub bump: u8,
+}
+
+impl AuthorityTransfer {
+    pub const LEN: usize = 1 + 32 + 32 + 1; // 66 bytes
+
+    pub fn new(marketplace: Address, new_authority: Address, bump: u8) -> Self {
+        Self {
+            is_initialized: true,
+            marketplace,
+            new_authority,
+            bump,
+        }
+    }
+
+    /// Decode the account data of an authority transfer account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MarketplaceError> {
+        let mut data = data
+            .get(..Self::LEN)
+            .ok_or(MarketplaceError::AccountNotInitialized)?;
+        Self::deserialize(&mut data).map_err(|_| MarketplaceError::AccountNotInitialized)
+    }
+}
+
+/// Running totals of a marketplace's activity, kept by `ListNft` and `BuyNft`
+/// so off-chain stats can be checked against the chain
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MarketplaceTotals {
+    pub is_initialized: bool,
+    pub marketplace: Address,
+    pub total_volume: u64, // Lamports paid by buyers, fees included
+    pub total_sales: u64,
+    pub total_listings: u64,
+    pub bump: u8,
+}
+
+impl MarketplaceTotals {
+    pub const LEN: usize = 1 + 32 + 8 + 8 + 8 + 1; // 58 bytes
+
+    pub fn new(marketplace: Address, bump: u8) -> Self {
+        Self {
+            is_initialized: true,
+            marketplace,
+            total_volume: 0,
+            total_sales: 0,
+            total_listings: 0,
+            bump,
+        }
+    }
+
+    /// Count a new listing
+    pub fn record_listing(&mut self) -> Result<(), MarketplaceError> {
+        self.total_listings = self
+            .total_listings
+            .checked_add(1)
+            .ok_or(MarketplaceError::AmountOverflow)?;
+        Ok(())
+    }
+
+    /// Count a sale at `price`
+    pub fn record_sale(&mut self, price: u64) -> Result<(), MarketplaceError> {
+        self.total_volume = self
+            .total_volume
+            .checked_add(price)
+            .ok_or(MarketplaceError::AmountOverflow)?;
+        self.total_sales = self
+            .total_sales
+            .checked_add(1)
+            .ok_or(MarketplaceError::AmountOverflow)?;
+        Ok(())
+    }
+
+    /// Decode the account data of a marketplace stats account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MarketplaceError> {
+        let mut data = data
+            .get(..Self::LEN)
+            .ok_or(MarketplaceError::AccountNotInitialized)?;
+        Self::deserialize(&mut data).map_err(|_| MarketplaceError::AccountNotInitialized)
+    }
+}
+
+/// The share of the marketplace fee `BuyNft` pays to the referrer passed with
+/// a purchase, set by the marketplace authority
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReferralConfig {
+    pub is_initialized: bool,
+    pub marketplace: Address,
+    pub referral_share: u16, // Basis points of the marketplace fee (10000 = all of it)
+    pub bump: u8,
+}
+
+impl ReferralConfig {
+    pub const LEN: usize = 1 + 32 + 2 + 1; // 36 bytes
+
+    pub fn new(marketplace: Address, referral_share: u16, bump: u8) -> Self {
+        Self {
+            is_initialized: true,
+            marketplace,
+            referral_share,
+            bump,
+        }
+    }
+
+    /// Decode the account data of a referral config account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MarketplaceError> {
+        let mut data = data
+            .get(..Self::LEN)
+            .ok_or(MarketplaceError::AccountNotInitialized)?;
+        Self::deserialize(&mut data).map_err(|_| MarketplaceError::AccountNotInitialized)
+    }
+
+    /// The referrer's part of a marketplace fee of `fee`
+    pub fn calculate_referral_fee(&self, fee: u64) -> Result<u64, MarketplaceError> {
+        let referral_fee = (fee as u128)
+            .checked_mul(self.referral_share as u128)
+            .ok_or(MarketplaceError::AmountOverflow)?
+            .checked_div(10000) // Basis points conversion
+            .ok_or(MarketplaceError::MarketplaceFeeCalculationError)?;
+
+        // At most the whole fee, so this fits in a u64
+        Ok(referral_fee as u64)
+    }
+}
+
+/// Most NFTs in one bundle; each takes three accounts in `ListBundle` and
+/// `CancelBundle` and five in `BuyBundle`, so buying a full bundle needs an
+/// address lookup table to fit in a transaction
+pub const MAX_BUNDLE_SIZE: usize = 5;
+
+/// Fewest NFTs in one bundle; a single NFT is listed with `ListNft`
+pub const MIN_BUNDLE_SIZE: usize = 2;
+
+/// Several NFTs listed together for one price. The bundle PDA owns the escrow
+/// token accounts holding them until the bundle is bought or cancelled.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Bundle {
+    pub is_initialized: bool,
+    pub marketplace: Address,
+    pub seller: Address,
+    pub bundle_id: u64, // Chosen by the seller to tell their bundles apart
+    pub price: u64,     // Price in lamports for all of `mints`
+    pub created_at: i64,
+    pub bump: u8,
+    pub mints: Vec<Address>, // In the order their accounts are passed
+}
+
+impl Bundle {
+    pub const LEN: usize = 1 + 32 + 32 + 8 + 8 + 8 + 1 + 4 + 32 * MAX_BUNDLE_SIZE; // 254 bytes
+
+    pub fn new(
+        marketplace: Address,
+        seller: Address,
+        bundle_id: u64,
+        price: u64,
+        created_at: i64,
+        bump: u8,
+        mints: Vec<Address>,
+    ) -> Self {
+        Self {
+            is_initialized: true,
+            marketplace,
+            seller,
+            bundle_id,
+            price,
+            created_at,
+            bump,
+            mints,
+        }
+    }
+
+    /// Decode the account data of a bundle account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MarketplaceError> {
+        let mut data = data
+            .get(..Self::LEN)
+            .ok_or(MarketplaceError::AccountNotInitialized)?;
+        Self::deserialize(&mut data).map_err(|_| MarketplaceError::AccountNotInitialized)
+    }
+}
+
+/// Seconds in one day, the unit of staking reward rates and rental rates
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// A staking campaign for the NFTs verified into a collection, run by the
+/// collection's update authority. Each staked NFT earns `reward_rate` base
+/// units of `reward_mint` per day, paid from the config's reward vault, which
+/// anyone can fund by sending it reward tokens.
+///
+/// `reward_index` is what one NFT staked since the config was created has
+/// earned, in reward base units times seconds per day, as of `accrued_at`.
+/// A stake's rewards are the growth of the index since its checkpoint, so a
+/// new rate only applies from when it's set.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StakingConfig {
+    pub is_initialized: bool,
+    pub collection_mint: Address,
+    pub authority: Address, // The collection's update authority when the config was last set
+    pub reward_mint: Address,
+    pub reward_rate: u64, // Reward base units per staked NFT per day
+    pub bump: u8,
+    pub reward_index: u128,
+    pub accrued_at: i64,
+}
+
+impl StakingConfig {
+    pub const LEN: usize = 1 + 32 + 32 + 32 + 8 + 1 + 16 + 8; // 130 bytes
+
+    pub fn new(
+        collection_mint: Address,
+        authority: Address,
+        reward_mint: Address,
+        reward_rate: u64,
+        created_at: i64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            is_initialized: true,
+            collection_mint,
+            authority,
+            reward_mint,
+            reward_rate,
+            bump,
+            reward_index: 0,
+            accrued_at: created_at,
+        }
+    }
+
+    /// The reward index as of `now`, accrued at the current rate since
+    /// `accrued_at`
+    pub fn reward_index_at(&self, now: i64) -> Result<u128, MarketplaceError> {
+        let elapsed = now.saturating_sub(self.accrued_at).max(0) as u128;
+        (self.reward_rate as u128)
+            .checked_mul(elapsed)
+            .and_then(|accrued| self.reward_index.checked_add(accrued))
+            .ok_or(MarketplaceError::AmountOverflow)
+    }
+
+    /// Set a new reward rate from `now`, accruing the time before it at the
+    /// old one
+    pub fn set_reward_rate(&mut self, reward_rate: u64, now: i64) -> Result<(), MarketplaceError> {
+        self.reward_index = self.reward_index_at(now)?;
+        self.accrued_at = now.max(self.accrued_at);
+        self.reward_rate = reward_rate;
+        Ok(())
+    }
+
+    /// Decode the account data of a staking config account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MarketplaceError> {
+        let mut data = data
+            .get(..Self::LEN)
+            .ok_or(MarketplaceError::AccountNotInitialized)?;
+        Self::deserialize(&mut data).map_err(|_| MarketplaceError::AccountNotInitialized)
+    }
+}
+
+/// An NFT staked under a staking config. The stake PDA owns the escrow token
+/// account holding the NFT until it is unstaked.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Stake {
+    pub is_initialized: bool,
+    pub staking_config: Address,
+    pub owner: Address,
+    pub nft_mint: Address,
+    pub staked_at: i64,
+    pub last_claimed_at: i64, // `staked_at` until the first claim
+    pub bump: u8,
+    pub reward_index: u128, // The config's reward index rewards were last paid up to
+}
+
+impl Stake {
+    pub const LEN: usize = 1 + 32 + 32 + 32 + 8 + 8 + 1 + 16; // 130 bytes
+
+    pub fn new(
+        staking_config: Address,
+        owner: Address,
+        nft_mint: Address,
+        staked_at: i64,
+        reward_index: u128,
+        bump: u8,
+    ) -> Self {
+        Self {
+            is_initialized: true,
+            staking_config,
+            owner,
+            nft_mint,
+            staked_at,
+            last_claimed_at: staked_at,
+            bump,
+            reward_index,
+        }
+    }
+
+    /// Decode the account data of a stake account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MarketplaceError> {
+        let mut data = data
+            .get(..Self::LEN)
+            .ok_or(MarketplaceError::AccountNotInitialized)?;
+        Self::deserialize(&mut data).map_err(|_| MarketplaceError::AccountNotInitialized)
+    }
+
+    /// Rewards earned under `staking_config` since the last claim, as of
+    /// `now`
+    pub fn rewards_due(
+        &self,
+        staking_config: &StakingConfig,
+        now: i64,
+    ) -> Result<u64, MarketplaceError> {
+        let earned = staking_config
+            .reward_index_at(now)?
+            .saturating_sub(self.reward_index);
+        u64::try_from(earned / SECONDS_PER_DAY as u128)
+            .map_err(|_| MarketplaceError::AmountOverflow)
+    }
+
+    /// Record `rewards` as paid. The checkpoint moves by exactly what they
+    /// cover, so the rounding remainder keeps accruing.
+    pub fn record_claim(&mut self, rewards: u64, now: i64) -> Result<(), MarketplaceError> {
+        self.reward_index = (rewards as u128)
+            .checked_mul(SECONDS_PER_DAY as u128)
+            .and_then(|paid| self.reward_index.checked_add(paid))
+            .ok_or(MarketplaceError::AmountOverflow)?;
+        self.last_claimed_at = now;
+        Ok(())
+    }
+}
+
+/// An NFT offered for rent by the day. The rental PDA owns the escrow token
+/// account holding the NFT while it isn't rented out. While it is, the NFT
+/// sits frozen in the renter's token account with the rental PDA as its
+/// delegate, and the rental account holds the rent paid on top of its own
+/// rent until the NFT is reclaimed.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Rental {
+    pub is_initialized: bool,
+    pub marketplace: Address,
+    pub owner: Address,
+    pub nft_mint: Address,
+    pub daily_rate: u64, // Rent in lamports per day
+    pub max_days: u16,   // Longest rental the owner accepts
+    pub renter: Address, // All zeros while not rented out
+    pub rent_paid: u64,  // Held for the owner until the NFT is reclaimed
+    pub expires_at: i64, // Unix timestamp the rental ends at; 0 while not rented out
+    pub bump: u8,
+}
+
+impl Rental {
+    pub const LEN: usize = 1 + 32 + 32 + 32 + 8 + 2 + 32 + 8 + 8 + 1; // 156 bytes
+
+    pub fn new(
+        marketplace: Address,
+        owner: Address,
+        nft_mint: Address,
+        daily_rate: u64,
+        max_days: u16,
+        bump: u8,
+    ) -> Self {
+        Self {
+            is_initialized: true,
+            marketplace,
+            owner,
+            nft_mint,
+            daily_rate,
+            max_days,
+            renter: [0; 32],
+            rent_paid: 0,
+            expires_at: 0,
+            bump,
+        }
+    }
+
+    /// Decode the account data of a rental account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MarketplaceError> {
+        let mut data = data
+            .get(..Self::LEN)
+            .ok_or(MarketplaceError::AccountNotInitialized)?;
+        Self::deserialize(&mut data).map_err(|_| MarketplaceError::AccountNotInitialized)
+    }
+
+    pub fn is_rented(&self) -> bool {
+        self.renter != [0; 32]
+    }
+
+    /// Rent in lamports for `days` days
+    pub fn rent_for(&self, days: u16) -> Result<u64, MarketplaceError> {
+        self.daily_rate
+            .checked_mul(u64::from(days))
+            .ok_or(MarketplaceError::AmountOverflow)
+    }
+
+    /// Rent the NFT to `renter` for `days` days from `now`, recording the
+    /// rent paid
+    pub fn start(&mut self, renter: Address, days: u16, now: i64) -> Result<(), MarketplaceError> {
+        self.rent_paid = self.rent_for(days)?;
+        self.expires_at = i64::from(days)
+            .checked_mul(SECONDS_PER_DAY)
+            .and_then(|duration| now.checked_add(duration))
+            .ok_or(MarketplaceError::AmountOverflow)?;
+        self.renter = renter;
+        Ok(())
+    }
+
+    /// Mark the NFT back from its renter and available to rent again
+    pub fn end(&mut self) {
+        self.renter = [0; 32];
+        self.rent_paid = 0;
+        self.expires_at = 0;
+    }
+}
+
+/// A drop of up to `max_supply` NFTs minted on demand at `price`, numbered
+/// from 1 in mint order. NFT `n` is named `{name_prefix} #{n}` with URI
+/// `{base_uri}{n}.json`, and the mint config PDA is its update authority.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MintConfig {
+    pub is_initialized: bool,
+    pub creator: Address,
+    pub treasury: Address, // Receives the mint price
+    pub config_id: u64,    // Chosen by the creator to tell their drops apart
+    pub price: u64,        // Price in lamports per NFT
+    pub max_supply: u64,
+    pub minted: u64,
+    pub go_live: i64, // Unix timestamp minting opens at
+    /// Merkle root of the wallets allowed to mint; `None` for a public drop
+    pub allowlist_root: Option<[u8; 32]>,
+    pub wallet_limit: u16, // Most NFTs one wallet can mint; 0 for no limit
+    pub bump: u8,
+    pub name_prefix: String,
+    pub symbol: String,
+    pub base_uri: String,
+}
+
+impl MintConfig {
+    pub const LEN: usize = 1
+        + 32
+        + 32
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 33
+        + 2
+        + 1
+        + (4 + MAX_NAME_LENGTH)
+        + (4 + MAX_SYMBOL_LENGTH)
+        + (4 + MAX_URI_LENGTH); // 395 bytes
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        creator: Address,
+        treasury: Address,
+        config_id: u64,
+        price: u64,
+        max_supply: u64,
+        go_live: i64,
+        allowlist_root: Option<[u8; 32]>,
+        wallet_limit: u16,
+        bump: u8,
+        name_prefix: String,
+        symbol: String,
+        base_uri: String,
+    ) -> Self {
+        Self {
+            is_initialized: true,
+            creator,
+            treasury,
+            config_id,
+            price,
+            max_supply,
+            minted: 0,
+            go_live,
+            allowlist_root,
+            wallet_limit,
+            bump,
+            name_prefix,
+            symbol,
+            base_uri,
+        }
+    }
+
+    /// Decode the account data of a mint config account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MarketplaceError> {
+        let mut data = data
+            .get(..Self::LEN)
+            .ok_or(MarketplaceError::AccountNotInitialized)?;
+        Self::deserialize(&mut data).map_err(|_| MarketplaceError::AccountNotInitialized)
+    }
+
+    pub fn is_sold_out(&self) -> bool {
+        self.minted >= self.max_supply
+    }
+
+    /// Name of NFT `number`
+    pub fn nft_name(&self, number: u64) -> String {
+        format!("{} #{}", self.name_prefix, number)
+    }
+
+    /// URI of NFT `number`
+    pub fn nft_uri(&self, number: u64) -> String {
+        format!("{}{}.json", self.base_uri, number)
+    }
+
+    /// Whether every NFT of the drop gets a name, symbol and URI the metadata
+    /// program accepts. The last NFT has the longest ones.
+    pub fn fits_metadata_limits(&self) -> bool {
+        self.symbol.len() <= MAX_SYMBOL_LENGTH
+            && self.nft_name(self.max_supply).len() <= MAX_NAME_LENGTH
+            && self.nft_uri(self.max_supply).len() <= MAX_URI_LENGTH
+    }
+}
+
+/// The NFTs one wallet minted from a mint config, checked against its
+/// `wallet_limit`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MintRecord {
+    pub is_initialized: bool,
+    pub mint_config: Address,
+    pub wallet: Address,
+    pub minted: u16,
+    pub bump: u8,
+}
+
+impl MintRecord {
+    pub const LEN: usize = 1 + 32 + 32 + 2 + 1; // 68 bytes
+
+    pub fn new(mint_config: Address, wallet: Address, bump: u8) -> Self {
+        Self {
+            is_initialized: true,
+            mint_config,
+            wallet,
+            minted: 0,
+            bump,
+        }
+    }
+
+    /// Decode the account data of a mint record account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, MarketplaceError> {
+        let mut data = data
+            .get(..Self::LEN)
+            .ok_or(MarketplaceError::AccountNotInitialized)?;
+        Self::deserialize(&mut data).map_err(|_| MarketplaceError::AccountNotInitialized)
+    }
+}
+
+#[cfg(feature = "program")]
+mod program {
+    use solana_program::{
+        program_error::ProgramError,
+        program_pack::{IsInitialized, Pack, Sealed},
+    };
+
+    use super::{
+        Auction, AuthorityTransfer, Bundle, CollectionConfig, CollectionOffer, Listing,
+        Marketplace, MarketplaceTotals, MintConfig, MintRecord, Offer, ReferralConfig, Rental,
+        Stake, StakingConfig,
+    };
+
+    impl Sealed for Marketplace {}
+
+    impl IsInitialized for Marketplace {
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+    }
+
+    impl Pack for Marketplace {
+        const LEN: usize = Marketplace::LEN;
+
+        fn pack_into_slice(&self, dst: &mut [u8]) {
+            let data = borsh::to_vec(self).unwrap();
+            dst[..data.len()].copy_from_slice(&data);
+        }
+
+        fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+            Marketplace::from_account_data(src).map_err(|_| ProgramError::InvalidAccountData)
+        }
+    }
+    impl Sealed for Listing {}
+
+    impl IsInitialized for Listing {
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+    }
+
+    impl Pack for Listing {
+        const LEN: usize = Listing::LEN;
+
+        fn pack_into_slice(&self, dst: &mut [u8]) {
+            let data = borsh::to_vec(self).unwrap();
+            dst[..data.len()].copy_from_slice(&data);
+        }
+
+        fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+            Listing::from_account_data(src).map_err(|_| ProgramError::InvalidAccountData)
+        }
+    }
+    impl Sealed for Offer {}
+
+    impl IsInitialized for Offer {
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+    }
+
+    impl Pack for Offer {
+        const LEN: usize = Offer::LEN;
+
+        fn pack_into_slice(&self, dst: &mut [u8]) {
+            let data = borsh::to_vec(self).unwrap();
+            dst[..data.len()].copy_from_slice(&data);
+        }
+
+        fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+            Offer::from_account_data(src).map_err(|_| ProgramError::InvalidAccountData)
+        }
+    }
+    impl Sealed for Auction {}
+
+    impl IsInitialized for Auction {
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+    }
+
+    impl Pack for Auction {
+        const LEN: usize = Auction::LEN;
+
+        fn pack_into_slice(&self, dst: &mut [u8]) {
+            let data = borsh::to_vec(self).unwrap();
+            dst[..data.len()].copy_from_slice(&data);
+        }
+
+        fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+            Auction::from_account_data(src).map_err(|_| ProgramError::InvalidAccountData)
+        }
+    }
+    impl Sealed for CollectionOffer {}
+
+    impl IsInitialized for CollectionOffer {
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+    }
+
+    impl Pack for CollectionOffer {
+        const LEN: usize = CollectionOffer::LEN;
+
+        fn pack_into_slice(&self, dst: &mut [u8]) {
+            let data = borsh::to_vec(self).unwrap();
+            dst[..data.len()].copy_from_slice(&data);
+        }
+
+        fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+            CollectionOffer::from_account_data(src).map_err(|_| ProgramError::InvalidAccountData)
+        }
+    }
+    impl Sealed for AuthorityTransfer {}
+
+    impl IsInitialized for AuthorityTransfer {
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+    }
+
+    impl Pack for AuthorityTransfer {
+        const LEN: usize = AuthorityTransfer::LEN;
+
+        fn pack_into_slice(&self, dst: &mut [u8]) {
+            let data = borsh::to_vec(self).unwrap();
+            dst[..data.len()].copy_from_slice(&data);
+        }
+
+        fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+            AuthorityTransfer::from_account_data(src).map_err(|_| ProgramError::InvalidAccountData)
+        }
+    }
+    impl Sealed for CollectionConfig {}
+
+    impl IsInitialized for CollectionConfig {
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+    }
+
+    impl Pack for CollectionConfig {
+        const LEN: usize = CollectionConfig::LEN;
+
+        fn pack_into_slice(&self, dst: &mut [u8]) {
+            let data = borsh::to_vec(self).unwrap();
+            dst[..data.len()].copy_from_slice(&data);
+        }
+
+        fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+            CollectionConfig::from_account_data(src).map_err(|_| ProgramError::InvalidAccountData)
+        }
+    }
+    impl Sealed for MarketplaceTotals {}
+
+    impl IsInitialized for MarketplaceTotals {
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+    }
+
+    impl Pack for MarketplaceTotals {
+        const LEN: usize = MarketplaceTotals::LEN;
+
+        fn pack_into_slice(&self, dst: &mut [u8]) {
+            let data = borsh::to_vec(self).unwrap();
+            dst[..data.len()].copy_from_slice(&data);
+        }
+
+        fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+            MarketplaceTotals::from_account_data(src).map_err(|_| ProgramError::InvalidAccountData)
+        }
+    }
+    impl Sealed for ReferralConfig {}
+
+    impl IsInitialized for ReferralConfig {
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+    }
+
+    impl Pack for ReferralConfig {
+        const LEN: usize = ReferralConfig::LEN;
+
+        fn pack_into_slice(&self, dst: &mut [u8]) {
+            let data = borsh::to_vec(self).unwrap();
+            dst[..data.len()].copy_from_slice(&data);
+        }
+
+        fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+            ReferralConfig::from_account_data(src).map_err(|_| ProgramError::InvalidAccountData)
+        }
+    }
+    impl Sealed for Bundle {}
+
+    impl IsInitialized for Bundle {
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+    }
+
+    impl Pack for Bundle {
+        const LEN: usize = Bundle::LEN;
+
+        fn pack_into_slice(&self, dst: &mut [u8]) {
+            let data = borsh::to_vec(self).unwrap();
+            dst[..data.len()].copy_from_slice(&data);
+        }
+
+        fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+            Bundle::from_account_data(src).map_err(|_| ProgramError::InvalidAccountData)
+        }
+    }
+    impl Sealed for StakingConfig {}
+
+    impl IsInitialized for StakingConfig {
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+    }
+
+    impl Pack for StakingConfig {
+        const LEN: usize = StakingConfig::LEN;
+
+        fn pack_into_slice(&self, dst: &mut [u8]) {
+            let data = borsh::to_vec(self).unwrap();
+            dst[..data.len()].copy_from_slice(&data);
+        }
+
+        fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+            StakingConfig::from_account_data(src).map_err(|_| ProgramError::InvalidAccountData)
+        }
+    }
+    impl Sealed for Stake {}
+
+    impl IsInitialized for Stake {
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+    }
+
+    impl Pack for Stake {
+        const LEN: usize = Stake::LEN;
+
+        fn pack_into_slice(&self, dst: &mut [u8]) {
+            let data = borsh::to_vec(self).unwrap();
+            dst[..data.len()].copy_from_slice(&data);
+        }
+
+        fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+            Stake::from_account_data(src).map_err(|_| ProgramError::InvalidAccountData)
+        }
+    }
+    impl Sealed for Rental {}
+
+    impl IsInitialized for Rental {
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+    }
+
+    impl Pack for Rental {
+        const LEN: usize = Rental::LEN;
+
+        fn pack_into_slice(&self, dst: &mut [u8]) {
+            let data = borsh::to_vec(self).unwrap();
+            dst[..data.len()].copy_from_slice(&data);
+        }
+
+        fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+            Rental::from_account_data(src).map_err(|_| ProgramError::InvalidAccountData)
+        }
+    }
+    impl Sealed for MintConfig {}
+
+    impl IsInitialized for MintConfig {
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+    }
+
+    impl Pack for MintConfig {
+        const LEN: usize = MintConfig::LEN;
+
+        fn pack_into_slice(&self, dst: &mut [u8]) {
+            let data = borsh::to_vec(self).unwrap();
+            dst[..data.len()].copy_from_slice(&data);
+        }
+
+        fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+            MintConfig::from_account_data(src).map_err(|_| ProgramError::InvalidAccountData)
+        }
+    }
+    impl Sealed for MintRecord {}
+
+    impl IsInitialized for MintRecord {
+        fn is_initialized(&self) -> bool {
+            self.is_initialized
+        }
+    }
+
+    impl Pack for MintRecord {
+        const LEN: usize = MintRecord::LEN;
+
+        fn pack_into_slice(&self, dst: &mut [u8]) {
+            let data = borsh::to_vec(self).unwrap();
+            dst[..data.len()].copy_from_slice(&data);
+        }
+
+        fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+            MintRecord::from_account_data(src).map_err(|_| ProgramError::InvalidAccountData)
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! What off-chain code needs to call the program, in `solana-program` types:
+//! the instruction builders (`.into()` converts what they return), the
+//! `get_*_pda` helpers, account types and their sizes, and error codes.
+//! Depend on the crate with `default-features = false, features = ["client"]`
+//! to leave the processor out.
+//!
+//! ```
+//! use nft_marketplace::client::{self, get_offer_pda};
+//! use solana_program::{instruction::Instruction, pubkey::Pubkey};
+//!
+//! let program_id = Pubkey::new_unique();
+//! let (bidder, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+//! let (offer, _) = get_offer_pda(&program_id, &mint, &bidder);
+//!
+//! let ix: Instruction = client::cancel_offer(
+//!     &program_id.to_bytes(),
+//!     &bidder.to_bytes(),
+//!     &mint.to_bytes(),
+//!     &offer.to_bytes(),
+//! )
+//! .into();
+//! assert_eq!(ix.accounts[2].pubkey, offer);
+//! assert_eq!(client::OFFER_SIZE, 122);
+//! ```
+
+pub use crate::{error::MarketplaceError, instruction::*, state::*};
+pub use nft_marketplace_sdk::{events::MarketplaceEvent, pda};
+
+/// Account sizes, for rent exemption and `getProgramAccounts` `dataSize`
+/// filters
+pub const MARKETPLACE_SIZE: usize = Marketplace::LEN;
+pub const LISTING_SIZE: usize = Listing::LEN;
+pub const OFFER_SIZE: usize = Offer::LEN;
+pub const COLLECTION_OFFER_SIZE: usize = CollectionOffer::LEN;
+pub const AUCTION_SIZE: usize = Auction::LEN;
+pub const COLLECTION_CONFIG_SIZE: usize = CollectionConfig::LEN;
+pub const AUTHORITY_TRANSFER_SIZE: usize = AuthorityTransfer::LEN;
+pub const MARKETPLACE_TOTALS_SIZE: usize = MarketplaceTotals::LEN;
+pub const REFERRAL_CONFIG_SIZE: usize = ReferralConfig::LEN;
+pub const BUNDLE_SIZE: usize = Bundle::LEN;
+pub const STAKING_CONFIG_SIZE: usize = StakingConfig::LEN;
+pub const STAKE_SIZE: usize = Stake::LEN;
+pub const RENTAL_SIZE: usize = Rental::LEN;
+pub const MINT_CONFIG_SIZE: usize = MintConfig::LEN;
+pub const MINT_RECORD_SIZE: usize = MintRecord::LEN;
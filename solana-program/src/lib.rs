@@ -1,17 +1,23 @@
-use solana_program::{
-    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, pubkey::Pubkey,
-};
+#[cfg(feature = "processor")]
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
 
+#[cfg(feature = "client")]
+pub mod client;
 pub mod error;
 pub mod instruction;
+#[cfg(feature = "processor")]
 pub mod processor;
 pub mod state;
+#[cfg(feature = "processor")]
+pub mod validation;
 
+#[cfg(feature = "processor")]
 use crate::processor::Processor;
 
-#[cfg(not(feature = "no-entrypoint"))]
-entrypoint!(process_instruction);
+#[cfg(all(feature = "processor", not(feature = "no-entrypoint")))]
+solana_program::entrypoint!(process_instruction);
 
+#[cfg(feature = "processor")]
 pub fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
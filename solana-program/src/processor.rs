@@ -1,7 +1,38 @@
-use crate::{error::MarketplaceError, instruction::MarketplaceInstruction, state::Marketplace};
+use crate::{
+    error::MarketplaceError,
+    instruction::MarketplaceInstruction,
+    state::{
+        Auction, AuthorityTransfer, Bundle, CollectionConfig, CollectionOffer, Listing,
+        Marketplace, MarketplaceTotals, MintConfig, MintRecord, Offer, ReferralConfig, Rental,
+        Stake, StakingConfig,
+    },
+    validation,
+};
+use nft_marketplace_sdk::{
+    allowlist, bubblegum,
+    events::{
+        AuctionCreateEvent, AuctionSettleEvent, BidEvent, BundleDelistEvent, BundleListEvent,
+        BundleSaleEvent, CollectionOfferAcceptEvent, CollectionOfferCancelEvent,
+        CollectionOfferEvent, CompressedLeaf, DelistEvent, EditionPrintEvent, ListEvent,
+        MarketplaceEvent, MetadataUpdateEvent, MintEvent, OfferAcceptEvent, OfferCancelEvent,
+        OfferEvent, PrivateListEvent, ReferralEvent, RentEvent, RentalCancelEvent, RentalListEvent,
+        RentalReclaimEvent, RewardClaimEvent, SaleEvent, StakeEvent, UnstakeEvent,
+    },
+    ids::{
+        AUCTION_SEED, AUTHORITY_TRANSFER_SEED, BUBBLEGUM_ASSET_SEED, BUBBLEGUM_PROGRAM_ID,
+        BUNDLE_SEED, COLLECTION_CONFIG_SEED, COLLECTION_OFFER_SEED, LISTING_SEED, MARKETPLACE_SEED,
+        MARKETPLACE_STATS_SEED, MINT_CONFIG_SEED, MINT_RECORD_SEED, OFFER_SEED,
+        REFERRAL_CONFIG_SEED, RENTAL_SEED, STAKE_SEED, STAKING_CONFIG_SEED,
+        TOKEN_METADATA_PROGRAM_ID,
+    },
+    metadata::{self, Metadata},
+    state::{MAX_BUNDLE_SIZE, MIN_BUNDLE_SIZE},
+};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::Clock,
     entrypoint::ProgramResult,
+    hash::hashv,
     msg,
     program::{invoke, invoke_signed},
     program_error::ProgramError,
@@ -11,11 +42,19 @@ use solana_program::{
     system_instruction,
     sysvar::Sysvar,
 };
-use spl_associated_token_account::instruction::create_associated_token_account;
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::{create_associated_token_account, create_associated_token_account_idempotent},
+};
 use spl_token::{
-    instruction::{initialize_mint, mint_to},
+    instruction::{approve, initialize_mint, mint_to, revoke},
     state::Mint,
 };
+use spl_token_2022::{
+    extension::StateWithExtensions,
+    instruction::close_account,
+    state::{Account as TokenAccount, Mint as TokenMint},
+};
 
 pub struct Processor;
 
@@ -38,7 +77,220 @@ impl Processor {
             }
             MarketplaceInstruction::MintNft { name, symbol, uri } => {
                 msg!("Instruction: MintNft");
-                Self::process_mint_nft(program_id, accounts, name, symbol, uri)
+                Self::process_mint_nft(program_id, accounts, name, symbol, uri, Some(0))
+            }
+            MarketplaceInstruction::ListNft { price } => {
+                msg!("Instruction: ListNft");
+                Self::process_list_nft(program_id, accounts, price, None)
+            }
+            MarketplaceInstruction::BuyNft { expected_price } => {
+                msg!("Instruction: BuyNft");
+                Self::process_buy_nft(program_id, accounts, expected_price)
+            }
+            MarketplaceInstruction::CancelListing => {
+                msg!("Instruction: CancelListing");
+                Self::process_cancel_listing(program_id, accounts)
+            }
+            MarketplaceInstruction::MakeOffer { amount, expires_at } => {
+                msg!("Instruction: MakeOffer");
+                Self::process_make_offer(program_id, accounts, amount, expires_at)
+            }
+            MarketplaceInstruction::AcceptOffer { expected_amount } => {
+                msg!("Instruction: AcceptOffer");
+                Self::process_accept_offer(program_id, accounts, expected_amount)
+            }
+            MarketplaceInstruction::CancelOffer => {
+                msg!("Instruction: CancelOffer");
+                Self::process_cancel_offer(program_id, accounts)
+            }
+            MarketplaceInstruction::CreateAuction {
+                reserve_price,
+                end_time,
+            } => {
+                msg!("Instruction: CreateAuction");
+                Self::process_create_auction(program_id, accounts, reserve_price, end_time)
+            }
+            MarketplaceInstruction::PlaceBid { amount } => {
+                msg!("Instruction: PlaceBid");
+                Self::process_place_bid(program_id, accounts, amount)
+            }
+            MarketplaceInstruction::SettleAuction => {
+                msg!("Instruction: SettleAuction");
+                Self::process_settle_auction(program_id, accounts)
+            }
+            MarketplaceInstruction::MakeCollectionOffer { amount, expires_at } => {
+                msg!("Instruction: MakeCollectionOffer");
+                Self::process_make_collection_offer(program_id, accounts, amount, expires_at)
+            }
+            MarketplaceInstruction::AcceptCollectionOffer { expected_amount } => {
+                msg!("Instruction: AcceptCollectionOffer");
+                Self::process_accept_collection_offer(program_id, accounts, expected_amount)
+            }
+            MarketplaceInstruction::CancelCollectionOffer => {
+                msg!("Instruction: CancelCollectionOffer");
+                Self::process_cancel_collection_offer(program_id, accounts)
+            }
+            MarketplaceInstruction::MintCompressedNft { name, symbol, uri } => {
+                msg!("Instruction: MintCompressedNft");
+                Self::process_mint_compressed_nft(accounts, name, symbol, uri)
+            }
+            MarketplaceInstruction::UpdateNftMetadata { name, uri } => {
+                msg!("Instruction: UpdateNftMetadata");
+                Self::process_update_nft_metadata(accounts, name, uri)
+            }
+            MarketplaceInstruction::MintMasterEdition {
+                name,
+                symbol,
+                uri,
+                max_supply,
+            } => {
+                msg!("Instruction: MintMasterEdition");
+                Self::process_mint_nft(program_id, accounts, name, symbol, uri, max_supply)
+            }
+            MarketplaceInstruction::PrintEdition { edition } => {
+                msg!("Instruction: PrintEdition");
+                Self::process_print_edition(accounts, edition)
+            }
+            MarketplaceInstruction::SetFeeRecipient => {
+                msg!("Instruction: SetFeeRecipient");
+                Self::process_set_fee_recipient(program_id, accounts)
+            }
+            MarketplaceInstruction::ProposeMarketplaceAuthority => {
+                msg!("Instruction: ProposeMarketplaceAuthority");
+                Self::process_propose_marketplace_authority(program_id, accounts)
+            }
+            MarketplaceInstruction::AcceptMarketplaceAuthority => {
+                msg!("Instruction: AcceptMarketplaceAuthority");
+                Self::process_accept_marketplace_authority(program_id, accounts)
+            }
+            MarketplaceInstruction::CancelMarketplaceAuthorityTransfer => {
+                msg!("Instruction: CancelMarketplaceAuthorityTransfer");
+                Self::process_cancel_marketplace_authority_transfer(program_id, accounts)
+            }
+            MarketplaceInstruction::SetCollectionConfig {
+                fee_percentage,
+                enforce_royalties,
+            } => {
+                msg!("Instruction: SetCollectionConfig");
+                Self::process_set_collection_config(
+                    program_id,
+                    accounts,
+                    fee_percentage,
+                    enforce_royalties,
+                )
+            }
+            MarketplaceInstruction::InitializeMarketplaceStats => {
+                msg!("Instruction: InitializeMarketplaceStats");
+                Self::process_initialize_marketplace_stats(program_id, accounts)
+            }
+            MarketplaceInstruction::SetReferralShare { referral_share } => {
+                msg!("Instruction: SetReferralShare");
+                Self::process_set_referral_share(program_id, accounts, referral_share)
+            }
+            MarketplaceInstruction::ListBundle { bundle_id, price } => {
+                msg!("Instruction: ListBundle");
+                Self::process_list_bundle(program_id, accounts, bundle_id, price)
+            }
+            MarketplaceInstruction::BuyBundle { expected_price } => {
+                msg!("Instruction: BuyBundle");
+                Self::process_buy_bundle(program_id, accounts, expected_price)
+            }
+            MarketplaceInstruction::CancelBundle => {
+                msg!("Instruction: CancelBundle");
+                Self::process_cancel_bundle(program_id, accounts)
+            }
+            MarketplaceInstruction::ListNftPrivate {
+                price,
+                allowed_buyer,
+            } => {
+                msg!("Instruction: ListNftPrivate");
+                Self::process_list_nft(program_id, accounts, price, Some(allowed_buyer))
+            }
+            MarketplaceInstruction::ListNftDelegated { price } => {
+                msg!("Instruction: ListNftDelegated");
+                Self::process_list_nft_delegated(program_id, accounts, price)
+            }
+            MarketplaceInstruction::SetStakingConfig { reward_rate } => {
+                msg!("Instruction: SetStakingConfig");
+                Self::process_set_staking_config(program_id, accounts, reward_rate)
+            }
+            MarketplaceInstruction::StakeNft => {
+                msg!("Instruction: StakeNft");
+                Self::process_stake_nft(program_id, accounts)
+            }
+            MarketplaceInstruction::UnstakeNft => {
+                msg!("Instruction: UnstakeNft");
+                Self::process_unstake_nft(program_id, accounts)
+            }
+            MarketplaceInstruction::ClaimRewards => {
+                msg!("Instruction: ClaimRewards");
+                Self::process_claim_rewards(program_id, accounts)
+            }
+            MarketplaceInstruction::ListNftForRent {
+                daily_rate,
+                max_days,
+            } => {
+                msg!("Instruction: ListNftForRent");
+                Self::process_list_nft_for_rent(program_id, accounts, daily_rate, max_days)
+            }
+            MarketplaceInstruction::RentNft {
+                days,
+                expected_daily_rate,
+            } => {
+                msg!("Instruction: RentNft");
+                Self::process_rent_nft(program_id, accounts, days, expected_daily_rate)
+            }
+            MarketplaceInstruction::ReclaimRental => {
+                msg!("Instruction: ReclaimRental");
+                Self::process_reclaim_rental(program_id, accounts)
+            }
+            MarketplaceInstruction::CancelRental => {
+                msg!("Instruction: CancelRental");
+                Self::process_cancel_rental(program_id, accounts)
+            }
+            MarketplaceInstruction::CreateMintConfig {
+                config_id,
+                price,
+                max_supply,
+                go_live,
+                allowlist_root,
+                wallet_limit,
+                name_prefix,
+                symbol,
+                base_uri,
+            } => {
+                msg!("Instruction: CreateMintConfig");
+                Self::process_create_mint_config(
+                    program_id,
+                    accounts,
+                    config_id,
+                    price,
+                    max_supply,
+                    go_live,
+                    allowlist_root,
+                    wallet_limit,
+                    name_prefix,
+                    symbol,
+                    base_uri,
+                )
+            }
+            MarketplaceInstruction::MintFromConfig {
+                expected_price,
+                allocation,
+                allowlist_proof,
+            } => {
+                msg!("Instruction: MintFromConfig");
+                Self::process_mint_from_config(
+                    program_id,
+                    accounts,
+                    expected_price,
+                    allocation,
+                    &allowlist_proof,
+                )
+            }
+            MarketplaceInstruction::MigrateMarketplace => {
+                msg!("Instruction: MigrateMarketplace");
+                Self::process_migrate_marketplace(program_id, accounts)
             }
         }
     }
@@ -53,21 +305,16 @@ impl Processor {
         let marketplace_info = next_account_info(account_info_iter)?;
         let system_program_info = next_account_info(account_info_iter)?;
         let rent_info = next_account_info(account_info_iter)?;
+        let marketplace_stats_info = next_account_info(account_info_iter)?;
 
         // Validate fee percentage (max 10% = 1000 basis points)
         if fee_percentage > 1000 {
             return Err(MarketplaceError::InvalidFeePercentage.into());
         }
 
-        // Verify authority is signer
-        if !authority_info.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-
-        // Verify marketplace account is owned by system program (uninitialized)
-        if marketplace_info.owner != &solana_program::system_program::id() {
-            return Err(MarketplaceError::InvalidAccountOwner.into());
-        }
+        validation::check_signer(authority_info)?;
+        validation::check_system_program(system_program_info)?;
+        validation::check_uninitialized(marketplace_info)?;
 
         let rent = Rent::from_account_info(rent_info)?;
 
@@ -97,7 +344,7 @@ impl Processor {
                 system_program_info.clone(),
             ],
             &[&[
-                b"marketplace",
+                MARKETPLACE_SEED,
                 authority_info.key.as_ref(),
                 &[marketplace_bump],
             ]],
@@ -105,13 +352,22 @@ impl Processor {
 
         // Initialize marketplace data
         let marketplace = Marketplace::new(
-            *authority_info.key,
+            authority_info.key.to_bytes(),
             fee_percentage,
-            *authority_info.key, // Authority is also the fee recipient initially
+            authority_info.key.to_bytes(), // Authority is also the fee recipient initially
         );
 
         Marketplace::pack(marketplace, &mut marketplace_info.data.borrow_mut())?;
 
+        Self::create_marketplace_stats(
+            program_id,
+            authority_info,
+            marketplace_info,
+            marketplace_stats_info,
+            system_program_info,
+            &rent,
+        )?;
+
         msg!(
             "Marketplace initialized with fee percentage: {}",
             fee_percentage
@@ -120,7 +376,7 @@ impl Processor {
     }
 
     fn process_update_marketplace_fee(
-        _program_id: &Pubkey,
+        program_id: &Pubkey,
         accounts: &[AccountInfo],
         new_fee_percentage: u16,
     ) -> ProgramResult {
@@ -133,27 +389,376 @@ impl Processor {
             return Err(MarketplaceError::InvalidFeePercentage.into());
         }
 
-        // Verify authority is signer
-        if !authority_info.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+        let mut marketplace =
+            Self::load_marketplace_as_authority(program_id, marketplace_info, authority_info)?;
+
+        // Update fee percentage
+        marketplace.fee_percentage = new_fee_percentage;
+        Self::save_marketplace(marketplace, marketplace_info)?;
+
+        msg!("Marketplace fee updated to: {}", new_fee_percentage);
+        Ok(())
+    }
+
+    fn process_set_fee_recipient(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let fee_recipient_info = next_account_info(account_info_iter)?;
+
+        let mut marketplace =
+            Self::load_marketplace_as_authority(program_id, marketplace_info, authority_info)?;
+
+        marketplace.fee_recipient = fee_recipient_info.key.to_bytes();
+        Self::save_marketplace(marketplace, marketplace_info)?;
+
+        msg!(
+            "Marketplace fee recipient set to: {}",
+            fee_recipient_info.key
+        );
+        Ok(())
+    }
+
+    fn process_propose_marketplace_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let new_authority_info = next_account_info(account_info_iter)?;
+        let authority_transfer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        validation::check_system_program(system_program_info)?;
+
+        Self::load_marketplace_as_authority(program_id, marketplace_info, authority_info)?;
+
+        let (authority_transfer_pda, authority_transfer_bump) =
+            crate::state::get_authority_transfer_pda(program_id, marketplace_info.key);
+        if authority_transfer_pda != *authority_transfer_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        // A pending proposal is replaced; otherwise create the account
+        if authority_transfer_info.owner != program_id {
+            let rent = Rent::from_account_info(rent_info)?;
+            invoke_signed(
+                &system_instruction::create_account(
+                    authority_info.key,
+                    authority_transfer_info.key,
+                    rent.minimum_balance(AuthorityTransfer::LEN),
+                    AuthorityTransfer::LEN as u64,
+                    program_id,
+                ),
+                &[
+                    authority_info.clone(),
+                    authority_transfer_info.clone(),
+                    system_program_info.clone(),
+                ],
+                &[&[
+                    AUTHORITY_TRANSFER_SEED,
+                    marketplace_info.key.as_ref(),
+                    &[authority_transfer_bump],
+                ]],
+            )?;
         }
 
+        let authority_transfer = AuthorityTransfer::new(
+            marketplace_info.key.to_bytes(),
+            new_authority_info.key.to_bytes(),
+            authority_transfer_bump,
+        );
+        AuthorityTransfer::pack(
+            authority_transfer,
+            &mut authority_transfer_info.data.borrow_mut(),
+        )?;
+
+        msg!(
+            "Marketplace authority transfer proposed to: {}",
+            new_authority_info.key
+        );
+        Ok(())
+    }
+
+    fn process_accept_marketplace_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let new_authority_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let authority_transfer_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(new_authority_info)?;
+
         // Load marketplace data
-        let mut marketplace = Marketplace::unpack(&marketplace_info.data.borrow())?;
-        if !marketplace.is_initialized() {
-            return Err(MarketplaceError::AccountNotInitialized.into());
+        validation::check_owner(marketplace_info, program_id)?;
+        let mut marketplace = Self::read_marketplace(marketplace_info)?;
+
+        let authority_transfer =
+            Self::load_authority_transfer(program_id, authority_transfer_info, marketplace_info)?;
+
+        // Verify the signer is the proposed authority
+        if authority_transfer.new_authority != new_authority_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidMarketplaceAuthority.into());
         }
 
-        // Verify authority
-        if marketplace.authority != *authority_info.key {
+        // The outgoing authority paid the transfer account's rent
+        if marketplace.authority != authority_info.key.to_bytes() {
             return Err(MarketplaceError::InvalidMarketplaceAuthority.into());
         }
 
-        // Update fee percentage
-        marketplace.fee_percentage = new_fee_percentage;
-        Marketplace::pack(marketplace, &mut marketplace_info.data.borrow_mut())?;
+        marketplace.authority = authority_transfer.new_authority;
+        Self::save_marketplace(marketplace, marketplace_info)?;
+        Self::close_program_account(authority_transfer_info, authority_info)?;
 
-        msg!("Marketplace fee updated to: {}", new_fee_percentage);
+        msg!(
+            "Marketplace authority transferred to: {}",
+            new_authority_info.key
+        );
+        Ok(())
+    }
+
+    fn process_cancel_marketplace_authority_transfer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let authority_transfer_info = next_account_info(account_info_iter)?;
+
+        Self::load_marketplace_as_authority(program_id, marketplace_info, authority_info)?;
+        Self::load_authority_transfer(program_id, authority_transfer_info, marketplace_info)?;
+
+        // Refund the rent
+        Self::close_program_account(authority_transfer_info, authority_info)?;
+
+        msg!("Marketplace authority transfer cancelled");
+        Ok(())
+    }
+
+    fn process_migrate_marketplace(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        validation::check_system_program(system_program_info)?;
+
+        let mut marketplace =
+            Self::load_marketplace_as_authority(program_id, marketplace_info, authority_info)?;
+        if !marketplace.needs_migration() {
+            msg!("Marketplace already at version {}", marketplace.version);
+            return Ok(());
+        }
+
+        // Top up the rent for the new size, then grow the account. The new
+        // bytes are zeroed, which is what the reserved space reads as.
+        let rent = Rent::get()?;
+        let top_up = rent
+            .minimum_balance(Marketplace::LEN)
+            .saturating_sub(marketplace_info.lamports());
+        if top_up > 0 {
+            invoke(
+                &system_instruction::transfer(authority_info.key, marketplace_info.key, top_up),
+                &[
+                    authority_info.clone(),
+                    marketplace_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        }
+        marketplace_info.resize(Marketplace::LEN)?;
+        validation::check_rent_exempt(marketplace_info, &rent)?;
+
+        let from_version = marketplace.version;
+        marketplace.version = Marketplace::CURRENT_VERSION;
+        Self::save_marketplace(marketplace, marketplace_info)?;
+
+        msg!(
+            "Marketplace migrated from version {} to {}",
+            from_version,
+            Marketplace::CURRENT_VERSION
+        );
+        Ok(())
+    }
+
+    fn process_set_collection_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        fee_percentage: Option<u16>,
+        enforce_royalties: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let collection_mint_info = next_account_info(account_info_iter)?;
+        let collection_config_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        validation::check_system_program(system_program_info)?;
+
+        // Validate fee percentage (max 10% = 1000 basis points)
+        if fee_percentage.is_some_and(|fee_percentage| fee_percentage > 1000) {
+            return Err(MarketplaceError::InvalidFeePercentage.into());
+        }
+
+        Self::load_marketplace_as_authority(program_id, marketplace_info, authority_info)?;
+
+        let (collection_config_pda, collection_config_bump) =
+            crate::state::get_collection_config_pda(
+                program_id,
+                marketplace_info.key,
+                collection_mint_info.key,
+            );
+        if collection_config_pda != *collection_config_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        // Update an existing config; otherwise create the account
+        if collection_config_info.owner != program_id {
+            let rent = Rent::from_account_info(rent_info)?;
+            invoke_signed(
+                &system_instruction::create_account(
+                    authority_info.key,
+                    collection_config_info.key,
+                    rent.minimum_balance(CollectionConfig::LEN),
+                    CollectionConfig::LEN as u64,
+                    program_id,
+                ),
+                &[
+                    authority_info.clone(),
+                    collection_config_info.clone(),
+                    system_program_info.clone(),
+                ],
+                &[&[
+                    COLLECTION_CONFIG_SEED,
+                    marketplace_info.key.as_ref(),
+                    collection_mint_info.key.as_ref(),
+                    &[collection_config_bump],
+                ]],
+            )?;
+        }
+
+        let collection_config = CollectionConfig::new(
+            marketplace_info.key.to_bytes(),
+            collection_mint_info.key.to_bytes(),
+            fee_percentage,
+            enforce_royalties,
+            collection_config_bump,
+        );
+        CollectionConfig::pack(
+            collection_config,
+            &mut collection_config_info.data.borrow_mut(),
+        )?;
+
+        msg!(
+            "Collection config set for {}: fee percentage {:?}, enforce royalties {}",
+            collection_mint_info.key,
+            fee_percentage,
+            enforce_royalties
+        );
+        Ok(())
+    }
+
+    fn process_initialize_marketplace_stats(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let marketplace_stats_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(payer_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        // Verify the marketplace exists
+        validation::check_owner(marketplace_info, program_id)?;
+        Self::read_marketplace(marketplace_info)?;
+
+        validation::check_uninitialized(marketplace_stats_info)?;
+
+        let rent = Rent::from_account_info(rent_info)?;
+        Self::create_marketplace_stats(
+            program_id,
+            payer_info,
+            marketplace_info,
+            marketplace_stats_info,
+            system_program_info,
+            &rent,
+        )?;
+
+        msg!("Marketplace stats initialized for {}", marketplace_info.key);
+        Ok(())
+    }
+
+    fn process_set_referral_share(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        referral_share: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let referral_config_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        validation::check_system_program(system_program_info)?;
+
+        // The share is of the fee, so at most all of it
+        if referral_share > 10000 {
+            return Err(MarketplaceError::InvalidFeePercentage.into());
+        }
+
+        Self::load_marketplace_as_authority(program_id, marketplace_info, authority_info)?;
+
+        let (referral_config_pda, referral_config_bump) =
+            crate::state::get_referral_config_pda(program_id, marketplace_info.key);
+        if referral_config_pda != *referral_config_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        // Update an existing config; otherwise create the account
+        if referral_config_info.owner != program_id {
+            let rent = Rent::from_account_info(rent_info)?;
+            invoke_signed(
+                &system_instruction::create_account(
+                    authority_info.key,
+                    referral_config_info.key,
+                    rent.minimum_balance(ReferralConfig::LEN),
+                    ReferralConfig::LEN as u64,
+                    program_id,
+                ),
+                &[
+                    authority_info.clone(),
+                    referral_config_info.clone(),
+                    system_program_info.clone(),
+                ],
+                &[&[
+                    REFERRAL_CONFIG_SEED,
+                    marketplace_info.key.as_ref(),
+                    &[referral_config_bump],
+                ]],
+            )?;
+        }
+
+        let referral_config = ReferralConfig::new(
+            marketplace_info.key.to_bytes(),
+            referral_share,
+            referral_config_bump,
+        );
+        ReferralConfig::pack(referral_config, &mut referral_config_info.data.borrow_mut())?;
+
+        msg!("Referral share set to: {}", referral_share);
         Ok(())
     }
 
@@ -163,6 +768,7 @@ impl Processor {
         name: String,
         symbol: String,
         uri: String,
+        max_supply: Option<u64>,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let mint_authority_info = next_account_info(account_info_iter)?;
@@ -172,109 +778,4303 @@ impl Processor {
         let associated_token_program_info = next_account_info(account_info_iter)?;
         let system_program_info = next_account_info(account_info_iter)?;
         let rent_info = next_account_info(account_info_iter)?;
+        let metadata_info = next_account_info(account_info_iter)?;
+        let master_edition_info = next_account_info(account_info_iter)?;
+        let token_metadata_program_info = next_account_info(account_info_iter)?;
+        // Optional collection to set and verify the NFT into
+        let collection_mint_info = next_account_info(account_info_iter).ok();
 
-        // Verify mint authority is signer
-        if !mint_authority_info.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
+        validation::check_signer(mint_authority_info)?;
+        // The mint is created with SPL Token's layout
+        validation::check_spl_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+        validation::check_token_metadata_program(token_metadata_program_info)?;
+        validation::check_uninitialized(mint_info)?;
 
-        // Verify mint account is owned by system program (uninitialized)
-        if mint_info.owner != &solana_program::system_program::id() {
-            return Err(MarketplaceError::InvalidAccountOwner.into());
+        // The metadata and master edition are the mint's Metaplex PDAs
+        let (metadata_pda, _) = crate::state::get_metadata_pda(mint_info.key);
+        let (master_edition_pda, _) = crate::state::get_master_edition_pda(mint_info.key);
+        if metadata_pda != *metadata_info.key || master_edition_pda != *master_edition_info.key {
+            return Err(ProgramError::InvalidSeeds);
         }
 
-        let rent = Rent::from_account_info(rent_info)?;
+        Self::create_nft_mint(
+            mint_authority_info,
+            mint_info,
+            associated_token_account_info,
+            token_program_info,
+            associated_token_program_info,
+            system_program_info,
+            rent_info,
+        )?;
 
-        // Calculate required space and rent for mint account
-        let mint_space = Mint::LEN;
-        let mint_rent = rent.minimum_balance(mint_space);
+        let mint = mint_info.key.to_bytes();
+        let mint_authority = mint_authority_info.key.to_bytes();
+        let metadata_account = metadata_info.key.to_bytes();
 
-        // Create mint account
+        // Create the Metaplex metadata, with the mint authority as update authority
         invoke(
-            &system_instruction::create_account(
-                mint_authority_info.key,
-                mint_info.key,
-                mint_rent,
-                mint_space as u64,
-                token_program_info.key,
-            ),
+            &metadata::create_metadata_account_v3(
+                &metadata_account,
+                &mint,
+                &mint_authority,
+                &mint_authority,
+                &mint_authority,
+                name.clone(),
+                symbol.clone(),
+                uri.clone(),
+            )
+            .into(),
             &[
-                mint_authority_info.clone(),
+                metadata_info.clone(),
                 mint_info.clone(),
+                mint_authority_info.clone(),
                 system_program_info.clone(),
-            ],
-        )?;
-
-        // Initialize mint account with 0 decimals for NFT
-        invoke(
-            &initialize_mint(
-                token_program_info.key,
-                mint_info.key,
-                mint_authority_info.key,
-                Some(mint_authority_info.key), // freeze authority
-                0,                             // 0 decimals for NFT
-            )?,
-            &[
-                mint_info.clone(),
                 rent_info.clone(),
-                token_program_info.clone(),
+                token_metadata_program_info.clone(),
             ],
         )?;
 
-        // Create associated token account
+        // Create the master edition. The metadata program takes over the mint
+        // and freeze authorities, locking the supply at 1; `max_supply` caps
+        // the editions that can be printed from it.
         invoke(
-            &create_associated_token_account(
-                mint_authority_info.key,
-                mint_authority_info.key,
-                mint_info.key,
-                token_program_info.key,
-            ),
+            &metadata::create_master_edition_v3(
+                &master_edition_info.key.to_bytes(),
+                &mint,
+                &mint_authority,
+                &mint_authority,
+                &mint_authority,
+                &metadata_account,
+                max_supply,
+            )
+            .into(),
             &[
-                mint_authority_info.clone(),
-                associated_token_account_info.clone(),
-                mint_authority_info.clone(),
+                master_edition_info.clone(),
                 mint_info.clone(),
-                system_program_info.clone(),
+                mint_authority_info.clone(),
+                metadata_info.clone(),
                 token_program_info.clone(),
-                associated_token_program_info.clone(),
+                system_program_info.clone(),
+                rent_info.clone(),
+                token_metadata_program_info.clone(),
             ],
         )?;
 
-        // Mint 1 token (NFT) to the associated token account
-        invoke(
-            &mint_to(
-                token_program_info.key,
-                mint_info.key,
-                associated_token_account_info.key,
-                mint_authority_info.key,
-                &[mint_authority_info.key],
-                1, // Mint 1 NFT
-            )?,
+        // Set and verify the collection; the metadata program checks the
+        // authority against the collection's metadata
+        if let Some(collection_mint_info) = collection_mint_info {
+            let collection_metadata_info = next_account_info(account_info_iter)?;
+            let collection_master_edition_info = next_account_info(account_info_iter)?;
+            let collection_authority_info = next_account_info(account_info_iter)?;
+
+            invoke(
+                &metadata::set_and_verify_collection(
+                    &metadata_account,
+                    &collection_authority_info.key.to_bytes(),
+                    &mint_authority,
+                    &mint_authority,
+                    &collection_mint_info.key.to_bytes(),
+                    &collection_metadata_info.key.to_bytes(),
+                    &collection_master_edition_info.key.to_bytes(),
+                )
+                .into(),
+                &[
+                    metadata_info.clone(),
+                    collection_authority_info.clone(),
+                    mint_authority_info.clone(),
+                    collection_mint_info.clone(),
+                    collection_metadata_info.clone(),
+                    collection_master_edition_info.clone(),
+                    token_metadata_program_info.clone(),
+                ],
+            )?;
+        }
+
+        // Emit event for indexer
+        MarketplaceEvent::NftMinted(MintEvent {
+            mint: mint_info.key.to_bytes(),
+            name: name.clone(),
+            symbol: symbol.clone(),
+            uri: uri.clone(),
+            creator: mint_authority_info.key.to_bytes(),
+            collection: collection_mint_info.map(|info| info.key.to_bytes()),
+            compressed: None,
+        })
+        .emit();
+
+        msg!(
+            "NFT minted successfully! Name: {}, Symbol: {}, URI: {}, Mint: {}",
+            name,
+            symbol,
+            uri,
+            mint_info.key
+        );
+        Ok(())
+    }
+
+    fn process_mint_compressed_nft(
+        accounts: &[AccountInfo],
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let minter_info = next_account_info(account_info_iter)?;
+        let tree_delegate_info = next_account_info(account_info_iter)?;
+        let tree_config_info = next_account_info(account_info_iter)?;
+        let merkle_tree_info = next_account_info(account_info_iter)?;
+        let log_wrapper_info = next_account_info(account_info_iter)?;
+        let compression_program_info = next_account_info(account_info_iter)?;
+        let bubblegum_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(minter_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        // The new leaf's index, and so its asset id, is the tree's mint count so far
+        let bubblegum_program_id = Pubkey::new_from_array(BUBBLEGUM_PROGRAM_ID);
+        if tree_config_info.owner != &bubblegum_program_id {
+            return Err(MarketplaceError::InvalidAccountOwner.into());
+        }
+        let leaf_index = bubblegum::tree_config_num_minted(&tree_config_info.data.borrow())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let (asset_id, _) = Pubkey::find_program_address(
             &[
-                mint_info.clone(),
-                associated_token_account_info.clone(),
-                mint_authority_info.clone(),
-                token_program_info.clone(),
+                BUBBLEGUM_ASSET_SEED,
+                merkle_tree_info.key.as_ref(),
+                &leaf_index.to_le_bytes(),
+            ],
+            &bubblegum_program_id,
+        );
+
+        // Mint the leaf; Bubblegum checks the tree config and delegate
+        let minter = minter_info.key.to_bytes();
+        invoke(
+            &bubblegum::mint_v1(
+                &tree_config_info.key.to_bytes(),
+                &minter,
+                &minter,
+                &merkle_tree_info.key.to_bytes(),
+                &minter,
+                &tree_delegate_info.key.to_bytes(),
+                &log_wrapper_info.key.to_bytes(),
+                name.clone(),
+                symbol.clone(),
+                uri.clone(),
+            )
+            .into(),
+            &[
+                tree_config_info.clone(),
+                minter_info.clone(),
+                merkle_tree_info.clone(),
+                tree_delegate_info.clone(),
+                log_wrapper_info.clone(),
+                compression_program_info.clone(),
+                system_program_info.clone(),
+                bubblegum_program_info.clone(),
             ],
         )?;
 
         // Emit event for indexer
-        msg!(
-            "NFT_MINTED:{{\"mint\":\"{}\",\"name\":\"{}\",\"symbol\":\"{}\",\"uri\":\"{}\",\"creator\":\"{}\"}}",
-            mint_info.key,
+        MarketplaceEvent::NftMinted(MintEvent {
+            mint: asset_id.to_bytes(),
             name,
             symbol,
             uri,
-            mint_authority_info.key
+            creator: minter_info.key.to_bytes(),
+            collection: None,
+            compressed: Some(CompressedLeaf {
+                tree: merkle_tree_info.key.to_bytes(),
+                leaf_index,
+            }),
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_update_nft_metadata(
+        accounts: &[AccountInfo],
+        name: Option<String>,
+        uri: Option<String>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let update_authority_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let metadata_info = next_account_info(account_info_iter)?;
+        let token_metadata_program_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(update_authority_info)?;
+        validation::check_token_metadata_program(token_metadata_program_info)?;
+
+        // Validate the new strings against the metadata program's limits
+        if name.is_none() && uri.is_none() {
+            return Err(MarketplaceError::InvalidMetadata.into());
+        }
+        if let Some(name) = &name {
+            if name.is_empty() || name.len() > metadata::MAX_NAME_LENGTH {
+                return Err(MarketplaceError::InvalidMetadata.into());
+            }
+        }
+        if let Some(uri) = &uri {
+            if uri.len() > metadata::MAX_URI_LENGTH {
+                return Err(MarketplaceError::InvalidMetadata.into());
+            }
+        }
+
+        // Verify the metadata belongs to the mint
+        if metadata_info.owner.to_bytes() != TOKEN_METADATA_PROGRAM_ID {
+            return Err(MarketplaceError::InvalidAccountOwner.into());
+        }
+        let (metadata_pda, _) = crate::state::get_metadata_pda(nft_mint_info.key);
+        if metadata_pda != *metadata_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let current = Metadata::from_account_data(&metadata_info.data.borrow())
+            .ok_or(MarketplaceError::InvalidMetadata)?;
+        if current.mint != nft_mint_info.key.to_bytes() || !current.is_mutable {
+            return Err(MarketplaceError::InvalidMetadata.into());
+        }
+
+        // Verify the signer is the metadata's update authority
+        if current.update_authority != update_authority_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidUpdateAuthority.into());
+        }
+
+        // Replace the data as a whole, keeping everything but the new fields
+        let mut data = current.data;
+        if let Some(name) = name {
+            data.name = name;
+        }
+        if let Some(uri) = uri {
+            data.uri = uri;
+        }
+        let (name, uri) = (data.name.clone(), data.uri.clone());
+
+        invoke(
+            &metadata::update_metadata_account_v2(
+                &metadata_info.key.to_bytes(),
+                &current.update_authority,
+                data,
+            )
+            .into(),
+            &[
+                metadata_info.clone(),
+                update_authority_info.clone(),
+                token_metadata_program_info.clone(),
+            ],
+        )?;
+
+        // Emit event for indexer
+        MarketplaceEvent::NftMetadataUpdated(MetadataUpdateEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            name,
+            uri,
+            update_authority: update_authority_info.key.to_bytes(),
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_print_edition(accounts: &[AccountInfo], edition: u64) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let holder_info = next_account_info(account_info_iter)?;
+        let new_mint_info = next_account_info(account_info_iter)?;
+        let new_token_account_info = next_account_info(account_info_iter)?;
+        let new_metadata_info = next_account_info(account_info_iter)?;
+        let new_edition_info = next_account_info(account_info_iter)?;
+        let master_mint_info = next_account_info(account_info_iter)?;
+        let master_edition_info = next_account_info(account_info_iter)?;
+        let master_metadata_info = next_account_info(account_info_iter)?;
+        let master_token_account_info = next_account_info(account_info_iter)?;
+        let edition_marker_info = next_account_info(account_info_iter)?;
+        let update_authority_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+        let token_metadata_program_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(holder_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+        validation::check_token_metadata_program(token_metadata_program_info)?;
+
+        // Editions are numbered from 1
+        if edition == 0 {
+            return Err(MarketplaceError::InvalidInstruction.into());
+        }
+
+        validation::check_uninitialized(new_mint_info)?;
+
+        // The new metadata's update authority must be the master's
+        if master_metadata_info.owner.to_bytes() != TOKEN_METADATA_PROGRAM_ID {
+            return Err(MarketplaceError::InvalidAccountOwner.into());
+        }
+        let (master_metadata_pda, _) = crate::state::get_metadata_pda(master_mint_info.key);
+        if master_metadata_pda != *master_metadata_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let master_metadata = Metadata::from_account_data(&master_metadata_info.data.borrow())
+            .ok_or(MarketplaceError::InvalidMetadata)?;
+        if master_metadata.update_authority != update_authority_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidUpdateAuthority.into());
+        }
+
+        // Each marker records 248 editions; the metadata program creates it
+        // on the first print it covers and refuses editions already marked
+        let (edition_marker_pda, _) =
+            crate::state::get_edition_marker_pda(master_mint_info.key, edition);
+        if edition_marker_pda != *edition_marker_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        // The print needs exactly one token before the metadata program takes
+        // over its mint
+        Self::create_nft_mint(
+            holder_info,
+            new_mint_info,
+            new_token_account_info,
+            token_program_info,
+            associated_token_program_info,
+            system_program_info,
+            rent_info,
+        )?;
+
+        // Print the edition; the metadata program checks the holder's master
+        // edition token and the max supply
+        let holder = holder_info.key.to_bytes();
+        invoke(
+            &metadata::mint_new_edition_from_master_edition_via_token(
+                &new_metadata_info.key.to_bytes(),
+                &new_edition_info.key.to_bytes(),
+                &master_edition_info.key.to_bytes(),
+                &new_mint_info.key.to_bytes(),
+                &edition_marker_info.key.to_bytes(),
+                &holder,
+                &holder,
+                &holder,
+                &master_token_account_info.key.to_bytes(),
+                &master_metadata.update_authority,
+                &master_metadata_info.key.to_bytes(),
+                edition,
+            )
+            .into(),
+            &[
+                new_metadata_info.clone(),
+                new_edition_info.clone(),
+                master_edition_info.clone(),
+                new_mint_info.clone(),
+                edition_marker_info.clone(),
+                holder_info.clone(),
+                master_token_account_info.clone(),
+                update_authority_info.clone(),
+                master_metadata_info.clone(),
+                token_program_info.clone(),
+                system_program_info.clone(),
+                rent_info.clone(),
+                token_metadata_program_info.clone(),
+            ],
+        )?;
+
+        // Emit event for indexer
+        MarketplaceEvent::EditionPrinted(EditionPrintEvent {
+            mint: new_mint_info.key.to_bytes(),
+            master_mint: master_mint_info.key.to_bytes(),
+            edition,
+            owner: holder_info.key.to_bytes(),
+        })
+        .emit();
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_create_mint_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        config_id: u64,
+        price: u64,
+        max_supply: u64,
+        go_live: i64,
+        allowlist_root: Option<[u8; 32]>,
+        wallet_limit: u16,
+        name_prefix: String,
+        symbol: String,
+        base_uri: String,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let creator_info = next_account_info(account_info_iter)?;
+        let mint_config_info = next_account_info(account_info_iter)?;
+        let treasury_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(creator_info)?;
+        validation::check_system_program(system_program_info)?;
+        validation::check_uninitialized(mint_config_info)?;
+
+        if max_supply == 0 {
+            return Err(MarketplaceError::InvalidInstruction.into());
+        }
+
+        let (mint_config_pda, mint_config_bump) =
+            crate::state::get_mint_config_pda(program_id, creator_info.key, config_id);
+        if mint_config_pda != *mint_config_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let mint_config = MintConfig::new(
+            creator_info.key.to_bytes(),
+            treasury_info.key.to_bytes(),
+            config_id,
+            price,
+            max_supply,
+            go_live,
+            allowlist_root,
+            wallet_limit,
+            mint_config_bump,
+            name_prefix,
+            symbol,
+            base_uri,
         );
+        // Every NFT of the drop must get a name, symbol and URI the metadata
+        // program accepts
+        if !mint_config.fits_metadata_limits() {
+            return Err(MarketplaceError::InvalidMetadata.into());
+        }
+
+        let rent = Rent::from_account_info(rent_info)?;
+        invoke_signed(
+            &system_instruction::create_account(
+                creator_info.key,
+                mint_config_info.key,
+                rent.minimum_balance(MintConfig::LEN),
+                MintConfig::LEN as u64,
+                program_id,
+            ),
+            &[
+                creator_info.clone(),
+                mint_config_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[
+                MINT_CONFIG_SEED,
+                creator_info.key.as_ref(),
+                &config_id.to_le_bytes(),
+                &[mint_config_bump],
+            ]],
+        )?;
+
+        MintConfig::pack(mint_config, &mut mint_config_info.data.borrow_mut())?;
 
         msg!(
-            "NFT minted successfully! Name: {}, Symbol: {}, URI: {}, Mint: {}",
-            name,
+            "Mint config {} created: {} NFTs at {} lamports from {}",
+            mint_config_info.key,
+            max_supply,
+            price,
+            go_live
+        );
+        Ok(())
+    }
+
+    fn process_mint_from_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        expected_price: u64,
+        allocation: u64,
+        allowlist_proof: &[[u8; 32]],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let minter_info = next_account_info(account_info_iter)?;
+        let mint_config_info = next_account_info(account_info_iter)?;
+        let treasury_info = next_account_info(account_info_iter)?;
+        let mint_record_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let minter_token_account_info = next_account_info(account_info_iter)?;
+        let metadata_info = next_account_info(account_info_iter)?;
+        let master_edition_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let token_metadata_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(minter_info)?;
+        // The mint is created with SPL Token's layout
+        validation::check_spl_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_token_metadata_program(token_metadata_program_info)?;
+        validation::check_system_program(system_program_info)?;
+        validation::check_uninitialized(mint_info)?;
+
+        // Load mint config data
+        if mint_config_info.owner != program_id {
+            return Err(MarketplaceError::AccountNotInitialized.into());
+        }
+        let mut mint_config = MintConfig::unpack(&mint_config_info.data.borrow())?;
+        let creator = mint_config.creator;
+        let config_id = mint_config.config_id.to_le_bytes();
+        let mint_config_bump = [mint_config.bump];
+        let mint_config_seeds: &[&[u8]] =
+            &[MINT_CONFIG_SEED, &creator, &config_id, &mint_config_bump];
+        validation::check_pda(mint_config_info, mint_config_seeds, program_id)?;
+        if mint_config.treasury != treasury_info.key.to_bytes() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if mint_config.price != expected_price {
+            return Err(MarketplaceError::ExpectedAmountMismatch.into());
+        }
+        if Clock::get()?.unix_timestamp < mint_config.go_live {
+            return Err(MarketplaceError::MintNotLive.into());
+        }
+        if mint_config.is_sold_out() {
+            return Err(MarketplaceError::MintSoldOut.into());
+        }
+        let mut wallet_limit = match mint_config.wallet_limit {
+            0 => u64::MAX,
+            limit => u64::from(limit),
+        };
+        if let Some(allowlist_root) = mint_config.allowlist_root {
+            if !Self::verify_allowlist_proof(
+                minter_info.key,
+                allocation,
+                allowlist_proof,
+                &allowlist_root,
+            ) {
+                return Err(MarketplaceError::NotOnAllowlist.into());
+            }
+            wallet_limit = wallet_limit.min(allocation);
+        }
+
+        // The metadata and master edition are the mint's Metaplex PDAs
+        let (metadata_pda, _) = crate::state::get_metadata_pda(mint_info.key);
+        let (master_edition_pda, _) = crate::state::get_master_edition_pda(mint_info.key);
+        if metadata_pda != *metadata_info.key || master_edition_pda != *master_edition_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        // Count the NFT against the minter's limit, creating their mint record
+        // on their first mint from the config
+        let (mint_record_pda, mint_record_bump) =
+            crate::state::get_mint_record_pda(program_id, mint_config_info.key, minter_info.key);
+        if mint_record_pda != *mint_record_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let mut mint_record = if mint_record_info.owner != program_id {
+            let rent = Rent::from_account_info(rent_info)?;
+            invoke_signed(
+                &system_instruction::create_account(
+                    minter_info.key,
+                    mint_record_info.key,
+                    rent.minimum_balance(MintRecord::LEN),
+                    MintRecord::LEN as u64,
+                    program_id,
+                ),
+                &[
+                    minter_info.clone(),
+                    mint_record_info.clone(),
+                    system_program_info.clone(),
+                ],
+                &[&[
+                    MINT_RECORD_SEED,
+                    mint_config_info.key.as_ref(),
+                    minter_info.key.as_ref(),
+                    &[mint_record_bump],
+                ]],
+            )?;
+            MintRecord::new(
+                mint_config_info.key.to_bytes(),
+                minter_info.key.to_bytes(),
+                mint_record_bump,
+            )
+        } else {
+            MintRecord::unpack(&mint_record_info.data.borrow())?
+        };
+        if u64::from(mint_record.minted) >= wallet_limit {
+            return Err(MarketplaceError::WalletLimitReached.into());
+        }
+        mint_record.minted = mint_record
+            .minted
+            .checked_add(1)
+            .ok_or(MarketplaceError::AmountOverflow)?;
+        MintRecord::pack(mint_record, &mut mint_record_info.data.borrow_mut())?;
+
+        // Pay the mint price to the treasury
+        if mint_config.price > 0 {
+            invoke(
+                &system_instruction::transfer(
+                    minter_info.key,
+                    treasury_info.key,
+                    mint_config.price,
+                ),
+                &[
+                    minter_info.clone(),
+                    treasury_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        }
+
+        mint_config.minted = mint_config
+            .minted
+            .checked_add(1)
+            .ok_or(MarketplaceError::AmountOverflow)?;
+        let number = mint_config.minted;
+        let max_supply = mint_config.max_supply;
+        let name = mint_config.nft_name(number);
+        let symbol = mint_config.symbol.clone();
+        let uri = mint_config.nft_uri(number);
+        MintConfig::pack(mint_config, &mut mint_config_info.data.borrow_mut())?;
+
+        Self::create_nft_mint(
+            minter_info,
+            mint_info,
+            minter_token_account_info,
+            token_program_info,
+            associated_token_program_info,
+            system_program_info,
+            rent_info,
+        )?;
+
+        let mint = mint_info.key.to_bytes();
+        let minter = minter_info.key.to_bytes();
+        let metadata_account = metadata_info.key.to_bytes();
+        let update_authority = mint_config_info.key.to_bytes();
+
+        // Create the metadata and master edition with the mint config signing
+        // as update authority
+        invoke_signed(
+            &metadata::create_metadata_account_v3(
+                &metadata_account,
+                &mint,
+                &minter,
+                &minter,
+                &update_authority,
+                name.clone(),
+                symbol.clone(),
+                uri.clone(),
+            )
+            .into(),
+            &[
+                metadata_info.clone(),
+                mint_info.clone(),
+                minter_info.clone(),
+                mint_config_info.clone(),
+                system_program_info.clone(),
+                rent_info.clone(),
+                token_metadata_program_info.clone(),
+            ],
+            &[mint_config_seeds],
+        )?;
+        invoke_signed(
+            &metadata::create_master_edition_v3(
+                &master_edition_info.key.to_bytes(),
+                &mint,
+                &update_authority,
+                &minter,
+                &minter,
+                &metadata_account,
+                Some(0),
+            )
+            .into(),
+            &[
+                master_edition_info.clone(),
+                mint_info.clone(),
+                mint_config_info.clone(),
+                minter_info.clone(),
+                metadata_info.clone(),
+                token_program_info.clone(),
+                system_program_info.clone(),
+                rent_info.clone(),
+                token_metadata_program_info.clone(),
+            ],
+            &[mint_config_seeds],
+        )?;
+
+        // Emit event for indexer
+        MarketplaceEvent::NftMinted(MintEvent {
+            mint,
+            name: name.clone(),
             symbol,
             uri,
+            creator: minter,
+            collection: None,
+            compressed: None,
+        })
+        .emit();
+
+        msg!(
+            "NFT {} of {} minted from {}: {}, Mint: {}",
+            number,
+            max_supply,
+            mint_config_info.key,
+            name,
             mint_info.key
         );
         Ok(())
     }
+
+    /// Whether `proof` leads from the allowlist leaf of `wallet` and
+    /// `allocation` to `root`, hashing through the syscall. The scheme is the
+    /// SDK's, which the backend builds its trees with.
+    fn verify_allowlist_proof(
+        wallet: &Pubkey,
+        allocation: u64,
+        proof: &[[u8; 32]],
+        root: &[u8; 32],
+    ) -> bool {
+        allowlist::verify_proof(
+            |values| hashv(values).to_bytes(),
+            &wallet.to_bytes(),
+            allocation,
+            proof,
+            root,
+        )
+    }
+
+    fn process_list_nft(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        price: u64,
+        allowed_buyer: Option<[u8; 32]>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let seller_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let seller_token_account_info = next_account_info(account_info_iter)?;
+        let listing_info = next_account_info(account_info_iter)?;
+        let escrow_token_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+        let marketplace_stats_info = next_account_info(account_info_iter)?;
+
+        if price == 0 {
+            return Err(MarketplaceError::InvalidPrice.into());
+        }
+
+        validation::check_signer(seller_info)?;
+        validation::check_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+        validation::check_token_account(
+            seller_token_account_info,
+            seller_info.key,
+            nft_mint_info.key,
+        )?;
+
+        // Verify the marketplace exists
+        validation::check_owner(marketplace_info, program_id)?;
+        Self::read_marketplace(marketplace_info)?;
+
+        validation::check_uninitialized(listing_info)?;
+
+        let (listing_pda, listing_bump) =
+            crate::state::get_listing_pda(program_id, nft_mint_info.key, seller_info.key);
+
+        if listing_pda != *listing_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let rent = Rent::from_account_info(rent_info)?;
+
+        // Create listing account
+        invoke_signed(
+            &system_instruction::create_account(
+                seller_info.key,
+                listing_info.key,
+                rent.minimum_balance(Listing::LEN),
+                Listing::LEN as u64,
+                program_id,
+            ),
+            &[
+                seller_info.clone(),
+                listing_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[
+                LISTING_SEED,
+                nft_mint_info.key.as_ref(),
+                seller_info.key.as_ref(),
+                &[listing_bump],
+            ]],
+        )?;
+
+        // Create the escrow token account, owned by the listing PDA
+        invoke(
+            &create_associated_token_account(
+                seller_info.key,
+                listing_info.key,
+                nft_mint_info.key,
+                token_program_info.key,
+            ),
+            &[
+                seller_info.clone(),
+                escrow_token_account_info.clone(),
+                listing_info.clone(),
+                nft_mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+
+        // Move the NFT into escrow
+        Self::transfer_nft(
+            token_program_info,
+            seller_token_account_info,
+            nft_mint_info,
+            escrow_token_account_info,
+            seller_info,
+            account_info_iter.as_slice(),
+            &[],
+        )?;
+
+        let listing = Listing::new(
+            marketplace_info.key.to_bytes(),
+            seller_info.key.to_bytes(),
+            nft_mint_info.key.to_bytes(),
+            price,
+            Clock::get()?.unix_timestamp,
+            listing_bump,
+            allowed_buyer,
+        );
+        Listing::pack(listing, &mut listing_info.data.borrow_mut())?;
+
+        let mut marketplace_stats =
+            Self::load_marketplace_stats(program_id, marketplace_stats_info, marketplace_info)?;
+        marketplace_stats.record_listing()?;
+        MarketplaceTotals::pack(
+            marketplace_stats,
+            &mut marketplace_stats_info.data.borrow_mut(),
+        )?;
+
+        // Emit event for indexer
+        match allowed_buyer {
+            Some(allowed_buyer) => MarketplaceEvent::NftListedPrivately(PrivateListEvent {
+                mint: nft_mint_info.key.to_bytes(),
+                seller: seller_info.key.to_bytes(),
+                price,
+                listing: listing_info.key.to_bytes(),
+                allowed_buyer,
+            }),
+            None => MarketplaceEvent::NftListed(ListEvent {
+                mint: nft_mint_info.key.to_bytes(),
+                seller: seller_info.key.to_bytes(),
+                price,
+                listing: listing_info.key.to_bytes(),
+            }),
+        }
+        .emit();
+        Ok(())
+    }
+
+    fn process_list_nft_delegated(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        price: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let seller_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let seller_token_account_info = next_account_info(account_info_iter)?;
+        let listing_info = next_account_info(account_info_iter)?;
+        let nft_edition_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let token_metadata_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+        let marketplace_stats_info = next_account_info(account_info_iter)?;
+
+        if price == 0 {
+            return Err(MarketplaceError::InvalidPrice.into());
+        }
+
+        validation::check_signer(seller_info)?;
+
+        // The metadata program only freezes SPL Token accounts
+        validation::check_spl_token_program(token_program_info)?;
+        validation::check_token_metadata_program(token_metadata_program_info)?;
+        validation::check_system_program(system_program_info)?;
+        validation::check_token_account(
+            seller_token_account_info,
+            seller_info.key,
+            nft_mint_info.key,
+        )?;
+
+        // Verify the marketplace exists
+        validation::check_owner(marketplace_info, program_id)?;
+        Self::read_marketplace(marketplace_info)?;
+
+        validation::check_uninitialized(listing_info)?;
+
+        let (listing_pda, listing_bump) =
+            crate::state::get_listing_pda(program_id, nft_mint_info.key, seller_info.key);
+
+        if listing_pda != *listing_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let listing_seeds: &[&[u8]] = &[
+            LISTING_SEED,
+            nft_mint_info.key.as_ref(),
+            seller_info.key.as_ref(),
+            &[listing_bump],
+        ];
+
+        let rent = Rent::from_account_info(rent_info)?;
+
+        // Create listing account
+        invoke_signed(
+            &system_instruction::create_account(
+                seller_info.key,
+                listing_info.key,
+                rent.minimum_balance(Listing::LEN),
+                Listing::LEN as u64,
+                program_id,
+            ),
+            &[
+                seller_info.clone(),
+                listing_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[listing_seeds],
+        )?;
+
+        // Make the listing PDA the NFT's delegate, then freeze the seller's
+        // token account so the NFT can't leave it until a sale or cancel
+        invoke(
+            &approve(
+                token_program_info.key,
+                seller_token_account_info.key,
+                listing_info.key,
+                seller_info.key,
+                &[],
+                1,
+            )?,
+            &[
+                seller_token_account_info.clone(),
+                listing_info.clone(),
+                seller_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+        invoke_signed(
+            &metadata::freeze_delegated_account(
+                &listing_info.key.to_bytes(),
+                &seller_token_account_info.key.to_bytes(),
+                &nft_edition_info.key.to_bytes(),
+                &nft_mint_info.key.to_bytes(),
+            )
+            .into(),
+            &[
+                listing_info.clone(),
+                seller_token_account_info.clone(),
+                nft_edition_info.clone(),
+                nft_mint_info.clone(),
+                token_program_info.clone(),
+                token_metadata_program_info.clone(),
+            ],
+            &[listing_seeds],
+        )?;
+
+        let listing = Listing::new(
+            marketplace_info.key.to_bytes(),
+            seller_info.key.to_bytes(),
+            nft_mint_info.key.to_bytes(),
+            price,
+            Clock::get()?.unix_timestamp,
+            listing_bump,
+            None,
+        );
+        Listing::pack(listing, &mut listing_info.data.borrow_mut())?;
+
+        let mut marketplace_stats =
+            Self::load_marketplace_stats(program_id, marketplace_stats_info, marketplace_info)?;
+        marketplace_stats.record_listing()?;
+        MarketplaceTotals::pack(
+            marketplace_stats,
+            &mut marketplace_stats_info.data.borrow_mut(),
+        )?;
+
+        // Emit event for indexer
+        MarketplaceEvent::NftListed(ListEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            seller: seller_info.key.to_bytes(),
+            price,
+            listing: listing_info.key.to_bytes(),
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_buy_nft(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        expected_price: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let buyer_info = next_account_info(account_info_iter)?;
+        let seller_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let fee_recipient_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let listing_info = next_account_info(account_info_iter)?;
+        let escrow_token_account_info = next_account_info(account_info_iter)?;
+        let buyer_token_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let nft_metadata_info = next_account_info(account_info_iter)?;
+        let collection_config_info = next_account_info(account_info_iter)?;
+        let marketplace_stats_info = next_account_info(account_info_iter)?;
+        let referral_config_info = next_account_info(account_info_iter)?;
+        let referrer_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(buyer_info)?;
+        validation::check_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        // Load listing data
+        if listing_info.owner != program_id {
+            return Err(MarketplaceError::NftNotForSale.into());
+        }
+        // Not `unpack`, which rejects legacy listings for their size
+        let listing = Listing::from_account_data(&listing_info.data.borrow())?;
+
+        // The address commits to the mint and seller, so this also checks both
+        let listing_seeds: &[&[u8]] = &[
+            LISTING_SEED,
+            nft_mint_info.key.as_ref(),
+            seller_info.key.as_ref(),
+            &[listing.bump],
+        ];
+        validation::check_pda(listing_info, listing_seeds, program_id)?;
+        if listing.seller != seller_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidSeller.into());
+        }
+        if buyer_info.key == seller_info.key {
+            return Err(MarketplaceError::InvalidBuyer.into());
+        }
+        if listing
+            .allowed_buyer
+            .is_some_and(|allowed_buyer| allowed_buyer != buyer_info.key.to_bytes())
+        {
+            return Err(MarketplaceError::InvalidBuyer.into());
+        }
+        if listing.price != expected_price {
+            return Err(MarketplaceError::ExpectedAmountMismatch.into());
+        }
+
+        // Load marketplace data
+        if listing.marketplace != marketplace_info.key.to_bytes()
+            || marketplace_info.owner != program_id
+        {
+            return Err(MarketplaceError::InvalidAccountOwner.into());
+        }
+        let mut marketplace = Self::read_marketplace(marketplace_info)?;
+        if marketplace.fee_recipient != fee_recipient_info.key.to_bytes() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // A collection config's fee takes precedence for this sale
+        let collection_config = Self::load_collection_config(
+            program_id,
+            marketplace_info,
+            nft_mint_info,
+            nft_metadata_info,
+            collection_config_info,
+        )?;
+        if let Some(fee_percentage) = collection_config
+            .as_ref()
+            .and_then(|(collection_config, _)| collection_config.fee_percentage)
+        {
+            marketplace.fee_percentage = fee_percentage;
+        }
+        let royalty_payments =
+            Self::royalty_payments(collection_config.as_ref(), listing.price, account_info_iter)?;
+
+        if buyer_info.lamports() < listing.price {
+            return Err(MarketplaceError::InsufficientFunds.into());
+        }
+
+        let fee = marketplace.calculate_fee(listing.price)?;
+        let seller_proceeds = Self::deduct_royalties(
+            marketplace.calculate_seller_proceeds(listing.price)?,
+            &royalty_payments,
+        )?;
+
+        // Passing the fee recipient as the referrer means there is none
+        let referral_fee = if referrer_info.key != fee_recipient_info.key {
+            match Self::load_referral_config(program_id, marketplace_info, referral_config_info)? {
+                Some(referral_config) => referral_config.calculate_referral_fee(fee)?,
+                None => 0,
+            }
+        } else {
+            0
+        };
+        let marketplace_fee = fee
+            .checked_sub(referral_fee)
+            .ok_or(MarketplaceError::AmountOverflow)?;
+
+        // Pay the seller, the marketplace, the referrer and the creators
+        invoke(
+            &system_instruction::transfer(buyer_info.key, seller_info.key, seller_proceeds),
+            &[
+                buyer_info.clone(),
+                seller_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+        if marketplace_fee > 0 {
+            invoke(
+                &system_instruction::transfer(
+                    buyer_info.key,
+                    fee_recipient_info.key,
+                    marketplace_fee,
+                ),
+                &[
+                    buyer_info.clone(),
+                    fee_recipient_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        }
+        if referral_fee > 0 {
+            invoke(
+                &system_instruction::transfer(buyer_info.key, referrer_info.key, referral_fee),
+                &[
+                    buyer_info.clone(),
+                    referrer_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        }
+        for &(creator_info, royalty) in &royalty_payments {
+            if royalty > 0 {
+                invoke(
+                    &system_instruction::transfer(buyer_info.key, creator_info.key, royalty),
+                    &[
+                        buyer_info.clone(),
+                        creator_info.clone(),
+                        system_program_info.clone(),
+                    ],
+                )?;
+            }
+        }
+
+        // Release the NFT to the buyer
+        invoke(
+            &create_associated_token_account_idempotent(
+                buyer_info.key,
+                buyer_info.key,
+                nft_mint_info.key,
+                token_program_info.key,
+            ),
+            &[
+                buyer_info.clone(),
+                buyer_token_account_info.clone(),
+                buyer_info.clone(),
+                nft_mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+        if Self::is_listing_escrow(
+            listing_info,
+            nft_mint_info,
+            token_program_info,
+            escrow_token_account_info,
+        ) {
+            Self::release_escrow(
+                listing_info,
+                listing_seeds,
+                escrow_token_account_info,
+                nft_mint_info,
+                buyer_token_account_info,
+                seller_info,
+                token_program_info,
+                account_info_iter.as_slice(),
+            )?;
+        } else {
+            // A delegated listing: the NFT is frozen in the seller's token
+            // account, and the listing sends it on as delegate once thawed
+            Self::thaw_delegated_account(
+                listing_info,
+                listing_seeds,
+                escrow_token_account_info,
+                nft_mint_info,
+                token_program_info,
+                account_info_iter.as_slice(),
+            )?;
+            Self::transfer_nft(
+                token_program_info,
+                escrow_token_account_info,
+                nft_mint_info,
+                buyer_token_account_info,
+                listing_info,
+                &[],
+                &[listing_seeds],
+            )?;
+            Self::close_program_account(listing_info, seller_info)?;
+        }
+
+        let mut marketplace_stats =
+            Self::load_marketplace_stats(program_id, marketplace_stats_info, marketplace_info)?;
+        marketplace_stats.record_sale(listing.price)?;
+        MarketplaceTotals::pack(
+            marketplace_stats,
+            &mut marketplace_stats_info.data.borrow_mut(),
+        )?;
+
+        // Emit event for indexer
+        MarketplaceEvent::NftSold(SaleEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            seller: seller_info.key.to_bytes(),
+            buyer: buyer_info.key.to_bytes(),
+            price: listing.price,
+            fee,
+        })
+        .emit();
+        if referral_fee > 0 {
+            MarketplaceEvent::ReferralPaid(ReferralEvent {
+                mint: nft_mint_info.key.to_bytes(),
+                referrer: referrer_info.key.to_bytes(),
+                amount: referral_fee,
+            })
+            .emit();
+        }
+        Ok(())
+    }
+
+    fn process_cancel_listing(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let seller_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let listing_info = next_account_info(account_info_iter)?;
+        let escrow_token_account_info = next_account_info(account_info_iter)?;
+        let seller_token_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(seller_info)?;
+        validation::check_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        // Load listing data
+        if listing_info.owner != program_id {
+            return Err(MarketplaceError::NftNotForSale.into());
+        }
+        // Not `unpack`, which rejects legacy listings for their size
+        let listing = Listing::from_account_data(&listing_info.data.borrow())?;
+
+        // Verify the signer is the seller who listed
+        if listing.seller != seller_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidSeller.into());
+        }
+
+        let listing_seeds: &[&[u8]] = &[
+            LISTING_SEED,
+            nft_mint_info.key.as_ref(),
+            seller_info.key.as_ref(),
+            &[listing.bump],
+        ];
+        validation::check_pda(listing_info, listing_seeds, program_id)?;
+
+        if !Self::is_listing_escrow(
+            listing_info,
+            nft_mint_info,
+            token_program_info,
+            escrow_token_account_info,
+        ) {
+            // A delegated listing: thaw the seller's token account and take
+            // back the delegation; the NFT never left it
+            Self::thaw_delegated_account(
+                listing_info,
+                listing_seeds,
+                escrow_token_account_info,
+                nft_mint_info,
+                token_program_info,
+                account_info_iter.as_slice(),
+            )?;
+            invoke(
+                &revoke(
+                    token_program_info.key,
+                    escrow_token_account_info.key,
+                    seller_info.key,
+                    &[],
+                )?,
+                &[
+                    escrow_token_account_info.clone(),
+                    seller_info.clone(),
+                    token_program_info.clone(),
+                ],
+            )?;
+            Self::close_program_account(listing_info, seller_info)?;
+
+            // Emit event for indexer
+            MarketplaceEvent::NftDelisted(DelistEvent {
+                mint: nft_mint_info.key.to_bytes(),
+                seller: seller_info.key.to_bytes(),
+                listing: listing_info.key.to_bytes(),
+            })
+            .emit();
+            return Ok(());
+        }
+
+        // The seller may have closed their token account after listing
+        invoke(
+            &create_associated_token_account_idempotent(
+                seller_info.key,
+                seller_info.key,
+                nft_mint_info.key,
+                token_program_info.key,
+            ),
+            &[
+                seller_info.clone(),
+                seller_token_account_info.clone(),
+                seller_info.clone(),
+                nft_mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+        Self::release_escrow(
+            listing_info,
+            listing_seeds,
+            escrow_token_account_info,
+            nft_mint_info,
+            seller_token_account_info,
+            seller_info,
+            token_program_info,
+            account_info_iter.as_slice(),
+        )?;
+
+        // Emit event for indexer
+        MarketplaceEvent::NftDelisted(DelistEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            seller: seller_info.key.to_bytes(),
+            listing: listing_info.key.to_bytes(),
+        })
+        .emit();
+        Ok(())
+    }
+
+    /// Whether `token_account_info` is the escrow token account of
+    /// `listing_info`. If not, the listing is a `ListNftDelegated` one and the
+    /// NFT is in the seller's token account; the token and metadata programs
+    /// reject it unless that account is frozen with the listing as delegate.
+    fn is_listing_escrow(
+        listing_info: &AccountInfo,
+        nft_mint_info: &AccountInfo,
+        token_program_info: &AccountInfo,
+        token_account_info: &AccountInfo,
+    ) -> bool {
+        get_associated_token_address_with_program_id(
+            listing_info.key,
+            nft_mint_info.key,
+            token_program_info.key,
+        ) == *token_account_info.key
+    }
+
+    /// Thaw a token account frozen with `delegate_info` as its delegate, by a
+    /// `ListNftDelegated` listing or a rental, signing as the delegate.
+    /// `metadata_accounts` holds the NFT edition account and the token
+    /// metadata program.
+    fn thaw_delegated_account<'a>(
+        delegate_info: &AccountInfo<'a>,
+        delegate_seeds: &[&[u8]],
+        token_account_info: &AccountInfo<'a>,
+        nft_mint_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        metadata_accounts: &[AccountInfo<'a>],
+    ) -> ProgramResult {
+        let [nft_edition_info, token_metadata_program_info, ..] = metadata_accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        validation::check_token_metadata_program(token_metadata_program_info)?;
+
+        invoke_signed(
+            &metadata::thaw_delegated_account(
+                &delegate_info.key.to_bytes(),
+                &token_account_info.key.to_bytes(),
+                &nft_edition_info.key.to_bytes(),
+                &nft_mint_info.key.to_bytes(),
+            )
+            .into(),
+            &[
+                delegate_info.clone(),
+                token_account_info.clone(),
+                nft_edition_info.clone(),
+                nft_mint_info.clone(),
+                token_program_info.clone(),
+                token_metadata_program_info.clone(),
+            ],
+            &[delegate_seeds],
+        )
+    }
+
+    /// Send the escrowed NFT to `recipient_token_account_info`, then close the
+    /// escrow token account and the listing or auction PDA owning it,
+    /// returning their rent to the seller
+    #[allow(clippy::too_many_arguments)]
+    fn release_escrow<'a>(
+        escrow_owner_info: &AccountInfo<'a>,
+        escrow_owner_seeds: &[&[u8]],
+        escrow_token_account_info: &AccountInfo<'a>,
+        nft_mint_info: &AccountInfo<'a>,
+        recipient_token_account_info: &AccountInfo<'a>,
+        seller_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        hook_accounts: &[AccountInfo<'a>],
+    ) -> ProgramResult {
+        Self::empty_escrow(
+            escrow_owner_info,
+            escrow_owner_seeds,
+            escrow_token_account_info,
+            nft_mint_info,
+            recipient_token_account_info,
+            seller_info,
+            token_program_info,
+            hook_accounts,
+        )?;
+        Self::close_program_account(escrow_owner_info, seller_info)
+    }
+
+    /// Send the escrowed NFT to `recipient_token_account_info` and close the
+    /// escrow token account, returning its rent to the seller
+    #[allow(clippy::too_many_arguments)]
+    fn empty_escrow<'a>(
+        escrow_owner_info: &AccountInfo<'a>,
+        escrow_owner_seeds: &[&[u8]],
+        escrow_token_account_info: &AccountInfo<'a>,
+        nft_mint_info: &AccountInfo<'a>,
+        recipient_token_account_info: &AccountInfo<'a>,
+        seller_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        hook_accounts: &[AccountInfo<'a>],
+    ) -> ProgramResult {
+        Self::transfer_nft(
+            token_program_info,
+            escrow_token_account_info,
+            nft_mint_info,
+            recipient_token_account_info,
+            escrow_owner_info,
+            hook_accounts,
+            &[escrow_owner_seeds],
+        )?;
+        invoke_signed(
+            &close_account(
+                token_program_info.key,
+                escrow_token_account_info.key,
+                seller_info.key,
+                escrow_owner_info.key,
+                &[escrow_owner_info.key],
+            )?,
+            &[
+                escrow_token_account_info.clone(),
+                seller_info.clone(),
+                escrow_owner_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[escrow_owner_seeds],
+        )
+    }
+
+    /// Move one NFT between token accounts with `transfer_checked`, which SPL
+    /// Token and Token-2022 both support. For Token-2022 mints with a transfer
+    /// hook, `hook_accounts` holds the accounts the hook needs; they're
+    /// resolved from the mint's extra account metas.
+    fn transfer_nft<'a>(
+        token_program_info: &AccountInfo<'a>,
+        source_info: &AccountInfo<'a>,
+        nft_mint_info: &AccountInfo<'a>,
+        destination_info: &AccountInfo<'a>,
+        authority_info: &AccountInfo<'a>,
+        hook_accounts: &[AccountInfo<'a>],
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        spl_token_2022::onchain::invoke_transfer_checked(
+            token_program_info.key,
+            source_info.clone(),
+            nft_mint_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            hook_accounts,
+            1,
+            0, // NFTs have no decimals
+            signer_seeds,
+        )
+    }
+
+    fn process_list_bundle(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        bundle_id: u64,
+        price: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let seller_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let bundle_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+        let marketplace_stats_info = next_account_info(account_info_iter)?;
+        let item_infos = Self::bundle_item_infos(account_info_iter.as_slice())?;
+
+        if price == 0 {
+            return Err(MarketplaceError::InvalidPrice.into());
+        }
+        if item_infos.len() < MIN_BUNDLE_SIZE {
+            return Err(MarketplaceError::InvalidBundle.into());
+        }
+
+        validation::check_signer(seller_info)?;
+        validation::check_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        // Verify the marketplace exists
+        validation::check_owner(marketplace_info, program_id)?;
+        Self::read_marketplace(marketplace_info)?;
+
+        validation::check_uninitialized(bundle_info)?;
+
+        let (bundle_pda, bundle_bump) =
+            crate::state::get_bundle_pda(program_id, seller_info.key, bundle_id);
+        if bundle_pda != *bundle_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let mut mints: Vec<[u8; 32]> = Vec::with_capacity(item_infos.len());
+        for &[nft_mint_info, _, _] in &item_infos {
+            let mint = nft_mint_info.key.to_bytes();
+            if mints.contains(&mint) {
+                return Err(MarketplaceError::InvalidBundle.into());
+            }
+            // Bundles pass no transfer hook accounts, and every later
+            // transfer out of escrow relies on that
+            validation::check_no_transfer_hook(nft_mint_info)?;
+            mints.push(mint);
+        }
+
+        let rent = Rent::from_account_info(rent_info)?;
+
+        // Create bundle account
+        invoke_signed(
+            &system_instruction::create_account(
+                seller_info.key,
+                bundle_info.key,
+                rent.minimum_balance(Bundle::LEN),
+                Bundle::LEN as u64,
+                program_id,
+            ),
+            &[
+                seller_info.clone(),
+                bundle_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[
+                BUNDLE_SEED,
+                seller_info.key.as_ref(),
+                &bundle_id.to_le_bytes(),
+                &[bundle_bump],
+            ]],
+        )?;
+
+        // Move each NFT into an escrow token account owned by the bundle PDA
+        for &[nft_mint_info, escrow_token_account_info, seller_token_account_info] in &item_infos {
+            validation::check_token_account(
+                seller_token_account_info,
+                seller_info.key,
+                nft_mint_info.key,
+            )?;
+            invoke(
+                &create_associated_token_account(
+                    seller_info.key,
+                    bundle_info.key,
+                    nft_mint_info.key,
+                    token_program_info.key,
+                ),
+                &[
+                    seller_info.clone(),
+                    escrow_token_account_info.clone(),
+                    bundle_info.clone(),
+                    nft_mint_info.clone(),
+                    system_program_info.clone(),
+                    token_program_info.clone(),
+                    associated_token_program_info.clone(),
+                ],
+            )?;
+            Self::transfer_nft(
+                token_program_info,
+                seller_token_account_info,
+                nft_mint_info,
+                escrow_token_account_info,
+                seller_info,
+                &[],
+                &[],
+            )?;
+        }
+
+        let bundle = Bundle::new(
+            marketplace_info.key.to_bytes(),
+            seller_info.key.to_bytes(),
+            bundle_id,
+            price,
+            Clock::get()?.unix_timestamp,
+            bundle_bump,
+            mints.clone(),
+        );
+        Bundle::pack(bundle, &mut bundle_info.data.borrow_mut())?;
+
+        let mut marketplace_stats =
+            Self::load_marketplace_stats(program_id, marketplace_stats_info, marketplace_info)?;
+        marketplace_stats.record_listing()?;
+        MarketplaceTotals::pack(
+            marketplace_stats,
+            &mut marketplace_stats_info.data.borrow_mut(),
+        )?;
+
+        // Emit event for indexer
+        MarketplaceEvent::BundleListed(BundleListEvent {
+            bundle: bundle_info.key.to_bytes(),
+            seller: seller_info.key.to_bytes(),
+            mints,
+            price,
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_buy_bundle(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        expected_price: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let buyer_info = next_account_info(account_info_iter)?;
+        let seller_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let fee_recipient_info = next_account_info(account_info_iter)?;
+        let bundle_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let marketplace_stats_info = next_account_info(account_info_iter)?;
+        let purchase_infos = Self::bundle_item_infos::<5>(account_info_iter.as_slice())?;
+        let item_infos: Vec<[&AccountInfo; 3]> = purchase_infos
+            .iter()
+            .map(
+                |&[nft_mint_info, escrow_token_account_info, buyer_token_account_info, _, _]| {
+                    [
+                        nft_mint_info,
+                        escrow_token_account_info,
+                        buyer_token_account_info,
+                    ]
+                },
+            )
+            .collect();
+
+        validation::check_signer(buyer_info)?;
+        validation::check_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        let bundle = Self::load_bundle(program_id, bundle_info, seller_info, &item_infos)?;
+        if buyer_info.key == seller_info.key {
+            return Err(MarketplaceError::InvalidBuyer.into());
+        }
+        if bundle.price != expected_price {
+            return Err(MarketplaceError::ExpectedAmountMismatch.into());
+        }
+
+        // Load marketplace data
+        if bundle.marketplace != marketplace_info.key.to_bytes()
+            || marketplace_info.owner != program_id
+        {
+            return Err(MarketplaceError::InvalidAccountOwner.into());
+        }
+        let marketplace = Self::read_marketplace(marketplace_info)?;
+        if marketplace.fee_recipient != fee_recipient_info.key.to_bytes() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // The bundle price isn't split between its NFTs, so it can't be
+        // charged a collection's fee override or pay its royalties. Refuse
+        // the sale rather than skip them; the seller can cancel the bundle
+        // and sell those NFTs alone.
+        for &[nft_mint_info, _, _, nft_metadata_info, collection_config_info] in &purchase_infos {
+            let collection_config = Self::load_collection_config(
+                program_id,
+                marketplace_info,
+                nft_mint_info,
+                nft_metadata_info,
+                collection_config_info,
+            )?;
+            if collection_config.is_some_and(|(collection_config, _)| {
+                collection_config.enforce_royalties || collection_config.fee_percentage.is_some()
+            }) {
+                return Err(MarketplaceError::InvalidBundle.into());
+            }
+        }
+
+        if buyer_info.lamports() < bundle.price {
+            return Err(MarketplaceError::InsufficientFunds.into());
+        }
+
+        let fee = marketplace.calculate_fee(bundle.price)?;
+        let seller_proceeds = marketplace.calculate_seller_proceeds(bundle.price)?;
+
+        // Pay the seller and the marketplace
+        invoke(
+            &system_instruction::transfer(buyer_info.key, seller_info.key, seller_proceeds),
+            &[
+                buyer_info.clone(),
+                seller_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+        if fee > 0 {
+            invoke(
+                &system_instruction::transfer(buyer_info.key, fee_recipient_info.key, fee),
+                &[
+                    buyer_info.clone(),
+                    fee_recipient_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        }
+
+        // Release every NFT to the buyer
+        let bundle_id = bundle.bundle_id.to_le_bytes();
+        let bundle_seeds: &[&[u8]] = &[
+            BUNDLE_SEED,
+            seller_info.key.as_ref(),
+            &bundle_id,
+            &[bundle.bump],
+        ];
+        Self::release_bundle(
+            bundle_info,
+            bundle_seeds,
+            &item_infos,
+            buyer_info,
+            seller_info,
+            token_program_info,
+            associated_token_program_info,
+            system_program_info,
+        )?;
+
+        let mut marketplace_stats =
+            Self::load_marketplace_stats(program_id, marketplace_stats_info, marketplace_info)?;
+        marketplace_stats.record_sale(bundle.price)?;
+        MarketplaceTotals::pack(
+            marketplace_stats,
+            &mut marketplace_stats_info.data.borrow_mut(),
+        )?;
+
+        // Emit event for indexer
+        MarketplaceEvent::BundleSold(BundleSaleEvent {
+            bundle: bundle_info.key.to_bytes(),
+            seller: seller_info.key.to_bytes(),
+            buyer: buyer_info.key.to_bytes(),
+            price: bundle.price,
+            fee,
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_cancel_bundle(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let seller_info = next_account_info(account_info_iter)?;
+        let bundle_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let item_infos = Self::bundle_item_infos(account_info_iter.as_slice())?;
+
+        validation::check_signer(seller_info)?;
+        validation::check_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        let bundle = Self::load_bundle(program_id, bundle_info, seller_info, &item_infos)?;
+
+        // The seller may have closed their token accounts after listing
+        let bundle_id = bundle.bundle_id.to_le_bytes();
+        let bundle_seeds: &[&[u8]] = &[
+            BUNDLE_SEED,
+            seller_info.key.as_ref(),
+            &bundle_id,
+            &[bundle.bump],
+        ];
+        Self::release_bundle(
+            bundle_info,
+            bundle_seeds,
+            &item_infos,
+            seller_info,
+            seller_info,
+            token_program_info,
+            associated_token_program_info,
+            system_program_info,
+        )?;
+
+        // Emit event for indexer
+        MarketplaceEvent::BundleDelisted(BundleDelistEvent {
+            bundle: bundle_info.key.to_bytes(),
+            seller: seller_info.key.to_bytes(),
+        })
+        .emit();
+        Ok(())
+    }
+
+    /// Split the accounts after a bundle instruction's own into one group of
+    /// `N` per NFT, starting `[mint, escrow, token account]`
+    fn bundle_item_infos<'b, 'a, const N: usize>(
+        accounts: &'b [AccountInfo<'a>],
+    ) -> Result<Vec<[&'b AccountInfo<'a>; N]>, ProgramError> {
+        if accounts.is_empty() || accounts.len() % N != 0 || accounts.len() / N > MAX_BUNDLE_SIZE {
+            return Err(MarketplaceError::InvalidBundle.into());
+        }
+        Ok(accounts
+            .chunks_exact(N)
+            .map(|item_info| std::array::from_fn(|i| &item_info[i]))
+            .collect())
+    }
+
+    /// Load a bundle of `seller_info`, checking its address and that
+    /// `item_infos` hold its mints in order
+    fn load_bundle(
+        program_id: &Pubkey,
+        bundle_info: &AccountInfo,
+        seller_info: &AccountInfo,
+        item_infos: &[[&AccountInfo; 3]],
+    ) -> Result<Bundle, ProgramError> {
+        if bundle_info.owner != program_id {
+            return Err(MarketplaceError::NftNotForSale.into());
+        }
+        let bundle = Bundle::unpack(&bundle_info.data.borrow())?;
+
+        if bundle.seller != seller_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidSeller.into());
+        }
+        validation::check_pda(
+            bundle_info,
+            &[
+                BUNDLE_SEED,
+                seller_info.key.as_ref(),
+                &bundle.bundle_id.to_le_bytes(),
+                &[bundle.bump],
+            ],
+            program_id,
+        )?;
+
+        if item_infos.len() != bundle.mints.len()
+            || item_infos
+                .iter()
+                .zip(&bundle.mints)
+                .any(|([nft_mint_info, _, _], mint)| nft_mint_info.key.to_bytes() != *mint)
+        {
+            return Err(MarketplaceError::InvalidBundle.into());
+        }
+        Ok(bundle)
+    }
+
+    /// Send every NFT of a bundle to the associated token accounts of
+    /// `recipient_info` (created if missing), then close the escrow token
+    /// accounts and the bundle, returning their rent to the seller
+    #[allow(clippy::too_many_arguments)]
+    fn release_bundle<'a>(
+        bundle_info: &AccountInfo<'a>,
+        bundle_seeds: &[&[u8]],
+        item_infos: &[[&AccountInfo<'a>; 3]],
+        recipient_info: &AccountInfo<'a>,
+        seller_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        associated_token_program_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        for &[nft_mint_info, escrow_token_account_info, recipient_token_account_info] in item_infos
+        {
+            invoke(
+                &create_associated_token_account_idempotent(
+                    recipient_info.key,
+                    recipient_info.key,
+                    nft_mint_info.key,
+                    token_program_info.key,
+                ),
+                &[
+                    recipient_info.clone(),
+                    recipient_token_account_info.clone(),
+                    recipient_info.clone(),
+                    nft_mint_info.clone(),
+                    system_program_info.clone(),
+                    token_program_info.clone(),
+                    associated_token_program_info.clone(),
+                ],
+            )?;
+            Self::empty_escrow(
+                bundle_info,
+                bundle_seeds,
+                escrow_token_account_info,
+                nft_mint_info,
+                recipient_token_account_info,
+                seller_info,
+                token_program_info,
+                &[],
+            )?;
+        }
+        Self::close_program_account(bundle_info, seller_info)
+    }
+
+    fn process_list_nft_for_rent(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        daily_rate: u64,
+        max_days: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let owner_token_account_info = next_account_info(account_info_iter)?;
+        let rental_info = next_account_info(account_info_iter)?;
+        let escrow_token_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if daily_rate == 0 {
+            return Err(MarketplaceError::InvalidPrice.into());
+        }
+        if max_days == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        validation::check_signer(owner_info)?;
+
+        // Rentals freeze the NFT through the metadata program, which only
+        // freezes SPL Token accounts
+        validation::check_spl_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        // Verify the marketplace exists
+        validation::check_owner(marketplace_info, program_id)?;
+        Self::read_marketplace(marketplace_info)?;
+
+        validation::check_uninitialized(rental_info)?;
+
+        let (rental_pda, rental_bump) =
+            crate::state::get_rental_pda(program_id, nft_mint_info.key, owner_info.key);
+
+        if rental_pda != *rental_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let rent = Rent::from_account_info(rent_info)?;
+
+        // Create rental account
+        invoke_signed(
+            &system_instruction::create_account(
+                owner_info.key,
+                rental_info.key,
+                rent.minimum_balance(Rental::LEN),
+                Rental::LEN as u64,
+                program_id,
+            ),
+            &[
+                owner_info.clone(),
+                rental_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[
+                RENTAL_SEED,
+                nft_mint_info.key.as_ref(),
+                owner_info.key.as_ref(),
+                &[rental_bump],
+            ]],
+        )?;
+
+        // Create the escrow token account, owned by the rental PDA
+        invoke(
+            &create_associated_token_account(
+                owner_info.key,
+                rental_info.key,
+                nft_mint_info.key,
+                token_program_info.key,
+            ),
+            &[
+                owner_info.clone(),
+                escrow_token_account_info.clone(),
+                rental_info.clone(),
+                nft_mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+
+        // Move the NFT into escrow
+        Self::transfer_nft(
+            token_program_info,
+            owner_token_account_info,
+            nft_mint_info,
+            escrow_token_account_info,
+            owner_info,
+            &[],
+            &[],
+        )?;
+
+        let rental = Rental::new(
+            marketplace_info.key.to_bytes(),
+            owner_info.key.to_bytes(),
+            nft_mint_info.key.to_bytes(),
+            daily_rate,
+            max_days,
+            rental_bump,
+        );
+        Rental::pack(rental, &mut rental_info.data.borrow_mut())?;
+
+        // Emit event for indexer
+        MarketplaceEvent::RentalListed(RentalListEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            owner: owner_info.key.to_bytes(),
+            daily_rate,
+            max_days,
+            rental: rental_info.key.to_bytes(),
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_rent_nft(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        days: u16,
+        expected_daily_rate: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let renter_info = next_account_info(account_info_iter)?;
+        let rental_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let escrow_token_account_info = next_account_info(account_info_iter)?;
+        let renter_token_account_info = next_account_info(account_info_iter)?;
+        let nft_edition_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let token_metadata_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(renter_info)?;
+        validation::check_spl_token_program(token_program_info)?;
+        validation::check_token_metadata_program(token_metadata_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        let mut rental = Self::load_rental(program_id, rental_info, nft_mint_info)?;
+        if rental.is_rented() {
+            return Err(MarketplaceError::RentalActive.into());
+        }
+        if rental.owner == renter_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidBuyer.into());
+        }
+        if rental.daily_rate != expected_daily_rate {
+            return Err(MarketplaceError::ExpectedAmountMismatch.into());
+        }
+        if days == 0 || days > rental.max_days {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        rental.start(renter_info.key.to_bytes(), days, now)?;
+        if renter_info.lamports() < rental.rent_paid {
+            return Err(MarketplaceError::InsufficientFunds.into());
+        }
+
+        // Hold the rent in the rental account until the NFT is reclaimed
+        invoke(
+            &system_instruction::transfer(renter_info.key, rental_info.key, rental.rent_paid),
+            &[
+                renter_info.clone(),
+                rental_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+
+        // Hand the NFT to the renter
+        invoke(
+            &create_associated_token_account_idempotent(
+                renter_info.key,
+                renter_info.key,
+                nft_mint_info.key,
+                token_program_info.key,
+            ),
+            &[
+                renter_info.clone(),
+                renter_token_account_info.clone(),
+                renter_info.clone(),
+                nft_mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+        let rental_seeds: &[&[u8]] = &[
+            RENTAL_SEED,
+            nft_mint_info.key.as_ref(),
+            &rental.owner,
+            &[rental.bump],
+        ];
+        Self::transfer_nft(
+            token_program_info,
+            escrow_token_account_info,
+            nft_mint_info,
+            renter_token_account_info,
+            rental_info,
+            &[],
+            &[rental_seeds],
+        )?;
+
+        // Make the rental PDA the NFT's delegate, then freeze the renter's
+        // token account so the NFT can't leave it before it is reclaimed
+        invoke(
+            &approve(
+                token_program_info.key,
+                renter_token_account_info.key,
+                rental_info.key,
+                renter_info.key,
+                &[],
+                1,
+            )?,
+            &[
+                renter_token_account_info.clone(),
+                rental_info.clone(),
+                renter_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+        invoke_signed(
+            &metadata::freeze_delegated_account(
+                &rental_info.key.to_bytes(),
+                &renter_token_account_info.key.to_bytes(),
+                &nft_edition_info.key.to_bytes(),
+                &nft_mint_info.key.to_bytes(),
+            )
+            .into(),
+            &[
+                rental_info.clone(),
+                renter_token_account_info.clone(),
+                nft_edition_info.clone(),
+                nft_mint_info.clone(),
+                token_program_info.clone(),
+                token_metadata_program_info.clone(),
+            ],
+            &[rental_seeds],
+        )?;
+
+        Rental::pack(rental.clone(), &mut rental_info.data.borrow_mut())?;
+
+        // Emit event for indexer
+        MarketplaceEvent::NftRented(RentEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            owner: rental.owner,
+            renter: rental.renter,
+            days,
+            price: rental.rent_paid,
+            expires_at: rental.expires_at,
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_reclaim_rental(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let fee_recipient_info = next_account_info(account_info_iter)?;
+        let rental_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let escrow_token_account_info = next_account_info(account_info_iter)?;
+        let renter_token_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        validation::check_spl_token_program(token_program_info)?;
+
+        let mut rental = Self::load_rental(program_id, rental_info, nft_mint_info)?;
+        if rental.owner != owner_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidSeller.into());
+        }
+        // Anyone may reclaim, but only once the renter's time is up
+        if !rental.is_rented() || Clock::get()?.unix_timestamp < rental.expires_at {
+            return Err(MarketplaceError::RentalNotExpired.into());
+        }
+
+        // Load marketplace data
+        if rental.marketplace != marketplace_info.key.to_bytes()
+            || marketplace_info.owner != program_id
+        {
+            return Err(MarketplaceError::InvalidAccountOwner.into());
+        }
+        let marketplace = Self::read_marketplace(marketplace_info)?;
+        if marketplace.fee_recipient != fee_recipient_info.key.to_bytes() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Anyone can reclaim, so the NFT must go back to the rental's own
+        // escrow rather than an account the caller picked
+        if *escrow_token_account_info.key
+            != get_associated_token_address_with_program_id(
+                rental_info.key,
+                nft_mint_info.key,
+                token_program_info.key,
+            )
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Thaw the renter's token account and take the NFT back to escrow as
+        // its delegate. The token program checks the account is the one the
+        // rental was delegated.
+        let rental_seeds: &[&[u8]] = &[
+            RENTAL_SEED,
+            nft_mint_info.key.as_ref(),
+            owner_info.key.as_ref(),
+            &[rental.bump],
+        ];
+        Self::thaw_delegated_account(
+            rental_info,
+            rental_seeds,
+            renter_token_account_info,
+            nft_mint_info,
+            token_program_info,
+            account_info_iter.as_slice(),
+        )?;
+        Self::transfer_nft(
+            token_program_info,
+            renter_token_account_info,
+            nft_mint_info,
+            escrow_token_account_info,
+            rental_info,
+            &[],
+            &[rental_seeds],
+        )?;
+
+        // Pay the owner and the marketplace from the rent held
+        let fee = marketplace.calculate_fee(rental.rent_paid)?;
+        let owner_proceeds = marketplace.calculate_seller_proceeds(rental.rent_paid)?;
+        Self::move_lamports(rental_info, owner_info, owner_proceeds)?;
+        Self::move_lamports(rental_info, fee_recipient_info, fee)?;
+        validation::check_rent_exempt(rental_info, &Rent::get()?)?;
+
+        let renter = rental.renter;
+        rental.end();
+        Rental::pack(rental, &mut rental_info.data.borrow_mut())?;
+
+        // Emit event for indexer
+        MarketplaceEvent::RentalReclaimed(RentalReclaimEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            owner: owner_info.key.to_bytes(),
+            renter,
+            fee,
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_cancel_rental(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let rental_info = next_account_info(account_info_iter)?;
+        let escrow_token_account_info = next_account_info(account_info_iter)?;
+        let owner_token_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(owner_info)?;
+        validation::check_spl_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        let rental = Self::load_rental(program_id, rental_info, nft_mint_info)?;
+        if rental.owner != owner_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidSeller.into());
+        }
+        if rental.is_rented() {
+            return Err(MarketplaceError::RentalActive.into());
+        }
+
+        // The owner may have closed their token account after listing
+        invoke(
+            &create_associated_token_account_idempotent(
+                owner_info.key,
+                owner_info.key,
+                nft_mint_info.key,
+                token_program_info.key,
+            ),
+            &[
+                owner_info.clone(),
+                owner_token_account_info.clone(),
+                owner_info.clone(),
+                nft_mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+        Self::release_escrow(
+            rental_info,
+            &[
+                RENTAL_SEED,
+                nft_mint_info.key.as_ref(),
+                owner_info.key.as_ref(),
+                &[rental.bump],
+            ],
+            escrow_token_account_info,
+            nft_mint_info,
+            owner_token_account_info,
+            owner_info,
+            token_program_info,
+            &[],
+        )?;
+
+        // Emit event for indexer
+        MarketplaceEvent::RentalCancelled(RentalCancelEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            owner: owner_info.key.to_bytes(),
+            rental: rental_info.key.to_bytes(),
+        })
+        .emit();
+        Ok(())
+    }
+
+    /// Load the rental of `nft_mint_info`, checking its address
+    fn load_rental(
+        program_id: &Pubkey,
+        rental_info: &AccountInfo,
+        nft_mint_info: &AccountInfo,
+    ) -> Result<Rental, ProgramError> {
+        if rental_info.owner != program_id {
+            return Err(MarketplaceError::AccountNotInitialized.into());
+        }
+        let rental = Rental::unpack(&rental_info.data.borrow())?;
+
+        validation::check_pda(
+            rental_info,
+            &[
+                RENTAL_SEED,
+                nft_mint_info.key.as_ref(),
+                &rental.owner,
+                &[rental.bump],
+            ],
+            program_id,
+        )?;
+        Ok(rental)
+    }
+
+    fn process_set_staking_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        reward_rate: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let collection_mint_info = next_account_info(account_info_iter)?;
+        let collection_metadata_info = next_account_info(account_info_iter)?;
+        let staking_config_info = next_account_info(account_info_iter)?;
+        let reward_mint_info = next_account_info(account_info_iter)?;
+        let reward_vault_info = next_account_info(account_info_iter)?;
+        let reward_token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(authority_info)?;
+        validation::check_token_program(reward_token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+        if reward_mint_info.owner != reward_token_program_info.key {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Verify the signer is the collection's update authority
+        if collection_metadata_info.owner.to_bytes() != TOKEN_METADATA_PROGRAM_ID {
+            return Err(MarketplaceError::InvalidAccountOwner.into());
+        }
+        let (metadata_pda, _) = crate::state::get_metadata_pda(collection_mint_info.key);
+        if metadata_pda != *collection_metadata_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let collection_metadata =
+            Metadata::from_account_data(&collection_metadata_info.data.borrow())
+                .ok_or(MarketplaceError::InvalidMetadata)?;
+        if collection_metadata.mint != collection_mint_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidMetadata.into());
+        }
+        if collection_metadata.update_authority != authority_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidUpdateAuthority.into());
+        }
+
+        let (staking_config_pda, staking_config_bump) =
+            crate::state::get_staking_config_pda(program_id, collection_mint_info.key);
+        if staking_config_pda != *staking_config_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        // Update an existing config, keeping its reward mint since stakers
+        // are owed rewards in it and accruing them at the old rate up to now;
+        // otherwise create the account
+        let now = Clock::get()?.unix_timestamp;
+        let staking_config = if staking_config_info.owner == program_id {
+            let mut staking_config = StakingConfig::unpack(&staking_config_info.data.borrow())?;
+            if staking_config.reward_mint != reward_mint_info.key.to_bytes() {
+                return Err(ProgramError::InvalidArgument);
+            }
+            staking_config.set_reward_rate(reward_rate, now)?;
+            staking_config.authority = authority_info.key.to_bytes();
+            staking_config
+        } else {
+            let rent = Rent::from_account_info(rent_info)?;
+            invoke_signed(
+                &system_instruction::create_account(
+                    authority_info.key,
+                    staking_config_info.key,
+                    rent.minimum_balance(StakingConfig::LEN),
+                    StakingConfig::LEN as u64,
+                    program_id,
+                ),
+                &[
+                    authority_info.clone(),
+                    staking_config_info.clone(),
+                    system_program_info.clone(),
+                ],
+                &[&[
+                    STAKING_CONFIG_SEED,
+                    collection_mint_info.key.as_ref(),
+                    &[staking_config_bump],
+                ]],
+            )?;
+            StakingConfig::new(
+                collection_mint_info.key.to_bytes(),
+                authority_info.key.to_bytes(),
+                reward_mint_info.key.to_bytes(),
+                reward_rate,
+                now,
+                staking_config_bump,
+            )
+        };
+
+        // Create the reward vault, owned by the staking config PDA
+        invoke(
+            &create_associated_token_account_idempotent(
+                authority_info.key,
+                staking_config_info.key,
+                reward_mint_info.key,
+                reward_token_program_info.key,
+            ),
+            &[
+                authority_info.clone(),
+                reward_vault_info.clone(),
+                staking_config_info.clone(),
+                reward_mint_info.clone(),
+                system_program_info.clone(),
+                reward_token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+
+        StakingConfig::pack(staking_config, &mut staking_config_info.data.borrow_mut())?;
+
+        msg!(
+            "Staking config set for {}: {} of {} per NFT per day",
+            collection_mint_info.key,
+            reward_rate,
+            reward_mint_info.key
+        );
+        Ok(())
+    }
+
+    fn process_stake_nft(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_info = next_account_info(account_info_iter)?;
+        let staking_config_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let nft_metadata_info = next_account_info(account_info_iter)?;
+        let owner_token_account_info = next_account_info(account_info_iter)?;
+        let stake_info = next_account_info(account_info_iter)?;
+        let escrow_token_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(owner_info)?;
+        validation::check_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+        validation::check_token_account(
+            owner_token_account_info,
+            owner_info.key,
+            nft_mint_info.key,
+        )?;
+
+        let staking_config = Self::load_staking_config(program_id, staking_config_info)?;
+        Self::verify_collection_member(
+            nft_mint_info,
+            nft_metadata_info,
+            &staking_config.collection_mint,
+        )?;
+
+        validation::check_uninitialized(stake_info)?;
+
+        let (stake_pda, stake_bump) =
+            crate::state::get_stake_pda(program_id, nft_mint_info.key, owner_info.key);
+        if stake_pda != *stake_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let rent = Rent::from_account_info(rent_info)?;
+
+        // Create stake account
+        invoke_signed(
+            &system_instruction::create_account(
+                owner_info.key,
+                stake_info.key,
+                rent.minimum_balance(Stake::LEN),
+                Stake::LEN as u64,
+                program_id,
+            ),
+            &[
+                owner_info.clone(),
+                stake_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[
+                STAKE_SEED,
+                nft_mint_info.key.as_ref(),
+                owner_info.key.as_ref(),
+                &[stake_bump],
+            ]],
+        )?;
+
+        // Create the escrow token account, owned by the stake PDA
+        invoke(
+            &create_associated_token_account(
+                owner_info.key,
+                stake_info.key,
+                nft_mint_info.key,
+                token_program_info.key,
+            ),
+            &[
+                owner_info.clone(),
+                escrow_token_account_info.clone(),
+                stake_info.clone(),
+                nft_mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+
+        // Move the NFT into escrow
+        Self::transfer_nft(
+            token_program_info,
+            owner_token_account_info,
+            nft_mint_info,
+            escrow_token_account_info,
+            owner_info,
+            account_info_iter.as_slice(),
+            &[],
+        )?;
+
+        // Rewards accrue from the config's reward index as of now
+        let now = Clock::get()?.unix_timestamp;
+        let stake = Stake::new(
+            staking_config_info.key.to_bytes(),
+            owner_info.key.to_bytes(),
+            nft_mint_info.key.to_bytes(),
+            now,
+            staking_config.reward_index_at(now)?,
+            stake_bump,
+        );
+        Stake::pack(stake, &mut stake_info.data.borrow_mut())?;
+
+        // Emit event for indexer
+        MarketplaceEvent::NftStaked(StakeEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            owner: owner_info.key.to_bytes(),
+            collection: staking_config.collection_mint,
+            stake: stake_info.key.to_bytes(),
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_unstake_nft(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_info = next_account_info(account_info_iter)?;
+        let staking_config_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let stake_info = next_account_info(account_info_iter)?;
+        let escrow_token_account_info = next_account_info(account_info_iter)?;
+        let owner_token_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let reward_mint_info = next_account_info(account_info_iter)?;
+        let reward_vault_info = next_account_info(account_info_iter)?;
+        let owner_reward_token_account_info = next_account_info(account_info_iter)?;
+        let reward_token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(owner_info)?;
+        validation::check_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        let staking_config = Self::load_staking_config(program_id, staking_config_info)?;
+        let stake = Self::load_stake(
+            program_id,
+            stake_info,
+            staking_config_info,
+            nft_mint_info,
+            owner_info,
+        )?;
+        Self::verify_reward_accounts(
+            staking_config_info,
+            &staking_config,
+            reward_mint_info,
+            reward_vault_info,
+            reward_token_program_info,
+        )?;
+
+        // Rewards due aren't forfeited: until the vault is funded to cover
+        // them, the NFT stays staked
+        let rewards = stake.rewards_due(&staking_config, Clock::get()?.unix_timestamp)?;
+        if rewards > Self::token_balance(reward_vault_info)? {
+            return Err(MarketplaceError::InsufficientRewards.into());
+        }
+        if rewards > 0 {
+            Self::pay_rewards(
+                staking_config_info,
+                &staking_config,
+                reward_mint_info,
+                reward_vault_info,
+                owner_reward_token_account_info,
+                owner_info,
+                reward_token_program_info,
+                associated_token_program_info,
+                system_program_info,
+                rewards,
+            )?;
+        }
+
+        // The owner may have closed their token account after staking
+        invoke(
+            &create_associated_token_account_idempotent(
+                owner_info.key,
+                owner_info.key,
+                nft_mint_info.key,
+                token_program_info.key,
+            ),
+            &[
+                owner_info.clone(),
+                owner_token_account_info.clone(),
+                owner_info.clone(),
+                nft_mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+
+        let stake_seeds: &[&[u8]] = &[
+            STAKE_SEED,
+            nft_mint_info.key.as_ref(),
+            owner_info.key.as_ref(),
+            &[stake.bump],
+        ];
+        Self::release_escrow(
+            stake_info,
+            stake_seeds,
+            escrow_token_account_info,
+            nft_mint_info,
+            owner_token_account_info,
+            owner_info,
+            token_program_info,
+            account_info_iter.as_slice(),
+        )?;
+
+        // Emit event for indexer
+        MarketplaceEvent::NftUnstaked(UnstakeEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            owner: owner_info.key.to_bytes(),
+            collection: staking_config.collection_mint,
+            rewards,
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_claim_rewards(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_info = next_account_info(account_info_iter)?;
+        let staking_config_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let stake_info = next_account_info(account_info_iter)?;
+        let reward_mint_info = next_account_info(account_info_iter)?;
+        let reward_vault_info = next_account_info(account_info_iter)?;
+        let owner_reward_token_account_info = next_account_info(account_info_iter)?;
+        let reward_token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(owner_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        let staking_config = Self::load_staking_config(program_id, staking_config_info)?;
+        let mut stake = Self::load_stake(
+            program_id,
+            stake_info,
+            staking_config_info,
+            nft_mint_info,
+            owner_info,
+        )?;
+        Self::verify_reward_accounts(
+            staking_config_info,
+            &staking_config,
+            reward_mint_info,
+            reward_vault_info,
+            reward_token_program_info,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let rewards = stake.rewards_due(&staking_config, now)?;
+        if rewards > Self::token_balance(reward_vault_info)? {
+            return Err(MarketplaceError::InsufficientRewards.into());
+        }
+        Self::pay_rewards(
+            staking_config_info,
+            &staking_config,
+            reward_mint_info,
+            reward_vault_info,
+            owner_reward_token_account_info,
+            owner_info,
+            reward_token_program_info,
+            associated_token_program_info,
+            system_program_info,
+            rewards,
+        )?;
+
+        stake.record_claim(rewards, now)?;
+        Stake::pack(stake, &mut stake_info.data.borrow_mut())?;
+
+        // Emit event for indexer
+        MarketplaceEvent::RewardsClaimed(RewardClaimEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            owner: owner_info.key.to_bytes(),
+            reward_mint: staking_config.reward_mint,
+            amount: rewards,
+        })
+        .emit();
+        Ok(())
+    }
+
+    /// Load a staking config, checking its address
+    fn load_staking_config(
+        program_id: &Pubkey,
+        staking_config_info: &AccountInfo,
+    ) -> Result<StakingConfig, ProgramError> {
+        if staking_config_info.owner != program_id {
+            return Err(MarketplaceError::AccountNotInitialized.into());
+        }
+        let staking_config = StakingConfig::unpack(&staking_config_info.data.borrow())?;
+
+        validation::check_pda(
+            staking_config_info,
+            &[
+                STAKING_CONFIG_SEED,
+                &staking_config.collection_mint,
+                &[staking_config.bump],
+            ],
+            program_id,
+        )?;
+        Ok(staking_config)
+    }
+
+    /// Load the stake of `nft_mint_info` by `owner_info` under
+    /// `staking_config_info`, checking its address
+    fn load_stake(
+        program_id: &Pubkey,
+        stake_info: &AccountInfo,
+        staking_config_info: &AccountInfo,
+        nft_mint_info: &AccountInfo,
+        owner_info: &AccountInfo,
+    ) -> Result<Stake, ProgramError> {
+        if stake_info.owner != program_id {
+            return Err(MarketplaceError::AccountNotInitialized.into());
+        }
+        let stake = Stake::unpack(&stake_info.data.borrow())?;
+
+        if stake.owner != owner_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidSeller.into());
+        }
+        if stake.nft_mint != nft_mint_info.key.to_bytes()
+            || stake.staking_config != staking_config_info.key.to_bytes()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        validation::check_pda(
+            stake_info,
+            &[
+                STAKE_SEED,
+                nft_mint_info.key.as_ref(),
+                owner_info.key.as_ref(),
+                &[stake.bump],
+            ],
+            program_id,
+        )?;
+        Ok(stake)
+    }
+
+    /// Check the reward mint, vault and token program passed for a staking
+    /// config are the ones it pays from
+    fn verify_reward_accounts(
+        staking_config_info: &AccountInfo,
+        staking_config: &StakingConfig,
+        reward_mint_info: &AccountInfo,
+        reward_vault_info: &AccountInfo,
+        reward_token_program_info: &AccountInfo,
+    ) -> ProgramResult {
+        validation::check_token_program(reward_token_program_info)?;
+        if reward_mint_info.key.to_bytes() != staking_config.reward_mint
+            || reward_mint_info.owner != reward_token_program_info.key
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if *reward_vault_info.key
+            != get_associated_token_address_with_program_id(
+                staking_config_info.key,
+                reward_mint_info.key,
+                reward_token_program_info.key,
+            )
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    /// Tokens held by an SPL Token or Token-2022 account
+    fn token_balance(token_account_info: &AccountInfo) -> Result<u64, ProgramError> {
+        let data = token_account_info.data.borrow();
+        Ok(StateWithExtensions::<TokenAccount>::unpack(&data)?
+            .base
+            .amount)
+    }
+
+    /// Send `amount` reward tokens from a staking config's vault to the
+    /// associated token account of `owner_info` (created if missing), signed
+    /// by the config
+    #[allow(clippy::too_many_arguments)]
+    fn pay_rewards<'a>(
+        staking_config_info: &AccountInfo<'a>,
+        staking_config: &StakingConfig,
+        reward_mint_info: &AccountInfo<'a>,
+        reward_vault_info: &AccountInfo<'a>,
+        owner_reward_token_account_info: &AccountInfo<'a>,
+        owner_info: &AccountInfo<'a>,
+        reward_token_program_info: &AccountInfo<'a>,
+        associated_token_program_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        amount: u64,
+    ) -> ProgramResult {
+        invoke(
+            &create_associated_token_account_idempotent(
+                owner_info.key,
+                owner_info.key,
+                reward_mint_info.key,
+                reward_token_program_info.key,
+            ),
+            &[
+                owner_info.clone(),
+                owner_reward_token_account_info.clone(),
+                owner_info.clone(),
+                reward_mint_info.clone(),
+                system_program_info.clone(),
+                reward_token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+
+        let decimals = StateWithExtensions::<TokenMint>::unpack(&reward_mint_info.data.borrow())?
+            .base
+            .decimals;
+        spl_token_2022::onchain::invoke_transfer_checked(
+            reward_token_program_info.key,
+            reward_vault_info.clone(),
+            reward_mint_info.clone(),
+            owner_reward_token_account_info.clone(),
+            staking_config_info.clone(),
+            &[],
+            amount,
+            decimals,
+            &[&[
+                STAKING_CONFIG_SEED,
+                &staking_config.collection_mint,
+                &[staking_config.bump],
+            ]],
+        )
+    }
+
+    fn process_make_offer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        expires_at: i64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let bidder_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let offer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if amount == 0 {
+            return Err(MarketplaceError::InvalidPrice.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        if expires_at != 0 && expires_at <= now {
+            return Err(MarketplaceError::OfferExpired.into());
+        }
+
+        validation::check_signer(bidder_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        // Verify the marketplace exists
+        validation::check_owner(marketplace_info, program_id)?;
+        Self::read_marketplace(marketplace_info)?;
+
+        // Verify the mint is an SPL Token or Token-2022 mint
+        if spl_token_2022::check_spl_token_program_account(nft_mint_info.owner).is_err() {
+            return Err(MarketplaceError::InvalidAccountOwner.into());
+        }
+
+        validation::check_uninitialized(offer_info)?;
+
+        let (offer_pda, offer_bump) =
+            crate::state::get_offer_pda(program_id, nft_mint_info.key, bidder_info.key);
+
+        if offer_pda != *offer_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let rent = Rent::from_account_info(rent_info)?;
+        let lamports = rent
+            .minimum_balance(Offer::LEN)
+            .checked_add(amount)
+            .ok_or(MarketplaceError::AmountOverflow)?;
+
+        if bidder_info.lamports() < lamports {
+            return Err(MarketplaceError::InsufficientFunds.into());
+        }
+
+        // Create the offer account holding its rent and the offered lamports
+        invoke_signed(
+            &system_instruction::create_account(
+                bidder_info.key,
+                offer_info.key,
+                lamports,
+                Offer::LEN as u64,
+                program_id,
+            ),
+            &[
+                bidder_info.clone(),
+                offer_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[
+                OFFER_SEED,
+                nft_mint_info.key.as_ref(),
+                bidder_info.key.as_ref(),
+                &[offer_bump],
+            ]],
+        )?;
+
+        let offer = Offer::new(
+            marketplace_info.key.to_bytes(),
+            bidder_info.key.to_bytes(),
+            nft_mint_info.key.to_bytes(),
+            amount,
+            expires_at,
+            now,
+            offer_bump,
+        );
+        Offer::pack(offer, &mut offer_info.data.borrow_mut())?;
+
+        // Emit event for indexer
+        MarketplaceEvent::OfferMade(OfferEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            bidder: bidder_info.key.to_bytes(),
+            amount,
+            expires_at,
+            offer: offer_info.key.to_bytes(),
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_accept_offer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        expected_amount: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let seller_info = next_account_info(account_info_iter)?;
+        let bidder_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let fee_recipient_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let offer_info = next_account_info(account_info_iter)?;
+        let seller_token_account_info = next_account_info(account_info_iter)?;
+        let bidder_token_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let nft_metadata_info = next_account_info(account_info_iter)?;
+        let collection_config_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(seller_info)?;
+        validation::check_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+        validation::check_token_account(
+            seller_token_account_info,
+            seller_info.key,
+            nft_mint_info.key,
+        )?;
+
+        // Load offer data
+        if offer_info.owner != program_id {
+            return Err(MarketplaceError::AccountNotInitialized.into());
+        }
+        let offer = Offer::unpack(&offer_info.data.borrow())?;
+        Self::verify_offer_address(program_id, offer_info, &offer, nft_mint_info, bidder_info)?;
+
+        if seller_info.key == bidder_info.key {
+            return Err(MarketplaceError::InvalidSeller.into());
+        }
+        if offer.amount != expected_amount {
+            return Err(MarketplaceError::ExpectedAmountMismatch.into());
+        }
+        if offer.is_expired(Clock::get()?.unix_timestamp) {
+            return Err(MarketplaceError::OfferExpired.into());
+        }
+
+        // Load marketplace data
+        if offer.marketplace != marketplace_info.key.to_bytes()
+            || marketplace_info.owner != program_id
+        {
+            return Err(MarketplaceError::InvalidAccountOwner.into());
+        }
+        let mut marketplace = Self::read_marketplace(marketplace_info)?;
+        if marketplace.fee_recipient != fee_recipient_info.key.to_bytes() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // A collection config's fee takes precedence for this sale
+        let collection_config = Self::load_collection_config(
+            program_id,
+            marketplace_info,
+            nft_mint_info,
+            nft_metadata_info,
+            collection_config_info,
+        )?;
+        if let Some(fee_percentage) = collection_config
+            .as_ref()
+            .and_then(|(collection_config, _)| collection_config.fee_percentage)
+        {
+            marketplace.fee_percentage = fee_percentage;
+        }
+        let royalty_payments =
+            Self::royalty_payments(collection_config.as_ref(), offer.amount, account_info_iter)?;
+
+        let fee = marketplace.calculate_fee(offer.amount)?;
+        let seller_proceeds = Self::deduct_royalties(
+            marketplace.calculate_seller_proceeds(offer.amount)?,
+            &royalty_payments,
+        )?;
+
+        // Deliver the NFT; the token program checks the seller holds it
+        invoke(
+            &create_associated_token_account_idempotent(
+                seller_info.key,
+                bidder_info.key,
+                nft_mint_info.key,
+                token_program_info.key,
+            ),
+            &[
+                seller_info.clone(),
+                bidder_token_account_info.clone(),
+                bidder_info.clone(),
+                nft_mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+        Self::transfer_nft(
+            token_program_info,
+            seller_token_account_info,
+            nft_mint_info,
+            bidder_token_account_info,
+            seller_info,
+            account_info_iter.as_slice(),
+            &[],
+        )?;
+
+        // Pay the seller, the marketplace and the creators from the offer,
+        // then return the offer's rent to the bidder
+        Self::move_lamports(offer_info, seller_info, seller_proceeds)?;
+        Self::move_lamports(offer_info, fee_recipient_info, fee)?;
+        for &(creator_info, royalty) in &royalty_payments {
+            Self::move_lamports(offer_info, creator_info, royalty)?;
+        }
+        Self::close_program_account(offer_info, bidder_info)?;
+
+        // Emit event for indexer
+        MarketplaceEvent::OfferAccepted(OfferAcceptEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            seller: seller_info.key.to_bytes(),
+            bidder: bidder_info.key.to_bytes(),
+            amount: offer.amount,
+            fee,
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_cancel_offer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let bidder_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let offer_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(bidder_info)?;
+
+        // Load offer data
+        if offer_info.owner != program_id {
+            return Err(MarketplaceError::AccountNotInitialized.into());
+        }
+        let offer = Offer::unpack(&offer_info.data.borrow())?;
+
+        // Verify the signer is the bidder who made the offer
+        if offer.bidder != bidder_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidBuyer.into());
+        }
+        Self::verify_offer_address(program_id, offer_info, &offer, nft_mint_info, bidder_info)?;
+
+        // Refund the offered lamports along with the rent
+        Self::close_program_account(offer_info, bidder_info)?;
+
+        // Emit event for indexer
+        MarketplaceEvent::OfferCancelled(OfferCancelEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            bidder: bidder_info.key.to_bytes(),
+            offer: offer_info.key.to_bytes(),
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_create_auction(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        reserve_price: u64,
+        end_time: i64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let seller_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let seller_token_account_info = next_account_info(account_info_iter)?;
+        let auction_info = next_account_info(account_info_iter)?;
+        let escrow_token_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if reserve_price == 0 {
+            return Err(MarketplaceError::InvalidPrice.into());
+        }
+        if end_time <= Clock::get()?.unix_timestamp {
+            return Err(MarketplaceError::AuctionEnded.into());
+        }
+
+        validation::check_signer(seller_info)?;
+        validation::check_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+        validation::check_token_account(
+            seller_token_account_info,
+            seller_info.key,
+            nft_mint_info.key,
+        )?;
+
+        // Verify the marketplace exists
+        validation::check_owner(marketplace_info, program_id)?;
+        Self::read_marketplace(marketplace_info)?;
+
+        validation::check_uninitialized(auction_info)?;
+
+        let (auction_pda, auction_bump) =
+            crate::state::get_auction_pda(program_id, nft_mint_info.key, seller_info.key);
+
+        if auction_pda != *auction_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let rent = Rent::from_account_info(rent_info)?;
+
+        // Create auction account
+        invoke_signed(
+            &system_instruction::create_account(
+                seller_info.key,
+                auction_info.key,
+                rent.minimum_balance(Auction::LEN),
+                Auction::LEN as u64,
+                program_id,
+            ),
+            &[
+                seller_info.clone(),
+                auction_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[
+                AUCTION_SEED,
+                nft_mint_info.key.as_ref(),
+                seller_info.key.as_ref(),
+                &[auction_bump],
+            ]],
+        )?;
+
+        // Create the escrow token account, owned by the auction PDA
+        invoke(
+            &create_associated_token_account(
+                seller_info.key,
+                auction_info.key,
+                nft_mint_info.key,
+                token_program_info.key,
+            ),
+            &[
+                seller_info.clone(),
+                escrow_token_account_info.clone(),
+                auction_info.clone(),
+                nft_mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+
+        // Move the NFT into escrow
+        Self::transfer_nft(
+            token_program_info,
+            seller_token_account_info,
+            nft_mint_info,
+            escrow_token_account_info,
+            seller_info,
+            account_info_iter.as_slice(),
+            &[],
+        )?;
+
+        let auction = Auction::new(
+            marketplace_info.key.to_bytes(),
+            seller_info.key.to_bytes(),
+            nft_mint_info.key.to_bytes(),
+            reserve_price,
+            end_time,
+            auction_bump,
+        );
+        Auction::pack(auction, &mut auction_info.data.borrow_mut())?;
+
+        // Emit event for indexer
+        MarketplaceEvent::AuctionCreated(AuctionCreateEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            seller: seller_info.key.to_bytes(),
+            reserve_price,
+            end_time,
+            auction: auction_info.key.to_bytes(),
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_place_bid(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let bidder_info = next_account_info(account_info_iter)?;
+        let auction_info = next_account_info(account_info_iter)?;
+        let highest_bidder_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(bidder_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        // Load auction data
+        if auction_info.owner != program_id {
+            return Err(MarketplaceError::AccountNotInitialized.into());
+        }
+        let mut auction = Auction::unpack(&auction_info.data.borrow())?;
+        Self::verify_auction_address(program_id, auction_info, &auction)?;
+
+        if Clock::get()?.unix_timestamp >= auction.end_time {
+            return Err(MarketplaceError::AuctionEnded.into());
+        }
+        if auction.seller == bidder_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidBuyer.into());
+        }
+        if amount < auction.minimum_bid()? {
+            return Err(MarketplaceError::BidTooLow.into());
+        }
+        if auction.has_bids() && auction.highest_bidder != highest_bidder_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidBuyer.into());
+        }
+
+        // Escrow the new bid in the auction account
+        invoke(
+            &system_instruction::transfer(bidder_info.key, auction_info.key, amount),
+            &[
+                bidder_info.clone(),
+                auction_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+
+        // Refund the bid it beats
+        if auction.has_bids() {
+            Self::move_lamports(auction_info, highest_bidder_info, auction.highest_bid)?;
+            validation::check_rent_exempt(auction_info, &Rent::get()?)?;
+        }
+
+        auction.highest_bid = amount;
+        auction.highest_bidder = bidder_info.key.to_bytes();
+        Auction::pack(auction, &mut auction_info.data.borrow_mut())?;
+
+        // Emit event for indexer
+        MarketplaceEvent::BidPlaced(BidEvent {
+            auction: auction_info.key.to_bytes(),
+            bidder: bidder_info.key.to_bytes(),
+            amount,
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_settle_auction(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_info = next_account_info(account_info_iter)?;
+        let seller_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let fee_recipient_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let auction_info = next_account_info(account_info_iter)?;
+        let escrow_token_account_info = next_account_info(account_info_iter)?;
+        let recipient_info = next_account_info(account_info_iter)?;
+        let recipient_token_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let nft_metadata_info = next_account_info(account_info_iter)?;
+        let collection_config_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(payer_info)?;
+        validation::check_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        // Load auction data
+        if auction_info.owner != program_id {
+            return Err(MarketplaceError::AccountNotInitialized.into());
+        }
+        let auction = Auction::unpack(&auction_info.data.borrow())?;
+        Self::verify_auction_address(program_id, auction_info, &auction)?;
+
+        if auction.seller != seller_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidSeller.into());
+        }
+        if auction.nft_mint != nft_mint_info.key.to_bytes() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if Clock::get()?.unix_timestamp < auction.end_time {
+            return Err(MarketplaceError::AuctionNotEnded.into());
+        }
+
+        // The NFT goes to the winner, or back to the seller without bids
+        let recipient = if auction.has_bids() {
+            auction.highest_bidder
+        } else {
+            auction.seller
+        };
+        if recipient != recipient_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidBuyer.into());
+        }
+
+        // Load marketplace data
+        if auction.marketplace != marketplace_info.key.to_bytes()
+            || marketplace_info.owner != program_id
+        {
+            return Err(MarketplaceError::InvalidAccountOwner.into());
+        }
+        let mut marketplace = Self::read_marketplace(marketplace_info)?;
+        if marketplace.fee_recipient != fee_recipient_info.key.to_bytes() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Pay the seller, the marketplace and the creators from the winning
+        // bid, at the collection config's fee if it sets one
+        let mut fee = 0;
+        if auction.has_bids() {
+            let collection_config = Self::load_collection_config(
+                program_id,
+                marketplace_info,
+                nft_mint_info,
+                nft_metadata_info,
+                collection_config_info,
+            )?;
+            if let Some(fee_percentage) = collection_config
+                .as_ref()
+                .and_then(|(collection_config, _)| collection_config.fee_percentage)
+            {
+                marketplace.fee_percentage = fee_percentage;
+            }
+            let royalty_payments = Self::royalty_payments(
+                collection_config.as_ref(),
+                auction.highest_bid,
+                account_info_iter,
+            )?;
+
+            fee = marketplace.calculate_fee(auction.highest_bid)?;
+            let seller_proceeds = Self::deduct_royalties(
+                marketplace.calculate_seller_proceeds(auction.highest_bid)?,
+                &royalty_payments,
+            )?;
+            Self::move_lamports(auction_info, seller_info, seller_proceeds)?;
+            Self::move_lamports(auction_info, fee_recipient_info, fee)?;
+            for &(creator_info, royalty) in &royalty_payments {
+                Self::move_lamports(auction_info, creator_info, royalty)?;
+            }
+        }
+
+        // Deliver the NFT and close the escrow and auction accounts
+        invoke(
+            &create_associated_token_account_idempotent(
+                payer_info.key,
+                recipient_info.key,
+                nft_mint_info.key,
+                token_program_info.key,
+            ),
+            &[
+                payer_info.clone(),
+                recipient_token_account_info.clone(),
+                recipient_info.clone(),
+                nft_mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+        Self::release_escrow(
+            auction_info,
+            &[
+                AUCTION_SEED,
+                nft_mint_info.key.as_ref(),
+                seller_info.key.as_ref(),
+                &[auction.bump],
+            ],
+            escrow_token_account_info,
+            nft_mint_info,
+            recipient_token_account_info,
+            seller_info,
+            token_program_info,
+            account_info_iter.as_slice(),
+        )?;
+
+        // Emit event for indexer
+        MarketplaceEvent::AuctionSettled(AuctionSettleEvent {
+            mint: nft_mint_info.key.to_bytes(),
+            seller: seller_info.key.to_bytes(),
+            winner: recipient_info.key.to_bytes(),
+            amount: auction.highest_bid,
+            fee,
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_make_collection_offer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        expires_at: i64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let bidder_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let collection_mint_info = next_account_info(account_info_iter)?;
+        let collection_offer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if amount == 0 {
+            return Err(MarketplaceError::InvalidPrice.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        if expires_at != 0 && expires_at <= now {
+            return Err(MarketplaceError::OfferExpired.into());
+        }
+
+        validation::check_signer(bidder_info)?;
+        validation::check_system_program(system_program_info)?;
+
+        // Verify the marketplace exists
+        validation::check_owner(marketplace_info, program_id)?;
+        Self::read_marketplace(marketplace_info)?;
+
+        // Verify the collection mint is an SPL Token or Token-2022 mint
+        if spl_token_2022::check_spl_token_program_account(collection_mint_info.owner).is_err() {
+            return Err(MarketplaceError::InvalidAccountOwner.into());
+        }
+
+        validation::check_uninitialized(collection_offer_info)?;
+
+        let (collection_offer_pda, collection_offer_bump) = crate::state::get_collection_offer_pda(
+            program_id,
+            collection_mint_info.key,
+            bidder_info.key,
+        );
+
+        if collection_offer_pda != *collection_offer_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let rent = Rent::from_account_info(rent_info)?;
+        let lamports = rent
+            .minimum_balance(CollectionOffer::LEN)
+            .checked_add(amount)
+            .ok_or(MarketplaceError::AmountOverflow)?;
+
+        if bidder_info.lamports() < lamports {
+            return Err(MarketplaceError::InsufficientFunds.into());
+        }
+
+        // Create the collection offer account holding its rent and the offered lamports
+        invoke_signed(
+            &system_instruction::create_account(
+                bidder_info.key,
+                collection_offer_info.key,
+                lamports,
+                CollectionOffer::LEN as u64,
+                program_id,
+            ),
+            &[
+                bidder_info.clone(),
+                collection_offer_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[
+                COLLECTION_OFFER_SEED,
+                collection_mint_info.key.as_ref(),
+                bidder_info.key.as_ref(),
+                &[collection_offer_bump],
+            ]],
+        )?;
+
+        let collection_offer = CollectionOffer::new(
+            marketplace_info.key.to_bytes(),
+            bidder_info.key.to_bytes(),
+            collection_mint_info.key.to_bytes(),
+            amount,
+            expires_at,
+            now,
+            collection_offer_bump,
+        );
+        CollectionOffer::pack(
+            collection_offer,
+            &mut collection_offer_info.data.borrow_mut(),
+        )?;
+
+        // Emit event for indexer
+        MarketplaceEvent::CollectionOfferMade(CollectionOfferEvent {
+            collection: collection_mint_info.key.to_bytes(),
+            bidder: bidder_info.key.to_bytes(),
+            amount,
+            expires_at,
+            offer: collection_offer_info.key.to_bytes(),
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_accept_collection_offer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        expected_amount: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let seller_info = next_account_info(account_info_iter)?;
+        let bidder_info = next_account_info(account_info_iter)?;
+        let marketplace_info = next_account_info(account_info_iter)?;
+        let fee_recipient_info = next_account_info(account_info_iter)?;
+        let nft_mint_info = next_account_info(account_info_iter)?;
+        let nft_metadata_info = next_account_info(account_info_iter)?;
+        let collection_offer_info = next_account_info(account_info_iter)?;
+        let seller_token_account_info = next_account_info(account_info_iter)?;
+        let bidder_token_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let collection_config_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(seller_info)?;
+        validation::check_token_program(token_program_info)?;
+        validation::check_associated_token_program(associated_token_program_info)?;
+        validation::check_system_program(system_program_info)?;
+        validation::check_token_account(
+            seller_token_account_info,
+            seller_info.key,
+            nft_mint_info.key,
+        )?;
+
+        // Load collection offer data
+        if collection_offer_info.owner != program_id {
+            return Err(MarketplaceError::AccountNotInitialized.into());
+        }
+        let collection_offer = CollectionOffer::unpack(&collection_offer_info.data.borrow())?;
+        Self::verify_collection_offer_address(
+            program_id,
+            collection_offer_info,
+            &collection_offer,
+            &collection_offer.collection_mint,
+            bidder_info,
+        )?;
+
+        if seller_info.key == bidder_info.key {
+            return Err(MarketplaceError::InvalidSeller.into());
+        }
+        if collection_offer.amount != expected_amount {
+            return Err(MarketplaceError::ExpectedAmountMismatch.into());
+        }
+        if collection_offer.is_expired(Clock::get()?.unix_timestamp) {
+            return Err(MarketplaceError::OfferExpired.into());
+        }
+
+        Self::verify_collection_member(
+            nft_mint_info,
+            nft_metadata_info,
+            &collection_offer.collection_mint,
+        )?;
+
+        // Load marketplace data
+        if collection_offer.marketplace != marketplace_info.key.to_bytes()
+            || marketplace_info.owner != program_id
+        {
+            return Err(MarketplaceError::InvalidAccountOwner.into());
+        }
+        let mut marketplace = Self::read_marketplace(marketplace_info)?;
+        if marketplace.fee_recipient != fee_recipient_info.key.to_bytes() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // The NFT is verified into the offer's collection, so this is the
+        // offer collection's config
+        let collection_config = Self::load_collection_config(
+            program_id,
+            marketplace_info,
+            nft_mint_info,
+            nft_metadata_info,
+            collection_config_info,
+        )?;
+        if let Some(fee_percentage) = collection_config
+            .as_ref()
+            .and_then(|(collection_config, _)| collection_config.fee_percentage)
+        {
+            marketplace.fee_percentage = fee_percentage;
+        }
+        let royalty_payments = Self::royalty_payments(
+            collection_config.as_ref(),
+            collection_offer.amount,
+            account_info_iter,
+        )?;
+
+        let fee = marketplace.calculate_fee(collection_offer.amount)?;
+        let seller_proceeds = Self::deduct_royalties(
+            marketplace.calculate_seller_proceeds(collection_offer.amount)?,
+            &royalty_payments,
+        )?;
+
+        // Deliver the NFT; the token program checks the seller holds it
+        invoke(
+            &create_associated_token_account_idempotent(
+                seller_info.key,
+                bidder_info.key,
+                nft_mint_info.key,
+                token_program_info.key,
+            ),
+            &[
+                seller_info.clone(),
+                bidder_token_account_info.clone(),
+                bidder_info.clone(),
+                nft_mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+        Self::transfer_nft(
+            token_program_info,
+            seller_token_account_info,
+            nft_mint_info,
+            bidder_token_account_info,
+            seller_info,
+            account_info_iter.as_slice(),
+            &[],
+        )?;
+
+        // Pay the seller, the marketplace and the creators from the offer,
+        // then return the offer's rent to the bidder
+        Self::move_lamports(collection_offer_info, seller_info, seller_proceeds)?;
+        Self::move_lamports(collection_offer_info, fee_recipient_info, fee)?;
+        for &(creator_info, royalty) in &royalty_payments {
+            Self::move_lamports(collection_offer_info, creator_info, royalty)?;
+        }
+        Self::close_program_account(collection_offer_info, bidder_info)?;
+
+        // Emit event for indexer
+        MarketplaceEvent::CollectionOfferAccepted(CollectionOfferAcceptEvent {
+            collection: collection_offer.collection_mint,
+            mint: nft_mint_info.key.to_bytes(),
+            seller: seller_info.key.to_bytes(),
+            bidder: bidder_info.key.to_bytes(),
+            amount: collection_offer.amount,
+            fee,
+        })
+        .emit();
+        Ok(())
+    }
+
+    fn process_cancel_collection_offer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let bidder_info = next_account_info(account_info_iter)?;
+        let collection_mint_info = next_account_info(account_info_iter)?;
+        let collection_offer_info = next_account_info(account_info_iter)?;
+
+        validation::check_signer(bidder_info)?;
+
+        // Load collection offer data
+        if collection_offer_info.owner != program_id {
+            return Err(MarketplaceError::AccountNotInitialized.into());
+        }
+        let collection_offer = CollectionOffer::unpack(&collection_offer_info.data.borrow())?;
+
+        // Verify the signer is the bidder who made the offer
+        if collection_offer.bidder != bidder_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidBuyer.into());
+        }
+        Self::verify_collection_offer_address(
+            program_id,
+            collection_offer_info,
+            &collection_offer,
+            &collection_mint_info.key.to_bytes(),
+            bidder_info,
+        )?;
+
+        // Refund the offered lamports along with the rent
+        Self::close_program_account(collection_offer_info, bidder_info)?;
+
+        // Emit event for indexer
+        MarketplaceEvent::CollectionOfferCancelled(CollectionOfferCancelEvent {
+            collection: collection_mint_info.key.to_bytes(),
+            bidder: bidder_info.key.to_bytes(),
+            offer: collection_offer_info.key.to_bytes(),
+        })
+        .emit();
+        Ok(())
+    }
+
+    /// Check `auction_info` is the auction PDA of the mint and seller stored
+    /// in it
+    fn verify_auction_address(
+        program_id: &Pubkey,
+        auction_info: &AccountInfo,
+        auction: &Auction,
+    ) -> ProgramResult {
+        validation::check_pda(
+            auction_info,
+            &[
+                AUCTION_SEED,
+                &auction.nft_mint,
+                &auction.seller,
+                &[auction.bump],
+            ],
+            program_id,
+        )?;
+        Ok(())
+    }
+
+    /// Check `offer_info` is the offer PDA of the mint and bidder passed with
+    /// it, which also ties the stored offer to both
+    fn verify_offer_address(
+        program_id: &Pubkey,
+        offer_info: &AccountInfo,
+        offer: &Offer,
+        nft_mint_info: &AccountInfo,
+        bidder_info: &AccountInfo,
+    ) -> ProgramResult {
+        validation::check_pda(
+            offer_info,
+            &[
+                OFFER_SEED,
+                nft_mint_info.key.as_ref(),
+                bidder_info.key.as_ref(),
+                &[offer.bump],
+            ],
+            program_id,
+        )?;
+        Ok(())
+    }
+
+    /// Check `collection_offer_info` is the collection offer PDA of
+    /// `collection_mint` and the bidder passed with it
+    fn verify_collection_offer_address(
+        program_id: &Pubkey,
+        collection_offer_info: &AccountInfo,
+        collection_offer: &CollectionOffer,
+        collection_mint: &[u8; 32],
+        bidder_info: &AccountInfo,
+    ) -> ProgramResult {
+        validation::check_pda(
+            collection_offer_info,
+            &[
+                COLLECTION_OFFER_SEED,
+                collection_mint,
+                bidder_info.key.as_ref(),
+                &[collection_offer.bump],
+            ],
+            program_id,
+        )?;
+        Ok(())
+    }
+
+    /// Create and initialize a 0-decimal mint with `owner` as its authorities
+    /// and mint one token to the owner's new associated token account
+    fn create_nft_mint<'a>(
+        owner_info: &AccountInfo<'a>,
+        mint_info: &AccountInfo<'a>,
+        token_account_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        associated_token_program_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        rent_info: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        let rent = Rent::from_account_info(rent_info)?;
+
+        // Calculate required space and rent for mint account
+        let mint_space = Mint::LEN;
+        let mint_rent = rent.minimum_balance(mint_space);
+
+        // Create mint account
+        invoke(
+            &system_instruction::create_account(
+                owner_info.key,
+                mint_info.key,
+                mint_rent,
+                mint_space as u64,
+                token_program_info.key,
+            ),
+            &[
+                owner_info.clone(),
+                mint_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+
+        // Initialize mint account with 0 decimals for NFT
+        invoke(
+            &initialize_mint(
+                token_program_info.key,
+                mint_info.key,
+                owner_info.key,
+                Some(owner_info.key), // freeze authority
+                0,                    // 0 decimals for NFT
+            )?,
+            &[
+                mint_info.clone(),
+                rent_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+
+        // Create associated token account
+        invoke(
+            &create_associated_token_account(
+                owner_info.key,
+                owner_info.key,
+                mint_info.key,
+                token_program_info.key,
+            ),
+            &[
+                owner_info.clone(),
+                token_account_info.clone(),
+                owner_info.clone(),
+                mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+
+        // Mint 1 token (NFT) to the associated token account
+        invoke(
+            &mint_to(
+                token_program_info.key,
+                mint_info.key,
+                token_account_info.key,
+                owner_info.key,
+                &[owner_info.key],
+                1, // Mint 1 NFT
+            )?,
+            &[
+                mint_info.clone(),
+                token_account_info.clone(),
+                owner_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Check the NFT is verified into `collection_mint`, going by its Metaplex
+    /// metadata account. Unverified collections are only the NFT creator's
+    /// claim, so they don't count.
+    fn verify_collection_member(
+        nft_mint_info: &AccountInfo,
+        nft_metadata_info: &AccountInfo,
+        collection_mint: &[u8; 32],
+    ) -> ProgramResult {
+        if nft_metadata_info.owner.to_bytes() != TOKEN_METADATA_PROGRAM_ID {
+            return Err(MarketplaceError::InvalidAccountOwner.into());
+        }
+
+        let (metadata_pda, _) = crate::state::get_metadata_pda(nft_mint_info.key);
+        if metadata_pda != *nft_metadata_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let metadata = Metadata::from_account_data(&nft_metadata_info.data.borrow())
+            .ok_or(MarketplaceError::InvalidCollection)?;
+        if metadata.mint != nft_mint_info.key.to_bytes()
+            || metadata.verified_collection() != Some(*collection_mint)
+        {
+            return Err(MarketplaceError::InvalidCollection.into());
+        }
+        Ok(())
+    }
+
+    /// Read a marketplace account, legacy or current. Not `unpack`, which
+    /// rejects legacy marketplaces for their size.
+    fn read_marketplace(marketplace_info: &AccountInfo) -> Result<Marketplace, ProgramError> {
+        let marketplace = Marketplace::from_account_data(&marketplace_info.data.borrow())?;
+        if !marketplace.is_initialized() {
+            return Err(MarketplaceError::AccountNotInitialized.into());
+        }
+        Ok(marketplace)
+    }
+
+    /// Write back a marketplace account, which `MigrateMarketplace` must have
+    /// grown to the current layout first
+    fn save_marketplace(marketplace: Marketplace, marketplace_info: &AccountInfo) -> ProgramResult {
+        if marketplace.needs_migration() {
+            return Err(MarketplaceError::MarketplaceNeedsMigration.into());
+        }
+        Marketplace::pack(marketplace, &mut marketplace_info.data.borrow_mut())
+    }
+
+    /// Load a marketplace, checking `authority_info` is its authority and has
+    /// signed
+    fn load_marketplace_as_authority(
+        program_id: &Pubkey,
+        marketplace_info: &AccountInfo,
+        authority_info: &AccountInfo,
+    ) -> Result<Marketplace, ProgramError> {
+        // Verify authority is signer. There is no approval threshold here; an
+        // authority that is an external multisig's PDA enforces its own.
+        validation::check_signer(authority_info)?;
+
+        // Load marketplace data
+        validation::check_owner(marketplace_info, program_id)?;
+        let marketplace = Self::read_marketplace(marketplace_info)?;
+
+        // Verify authority
+        if marketplace.authority != authority_info.key.to_bytes() {
+            return Err(MarketplaceError::InvalidMarketplaceAuthority.into());
+        }
+        Ok(marketplace)
+    }
+
+    /// The config of the NFT's verified collection, if there is one, with the
+    /// NFT's metadata. Both accounts are checked against their PDAs, so a
+    /// buyer can't dodge a fee override or royalties by passing other
+    /// accounts.
+    fn load_collection_config(
+        program_id: &Pubkey,
+        marketplace_info: &AccountInfo,
+        nft_mint_info: &AccountInfo,
+        nft_metadata_info: &AccountInfo,
+        collection_config_info: &AccountInfo,
+    ) -> Result<Option<(CollectionConfig, Metadata)>, ProgramError> {
+        let (metadata_pda, _) = crate::state::get_metadata_pda(nft_mint_info.key);
+        if metadata_pda != *nft_metadata_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        // No metadata, or no verified collection: no config applies
+        if nft_metadata_info.owner.to_bytes() != TOKEN_METADATA_PROGRAM_ID {
+            return Ok(None);
+        }
+        let Some(metadata) = Metadata::from_account_data(&nft_metadata_info.data.borrow()) else {
+            return Ok(None);
+        };
+        let Some(collection_mint) = metadata.verified_collection() else {
+            return Ok(None);
+        };
+
+        let (collection_config_pda, _) = crate::state::get_collection_config_pda(
+            program_id,
+            marketplace_info.key,
+            &Pubkey::new_from_array(collection_mint),
+        );
+        if collection_config_pda != *collection_config_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if collection_config_info.owner != program_id {
+            return Ok(None);
+        }
+
+        let collection_config = CollectionConfig::unpack(&collection_config_info.data.borrow())?;
+        Ok(Some((collection_config, metadata)))
+    }
+
+    /// The royalties a sale at `price` owes each creator, with the creator
+    /// accounts to pay, taken from `account_info_iter` in metadata order.
+    /// Nothing is owed unless the collection config enforces royalties.
+    fn royalty_payments<'b, 'a>(
+        collection_config: Option<&(CollectionConfig, Metadata)>,
+        price: u64,
+        account_info_iter: &mut std::slice::Iter<'b, AccountInfo<'a>>,
+    ) -> Result<Vec<(&'b AccountInfo<'a>, u64)>, ProgramError> {
+        let Some((_, metadata)) =
+            collection_config.filter(|(collection_config, _)| collection_config.enforce_royalties)
+        else {
+            return Ok(Vec::new());
+        };
+
+        metadata
+            .royalties(price)
+            .into_iter()
+            .map(|(creator, amount)| {
+                let creator_info = next_account_info(account_info_iter)?;
+                if creator_info.key.to_bytes() != creator {
+                    return Err(ProgramError::InvalidArgument);
+                }
+                Ok((creator_info, amount))
+            })
+            .collect()
+    }
+
+    /// Take `royalty_payments` out of the seller's share of a sale
+    fn deduct_royalties(
+        seller_proceeds: u64,
+        royalty_payments: &[(&AccountInfo, u64)],
+    ) -> Result<u64, ProgramError> {
+        royalty_payments
+            .iter()
+            .try_fold(seller_proceeds, |proceeds, (_, amount)| {
+                proceeds.checked_sub(*amount)
+            })
+            .ok_or_else(|| MarketplaceError::AmountOverflow.into())
+    }
+
+    /// The referral config of the marketplace behind `marketplace_info`, if
+    /// its authority has set one. The account is checked against its PDA, so
+    /// a buyer can't pass a config with a larger share.
+    fn load_referral_config(
+        program_id: &Pubkey,
+        marketplace_info: &AccountInfo,
+        referral_config_info: &AccountInfo,
+    ) -> Result<Option<ReferralConfig>, ProgramError> {
+        let (referral_config_pda, _) =
+            crate::state::get_referral_config_pda(program_id, marketplace_info.key);
+        if referral_config_pda != *referral_config_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if referral_config_info.owner != program_id {
+            return Ok(None);
+        }
+
+        let referral_config = ReferralConfig::unpack(&referral_config_info.data.borrow())?;
+        Ok(Some(referral_config))
+    }
+
+    /// Load the pending authority transfer of the marketplace behind
+    /// `marketplace_info`
+    fn load_authority_transfer(
+        program_id: &Pubkey,
+        authority_transfer_info: &AccountInfo,
+        marketplace_info: &AccountInfo,
+    ) -> Result<AuthorityTransfer, ProgramError> {
+        if authority_transfer_info.owner != program_id {
+            return Err(MarketplaceError::AccountNotInitialized.into());
+        }
+        let authority_transfer = AuthorityTransfer::unpack(&authority_transfer_info.data.borrow())?;
+
+        validation::check_pda(
+            authority_transfer_info,
+            &[
+                AUTHORITY_TRANSFER_SEED,
+                marketplace_info.key.as_ref(),
+                &[authority_transfer.bump],
+            ],
+            program_id,
+        )?;
+        if authority_transfer.marketplace != marketplace_info.key.to_bytes() {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(authority_transfer)
+    }
+
+    /// Create and initialize the stats account of the marketplace behind
+    /// `marketplace_info`, paid for by `payer_info`
+    fn create_marketplace_stats<'a>(
+        program_id: &Pubkey,
+        payer_info: &AccountInfo<'a>,
+        marketplace_info: &AccountInfo<'a>,
+        marketplace_stats_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        rent: &Rent,
+    ) -> ProgramResult {
+        let (marketplace_stats_pda, marketplace_stats_bump) =
+            crate::state::get_marketplace_stats_pda(program_id, marketplace_info.key);
+        if marketplace_stats_pda != *marketplace_stats_info.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_info.key,
+                marketplace_stats_info.key,
+                rent.minimum_balance(MarketplaceTotals::LEN),
+                MarketplaceTotals::LEN as u64,
+                program_id,
+            ),
+            &[
+                payer_info.clone(),
+                marketplace_stats_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[
+                MARKETPLACE_STATS_SEED,
+                marketplace_info.key.as_ref(),
+                &[marketplace_stats_bump],
+            ]],
+        )?;
+
+        let marketplace_stats =
+            MarketplaceTotals::new(marketplace_info.key.to_bytes(), marketplace_stats_bump);
+        MarketplaceTotals::pack(
+            marketplace_stats,
+            &mut marketplace_stats_info.data.borrow_mut(),
+        )
+    }
+
+    /// Load the stats of the marketplace behind `marketplace_info`
+    fn load_marketplace_stats(
+        program_id: &Pubkey,
+        marketplace_stats_info: &AccountInfo,
+        marketplace_info: &AccountInfo,
+    ) -> Result<MarketplaceTotals, ProgramError> {
+        if marketplace_stats_info.owner != program_id {
+            return Err(MarketplaceError::AccountNotInitialized.into());
+        }
+        let marketplace_stats = MarketplaceTotals::unpack(&marketplace_stats_info.data.borrow())?;
+
+        validation::check_pda(
+            marketplace_stats_info,
+            &[
+                MARKETPLACE_STATS_SEED,
+                marketplace_info.key.as_ref(),
+                &[marketplace_stats.bump],
+            ],
+            program_id,
+        )?;
+        if marketplace_stats.marketplace != marketplace_info.key.to_bytes() {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(marketplace_stats)
+    }
+
+    /// Move lamports out of an account owned by this program
+    fn move_lamports(
+        from_info: &AccountInfo,
+        to_info: &AccountInfo,
+        lamports: u64,
+    ) -> ProgramResult {
+        **from_info.lamports.borrow_mut() = from_info
+            .lamports()
+            .checked_sub(lamports)
+            .ok_or(MarketplaceError::InsufficientFunds)?;
+        **to_info.lamports.borrow_mut() = to_info
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(MarketplaceError::AmountOverflow)?;
+        Ok(())
+    }
+
+    /// Close an account owned by this program, moving its lamports to
+    /// `destination_info`. The runtime deletes it at the end of the
+    /// transaction; zeroing the data stops it being read as initialized
+    /// before then.
+    fn close_program_account(
+        account_info: &AccountInfo,
+        destination_info: &AccountInfo,
+    ) -> ProgramResult {
+        Self::move_lamports(account_info, destination_info, account_info.lamports())?;
+        account_info.data.borrow_mut().fill(0);
+        Ok(())
+    }
 }
@@ -1,78 +1,178 @@
-use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::{
-    program_pack::{IsInitialized, Pack, Sealed},
-    pubkey::Pubkey,
+use nft_marketplace_sdk::{
+    ids::{
+        AUCTION_SEED, AUTHORITY_TRANSFER_SEED, BUNDLE_SEED, COLLECTION_CONFIG_SEED,
+        COLLECTION_OFFER_SEED, LISTING_SEED, MARKETPLACE_SEED, MARKETPLACE_STATS_SEED,
+        MASTER_EDITION_SEED, METADATA_SEED, MINT_CONFIG_SEED, MINT_RECORD_SEED, OFFER_SEED,
+        REFERRAL_CONFIG_SEED, RENTAL_SEED, STAKE_SEED, STAKING_CONFIG_SEED,
+        TOKEN_METADATA_PROGRAM_ID,
+    },
+    metadata::EDITION_MARKER_BIT_SIZE,
 };
+use solana_program::pubkey::Pubkey;
 
-/// Marketplace account data
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
-pub struct Marketplace {
-    pub is_initialized: bool,
-    pub authority: Pubkey,
-    pub fee_percentage: u16, // Fee percentage in basis points (e.g., 250 = 2.5%)
-    pub fee_recipient: Pubkey,
+pub use nft_marketplace_sdk::{
+    Auction, AuthorityTransfer, Bundle, CollectionConfig, CollectionOffer, Listing, Marketplace,
+    MarketplaceTotals, MintConfig, MintRecord, Offer, ReferralConfig, Rental, Stake, StakingConfig,
+};
+
+/// Helper function to get marketplace PDA
+pub fn get_marketplace_pda(program_id: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MARKETPLACE_SEED, authority.as_ref()], program_id)
+}
+
+/// Helper function to get listing PDA
+pub fn get_listing_pda(program_id: &Pubkey, nft_mint: &Pubkey, seller: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LISTING_SEED, nft_mint.as_ref(), seller.as_ref()],
+        program_id,
+    )
+}
+
+/// Helper function to get offer PDA
+pub fn get_offer_pda(program_id: &Pubkey, nft_mint: &Pubkey, bidder: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[OFFER_SEED, nft_mint.as_ref(), bidder.as_ref()],
+        program_id,
+    )
+}
+
+/// Helper function to get auction PDA
+pub fn get_auction_pda(program_id: &Pubkey, nft_mint: &Pubkey, seller: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[AUCTION_SEED, nft_mint.as_ref(), seller.as_ref()],
+        program_id,
+    )
+}
+
+/// Helper function to get bundle PDA
+pub fn get_bundle_pda(program_id: &Pubkey, seller: &Pubkey, bundle_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[BUNDLE_SEED, seller.as_ref(), &bundle_id.to_le_bytes()],
+        program_id,
+    )
 }
 
-impl Marketplace {
-    pub const LEN: usize = 1 + 32 + 2 + 32; // 65 bytes
+/// Helper function to get rental PDA
+pub fn get_rental_pda(program_id: &Pubkey, nft_mint: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RENTAL_SEED, nft_mint.as_ref(), owner.as_ref()],
+        program_id,
+    )
+}
+
+/// Helper function to get mint config PDA
+pub fn get_mint_config_pda(program_id: &Pubkey, creator: &Pubkey, config_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[MINT_CONFIG_SEED, creator.as_ref(), &config_id.to_le_bytes()],
+        program_id,
+    )
+}
 
-    pub fn new(authority: Pubkey, fee_percentage: u16, fee_recipient: Pubkey) -> Self {
-        Self {
-            is_initialized: true,
-            authority,
-            fee_percentage,
-            fee_recipient,
-        }
-    }
+/// Helper function to get mint record PDA
+pub fn get_mint_record_pda(
+    program_id: &Pubkey,
+    mint_config: &Pubkey,
+    wallet: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[MINT_RECORD_SEED, mint_config.as_ref(), wallet.as_ref()],
+        program_id,
+    )
+}
 
-    pub fn calculate_fee(&self, price: u64) -> Result<u64, crate::error::MarketplaceError> {
-        let fee = (price as u128)
-            .checked_mul(self.fee_percentage as u128)
-            .ok_or(crate::error::MarketplaceError::AmountOverflow)?
-            .checked_div(10000) // Basis points conversion
-            .ok_or(crate::error::MarketplaceError::MarketplaceFeeCalculationError)?;
+/// Helper function to get staking config PDA
+pub fn get_staking_config_pda(program_id: &Pubkey, collection_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKING_CONFIG_SEED, collection_mint.as_ref()], program_id)
+}
 
-        if fee > u64::MAX as u128 {
-            return Err(crate::error::MarketplaceError::AmountOverflow);
-        }
+/// Helper function to get stake PDA
+pub fn get_stake_pda(program_id: &Pubkey, nft_mint: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_SEED, nft_mint.as_ref(), owner.as_ref()], program_id)
+}
 
-        Ok(fee as u64)
-    }
+/// Helper function to get collection offer PDA
+pub fn get_collection_offer_pda(
+    program_id: &Pubkey,
+    collection_mint: &Pubkey,
+    bidder: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            COLLECTION_OFFER_SEED,
+            collection_mint.as_ref(),
+            bidder.as_ref(),
+        ],
+        program_id,
+    )
+}
 
-    pub fn calculate_seller_proceeds(
-        &self,
-        price: u64,
-    ) -> Result<u64, crate::error::MarketplaceError> {
-        let fee = self.calculate_fee(price)?;
-        price
-            .checked_sub(fee)
-            .ok_or(crate::error::MarketplaceError::AmountOverflow)
-    }
+/// Helper function to get the collection config PDA of a marketplace
+pub fn get_collection_config_pda(
+    program_id: &Pubkey,
+    marketplace: &Pubkey,
+    collection_mint: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            COLLECTION_CONFIG_SEED,
+            marketplace.as_ref(),
+            collection_mint.as_ref(),
+        ],
+        program_id,
+    )
 }
 
-impl Sealed for Marketplace {}
+/// Helper function to get the pending authority transfer PDA of a marketplace
+pub fn get_authority_transfer_pda(program_id: &Pubkey, marketplace: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUTHORITY_TRANSFER_SEED, marketplace.as_ref()], program_id)
+}
 
-impl IsInitialized for Marketplace {
-    fn is_initialized(&self) -> bool {
-        self.is_initialized
-    }
+/// Helper function to get the cumulative stats PDA of a marketplace
+pub fn get_marketplace_stats_pda(program_id: &Pubkey, marketplace: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MARKETPLACE_STATS_SEED, marketplace.as_ref()], program_id)
 }
 
-impl Pack for Marketplace {
-    const LEN: usize = Self::LEN;
+/// Helper function to get the referral config PDA of a marketplace
+pub fn get_referral_config_pda(program_id: &Pubkey, marketplace: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REFERRAL_CONFIG_SEED, marketplace.as_ref()], program_id)
+}
 
-    fn pack_into_slice(&self, dst: &mut [u8]) {
-        let data = self.try_to_vec().unwrap();
-        dst[..data.len()].copy_from_slice(&data);
-    }
+/// Helper function to get the Metaplex metadata PDA of a mint
+pub fn get_metadata_pda(mint: &Pubkey) -> (Pubkey, u8) {
+    let metadata_program_id = Pubkey::new_from_array(TOKEN_METADATA_PROGRAM_ID);
+    Pubkey::find_program_address(
+        &[METADATA_SEED, metadata_program_id.as_ref(), mint.as_ref()],
+        &metadata_program_id,
+    )
+}
 
-    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
-        Self::try_from_slice(src)
-            .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)
-    }
+/// Helper function to get the Metaplex master edition PDA of a mint
+pub fn get_master_edition_pda(mint: &Pubkey) -> (Pubkey, u8) {
+    let metadata_program_id = Pubkey::new_from_array(TOKEN_METADATA_PROGRAM_ID);
+    Pubkey::find_program_address(
+        &[
+            METADATA_SEED,
+            metadata_program_id.as_ref(),
+            mint.as_ref(),
+            MASTER_EDITION_SEED,
+        ],
+        &metadata_program_id,
+    )
 }
 
-/// Helper function to get marketplace PDA
-pub fn get_marketplace_pda(program_id: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
-    Pubkey::find_program_address(&[b"marketplace", authority.as_ref()], program_id)
+/// Helper function to get the Metaplex edition marker PDA covering `edition`
+/// of the master edition of `master_mint`
+pub fn get_edition_marker_pda(master_mint: &Pubkey, edition: u64) -> (Pubkey, u8) {
+    let metadata_program_id = Pubkey::new_from_array(TOKEN_METADATA_PROGRAM_ID);
+    let marker = (edition / EDITION_MARKER_BIT_SIZE).to_string();
+    Pubkey::find_program_address(
+        &[
+            METADATA_SEED,
+            metadata_program_id.as_ref(),
+            master_mint.as_ref(),
+            MASTER_EDITION_SEED,
+            marker.as_bytes(),
+        ],
+        &metadata_program_id,
+    )
 }
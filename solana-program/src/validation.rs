@@ -0,0 +1,127 @@
+//! Account checks shared by the instruction processors. Each returns the
+//! error the processors already use for that failure, so they can replace the
+//! inline checks one for one.
+
+use crate::error::MarketplaceError;
+use nft_marketplace_sdk::ids::TOKEN_METADATA_PROGRAM_ID;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey, rent::Rent, system_program,
+};
+use spl_token_2022::{
+    extension::{transfer_hook::TransferHook, BaseStateWithExtensions, StateWithExtensions},
+    state::{Account as TokenAccount, Mint},
+};
+
+/// The account signed the transaction
+pub fn check_signer(account_info: &AccountInfo) -> ProgramResult {
+    if !account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+/// The account is owned by `owner`
+pub fn check_owner(account_info: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+    if account_info.owner != owner {
+        return Err(MarketplaceError::InvalidAccountOwner.into());
+    }
+    Ok(())
+}
+
+/// The account doesn't exist yet, so the program can create it
+pub fn check_uninitialized(account_info: &AccountInfo) -> ProgramResult {
+    if account_info.owner != &system_program::id() {
+        return Err(MarketplaceError::AccountAlreadyInitialized.into());
+    }
+    Ok(())
+}
+
+/// The account is the program address of `seeds`, which end with the bump
+pub fn check_pda(
+    account_info: &AccountInfo,
+    seeds: &[&[u8]],
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let pda = Pubkey::create_program_address(seeds, program_id)
+        .map_err(|_| ProgramError::InvalidSeeds)?;
+    if pda != *account_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(())
+}
+
+/// The account holds at least the rent-exempt minimum for its size. Checked
+/// after moving lamports out of a program account that stays open.
+pub fn check_rent_exempt(account_info: &AccountInfo, rent: &Rent) -> ProgramResult {
+    if !rent.is_exempt(account_info.lamports(), account_info.data_len()) {
+        return Err(MarketplaceError::NotRentExempt.into());
+    }
+    Ok(())
+}
+
+/// The account is SPL Token or Token-2022
+pub fn check_token_program(account_info: &AccountInfo) -> ProgramResult {
+    spl_token_2022::check_spl_token_program_account(account_info.key)
+}
+
+/// The account is SPL Token, for instructions the token metadata program
+/// only supports there
+pub fn check_spl_token_program(account_info: &AccountInfo) -> ProgramResult {
+    check_program_id(account_info, &spl_token::id())
+}
+
+pub fn check_system_program(account_info: &AccountInfo) -> ProgramResult {
+    check_program_id(account_info, &system_program::id())
+}
+
+pub fn check_associated_token_program(account_info: &AccountInfo) -> ProgramResult {
+    check_program_id(account_info, &spl_associated_token_account::id())
+}
+
+pub fn check_token_metadata_program(account_info: &AccountInfo) -> ProgramResult {
+    check_program_id(
+        account_info,
+        &Pubkey::new_from_array(TOKEN_METADATA_PROGRAM_ID),
+    )
+}
+
+fn check_program_id(account_info: &AccountInfo, program_id: &Pubkey) -> ProgramResult {
+    if account_info.key != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// The account is a token account of `mint` held by `owner`, under SPL Token
+/// or Token-2022
+pub fn check_token_account(
+    account_info: &AccountInfo,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> ProgramResult {
+    spl_token_2022::check_spl_token_program_account(account_info.owner)
+        .map_err(|_| ProgramError::from(MarketplaceError::InvalidAccountOwner))?;
+    let data = account_info.data.borrow();
+    let token_account = StateWithExtensions::<TokenAccount>::unpack(&data)?;
+    if token_account.base.mint != *mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if token_account.base.owner != *owner {
+        return Err(MarketplaceError::InvalidAccountOwner.into());
+    }
+    Ok(())
+}
+
+/// The mint has no transfer hook extension, for instructions that move
+/// several NFTs and have no room for each one's hook accounts. Extensions
+/// can't be added after a mint is initialized, so a mint that passes keeps
+/// passing.
+pub fn check_no_transfer_hook(mint_info: &AccountInfo) -> ProgramResult {
+    let data = mint_info.data.borrow();
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    if mint.get_extension::<TransferHook>().is_ok() {
+        return Err(MarketplaceError::InvalidBundle.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,254 @@
+mod common;
+
+use common::{
+    add_metadata, add_nft_mint, add_token_account, pack, rent_exempt_minimum, system_program_id,
+    Ledger, TestMarketplace,
+};
+use nft_marketplace::{
+    error::MarketplaceError,
+    instruction::{self, BundleItemAccounts, BundlePurchaseItemAccounts},
+    state::{get_bundle_pda, get_collection_config_pda, get_metadata_pda},
+};
+use nft_marketplace_sdk::{Bundle, CollectionConfig, MarketplaceTotals};
+use solana_program::{program_error::ProgramError, program_pack::Pack, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::Account as TokenAccount;
+
+const BUNDLE_ID: u64 = 7;
+const PRICE: u64 = 2_000_000_000;
+
+struct TestBundle {
+    marketplace: TestMarketplace,
+    seller: Pubkey,
+    buyer: Pubkey,
+    address: Pubkey,
+    items: Vec<BundlePurchaseItemAccounts>,
+}
+
+impl TestBundle {
+    /// A bundle of two NFTs listed at `PRICE` on a marketplace taking 2.5%
+    fn new(ledger: &mut Ledger) -> Self {
+        let marketplace = TestMarketplace::new(ledger, 250);
+        let seller = Pubkey::new_unique();
+        let buyer = Pubkey::new_unique();
+        ledger.add_wallet(seller, 1_000_000_000);
+        ledger.add_wallet(buyer, 10_000_000_000);
+
+        let (address, bump) = get_bundle_pda(&ledger.program_id, &seller, BUNDLE_ID);
+        let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let items = mints
+            .iter()
+            .map(|&nft_mint| {
+                let escrow_token_account = get_associated_token_address(&address, &nft_mint);
+                let token_account = get_associated_token_address(&buyer, &nft_mint);
+                let (nft_metadata, _) = get_metadata_pda(&nft_mint);
+                add_nft_mint(ledger, nft_mint);
+                add_token_account(ledger, escrow_token_account, nft_mint, address, 0);
+                ledger.add_empty(token_account);
+                add_metadata(ledger, nft_metadata, nft_mint, 500, &[], None);
+
+                // No collection, so no config applies; any address will do
+                let collection_config_account = Pubkey::new_unique();
+                ledger.add_empty(collection_config_account);
+
+                BundlePurchaseItemAccounts {
+                    item: BundleItemAccounts {
+                        nft_mint: nft_mint.to_bytes(),
+                        escrow_token_account: escrow_token_account.to_bytes(),
+                        token_account: token_account.to_bytes(),
+                    },
+                    nft_metadata: nft_metadata.to_bytes(),
+                    collection_config_account: collection_config_account.to_bytes(),
+                }
+            })
+            .collect();
+
+        ledger.add_rent_exempt(
+            address,
+            pack(Bundle::new(
+                marketplace.address.to_bytes(),
+                seller.to_bytes(),
+                BUNDLE_ID,
+                PRICE,
+                0,
+                bump,
+                mints.iter().map(|mint| mint.to_bytes()).collect(),
+            )),
+            ledger.program_id,
+        );
+        Self {
+            marketplace,
+            seller,
+            buyer,
+            address,
+            items,
+        }
+    }
+
+    /// Put the bundle's first NFT into a collection with `collection_config`
+    fn add_collection_config(
+        &mut self,
+        ledger: &mut Ledger,
+        fee_percentage: Option<u16>,
+        enforce_royalties: bool,
+    ) {
+        let collection_mint = Pubkey::new_unique();
+        let (collection_config_account, bump) = get_collection_config_pda(
+            &ledger.program_id,
+            &self.marketplace.address,
+            &collection_mint,
+        );
+        ledger.add_rent_exempt(
+            collection_config_account,
+            pack(CollectionConfig::new(
+                self.marketplace.address.to_bytes(),
+                collection_mint.to_bytes(),
+                fee_percentage,
+                enforce_royalties,
+                bump,
+            )),
+            ledger.program_id,
+        );
+
+        // Replaces the metadata added without a collection
+        let item = &mut self.items[0];
+        add_metadata(
+            ledger,
+            Pubkey::new_from_array(item.nft_metadata),
+            Pubkey::new_from_array(item.item.nft_mint),
+            500,
+            &[(Pubkey::new_unique(), 100)],
+            Some(collection_mint),
+        );
+        item.collection_config_account = collection_config_account.to_bytes();
+    }
+
+    fn buy(&self, ledger: &Ledger, expected_price: u64) -> Result<(), ProgramError> {
+        ledger.process(instruction::buy_bundle(
+            &ledger.program_id.to_bytes(),
+            &self.buyer.to_bytes(),
+            &self.seller.to_bytes(),
+            &self.marketplace.address.to_bytes(),
+            &self.marketplace.fee_recipient.to_bytes(),
+            &self.address.to_bytes(),
+            &spl_token::id().to_bytes(),
+            &self.marketplace.stats.to_bytes(),
+            &self.items,
+            expected_price,
+        ))
+    }
+}
+
+#[test]
+fn buy_bundle_pays_seller_and_fee_and_releases_every_nft() {
+    let mut ledger = Ledger::new();
+    let bundle = TestBundle::new(&mut ledger);
+    let seller_before = ledger.lamports(&bundle.seller);
+    let buyer_before = ledger.lamports(&bundle.buyer);
+    let escrow_rent = rent_exempt_minimum(TokenAccount::LEN);
+    let bundle_rent = ledger.lamports(&bundle.address);
+
+    bundle.buy(&ledger, PRICE).unwrap();
+
+    // 2.5% of the price to the marketplace, the rest and the rent of the
+    // bundle and its escrows to the seller
+    let fee = PRICE / 40;
+    assert_eq!(ledger.lamports(&bundle.marketplace.fee_recipient), fee);
+    assert_eq!(
+        ledger.lamports(&bundle.seller),
+        seller_before + PRICE - fee + 2 * escrow_rent + bundle_rent
+    );
+    // The buyer pays the price and the rent of their new token accounts
+    assert_eq!(
+        ledger.lamports(&bundle.buyer),
+        buyer_before - PRICE - 2 * escrow_rent
+    );
+
+    assert_eq!(ledger.lamports(&bundle.address), 0);
+    assert!(ledger.data(&bundle.address).iter().all(|&byte| byte == 0));
+    for purchase_item in &bundle.items {
+        let item = &purchase_item.item;
+        let escrow = Pubkey::new_from_array(item.escrow_token_account);
+        assert_eq!(ledger.lamports(&escrow), 0);
+        assert_eq!(ledger.owner(&escrow), system_program_id());
+
+        let token_account: TokenAccount =
+            ledger.unpack(&Pubkey::new_from_array(item.token_account));
+        assert_eq!(token_account.owner, bundle.buyer);
+        assert_eq!(token_account.mint.to_bytes(), item.nft_mint);
+    }
+
+    let stats: MarketplaceTotals = ledger.unpack(&bundle.marketplace.stats);
+    assert_eq!(stats.total_volume, PRICE);
+    assert_eq!(stats.total_sales, 1);
+}
+
+#[test]
+fn buy_bundle_rejects_a_changed_price() {
+    let mut ledger = Ledger::new();
+    let bundle = TestBundle::new(&mut ledger);
+
+    assert_eq!(
+        bundle.buy(&ledger, PRICE - 1),
+        Err(MarketplaceError::ExpectedAmountMismatch.into())
+    );
+}
+
+#[test]
+fn buy_bundle_refuses_nfts_owing_royalties() {
+    let mut ledger = Ledger::new();
+    let mut bundle = TestBundle::new(&mut ledger);
+    bundle.add_collection_config(&mut ledger, None, true);
+    let buyer_before = ledger.lamports(&bundle.buyer);
+
+    assert_eq!(
+        bundle.buy(&ledger, PRICE),
+        Err(MarketplaceError::InvalidBundle.into())
+    );
+    assert_eq!(ledger.lamports(&bundle.buyer), buyer_before);
+}
+
+#[test]
+fn buy_bundle_refuses_nfts_with_a_collection_fee() {
+    let mut ledger = Ledger::new();
+    let mut bundle = TestBundle::new(&mut ledger);
+    bundle.add_collection_config(&mut ledger, Some(1_000), false);
+
+    assert_eq!(
+        bundle.buy(&ledger, PRICE),
+        Err(MarketplaceError::InvalidBundle.into())
+    );
+}
+
+#[test]
+fn list_bundle_rejects_a_single_nft() {
+    let mut ledger = Ledger::new();
+    let marketplace = TestMarketplace::new(&mut ledger, 250);
+    let seller = Pubkey::new_unique();
+    let nft_mint = Pubkey::new_unique();
+    let (bundle, _) = get_bundle_pda(&ledger.program_id, &seller, BUNDLE_ID);
+    let escrow_token_account = get_associated_token_address(&bundle, &nft_mint);
+    let seller_token_account = get_associated_token_address(&seller, &nft_mint);
+    ledger.add_wallet(seller, 1_000_000_000);
+    ledger.add_empty(bundle);
+    add_nft_mint(&mut ledger, nft_mint);
+    ledger.add_empty(escrow_token_account);
+    add_token_account(&mut ledger, seller_token_account, nft_mint, seller, 1);
+
+    let result = ledger.process(instruction::list_bundle(
+        &ledger.program_id.to_bytes(),
+        &seller.to_bytes(),
+        &marketplace.address.to_bytes(),
+        &bundle.to_bytes(),
+        &spl_token::id().to_bytes(),
+        &marketplace.stats.to_bytes(),
+        &[BundleItemAccounts {
+            nft_mint: nft_mint.to_bytes(),
+            escrow_token_account: escrow_token_account.to_bytes(),
+            token_account: seller_token_account.to_bytes(),
+        }],
+        BUNDLE_ID,
+        PRICE,
+    ));
+    assert_eq!(result, Err(MarketplaceError::InvalidBundle.into()));
+}
@@ -0,0 +1,267 @@
+mod common;
+
+use common::{
+    add_metadata, add_nft_mint, add_token_account, pack, rent_exempt_minimum, Ledger,
+    TestMarketplace,
+};
+use nft_marketplace::{
+    error::MarketplaceError,
+    instruction,
+    state::{get_collection_config_pda, get_collection_offer_pda, get_metadata_pda},
+};
+use nft_marketplace_sdk::{CollectionConfig, CollectionOffer};
+use solana_program::{program_error::ProgramError, program_pack::Pack, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::Account as TokenAccount;
+
+const AMOUNT: u64 = 1_000_000_000;
+
+struct TestCollectionOffer {
+    marketplace: TestMarketplace,
+    seller: Pubkey,
+    bidder: Pubkey,
+    collection_mint: Pubkey,
+    nft_mint: Pubkey,
+    nft_metadata: Pubkey,
+    address: Pubkey,
+    collection_config_account: Pubkey,
+    creators: [Pubkey; 2],
+}
+
+impl TestCollectionOffer {
+    /// An offer of `AMOUNT` for any NFT of a collection whose NFTs pay 10%
+    /// royalties, split 70/30 between two creators, on a marketplace taking
+    /// 2.5%. The seller holds one of its NFTs.
+    fn new(ledger: &mut Ledger) -> Self {
+        let marketplace = TestMarketplace::new(ledger, 250);
+        let seller = Pubkey::new_unique();
+        let bidder = Pubkey::new_unique();
+        let collection_mint = Pubkey::new_unique();
+        let nft_mint = Pubkey::new_unique();
+        let creators = [Pubkey::new_unique(), Pubkey::new_unique()];
+        ledger.add_wallet(seller, 1_000_000_000);
+        ledger.add_wallet(bidder, 1_000_000_000);
+        for creator in creators {
+            ledger.add_wallet(creator, 0);
+        }
+
+        let (nft_metadata, _) = get_metadata_pda(&nft_mint);
+        add_nft_mint(ledger, nft_mint);
+        add_metadata(
+            ledger,
+            nft_metadata,
+            nft_mint,
+            1_000,
+            &[(creators[0], 70), (creators[1], 30)],
+            Some(collection_mint),
+        );
+        add_token_account(
+            ledger,
+            get_associated_token_address(&seller, &nft_mint),
+            nft_mint,
+            seller,
+            1,
+        );
+        ledger.add_empty(get_associated_token_address(&bidder, &nft_mint));
+
+        let (address, bump) =
+            get_collection_offer_pda(&ledger.program_id, &collection_mint, &bidder);
+        let data = pack(CollectionOffer::new(
+            marketplace.address.to_bytes(),
+            bidder.to_bytes(),
+            collection_mint.to_bytes(),
+            AMOUNT,
+            0,
+            0,
+            bump,
+        ));
+        ledger.add(
+            address,
+            rent_exempt_minimum(data.len()) + AMOUNT,
+            data,
+            ledger.program_id,
+        );
+
+        let (collection_config_account, _) =
+            get_collection_config_pda(&ledger.program_id, &marketplace.address, &collection_mint);
+        ledger.add_empty(collection_config_account);
+
+        Self {
+            marketplace,
+            seller,
+            bidder,
+            collection_mint,
+            nft_mint,
+            nft_metadata,
+            address,
+            collection_config_account,
+            creators,
+        }
+    }
+
+    /// Set the collection's config on the marketplace
+    fn set_collection_config(
+        &self,
+        ledger: &mut Ledger,
+        fee_percentage: Option<u16>,
+        enforce_royalties: bool,
+    ) {
+        let (_, bump) = get_collection_config_pda(
+            &ledger.program_id,
+            &self.marketplace.address,
+            &self.collection_mint,
+        );
+        ledger.add_rent_exempt(
+            self.collection_config_account,
+            pack(CollectionConfig::new(
+                self.marketplace.address.to_bytes(),
+                self.collection_mint.to_bytes(),
+                fee_percentage,
+                enforce_royalties,
+                bump,
+            )),
+            ledger.program_id,
+        );
+    }
+
+    fn accept(&self, ledger: &Ledger, royalty_creators: &[Pubkey]) -> Result<(), ProgramError> {
+        let royalty_creators: Vec<_> = royalty_creators.iter().map(|c| c.to_bytes()).collect();
+        ledger.process(instruction::accept_collection_offer(
+            &ledger.program_id.to_bytes(),
+            &self.seller.to_bytes(),
+            &self.bidder.to_bytes(),
+            &self.marketplace.address.to_bytes(),
+            &self.marketplace.fee_recipient.to_bytes(),
+            &self.nft_mint.to_bytes(),
+            &self.nft_metadata.to_bytes(),
+            &self.address.to_bytes(),
+            &get_associated_token_address(&self.seller, &self.nft_mint).to_bytes(),
+            &get_associated_token_address(&self.bidder, &self.nft_mint).to_bytes(),
+            &spl_token::id().to_bytes(),
+            &self.collection_config_account.to_bytes(),
+            &royalty_creators,
+            AMOUNT,
+        ))
+    }
+}
+
+#[test]
+fn accept_collection_offer_pays_seller_and_marketplace_fee() {
+    let mut ledger = Ledger::new();
+    let offer = TestCollectionOffer::new(&mut ledger);
+    let seller_before = ledger.lamports(&offer.seller);
+    let bidder_before = ledger.lamports(&offer.bidder);
+    let offer_rent = rent_exempt_minimum(CollectionOffer::LEN);
+    let token_account_rent = rent_exempt_minimum(TokenAccount::LEN);
+
+    offer.accept(&ledger, &[]).unwrap();
+
+    // Without a collection config, the marketplace fee applies and no
+    // royalties are paid. The seller pays for the bidder's token account.
+    let fee = AMOUNT / 40;
+    assert_eq!(ledger.lamports(&offer.marketplace.fee_recipient), fee);
+    assert_eq!(
+        ledger.lamports(&offer.seller),
+        seller_before + AMOUNT - fee - token_account_rent
+    );
+    assert_eq!(ledger.lamports(&offer.bidder), bidder_before + offer_rent);
+    for creator in offer.creators {
+        assert_eq!(ledger.lamports(&creator), 0);
+    }
+
+    assert_eq!(ledger.lamports(&offer.address), 0);
+    assert!(ledger.data(&offer.address).iter().all(|&byte| byte == 0));
+    let bidder_token_account: TokenAccount = ledger.unpack(&get_associated_token_address(
+        &offer.bidder,
+        &offer.nft_mint,
+    ));
+    assert_eq!(bidder_token_account.owner, offer.bidder);
+    assert_eq!(bidder_token_account.mint, offer.nft_mint);
+}
+
+#[test]
+fn accept_collection_offer_applies_collection_fee_and_royalties() {
+    let mut ledger = Ledger::new();
+    let offer = TestCollectionOffer::new(&mut ledger);
+    offer.set_collection_config(&mut ledger, Some(500), true);
+    let seller_before = ledger.lamports(&offer.seller);
+    let token_account_rent = rent_exempt_minimum(TokenAccount::LEN);
+
+    offer.accept(&ledger, &offer.creators).unwrap();
+
+    // 5% to the marketplace in place of its 2.5%, then 10% royalties split
+    // 70/30, out of the seller's share
+    let fee = AMOUNT / 20;
+    let royalties = [AMOUNT * 7 / 100, AMOUNT * 3 / 100];
+    assert_eq!(ledger.lamports(&offer.marketplace.fee_recipient), fee);
+    assert_eq!(ledger.lamports(&offer.creators[0]), royalties[0]);
+    assert_eq!(ledger.lamports(&offer.creators[1]), royalties[1]);
+    assert_eq!(
+        ledger.lamports(&offer.seller),
+        seller_before + AMOUNT - fee - royalties[0] - royalties[1] - token_account_rent
+    );
+}
+
+#[test]
+fn accept_collection_offer_applies_collection_fee_without_royalties() {
+    let mut ledger = Ledger::new();
+    let offer = TestCollectionOffer::new(&mut ledger);
+    offer.set_collection_config(&mut ledger, Some(0), false);
+    let seller_before = ledger.lamports(&offer.seller);
+    let token_account_rent = rent_exempt_minimum(TokenAccount::LEN);
+
+    offer.accept(&ledger, &[]).unwrap();
+
+    assert_eq!(ledger.lamports(&offer.marketplace.fee_recipient), 0);
+    assert_eq!(
+        ledger.lamports(&offer.seller),
+        seller_before + AMOUNT - token_account_rent
+    );
+}
+
+#[test]
+fn accept_collection_offer_requires_the_royalty_creators() {
+    let mut ledger = Ledger::new();
+    let offer = TestCollectionOffer::new(&mut ledger);
+    offer.set_collection_config(&mut ledger, None, true);
+    let impostor = Pubkey::new_unique();
+    ledger.add_wallet(impostor, 0);
+
+    assert_eq!(
+        offer.accept(&ledger, &[]),
+        Err(ProgramError::NotEnoughAccountKeys)
+    );
+    assert_eq!(
+        offer.accept(&ledger, &[offer.creators[0], impostor]),
+        Err(ProgramError::InvalidArgument)
+    );
+}
+
+#[test]
+fn accept_collection_offer_rejects_the_wrong_collection_config() {
+    let mut ledger = Ledger::new();
+    let mut offer = TestCollectionOffer::new(&mut ledger);
+    offer.collection_config_account = Pubkey::new_unique();
+    ledger.add_empty(offer.collection_config_account);
+
+    assert_eq!(offer.accept(&ledger, &[]), Err(ProgramError::InvalidSeeds));
+}
+
+#[test]
+fn accept_collection_offer_rejects_nfts_outside_the_collection() {
+    let mut ledger = Ledger::new();
+    let offer = TestCollectionOffer::new(&mut ledger);
+    add_metadata(
+        &mut ledger,
+        offer.nft_metadata,
+        offer.nft_mint,
+        1_000,
+        &[],
+        Some(Pubkey::new_unique()),
+    );
+
+    assert_eq!(
+        offer.accept(&ledger, &[]),
+        Err(MarketplaceError::InvalidCollection.into())
+    );
+}
@@ -0,0 +1,513 @@
+//! Runs the processor natively against an in-memory set of accounts. The
+//! runtime's CPI and sysvar syscalls are stubbed: the system program, SPL
+//! Token and the associated token account program run in-process, and token
+//! metadata program calls are only recorded.
+//!
+//! NFTs are moved with Token-2022's `invoke_transfer_checked`, which makes the
+//! syscall itself and is a no-op off-chain. Tests therefore don't see NFTs
+//! change hands; escrowed NFTs are set up as empty token accounts so that
+//! closing their escrows succeeds, and the tests check the lamports instead.
+
+#![allow(dead_code)]
+
+use borsh::BorshSerialize;
+use nft_marketplace::state::{get_marketplace_pda, get_marketplace_stats_pda};
+use nft_marketplace_sdk::{
+    ids::{SYSTEM_PROGRAM_ID, TOKEN_METADATA_PROGRAM_ID},
+    metadata::{Collection, Creator},
+    Marketplace, MarketplaceTotals,
+};
+use solana_program::{
+    account_info::AccountInfo,
+    bpf_loader,
+    clock::Clock,
+    entrypoint::{ProgramResult, SUCCESS},
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::{IsInitialized, Pack},
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar,
+};
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    sync::Once,
+};
+
+thread_local! {
+    /// The programs on the call stack, innermost last
+    static PROGRAMS: RefCell<Vec<Pubkey>> = const { RefCell::new(Vec::new()) };
+    static RETURN_DATA: RefCell<Option<(Pubkey, Vec<u8>)>> = const { RefCell::new(None) };
+    static UNIX_TIMESTAMP: Cell<i64> = const { Cell::new(0) };
+    /// Every CPI made, in order
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Set the clock the processor reads on this thread
+pub fn set_clock(unix_timestamp: i64) {
+    UNIX_TIMESTAMP.with(|now| now.set(unix_timestamp));
+}
+
+/// The CPIs made on this thread since the last call
+pub fn take_invoked() -> Vec<Instruction> {
+    INVOKED.with(|invoked| invoked.take())
+}
+
+pub fn system_program_id() -> Pubkey {
+    Pubkey::new_from_array(SYSTEM_PROGRAM_ID)
+}
+
+pub fn metadata_program_id() -> Pubkey {
+    Pubkey::new_from_array(TOKEN_METADATA_PROGRAM_ID)
+}
+
+pub fn rent_exempt_minimum(data_len: usize) -> u64 {
+    Rent::default().minimum_balance(data_len)
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller = PROGRAMS.with(|programs| *programs.borrow().last().unwrap());
+        let pda_signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+
+        // The callee gets the caller's accounts with the privileges the
+        // instruction asks for, which it can't escalate
+        let callee_infos = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                let mut info = account_infos
+                    .iter()
+                    .find(|info| *info.key == meta.pubkey)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?
+                    .clone();
+                let (is_signer, is_writable) = privileges(&instruction.accounts, &meta.pubkey);
+                if is_signer && !info.is_signer && !pda_signers.contains(info.key) {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                if is_writable && !info.is_writable {
+                    return Err(ProgramError::InvalidArgument);
+                }
+                info.is_signer = is_signer;
+                info.is_writable = is_writable;
+                Ok(info)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        PROGRAMS.with(|programs| programs.borrow_mut().push(instruction.program_id));
+        let result = dispatch(instruction, &callee_infos);
+        PROGRAMS.with(|programs| programs.borrow_mut().pop());
+        result
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: UNIX_TIMESTAMP.with(Cell::get),
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        RETURN_DATA.with(|return_data| return_data.borrow().clone())
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        let program = PROGRAMS.with(|programs| *programs.borrow().last().unwrap());
+        RETURN_DATA.with(|return_data| *return_data.borrow_mut() = Some((program, data.to_vec())));
+    }
+}
+
+/// Whether an instruction signs with and writes to `key`. Like the runtime,
+/// an account passed more than once gets the privileges of all its metas.
+fn privileges(metas: &[AccountMeta], key: &Pubkey) -> (bool, bool) {
+    metas.iter().filter(|meta| meta.pubkey == *key).fold(
+        (false, false),
+        |(is_signer, is_writable), meta| {
+            (is_signer || meta.is_signer, is_writable || meta.is_writable)
+        },
+    )
+}
+
+fn dispatch(instruction: &Instruction, account_infos: &[AccountInfo]) -> ProgramResult {
+    let program_id = instruction.program_id;
+    let data = instruction.data.as_slice();
+    if program_id == system_program_id() {
+        process_system_instruction(account_infos, data)
+    } else if program_id == spl_token::id() {
+        spl_token::processor::Processor::process(&program_id, account_infos, data)?;
+        // SPL Token sets its return data with a syscall that's a no-op
+        // off-chain, so answer `GetAccountDataSize` for it
+        if data.first() == Some(&21) {
+            RETURN_DATA.with(|return_data| {
+                *return_data.borrow_mut() = Some((
+                    program_id,
+                    (TokenAccount::LEN as u64).to_le_bytes().to_vec(),
+                ))
+            });
+        }
+        Ok(())
+    } else if program_id == spl_associated_token_account::id() {
+        spl_associated_token_account::processor::process_instruction(
+            &program_id,
+            account_infos,
+            data,
+        )
+    } else if program_id == metadata_program_id() {
+        Ok(())
+    } else {
+        Err(ProgramError::IncorrectProgramId)
+    }
+}
+
+/// The system program instructions the marketplace and the programs it calls
+/// use, decoded by hand from their bincode layout
+fn process_system_instruction(account_infos: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let read_u64 = |offset: usize| -> Result<u64, ProgramError> {
+        data.get(offset..offset + 8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+            .ok_or(ProgramError::InvalidInstructionData)
+    };
+    let read_pubkey = |offset: usize| -> Result<Pubkey, ProgramError> {
+        data.get(offset..offset + 32)
+            .map(|bytes| Pubkey::try_from(bytes).unwrap())
+            .ok_or(ProgramError::InvalidInstructionData)
+    };
+    let tag = data
+        .get(..4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    match (tag, account_infos) {
+        // CreateAccount
+        (0, [from_info, to_info, ..]) => {
+            if to_info.lamports() != 0 || *to_info.owner != system_program_id() {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            transfer(from_info, to_info, read_u64(4)?)?;
+            allocate(to_info, read_u64(12)?)?;
+            assign(to_info, &read_pubkey(20)?)
+        }
+        // Assign
+        (1, [account_info, ..]) => assign(account_info, &read_pubkey(4)?),
+        // Transfer
+        (2, [from_info, to_info, ..]) => transfer(from_info, to_info, read_u64(4)?),
+        // Allocate
+        (8, [account_info, ..]) => allocate(account_info, read_u64(4)?),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn transfer(from_info: &AccountInfo, to_info: &AccountInfo, lamports: u64) -> ProgramResult {
+    if !from_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *from_info.owner != system_program_id() || !from_info.data_is_empty() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let from_lamports = from_info
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **from_info.lamports.borrow_mut() = from_lamports;
+    **to_info.lamports.borrow_mut() += lamports;
+    Ok(())
+}
+
+fn allocate(account_info: &AccountInfo, space: u64) -> ProgramResult {
+    if !account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !account_info.data_is_empty() || *account_info.owner != system_program_id() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    *account_info.data.borrow_mut() = Box::leak(vec![0; space as usize].into_boxed_slice());
+    Ok(())
+}
+
+fn assign(account_info: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+    if !account_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    account_info.assign(owner);
+    Ok(())
+}
+
+/// Accounts the tests run instructions against. They're leaked, so the
+/// `AccountInfo`s handed to the processor can live for `'static`.
+pub struct Ledger {
+    pub program_id: Pubkey,
+    accounts: HashMap<Pubkey, AccountInfo<'static>>,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            set_syscall_stubs(Box::new(Stubs));
+        });
+        set_clock(1_700_000_000);
+        take_invoked();
+
+        let mut ledger = Self {
+            program_id: Pubkey::new_unique(),
+            accounts: HashMap::new(),
+        };
+        for program_id in [
+            ledger.program_id,
+            system_program_id(),
+            spl_token::id(),
+            spl_associated_token_account::id(),
+            metadata_program_id(),
+        ] {
+            ledger.insert(program_id, 1, Vec::new(), bpf_loader::id(), true);
+        }
+
+        // The rent sysvar in its bincode layout, for instructions that take it
+        let rent = Rent::default();
+        let mut rent_data = rent.lamports_per_byte_year.to_le_bytes().to_vec();
+        rent_data.extend(rent.exemption_threshold.to_le_bytes());
+        rent_data.push(rent.burn_percent);
+        ledger.add(sysvar::rent::id(), 1, rent_data, sysvar::id());
+        ledger
+    }
+
+    fn insert(
+        &mut self,
+        key: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+        owner: Pubkey,
+        executable: bool,
+    ) {
+        let account_info = AccountInfo::new(
+            Box::leak(Box::new(key)),
+            false,
+            false,
+            Box::leak(Box::new(lamports)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            executable,
+            0,
+        );
+        self.accounts.insert(key, account_info);
+    }
+
+    pub fn add(&mut self, key: Pubkey, lamports: u64, data: Vec<u8>, owner: Pubkey) {
+        self.insert(key, lamports, data, owner, false);
+    }
+
+    /// Add a rent-exempt account holding `data`
+    pub fn add_rent_exempt(&mut self, key: Pubkey, data: Vec<u8>, owner: Pubkey) {
+        self.add(key, rent_exempt_minimum(data.len()), data, owner);
+    }
+
+    pub fn add_wallet(&mut self, key: Pubkey, lamports: u64) {
+        self.add(key, lamports, Vec::new(), system_program_id());
+    }
+
+    /// Add an account that doesn't exist yet, for the processor to create
+    pub fn add_empty(&mut self, key: Pubkey) {
+        self.add_wallet(key, 0);
+    }
+
+    fn get(&self, key: &Pubkey) -> &AccountInfo<'static> {
+        self.accounts
+            .get(key)
+            .unwrap_or_else(|| panic!("no account {key}"))
+    }
+
+    pub fn lamports(&self, key: &Pubkey) -> u64 {
+        self.get(key).lamports()
+    }
+
+    pub fn owner(&self, key: &Pubkey) -> Pubkey {
+        *self.get(key).owner
+    }
+
+    pub fn data(&self, key: &Pubkey) -> Vec<u8> {
+        self.get(key).data.borrow().to_vec()
+    }
+
+    pub fn unpack<T: Pack + IsInitialized>(&self, key: &Pubkey) -> T {
+        T::unpack(&self.get(key).data.borrow()).unwrap()
+    }
+
+    /// Run `instruction` as the only instruction of a transaction signed by
+    /// the accounts it marks as signers
+    pub fn process(&self, instruction: impl Into<Instruction>) -> ProgramResult {
+        let instruction = instruction.into();
+        let account_infos: Vec<_> = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                let mut info = self.get(&meta.pubkey).clone();
+                (info.is_signer, info.is_writable) =
+                    privileges(&instruction.accounts, &meta.pubkey);
+                info
+            })
+            .collect();
+
+        PROGRAMS.with(|programs| programs.borrow_mut().push(instruction.program_id));
+        let result = nft_marketplace::process_instruction(
+            &instruction.program_id,
+            &account_infos,
+            &instruction.data,
+        );
+        PROGRAMS.with(|programs| programs.borrow_mut().pop());
+        result
+    }
+}
+
+/// A marketplace taking `fee_percentage` with its stats account
+pub struct TestMarketplace {
+    pub address: Pubkey,
+    pub authority: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub stats: Pubkey,
+}
+
+impl TestMarketplace {
+    pub fn new(ledger: &mut Ledger, fee_percentage: u16) -> Self {
+        let authority = Pubkey::new_unique();
+        let fee_recipient = Pubkey::new_unique();
+        let (address, _) = get_marketplace_pda(&ledger.program_id, &authority);
+        let (stats, stats_bump) = get_marketplace_stats_pda(&ledger.program_id, &address);
+
+        ledger.add_wallet(authority, 1_000_000_000);
+        ledger.add_wallet(fee_recipient, 0);
+        ledger.add_rent_exempt(
+            address,
+            pack(Marketplace::new(
+                authority.to_bytes(),
+                fee_percentage,
+                fee_recipient.to_bytes(),
+            )),
+            ledger.program_id,
+        );
+        ledger.add_rent_exempt(
+            stats,
+            pack(MarketplaceTotals::new(address.to_bytes(), stats_bump)),
+            ledger.program_id,
+        );
+        Self {
+            address,
+            authority,
+            fee_recipient,
+            stats,
+        }
+    }
+}
+
+pub fn pack<T: Pack>(value: T) -> Vec<u8> {
+    let mut data = vec![0; T::LEN];
+    T::pack(value, &mut data).unwrap();
+    data
+}
+
+/// Add an NFT mint under SPL Token
+pub fn add_nft_mint(ledger: &mut Ledger, mint: Pubkey) {
+    let authority = COption::Some(Pubkey::new_unique());
+    ledger.add_rent_exempt(
+        mint,
+        pack(Mint {
+            mint_authority: authority,
+            supply: 1,
+            decimals: 0,
+            is_initialized: true,
+            freeze_authority: authority,
+        }),
+        spl_token::id(),
+    );
+}
+
+/// Add an SPL Token account of `mint` held by `owner`
+pub fn add_token_account(
+    ledger: &mut Ledger,
+    key: Pubkey,
+    mint: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+) {
+    ledger.add_rent_exempt(
+        key,
+        pack(TokenAccount {
+            mint,
+            owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        }),
+        spl_token::id(),
+    );
+}
+
+/// Add the Metaplex metadata account of `mint`, verified into `collection`
+/// if one is given
+pub fn add_metadata(
+    ledger: &mut Ledger,
+    metadata: Pubkey,
+    mint: Pubkey,
+    seller_fee_basis_points: u16,
+    creators: &[(Pubkey, u8)],
+    collection: Option<Pubkey>,
+) {
+    let creators: Vec<Creator> = creators
+        .iter()
+        .map(|&(address, share)| Creator {
+            address: address.to_bytes(),
+            verified: true,
+            share,
+        })
+        .collect();
+    let collection = collection.map(|collection| Collection {
+        verified: true,
+        key: collection.to_bytes(),
+    });
+
+    let mut data = Vec::new();
+    (
+        4u8, // Key::MetadataV1
+        [0u8; 32],
+        mint.to_bytes(),
+        String::from("Solmint #1"),
+        String::from("SOLM"),
+        String::from("https://example.com/1.json"),
+        seller_fee_basis_points,
+        Some(creators),
+        false,      // primary_sale_happened
+        true,       // is_mutable
+        None::<u8>, // edition_nonce
+        Some(0u8),  // token_standard: NonFungible
+        collection,
+        None::<u8>, // uses
+    )
+        .serialize(&mut data)
+        .unwrap();
+    ledger.add_rent_exempt(metadata, data, metadata_program_id());
+}
@@ -0,0 +1,242 @@
+mod common;
+
+use common::{
+    add_metadata, add_nft_mint, add_token_account, pack, rent_exempt_minimum, take_invoked, Ledger,
+    TestMarketplace,
+};
+use nft_marketplace::{
+    instruction,
+    state::{
+        get_collection_config_pda, get_listing_pda, get_master_edition_pda, get_metadata_pda,
+        get_referral_config_pda,
+    },
+};
+use nft_marketplace_sdk::{metadata, Address, CollectionConfig, Listing, MarketplaceTotals};
+use solana_program::{
+    instruction::Instruction, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::Account as TokenAccount;
+
+const PRICE: u64 = 1_000_000_000;
+
+/// `metadata::freeze_delegated_account` or `metadata::thaw_delegated_account`
+type DelegatedAccountBuilder =
+    fn(&Address, &Address, &Address, &Address) -> nft_marketplace_sdk::Instruction;
+
+struct TestListing {
+    marketplace: TestMarketplace,
+    seller: Pubkey,
+    buyer: Pubkey,
+    creator: Pubkey,
+    nft_mint: Pubkey,
+    nft_metadata: Pubkey,
+    nft_edition: Pubkey,
+    collection_config_account: Pubkey,
+    referral_config_account: Pubkey,
+    address: Pubkey,
+    seller_token_account: Pubkey,
+    buyer_token_account: Pubkey,
+}
+
+impl TestListing {
+    /// A seller holding an NFT that pays its one creator 10% royalties, on a
+    /// marketplace taking 2.5% whose config for the NFT's collection
+    /// enforces royalties
+    fn new(ledger: &mut Ledger) -> Self {
+        let marketplace = TestMarketplace::new(ledger, 250);
+        let seller = Pubkey::new_unique();
+        let buyer = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        let nft_mint = Pubkey::new_unique();
+        let collection_mint = Pubkey::new_unique();
+        ledger.add_wallet(seller, 1_000_000_000);
+        ledger.add_wallet(buyer, 2_000_000_000);
+        ledger.add_wallet(creator, 0);
+
+        let (nft_metadata, _) = get_metadata_pda(&nft_mint);
+        let (nft_edition, _) = get_master_edition_pda(&nft_mint);
+        add_nft_mint(ledger, nft_mint);
+        add_metadata(
+            ledger,
+            nft_metadata,
+            nft_mint,
+            1_000,
+            &[(creator, 100)],
+            Some(collection_mint),
+        );
+        ledger.add_empty(nft_edition);
+
+        let (collection_config_account, bump) =
+            get_collection_config_pda(&ledger.program_id, &marketplace.address, &collection_mint);
+        ledger.add_rent_exempt(
+            collection_config_account,
+            pack(CollectionConfig::new(
+                marketplace.address.to_bytes(),
+                collection_mint.to_bytes(),
+                None,
+                true,
+                bump,
+            )),
+            ledger.program_id,
+        );
+        let (referral_config_account, _) =
+            get_referral_config_pda(&ledger.program_id, &marketplace.address);
+        ledger.add_empty(referral_config_account);
+
+        let (address, _) = get_listing_pda(&ledger.program_id, &nft_mint, &seller);
+        let seller_token_account = get_associated_token_address(&seller, &nft_mint);
+        let buyer_token_account = get_associated_token_address(&buyer, &nft_mint);
+        ledger.add_empty(address);
+        add_token_account(ledger, seller_token_account, nft_mint, seller, 1);
+        ledger.add_empty(buyer_token_account);
+
+        Self {
+            marketplace,
+            seller,
+            buyer,
+            creator,
+            nft_mint,
+            nft_metadata,
+            nft_edition,
+            collection_config_account,
+            referral_config_account,
+            address,
+            seller_token_account,
+            buyer_token_account,
+        }
+    }
+
+    fn list(&self, ledger: &Ledger) -> Result<(), ProgramError> {
+        ledger.process(instruction::list_nft_delegated(
+            &ledger.program_id.to_bytes(),
+            &self.seller.to_bytes(),
+            &self.marketplace.address.to_bytes(),
+            &self.nft_mint.to_bytes(),
+            &self.seller_token_account.to_bytes(),
+            &self.address.to_bytes(),
+            &self.nft_edition.to_bytes(),
+            &self.marketplace.stats.to_bytes(),
+            PRICE,
+        ))
+    }
+
+    /// `BuyNft` for the listing, without a referrer
+    fn buy_instruction(&self, program_id: &Pubkey) -> Instruction {
+        instruction::buy_delegated_nft(
+            &program_id.to_bytes(),
+            &self.buyer.to_bytes(),
+            &self.seller.to_bytes(),
+            &self.marketplace.address.to_bytes(),
+            &self.marketplace.fee_recipient.to_bytes(),
+            &self.nft_mint.to_bytes(),
+            &self.address.to_bytes(),
+            &self.seller_token_account.to_bytes(),
+            &self.buyer_token_account.to_bytes(),
+            &self.nft_metadata.to_bytes(),
+            &self.nft_edition.to_bytes(),
+            &self.collection_config_account.to_bytes(),
+            &self.marketplace.stats.to_bytes(),
+            &self.referral_config_account.to_bytes(),
+            &self.marketplace.fee_recipient.to_bytes(),
+            &[self.creator.to_bytes()],
+            PRICE,
+        )
+        .into()
+    }
+
+    /// The metadata program instruction freezing or thawing the seller's
+    /// token account, as `build` makes it
+    fn delegated_account_instruction(&self, build: DelegatedAccountBuilder) -> Instruction {
+        build(
+            &self.address.to_bytes(),
+            &self.seller_token_account.to_bytes(),
+            &self.nft_edition.to_bytes(),
+            &self.nft_mint.to_bytes(),
+        )
+        .into()
+    }
+}
+
+#[test]
+fn list_nft_delegated_freezes_the_nft_with_the_seller() {
+    let mut ledger = Ledger::new();
+    let listing = TestListing::new(&mut ledger);
+    let seller_before = ledger.lamports(&listing.seller);
+
+    listing.list(&ledger).unwrap();
+
+    let listing_rent = rent_exempt_minimum(Listing::LEN);
+    assert_eq!(
+        ledger.lamports(&listing.seller),
+        seller_before - listing_rent
+    );
+    assert_eq!(ledger.lamports(&listing.address), listing_rent);
+    let state: Listing = ledger.unpack(&listing.address);
+    assert_eq!(state.seller, listing.seller.to_bytes());
+    assert_eq!(state.price, PRICE);
+
+    // The NFT stays with the seller; the listing PDA is its delegate, and
+    // signs the freeze
+    let seller_token_account: TokenAccount = ledger.unpack(&listing.seller_token_account);
+    assert_eq!(seller_token_account.amount, 1);
+    assert_eq!(seller_token_account.delegate, Some(listing.address).into());
+    assert_eq!(seller_token_account.delegated_amount, 1);
+    let freeze = listing.delegated_account_instruction(metadata::freeze_delegated_account);
+    assert!(take_invoked().contains(&freeze));
+
+    let stats: MarketplaceTotals = ledger.unpack(&listing.marketplace.stats);
+    assert_eq!(stats.total_listings, 1);
+}
+
+#[test]
+fn buy_delegated_listing_pays_seller_fee_and_royalties() {
+    let mut ledger = Ledger::new();
+    let listing = TestListing::new(&mut ledger);
+    listing.list(&ledger).unwrap();
+    take_invoked();
+    let seller_before = ledger.lamports(&listing.seller);
+    let buyer_before = ledger.lamports(&listing.buyer);
+    let listing_rent = rent_exempt_minimum(Listing::LEN);
+
+    ledger
+        .process(listing.buy_instruction(&ledger.program_id))
+        .unwrap();
+
+    // 2.5% to the marketplace and 10% to the creator, out of the seller's
+    // share. The listing's rent goes back to the seller; the buyer pays for
+    // their own token account.
+    let fee = PRICE / 40;
+    let royalty = PRICE / 10;
+    assert_eq!(ledger.lamports(&listing.marketplace.fee_recipient), fee);
+    assert_eq!(ledger.lamports(&listing.creator), royalty);
+    assert_eq!(
+        ledger.lamports(&listing.seller),
+        seller_before + PRICE - fee - royalty + listing_rent
+    );
+    assert_eq!(
+        ledger.lamports(&listing.buyer),
+        buyer_before - PRICE - rent_exempt_minimum(TokenAccount::LEN)
+    );
+
+    assert_eq!(ledger.lamports(&listing.address), 0);
+    assert!(ledger.data(&listing.address).iter().all(|&byte| byte == 0));
+    let thaw = listing.delegated_account_instruction(metadata::thaw_delegated_account);
+    assert!(take_invoked().contains(&thaw));
+
+    let stats: MarketplaceTotals = ledger.unpack(&listing.marketplace.stats);
+    assert_eq!(stats.total_sales, 1);
+    assert_eq!(stats.total_volume, PRICE);
+}
+
+#[test]
+fn buy_delegated_listing_needs_the_edition_and_metadata_program() {
+    let mut ledger = Ledger::new();
+    let listing = TestListing::new(&mut ledger);
+    listing.list(&ledger).unwrap();
+
+    // A plain `buy_nft`, as for an escrowed listing
+    let mut buy = listing.buy_instruction(&ledger.program_id);
+    buy.accounts.truncate(buy.accounts.len() - 2);
+    assert_eq!(ledger.process(buy), Err(ProgramError::NotEnoughAccountKeys));
+}
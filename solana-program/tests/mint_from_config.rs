@@ -0,0 +1,232 @@
+mod common;
+
+use common::{pack, rent_exempt_minimum, set_clock, take_invoked, Ledger};
+use nft_marketplace::{
+    error::MarketplaceError,
+    instruction,
+    state::{get_master_edition_pda, get_metadata_pda, get_mint_config_pda, get_mint_record_pda},
+};
+use nft_marketplace_sdk::{metadata, MintConfig, MintRecord};
+use solana_program::{
+    instruction::Instruction, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::{Account as TokenAccount, Mint};
+
+const PRICE: u64 = 500_000_000;
+const GO_LIVE: i64 = 1_700_000_000;
+
+struct TestMintConfig {
+    treasury: Pubkey,
+    address: Pubkey,
+}
+
+impl TestMintConfig {
+    /// A drop of `max_supply` NFTs at `PRICE`, open from `GO_LIVE`, of which
+    /// each wallet can mint `wallet_limit`
+    fn new(ledger: &mut Ledger, max_supply: u64, wallet_limit: u16) -> Self {
+        let creator = Pubkey::new_unique();
+        let treasury = Pubkey::new_unique();
+        ledger.add_wallet(treasury, 0);
+
+        let (address, bump) = get_mint_config_pda(&ledger.program_id, &creator, 1);
+        ledger.add_rent_exempt(
+            address,
+            pack(MintConfig::new(
+                creator.to_bytes(),
+                treasury.to_bytes(),
+                1,
+                PRICE,
+                max_supply,
+                GO_LIVE,
+                None,
+                wallet_limit,
+                bump,
+                "Drop".to_string(),
+                "DROP".to_string(),
+                "https://example.com/drop/".to_string(),
+            )),
+            ledger.program_id,
+        );
+
+        Self { treasury, address }
+    }
+
+    /// Add a wallet holding `lamports` that hasn't minted from the config yet
+    fn add_minter(&self, ledger: &mut Ledger, lamports: u64) -> Pubkey {
+        let minter = Pubkey::new_unique();
+        ledger.add_wallet(minter, lamports);
+        let (mint_record_account, _) =
+            get_mint_record_pda(&ledger.program_id, &self.address, &minter);
+        ledger.add_empty(mint_record_account);
+        minter
+    }
+}
+
+struct TestMint {
+    minter: Pubkey,
+    mint: Pubkey,
+    minter_token_account: Pubkey,
+    metadata_account: Pubkey,
+    master_edition_account: Pubkey,
+    mint_record_account: Pubkey,
+}
+
+impl TestMint {
+    /// The accounts for `minter` to mint a new NFT from `config`
+    fn new(ledger: &mut Ledger, config: &TestMintConfig, minter: Pubkey) -> Self {
+        let mint = Pubkey::new_unique();
+        let minter_token_account = get_associated_token_address(&minter, &mint);
+        let (metadata_account, _) = get_metadata_pda(&mint);
+        let (master_edition_account, _) = get_master_edition_pda(&mint);
+        let (mint_record_account, _) =
+            get_mint_record_pda(&ledger.program_id, &config.address, &minter);
+        for key in [
+            mint,
+            minter_token_account,
+            metadata_account,
+            master_edition_account,
+        ] {
+            ledger.add_empty(key);
+        }
+
+        Self {
+            minter,
+            mint,
+            minter_token_account,
+            metadata_account,
+            master_edition_account,
+            mint_record_account,
+        }
+    }
+
+    fn mint(
+        &self,
+        ledger: &Ledger,
+        config: &TestMintConfig,
+        expected_price: u64,
+    ) -> Result<(), ProgramError> {
+        ledger.process(instruction::mint_from_config(
+            &ledger.program_id.to_bytes(),
+            &self.minter.to_bytes(),
+            &config.address.to_bytes(),
+            &config.treasury.to_bytes(),
+            &self.mint_record_account.to_bytes(),
+            &self.mint.to_bytes(),
+            &self.minter_token_account.to_bytes(),
+            &self.metadata_account.to_bytes(),
+            &self.master_edition_account.to_bytes(),
+            expected_price,
+            0,
+            Vec::new(),
+        ))
+    }
+}
+
+#[test]
+fn mint_from_config_pays_the_treasury_and_mints_the_nft() {
+    let mut ledger = Ledger::new();
+    let config = TestMintConfig::new(&mut ledger, 10, 0);
+    let minter = config.add_minter(&mut ledger, 1_000_000_000);
+    let nft = TestMint::new(&mut ledger, &config, minter);
+
+    nft.mint(&ledger, &config, PRICE).unwrap();
+
+    // The minter pays the price to the treasury, and the rent of their mint
+    // record, the mint and their token account
+    assert_eq!(ledger.lamports(&config.treasury), PRICE);
+    assert_eq!(
+        ledger.lamports(&minter),
+        1_000_000_000
+            - PRICE
+            - rent_exempt_minimum(MintRecord::LEN)
+            - rent_exempt_minimum(Mint::LEN)
+            - rent_exempt_minimum(TokenAccount::LEN)
+    );
+
+    let state: MintConfig = ledger.unpack(&config.address);
+    assert_eq!(state.minted, 1);
+    let mint_record: MintRecord = ledger.unpack(&nft.mint_record_account);
+    assert_eq!(mint_record.wallet, minter.to_bytes());
+    assert_eq!(mint_record.minted, 1);
+
+    let mint: Mint = ledger.unpack(&nft.mint);
+    assert_eq!(mint.supply, 1);
+    assert_eq!(mint.decimals, 0);
+    let minter_token_account: TokenAccount = ledger.unpack(&nft.minter_token_account);
+    assert_eq!(minter_token_account.owner, minter);
+    assert_eq!(minter_token_account.amount, 1);
+
+    // The metadata is the drop's first NFT's, with the mint config signing
+    // as update authority
+    let create_metadata: Instruction = metadata::create_metadata_account_v3(
+        &nft.metadata_account.to_bytes(),
+        &nft.mint.to_bytes(),
+        &minter.to_bytes(),
+        &minter.to_bytes(),
+        &config.address.to_bytes(),
+        "Drop #1".to_string(),
+        "DROP".to_string(),
+        "https://example.com/drop/1.json".to_string(),
+    )
+    .into();
+    assert!(take_invoked().contains(&create_metadata));
+}
+
+#[test]
+fn mint_from_config_checks_the_price_and_go_live() {
+    let mut ledger = Ledger::new();
+    let config = TestMintConfig::new(&mut ledger, 10, 0);
+    let minter = config.add_minter(&mut ledger, 1_000_000_000);
+    let nft = TestMint::new(&mut ledger, &config, minter);
+
+    assert_eq!(
+        nft.mint(&ledger, &config, PRICE - 1),
+        Err(MarketplaceError::ExpectedAmountMismatch.into())
+    );
+    set_clock(GO_LIVE - 1);
+    assert_eq!(
+        nft.mint(&ledger, &config, PRICE),
+        Err(MarketplaceError::MintNotLive.into())
+    );
+    assert_eq!(ledger.lamports(&config.treasury), 0);
+}
+
+#[test]
+fn mint_from_config_enforces_the_wallet_limit() {
+    let mut ledger = Ledger::new();
+    let config = TestMintConfig::new(&mut ledger, 10, 1);
+    let minter = config.add_minter(&mut ledger, 2_000_000_000);
+    TestMint::new(&mut ledger, &config, minter)
+        .mint(&ledger, &config, PRICE)
+        .unwrap();
+
+    let second = TestMint::new(&mut ledger, &config, minter);
+    assert_eq!(
+        second.mint(&ledger, &config, PRICE),
+        Err(MarketplaceError::WalletLimitReached.into())
+    );
+
+    // The limit is per wallet
+    let other_minter = config.add_minter(&mut ledger, 1_000_000_000);
+    TestMint::new(&mut ledger, &config, other_minter)
+        .mint(&ledger, &config, PRICE)
+        .unwrap();
+    assert_eq!(ledger.lamports(&config.treasury), 2 * PRICE);
+}
+
+#[test]
+fn mint_from_config_stops_at_max_supply() {
+    let mut ledger = Ledger::new();
+    let config = TestMintConfig::new(&mut ledger, 1, 0);
+    let minter = config.add_minter(&mut ledger, 2_000_000_000);
+    TestMint::new(&mut ledger, &config, minter)
+        .mint(&ledger, &config, PRICE)
+        .unwrap();
+
+    let second = TestMint::new(&mut ledger, &config, minter);
+    assert_eq!(
+        second.mint(&ledger, &config, PRICE),
+        Err(MarketplaceError::MintSoldOut.into())
+    );
+}
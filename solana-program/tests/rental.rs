@@ -0,0 +1,224 @@
+mod common;
+
+use common::{
+    add_nft_mint, add_token_account, pack, rent_exempt_minimum, set_clock, take_invoked, Ledger,
+    TestMarketplace,
+};
+use nft_marketplace::{
+    error::MarketplaceError,
+    instruction,
+    state::{get_master_edition_pda, get_rental_pda},
+};
+use nft_marketplace_sdk::{metadata, state::SECONDS_PER_DAY, Address, Rental};
+use solana_program::{
+    instruction::Instruction, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::Account as TokenAccount;
+
+const DAILY_RATE: u64 = 100_000_000;
+const DAYS: u16 = 3;
+const NOW: i64 = 1_700_000_000;
+
+/// `metadata::freeze_delegated_account` or `metadata::thaw_delegated_account`
+type DelegatedAccountBuilder =
+    fn(&Address, &Address, &Address, &Address) -> nft_marketplace_sdk::Instruction;
+
+struct TestRental {
+    marketplace: TestMarketplace,
+    owner: Pubkey,
+    renter: Pubkey,
+    nft_mint: Pubkey,
+    nft_edition: Pubkey,
+    address: Pubkey,
+    escrow_token_account: Pubkey,
+    renter_token_account: Pubkey,
+}
+
+impl TestRental {
+    /// An NFT listed for rent at `DAILY_RATE` on a marketplace taking 2.5%
+    fn new(ledger: &mut Ledger) -> Self {
+        let marketplace = TestMarketplace::new(ledger, 250);
+        let owner = Pubkey::new_unique();
+        let renter = Pubkey::new_unique();
+        let nft_mint = Pubkey::new_unique();
+        let (nft_edition, _) = get_master_edition_pda(&nft_mint);
+        ledger.add_wallet(owner, 1_000_000_000);
+        ledger.add_wallet(renter, 1_000_000_000);
+        add_nft_mint(ledger, nft_mint);
+        ledger.add_empty(nft_edition);
+
+        let (address, bump) = get_rental_pda(&ledger.program_id, &nft_mint, &owner);
+        let escrow_token_account = get_associated_token_address(&address, &nft_mint);
+        let renter_token_account = get_associated_token_address(&renter, &nft_mint);
+        ledger.add_rent_exempt(
+            address,
+            pack(Rental::new(
+                marketplace.address.to_bytes(),
+                owner.to_bytes(),
+                nft_mint.to_bytes(),
+                DAILY_RATE,
+                7,
+                bump,
+            )),
+            ledger.program_id,
+        );
+        add_token_account(ledger, escrow_token_account, nft_mint, address, 1);
+        ledger.add_empty(renter_token_account);
+
+        Self {
+            marketplace,
+            owner,
+            renter,
+            nft_mint,
+            nft_edition,
+            address,
+            escrow_token_account,
+            renter_token_account,
+        }
+    }
+
+    fn rent(&self, ledger: &Ledger) -> Result<(), ProgramError> {
+        ledger.process(instruction::rent_nft(
+            &ledger.program_id.to_bytes(),
+            &self.renter.to_bytes(),
+            &self.address.to_bytes(),
+            &self.nft_mint.to_bytes(),
+            &self.escrow_token_account.to_bytes(),
+            &self.renter_token_account.to_bytes(),
+            &self.nft_edition.to_bytes(),
+            DAYS,
+            DAILY_RATE,
+        ))
+    }
+
+    fn reclaim(&self, ledger: &Ledger) -> Result<(), ProgramError> {
+        ledger.process(instruction::reclaim_rental(
+            &ledger.program_id.to_bytes(),
+            &self.owner.to_bytes(),
+            &self.marketplace.address.to_bytes(),
+            &self.marketplace.fee_recipient.to_bytes(),
+            &self.address.to_bytes(),
+            &self.nft_mint.to_bytes(),
+            &self.escrow_token_account.to_bytes(),
+            &self.renter_token_account.to_bytes(),
+            &self.nft_edition.to_bytes(),
+        ))
+    }
+
+    /// The metadata program instruction freezing or thawing the renter's
+    /// token account, as `build` makes it
+    fn delegated_account_instruction(&self, build: DelegatedAccountBuilder) -> Instruction {
+        build(
+            &self.address.to_bytes(),
+            &self.renter_token_account.to_bytes(),
+            &self.nft_edition.to_bytes(),
+            &self.nft_mint.to_bytes(),
+        )
+        .into()
+    }
+}
+
+#[test]
+fn rent_nft_holds_the_rent_and_freezes_the_nft_with_the_renter() {
+    let mut ledger = Ledger::new();
+    let rental = TestRental::new(&mut ledger);
+    let renter_before = ledger.lamports(&rental.renter);
+    let rental_before = ledger.lamports(&rental.address);
+    set_clock(NOW);
+
+    rental.rent(&ledger).unwrap();
+
+    let rent_paid = DAILY_RATE * u64::from(DAYS);
+    assert_eq!(ledger.lamports(&rental.address), rental_before + rent_paid);
+    assert_eq!(
+        ledger.lamports(&rental.renter),
+        renter_before - rent_paid - rent_exempt_minimum(TokenAccount::LEN)
+    );
+    let state: Rental = ledger.unpack(&rental.address);
+    assert_eq!(state.renter, rental.renter.to_bytes());
+    assert_eq!(state.rent_paid, rent_paid);
+    assert_eq!(state.expires_at, NOW + i64::from(DAYS) * SECONDS_PER_DAY);
+
+    // The rental PDA is the delegate of the renter's token account, and
+    // signs its freeze
+    let renter_token_account: TokenAccount = ledger.unpack(&rental.renter_token_account);
+    assert_eq!(renter_token_account.owner, rental.renter);
+    assert_eq!(renter_token_account.delegate, Some(rental.address).into());
+    let freeze = rental.delegated_account_instruction(metadata::freeze_delegated_account);
+    assert!(take_invoked().contains(&freeze));
+}
+
+#[test]
+fn reclaim_rental_waits_for_the_rental_to_end() {
+    let mut ledger = Ledger::new();
+    let rental = TestRental::new(&mut ledger);
+    set_clock(NOW);
+    rental.rent(&ledger).unwrap();
+
+    set_clock(NOW + i64::from(DAYS) * SECONDS_PER_DAY - 1);
+    assert_eq!(
+        rental.reclaim(&ledger),
+        Err(MarketplaceError::RentalNotExpired.into())
+    );
+}
+
+#[test]
+fn reclaim_rental_pays_owner_and_fee_and_thaws_the_nft() {
+    let mut ledger = Ledger::new();
+    let rental = TestRental::new(&mut ledger);
+    set_clock(NOW);
+    rental.rent(&ledger).unwrap();
+    take_invoked();
+    let owner_before = ledger.lamports(&rental.owner);
+
+    // Anyone can reclaim once the rental has ended; the owner doesn't sign
+    set_clock(NOW + i64::from(DAYS) * SECONDS_PER_DAY);
+    rental.reclaim(&ledger).unwrap();
+
+    // 2.5% of the rent to the marketplace, the rest to the owner, leaving
+    // the rental account its own rent
+    let rent_paid = DAILY_RATE * u64::from(DAYS);
+    let fee = rent_paid / 40;
+    assert_eq!(ledger.lamports(&rental.marketplace.fee_recipient), fee);
+    assert_eq!(
+        ledger.lamports(&rental.owner),
+        owner_before + rent_paid - fee
+    );
+    assert_eq!(
+        ledger.lamports(&rental.address),
+        rent_exempt_minimum(Rental::LEN)
+    );
+
+    let state: Rental = ledger.unpack(&rental.address);
+    assert!(!state.is_rented());
+    assert_eq!(state.rent_paid, 0);
+
+    let invoked = take_invoked();
+    let thaw = rental.delegated_account_instruction(metadata::thaw_delegated_account);
+    assert_eq!(invoked.first(), Some(&thaw));
+}
+
+#[test]
+fn reclaim_rental_returns_the_nft_to_its_own_escrow() {
+    let mut ledger = Ledger::new();
+    let mut rental = TestRental::new(&mut ledger);
+    set_clock(NOW);
+    rental.rent(&ledger).unwrap();
+
+    // A token account of the mint the caller picked instead
+    rental.escrow_token_account = Pubkey::new_unique();
+    add_token_account(
+        &mut ledger,
+        rental.escrow_token_account,
+        rental.nft_mint,
+        Pubkey::new_unique(),
+        0,
+    );
+
+    set_clock(NOW + i64::from(DAYS) * SECONDS_PER_DAY);
+    assert_eq!(
+        rental.reclaim(&ledger),
+        Err(ProgramError::InvalidAccountData)
+    );
+}